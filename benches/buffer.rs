@@ -42,6 +42,42 @@ fn buffer_draw_text(c: &mut Criterion) {
             buffer.draw_text(0, 0, black_box(&long_text), style);
         })
     });
+
+    // A frame of 200 short labels, like a busy dashboard. Printable ASCII
+    // takes the single-row fast path; the same labels with a trailing `\r`,
+    // which draws nothing, take the general path for comparison.
+    {
+        let mut labels_group = c.benchmark_group("draw_text_labels");
+        for (name, suffix) in [("ascii_fast_path", ""), ("general_path", "\r")] {
+            let labels: Vec<String> = (0..200)
+                .map(|i| format!("item {i:>3}: ok{suffix}"))
+                .collect();
+            labels_group.bench_function(name, |b| {
+                b.iter(|| {
+                    for (i, label) in (0u32..).zip(&labels) {
+                        buffer.draw_text((i % 10) * 20, i / 4, black_box(label), Style::NONE);
+                    }
+                })
+            });
+        }
+        labels_group.finish();
+    }
+
+    // Full 80-column lines, like a log view.
+    {
+        let mut lines_group = c.benchmark_group("draw_text_lines");
+        for (name, suffix) in [("ascii_fast_path", ""), ("general_path", "\r")] {
+            let line = format!("{}{suffix}", "0123456789 ".repeat(8).trim_end());
+            lines_group.bench_function(name, |b| {
+                b.iter(|| {
+                    for y in 0..50 {
+                        buffer.draw_text(0, y, black_box(&line), Style::NONE);
+                    }
+                })
+            });
+        }
+        lines_group.finish();
+    }
 }
 
 fn buffer_cell_ops(c: &mut Criterion) {
@@ -4,6 +4,7 @@
 
 use criterion::{Criterion, criterion_group, criterion_main};
 use opentui::Rgba;
+use opentui::color::set_quantization_cache;
 use opentui_rust as opentui;
 use std::hint::black_box;
 
@@ -99,6 +100,38 @@ fn color_conversion(c: &mut Criterion) {
         b.iter(|| black_box(color).to_256_color());
     });
 
+    c.bench_function("to_256_color_fast", |b| {
+        b.iter(|| black_box(color).to_256_color_fast());
+    });
+
+    // The memoized Oklab search against a fresh search per call, over a
+    // small working set like a themed frame's colors.
+    {
+        let colors: Vec<Rgba> = (0u8..64)
+            .map(|i| Rgba::from_rgb_u8(i.wrapping_mul(37), i.wrapping_mul(91), i * 4))
+            .collect();
+        let mut group = c.benchmark_group("to_256_color_cache");
+        for (name, cached) in [("cached", true), ("uncached", false)] {
+            group.bench_function(name, |b| {
+                set_quantization_cache(cached);
+                b.iter(|| {
+                    for &color in &colors {
+                        black_box(black_box(color).to_256_color());
+                    }
+                });
+            });
+        }
+        set_quantization_cache(true);
+        group.bench_function("fast", |b| {
+            b.iter(|| {
+                for &color in &colors {
+                    black_box(black_box(color).to_256_color_fast());
+                }
+            });
+        });
+        group.finish();
+    }
+
     c.bench_function("to_16_color", |b| {
         b.iter(|| black_box(color).to_16_color());
     });
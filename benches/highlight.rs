@@ -105,8 +105,8 @@ fn bench_incremental_update(c: &mut Criterion) {
     c.bench_function("highlight_incremental_single target<1ms", |b| {
         b.iter(|| {
             let rope = highlighted.rope_mut();
-            rope.insert(edit_char, " ");
-            rope.remove(edit_char..=edit_char);
+            rope.insert(edit_char, " ").unwrap();
+            rope.remove(edit_char..=edit_char).unwrap();
             highlighted.mark_dirty(dirty_line, dirty_line + 1);
             highlighted.update_highlighting();
         });
@@ -5,7 +5,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 use opentui::ansi::AnsiWriter;
 use opentui::buffer::BoxStyle;
-use opentui::renderer::BufferDiff;
+use opentui::renderer::{BufferDiff, Renderer, RendererOptions};
 use opentui::{Cell, OptimizedBuffer, Rgba, Style};
 use opentui_rust as opentui;
 use std::hint::black_box;
@@ -316,6 +316,34 @@ fn render_cycle(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark presenting an idle frame against one whose buffer was touched.
+fn present_idle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("present_idle");
+    let options = RendererOptions {
+        use_alt_screen: false,
+        hide_cursor: false,
+        enable_mouse: false,
+        query_capabilities: false,
+        capabilities: None,
+    };
+    let mut renderer = Renderer::with_writer(400, 200, options, std::io::sink()).unwrap();
+    renderer.present().unwrap();
+
+    group.bench_function("idle_400x200", |b| {
+        b.iter(|| renderer.present().unwrap());
+    });
+
+    // Touching the buffer without changing it costs a full diff.
+    group.bench_function("touched_400x200", |b| {
+        b.iter(|| {
+            renderer.buffer().cells_mut();
+            renderer.present().unwrap();
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     diff_identical_buffers,
@@ -326,6 +354,7 @@ criterion_group!(
     diff_reuse_vs_alloc,
     diff_should_full_redraw,
     ansi_generation,
-    render_cycle
+    render_cycle,
+    present_idle
 );
 criterion_main!(benches);
@@ -51,7 +51,7 @@ fn rope_insert(c: &mut Criterion) {
         b.iter_batched(
             || TextBuffer::with_text(&text),
             |mut buffer| {
-                buffer.rope_mut().insert(0, "abc");
+                buffer.rope_mut().insert(0, "abc").unwrap();
             },
             BatchSize::SmallInput,
         );
@@ -61,7 +61,7 @@ fn rope_insert(c: &mut Criterion) {
         b.iter_batched(
             || TextBuffer::with_text(&text),
             |mut buffer| {
-                buffer.rope_mut().insert(5_000, "abc");
+                buffer.rope_mut().insert(5_000, "abc").unwrap();
             },
             BatchSize::SmallInput,
         );
@@ -71,7 +71,7 @@ fn rope_insert(c: &mut Criterion) {
         b.iter_batched(
             || TextBuffer::with_text(&text),
             |mut buffer| {
-                buffer.rope_mut().insert(10_000, "abc");
+                buffer.rope_mut().insert(10_000, "abc").unwrap();
             },
             BatchSize::SmallInput,
         );
@@ -89,7 +89,7 @@ fn rope_delete(c: &mut Criterion) {
         b.iter_batched(
             || TextBuffer::with_text(&text),
             |mut buffer| {
-                buffer.rope_mut().remove(0..10);
+                buffer.rope_mut().remove(0..10).unwrap();
             },
             BatchSize::SmallInput,
         );
@@ -99,7 +99,7 @@ fn rope_delete(c: &mut Criterion) {
         b.iter_batched(
             || TextBuffer::with_text(&text),
             |mut buffer| {
-                buffer.rope_mut().remove(5_000..5_010);
+                buffer.rope_mut().remove(5_000..5_010).unwrap();
             },
             BatchSize::SmallInput,
         );
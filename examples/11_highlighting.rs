@@ -64,6 +64,7 @@ fn main() -> io::Result<()> {
         hide_cursor: false,
         enable_mouse: false,
         query_capabilities: true,
+        capabilities: None,
     };
     let mut renderer = Renderer::new_with_options(width, height, options)?;
     renderer.set_title("OpenTUI Highlighting Demo")?;
@@ -209,7 +210,8 @@ fn draw_frame(renderer: &mut Renderer, editor: &mut EditorView, theme: &Theme) -
     );
 
     editor.render_to(buffer, 0, 0, 0, 0);
-    renderer.present()
+    renderer.present()?;
+    Ok(())
 }
 
 const fn theme_index(code: KeyCode) -> Option<usize> {
@@ -77,6 +77,7 @@ fn main() -> io::Result<()> {
         hide_cursor: false, // We'll manage cursor ourselves
         enable_mouse: true,
         query_capabilities: true,
+        capabilities: None,
     };
     let mut renderer = Renderer::new_with_options(width, height, options)?;
     renderer.set_title("OpenTUI Editor Demo")?;
@@ -221,7 +222,8 @@ fn handle_event(
                         *wrap_mode = match *wrap_mode {
                             WrapMode::None => WrapMode::Word,
                             WrapMode::Word => WrapMode::Char,
-                            WrapMode::Char => WrapMode::None,
+                            WrapMode::Char => WrapMode::Truncate { ellipsis: true },
+                            WrapMode::Truncate { .. } => WrapMode::None,
                         };
                         editor.set_wrap_mode(*wrap_mode);
                     }
@@ -248,14 +250,14 @@ fn handle_event(
                 // Regular keys
                 match key.code {
                     KeyCode::Up => {
-                        if *wrap_mode != WrapMode::None {
+                        if wrap_mode.wraps() {
                             editor.move_up_visual(viewport_width, viewport_height);
                         } else {
                             editor.edit_buffer_mut().move_up();
                         }
                     }
                     KeyCode::Down => {
-                        if *wrap_mode != WrapMode::None {
+                        if wrap_mode.wraps() {
                             editor.move_down_visual(viewport_width, viewport_height);
                         } else {
                             editor.edit_buffer_mut().move_down();
@@ -268,14 +270,14 @@ fn handle_event(
                         editor.edit_buffer_mut().move_right();
                     }
                     KeyCode::Home => {
-                        if *wrap_mode != WrapMode::None {
+                        if wrap_mode.wraps() {
                             editor.move_to_visual_sol(viewport_width, viewport_height);
                         } else {
                             editor.edit_buffer_mut().move_to_line_start();
                         }
                     }
                     KeyCode::End => {
-                        if *wrap_mode != WrapMode::None {
+                        if wrap_mode.wraps() {
                             editor.move_to_visual_eol(viewport_width, viewport_height);
                         } else {
                             editor.edit_buffer_mut().move_to_line_end();
@@ -283,7 +285,7 @@ fn handle_event(
                     }
                     KeyCode::PageUp => {
                         for _ in 0..viewport_height {
-                            if *wrap_mode != WrapMode::None {
+                            if wrap_mode.wraps() {
                                 editor.move_up_visual(viewport_width, viewport_height);
                             } else {
                                 editor.edit_buffer_mut().move_up();
@@ -292,7 +294,7 @@ fn handle_event(
                     }
                     KeyCode::PageDown => {
                         for _ in 0..viewport_height {
-                            if *wrap_mode != WrapMode::None {
+                            if wrap_mode.wraps() {
                                 editor.move_down_visual(viewport_width, viewport_height);
                             } else {
                                 editor.edit_buffer_mut().move_down();
@@ -381,6 +383,7 @@ fn draw_status_bar(
         WrapMode::None => "nowrap",
         WrapMode::Word => "word",
         WrapMode::Char => "char",
+        WrapMode::Truncate { .. } => "truncate",
     };
     let left = format!(
         " Ln {}, Col {} | {} | {}",
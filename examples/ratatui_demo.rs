@@ -0,0 +1,137 @@
+//! Example: ratatui on the `OpenTUI` renderer
+//!
+//! Demonstrates:
+//! - Wrapping a `Renderer` in `OpenTuiBackend`
+//! - Driving it with a stock `ratatui::Terminal` and ordinary widgets
+//! - `OpenTUI` doing the diffing and ANSI output underneath
+//!
+//! The `ui` function is plain ratatui code; nothing in it knows about
+//! `OpenTUI`. Runs for about eight seconds.
+//!
+//! ```bash
+//! cargo run --example ratatui_demo --features ratatui-backend
+//! ```
+
+use opentui::Renderer;
+use opentui::compat::ratatui::OpenTuiBackend;
+use opentui::terminal::{enable_raw_mode, terminal_size};
+use opentui_rust as opentui;
+use ratatui::Frame;
+use ratatui::Terminal;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, Paragraph, Row, Sparkline, Table};
+use std::io;
+use std::time::{Duration, Instant};
+
+const SERVICES: [(&str, &str, &str); 4] = [
+    ("api", "running", "12 ms"),
+    ("worker", "running", "48 ms"),
+    ("cache", "degraded", "230 ms"),
+    ("mailer", "stopped", "-"),
+];
+
+fn ui(frame: &mut Frame, tick: u16) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(3),
+    ])
+    .areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(body);
+    let [list_area, spark_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(6)]).areas(left);
+
+    let title = Line::from(vec![
+        "ratatui ".bold().fg(Color::LightCyan),
+        "on ".into(),
+        "OpenTUI ".bold().fg(Color::LightGreen),
+        "— 世界 👩‍🚀".fg(Color::Yellow),
+    ]);
+    frame.render_widget(
+        Paragraph::new(title).block(Block::default().borders(Borders::ALL)),
+        header,
+    );
+
+    let items = ["Overview", "Services", "Logs", "Settings"];
+    let selected = usize::from(tick / 10) % items.len();
+    let list = List::new(items.iter().enumerate().map(|(i, item)| {
+        if i == selected {
+            Line::from(format!("> {item}")).style(Style::new().fg(Color::Black).bg(Color::Cyan))
+        } else {
+            Line::from(format!("  {item}"))
+        }
+    }))
+    .block(Block::default().title("Menu").borders(Borders::ALL));
+    frame.render_widget(list, list_area);
+
+    let data: Vec<u64> = (0..64)
+        .map(|i| {
+            // A triangle wave rolling left as the ticks advance.
+            let phase = (i + u64::from(tick)) % 20;
+            10 + phase.min(20 - phase) * 8
+        })
+        .collect();
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::default().title("Load").borders(Borders::ALL))
+            .data(&data)
+            .style(Style::new().fg(Color::Indexed(208))),
+        spark_area,
+    );
+
+    let rows = SERVICES.iter().map(|(name, state, latency)| {
+        let color = match *state {
+            "running" => Color::Green,
+            "degraded" => Color::Yellow,
+            _ => Color::Red,
+        };
+        Row::new(vec![
+            (*name).to_string().into(),
+            Line::from(*state).fg(color),
+            (*latency).to_string().into(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(6),
+        ],
+    )
+    .header(Row::new(vec!["Service", "State", "Latency"]).add_modifier(Modifier::BOLD))
+    .block(Block::default().title("Services").borders(Borders::ALL));
+    frame.render_widget(table, right);
+
+    let percent = tick % 101;
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().title("Deploy").borders(Borders::ALL))
+            .gauge_style(
+                Style::new()
+                    .fg(Color::Rgb(120, 90, 255))
+                    .bg(Color::DarkGray),
+            )
+            .percent(percent),
+        footer,
+    );
+}
+
+fn main() -> io::Result<()> {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+    let renderer = Renderer::new(u32::from(width), u32::from(height))?;
+    let _raw_guard = enable_raw_mode()?;
+
+    let mut terminal = Terminal::new(OpenTuiBackend::new(renderer))?;
+    let start = Instant::now();
+    let mut tick = 0;
+    while start.elapsed() < Duration::from_secs(8) {
+        terminal.draw(|frame| ui(frame, tick))?;
+        tick = tick.wrapping_add(1);
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+//! Run-loop helper demo.
+//!
+//! Demonstrates:
+//! - `opentui::run` owning raw mode, input, resize, and frame pacing
+//! - Reading key and resize events from each `Frame`
+//! - Using elapsed/delta time for animation
+//!
+//! Press `q` or Esc to quit.
+
+use opentui::{ControlFlow, Event, KeyCode, RunOptions, Style, run};
+use opentui_rust as opentui;
+
+fn main() -> opentui::Result<()> {
+    let mut keys = 0u32;
+    let mut last_event = String::from("(none)");
+
+    run(RunOptions::default(), |mut frame| {
+        for event in frame.events() {
+            match event {
+                Event::Key(key) if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) => {
+                    return ControlFlow::Quit;
+                }
+                Event::Key(_) => keys += 1,
+                _ => {}
+            }
+            last_event = format!("{event:?}");
+        }
+
+        let (width, height) = frame.size();
+        let elapsed = frame.elapsed().as_secs_f32();
+        let fps = 1.0 / frame.delta().as_secs_f32().max(f32::EPSILON);
+        let lines = [
+            format!("opentui::run - frame {}", frame.index()),
+            format!("size {width}x{height}, {elapsed:.1}s elapsed, {fps:.0} fps"),
+            format!("keys pressed: {keys}"),
+            format!("last event: {last_event}"),
+            String::from("q / Esc to quit"),
+        ];
+
+        let buffer = frame.buffer();
+        buffer.clear(opentui::Rgba::BLACK);
+        for (y, line) in (1..).zip(&lines) {
+            buffer.draw_text(2, y, line, Style::NONE);
+        }
+
+        // A dot sweeping along the bottom row, paced by elapsed time.
+        let track = width.saturating_sub(4).max(1);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let x = 2 + ((elapsed * 20.0) as u32 % track);
+        buffer.draw_text(x, height.saturating_sub(2), "●", Style::NONE);
+
+        ControlFlow::Continue
+    })
+}
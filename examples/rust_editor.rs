@@ -0,0 +1,109 @@
+//! A small Rust-file editor built on `EditorView::with_highlighting`.
+//!
+//! Demonstrates:
+//! - One-call syntax highlighting for an `EditorView`
+//! - Incremental retokenization as you type (try `//` at a line start)
+//! - Theme switching without retokenizing
+//! - Saving the buffer back to the file it came from
+//!
+//! Run with: `cargo run --example rust_editor [path/to/file.rs]`
+//!
+//! Keys:
+//! - Ctrl+S: Save (when opened from a file)
+//! - Ctrl+T: Next theme
+//! - Ctrl+Q / Esc: Quit
+
+use opentui::highlight::{Theme, TokenizerRegistry};
+use opentui::{ControlFlow, EditBuffer, EditorView, Event, KeyCode, Rgba, RunOptions, Style, run};
+use opentui_rust as opentui;
+
+const SAMPLE: &str = r#"/// Greets everyone on the list.
+fn main() {
+    let names = ["Ada", "Grace", "Linus"];
+    for name in names {
+        println!("hello, {name}!");
+    }
+}
+"#;
+
+fn main() -> opentui::Result<()> {
+    let path = std::env::args().nth(1);
+    let text = match &path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => SAMPLE.to_string(),
+    };
+
+    let registry = TokenizerRegistry::with_builtins();
+    let themes = [Theme::dark(), Theme::light(), Theme::solarized_dark()];
+    let mut theme = 0;
+    let mut editor = EditorView::new(EditBuffer::with_text(&text)).with_highlighting(
+        "rust",
+        &themes[theme],
+        &registry,
+    );
+    editor.set_line_numbers(true);
+    let mut status = String::from("^S save | ^T theme | ^Q quit");
+
+    run(RunOptions::default(), |mut frame| {
+        let (width, height) = frame.size();
+        let text_height = height.saturating_sub(1);
+        for event in frame.events() {
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.ctrl() {
+                match key.code {
+                    KeyCode::Char('q') => return ControlFlow::Quit,
+                    KeyCode::Char('t') => {
+                        theme = (theme + 1) % themes.len();
+                        editor.set_highlighting_theme(themes[theme].clone());
+                        status = format!("theme: {}", themes[theme].name());
+                    }
+                    KeyCode::Char('s') => {
+                        status = save(path.as_deref(), &editor.edit_buffer().text());
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            let buffer = editor.edit_buffer_mut();
+            match key.code {
+                KeyCode::Esc => return ControlFlow::Quit,
+                KeyCode::Char(c) => buffer.insert(&c.to_string()),
+                KeyCode::Enter => buffer.insert("\n"),
+                KeyCode::Tab => buffer.insert("    "),
+                KeyCode::Backspace => buffer.delete_backward(),
+                KeyCode::Delete => buffer.delete_forward(),
+                KeyCode::Left => buffer.move_left(),
+                KeyCode::Right => buffer.move_right(),
+                KeyCode::Up => buffer.move_up(),
+                KeyCode::Down => buffer.move_down(),
+                KeyCode::Home => buffer.move_to_line_start(),
+                KeyCode::End => buffer.move_to_line_end(),
+                _ => {}
+            }
+        }
+        editor.scroll_to_cursor(width, text_height);
+
+        let theme = &themes[theme];
+        let buffer = frame.buffer();
+        buffer.clear(theme.background());
+        editor.render_to(buffer, 0, 0, width, text_height);
+        let cursor = editor.edit_buffer().cursor();
+        let status = format!(" Ln {}, Col {} | {status}", cursor.row + 1, cursor.col + 1);
+        buffer.fill_rect(0, text_height, width, 1, Rgba::from_rgb_u8(40, 45, 55));
+        buffer.draw_text(0, text_height, &status, Style::fg(Rgba::WHITE));
+
+        ControlFlow::Continue
+    })
+}
+
+fn save(path: Option<&str>, text: &str) -> String {
+    let Some(path) = path else {
+        return String::from("no file to save to");
+    };
+    match std::fs::write(path, text) {
+        Ok(()) => format!("saved {path}"),
+        Err(err) => format!("save failed: {err}"),
+    }
+}
@@ -0,0 +1,161 @@
+#ifndef OPENTUI_H
+#define OPENTUI_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdint.h>
+
+// Success.
+#define OTUI_OK 0
+
+// `otui_hit_test` found no hit area at the point.
+#define OTUI_NO_HIT 1
+
+// The handle does not name a live renderer on this thread.
+#define OTUI_ERR_INVALID_HANDLE -1
+
+// A required pointer was null.
+#define OTUI_ERR_NULL_POINTER -2
+
+// Text was not valid UTF-8.
+#define OTUI_ERR_INVALID_UTF8 -3
+
+// An argument was out of range, such as a zero dimension.
+#define OTUI_ERR_INVALID_ARGUMENT -4
+
+// Writing to the output failed.
+#define OTUI_ERR_IO -5
+
+// The output is gone and the renderer has stopped writing.
+#define OTUI_ERR_OUTPUT_CLOSED -6
+
+// The call panicked; the renderer may be left mid-frame.
+#define OTUI_ERR_PANIC -7
+
+// The call re-entered the renderer, e.g. from an output callback.
+#define OTUI_ERR_BUSY -8
+
+// Switch to the alternate screen on create.
+#define OTUI_INIT_ALT_SCREEN (1 << 0)
+
+// Hide the cursor on create.
+#define OTUI_INIT_HIDE_CURSOR (1 << 1)
+
+// Enable mouse tracking on create.
+#define OTUI_INIT_MOUSE (1 << 2)
+
+// Query terminal capabilities on create.
+#define OTUI_INIT_QUERY_CAPABILITIES (1 << 3)
+
+// [`OtuiStyle::fg`] is set; otherwise the terminal default is used.
+#define OTUI_STYLE_FG (1 << 0)
+
+// [`OtuiStyle::bg`] is set; otherwise the terminal default is used.
+#define OTUI_STYLE_BG (1 << 1)
+
+// Identifies a renderer created by `otui_renderer_create*`. Never 0.
+typedef uint64_t OtuiHandle;
+
+// Output callback: write `len` bytes from `data`, returning the number of
+// bytes written or a negative value on failure.
+typedef intptr_t (*OtuiWriteFn)(void *userdata, const uint8_t *data, uintptr_t len);
+
+// Plain-data text style.
+//
+// Colors are packed `0xRRGGBBAA`. `attributes` holds
+// [`TextAttributes`] flag bits (bold = 1, dim = 2, italic = 4, ...);
+// higher bits are ignored. `flags` says which colors are set.
+// `extra_attributes` holds the flag bits above 31, shifted down by 32
+// (overline = 1, superscript = 2, subscript = 4).
+typedef struct OtuiStyle {
+  uint32_t fg;
+  uint32_t bg;
+  uint32_t attributes;
+  uint32_t flags;
+  uint32_t extra_attributes;
+} OtuiStyle;
+
+// Create a renderer that writes to stdout.
+//
+// `flags` combines `OTUI_INIT_*` values. On success the new handle is
+// written to `out_handle`.
+//
+// # Safety
+//
+// `out_handle` must be null or valid for a write.
+int32_t otui_renderer_create(uint32_t width, uint32_t height, uint32_t flags, OtuiHandle *out_handle);
+
+// Create a renderer that sends its output to `write`.
+//
+// `userdata` is passed back to every `write` call, including the cleanup
+// output written by `otui_renderer_destroy`.
+//
+// # Safety
+//
+// `out_handle` must be null or valid for a write. `write` must be safe to
+// call with `userdata` until the renderer is destroyed.
+int32_t otui_renderer_create_with_writer(uint32_t width,
+                                         uint32_t height,
+                                         uint32_t flags,
+                                         OtuiWriteFn write,
+                                         void *userdata,
+                                         OtuiHandle *out_handle);
+
+// Destroy a renderer, restoring the terminal state it changed.
+int32_t otui_renderer_destroy(OtuiHandle handle);
+
+// Write the back buffer's width and height.
+//
+// # Safety
+//
+// `out_width` and `out_height` must each be null or valid for a write.
+int32_t otui_buffer_size(OtuiHandle handle, uint32_t *out_width, uint32_t *out_height);
+
+// Draw `len` bytes of UTF-8 text at a cell position.
+//
+// Text past the buffer edge is clipped. `text` may be null when `len`
+// is 0.
+//
+// # Safety
+//
+// `text` must be null or valid for reads of `len` bytes.
+int32_t otui_draw_text(OtuiHandle handle,
+                       uint32_t x,
+                       uint32_t y,
+                       const uint8_t *text,
+                       uintptr_t len,
+                       OtuiStyle style);
+
+// Fill a rectangle of the back buffer with a `0xRRGGBBAA` background.
+int32_t otui_fill_rect(OtuiHandle handle,
+                       uint32_t x,
+                       uint32_t y,
+                       uint32_t width,
+                       uint32_t height,
+                       uint32_t bg);
+
+// Write the changes since the last frame to the output.
+int32_t otui_present(OtuiHandle handle);
+
+// Resize the renderer's buffers. Both dimensions must be non-zero.
+int32_t otui_resize(OtuiHandle handle, uint32_t width, uint32_t height);
+
+// Register a hit area with `id` for the next presented frame.
+int32_t otui_register_hit(OtuiHandle handle,
+                          uint32_t x,
+                          uint32_t y,
+                          uint32_t width,
+                          uint32_t height,
+                          uint32_t id);
+
+// Find the hit area at a cell in the last presented frame.
+//
+// Writes its id to `out_id` and returns `OTUI_OK`, or returns
+// `OTUI_NO_HIT` and leaves `out_id` alone.
+//
+// # Safety
+//
+// `out_id` must be null or valid for a write.
+int32_t otui_hit_test(OtuiHandle handle, uint32_t x, uint32_t y, uint32_t *out_id);
+
+#endif  /* OPENTUI_H */
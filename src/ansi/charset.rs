@@ -0,0 +1,97 @@
+//! Line-drawing fallbacks for terminals without Unicode.
+//!
+//! Box-drawing glyphs (U+2500–U+257F) are written as UTF-8 by default. On
+//! terminals or locales without UTF-8 they can instead be drawn from the DEC
+//! special graphics charset, or degraded to ASCII.
+
+/// Switch G0 to the DEC special graphics charset.
+pub const DEC_GRAPHICS_ON: &str = "\x1b(0";
+/// Switch G0 back to ASCII.
+pub const DEC_GRAPHICS_OFF: &str = "\x1b(B";
+
+/// How [`AnsiWriter`](super::AnsiWriter) writes box-drawing glyphs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum LineDrawing {
+    /// Write glyphs as UTF-8.
+    #[default]
+    Unicode,
+    /// Use the DEC special graphics charset, with [`Self::Ascii`] for glyphs
+    /// it has no equivalent for.
+    DecGraphics,
+    /// Degrade glyphs to `+`, `-`, and `|`.
+    Ascii,
+}
+
+/// The DEC special graphics byte drawing `ch`, if there is one.
+///
+/// Double, heavy, and rounded variants map to the plain single-line glyph.
+#[must_use]
+pub const fn dec_graphics(ch: char) -> Option<u8> {
+    Some(match ch {
+        '─' | '━' | '═' => b'q',
+        '│' | '┃' | '║' => b'x',
+        '┌' | '┏' | '╔' | '╒' | '╓' | '╭' => b'l',
+        '┐' | '┓' | '╗' | '╕' | '╖' | '╮' => b'k',
+        '└' | '┗' | '╚' | '╘' | '╙' | '╰' => b'm',
+        '┘' | '┛' | '╝' | '╛' | '╜' | '╯' => b'j',
+        '├' | '┣' | '╠' | '╞' | '╟' => b't',
+        '┤' | '┫' | '╣' | '╡' | '╢' => b'u',
+        '┬' | '┳' | '╦' | '╤' | '╥' => b'w',
+        '┴' | '┻' | '╩' | '╧' | '╨' => b'v',
+        '┼' | '╋' | '╬' | '╪' | '╫' => b'n',
+        _ => return None,
+    })
+}
+
+/// The ASCII stand-in for a box-drawing glyph, if `ch` is one.
+///
+/// Horizontal lines become `-`, vertical lines `|`, and corners, tees,
+/// crosses, and everything else in the block `+`.
+#[must_use]
+pub const fn ascii_fallback(ch: char) -> Option<char> {
+    match ch {
+        '─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '╴' | '╶' | '╸' | '╺' | '╼' | '╾' => {
+            Some('-')
+        }
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '╵' | '╷' | '╹' | '╻' | '╽' | '╿' => {
+            Some('|')
+        }
+        '\u{2500}'..='\u{257f}' => Some('+'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_dec_mapping_has_an_ascii_fallback() {
+        for ch in '\u{2500}'..='\u{257f}' {
+            if let Some(dec) = dec_graphics(ch) {
+                let ascii = ascii_fallback(ch).unwrap();
+                let expected = match dec {
+                    b'q' => '-',
+                    b'x' => '|',
+                    _ => '+',
+                };
+                assert_eq!(ascii, expected, "{ch}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_box_chars_are_unmapped() {
+        for ch in ['a', '+', '█', '▒'] {
+            assert_eq!(ascii_fallback(ch), None, "{ch}");
+            assert_eq!(dec_graphics(ch), None, "{ch}");
+        }
+        assert_eq!(ascii_fallback('╱'), Some('+'));
+        assert_eq!(dec_graphics('╱'), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Precomputed palette indices for colors written in 256-color and
+//! 16-color modes.
+
+use std::collections::HashMap;
+
+use super::ColorMode;
+use crate::color::Rgba;
+
+/// Palette indices chosen for one color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PaletteFallback {
+    /// Index into the 256-color palette (16-255; the system colors are skipped).
+    pub color256: u8,
+    /// Index into the 16-color palette (0-15).
+    pub color16: u8,
+}
+
+/// Palette indices for a set of colors, consulted by
+/// [`AnsiWriter::set_color_fallbacks`] before quantizing.
+///
+/// Usually built once from a theme with
+/// [`Theme::derive_fallbacks`](crate::highlight::Theme::derive_fallbacks), so
+/// its colors are not re-quantized per cell per frame. Colors are keyed by
+/// their 8-bit RGB value; colors not in the table are quantized as usual.
+///
+/// [`AnsiWriter::set_color_fallbacks`]: super::AnsiWriter::set_color_fallbacks
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColorFallbacks {
+    colors: HashMap<(u8, u8, u8), PaletteFallback>,
+}
+
+impl ColorFallbacks {
+    /// Create an empty table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `fallback` for `color`, replacing any earlier entry.
+    pub fn insert(&mut self, color: Rgba, fallback: PaletteFallback) {
+        self.colors.insert(color.to_rgb_u8(), fallback);
+    }
+
+    /// Fallback indices for a color, if the table covers it.
+    #[must_use]
+    pub fn get(&self, color: Rgba) -> Option<PaletteFallback> {
+        self.colors.get(&color.to_rgb_u8()).copied()
+    }
+
+    /// Palette index for a color in `mode`, or `None` for truecolor and
+    /// no-color output or colors the table does not cover.
+    #[must_use]
+    pub fn index_for(&self, color: Rgba, mode: ColorMode) -> Option<u8> {
+        match mode {
+            ColorMode::Color256 => self.get(color).map(|fallback| fallback.color256),
+            ColorMode::Color16 => self.get(color).map(|fallback| fallback.color16),
+            ColorMode::TrueColor | ColorMode::NoColor => None,
+        }
+    }
+
+    /// Number of colors covered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns true if no colors are covered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+impl FromIterator<(Rgba, PaletteFallback)> for ColorFallbacks {
+    fn from_iter<I: IntoIterator<Item = (Rgba, PaletteFallback)>>(iter: I) -> Self {
+        let mut fallbacks = Self::new();
+        for (color, fallback) in iter {
+            fallbacks.insert(color, fallback);
+        }
+        fallbacks
+    }
+}
@@ -4,19 +4,34 @@
 //! into terminal control sequences. Most applications never touch this module
 //! directly; instead, they draw into buffers and let the renderer emit ANSI.
 
+pub mod charset;
+pub mod fallback;
 pub mod output;
+pub mod parse;
+mod scan;
 pub mod sequences;
+pub mod sixel;
+pub mod strip;
 
-pub use output::AnsiWriter;
+pub use charset::LineDrawing;
+pub use fallback::{ColorFallbacks, PaletteFallback};
+pub use output::{AnsiWriter, AnsiWriterMetrics};
+pub use parse::{ParseOptions, parse_to_buffer};
 pub use sequences::*;
+pub use strip::{strip, truncate_visible, visible_width};
 
 use crate::color::Rgba;
-use crate::style::TextAttributes;
+use crate::style::{TextAttributes, UnderlineStyle};
 use crate::terminal::ColorSupport;
 use std::io::{self, Write};
 
 /// Color output mode for ANSI sequences.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum ColorMode {
     /// True color (24-bit RGB).
     #[default]
@@ -113,6 +128,8 @@ pub fn write_fg_color_with_mode(
     mode: ColorMode,
 ) -> io::Result<()> {
     match mode {
+        ColorMode::NoColor => Ok(()),
+        _ if color.is_default() => w.write_all(b"\x1b[39m"),
         ColorMode::TrueColor => {
             let (r, g, b) = color.to_rgb_u8();
             w.write_all(b"\x1b[38;2;")?;
@@ -123,16 +140,52 @@ pub fn write_fg_color_with_mode(
             write_u8_decimal(w, b)?;
             w.write_all(b"m")
         }
-        ColorMode::Color256 => {
-            let idx = color.to_256_color();
+        ColorMode::Color256 | ColorMode::Color16 => {
+            write_fg_index_with_mode(w, quantize(color, mode), mode)
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static QUANTIZE_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Number of per-color palette quantizations on this thread (test builds).
+#[cfg(test)]
+pub(crate) fn quantize_calls() -> usize {
+    QUANTIZE_CALLS.with(std::cell::Cell::get)
+}
+
+/// Quantize a color to a palette index for `mode`.
+fn quantize(color: Rgba, mode: ColorMode) -> u8 {
+    #[cfg(test)]
+    QUANTIZE_CALLS.with(|calls| calls.set(calls.get() + 1));
+    if mode == ColorMode::Color16 {
+        color.to_16_color()
+    } else {
+        color.to_256_color()
+    }
+}
+
+/// Write SGR sequence for a foreground palette index to a writer.
+///
+/// For [`ColorMode::Color16`] the index must be 0-15. Writes nothing for
+/// [`ColorMode::NoColor`]; [`ColorMode::TrueColor`] uses the 256-color form.
+pub fn write_fg_index_with_mode(w: &mut impl Write, index: u8, mode: ColorMode) -> io::Result<()> {
+    match mode {
+        ColorMode::TrueColor | ColorMode::Color256 => {
             w.write_all(b"\x1b[38;5;")?;
-            write_u8_decimal(w, idx)?;
+            write_u8_decimal(w, index)?;
             w.write_all(b"m")
         }
         ColorMode::Color16 => {
-            let idx = color.to_16_color();
             // ANSI 16 colors: 30-37 for normal, 90-97 for bright
-            let code = if idx < 8 { 30 + idx } else { 90 + idx - 8 };
+            let code = if index < 8 {
+                30 + index
+            } else {
+                90 + index - 8
+            };
             w.write_all(b"\x1b[")?;
             write_u8_decimal(w, code)?;
             w.write_all(b"m")
@@ -158,6 +211,8 @@ pub fn write_bg_color_with_mode(
     mode: ColorMode,
 ) -> io::Result<()> {
     match mode {
+        ColorMode::NoColor => Ok(()),
+        _ if color.is_default() => w.write_all(b"\x1b[49m"),
         ColorMode::TrueColor => {
             let (r, g, b) = color.to_rgb_u8();
             w.write_all(b"\x1b[48;2;")?;
@@ -168,16 +223,30 @@ pub fn write_bg_color_with_mode(
             write_u8_decimal(w, b)?;
             w.write_all(b"m")
         }
-        ColorMode::Color256 => {
-            let idx = color.to_256_color();
+        ColorMode::Color256 | ColorMode::Color16 => {
+            write_bg_index_with_mode(w, quantize(color, mode), mode)
+        }
+    }
+}
+
+/// Write SGR sequence for a background palette index to a writer.
+///
+/// For [`ColorMode::Color16`] the index must be 0-15. Writes nothing for
+/// [`ColorMode::NoColor`]; [`ColorMode::TrueColor`] uses the 256-color form.
+pub fn write_bg_index_with_mode(w: &mut impl Write, index: u8, mode: ColorMode) -> io::Result<()> {
+    match mode {
+        ColorMode::TrueColor | ColorMode::Color256 => {
             w.write_all(b"\x1b[48;5;")?;
-            write_u8_decimal(w, idx)?;
+            write_u8_decimal(w, index)?;
             w.write_all(b"m")
         }
         ColorMode::Color16 => {
-            let idx = color.to_16_color();
             // ANSI 16 colors: 40-47 for normal, 100-107 for bright
-            let code = if idx < 8 { 40 + idx } else { 100 + idx - 8 };
+            let code = if index < 8 {
+                40 + index
+            } else {
+                100 + index - 8
+            };
             w.write_all(b"\x1b[")?;
             write_u8_decimal(w, code)?;
             w.write_all(b"m")
@@ -198,8 +267,8 @@ pub fn attributes(attrs: TextAttributes) -> String {
 ///
 /// Uses a stack-allocated array to avoid heap allocation on every call.
 pub fn write_attributes(w: &mut impl Write, attrs: TextAttributes) -> io::Result<()> {
-    // Stack-allocated array - max 8 attribute codes possible
-    let mut codes: [&str; 8] = [""; 8];
+    // Stack-allocated array - max 11 attribute codes possible
+    let mut codes: [&str; 11] = [""; 11];
     let mut count = 0;
 
     if attrs.contains(TextAttributes::BOLD) {
@@ -234,6 +303,18 @@ pub fn write_attributes(w: &mut impl Write, attrs: TextAttributes) -> io::Result
         codes[count] = "9";
         count += 1;
     }
+    if attrs.contains(TextAttributes::OVERLINE) {
+        codes[count] = "53";
+        count += 1;
+    }
+    if attrs.contains(TextAttributes::SUPERSCRIPT) {
+        codes[count] = "73";
+        count += 1;
+    }
+    if attrs.contains(TextAttributes::SUBSCRIPT) {
+        codes[count] = "74";
+        count += 1;
+    }
 
     if count == 0 {
         Ok(())
@@ -250,6 +331,62 @@ pub fn write_attributes(w: &mut impl Write, attrs: TextAttributes) -> io::Result
     }
 }
 
+/// Generate SGR sequence for a styled underline (`4:n`).
+#[must_use]
+pub fn underline(style: UnderlineStyle) -> String {
+    let mut buf = Vec::new();
+    write_underline(&mut buf, style).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+/// Write SGR sequence for a styled underline (`4:n`) to a writer.
+///
+/// Only terminals with styled underline support understand the colon
+/// subparameter; use plain `4` (see [`write_attributes`]) otherwise.
+pub fn write_underline(w: &mut impl Write, style: UnderlineStyle) -> io::Result<()> {
+    w.write_all(b"\x1b[4:")?;
+    write_u8_decimal(w, style.sgr_subparam())?;
+    w.write_all(b"m")
+}
+
+/// Generate SGR sequence for underline color with specified color mode.
+#[must_use]
+pub fn underline_color_with_mode(color: Rgba, mode: ColorMode) -> String {
+    let mut buf = Vec::new();
+    write_underline_color_with_mode(&mut buf, color, mode).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+/// Write SGR 58 sequence for underline color to a writer.
+///
+/// SGR 58 has no 16-color form, so [`ColorMode::Color16`] uses the 256-color
+/// form with a 0-15 index.
+pub fn write_underline_color_with_mode(
+    w: &mut impl Write,
+    color: Rgba,
+    mode: ColorMode,
+) -> io::Result<()> {
+    match mode {
+        ColorMode::NoColor => Ok(()),
+        _ if color.is_default() => w.write_all(b"\x1b[59m"),
+        ColorMode::TrueColor => {
+            let (r, g, b) = color.to_rgb_u8();
+            w.write_all(b"\x1b[58;2;")?;
+            write_u8_decimal(w, r)?;
+            w.write_all(b";")?;
+            write_u8_decimal(w, g)?;
+            w.write_all(b";")?;
+            write_u8_decimal(w, b)?;
+            w.write_all(b"m")
+        }
+        ColorMode::Color256 | ColorMode::Color16 => {
+            w.write_all(b"\x1b[58;5;")?;
+            write_u8_decimal(w, quantize(color, mode))?;
+            w.write_all(b"m")
+        }
+    }
+}
+
 /// Generate cursor position sequence (1-indexed).
 #[must_use]
 pub fn cursor_position(row: u32, col: u32) -> String {
@@ -342,6 +479,17 @@ pub fn write_scroll_down(w: &mut impl Write, lines: u32) -> io::Result<()> {
     w.write_all(b"T")
 }
 
+/// Write REP (repeat preceding graphic character) sequence: `ESC [ <n> b`.
+pub fn write_repeat(w: &mut impl Write, count: u32) -> io::Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    w.write_all(b"\x1b[")?;
+    write_u32_decimal(w, count)?;
+    w.write_all(b"b")
+}
+
 /// Escape a URL for safe inclusion in OSC 8 hyperlink sequences.
 ///
 /// Control characters are percent-encoded to prevent escape sequence injection:
@@ -395,8 +543,38 @@ pub fn hyperlink_start(id: u32, url: &str) -> String {
 ///
 /// The URL is automatically escaped to prevent control character injection.
 pub fn write_hyperlink_start(w: &mut impl Write, id: u32, url: &str) -> io::Result<()> {
+    write_hyperlink_start_with_params(w, id, "", url)
+}
+
+/// Generate OSC 8 hyperlink start sequence with extra params after `id=`.
+#[must_use]
+pub fn hyperlink_start_with_params(id: u32, params: &str, url: &str) -> String {
+    let mut buf = Vec::new();
+    write_hyperlink_start_with_params(&mut buf, id, params, url).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+/// Write OSC 8 hyperlink start sequence with extra params after `id=`.
+///
+/// `params` are `key=value` pairs separated by `:`. Control characters and
+/// `;`, which would end the params field, are dropped from them; the URL is
+/// escaped as in [`write_hyperlink_start`].
+pub fn write_hyperlink_start_with_params(
+    w: &mut impl Write,
+    id: u32,
+    params: &str,
+    url: &str,
+) -> io::Result<()> {
     let escaped_url = escape_url_for_osc8(url);
-    write!(w, "\x1b]8;id={id};{escaped_url}\x1b\\")
+    write!(w, "\x1b]8;id={id}")?;
+    if !params.is_empty() {
+        let params: String = params
+            .chars()
+            .filter(|&c| !c.is_control() && c != ';')
+            .collect();
+        write!(w, ":{params}")?;
+    }
+    write!(w, ";{escaped_url}\x1b\\")
 }
 
 /// OSC 8 hyperlink end sequence.
@@ -587,6 +765,9 @@ mod tests {
             AnsiSequence::new("inverse", &attributes(TextAttributes::INVERSE)),
             AnsiSequence::new("hidden", &attributes(TextAttributes::HIDDEN)),
             AnsiSequence::new("strikethrough", &attributes(TextAttributes::STRIKETHROUGH)),
+            AnsiSequence::new("overline", &attributes(TextAttributes::OVERLINE)),
+            AnsiSequence::new("superscript", &attributes(TextAttributes::SUPERSCRIPT)),
+            AnsiSequence::new("subscript", &attributes(TextAttributes::SUBSCRIPT)),
             AnsiSequence::new(
                 "bold_italic",
                 &attributes(TextAttributes::BOLD | TextAttributes::ITALIC),
@@ -597,11 +778,47 @@ mod tests {
                     TextAttributes::BOLD | TextAttributes::UNDERLINE | TextAttributes::INVERSE,
                 ),
             ),
+            AnsiSequence::new(
+                "dim_overline_subscript",
+                &attributes(
+                    TextAttributes::DIM | TextAttributes::OVERLINE | TextAttributes::SUBSCRIPT,
+                ),
+            ),
             AnsiSequence::new("empty", &attributes(TextAttributes::empty())),
         ];
         assert_json_snapshot!(sequences);
     }
 
+    #[test]
+    fn snapshot_underline_styles() {
+        let red = Rgba::from_rgb_u8(255, 0, 0);
+        let sequences: Vec<AnsiSequence> = vec![
+            AnsiSequence::new("single", &underline(UnderlineStyle::Single)),
+            AnsiSequence::new("double", &underline(UnderlineStyle::Double)),
+            AnsiSequence::new("curly", &underline(UnderlineStyle::Curly)),
+            AnsiSequence::new("dotted", &underline(UnderlineStyle::Dotted)),
+            AnsiSequence::new("dashed", &underline(UnderlineStyle::Dashed)),
+            AnsiSequence::new(
+                "color_truecolor",
+                &underline_color_with_mode(red, ColorMode::TrueColor),
+            ),
+            AnsiSequence::new(
+                "color_256",
+                &underline_color_with_mode(red, ColorMode::Color256),
+            ),
+            AnsiSequence::new(
+                "color_16",
+                &underline_color_with_mode(red, ColorMode::Color16),
+            ),
+            AnsiSequence::new(
+                "color_nocolor",
+                &underline_color_with_mode(red, ColorMode::NoColor),
+            ),
+            AnsiSequence::new("color_reset", color::UNDERLINE_DEFAULT),
+        ];
+        assert_json_snapshot!(sequences);
+    }
+
     #[test]
     fn snapshot_cursor_position() {
         let sequences: Vec<AnsiSequence> = vec![
@@ -637,6 +854,14 @@ mod tests {
                 "link_with_path",
                 &hyperlink_start(42, "https://example.com/path/to/file.txt"),
             ),
+            AnsiSequence::new(
+                "link_with_params",
+                &hyperlink_start_with_params(7, "group=docs", "https://example.com/docs"),
+            ),
+            AnsiSequence::new(
+                "link_with_unsafe_params",
+                &hyperlink_start_with_params(7, "a=1;b=\x1b2", "https://example.com"),
+            ),
             AnsiSequence::new("link_end", HYPERLINK_END),
         ];
         assert_json_snapshot!(sequences);
@@ -1202,4 +1427,23 @@ mod tests {
         assert_eq!(cursor_style::BAR_STEADY, "\x1b[6 q");
         assert_eq!(cursor_style::DEFAULT, "\x1b[0 q");
     }
+
+    #[test]
+    fn test_default_color_sentinels_write_sgr_defaults() {
+        for mode in [
+            ColorMode::TrueColor,
+            ColorMode::Color256,
+            ColorMode::Color16,
+        ] {
+            let mut out = Vec::new();
+            write_fg_color_with_mode(&mut out, Rgba::DEFAULT_FG, mode).unwrap();
+            write_bg_color_with_mode(&mut out, Rgba::DEFAULT_BG, mode).unwrap();
+            write_underline_color_with_mode(&mut out, Rgba::DEFAULT_FG, mode).unwrap();
+            assert_eq!(out, b"\x1b[39m\x1b[49m\x1b[59m", "{mode:?}");
+        }
+
+        let mut out = Vec::new();
+        write_bg_color_with_mode(&mut out, Rgba::DEFAULT_BG, ColorMode::NoColor).unwrap();
+        assert!(out.is_empty());
+    }
 }
@@ -1,11 +1,68 @@
 //! Buffered ANSI output writer with state tracking.
 
-use crate::ansi::{self, ColorMode};
+use crate::ansi::charset::{self, LineDrawing};
+use crate::ansi::strip::{self, Sequence};
+use crate::ansi::{self, ColorFallbacks, ColorMode};
+use crate::buffer::RowSize;
 use crate::cell::Cell;
 use crate::color::Rgba;
 use crate::grapheme_pool::GraphemePool;
-use crate::style::TextAttributes;
+use crate::style::{TextAttributes, UnderlineStyle};
 use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Counts of what an [`AnsiWriter`] has flushed, by kind of output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnsiWriterMetrics {
+    /// Cursor moves: CUP, CUU/CUD/CUF/CUB, CHA, VPA, and carriage returns.
+    pub cursor_moves: u64,
+    /// SGR sequences (colors and attributes).
+    pub sgr_sequences: u64,
+    /// OSC sequences (hyperlinks, palette, title, ...).
+    pub osc_sequences: u64,
+    /// Every other escape sequence (erase, REP, charset shifts, DCS, ...).
+    pub other_sequences: u64,
+    /// Bytes of literal text.
+    pub text_bytes: u64,
+    /// All bytes flushed.
+    pub total_bytes: u64,
+}
+
+impl AnsiWriterMetrics {
+    /// Add the counts for `bytes`, a run of whole escape sequences and text.
+    fn record(&mut self, bytes: &[u8]) {
+        self.total_bytes += bytes.len() as u64;
+        let mut pos = 0;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                0x1b => {
+                    let (end, kind) = strip::sequence(bytes, pos + 1);
+                    match kind {
+                        Sequence::Sgr | Sequence::SgrReset => self.sgr_sequences += 1,
+                        Sequence::Csi(b'A'..=b'H' | b'a' | b'd' | b'e' | b'f' | b'`') => {
+                            self.cursor_moves += 1
+                        }
+                        Sequence::Hyperlink(_) | Sequence::Osc => self.osc_sequences += 1,
+                        Sequence::Csi(_) | Sequence::Other => self.other_sequences += 1,
+                    }
+                    pos = end;
+                }
+                b'\r' => {
+                    self.cursor_moves += 1;
+                    pos += 1;
+                }
+                _ => {
+                    let end = bytes[pos..]
+                        .iter()
+                        .position(|&b| b == 0x1b || b == b'\r')
+                        .map_or(bytes.len(), |len| pos + len);
+                    self.text_bytes += (end - pos) as u64;
+                    pos = end;
+                }
+            }
+        }
+    }
+}
 
 /// Buffered writer that tracks ANSI state to minimize escape sequences.
 pub struct AnsiWriter<W: Write> {
@@ -14,11 +71,25 @@ pub struct AnsiWriter<W: Write> {
 
     // Color output mode
     color_mode: ColorMode,
+    // Precomputed palette indices consulted before quantizing
+    fallbacks: Option<Arc<ColorFallbacks>>,
+    // Emit underline shapes and colors (SGR 4:n / 58)
+    styled_underlines: bool,
+    // Encode runs of identical cells with REP (CSI n b)
+    rep: bool,
+    // How box-drawing glyphs are written
+    line_drawing: LineDrawing,
+    // G0 is currently the DEC special graphics charset
+    dec_graphics_active: bool,
+    // Output counts, when enabled
+    metrics: Option<AnsiWriterMetrics>,
 
     // Current state for delta encoding
     current_fg: Option<Rgba>,
     current_bg: Option<Rgba>,
     current_attrs: TextAttributes,
+    current_underline_style: UnderlineStyle,
+    current_underline_color: Option<Rgba>,
     current_link: Option<u32>,
 
     // Cursor position
@@ -33,9 +104,17 @@ impl<W: Write> AnsiWriter<W> {
             writer,
             buffer: Vec::with_capacity(8192),
             color_mode: ColorMode::TrueColor,
+            fallbacks: None,
+            styled_underlines: false,
+            rep: false,
+            line_drawing: LineDrawing::Unicode,
+            dec_graphics_active: false,
+            metrics: None,
             current_fg: None,
             current_bg: None,
             current_attrs: TextAttributes::empty(),
+            current_underline_style: UnderlineStyle::Single,
+            current_underline_color: None,
             current_link: None,
             cursor_row: 0,
             cursor_col: 0,
@@ -48,15 +127,39 @@ impl<W: Write> AnsiWriter<W> {
             writer,
             buffer: Vec::with_capacity(8192),
             color_mode,
+            fallbacks: None,
+            styled_underlines: false,
+            rep: false,
+            line_drawing: LineDrawing::Unicode,
+            dec_graphics_active: false,
+            metrics: None,
             current_fg: None,
             current_bg: None,
             current_attrs: TextAttributes::empty(),
+            current_underline_style: UnderlineStyle::Single,
+            current_underline_color: None,
             current_link: None,
             cursor_row: 0,
             cursor_col: 0,
         }
     }
 
+    /// Count flushed output by kind; see [`Self::metrics`].
+    #[must_use]
+    pub fn with_metrics(mut self) -> Self {
+        self.metrics = Some(AnsiWriterMetrics::default());
+        self
+    }
+
+    /// Counts of everything flushed so far, if enabled with
+    /// [`Self::with_metrics`].
+    ///
+    /// Output still in the buffer is counted on the next [`Self::flush`].
+    #[must_use]
+    pub fn metrics(&self) -> Option<AnsiWriterMetrics> {
+        self.metrics
+    }
+
     /// Set the color output mode.
     pub fn set_color_mode(&mut self, mode: ColorMode) {
         self.color_mode = mode;
@@ -68,11 +171,131 @@ impl<W: Write> AnsiWriter<W> {
         self.color_mode
     }
 
+    /// Use precomputed palette indices for colors they cover.
+    ///
+    /// In 256-color and 16-color modes, covered colors are written with
+    /// their [`ColorFallbacks`] index instead of being quantized on the fly.
+    pub fn set_color_fallbacks(&mut self, fallbacks: Option<Arc<ColorFallbacks>>) {
+        self.fallbacks = fallbacks;
+    }
+
+    /// Emit underline shapes and colors.
+    ///
+    /// Off by default: cells with a styled underline get a plain underline
+    /// and their underline color is ignored. Enable it when the terminal
+    /// reports [`Capabilities::styled_underlines`].
+    ///
+    /// [`Capabilities::styled_underlines`]: crate::terminal::Capabilities::styled_underlines
+    pub fn set_styled_underlines(&mut self, enabled: bool) {
+        self.styled_underlines = enabled;
+    }
+
+    /// Returns true if underline shapes and colors are emitted.
+    #[must_use]
+    pub fn styled_underlines(&self) -> bool {
+        self.styled_underlines
+    }
+
+    /// Encode runs of identical cells with REP (`CSI n b`).
+    ///
+    /// Off by default: [`Self::write_cell_run_with_pool_and_link`] writes
+    /// every copy of the glyph. Enable it when the terminal reports
+    /// [`Capabilities::rep`].
+    ///
+    /// [`Capabilities::rep`]: crate::terminal::Capabilities::rep
+    pub fn set_rep(&mut self, enabled: bool) {
+        self.rep = enabled;
+    }
+
+    /// Returns true if runs of identical cells may be encoded with REP.
+    #[must_use]
+    pub fn rep(&self) -> bool {
+        self.rep
+    }
+
+    /// Choose how box-drawing glyphs are written.
+    ///
+    /// Defaults to [`LineDrawing::Unicode`]. Use [`LineDrawing::DecGraphics`]
+    /// when the terminal lacks [`Capabilities::unicode`]. In that mode the
+    /// writer switches charsets around runs of line-drawing glyphs and
+    /// switches back before other text and on [`Self::reset`]; text written
+    /// with [`Self::write_str`] or [`Self::write_raw`] should follow a reset.
+    ///
+    /// [`Capabilities::unicode`]: crate::terminal::Capabilities::unicode
+    pub fn set_line_drawing(&mut self, mode: LineDrawing) {
+        if mode != LineDrawing::DecGraphics {
+            self.set_dec_graphics(false);
+        }
+        self.line_drawing = mode;
+    }
+
+    /// Get how box-drawing glyphs are written.
+    #[must_use]
+    pub fn line_drawing(&self) -> LineDrawing {
+        self.line_drawing
+    }
+
+    /// Switch G0 between ASCII and DEC special graphics, if not already there.
+    fn set_dec_graphics(&mut self, active: bool) {
+        if self.dec_graphics_active != active {
+            let seq = if active {
+                charset::DEC_GRAPHICS_ON
+            } else {
+                charset::DEC_GRAPHICS_OFF
+            };
+            self.buffer.extend_from_slice(seq.as_bytes());
+            self.dec_graphics_active = active;
+        }
+    }
+
+    /// The char written for `c` under the line-drawing mode, and whether it
+    /// needs DEC special graphics on (`Some(true)`), off, or either (`None`).
+    fn map_char(&self, c: char) -> (char, Option<bool>) {
+        match self.line_drawing {
+            LineDrawing::Unicode => (c, Some(false)),
+            LineDrawing::DecGraphics => match charset::dec_graphics(c) {
+                Some(byte) => (char::from(byte), Some(true)),
+                // Space is the same in both charsets
+                None if c == ' ' => (c, None),
+                None => (charset::ascii_fallback(c).unwrap_or(c), Some(false)),
+            },
+            LineDrawing::Ascii => (charset::ascii_fallback(c).unwrap_or(c), Some(false)),
+        }
+    }
+
+    /// Write a char, applying the line-drawing mode.
+    fn write_char(&mut self, c: char) {
+        let (c, dec_graphics) = self.map_char(c);
+        if let Some(active) = dec_graphics {
+            self.set_dec_graphics(active);
+        }
+        let mut buf = [0u8; 4];
+        self.buffer
+            .extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    }
+
+    /// Write a grapheme string, leaving DEC special graphics first.
+    fn write_grapheme(&mut self, grapheme: &str) {
+        self.set_dec_graphics(false);
+        self.buffer.extend_from_slice(grapheme.as_bytes());
+    }
+
+    fn fallback_index(&self, color: Rgba) -> Option<u8> {
+        if color.is_default() {
+            return None;
+        }
+        self.fallbacks
+            .as_ref()
+            .and_then(|fallbacks| fallbacks.index_for(color, self.color_mode))
+    }
+
     /// Reset all state tracking.
     pub fn reset_state(&mut self) {
         self.current_fg = None;
         self.current_bg = None;
         self.current_attrs = TextAttributes::empty();
+        self.current_underline_style = UnderlineStyle::Single;
+        self.current_underline_color = None;
         self.current_link = None;
         self.cursor_row = 0;
         self.cursor_col = 0;
@@ -88,7 +311,43 @@ impl<W: Write> AnsiWriter<W> {
         self.buffer.extend_from_slice(s.as_bytes());
     }
 
-    /// Move cursor to position, using relative moves if more efficient.
+    /// Let the next printed character wrap onto the start of `row` instead
+    /// of moving there, if the cursor is past the last of `width` columns of
+    /// the row above, where terminals hold it until the next character.
+    ///
+    /// The next output must be printed text, and the row above must not be
+    /// the bottom of the scrolling region, where wrapping scrolls. Returns
+    /// whether the cursor now tracks the start of `row`.
+    pub fn wrap_to_row(&mut self, row: u32, width: u32) -> bool {
+        let wraps = self.cursor_row + 1 == row && self.cursor_col == width;
+        if wraps {
+            self.cursor_row = row;
+            self.cursor_col = 0;
+        }
+        wraps
+    }
+
+    /// Show `row` at `size` with its DEC line attribute, leaving the cursor
+    /// at the start of the row.
+    ///
+    /// The attribute applies to the line the cursor is on, so this must not
+    /// directly follow [`Self::wrap_to_row`], which leaves the cursor on the
+    /// row above until the next character. On a double-size row only the
+    /// first half of the columns exist.
+    pub fn set_row_size(&mut self, row: u32, size: RowSize) {
+        use ansi::sequences::line_size;
+
+        self.move_cursor(row, 0);
+        self.write_str(match size {
+            RowSize::Normal => line_size::SINGLE_WIDTH,
+            RowSize::DoubleWidth => line_size::DOUBLE_WIDTH,
+            RowSize::DoubleHeightTop => line_size::DOUBLE_HEIGHT_TOP,
+            RowSize::DoubleHeightBottom => line_size::DOUBLE_HEIGHT_BOTTOM,
+        });
+    }
+
+    /// Move cursor to position, using whichever of an absolute move, relative
+    /// moves, or a carriage return plus a vertical move is fewest bytes.
     pub fn move_cursor(&mut self, row: u32, col: u32) {
         if row == self.cursor_row && col == self.cursor_col {
             return;
@@ -100,17 +359,25 @@ impl<W: Write> AnsiWriter<W> {
         // Calculate cost of absolute vs relative move
         // ESC[r;cH = 1+1+digits(r)+1+digits(c)+1 = 4 + digits
         let abs_cost = 4 + digits(row + 1) + digits(col + 1);
-        let rel_cost = if dy != 0 {
+        let vertical_cost = if dy != 0 {
             3 + digits(dy.unsigned_abs())
         } else {
             0
-        } + if dx != 0 {
-            3 + digits(dx.unsigned_abs())
-        } else {
-            0
         };
-
-        if rel_cost < abs_cost && (dy != 0 || dx != 0) {
+        let rel_cost = vertical_cost
+            + if dx != 0 {
+                3 + digits(dx.unsigned_abs())
+            } else {
+                0
+            };
+        // CR returns to column 0 from anywhere, including the pending-wrap
+        // state after the last column.
+        let cr_cost = 1 + vertical_cost;
+
+        if col == 0 && dx != 0 && cr_cost < abs_cost.min(rel_cost) {
+            self.buffer.push(b'\r');
+            let _ = ansi::write_cursor_move(&mut self.buffer, 0, dy);
+        } else if rel_cost < abs_cost {
             let _ = ansi::write_cursor_move(&mut self.buffer, dx, dy);
         } else {
             let _ = ansi::write_cursor_position(&mut self.buffer, row, col);
@@ -123,7 +390,8 @@ impl<W: Write> AnsiWriter<W> {
     /// Set foreground color if different from current.
     pub fn set_fg(&mut self, color: Rgba) {
         if self.current_fg != Some(color) {
-            let _ = ansi::write_fg_color_with_mode(&mut self.buffer, color, self.color_mode);
+            let index = self.fallback_index(color);
+            let _ = write_fg(&mut self.buffer, color, index, self.color_mode);
             self.current_fg = Some(color);
         }
     }
@@ -131,7 +399,8 @@ impl<W: Write> AnsiWriter<W> {
     /// Set background color if different from current.
     pub fn set_bg(&mut self, color: Rgba) {
         if self.current_bg != Some(color) {
-            let _ = ansi::write_bg_color_with_mode(&mut self.buffer, color, self.color_mode);
+            let index = self.fallback_index(color);
+            let _ = write_bg(&mut self.buffer, color, index, self.color_mode);
             self.current_bg = Some(color);
         }
     }
@@ -140,81 +409,175 @@ impl<W: Write> AnsiWriter<W> {
     ///
     /// Uses a stack-allocated array to avoid heap allocation on every call.
     pub fn set_attributes(&mut self, attrs: TextAttributes) {
+        self.set_attributes_with_underline(attrs, UnderlineStyle::Single);
+    }
+
+    /// Set text attributes and the underline shape, only writing changes.
+    ///
+    /// Without [styled underlines](Self::set_styled_underlines) every shape
+    /// is written as a plain underline.
+    pub fn set_attributes_with_underline(&mut self, attrs: TextAttributes, style: UnderlineStyle) {
         let attrs = attrs.flags_only();
-        if self.current_attrs == attrs {
+        let style = if self.styled_underlines && attrs.contains(TextAttributes::UNDERLINE) {
+            style
+        } else {
+            UnderlineStyle::Single
+        };
+        if self.current_attrs == attrs && self.current_underline_style == style {
             return;
         }
 
         // Check what needs to be turned off
         let removed = self.current_attrs - attrs;
         if !removed.is_empty() {
-            // Use stack-allocated array instead of Vec to avoid heap allocation
-            // Maximum 7 reset codes possible (one per attribute type)
-            let mut codes: [&str; 7] = [""; 7];
-            let mut count = 0;
-
-            if removed.contains(TextAttributes::BOLD) || removed.contains(TextAttributes::DIM) {
-                codes[count] = "22";
-                count += 1;
+            let (codes, count) = removal_codes(removed);
+            // Manually construct the SGR escape sequence
+            self.buffer.extend_from_slice(b"\x1b[");
+            for (i, code) in codes[..count].iter().enumerate() {
+                if i > 0 {
+                    self.buffer.push(b';');
+                }
+                self.buffer.extend_from_slice(code.as_bytes());
             }
-            if removed.contains(TextAttributes::ITALIC) {
-                codes[count] = "23";
-                count += 1;
+            self.buffer.push(b'm');
+
+            // Update current attributes to reflect removal. SGR 22 turns off
+            // both bold and dim, and SGR 75 both superscript and subscript,
+            // so a kept one is added back below.
+            self.current_attrs -= removed;
+            for shared in [INTENSITY, SCRIPT] {
+                if removed.intersects(shared) {
+                    self.current_attrs -= shared;
+                }
             }
             if removed.contains(TextAttributes::UNDERLINE) {
-                codes[count] = "24";
-                count += 1;
-            }
-            if removed.contains(TextAttributes::BLINK) {
-                codes[count] = "25";
-                count += 1;
-            }
-            if removed.contains(TextAttributes::INVERSE) {
-                codes[count] = "27";
-                count += 1;
+                self.current_underline_style = UnderlineStyle::Single;
             }
-            if removed.contains(TextAttributes::HIDDEN) {
-                codes[count] = "28";
-                count += 1;
-            }
-            if removed.contains(TextAttributes::STRIKETHROUGH) {
-                codes[count] = "29";
-                count += 1;
+        }
+        // Apply new attributes; a styled underline is written as `4:n`
+        // instead of `4`, or on its own when only the shape changed
+        let mut to_add = attrs - self.current_attrs;
+        if style != self.current_underline_style {
+            to_add -= TextAttributes::UNDERLINE;
+            let _ = ansi::write_underline(&mut self.buffer, style);
+        }
+        if !to_add.is_empty() {
+            let _ = ansi::write_attributes(&mut self.buffer, to_add);
+        }
+
+        self.current_attrs = attrs;
+        self.current_underline_style = style;
+    }
+
+    /// Set the underline color if different from current.
+    ///
+    /// `None` restores the default (the foreground color). Writes nothing
+    /// without [styled underlines](Self::set_styled_underlines).
+    pub fn set_underline_color(&mut self, color: Option<Rgba>) {
+        if !self.styled_underlines || self.current_underline_color == color {
+            return;
+        }
+        let _ = match color {
+            Some(color) => {
+                ansi::write_underline_color_with_mode(&mut self.buffer, color, self.color_mode)
             }
+            None => self
+                .buffer
+                .write_all(ansi::color::UNDERLINE_DEFAULT.as_bytes()),
+        };
+        self.current_underline_color = color;
+    }
 
-            if count > 0 {
-                // Manually construct the SGR escape sequence
-                self.buffer.extend_from_slice(b"\x1b[");
-                for (i, code) in codes[..count].iter().enumerate() {
-                    if i > 0 {
-                        self.buffer.push(b';');
-                    }
-                    self.buffer.extend_from_slice(code.as_bytes());
-                }
-                self.buffer.push(b'm');
+    /// Apply a cell's attributes, underline, and colors, writing only what
+    /// changed since the previous cell.
+    ///
+    /// Attributes the cell drops are turned off one by one (SGR 22-29, 55,
+    /// and 75), unless a full `0m` reset followed by the parts still wanted is
+    /// shorter.
+    fn set_cell_style(&mut self, cell: &Cell) {
+        let underlined = cell.attributes.contains(TextAttributes::UNDERLINE);
+        let underline_color = cell.underline_color.filter(|_| underlined);
+        if self.full_reset_is_shorter(cell, underline_color) {
+            self.write_str(ansi::RESET);
+            self.clear_sgr_state();
+        }
+        self.set_attributes_with_underline(cell.attributes, cell.underline_style);
+        self.set_underline_color(underline_color);
+        self.set_fg(cell.fg);
+        self.set_bg(cell.bg);
+    }
+
+    /// Compare the bytes needed to drop attributes individually against a
+    /// `0m` reset plus re-emitting the unchanged parts of `cell`'s style.
+    fn full_reset_is_shorter(&self, cell: &Cell, underline_color: Option<Rgba>) -> bool {
+        let attrs = cell.attributes.flags_only();
+        let removed = self.current_attrs - attrs;
+        if removed.is_empty() {
+            return false;
+        }
+
+        let (codes, count) = removal_codes(removed);
+        let mut individual = sgr_len(&codes[..count]);
+        let kept = self.current_attrs & attrs;
+        for (shared, code) in [(INTENSITY, "1"), (SCRIPT, "73")] {
+            if removed.intersects(shared) && kept.intersects(shared) {
+                individual += sgr_len(&[code]);
             }
+        }
 
-            // Update current attributes to reflect removal
-            self.current_attrs -= removed;
+        let mut full = CountingWriter::default();
+        full.0 += ansi::RESET.len();
+        let _ = ansi::write_attributes(&mut full, kept);
+        if kept.contains(TextAttributes::UNDERLINE)
+            && self.current_underline_style != UnderlineStyle::Single
+        {
+            let _ = ansi::write_underline(&mut full, self.current_underline_style);
         }
-        // Apply new attributes
-        let to_add = attrs - self.current_attrs;
-        if !to_add.is_empty() {
-            let _ = ansi::write_attributes(&mut self.buffer, to_add);
+        if let Some(color) = underline_color.filter(|_| self.styled_underlines) {
+            if self.current_underline_color == Some(color) {
+                let _ = ansi::write_underline_color_with_mode(&mut full, color, self.color_mode);
+            }
+        }
+        if self.current_fg == Some(cell.fg) {
+            let index = self.fallback_index(cell.fg);
+            let _ = write_fg(&mut full, cell.fg, index, self.color_mode);
         }
+        if self.current_bg == Some(cell.bg) {
+            let index = self.fallback_index(cell.bg);
+            let _ = write_bg(&mut full, cell.bg, index, self.color_mode);
+        }
+        full.0 < individual
+    }
 
-        self.current_attrs = attrs;
+    /// Forget the SGR state after a `0m` reset (links and cursor are kept).
+    fn clear_sgr_state(&mut self) {
+        self.current_fg = None;
+        self.current_bg = None;
+        self.current_attrs = TextAttributes::empty();
+        self.current_underline_style = UnderlineStyle::Single;
+        self.current_underline_color = None;
     }
 
     /// Set hyperlink if different from current.
     pub fn set_link(&mut self, link_id: Option<u32>, url: Option<&str>) {
+        self.set_link_with_params(link_id, url, "");
+    }
+
+    /// Set hyperlink if different from current, writing `params` after the
+    /// `id=` param (see [`LinkPool::params`](crate::LinkPool::params)).
+    ///
+    /// The link stays open across cells and cursor moves until a different
+    /// link is set or [`Self::reset`] is called, so a run of linked cells
+    /// costs one open and one close. A link without a URL counts as no link.
+    pub fn set_link_with_params(&mut self, link_id: Option<u32>, url: Option<&str>, params: &str) {
+        let link_id = link_id.filter(|_| url.is_some());
         if self.current_link == link_id {
             return;
         }
 
         match (link_id, url) {
             (Some(id), Some(url)) => {
-                let _ = ansi::write_hyperlink_start(&mut self.buffer, id, url);
+                let _ = ansi::write_hyperlink_start_with_params(&mut self.buffer, id, params, url);
             }
             _ => {
                 self.write_str(ansi::HYPERLINK_END);
@@ -274,6 +637,26 @@ impl<W: Write> AnsiWriter<W> {
         self.write_str(ansi::CLEAR_LINE_LEFT);
     }
 
+    /// Erase `count` cells from the cursor to the terminal's default
+    /// background, with EL 0 when `to_line_end` and ECH otherwise.
+    ///
+    /// Attributes and any hyperlink are reset first, since terminals erase
+    /// with the current background. The cursor doesn't move.
+    pub fn erase_cells(&mut self, count: u32, to_line_end: bool) {
+        self.set_link(None, None);
+        if !self.current_attrs.is_empty() {
+            self.write_str(ansi::RESET);
+            self.clear_sgr_state();
+        } else if self.current_bg.take().is_some() {
+            self.write_str(ansi::sequences::color::BG_DEFAULT);
+        }
+        if to_line_end {
+            self.write_str(ansi::CLEAR_LINE_RIGHT);
+        } else {
+            let _ = write!(self.buffer, "\x1b[{count}X");
+        }
+    }
+
     /// Erase the entire current line (EL 2).
     pub fn erase_entire_line(&mut self) {
         self.write_str(ansi::CLEAR_LINE);
@@ -304,18 +687,12 @@ impl<W: Write> AnsiWriter<W> {
         self.set_link(cell.attributes.link_id(), link_url);
 
         // Update style state
-        self.set_attributes(cell.attributes);
-        self.set_fg(cell.fg);
-        self.set_bg(cell.bg);
+        self.set_cell_style(cell);
 
         // Write content using the cell's string representation
         // This handles all content types correctly without fixed-size buffer limitations
         match &cell.content {
-            crate::cell::CellContent::Char(c) => {
-                let mut buf = [0u8; 4];
-                let s = c.encode_utf8(&mut buf);
-                self.buffer.extend_from_slice(s.as_bytes());
-            }
+            crate::cell::CellContent::Char(c) => self.write_char(*c),
             crate::cell::CellContent::Grapheme(id) => {
                 for _ in 0..id.width() {
                     self.buffer.push(b' ');
@@ -344,20 +721,14 @@ impl<W: Write> AnsiWriter<W> {
         self.set_link(cell.attributes.link_id(), link_url);
 
         // Update style state
-        self.set_attributes(cell.attributes);
-        self.set_fg(cell.fg);
-        self.set_bg(cell.bg);
+        self.set_cell_style(cell);
 
         // Write content using the pool to resolve graphemes
         match &cell.content {
-            crate::cell::CellContent::Char(c) => {
-                let mut buf = [0u8; 4];
-                let s = c.encode_utf8(&mut buf);
-                self.buffer.extend_from_slice(s.as_bytes());
-            }
+            crate::cell::CellContent::Char(c) => self.write_char(*c),
             crate::cell::CellContent::Grapheme(id) => {
                 if let Some(grapheme) = pool.get(*id) {
-                    self.buffer.extend_from_slice(grapheme.as_bytes());
+                    self.write_grapheme(grapheme);
                 } else {
                     for _ in 0..id.width() {
                         self.buffer.push(b' ');
@@ -424,20 +795,72 @@ impl<W: Write> AnsiWriter<W> {
         link_url: Option<&str>,
     ) {
         self.set_link(cell.attributes.link_id(), link_url);
-        self.set_attributes(cell.attributes);
-        self.set_fg(cell.fg);
-        self.set_bg(cell.bg);
+        self.set_cell_style(cell);
+        self.write_content_with_pool(cell, pool);
+    }
+
+    /// Write `count` copies of a cell from the current cursor position, with
+    /// pool lookup and optional hyperlink.
+    ///
+    /// The cell's style and link are emitted once. Single-width chars and
+    /// empty cells are then repeated with REP when enabled (see
+    /// [`Self::set_rep`]) and shorter than writing the copies; other cells
+    /// are written `count` times.
+    pub fn write_cell_run_with_pool_and_link(
+        &mut self,
+        cell: &Cell,
+        count: u32,
+        pool: &GraphemePool,
+        link_url: Option<&str>,
+    ) {
+        if count == 0 {
+            return;
+        }
+        self.set_link(cell.attributes.link_id(), link_url);
+        self.set_cell_style(cell);
+        self.write_content_repeated(cell, count, pool);
+    }
 
+    /// Write adjacent cells that share one style and link from the current
+    /// cursor position.
+    ///
+    /// The style and link of the first cell are emitted once and the glyphs
+    /// follow back to back; runs of identical cells within use REP like
+    /// [`Self::write_cell_run_with_pool_and_link`]. Callers group the cells,
+    /// so the other cells' styles are not checked.
+    pub fn write_styled_run_with_pool_and_link(
+        &mut self,
+        cells: &[Cell],
+        pool: &GraphemePool,
+        link_url: Option<&str>,
+    ) {
+        let Some(first) = cells.first() else {
+            return;
+        };
+        self.set_link(first.attributes.link_id(), link_url);
+        self.set_cell_style(first);
+
+        let mut rest = cells;
+        while let Some(cell) = rest.first() {
+            let count = if cell.display_width() == 1 {
+                rest.iter().take_while(|next| next.bits_eq(cell)).count()
+            } else {
+                1
+            };
+            self.write_content_repeated(cell, count as u32, pool);
+            rest = &rest[count..];
+        }
+    }
+
+    /// Write a cell's glyph, resolving graphemes from the pool, and advance
+    /// the cursor. The style must already be set.
+    fn write_content_with_pool(&mut self, cell: &Cell, pool: &GraphemePool) {
         match &cell.content {
-            crate::cell::CellContent::Char(c) => {
-                let mut buf = [0u8; 4];
-                let s = c.encode_utf8(&mut buf);
-                self.buffer.extend_from_slice(s.as_bytes());
-            }
+            crate::cell::CellContent::Char(c) => self.write_char(*c),
             crate::cell::CellContent::Grapheme(id) => {
                 // Look up the grapheme in the pool
                 if let Some(grapheme) = pool.get(*id) {
-                    self.buffer.extend_from_slice(grapheme.as_bytes());
+                    self.write_grapheme(grapheme);
                 } else {
                     // Fallback: write spaces matching width
                     for _ in 0..id.width() {
@@ -456,6 +879,40 @@ impl<W: Write> AnsiWriter<W> {
         self.cursor_col += cell.display_width() as u32;
     }
 
+    /// Write a cell's glyph `count` times (at least once), using REP where
+    /// it is enabled and shorter. The style must already be set.
+    fn write_content_repeated(&mut self, cell: &Cell, count: u32, pool: &GraphemePool) {
+        self.write_content_with_pool(cell, pool);
+        let rest = count.saturating_sub(1);
+        if rest == 0 {
+            return;
+        }
+
+        let mut buf = [0u8; 4];
+        let glyph = match cell.content {
+            crate::cell::CellContent::Char(c) if cell.display_width() == 1 => {
+                &*self.map_char(c).0.encode_utf8(&mut buf)
+            }
+            crate::cell::CellContent::Empty => " ",
+            _ => {
+                for _ in 0..rest {
+                    self.write_content_with_pool(cell, pool);
+                }
+                return;
+            }
+        };
+
+        // ESC[nb = 3 + digits
+        if self.rep && 3 + digits(rest) < glyph.len() * rest as usize {
+            let _ = ansi::write_repeat(&mut self.buffer, rest);
+        } else {
+            for _ in 0..rest {
+                self.buffer.extend_from_slice(glyph.as_bytes());
+            }
+        }
+        self.cursor_col += rest;
+    }
+
     /// Write a cell at a specific position, resolving grapheme IDs from the pool.
     pub fn write_cell_at_with_pool(
         &mut self,
@@ -481,18 +938,20 @@ impl<W: Write> AnsiWriter<W> {
         self.write_cell_with_pool_and_link(cell, pool, link_url);
     }
 
-    /// Reset all ANSI attributes.
+    /// Reset all ANSI attributes and close any open hyperlink.
     pub fn reset(&mut self) {
+        self.set_dec_graphics(false);
         self.write_str(ansi::RESET);
-        self.current_fg = None;
-        self.current_bg = None;
-        self.current_attrs = TextAttributes::empty();
-        self.current_link = None;
+        self.clear_sgr_state();
+        self.set_link(None, None);
     }
 
     /// Flush the buffer to the underlying writer.
     pub fn flush(&mut self) -> io::Result<()> {
         self.writer.write_all(&self.buffer)?;
+        if let Some(metrics) = &mut self.metrics {
+            metrics.record(&self.buffer);
+        }
         self.buffer.clear();
         self.writer.flush()
     }
@@ -513,6 +972,73 @@ impl<W: Write> AnsiWriter<W> {
     }
 }
 
+/// Write a foreground color, using a precomputed palette `index` if given.
+fn write_fg(w: &mut impl Write, color: Rgba, index: Option<u8>, mode: ColorMode) -> io::Result<()> {
+    match index {
+        Some(index) => ansi::write_fg_index_with_mode(w, index, mode),
+        None => ansi::write_fg_color_with_mode(w, color, mode),
+    }
+}
+
+/// Write a background color, using a precomputed palette `index` if given.
+fn write_bg(w: &mut impl Write, color: Rgba, index: Option<u8>, mode: ColorMode) -> io::Result<()> {
+    match index {
+        Some(index) => ansi::write_bg_index_with_mode(w, index, mode),
+        None => ansi::write_bg_color_with_mode(w, color, mode),
+    }
+}
+
+/// Bold and dim, both turned off by SGR 22.
+const INTENSITY: TextAttributes = TextAttributes::BOLD.union(TextAttributes::DIM);
+
+/// Superscript and subscript, both turned off by SGR 75.
+const SCRIPT: TextAttributes = TextAttributes::SUPERSCRIPT.union(TextAttributes::SUBSCRIPT);
+
+/// SGR codes turning off `removed`, in a stack-allocated array.
+fn removal_codes(removed: TextAttributes) -> ([&'static str; 9], usize) {
+    // Maximum 9 reset codes possible (one per attribute type)
+    let mut codes: [&str; 9] = [""; 9];
+    let mut count = 0;
+    for (attrs, code) in [
+        (INTENSITY, "22"),
+        (TextAttributes::ITALIC, "23"),
+        (TextAttributes::UNDERLINE, "24"),
+        (TextAttributes::BLINK, "25"),
+        (TextAttributes::INVERSE, "27"),
+        (TextAttributes::HIDDEN, "28"),
+        (TextAttributes::STRIKETHROUGH, "29"),
+        (TextAttributes::OVERLINE, "55"),
+        (SCRIPT, "75"),
+    ] {
+        if removed.intersects(attrs) {
+            codes[count] = code;
+            count += 1;
+        }
+    }
+    (codes, count)
+}
+
+/// Length of an SGR sequence with the given codes.
+fn sgr_len(codes: &[&str]) -> usize {
+    // ESC [ codes joined by ';' m
+    3 + codes.iter().map(|code| code.len()).sum::<usize>() + codes.len().saturating_sub(1)
+}
+
+/// Writer that only counts bytes, for comparing encodings.
+#[derive(Default)]
+struct CountingWriter(usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Count decimal digits in a number.
 fn digits(n: u32) -> usize {
     if n == 0 { 1 } else { (n.ilog10() + 1) as usize }
@@ -523,6 +1049,68 @@ mod tests {
     use super::*;
     use crate::style::Style;
 
+    /// A titled box split by a double-line divider, written row by row
+    /// with `mode`, one output line per row and ESC shown as `ESC`.
+    fn box_output(mode: LineDrawing) -> String {
+        use crate::buffer::{BoxOptions, BoxStyle, OptimizedBuffer};
+
+        let mut buffer = OptimizedBuffer::new(10, 4);
+        let mut options = BoxOptions::new(BoxStyle::single(Style::NONE));
+        options.title = Some("ok".to_string());
+        buffer.draw_box_with_options(0, 0, 10, 4, options);
+        buffer.draw_text(1, 1, "a b", Style::NONE);
+        buffer.set(0, 2, Cell::new('╞', Style::NONE));
+        for x in 1..9 {
+            buffer.set(x, 2, Cell::new('═', Style::NONE));
+        }
+        buffer.set(9, 2, Cell::new('╡', Style::NONE));
+
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_line_drawing(mode);
+        let mut rows = Vec::new();
+        for y in 0..4 {
+            for x in 0..10 {
+                writer.write_cell(buffer.get(x, y).unwrap());
+            }
+            if y == 3 {
+                writer.reset();
+            }
+            rows.push(String::from_utf8(std::mem::take(&mut writer.buffer)).unwrap());
+        }
+        rows.join("\n").replace('\x1b', "ESC")
+    }
+
+    #[test]
+    fn test_box_line_drawing_unicode() {
+        insta::assert_snapshot!(box_output(LineDrawing::Unicode));
+    }
+
+    #[test]
+    fn test_box_line_drawing_dec_graphics() {
+        insta::assert_snapshot!(box_output(LineDrawing::DecGraphics));
+    }
+
+    #[test]
+    fn test_box_line_drawing_ascii() {
+        insta::assert_snapshot!(box_output(LineDrawing::Ascii));
+    }
+
+    #[test]
+    fn test_dec_graphics_shift_is_tracked_across_cells() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_line_drawing(LineDrawing::DecGraphics);
+        for ch in ['─', '─', ' ', '┼', 'x', '╱', '│'] {
+            writer.write_cell(&Cell::new(ch, Style::NONE));
+        }
+        let out = String::from_utf8_lossy(writer.buffer()).into_owned();
+        let text = out.rsplit('m').next().unwrap();
+        assert_eq!(text, "\x1b(0qq n\x1b(Bx+\x1b(0x");
+
+        // Switching modes leaves DEC graphics
+        writer.set_line_drawing(LineDrawing::Unicode);
+        assert!(writer.buffer().ends_with(b"x\x1b(B"));
+    }
+
     #[test]
     fn test_ansi_writer_basic() {
         let mut writer = AnsiWriter::new(Vec::new());
@@ -584,6 +1172,25 @@ mod tests {
         assert!(writer.buffer().is_empty());
     }
 
+    #[test]
+    fn test_erase_cells_resets_background_first() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.erase_cells(3, false);
+        assert_eq!(writer.buffer(), b"\x1b[3X");
+
+        writer.clear_buffer();
+        writer.set_bg(Rgba::RED);
+        writer.clear_buffer();
+        writer.erase_cells(3, true);
+        assert_eq!(writer.buffer(), b"\x1b[49m\x1b[K");
+
+        writer.clear_buffer();
+        writer.set_attributes(TextAttributes::BOLD);
+        writer.clear_buffer();
+        writer.erase_cells(2, false);
+        assert_eq!(writer.buffer(), b"\x1b[0m\x1b[2X");
+    }
+
     #[test]
     fn test_reset_scroll_region() {
         let mut writer = AnsiWriter::new(Vec::new());
@@ -678,6 +1285,7 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: crate::style::TextAttributes::empty(),
+            ..Default::default()
         };
 
         writer.write_cell_with_pool(&cell, &pool);
@@ -698,6 +1306,7 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: crate::style::TextAttributes::empty(),
+            ..Default::default()
         };
 
         writer.write_cell_with_pool(&cell, &pool);
@@ -737,6 +1346,7 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::TRANSPARENT,
             attributes: crate::style::TextAttributes::empty(),
+            ..Default::default()
         };
 
         writer.write_cell_at_with_pool(5, 10, &cell, &pool);
@@ -785,6 +1395,7 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: TextAttributes::empty(),
+            ..Default::default()
         };
 
         writer.write_cell_with_pool(&cell, &pool);
@@ -912,6 +1523,22 @@ mod tests {
         assert!(writer.buffer().is_empty(), "No move to current position");
     }
 
+    #[test]
+    fn test_move_to_next_line_start_uses_carriage_return() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.move_cursor(10, 80);
+        writer.clear_buffer();
+
+        writer.move_cursor(11, 0);
+        assert_eq!(writer.buffer(), b"\r\x1b[1B");
+
+        // Same row: a bare CR beats ESC[nD.
+        writer.move_cursor(11, 40);
+        writer.clear_buffer();
+        writer.move_cursor(11, 0);
+        assert_eq!(writer.buffer(), b"\r");
+    }
+
     // ============================================
     // State Reset Tests
     // ============================================
@@ -1005,6 +1632,67 @@ mod tests {
         assert!(!output.contains("22"), "Bold should not be reset");
     }
 
+    #[test]
+    fn test_removing_bold_keeps_dim() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_attributes(TextAttributes::BOLD | TextAttributes::DIM);
+        writer.clear_buffer();
+
+        // SGR 22 turns off both, so dim has to be written again.
+        writer.set_attributes(TextAttributes::DIM);
+        assert_eq!(writer.buffer(), b"\x1b[22m\x1b[2m");
+    }
+
+    #[test]
+    fn test_overline_and_script_resets() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_attributes(
+            TextAttributes::OVERLINE | TextAttributes::SUPERSCRIPT | TextAttributes::SUBSCRIPT,
+        );
+        assert_eq!(writer.buffer(), b"\x1b[53;73;74m");
+        writer.clear_buffer();
+
+        // SGR 75 turns off both, so subscript has to be written again.
+        writer.set_attributes(TextAttributes::OVERLINE | TextAttributes::SUBSCRIPT);
+        assert_eq!(writer.buffer(), b"\x1b[75m\x1b[74m");
+        writer.clear_buffer();
+
+        writer.set_attributes(TextAttributes::empty());
+        assert_eq!(writer.buffer(), b"\x1b[55;75m");
+    }
+
+    #[test]
+    fn test_full_reset_only_when_shorter() {
+        let all = TextAttributes::BOLD
+            | TextAttributes::ITALIC
+            | TextAttributes::UNDERLINE
+            | TextAttributes::INVERSE
+            | TextAttributes::STRIKETHROUGH;
+        let styled = Cell::new(
+            'a',
+            Style::fg(Rgba::RED)
+                .with_bg(Rgba::BLUE)
+                .with_attributes(all),
+        );
+
+        // Colors change anyway: `0m` beats five individual resets.
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.write_cell(&styled);
+        writer.clear_buffer();
+        writer.write_cell(&Cell::new('b', Style::fg(Rgba::GREEN).with_bg(Rgba::BLACK)));
+        let output = String::from_utf8_lossy(writer.buffer()).replace('\x1b', "ESC");
+        assert!(output.starts_with("ESC[0m"), "{output}");
+        assert!(!output.contains("22"), "{output}");
+
+        // Colors unchanged: re-emitting them would cost more than the resets.
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.write_cell(&styled);
+        writer.clear_buffer();
+        writer.write_cell(&Cell::new('b', Style::fg(Rgba::RED).with_bg(Rgba::BLUE)));
+        let output = String::from_utf8_lossy(writer.buffer()).replace('\x1b', "ESC");
+        assert_eq!(output, "ESC[22;23;24;27;29mb");
+    }
+
     #[test]
     fn test_attribute_addition_only() {
         let mut writer = AnsiWriter::new(Vec::new());
@@ -1065,6 +1753,53 @@ mod tests {
         assert!(output.contains("\x1b]8;;\x1b\\"), "Link end sequence");
     }
 
+    #[test]
+    fn test_reset_closes_open_link() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_link(Some(1), Some("https://example.com"));
+        writer.clear_buffer();
+
+        writer.reset();
+        let output = String::from_utf8_lossy(writer.buffer()).into_owned();
+        assert!(output.ends_with(ansi::HYPERLINK_END), "{output:?}");
+
+        // Nothing open any more: a second reset has no link to close.
+        writer.clear_buffer();
+        writer.reset();
+        assert!(!String::from_utf8_lossy(writer.buffer()).contains("]8;"));
+    }
+
+    #[test]
+    fn test_link_stays_open_across_cursor_moves() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        let cell = Cell::new('x', Style::NONE.with_link(3));
+        let url = Some("https://example.com");
+
+        writer.write_cell_at_with_link(0, 2, &cell, url);
+        writer.write_cell_at_with_link(0, 7, &cell, url);
+        writer.write_cell_at_with_link(0, 4, &cell, url);
+        writer.reset();
+
+        let output = String::from_utf8_lossy(writer.buffer()).into_owned();
+        assert_eq!(output.matches("\x1b]8;id=3;").count(), 1, "{output:?}");
+        assert_eq!(output.matches(ansi::HYPERLINK_END).count(), 1, "{output:?}");
+    }
+
+    #[test]
+    fn test_link_without_url_is_not_tracked() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        let cell = Cell::new('x', Style::NONE.with_link(5));
+
+        writer.write_cell_with_link(&cell, None);
+        writer.write_cell_with_link(&cell, Some("https://example.com"));
+
+        let output = String::from_utf8_lossy(writer.buffer()).into_owned();
+        assert!(
+            output.contains("\x1b]8;id=5;https://example.com"),
+            "{output:?}"
+        );
+    }
+
     // ============================================
     // Color Mode Tests
     // ============================================
@@ -1102,6 +1837,65 @@ mod tests {
         assert!(writer.buffer().is_empty(), "NoColor emits nothing");
     }
 
+    #[test]
+    fn test_color_fallbacks_replace_per_cell_quantization() {
+        use crate::ansi::{ColorFallbacks, PaletteFallback};
+
+        let colors = [
+            Rgba::from_rgb_u8(30, 30, 46),
+            Rgba::from_rgb_u8(205, 214, 244),
+            Rgba::from_rgb_u8(69, 71, 90),
+            Rgba::from_rgb_u8(108, 112, 134),
+        ];
+        // Indices quantization would not pick, so the lookup shows.
+        let fallbacks: ColorFallbacks = (0u8..)
+            .zip(colors)
+            .map(|(i, color)| {
+                let fallback = PaletteFallback {
+                    color256: 200 + i,
+                    color16: 10 + i,
+                };
+                (color, fallback)
+            })
+            .collect();
+        let fallbacks = Arc::new(fallbacks);
+
+        // Without fallbacks every color change is quantized.
+        let mut writer = AnsiWriter::with_color_mode(Vec::new(), ColorMode::Color16);
+        let before = ansi::quantize_calls();
+        for _frame in 0..10 {
+            for color in colors {
+                writer.set_fg(color);
+                writer.set_bg(color);
+            }
+        }
+        assert_eq!(ansi::quantize_calls() - before, 10 * 2 * colors.len());
+
+        // With fallbacks, covered colors are looked up instead.
+        let mut writer = AnsiWriter::with_color_mode(Vec::new(), ColorMode::Color16);
+        writer.set_color_fallbacks(Some(Arc::clone(&fallbacks)));
+        let before = ansi::quantize_calls();
+        for _frame in 0..10 {
+            for color in colors {
+                writer.set_fg(color);
+                writer.set_bg(color);
+            }
+        }
+        assert_eq!(ansi::quantize_calls(), before);
+
+        // The precomputed index is what gets written.
+        writer.clear_buffer();
+        writer.set_fg(Rgba::RED);
+        writer.set_fg(colors[1]);
+        let output = String::from_utf8_lossy(writer.buffer()).to_string();
+        assert!(output.ends_with("\x1b[93m"), "{output:?}");
+
+        // Colors outside the table still fall back to quantization.
+        let before = ansi::quantize_calls();
+        writer.set_fg(Rgba::from_rgb_u8(1, 2, 3));
+        assert_eq!(ansi::quantize_calls() - before, 1);
+    }
+
     // ============================================
     // Flush and Buffer Tests
     // ============================================
@@ -1192,6 +1986,7 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: attrs,
+            ..Default::default()
         };
 
         writer.write_cell(&cell);
@@ -1204,4 +1999,96 @@ mod tests {
         assert!(output.contains('4'), "Underline");
         assert!(output.contains('9'), "Strikethrough");
     }
+
+    fn underlined(ch: char, style: UnderlineStyle, color: Option<Rgba>) -> Cell {
+        let mut style = Style::fg(Rgba::WHITE)
+            .with_bg(Rgba::BLACK)
+            .with_underline_style(style);
+        style.underline_color = color;
+        Cell::new(ch, style)
+    }
+
+    #[test]
+    fn test_styled_underlines_emit_shape_and_color() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_styled_underlines(true);
+        let red = Rgba::from_rgb_u8(255, 0, 0);
+
+        writer.write_cell(&underlined('a', UnderlineStyle::Curly, Some(red)));
+        // Same underline: nothing new.
+        writer.write_cell(&underlined('b', UnderlineStyle::Curly, Some(red)));
+        // Shape change only.
+        writer.write_cell(&underlined('c', UnderlineStyle::Dotted, Some(red)));
+        // Color reset only.
+        writer.write_cell(&underlined('d', UnderlineStyle::Dotted, None));
+        // Back to plain underline.
+        writer.write_cell(&underlined('e', UnderlineStyle::Single, None));
+        // Underline off.
+        writer.write_cell(&Cell::new('f', Style::fg(Rgba::WHITE).with_bg(Rgba::BLACK)));
+
+        let output = String::from_utf8_lossy(writer.buffer()).replace('\x1b', "ESC");
+        assert_eq!(
+            output,
+            "ESC[4:3mESC[58;2;255;0;0mESC[38;2;255;255;255mESC[48;2;0;0;0ma\
+             b\
+             ESC[4:4mc\
+             ESC[59md\
+             ESC[4:1me\
+             ESC[24mf"
+        );
+    }
+
+    #[test]
+    fn test_styled_underlines_degrade_without_capability() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        assert!(!writer.styled_underlines());
+        let red = Rgba::from_rgb_u8(255, 0, 0);
+
+        writer.write_cell(&underlined('a', UnderlineStyle::Curly, Some(red)));
+        writer.write_cell(&underlined('b', UnderlineStyle::Dashed, None));
+
+        let output = String::from_utf8_lossy(writer.buffer()).replace('\x1b', "ESC");
+        assert_eq!(output, "ESC[4mESC[38;2;255;255;255mESC[48;2;0;0;0mab");
+    }
+
+    #[test]
+    fn test_underline_color_ignored_without_underline() {
+        let mut writer = AnsiWriter::new(Vec::new());
+        writer.set_styled_underlines(true);
+        let cell = Cell::new(
+            'a',
+            Style::NONE.with_underline_color(Rgba::from_rgb_u8(255, 0, 0)),
+        );
+        writer.write_cell(&cell);
+        assert!(!String::from_utf8_lossy(writer.buffer()).contains("58;"));
+    }
+
+    #[test]
+    fn test_metrics_count_flushed_output_by_kind() {
+        let red = Style::fg(Rgba::from_rgb_u8(255, 0, 0));
+        let mut writer = AnsiWriter::new(Vec::new()).with_metrics();
+        writer.write_str("\x1b[H");
+        writer.write_cell(&Cell::new('a', red));
+        writer.write_cell(&Cell::new('b', red));
+        writer.move_cursor(1, 0);
+        writer.write_cell(&Cell::new('c', Style::NONE));
+        writer.move_cursor(2, 5);
+        writer.set_link(Some(1), Some("https://example.com"));
+        writer.write_cell(&Cell::new('d', Style::NONE));
+        writer.reset();
+        assert_eq!(writer.metrics(), Some(AnsiWriterMetrics::default()));
+
+        writer.flush().unwrap();
+        let metrics = writer.metrics().unwrap();
+        let output = writer.into_inner();
+        // ESC[H, then CR + ESC[1B to row 1, then ESC[3;6H
+        assert_eq!(metrics.cursor_moves, 4);
+        // Red fg, black bg, white fg, reset
+        assert_eq!(metrics.sgr_sequences, 4);
+        // Link opened, then closed for the unlinked cell
+        assert_eq!(metrics.osc_sequences, 2);
+        assert_eq!(metrics.other_sequences, 0);
+        assert_eq!(metrics.text_bytes, "abcd".len() as u64);
+        assert_eq!(metrics.total_bytes, output.len() as u64);
+    }
 }
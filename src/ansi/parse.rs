@@ -0,0 +1,586 @@
+//! Interpret ANSI-escaped text into an [`OptimizedBuffer`].
+//!
+//! This is the inverse of the output layer: it takes the colored output of
+//! an external tool (`git diff --color`, `cargo`, `ls --color`) and draws it
+//! as styled cells, so it can be embedded inside a pane.
+//!
+//! The interpreter understands printable text, SGR colors and attributes,
+//! cursor movement and erasure relative to the target region, and line
+//! wrapping. Everything else (OSC, DCS, DEC private modes, charset
+//! designations) is skipped. Malformed or truncated sequences are dropped
+//! rather than reported.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::scan::{self, Escape};
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::color::Rgba;
+use crate::grapheme_pool::GraphemePool;
+use crate::style::{Style, TextAttributes, UnderlineStyle};
+use crate::unicode::display_width;
+
+const ESC: u8 = 0x1b;
+
+/// Parameter groups kept per CSI sequence; extra groups are ignored.
+const MAX_PARAMS: usize = 32;
+/// Largest value a single parameter saturates to.
+const MAX_PARAM_VALUE: u32 = u16::MAX as u32;
+
+/// Options for [`parse_to_buffer`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParseOptions {
+    /// Width of the target region in cells (`None` = to the buffer's right edge).
+    pub width: Option<u32>,
+    /// Height of the target region in cells (`None` = to the buffer's bottom
+    /// edge). Rows past it are still counted but not drawn.
+    pub height: Option<u32>,
+    /// Wrap text that reaches the right edge onto the next row. When false,
+    /// the overflow is clipped.
+    pub wrap: bool,
+    /// Tab stop interval in cells.
+    pub tab_width: u32,
+    /// Style used before any SGR sequence and restored by SGR 0, 39 and 49.
+    pub base_style: Style,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            width: None,
+            height: None,
+            wrap: true,
+            tab_width: 8,
+            base_style: Style::NONE,
+        }
+    }
+}
+
+/// Draw ANSI-escaped `input` into `buf`, with the region's top-left at `origin`.
+///
+/// Cursor movement (`CUP`, `CUU`, `CHA`, ...) is relative to the region, and
+/// `LF` returns to the region's first column. Returns the number of rows the
+/// output occupies, including rows below the region that were not drawn, so
+/// callers can size scrollback to fit.
+pub fn parse_to_buffer(
+    input: &[u8],
+    buf: &mut OptimizedBuffer,
+    pool: &mut GraphemePool,
+    origin: (u32, u32),
+    opts: ParseOptions,
+) -> u32 {
+    let width = opts
+        .width
+        .unwrap_or_else(|| buf.width().saturating_sub(origin.0));
+    let height = opts
+        .height
+        .unwrap_or_else(|| buf.height().saturating_sub(origin.1));
+    if width == 0 {
+        return 0;
+    }
+
+    let mut interpreter = Interpreter {
+        buf,
+        pool,
+        origin,
+        width,
+        height,
+        opts,
+        style: opts.base_style,
+        row: 0,
+        col: 0,
+        saved: (0, 0),
+        printed_rows: 0,
+    };
+    interpreter.run(input);
+    interpreter.rows_consumed()
+}
+
+struct Interpreter<'a> {
+    buf: &'a mut OptimizedBuffer,
+    pool: &'a mut GraphemePool,
+    origin: (u32, u32),
+    width: u32,
+    height: u32,
+    opts: ParseOptions,
+    style: Style,
+    row: u32,
+    // `col == width` means a wrap is pending before the next glyph
+    col: u32,
+    saved: (u32, u32),
+    printed_rows: u32,
+}
+
+impl Interpreter<'_> {
+    fn rows_consumed(&self) -> u32 {
+        self.printed_rows
+            .max(self.row.saturating_add(u32::from(self.col > 0)))
+    }
+
+    fn run(&mut self, input: &[u8]) {
+        let mut i = 0;
+        while i < input.len() {
+            match input[i] {
+                ESC => i = self.escape(input, i + 1),
+                b'\n' | 0x0b | 0x0c => {
+                    self.line_feed();
+                    i += 1;
+                }
+                b'\r' => {
+                    self.col = 0;
+                    i += 1;
+                }
+                b'\t' => {
+                    self.tab();
+                    i += 1;
+                }
+                0x08 => {
+                    self.col = self.col.min(self.width - 1).saturating_sub(1);
+                    i += 1;
+                }
+                0x00..=0x1f | 0x7f => i += 1,
+                _ => {
+                    let end = input[i..]
+                        .iter()
+                        .position(|&b| b < 0x20 || b == 0x7f)
+                        .map_or(input.len(), |len| i + len);
+                    self.print(&String::from_utf8_lossy(&input[i..end]));
+                    i = end;
+                }
+            }
+        }
+    }
+
+    fn line_feed(&mut self) {
+        self.row = self.row.saturating_add(1);
+        self.col = 0;
+    }
+
+    fn tab(&mut self) {
+        let tab = self.opts.tab_width.max(1);
+        if self.col < self.width {
+            self.col = ((self.col / tab + 1) * tab).min(self.width - 1);
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        for grapheme in text.graphemes(true) {
+            let Ok(w) = u32::try_from(display_width(grapheme)) else {
+                continue;
+            };
+            if w == 0 || w > self.width {
+                continue;
+            }
+            if self.col + w > self.width {
+                if !self.opts.wrap {
+                    self.col = self.width;
+                    continue;
+                }
+                self.line_feed();
+            }
+            if self.row < self.height {
+                self.buf.draw_char_with_pool(
+                    self.pool,
+                    self.origin.0 + self.col,
+                    self.origin.1 + self.row,
+                    grapheme,
+                    self.style,
+                );
+            }
+            self.col += w;
+            self.printed_rows = self.printed_rows.max(self.row.saturating_add(1));
+        }
+    }
+
+    /// Handle the sequence after an `ESC` at `i`; returns where parsing resumes.
+    fn escape(&mut self, input: &[u8], i: usize) -> usize {
+        let (end, escape) = scan::escape(input, i);
+        match escape {
+            Escape::Csi { body, final_byte } => {
+                let private = matches!(body.first(), Some(b'<'..=b'?'));
+                let intermediate = body.iter().any(|b| (0x20..=0x2f).contains(b));
+                if !private && !intermediate {
+                    self.dispatch(final_byte, &parse_params(body));
+                }
+            }
+            Escape::Final(b'7') => self.saved = (self.row, self.col),
+            Escape::Final(b'8') => (self.row, self.col) = self.saved,
+            // OSC, DCS, charset designations, and malformed sequences
+            Escape::String { .. } | Escape::Final(_) | Escape::Other => {}
+        }
+        end
+    }
+
+    fn dispatch(&mut self, action: u8, params: &[Vec<Option<u32>>]) {
+        let arg = |index: usize| {
+            params
+                .get(index)
+                .and_then(|group| group.first().copied().flatten())
+                .unwrap_or(0)
+        };
+        let count = |index: usize| arg(index).max(1);
+        let last_row = self.height.saturating_sub(1);
+        let last_col = self.width - 1;
+
+        match action {
+            b'm' => self.sgr(params),
+            b'A' => self.row = self.row.saturating_sub(count(0)),
+            b'B' => self.row = self.row.saturating_add(count(0)).min(last_row),
+            b'C' => self.col = self.col.saturating_add(count(0)).min(last_col),
+            b'D' => self.col = self.col.min(last_col).saturating_sub(count(0)),
+            b'E' => {
+                self.row = self.row.saturating_add(count(0)).min(last_row);
+                self.col = 0;
+            }
+            b'F' => {
+                self.row = self.row.saturating_sub(count(0));
+                self.col = 0;
+            }
+            b'G' | b'`' => self.col = (count(0) - 1).min(last_col),
+            b'd' => self.row = (count(0) - 1).min(last_row),
+            b'H' | b'f' => {
+                self.row = (count(0) - 1).min(last_row);
+                self.col = (count(1) - 1).min(last_col);
+            }
+            b'K' => self.erase_line(arg(0)),
+            b'J' => self.erase_display(arg(0)),
+            b's' => self.saved = (self.row, self.col),
+            b'u' => (self.row, self.col) = self.saved,
+            _ => {}
+        }
+    }
+
+    fn erase_cells(&mut self, row: u32, cols: std::ops::Range<u32>) {
+        if row >= self.height {
+            return;
+        }
+        let bg = self.style.bg.unwrap_or(Rgba::TRANSPARENT);
+        for col in cols {
+            self.buf.set_with_pool(
+                self.pool,
+                self.origin.0 + col,
+                self.origin.1 + row,
+                Cell::clear(bg),
+            );
+        }
+    }
+
+    fn erase_line(&mut self, mode: u32) {
+        let col = self.col.min(self.width);
+        match mode {
+            0 => self.erase_cells(self.row, col..self.width),
+            1 => self.erase_cells(self.row, 0..(col + 1).min(self.width)),
+            2 => self.erase_cells(self.row, 0..self.width),
+            _ => {}
+        }
+    }
+
+    fn erase_display(&mut self, mode: u32) {
+        let rows = match mode {
+            0 => {
+                self.erase_line(0);
+                self.row.saturating_add(1)..self.height
+            }
+            1 => {
+                self.erase_line(1);
+                0..self.row.min(self.height)
+            }
+            2 | 3 => 0..self.height,
+            _ => return,
+        };
+        for row in rows {
+            self.erase_cells(row, 0..self.width);
+        }
+    }
+
+    fn sgr(&mut self, params: &[Vec<Option<u32>>]) {
+        let base = self.opts.base_style;
+        if params.is_empty() {
+            self.style = base;
+            return;
+        }
+
+        let mut groups = params.iter();
+        while let Some(group) = groups.next() {
+            let code = group.first().copied().flatten().unwrap_or(0);
+            let style = &mut self.style;
+            match code {
+                0 => *style = base,
+                1 => style.attributes |= TextAttributes::BOLD,
+                2 => style.attributes |= TextAttributes::DIM,
+                3 => style.attributes |= TextAttributes::ITALIC,
+                4 => match group.get(1).copied().flatten() {
+                    Some(0) => style.attributes.remove(TextAttributes::UNDERLINE),
+                    shape => {
+                        style.attributes |= TextAttributes::UNDERLINE;
+                        style.underline_style = match shape {
+                            Some(2) => UnderlineStyle::Double,
+                            Some(3) => UnderlineStyle::Curly,
+                            Some(4) => UnderlineStyle::Dotted,
+                            Some(5) => UnderlineStyle::Dashed,
+                            _ => UnderlineStyle::Single,
+                        };
+                    }
+                },
+                5 | 6 => style.attributes |= TextAttributes::BLINK,
+                7 => style.attributes |= TextAttributes::INVERSE,
+                8 => style.attributes |= TextAttributes::HIDDEN,
+                9 => style.attributes |= TextAttributes::STRIKETHROUGH,
+                21 => {
+                    style.attributes |= TextAttributes::UNDERLINE;
+                    style.underline_style = UnderlineStyle::Double;
+                }
+                22 => style
+                    .attributes
+                    .remove(TextAttributes::BOLD | TextAttributes::DIM),
+                23 => style.attributes.remove(TextAttributes::ITALIC),
+                24 => style.attributes.remove(TextAttributes::UNDERLINE),
+                25 => style.attributes.remove(TextAttributes::BLINK),
+                27 => style.attributes.remove(TextAttributes::INVERSE),
+                28 => style.attributes.remove(TextAttributes::HIDDEN),
+                29 => style.attributes.remove(TextAttributes::STRIKETHROUGH),
+                53 => style.attributes |= TextAttributes::OVERLINE,
+                55 => style.attributes.remove(TextAttributes::OVERLINE),
+                73 => style.attributes |= TextAttributes::SUPERSCRIPT,
+                74 => style.attributes |= TextAttributes::SUBSCRIPT,
+                75 => style
+                    .attributes
+                    .remove(TextAttributes::SUPERSCRIPT | TextAttributes::SUBSCRIPT),
+                30..=37 => style.fg = Some(ansi_color(code - 30)),
+                90..=97 => style.fg = Some(ansi_color(code - 90 + 8)),
+                39 => style.fg = base.fg,
+                40..=47 => style.bg = Some(ansi_color(code - 40)),
+                100..=107 => style.bg = Some(ansi_color(code - 100 + 8)),
+                49 => style.bg = base.bg,
+                38 | 48 | 58 => {
+                    let color = extended_color(group, &mut groups);
+                    if let Some(color) = color {
+                        match code {
+                            38 => style.fg = Some(color),
+                            48 => style.bg = Some(color),
+                            _ => style.underline_color = Some(color),
+                        }
+                    }
+                }
+                59 => style.underline_color = base.underline_color,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Split CSI parameters into `;`-separated groups of `:`-separated values.
+///
+/// Empty values are `None`; values saturate at [`MAX_PARAM_VALUE`].
+fn parse_params(body: &[u8]) -> Vec<Vec<Option<u32>>> {
+    if body.is_empty() {
+        return Vec::new();
+    }
+    body.split(|&b| b == b';')
+        .take(MAX_PARAMS)
+        .map(|group| {
+            group
+                .split(|&b| b == b':')
+                .map(|value| {
+                    if value.is_empty() {
+                        return None;
+                    }
+                    Some(value.iter().fold(0u32, |acc, &b| {
+                        if b.is_ascii_digit() {
+                            (acc * 10 + u32::from(b - b'0')).min(MAX_PARAM_VALUE)
+                        } else {
+                            acc
+                        }
+                    }))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn ansi_color(index: u32) -> Rgba {
+    Rgba::from_16_color(u8::try_from(index).unwrap_or(0))
+}
+
+/// Read the color after an SGR 38/48/58, from either the colon form in
+/// `group` (`38:5:n`, `38:2::r:g:b`, `38:2:r:g:b`) or the semicolon form in
+/// the following groups (`38;5;n`, `38;2;r;g;b`).
+fn extended_color<'a>(
+    group: &[Option<u32>],
+    rest: &mut impl Iterator<Item = &'a Vec<Option<u32>>>,
+) -> Option<Rgba> {
+    let channel = |value: Option<u32>| value.and_then(|v| u8::try_from(v).ok());
+
+    if group.len() > 1 {
+        return match group[1] {
+            Some(5) => channel(group.get(2).copied().flatten()).map(Rgba::from_256_color),
+            Some(2) => {
+                // The optional color space ID makes this 6 values long
+                let rgb = if group.len() >= 6 {
+                    &group[3..6]
+                } else {
+                    group.get(2..5)?
+                };
+                Some(Rgba::from_rgb_u8(
+                    channel(rgb[0])?,
+                    channel(rgb[1])?,
+                    channel(rgb[2])?,
+                ))
+            }
+            _ => None,
+        };
+    }
+
+    let mut next = || {
+        rest.next()
+            .and_then(|group| group.first().copied().flatten())
+    };
+    match next()? {
+        5 => channel(next()).map(Rgba::from_256_color),
+        2 => {
+            let (r, g, b) = (next(), next(), next());
+            Some(Rgba::from_rgb_u8(channel(r)?, channel(g)?, channel(b)?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &[u8], opts: ParseOptions) -> (OptimizedBuffer, u32) {
+        let mut buf = OptimizedBuffer::new(20, 6);
+        let mut pool = GraphemePool::new();
+        let rows = parse_to_buffer(input, &mut buf, &mut pool, (1, 1), opts);
+        (buf, rows)
+    }
+
+    fn text(buf: &OptimizedBuffer, y: u32) -> String {
+        (1..buf.width())
+            .map(|x| buf.get(x, y).unwrap().content.as_char().unwrap_or(' '))
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_colon_and_semicolon_color_forms_agree() {
+        let forms: [&[u8]; 4] = [
+            b"\x1b[38;2;10;20;30mx",
+            b"\x1b[38:2::10:20:30mx",
+            b"\x1b[38:2:10:20:30mx",
+            b"\x1b[1;38;2;10;20;30;4mx",
+        ];
+        for form in forms {
+            let (buf, _) = parse(form, ParseOptions::default());
+            let cell = buf.get(1, 1).unwrap();
+            assert_eq!(cell.fg, Rgba::from_rgb_u8(10, 20, 30), "{form:?}");
+        }
+
+        let (buf, _) = parse(b"\x1b[48:5:196;4:3;58;5;21mx", ParseOptions::default());
+        let cell = buf.get(1, 1).unwrap();
+        assert_eq!(cell.bg, Rgba::from_256_color(196));
+        assert_eq!(cell.underline_style, UnderlineStyle::Curly);
+        assert_eq!(cell.underline_color, Some(Rgba::from_256_color(21)));
+    }
+
+    #[test]
+    fn test_wrapping_and_clipping() {
+        let opts = ParseOptions {
+            width: Some(4),
+            ..ParseOptions::default()
+        };
+        let (buf, rows) = parse(b"abcdefghij", opts);
+        assert_eq!(rows, 3);
+        assert_eq!(text(&buf, 1), "abcd");
+        assert_eq!(text(&buf, 2), "efgh");
+        assert_eq!(text(&buf, 3), "ij");
+
+        let (buf, rows) = parse(
+            b"abcdefghij\r\nk",
+            ParseOptions {
+                wrap: false,
+                ..opts
+            },
+        );
+        assert_eq!(rows, 2);
+        assert_eq!(text(&buf, 1), "abcd");
+        assert_eq!(text(&buf, 2), "k");
+    }
+
+    #[test]
+    fn test_wide_char_wraps_before_right_edge() {
+        let opts = ParseOptions {
+            width: Some(3),
+            ..ParseOptions::default()
+        };
+        let (buf, rows) = parse("ab世".as_bytes(), opts);
+        assert_eq!(rows, 2);
+        assert_eq!(text(&buf, 1), "ab");
+        assert_eq!(text(&buf, 2), "世");
+    }
+
+    #[test]
+    fn test_cursor_movement_is_relative_to_region() {
+        let (buf, rows) = parse(
+            b"\x1b[3;4Hx\x1b[2Ay\x1b[1Gz\x1b[5Cw",
+            ParseOptions::default(),
+        );
+        assert_eq!(rows, 3);
+        assert_eq!(text(&buf, 1), "z   y w");
+        assert_eq!(text(&buf, 3), "   x");
+    }
+
+    #[test]
+    fn test_rows_past_region_are_counted_not_drawn() {
+        let opts = ParseOptions {
+            height: Some(2),
+            ..ParseOptions::default()
+        };
+        let (buf, rows) = parse(b"one\ntwo\nthree\nfour\n", opts);
+        assert_eq!(rows, 4);
+        assert_eq!(text(&buf, 2), "two");
+        assert_eq!(text(&buf, 3), "");
+    }
+
+    #[test]
+    fn test_unsupported_sequences_are_skipped() {
+        let input = b"\x1b[?25l\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07\
+                      \x1b(0\x1b=\x1bP1$r\x1b\\!\x1b[>4;1m\x1b[?1049h";
+        let (buf, rows) = parse(input, ParseOptions::default());
+        assert_eq!(rows, 1);
+        assert_eq!(text(&buf, 1), "link!");
+        assert_eq!(buf.get(1, 1).unwrap().attributes, TextAttributes::empty());
+    }
+
+    #[test]
+    fn test_erase_uses_current_background() {
+        let (buf, _) = parse(b"abcdef\x1b[3G\x1b[44m\x1b[K", ParseOptions::default());
+        assert_eq!(text(&buf, 1), "ab");
+        let erased = buf.get(3, 1).unwrap();
+        assert!(erased.is_empty());
+        assert_eq!(erased.bg, Rgba::from_16_color(4));
+    }
+
+    #[test]
+    fn test_malformed_input_does_not_panic() {
+        let inputs: [&[u8]; 10] = [
+            b"\x1b",
+            b"\x1b[",
+            b"\x1b[38;2;1",
+            b"\x1b[38;5m\x1b[48:2m\x1b[38:2:999:0:0m",
+            b"\x1b[99999999999999999999A\x1b[99999999999B\x1b[0;0H",
+            b"\x1b]unterminated osc",
+            b"\x1b[12\nnext",
+            b"\xff\xfe\x80 bad utf8 \xe4\xb8",
+            b"\x1b(\x1b)\x1b#",
+            b"\x08\x08\t\t\t\t\t\x1b[D\x1b[F\x1b[u",
+        ];
+        for input in inputs {
+            parse(input, ParseOptions::default());
+        }
+        let (buf, rows) = parse(b"\x1b[12\nnext", ParseOptions::default());
+        assert_eq!((text(&buf, 2), rows), ("next".to_string(), 2));
+    }
+}
@@ -0,0 +1,158 @@
+//! Finding where an escape sequence ends.
+//!
+//! Shared by the stripper and the interpreter so both agree on the edge
+//! cases: BEL vs ST string terminators, intermediate bytes, and sequences
+//! cut short by a stray byte or the end of the input.
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// The shape of one escape sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Escape<'a> {
+    /// CSI with its parameter and intermediate bytes and its final byte.
+    Csi { body: &'a [u8], final_byte: u8 },
+    /// OSC, DCS, SOS, PM, or APC string, introduced by `kind` (`]`, `P`,
+    /// ...). The body excludes the terminator.
+    String { kind: u8, body: &'a [u8] },
+    /// Two-byte escape such as `ESC 7`, with its final byte.
+    Final(u8),
+    /// nF sequence (charset designation), or a truncated or malformed one.
+    Other,
+}
+
+/// Scan the escape sequence whose `ESC` precedes `i`, returning where it
+/// ends and its shape.
+///
+/// A malformed sequence ends at the offending byte, which the caller then
+/// handles as input. Strings end at BEL or ST; any other escape aborts them.
+/// Only ASCII bytes are consumed, so the end is always a char boundary.
+pub fn escape(bytes: &[u8], i: usize) -> (usize, Escape<'_>) {
+    match bytes.get(i) {
+        Some(b'[') => {
+            let body_start = i + 1;
+            let end = bytes[body_start..]
+                .iter()
+                .position(|b| !(0x20..=0x3f).contains(b))
+                .map_or(bytes.len(), |len| body_start + len);
+            match bytes.get(end) {
+                Some(&final_byte @ 0x40..=0x7e) => (
+                    end + 1,
+                    Escape::Csi {
+                        body: &bytes[body_start..end],
+                        final_byte,
+                    },
+                ),
+                _ => (end, Escape::Other),
+            }
+        }
+        // OSC, DCS, SOS, PM, APC: strings terminated by BEL or ST
+        Some(&kind @ (b']' | b'P' | b'X' | b'^' | b'_')) => {
+            let body_start = i + 1;
+            let mut end = body_start;
+            let mut body_end = bytes.len();
+            while let Some(&byte) = bytes.get(end) {
+                if byte == BEL {
+                    body_end = end;
+                    end += 1;
+                    break;
+                }
+                if byte == ESC {
+                    body_end = end;
+                    // Any escape other than ST aborts the string
+                    if bytes.get(end + 1) == Some(&b'\\') {
+                        end += 2;
+                    }
+                    break;
+                }
+                end += 1;
+            }
+            let body = &bytes[body_start..body_end];
+            (end, Escape::String { kind, body })
+        }
+        // nF sequences (charset designations): intermediates + final
+        Some(0x20..=0x2f) => {
+            let end = bytes[i..]
+                .iter()
+                .position(|b| !(0x20..=0x2f).contains(b))
+                .map_or(bytes.len(), |len| i + len);
+            match bytes.get(end) {
+                Some(0x30..=0x7e) => (end + 1, Escape::Other),
+                _ => (end, Escape::Other),
+            }
+        }
+        Some(&final_byte @ 0x30..=0x7e) => (i + 1, Escape::Final(final_byte)),
+        // A control or non-ASCII byte interrupts the escape
+        _ => (i, Escape::Other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_terminators() {
+        assert_eq!(
+            escape(b"\x1b]0;title\x07rest", 1),
+            (
+                10,
+                Escape::String {
+                    kind: b']',
+                    body: b"0;title"
+                }
+            )
+        );
+        assert_eq!(
+            escape(b"\x1bPq#0\x1b\\rest", 1),
+            (
+                7,
+                Escape::String {
+                    kind: b'P',
+                    body: b"q#0"
+                }
+            )
+        );
+        // A non-ST escape aborts the string and is left for the caller
+        assert_eq!(
+            escape(b"\x1b]0;t\x1b[m", 1),
+            (
+                5,
+                Escape::String {
+                    kind: b']',
+                    body: b"0;t"
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_csi_and_malformed_sequences() {
+        assert_eq!(
+            escape(b"\x1b[?25h", 1),
+            (
+                6,
+                Escape::Csi {
+                    body: b"?25",
+                    final_byte: b'h'
+                }
+            )
+        );
+        assert_eq!(
+            escape(b"\x1b[1 q", 1),
+            (
+                5,
+                Escape::Csi {
+                    body: b"1 ",
+                    final_byte: b'q'
+                }
+            )
+        );
+        assert_eq!(escape(b"\x1b[1\nx", 1), (3, Escape::Other));
+        assert_eq!(escape(b"\x1b[12", 1), (4, Escape::Other));
+        assert_eq!(escape(b"\x1b(B", 1), (3, Escape::Other));
+        assert_eq!(escape(b"\x1b7", 1), (2, Escape::Final(b'7')));
+        assert_eq!(escape("\x1bé".as_bytes(), 1), (1, Escape::Other));
+        assert_eq!(escape(b"\x1b", 1), (1, Escape::Other));
+    }
+}
@@ -24,6 +24,41 @@ pub const CLEAR_LINE_RIGHT: &str = "\x1b[K";
 /// Clear from cursor to beginning of line.
 pub const CLEAR_LINE_LEFT: &str = "\x1b[1K";
 
+/// Reset the scroll region to the whole screen (DECSTBM).
+pub const SCROLL_REGION_RESET: &str = "\x1b[r";
+
+/// Generate a scroll region sequence (DECSTBM).
+///
+/// `top` and `bottom` are 0-based and inclusive.
+#[must_use]
+pub fn scroll_region(top: u32, bottom: u32) -> String {
+    format!("\x1b[{};{}r", top + 1, bottom + 1)
+}
+
+/// Generate a scroll up sequence (SU): content moves up `n` lines.
+#[must_use]
+pub fn scroll_up(n: u32) -> String {
+    format!("\x1b[{n}S")
+}
+
+/// Generate a scroll down sequence (SD): content moves down `n` lines.
+#[must_use]
+pub fn scroll_down(n: u32) -> String {
+    format!("\x1b[{n}T")
+}
+
+/// Generate an insert lines sequence (IL) at the cursor row.
+#[must_use]
+pub fn insert_lines(n: u32) -> String {
+    format!("\x1b[{n}L")
+}
+
+/// Generate a delete lines sequence (DL) at the cursor row.
+#[must_use]
+pub fn delete_lines(n: u32) -> String {
+    format!("\x1b[{n}M")
+}
+
 /// Hide cursor.
 pub const CURSOR_HIDE: &str = "\x1b[?25l";
 
@@ -50,6 +85,28 @@ pub fn cursor_color(r: u8, g: u8, b: u8) -> String {
     format!("\x1b]12;#{r:02x}{g:02x}{b:02x}\x07")
 }
 
+/// Generate a palette color sequence (OSC 4).
+///
+/// Channels are 16-bit, written in the `rgb:RRRR/GGGG/BBBB` form.
+#[must_use]
+pub fn palette_color(index: u8, r: u16, g: u16, b: u16) -> String {
+    format!("\x1b]4;{index};rgb:{r:04x}/{g:04x}/{b:04x}\x1b\\")
+}
+
+/// Generate a palette reset sequence (OSC 104).
+///
+/// Resets the given palette entries, or the whole palette if `indices` is empty.
+#[must_use]
+pub fn palette_reset(indices: &[u8]) -> String {
+    let mut seq = String::from("\x1b]104");
+    for index in indices {
+        seq.push(';');
+        seq.push_str(&index.to_string());
+    }
+    seq.push_str("\x1b\\");
+    seq
+}
+
 /// Enable alternative screen buffer.
 pub const ALT_SCREEN_ON: &str = "\x1b[?1049h";
 
@@ -89,6 +146,15 @@ pub mod query {
     pub const PIXEL_RESOLUTION: &str = "\x1b[14t";
     /// Kitty keyboard protocol query.
     pub const KITTY_KEYBOARD: &str = "\x1b[?u";
+
+    /// Default background color query (OSC 11).
+    pub const BACKGROUND_COLOR: &str = "\x1b]11;?\x1b\\";
+
+    /// Palette color query (OSC 4 with `?` as the color).
+    #[must_use]
+    pub fn palette_color(index: u8) -> String {
+        format!("\x1b]4;{index};?\x1b\\")
+    }
 }
 
 /// Set window title prefix.
@@ -126,12 +192,26 @@ pub mod sync {
     pub const END: &str = "\x1b[?2026l";
 }
 
+/// DEC line attributes, applying to the whole line the cursor is on.
+pub mod line_size {
+    /// Normal-size line (DECSWL).
+    pub const SINGLE_WIDTH: &str = "\x1b#5";
+    /// Double-width line (DECDWL).
+    pub const DOUBLE_WIDTH: &str = "\x1b#6";
+    /// Top half of a double-height line (DECDHL).
+    pub const DOUBLE_HEIGHT_TOP: &str = "\x1b#3";
+    /// Bottom half of a double-height line (DECDHL).
+    pub const DOUBLE_HEIGHT_BOTTOM: &str = "\x1b#4";
+}
+
 /// Color reset sequences.
 pub mod color {
     /// Reset foreground to default.
     pub const FG_DEFAULT: &str = "\x1b[39m";
     /// Reset background to default.
     pub const BG_DEFAULT: &str = "\x1b[49m";
+    /// Reset underline color to default (the foreground).
+    pub const UNDERLINE_DEFAULT: &str = "\x1b[59m";
 }
 
 /// Attribute reset sequences.
@@ -150,6 +230,10 @@ pub mod attr {
     pub const RESET_HIDDEN: &str = "\x1b[28m";
     /// Reset strikethrough.
     pub const RESET_STRIKETHROUGH: &str = "\x1b[29m";
+    /// Reset overline.
+    pub const RESET_OVERLINE: &str = "\x1b[55m";
+    /// Reset superscript/subscript.
+    pub const RESET_SCRIPT: &str = "\x1b[75m";
 }
 
 #[cfg(test)]
@@ -252,6 +336,16 @@ mod tests {
         assert!(CURSOR_SHOW.ends_with('h'));
     }
 
+    #[test]
+    fn test_scroll_region_sequences() {
+        assert_eq!(scroll_region(0, 23), "\x1b[1;24r");
+        assert_eq!(SCROLL_REGION_RESET, "\x1b[r");
+        assert_eq!(scroll_up(3), "\x1b[3S");
+        assert_eq!(scroll_down(2), "\x1b[2T");
+        assert_eq!(insert_lines(1), "\x1b[1L");
+        assert_eq!(delete_lines(4), "\x1b[4M");
+    }
+
     #[test]
     fn test_cursor_save_decsc() {
         // DECSC - DEC Save Cursor (ESC 7)
@@ -303,6 +397,17 @@ mod tests {
         assert!(white.contains("#ffffff"));
     }
 
+    #[test]
+    fn test_palette_sequences_osc4_osc104() {
+        assert_eq!(
+            palette_color(1, 0xffff, 0x8080, 0),
+            "\x1b]4;1;rgb:ffff/8080/0000\x1b\\"
+        );
+        assert_eq!(palette_reset(&[]), "\x1b]104\x1b\\");
+        assert_eq!(palette_reset(&[1, 12]), "\x1b]104;1;12\x1b\\");
+        assert_eq!(query::palette_color(255), "\x1b]4;255;?\x1b\\");
+    }
+
     // =========================================================================
     // Alternative Screen Buffer (xterm)
     // =========================================================================
@@ -731,6 +836,8 @@ mod tests {
             attr::RESET_INVERSE,
             attr::RESET_HIDDEN,
             attr::RESET_STRIKETHROUGH,
+            attr::RESET_OVERLINE,
+            attr::RESET_SCRIPT,
         ];
 
         for seq in all_sequences {
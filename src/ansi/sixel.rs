@@ -0,0 +1,342 @@
+//! Sixel image encoding.
+//!
+//! A sixel image is a DCS sequence carrying a color palette followed by the
+//! image in bands six pixels tall. Each band is written once per palette
+//! color, as one char per column whose low six bits say which of the
+//! column's pixels take that color.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::buffer::PixelBuffer;
+
+/// Largest palette a sixel image may use.
+pub const MAX_COLORS: usize = 256;
+
+/// Options for [`encode`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SixelOptions {
+    /// Palette size limit, clamped to 1..=[`MAX_COLORS`]. Images with more
+    /// distinct colors are reduced by median cut.
+    pub max_colors: usize,
+    /// Pixels with alpha below this are left unpainted, showing whatever
+    /// the terminal already has there.
+    pub alpha_threshold: f32,
+}
+
+impl Default for SixelOptions {
+    fn default() -> Self {
+        Self {
+            max_colors: MAX_COLORS,
+            alpha_threshold: 0.5,
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+/// Encode a pixel buffer as a sixel image.
+///
+/// The output starts with the DCS header and raster attributes (square
+/// pixels, transparent background), then the palette and RLE-compressed
+/// bands, and ends with ST. Write it at the cell where the image's top-left
+/// corner should go.
+#[must_use]
+pub fn encode(pixels: &PixelBuffer, opts: SixelOptions) -> Vec<u8> {
+    let (palette, indices) = quantize(pixels, opts);
+    let width = pixels.width as usize;
+    let height = pixels.height as usize;
+
+    let mut out = Vec::with_capacity(64 + palette.len() * 16 + width * height / 2);
+    // P2 = 1: pixels without a color keep the terminal's contents.
+    out.extend_from_slice(b"\x1bP0;1;0q");
+    let _ = write!(out, "\"1;1;{};{}", pixels.width, pixels.height);
+    for (index, &(r, g, b)) in palette.iter().enumerate() {
+        let _ = write!(
+            out,
+            "#{index};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        );
+    }
+
+    let mut rows: Vec<Option<Vec<u8>>> = vec![None; palette.len()];
+    for top in (0..height).step_by(6) {
+        if top > 0 {
+            out.push(b'-');
+        }
+        for dy in 0..(height - top).min(6) {
+            let row = (top + dy) * width;
+            for (x, index) in indices[row..row + width].iter().enumerate() {
+                if let Some(index) = index {
+                    rows[usize::from(*index)].get_or_insert_with(|| vec![0; width])[x] |= 1 << dy;
+                }
+            }
+        }
+
+        let mut first = true;
+        for (index, bits) in rows.iter_mut().enumerate() {
+            let Some(bits) = bits.take() else {
+                continue;
+            };
+            if !first {
+                // Back to the start of the band for the next color.
+                out.push(b'$');
+            }
+            first = false;
+            let _ = write!(out, "#{index}");
+            write_band_row(&mut out, &bits);
+        }
+    }
+
+    out.extend_from_slice(b"\x1b\\");
+    out
+}
+
+/// Sixel color components are percentages.
+fn percent(value: u8) -> u32 {
+    (u32::from(value) * 100 + 127) / 255
+}
+
+/// Write one color's sixels for a band, run-length encoding repeats and
+/// dropping trailing empty columns.
+fn write_band_row(out: &mut Vec<u8>, bits: &[u8]) {
+    let end = bits
+        .iter()
+        .rposition(|&b| b != 0)
+        .map_or(0, |last| last + 1);
+    let mut x = 0;
+    while x < end {
+        let value = bits[x];
+        let run = bits[x..end].iter().take_while(|&&b| b == value).count();
+        let sixel = b'?' + value;
+        // !n<sixel> is 2 + digits bytes.
+        if run > 3 {
+            let _ = write!(out, "!{run}");
+            out.push(sixel);
+        } else {
+            out.extend(std::iter::repeat_n(sixel, run));
+        }
+        x += run;
+    }
+}
+
+/// Build a palette of at most `opts.max_colors` entries and map each pixel
+/// to its entry, or `None` if it is transparent.
+fn quantize(pixels: &PixelBuffer, opts: SixelOptions) -> (Vec<Rgb>, Vec<Option<u8>>) {
+    let max_colors = opts.max_colors.clamp(1, MAX_COLORS);
+    let opaque: Vec<Option<Rgb>> = pixels
+        .pixels
+        .iter()
+        .map(|pixel| (pixel.a >= opts.alpha_threshold).then(|| pixel.to_rgb_u8()))
+        .collect();
+
+    // Distinct colors with their pixel counts, in order of first appearance.
+    let mut seen: HashMap<Rgb, usize> = HashMap::new();
+    let mut colors: Vec<(Rgb, u32)> = Vec::new();
+    for rgb in opaque.iter().flatten() {
+        let index = *seen.entry(*rgb).or_insert_with(|| {
+            colors.push((*rgb, 0));
+            colors.len() - 1
+        });
+        colors[index].1 += 1;
+    }
+
+    let (palette, entry): (Vec<Rgb>, HashMap<Rgb, u8>) = if colors.len() <= max_colors {
+        let entry = colors
+            .iter()
+            .enumerate()
+            .map(|(index, (rgb, _))| (*rgb, index as u8))
+            .collect();
+        (colors.into_iter().map(|(rgb, _)| rgb).collect(), entry)
+    } else {
+        let boxes = median_cut(colors, max_colors);
+        let entry = boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, colors)| colors.iter().map(move |(rgb, _)| (*rgb, index as u8)))
+            .collect();
+        (boxes.iter().map(|colors| mean(colors)).collect(), entry)
+    };
+
+    let indices = opaque
+        .into_iter()
+        .map(|rgb| rgb.map(|rgb| entry[&rgb]))
+        .collect();
+    (palette, indices)
+}
+
+fn channel(rgb: Rgb, channel: usize) -> u8 {
+    match channel {
+        0 => rgb.0,
+        1 => rgb.1,
+        _ => rgb.2,
+    }
+}
+
+/// The channel with the widest range in a box, and that range.
+fn widest_channel(colors: &[(Rgb, u32)]) -> (usize, u8) {
+    (0..3)
+        .map(|ch| {
+            let values = colors.iter().map(|(rgb, _)| channel(*rgb, ch));
+            let min = values.clone().min().unwrap_or(0);
+            let max = values.max().unwrap_or(0);
+            (ch, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+/// Split colors into at most `max_colors` boxes, repeatedly cutting the
+/// box with the widest channel range at its pixel-weighted median.
+fn median_cut(colors: Vec<(Rgb, u32)>, max_colors: usize) -> Vec<Vec<(Rgb, u32)>> {
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let Some((index, ch)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(index, colors)| {
+                let (ch, range) = widest_channel(colors);
+                (index, ch, range)
+            })
+            .max_by_key(|&(_, _, range)| range)
+            .map(|(index, ch, _)| (index, ch))
+        else {
+            break;
+        };
+
+        let mut lower = boxes.swap_remove(index);
+        lower.sort_by_key(|(rgb, _)| channel(*rgb, ch));
+        let total: u64 = lower.iter().map(|(_, count)| u64::from(*count)).sum();
+        let mut seen = 0;
+        let median = lower
+            .iter()
+            .position(|(_, count)| {
+                seen += u64::from(*count);
+                seen * 2 >= total
+            })
+            .map_or(1, |position| position + 1);
+        let upper = lower.split_off(median.clamp(1, lower.len() - 1));
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+    boxes
+}
+
+/// Pixel-weighted mean color of a box.
+fn mean(colors: &[(Rgb, u32)]) -> Rgb {
+    let total: u64 = colors.iter().map(|(_, count)| u64::from(*count)).sum();
+    let average = |ch| {
+        let sum: u64 = colors
+            .iter()
+            .map(|(rgb, count)| u64::from(channel(*rgb, ch)) * u64::from(*count))
+            .sum();
+        ((sum + total / 2) / total.max(1)) as u8
+    };
+    (average(0), average(1), average(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgba;
+
+    fn image(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Rgba) -> PixelBuffer {
+        let mut buffer = PixelBuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                buffer.set(x, y, pixel(x, y));
+            }
+        }
+        buffer
+    }
+
+    fn encode_str(pixels: &PixelBuffer) -> String {
+        String::from_utf8(encode(pixels, SixelOptions::default())).unwrap()
+    }
+
+    #[test]
+    fn test_single_color_band() {
+        let red = image(2, 6, |_, _| Rgba::RED);
+        assert_eq!(
+            encode_str(&red),
+            "\x1bP0;1;0q\"1;1;2;6#0;2;100;0;0#0~~\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_colors_share_a_band_with_rle() {
+        let stripes = image(4, 2, |_, y| if y == 0 { Rgba::RED } else { Rgba::BLUE });
+        assert_eq!(
+            encode_str(&stripes),
+            "\x1bP0;1;0q\"1;1;4;2#0;2;100;0;0#1;2;0;0;100#0!4@$#1!4A\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_transparent_pixels_and_second_band() {
+        let green = image(1, 8, |_, y| {
+            if y == 2 {
+                Rgba::TRANSPARENT
+            } else {
+                Rgba::GREEN
+            }
+        });
+        assert_eq!(
+            encode_str(&green),
+            "\x1bP0;1;0q\"1;1;1;8#0;2;0;100;0#0z-#0B\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_trailing_empty_columns_are_dropped() {
+        let dot = image(5, 1, |x, _| {
+            if x == 1 {
+                Rgba::WHITE
+            } else {
+                Rgba::TRANSPARENT
+            }
+        });
+        assert!(encode_str(&dot).ends_with("#0?@\x1b\\"));
+    }
+
+    #[test]
+    fn test_palette_is_limited_by_median_cut() {
+        let gradient = image(300, 2, |x, y| {
+            Rgba::from_rgb_u8((x % 256) as u8, (x / 2) as u8, if y == 0 { 0 } else { 255 })
+        });
+        let sixel = encode_str(&gradient);
+        let palette_entries = sixel
+            .split('#')
+            .filter(|entry| entry.matches(';').count() == 4)
+            .count();
+        assert_eq!(palette_entries, MAX_COLORS);
+
+        let clusters = image(4, 1, |x, _| match x {
+            0 => Rgba::from_rgb_u8(250, 0, 0),
+            1 => Rgba::from_rgb_u8(240, 10, 0),
+            2 => Rgba::from_rgb_u8(0, 0, 250),
+            _ => Rgba::from_rgb_u8(10, 0, 240),
+        });
+        let sixel = String::from_utf8(encode(
+            &clusters,
+            SixelOptions {
+                max_colors: 2,
+                ..SixelOptions::default()
+            },
+        ))
+        .unwrap();
+        assert!(sixel.contains("#0;2;96;2;0#1;2;2;0;96"), "{sixel:?}");
+        assert!(sixel.ends_with("#0@@$#1??@@\x1b\\"), "{sixel:?}");
+    }
+
+    #[test]
+    fn test_empty_image() {
+        assert_eq!(
+            encode_str(&PixelBuffer::new(0, 0)),
+            "\x1bP0;1;0q\"1;1;0;0\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Measuring and cutting strings that contain ANSI escape sequences.
+//!
+//! Plugins and logs often hand over pre-colored strings. These helpers treat
+//! escape sequences as zero-width so such strings can be laid out by their
+//! visible text.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::scan::{self, Escape};
+use crate::unicode::display_width;
+
+const ESC: u8 = 0x1b;
+
+/// What an escape sequence does, as far as these helpers care.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Sequence {
+    /// SGR that resets every attribute (`ESC[m`, `ESC[0m`).
+    SgrReset,
+    /// Any other SGR.
+    Sgr,
+    /// A non-SGR CSI, with its final byte.
+    Csi(u8),
+    /// OSC 8 hyperlink; `true` when it opens a link.
+    Hyperlink(bool),
+    /// Any other OSC.
+    Osc,
+    Other,
+}
+
+/// Split `input` into text and escape sequences.
+///
+/// A sequence truncated by the end of the input is returned whole.
+fn segments(input: &str) -> impl Iterator<Item = (&str, Option<Sequence>)> {
+    let bytes = input.as_bytes();
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        if pos >= bytes.len() {
+            return None;
+        }
+        let start = pos;
+        if bytes[pos] != ESC {
+            pos = bytes[pos..]
+                .iter()
+                .position(|&b| b == ESC)
+                .map_or(bytes.len(), |len| pos + len);
+            return Some((&input[start..pos], None));
+        }
+        let (end, kind) = sequence(bytes, pos + 1);
+        pos = end;
+        Some((&input[start..end], Some(kind)))
+    })
+}
+
+/// Find the end of the escape sequence whose `ESC` precedes `i`.
+///
+/// Only ASCII bytes are consumed, so the end is always a char boundary.
+pub(crate) fn sequence(bytes: &[u8], i: usize) -> (usize, Sequence) {
+    let (end, escape) = scan::escape(bytes, i);
+    let kind = match escape {
+        Escape::Csi {
+            body,
+            final_byte: b'm',
+        } => {
+            if body.iter().all(|&b| b == b'0' || b == b';') {
+                Sequence::SgrReset
+            } else {
+                Sequence::Sgr
+            }
+        }
+        Escape::Csi { final_byte, .. } => Sequence::Csi(final_byte),
+        // OSC 8 ; params ; URL — an empty URL closes the link
+        Escape::String { kind: b']', body } => {
+            body.strip_prefix(b"8;").map_or(Sequence::Osc, |link| {
+                let url = link.splitn(2, |&b| b == b';').nth(1).unwrap_or_default();
+                Sequence::Hyperlink(!url.is_empty())
+            })
+        }
+        Escape::String { .. } | Escape::Final(_) | Escape::Other => Sequence::Other,
+    };
+    (end, kind)
+}
+
+/// Remove escape sequences (CSI, OSC, DCS, APC, ...) from `input`.
+///
+/// OSC-style strings may end with either BEL or ST (`ESC \`).
+///
+/// # Example
+///
+/// ```
+/// use opentui_rust::ansi;
+///
+/// let linked = "\x1b]8;;https://example.com\x1b\\\x1b[1mdocs\x1b[0m\x1b]8;;\x07";
+/// assert_eq!(ansi::strip(linked), "docs");
+/// ```
+#[must_use]
+pub fn strip(input: &str) -> String {
+    segments(input)
+        .filter(|(_, kind)| kind.is_none())
+        .map(|(text, _)| text)
+        .collect()
+}
+
+/// Display width of `input` in terminal columns, ignoring escape sequences.
+#[must_use]
+pub fn visible_width(input: &str) -> usize {
+    display_width(&strip(input))
+}
+
+/// Cut `input` to at most `max_cols` visible columns, ending with `ellipsis`
+/// when anything was removed.
+///
+/// The cut falls on a grapheme boundary; a wide char that would straddle
+/// `max_cols` is dropped whole. Escape sequences before the cut are kept,
+/// and if an SGR style or hyperlink is still open at the cut, a reset is
+/// appended so the result doesn't bleed into what follows it. An ellipsis
+/// wider than `max_cols` is left out.
+#[must_use]
+pub fn truncate_visible(input: &str, max_cols: usize, ellipsis: &str) -> String {
+    if visible_width(input) <= max_cols {
+        return input.to_string();
+    }
+    let ellipsis_width = visible_width(ellipsis);
+    let (ellipsis, budget) = if ellipsis_width <= max_cols {
+        (ellipsis, max_cols - ellipsis_width)
+    } else {
+        ("", max_cols)
+    };
+
+    let mut out = String::with_capacity(input.len());
+    let mut used = 0;
+    let mut sgr_open = false;
+    let mut link_open = false;
+    'segments: for (text, kind) in segments(input) {
+        match kind {
+            None => {
+                for grapheme in text.graphemes(true) {
+                    let width = display_width(grapheme);
+                    if used + width > budget {
+                        break 'segments;
+                    }
+                    used += width;
+                    out.push_str(grapheme);
+                }
+            }
+            Some(kind) => {
+                match kind {
+                    Sequence::SgrReset => sgr_open = false,
+                    Sequence::Sgr => sgr_open = true,
+                    Sequence::Hyperlink(open) => link_open = open,
+                    Sequence::Csi(_) | Sequence::Osc | Sequence::Other => {}
+                }
+                out.push_str(text);
+            }
+        }
+    }
+
+    out.push_str(ellipsis);
+    if link_open {
+        out.push_str("\x1b]8;;\x1b\\");
+    }
+    if sgr_open {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINK: &str = "\x1b]8;id=1;https://example.com/docs\x1b\\";
+    const LINK_END: &str = "\x1b]8;;\x07";
+
+    #[test]
+    fn test_strip_removes_every_sequence_kind() {
+        let input = format!(
+            "\x1b[38;2;255;0;0mred\x1b[m {LINK}link{LINK_END} \
+             \x1bP1$r0m\x1b\\\x1b_Gf=100;data\x1b\\\x1b(0q\x1b(B\x1b[?25h!"
+        );
+        assert_eq!(strip(&input), "red link q!");
+    }
+
+    #[test]
+    fn test_strip_drops_truncated_sequences() {
+        assert_eq!(strip("ok\x1b[38;2;1"), "ok");
+        assert_eq!(strip("ok\x1b]8;;https://example"), "ok");
+        assert_eq!(strip("ok\x1b"), "ok");
+    }
+
+    #[test]
+    fn test_visible_width_joins_graphemes_split_by_sgr() {
+        // A truecolor SGR between a base char and its combining mark
+        let input = "e\x1b[38;2;10;20;30m\u{301}x\x1b[0m";
+        assert_eq!(strip(input), "e\u{301}x");
+        assert_eq!(visible_width(input), 2);
+        assert_eq!(visible_width("\x1b[1m世界\x1b[22m"), 4);
+    }
+
+    #[test]
+    fn test_truncate_keeps_short_input_unchanged() {
+        let input = "\x1b[1mbold\x1b[0m";
+        assert_eq!(truncate_visible(input, 4, "…"), input);
+    }
+
+    #[test]
+    fn test_truncate_appends_reset_for_open_sgr() {
+        let input = "\x1b[38;2;255;0;0mhello world";
+        assert_eq!(
+            truncate_visible(input, 6, "…"),
+            "\x1b[38;2;255;0;0mhello…\x1b[0m"
+        );
+        // Closed before the cut: no reset needed
+        assert_eq!(
+            truncate_visible("\x1b[1mhi\x1b[0m there", 5, "…"),
+            "\x1b[1mhi\x1b[0m t…"
+        );
+    }
+
+    #[test]
+    fn test_truncate_closes_open_hyperlink() {
+        let input = format!("see {LINK}the docs{LINK_END} for more");
+        assert_eq!(
+            truncate_visible(&input, 8, "..."),
+            format!("see {LINK}t...\x1b]8;;\x1b\\")
+        );
+    }
+
+    #[test]
+    fn test_truncate_drops_wide_char_at_boundary() {
+        let input = "ab世界";
+        assert_eq!(truncate_visible(input, 4, ""), "ab世");
+        assert_eq!(truncate_visible(input, 3, ""), "ab");
+        assert_eq!(truncate_visible(input, 4, "…"), "ab…");
+        assert_eq!(visible_width(&truncate_visible(input, 5, "…")), 5);
+    }
+
+    #[test]
+    fn test_truncate_keeps_combining_mark_after_sgr() {
+        let input = "e\x1b[38;2;10;20;30m\u{301}xyz";
+        assert_eq!(
+            truncate_visible(input, 2, "…"),
+            "e\x1b[38;2;10;20;30m\u{301}…\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_oversized_ellipsis_is_dropped() {
+        assert_eq!(truncate_visible("abcdef", 2, "..."), "ab");
+    }
+}
@@ -19,6 +19,7 @@
 
 use opentui::GraphemePool;
 use opentui::buffer::{ClipRect, GrayscaleBuffer, OptimizedBuffer, PixelBuffer, ScissorStack};
+use opentui::color::{self, Gradient};
 use opentui::event::{LogLevel as OpentuiLogLevel, set_log_callback};
 use opentui::input::{Event, InputParser, KeyCode, KeyModifiers};
 #[allow(unused_imports)]
@@ -29,7 +30,7 @@ use opentui::terminal::{MouseButton, MouseEventKind, enable_raw_mode, terminal_s
 use opentui_rust as opentui;
 // TODO: EditBuffer, EditorView, WrapMode will be used for editor integration
 #[allow(unused_imports)]
-use opentui::text::{EditBuffer, EditorView, WrapMode};
+use opentui::text::{EditBuffer, EditorView, WrapMode, fuzzy};
 #[allow(unused_imports)] // Cell used only in tests
 use opentui::{Cell, CellContent, Renderer, RendererOptions, Rgba, Style};
 use std::collections::VecDeque;
@@ -445,6 +446,7 @@ impl Config {
             hide_cursor: true,
             enable_mouse: self.enable_mouse && self.cap_preset != CapPreset::NoMouse,
             query_capabilities: self.query_capabilities,
+            capabilities: None,
         }
     }
 
@@ -495,14 +497,18 @@ pub enum Backend {
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)] // Internal type, errors are obvious
 impl Backend {
     /// Create a new direct (synchronous) backend.
-    pub fn new_direct(width: u32, height: u32, options: RendererOptions) -> io::Result<Self> {
+    pub fn new_direct(width: u32, height: u32, options: RendererOptions) -> opentui::Result<Self> {
         Ok(Self::Direct(Renderer::new_with_options(
             width, height, options,
         )?))
     }
 
     /// Create a new threaded backend.
-    pub fn new_threaded(width: u32, height: u32, options: RendererOptions) -> io::Result<Self> {
+    pub fn new_threaded(
+        width: u32,
+        height: u32,
+        options: RendererOptions,
+    ) -> opentui::Result<Self> {
         let renderer = ThreadedRenderer::new_with_options(width, height, options)?;
         let capabilities = Capabilities::detect();
         Ok(Self::Threaded {
@@ -538,7 +544,7 @@ impl Backend {
     }
 
     /// Present the current frame.
-    pub fn present(&mut self) -> io::Result<()> {
+    pub fn present(&mut self) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.present(),
             Self::Threaded { renderer, .. } => renderer.present(),
@@ -546,7 +552,7 @@ impl Backend {
     }
 
     /// Resize the renderer.
-    pub fn resize(&mut self, width: u32, height: u32) -> io::Result<()> {
+    pub fn resize(&mut self, width: u32, height: u32) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.resize(width, height),
             Self::Threaded {
@@ -563,7 +569,7 @@ impl Backend {
     }
 
     /// Set the terminal title.
-    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+    pub fn set_title(&mut self, title: &str) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.set_title(title),
             Self::Threaded { renderer, .. } => renderer.set_title(title),
@@ -571,7 +577,7 @@ impl Backend {
     }
 
     /// Set cursor position and visibility.
-    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> io::Result<()> {
+    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.set_cursor(x, y, visible),
             Self::Threaded { renderer, .. } => renderer.set_cursor(x, y, visible),
@@ -579,7 +585,7 @@ impl Backend {
     }
 
     /// Set cursor style.
-    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> io::Result<()> {
+    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.set_cursor_style(style, blinking),
             Self::Threaded { renderer, .. } => renderer.set_cursor_style(style, blinking),
@@ -690,7 +696,7 @@ impl Backend {
     }
 
     /// Cleanup (for direct renderer only - threaded uses shutdown).
-    pub fn cleanup(&mut self) -> io::Result<()> {
+    pub fn cleanup(&mut self) -> opentui::Result<()> {
         match self {
             Self::Direct(r) => r.cleanup(),
             Self::Threaded { .. } => Ok(()), // Threaded cleanup happens on drop
@@ -698,7 +704,7 @@ impl Backend {
     }
 
     /// Shutdown the backend (consumes self for threaded).
-    pub fn shutdown(self) -> io::Result<()> {
+    pub fn shutdown(self) -> opentui::Result<()> {
         match self {
             Self::Direct(_) => Ok(()), // Direct renderer cleans up on drop
             Self::Threaded { renderer, .. } => renderer.shutdown(),
@@ -1284,17 +1290,9 @@ impl Theme {
     /// Create a horizontal gradient style iterator.
     ///
     /// Returns an iterator that yields colors from `start` to `end`
-    /// over `steps` columns.
-    #[allow(clippy::cast_precision_loss)] // Precision loss acceptable for gradient steps
+    /// over `steps` columns, interpolated in Oklab.
     pub fn gradient(start: Rgba, end: Rgba, steps: u32) -> impl Iterator<Item = Rgba> {
-        (0..steps).map(move |i| {
-            let t = if steps > 1 {
-                i as f32 / (steps - 1) as f32
-            } else {
-                0.0
-            };
-            Self::lerp(start, end, t)
-        })
+        Gradient::new(start, end).steps(steps as usize).into_iter()
     }
 }
 
@@ -1306,10 +1304,22 @@ impl Theme {
 pub struct Styles;
 
 impl Styles {
+    /// Minimum text contrast (WCAG AA for normal text).
+    const MIN_TEXT_CONTRAST: f32 = 4.5;
+
+    /// `fg`, adjusted if needed to stay readable on `bg`.
+    fn readable(fg: Rgba, bg: Rgba) -> Rgba {
+        color::ensure_contrast(fg, bg, Self::MIN_TEXT_CONTRAST)
+    }
+
     /// Header style: bold with primary accent.
     #[must_use]
     pub fn header(theme: &Theme) -> Style {
-        Style::builder().fg(theme.fg0).bg(theme.bg1).bold().build()
+        Style::builder()
+            .fg(Self::readable(theme.fg0, theme.bg1))
+            .bg(theme.bg1)
+            .bold()
+            .build()
     }
 
     /// Panel border style (unfocused).
@@ -1332,7 +1342,7 @@ impl Styles {
     #[must_use]
     pub fn selection(theme: &Theme) -> Style {
         Style::builder()
-            .fg(theme.fg0)
+            .fg(Self::readable(theme.fg0, theme.selection_bg))
             .bg(theme.selection_bg)
             .build()
     }
@@ -1340,19 +1350,29 @@ impl Styles {
     /// Muted/hint text style.
     #[must_use]
     pub fn muted(theme: &Theme) -> Style {
-        Style::builder().fg(theme.fg2).bg(theme.bg0).build()
+        Style::builder()
+            .fg(Self::readable(theme.fg2, theme.bg0))
+            .bg(theme.bg0)
+            .build()
     }
 
     /// Status bar style.
     #[must_use]
     pub fn status_bar(theme: &Theme) -> Style {
-        Style::builder().fg(theme.fg1).bg(theme.bg2).build()
+        Style::builder()
+            .fg(Self::readable(theme.fg1, theme.bg2))
+            .bg(theme.bg2)
+            .build()
     }
 
     /// Key hint style (hotkeys in status bar).
     #[must_use]
     pub fn key_hint(theme: &Theme) -> Style {
-        Style::builder().fg(theme.fg0).bg(theme.bg2).bold().build()
+        Style::builder()
+            .fg(Self::readable(theme.fg0, theme.bg2))
+            .bg(theme.bg2)
+            .bold()
+            .build()
     }
 
     /// Link style.
@@ -1562,19 +1582,19 @@ impl PaletteState {
         ("Quit", "Exit the application"),
     ];
 
-    /// Update filtered commands based on query.
+    /// Update filtered commands based on query, best fuzzy matches first.
     pub fn update_filter(&mut self) {
-        let query_lower = self.query.to_lowercase();
-        self.filtered = Self::COMMANDS
+        let mut ranked: Vec<(usize, i32)> = Self::COMMANDS
             .iter()
             .enumerate()
-            .filter(|(_, (name, desc))| {
-                query_lower.is_empty()
-                    || name.to_lowercase().contains(&query_lower)
-                    || desc.to_lowercase().contains(&query_lower)
+            .filter_map(|(i, (name, desc))| {
+                let name = fuzzy::match_score(&self.query, name).map(|m| m.score);
+                let desc = fuzzy::match_score(&self.query, desc).map(|m| m.score);
+                name.max(desc).map(|score| (i, score))
             })
-            .map(|(i, _)| i)
             .collect();
+        ranked.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        self.filtered = ranked.into_iter().map(|(i, _)| i).collect();
 
         // Clamp selection to valid range
         if !self.filtered.is_empty() && self.selected >= self.filtered.len() {
@@ -4547,13 +4567,11 @@ fn run_interactive(config: &Config) -> io::Result<()> {
                             app.handle_resize(new_w, new_h);
                         }
                         // Handle mouse events with hit testing
-                        Event::Mouse(mouse) => {
-                            // Only process left-button clicks
-                            if mouse.button == MouseButton::Left {
-                                if let Some(hit_id) = renderer.hit_test(mouse.x, mouse.y) {
-                                    let action = App::hit_to_action(hit_id, mouse.kind);
-                                    app.apply_action(&action);
-                                }
+                        // Only process left-button clicks
+                        Event::Mouse(mouse) if mouse.button == MouseButton::Left => {
+                            if let Some(hit_id) = renderer.hit_test(mouse.x, mouse.y) {
+                                let action = App::hit_to_action(hit_id, mouse.kind);
+                                app.apply_action(&action);
                             }
                         }
                         // Other events processed below
@@ -5742,13 +5760,11 @@ fn draw_tour_overlay(
     );
 
     // Description (may have newlines)
-    let mut desc_y = overlay_y + 4;
-    for line in desc.lines() {
+    for (desc_y, line) in (overlay_y + 4..).zip(desc.lines()) {
         if desc_y >= overlay_y + overlay_h - 1 {
             break;
         }
         buffer.draw_text(overlay_x + 3, desc_y, line, Style::fg(theme.fg1));
-        desc_y += 1;
     }
 
     // Navigation hint
@@ -6036,8 +6052,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                 style: BoxStyle::single(Style::fg(theme.fg0)),
                 sides: BoxSides::default(),
                 fill: None,
+                shadow: None,
                 title: Some("Left".to_string()),
                 title_align: TitleAlign::Left,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, titled_w, box_h, options);
             col += titled_w + 2;
@@ -6049,8 +6069,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                 style: BoxStyle::rounded(Style::fg(theme.accent_primary)),
                 sides: BoxSides::default(),
                 fill: None,
+                shadow: None,
                 title: Some("Center".to_string()),
                 title_align: TitleAlign::Center,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, titled_w, box_h, options);
             col += titled_w + 2;
@@ -6062,8 +6086,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                 style: BoxStyle::double(Style::fg(theme.accent_secondary)),
                 sides: BoxSides::default(),
                 fill: None,
+                shadow: None,
                 title: Some("Right".to_string()),
                 title_align: TitleAlign::Right,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, titled_w, box_h, options);
         }
@@ -6089,8 +6117,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                     left: true,
                 },
                 fill: None,
+                shadow: None,
                 title: None,
                 title_align: TitleAlign::Left,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, partial_w, box_h, options);
             buffer.draw_text(col + 1, row + 1, "No top", dim_style);
@@ -6108,8 +6140,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                     left: false,
                 },
                 fill: None,
+                shadow: None,
                 title: None,
                 title_align: TitleAlign::Left,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, partial_w, box_h, options);
             buffer.draw_text(col + 1, row + 1, "No left", dim_style);
@@ -6127,8 +6163,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
                     left: false,
                 },
                 fill: None,
+                shadow: None,
                 title: None,
                 title_align: TitleAlign::Left,
+                title_spans: Vec::new(),
+                secondary_title: None,
+                bottom_title: None,
             };
             buffer.draw_box_with_options(col, row, partial_w, box_h, options);
             buffer.draw_text(col + 1, row + 1, "H lines", dim_style);
@@ -6146,8 +6186,12 @@ fn draw_drawing_section(buffer: &mut OptimizedBuffer, rect: &Rect, theme: &Theme
             style: BoxStyle::rounded(Style::fg(theme.accent_primary)),
             sides: BoxSides::default(),
             fill: Some(fill_color),
+            shadow: None,
             title: Some("Filled".to_string()),
             title_align: TitleAlign::Center,
+            title_spans: Vec::new(),
+            secondary_title: None,
+            bottom_title: None,
         };
         buffer.draw_box_with_options(x + 2, row, 20, box_h, options);
         buffer.draw_text(x + 4, row + box_h / 2, "Alpha: 0.2", Style::fg(theme.fg0));
@@ -7414,7 +7458,6 @@ fn draw_rect_bg(buffer: &mut OptimizedBuffer, rect: &Rect, color: Rgba) {
 }
 
 /// Draw a horizontal gradient bar.
-#[allow(clippy::cast_precision_loss)] // Precision loss acceptable for gradient
 fn draw_gradient_bar(buffer: &mut OptimizedBuffer, rect: &Rect, start: Rgba, end: Rgba) {
     if rect.is_empty() {
         return;
@@ -7424,13 +7467,7 @@ fn draw_gradient_bar(buffer: &mut OptimizedBuffer, rect: &Rect, start: Rgba, end
     let y = u32::try_from(rect.y).unwrap_or(0);
 
     // Draw each column with interpolated color using fill_rect (1-column wide)
-    for col in 0..rect.w {
-        let t = if rect.w > 1 {
-            col as f32 / (rect.w - 1) as f32
-        } else {
-            0.0
-        };
-        let color = Theme::lerp(start, end, t);
+    for (col, color) in (0..rect.w).zip(Theme::gradient(start, end, rect.w)) {
         buffer.fill_rect(x + col, y, 1, rect.h, color);
     }
 }
@@ -8734,6 +8771,27 @@ mod tests {
         assert_eq!(style.bg, Some(theme.selection_bg));
     }
 
+    #[test]
+    fn test_styles_text_is_readable_in_every_theme() {
+        for theme in [
+            Theme::synthwave(),
+            Theme::paper_light(),
+            Theme::solarized(),
+            Theme::high_contrast(),
+        ] {
+            for style in [
+                Styles::header(&theme),
+                Styles::selection(&theme),
+                Styles::muted(&theme),
+                Styles::status_bar(&theme),
+                Styles::key_hint(&theme),
+            ] {
+                let (fg, bg) = (style.fg.unwrap(), style.bg.unwrap());
+                assert!(color::contrast_ratio(fg, bg) >= Styles::MIN_TEXT_CONTRAST);
+            }
+        }
+    }
+
     // ========================================================================
     // Render Pass Tests
     // ========================================================================
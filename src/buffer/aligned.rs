@@ -0,0 +1,333 @@
+//! Single-line text fitted to a fixed-width field.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::buffer::OptimizedBuffer;
+use crate::buffer::drawing::{draw_char_with_pool, draw_text, set_styled};
+use crate::cell::Cell;
+use crate::grapheme_pool::GraphemePool;
+use crate::style::Style;
+
+/// Horizontal placement of text narrower than its field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Where text wider than its field is cut.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Truncate {
+    /// Clip at the field edge, without an ellipsis.
+    None,
+    /// Keep the start: `Long te…`.
+    #[default]
+    End,
+    /// Keep the end: `…ng text`.
+    Start,
+    /// Keep both ends: `Lon…ext`.
+    Middle,
+}
+
+/// A cell-aligned piece of fitted text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Piece<'a> {
+    Text(&'a str, u32),
+    /// Stands in for a wide char cut by the truncation edge.
+    Space,
+}
+
+impl Piece<'_> {
+    const fn width(self) -> u32 {
+        match self {
+            Self::Text(_, width) => width,
+            Self::Space => 1,
+        }
+    }
+}
+
+fn graphemes(text: &str) -> Vec<Piece<'_>> {
+    text.graphemes(true)
+        .filter(|grapheme| !grapheme.chars().all(char::is_control))
+        .map(|grapheme| Piece::Text(grapheme, crate::unicode::display_width(grapheme) as u32))
+        .collect()
+}
+
+/// Take pieces from the front of `text` filling exactly `budget` columns,
+/// padding with spaces where a wide char doesn't fit.
+fn take<'a>(text: impl Iterator<Item = Piece<'a>>, budget: u32) -> Vec<Piece<'a>> {
+    let mut out = Vec::new();
+    let mut used = 0;
+    for piece in text {
+        if used + piece.width() > budget {
+            break;
+        }
+        used += piece.width();
+        out.push(piece);
+    }
+    out.extend((used..budget).map(|_| Piece::Space));
+    out
+}
+
+/// Fit `text` to at most `width` columns.
+fn fit<'a>(text: &'a str, width: u32, truncate: Truncate, ellipsis: &'a str) -> Vec<Piece<'a>> {
+    let pieces = graphemes(text);
+    let text_width: u32 = pieces.iter().map(|piece| piece.width()).sum();
+    if text_width <= width {
+        return pieces;
+    }
+
+    let ellipsis = graphemes(ellipsis);
+    let ellipsis_width: u32 = ellipsis.iter().map(|piece| piece.width()).sum();
+    let (ellipsis, budget) = match truncate {
+        Truncate::None => (Vec::new(), width),
+        _ if ellipsis_width > width => (Vec::new(), width),
+        _ => (ellipsis, width - ellipsis_width),
+    };
+
+    let prefix = |budget| take(pieces.iter().copied(), budget);
+    let suffix = |budget| {
+        let mut suffix = take(pieces.iter().rev().copied(), budget);
+        suffix.reverse();
+        suffix
+    };
+    match truncate {
+        Truncate::None | Truncate::End => [prefix(budget), ellipsis].concat(),
+        Truncate::Start => [ellipsis, suffix(budget)].concat(),
+        Truncate::Middle => {
+            let tail = budget / 2;
+            [prefix(budget - tail), ellipsis, suffix(tail)].concat()
+        }
+    }
+}
+
+/// Fit and align `text` in `width` columns, drawing graphemes with `draw`.
+#[allow(clippy::too_many_arguments)]
+fn draw_fitted(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    width: u32,
+    text: &str,
+    style: Style,
+    align: Align,
+    truncate: Truncate,
+    ellipsis: &str,
+    mut draw: impl FnMut(&mut OptimizedBuffer, u32, &str),
+) -> Range<u32> {
+    let pieces = fit(text, width, truncate, ellipsis);
+    let content_width: u32 = pieces.iter().map(|piece| piece.width()).sum();
+    let slack = width - content_width;
+    let start = x + match align {
+        Align::Left => 0,
+        Align::Center => slack / 2,
+        Align::Right => slack,
+    };
+
+    for col in (x..start).chain(start + content_width..x + width) {
+        set_styled(buffer, col, y, Cell::new(' ', style), style);
+    }
+    let mut col = start;
+    for piece in pieces {
+        match piece {
+            Piece::Text(grapheme, _) => draw(buffer, col, grapheme),
+            Piece::Space => set_styled(buffer, col, y, Cell::new(' ', style), style),
+        }
+        col += piece.width();
+    }
+    start..start + content_width
+}
+
+/// Draw `text` in a `width`-column field, returning the columns its content
+/// occupies.
+///
+/// Multi-codepoint graphemes get placeholder IDs; use
+/// [`draw_text_aligned_with_pool`] for emoji.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text_aligned(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    width: u32,
+    text: &str,
+    style: Style,
+    align: Align,
+    truncate: Truncate,
+    ellipsis: &str,
+) -> Range<u32> {
+    draw_fitted(
+        buffer,
+        x,
+        y,
+        width,
+        text,
+        style,
+        align,
+        truncate,
+        ellipsis,
+        |buffer, col, grapheme| draw_text(buffer, col, y, grapheme, style),
+    )
+}
+
+/// Like [`draw_text_aligned`], allocating graphemes from the pool.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text_aligned_with_pool(
+    buffer: &mut OptimizedBuffer,
+    pool: &mut GraphemePool,
+    x: u32,
+    y: u32,
+    width: u32,
+    text: &str,
+    style: Style,
+    align: Align,
+    truncate: Truncate,
+    ellipsis: &str,
+) -> Range<u32> {
+    draw_fitted(
+        buffer,
+        x,
+        y,
+        width,
+        text,
+        style,
+        align,
+        truncate,
+        ellipsis,
+        |buffer, col, grapheme| {
+            draw_char_with_pool(buffer, pool, col, y, grapheme, style);
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+    use crate::color::Rgba;
+
+    fn row(buffer: &OptimizedBuffer, pool: &GraphemePool, y: u32) -> String {
+        (0..buffer.width())
+            .filter_map(|x| match buffer.get(x, y).unwrap().content {
+                CellContent::Char(ch) => Some(ch.to_string()),
+                CellContent::Grapheme(id) => pool.get(id).map(str::to_string),
+                CellContent::Empty => Some("·".to_string()),
+                CellContent::Continuation => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_alignment_and_truncation_combinations() {
+        let aligns = [Align::Left, Align::Center, Align::Right];
+        let truncates = [
+            Truncate::None,
+            Truncate::End,
+            Truncate::Start,
+            Truncate::Middle,
+        ];
+        let mut out = String::new();
+        // Fits with odd slack; overflows so a wide char straddles each edge.
+        for text in ["日本語", "日本語のテキスト"] {
+            for align in aligns {
+                for truncate in truncates {
+                    let mut buffer = OptimizedBuffer::new(10, 1);
+                    let pool = GraphemePool::new();
+                    let range = draw_text_aligned(
+                        &mut buffer,
+                        1,
+                        0,
+                        8 + u32::from(text.chars().count() == 3),
+                        text,
+                        Style::NONE,
+                        align,
+                        truncate,
+                        "…",
+                    );
+                    out.push_str(&format!(
+                        "{:<6} {:<6} |{}| {range:?}\n",
+                        format!("{align:?}"),
+                        format!("{truncate:?}"),
+                        row(&buffer, &pool, 0)
+                    ));
+                }
+            }
+        }
+        insta::assert_snapshot!(out);
+    }
+
+    #[test]
+    fn test_padding_uses_style_background() {
+        let mut buffer = OptimizedBuffer::new(6, 1);
+        let style = Style::bg(Rgba::BLUE);
+        let range = draw_text_aligned(
+            &mut buffer,
+            0,
+            0,
+            6,
+            "ab",
+            style,
+            Align::Right,
+            Truncate::End,
+            "…",
+        );
+        assert_eq!(range, 4..6);
+        for x in 0..6 {
+            assert_eq!(buffer.get(x, 0).unwrap().bg, Rgba::BLUE);
+        }
+    }
+
+    #[test]
+    fn test_custom_and_oversized_ellipsis() {
+        let mut buffer = OptimizedBuffer::new(6, 2);
+        let pool = GraphemePool::new();
+        let long = "abcdefgh";
+        draw_text_aligned(
+            &mut buffer,
+            0,
+            0,
+            6,
+            long,
+            Style::NONE,
+            Align::Left,
+            Truncate::End,
+            "..",
+        );
+        draw_text_aligned(
+            &mut buffer,
+            0,
+            1,
+            2,
+            long,
+            Style::NONE,
+            Align::Left,
+            Truncate::End,
+            "...",
+        );
+        assert_eq!(row(&buffer, &pool, 0), "abcd..");
+        assert_eq!(row(&buffer, &pool, 1), "ab····");
+    }
+
+    #[test]
+    fn test_pool_variant_keeps_emoji() {
+        let mut buffer = OptimizedBuffer::new(8, 1);
+        let mut pool = GraphemePool::new();
+        let range = draw_text_aligned_with_pool(
+            &mut buffer,
+            &mut pool,
+            0,
+            0,
+            8,
+            "👨‍👩‍👧 family",
+            Style::NONE,
+            Align::Left,
+            Truncate::End,
+            "…",
+        );
+        assert_eq!(range, 0..8);
+        assert_eq!(row(&buffer, &pool, 0), "👨‍👩‍👧 fami…");
+    }
+}
@@ -0,0 +1,349 @@
+//! Large text drawn from an embedded block font.
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::style::Style;
+
+/// An embedded font for [`draw_big_text`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BigFont {
+    /// Glyphs 5 pixels tall and up to 3 wide, covering printable ASCII.
+    /// Narrow characters like `:` and `i` take fewer columns.
+    #[default]
+    Compact,
+}
+
+/// One glyph: its width in pixels and a bit row per pixel row, with the
+/// leftmost pixel in the highest of `width` bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Glyph {
+    width: u8,
+    rows: [u8; 5],
+}
+
+const fn g(width: u8, rows: [u8; 5]) -> Glyph {
+    Glyph { width, rows }
+}
+
+/// Drawn for characters the font doesn't cover.
+const PLACEHOLDER: Glyph = g(3, [0b111, 0b111, 0b111, 0b111, 0b111]);
+
+/// Glyphs for `' '..='~'`, in order.
+#[rustfmt::skip]
+const COMPACT: [Glyph; 95] = [
+    g(2, [0b00, 0b00, 0b00, 0b00, 0b00]),      // ' '
+    g(1, [0b1, 0b1, 0b1, 0b0, 0b1]),           // !
+    g(3, [0b101, 0b101, 0b000, 0b000, 0b000]), // "
+    g(3, [0b101, 0b111, 0b101, 0b111, 0b101]), // #
+    g(3, [0b011, 0b110, 0b010, 0b011, 0b110]), // $
+    g(3, [0b101, 0b001, 0b010, 0b100, 0b101]), // %
+    g(3, [0b010, 0b101, 0b010, 0b101, 0b011]), // &
+    g(1, [0b1, 0b1, 0b0, 0b0, 0b0]),           // '
+    g(2, [0b01, 0b10, 0b10, 0b10, 0b01]),      // (
+    g(2, [0b10, 0b01, 0b01, 0b01, 0b10]),      // )
+    g(3, [0b000, 0b101, 0b010, 0b101, 0b000]), // *
+    g(3, [0b000, 0b010, 0b111, 0b010, 0b000]), // +
+    g(2, [0b00, 0b00, 0b00, 0b01, 0b10]),      // ,
+    g(3, [0b000, 0b000, 0b111, 0b000, 0b000]), // -
+    g(1, [0b0, 0b0, 0b0, 0b0, 0b1]),           // .
+    g(3, [0b001, 0b001, 0b010, 0b100, 0b100]), // /
+    g(3, [0b111, 0b101, 0b101, 0b101, 0b111]), // 0
+    g(3, [0b010, 0b110, 0b010, 0b010, 0b111]), // 1
+    g(3, [0b111, 0b001, 0b111, 0b100, 0b111]), // 2
+    g(3, [0b111, 0b001, 0b111, 0b001, 0b111]), // 3
+    g(3, [0b101, 0b101, 0b111, 0b001, 0b001]), // 4
+    g(3, [0b111, 0b100, 0b111, 0b001, 0b111]), // 5
+    g(3, [0b111, 0b100, 0b111, 0b101, 0b111]), // 6
+    g(3, [0b111, 0b001, 0b001, 0b001, 0b001]), // 7
+    g(3, [0b111, 0b101, 0b111, 0b101, 0b111]), // 8
+    g(3, [0b111, 0b101, 0b111, 0b001, 0b111]), // 9
+    g(1, [0b0, 0b1, 0b0, 0b1, 0b0]),           // :
+    g(2, [0b00, 0b01, 0b00, 0b01, 0b10]),      // ;
+    g(3, [0b001, 0b010, 0b100, 0b010, 0b001]), // <
+    g(3, [0b000, 0b111, 0b000, 0b111, 0b000]), // =
+    g(3, [0b100, 0b010, 0b001, 0b010, 0b100]), // >
+    g(3, [0b111, 0b001, 0b011, 0b000, 0b010]), // ?
+    g(3, [0b010, 0b101, 0b111, 0b100, 0b011]), // @
+    g(3, [0b010, 0b101, 0b111, 0b101, 0b101]), // A
+    g(3, [0b110, 0b101, 0b110, 0b101, 0b110]), // B
+    g(3, [0b011, 0b100, 0b100, 0b100, 0b011]), // C
+    g(3, [0b110, 0b101, 0b101, 0b101, 0b110]), // D
+    g(3, [0b111, 0b100, 0b110, 0b100, 0b111]), // E
+    g(3, [0b111, 0b100, 0b110, 0b100, 0b100]), // F
+    g(3, [0b011, 0b100, 0b101, 0b101, 0b011]), // G
+    g(3, [0b101, 0b101, 0b111, 0b101, 0b101]), // H
+    g(3, [0b111, 0b010, 0b010, 0b010, 0b111]), // I
+    g(3, [0b001, 0b001, 0b001, 0b101, 0b010]), // J
+    g(3, [0b101, 0b101, 0b110, 0b101, 0b101]), // K
+    g(3, [0b100, 0b100, 0b100, 0b100, 0b111]), // L
+    g(3, [0b101, 0b111, 0b111, 0b101, 0b101]), // M
+    g(3, [0b110, 0b101, 0b101, 0b101, 0b101]), // N
+    g(3, [0b010, 0b101, 0b101, 0b101, 0b010]), // O
+    g(3, [0b110, 0b101, 0b110, 0b100, 0b100]), // P
+    g(3, [0b010, 0b101, 0b101, 0b110, 0b011]), // Q
+    g(3, [0b110, 0b101, 0b110, 0b101, 0b101]), // R
+    g(3, [0b011, 0b100, 0b010, 0b001, 0b110]), // S
+    g(3, [0b111, 0b010, 0b010, 0b010, 0b010]), // T
+    g(3, [0b101, 0b101, 0b101, 0b101, 0b111]), // U
+    g(3, [0b101, 0b101, 0b101, 0b101, 0b010]), // V
+    g(3, [0b101, 0b101, 0b111, 0b111, 0b101]), // W
+    g(3, [0b101, 0b101, 0b010, 0b101, 0b101]), // X
+    g(3, [0b101, 0b101, 0b010, 0b010, 0b010]), // Y
+    g(3, [0b111, 0b001, 0b010, 0b100, 0b111]), // Z
+    g(2, [0b11, 0b10, 0b10, 0b10, 0b11]),      // [
+    g(3, [0b100, 0b100, 0b010, 0b001, 0b001]), // \
+    g(2, [0b11, 0b01, 0b01, 0b01, 0b11]),      // ]
+    g(3, [0b010, 0b101, 0b000, 0b000, 0b000]), // ^
+    g(3, [0b000, 0b000, 0b000, 0b000, 0b111]), // _
+    g(2, [0b10, 0b01, 0b00, 0b00, 0b00]),      // `
+    g(3, [0b000, 0b011, 0b101, 0b101, 0b011]), // a
+    g(3, [0b100, 0b110, 0b101, 0b101, 0b110]), // b
+    g(3, [0b000, 0b011, 0b100, 0b100, 0b011]), // c
+    g(3, [0b001, 0b011, 0b101, 0b101, 0b011]), // d
+    g(3, [0b000, 0b010, 0b111, 0b100, 0b011]), // e
+    g(3, [0b011, 0b100, 0b110, 0b100, 0b100]), // f
+    g(3, [0b000, 0b011, 0b101, 0b011, 0b110]), // g
+    g(3, [0b100, 0b110, 0b101, 0b101, 0b101]), // h
+    g(1, [0b1, 0b0, 0b1, 0b1, 0b1]),           // i
+    g(2, [0b01, 0b00, 0b01, 0b01, 0b10]),      // j
+    g(3, [0b100, 0b101, 0b110, 0b101, 0b101]), // k
+    g(2, [0b10, 0b10, 0b10, 0b10, 0b01]),      // l
+    g(3, [0b000, 0b111, 0b111, 0b101, 0b101]), // m
+    g(3, [0b000, 0b110, 0b101, 0b101, 0b101]), // n
+    g(3, [0b000, 0b010, 0b101, 0b101, 0b010]), // o
+    g(3, [0b000, 0b110, 0b101, 0b110, 0b100]), // p
+    g(3, [0b000, 0b011, 0b101, 0b011, 0b001]), // q
+    g(3, [0b000, 0b011, 0b100, 0b100, 0b100]), // r
+    g(3, [0b000, 0b011, 0b110, 0b011, 0b110]), // s
+    g(3, [0b010, 0b111, 0b010, 0b010, 0b011]), // t
+    g(3, [0b000, 0b101, 0b101, 0b101, 0b011]), // u
+    g(3, [0b000, 0b101, 0b101, 0b101, 0b010]), // v
+    g(3, [0b000, 0b101, 0b101, 0b111, 0b111]), // w
+    g(3, [0b000, 0b101, 0b010, 0b010, 0b101]), // x
+    g(3, [0b101, 0b101, 0b011, 0b001, 0b110]), // y
+    g(3, [0b000, 0b111, 0b010, 0b100, 0b111]), // z
+    g(3, [0b011, 0b010, 0b110, 0b010, 0b011]), // {
+    g(1, [0b1, 0b1, 0b1, 0b1, 0b1]),           // |
+    g(3, [0b110, 0b010, 0b011, 0b010, 0b110]), // }
+    g(3, [0b000, 0b000, 0b011, 0b110, 0b000]), // ~
+];
+
+impl BigFont {
+    const fn height(self) -> u32 {
+        match self {
+            Self::Compact => 5,
+        }
+    }
+
+    fn glyph(self, ch: char) -> Glyph {
+        match self {
+            Self::Compact => (ch as usize)
+                .checked_sub(' ' as usize)
+                .and_then(|index| COMPACT.get(index))
+                .copied()
+                .unwrap_or(PLACEHOLDER),
+        }
+    }
+}
+
+/// Text rasterized to one bit per pixel, `scale` applied.
+struct Bitmap {
+    width: u32,
+    height: u32,
+    pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// Width in pixels of one line at scale 1, including one column between
+/// glyphs.
+fn line_width(line: &str, font: BigFont) -> u32 {
+    let glyphs: u32 = line.chars().map(|ch| u32::from(font.glyph(ch).width)).sum();
+    glyphs + (line.chars().count() as u32).saturating_sub(1)
+}
+
+fn rasterize(text: &str, font: BigFont, scale: u32) -> Bitmap {
+    let scale = scale.max(1);
+    let lines: Vec<&str> = text.split('\n').collect();
+    let line_height = font.height() * scale;
+    let width = lines
+        .iter()
+        .map(|line| line_width(line, font))
+        .max()
+        .unwrap_or(0)
+        * scale;
+    // One blank pixel row between lines.
+    let height = (lines.len() as u32 * (font.height() + 1) - 1) * scale;
+    let mut pixels = vec![false; width as usize * height as usize];
+
+    for (line_index, line) in (0..).zip(&lines) {
+        let top = line_index * (line_height + scale);
+        let mut left = 0;
+        for ch in line.chars() {
+            let glyph = font.glyph(ch);
+            for (row, bits) in (0..).zip(glyph.rows) {
+                for col in 0..u32::from(glyph.width) {
+                    if bits >> (u32::from(glyph.width) - 1 - col) & 1 == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        let y = top + row * scale + dy;
+                        let start = (y * width + left + col * scale) as usize;
+                        pixels[start..start + scale as usize].fill(true);
+                    }
+                }
+            }
+            left += (u32::from(glyph.width) + 1) * scale;
+        }
+    }
+    Bitmap {
+        width,
+        height,
+        pixels,
+    }
+}
+
+/// Size in cells of `text` drawn by [`draw_big_text`].
+///
+/// Each cell holds two pixel rows, so a line of the compact font at scale 1
+/// is 3 cells tall.
+#[must_use]
+pub fn big_text_size(text: &str, font: BigFont, scale: u32) -> (u32, u32) {
+    let bitmap = rasterize(text, font, scale);
+    (bitmap.width, bitmap.height.div_ceil(2))
+}
+
+/// Draw `text` in large block glyphs with its top-left corner at (`x`, `y`).
+///
+/// Glyph pixels are drawn with `█`, `▀`, and `▄` in `style`'s foreground;
+/// cells without any set pixels are left alone. `scale` repeats each pixel
+/// row and column, with 0 treated as 1. `\n` starts a new line. Characters
+/// the font lacks draw as a solid box. Cells are drawn like
+/// [`OptimizedBuffer::set_blended`], so the scissor clips big text that
+/// overflows its area.
+pub fn draw_big_text(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    text: &str,
+    style: Style,
+    font: BigFont,
+    scale: u32,
+) {
+    let bitmap = rasterize(text, font, scale);
+    for row in 0..bitmap.height.div_ceil(2) {
+        for col in 0..bitmap.width {
+            let top = bitmap.get(col, row * 2);
+            let bottom = bitmap.get(col, row * 2 + 1);
+            let ch = match (top, bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => continue,
+            };
+            buffer.set_blended(
+                x.saturating_add(col),
+                y.saturating_add(row),
+                Cell::new(ch, style),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::ClipRect;
+    use crate::cell::CellContent;
+
+    fn render(text: &str, scale: u32) -> String {
+        let (width, height) = big_text_size(text, BigFont::Compact, scale);
+        let mut buffer = OptimizedBuffer::new(width, height);
+        draw_big_text(
+            &mut buffer,
+            0,
+            0,
+            text,
+            Style::NONE,
+            BigFont::Compact,
+            scale,
+        );
+        let mut out = format!("{text:?} scale {scale}: {width}x{height}\n");
+        for y in 0..height {
+            for x in 0..width {
+                match buffer.get(x, y).unwrap().content {
+                    CellContent::Char(ch) => out.push(ch),
+                    _ => out.push('·'),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_big_text_clock_and_word() {
+        let out: Vec<String> = [("12:34", 1), ("12:34", 2), ("OK", 1), ("OK", 2)]
+            .into_iter()
+            .map(|(text, scale)| render(text, scale))
+            .collect();
+        insta::assert_snapshot!(out.join("\n"));
+    }
+
+    #[test]
+    fn test_big_text_size_and_kerning() {
+        // "1" and "2" are 3 wide, ":" 1 wide, with a column between each.
+        assert_eq!(big_text_size("12:", BigFont::Compact, 1), (9, 3));
+        assert_eq!(big_text_size("12:", BigFont::Compact, 3), (27, 8));
+        assert_eq!(big_text_size("", BigFont::Compact, 1), (0, 3));
+        // Lines stack with a blank pixel row between them.
+        assert_eq!(big_text_size("ab\nc", BigFont::Compact, 1), (7, 6));
+        // Scale 0 draws like 1.
+        assert_eq!(
+            big_text_size("OK", BigFont::Compact, 0),
+            big_text_size("OK", BigFont::Compact, 1)
+        );
+        // Characters outside the font draw a solid box.
+        for text in ["é", "\u{7f}"] {
+            assert!(render(text, 1).ends_with("3x3\n███\n███\n▀▀▀\n"));
+        }
+    }
+
+    #[test]
+    fn test_big_text_clips_to_scissor() {
+        let draw = |scissor: Option<ClipRect>| {
+            let mut buffer = OptimizedBuffer::new(20, 6);
+            if let Some(scissor) = scissor {
+                buffer.push_scissor(scissor);
+            }
+            draw_big_text(&mut buffer, 0, 0, "88", Style::NONE, BigFont::Compact, 2);
+            buffer
+        };
+        let full = draw(None);
+        let clipped = draw(Some(ClipRect::new(0, 0, 5, 2)));
+        for y in 0..6 {
+            for x in 0..20 {
+                let drawn = |buffer: &OptimizedBuffer| !buffer.get(x, y).unwrap().is_empty();
+                assert_eq!(
+                    drawn(&clipped),
+                    drawn(&full) && x < 5 && y < 2,
+                    "({x}, {y})"
+                );
+            }
+        }
+        // Big text overflowing the buffer is cut off without panicking.
+        draw_big_text(
+            &mut full.clone(),
+            15,
+            4,
+            "WIDE",
+            Style::NONE,
+            BigFont::Compact,
+            3,
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Braille dot canvases with 2x4 dots per cell.
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::style::Style;
+
+/// The first braille code point, with no dots raised.
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Dot bits by position in a cell, indexed `[row][col]`.
+///
+/// Unicode numbers the dots 1-2-3 down the left column and 4-5-6 down the
+/// right, with 7 and 8 added below; dot N is bit N - 1.
+const DOT_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The bit for the dot at (`col`, `row`) of a cell, with `col < 2` and
+/// `row < 4`.
+pub(super) const fn dot_bit(col: u32, row: u32) -> u8 {
+    DOT_BITS[row as usize][col as usize]
+}
+
+/// The braille character with the given dots raised.
+pub(super) fn braille_char(bits: u8) -> char {
+    char::from_u32(BRAILLE_BLANK + u32::from(bits)).unwrap_or(' ')
+}
+
+/// A grid of braille cells addressed by dot.
+///
+/// Dot coordinates run from (0, 0) at the top-left to
+/// ([`Self::dot_width`] - 1, [`Self::dot_height`] - 1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrailleCanvas {
+    width: u32,
+    height: u32,
+    /// Raised dot bits per cell, row-major.
+    cells: Vec<u8>,
+}
+
+impl BrailleCanvas {
+    /// Create a blank canvas `width` by `height` cells.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width as usize * height as usize],
+        }
+    }
+
+    /// Width in cells.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in cells.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Width in dots.
+    #[must_use]
+    pub const fn dot_width(&self) -> u32 {
+        self.width * 2
+    }
+
+    /// Height in dots.
+    #[must_use]
+    pub const fn dot_height(&self) -> u32 {
+        self.height * 4
+    }
+
+    /// Lower every dot.
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+    }
+
+    /// The cell index and bit for a dot, if it's on the canvas.
+    fn locate(&self, x: i32, y: i32) -> Option<(usize, u8)> {
+        let x = u32::try_from(x).ok().filter(|&x| x < self.dot_width())?;
+        let y = u32::try_from(y).ok().filter(|&y| y < self.dot_height())?;
+        let index = (y / 4) as usize * self.width as usize + (x / 2) as usize;
+        Some((index, dot_bit(x % 2, y % 4)))
+    }
+
+    /// Raise the dot at (`x`, `y`). Dots off the canvas are ignored.
+    pub fn set_point(&mut self, x: i32, y: i32) {
+        if let Some((index, bit)) = self.locate(x, y) {
+            self.cells[index] |= bit;
+        }
+    }
+
+    /// Whether the dot at (`x`, `y`) is raised.
+    #[must_use]
+    pub fn is_set(&self, x: i32, y: i32) -> bool {
+        self.locate(x, y)
+            .is_some_and(|(index, bit)| self.cells[index] & bit != 0)
+    }
+
+    /// The braille character for a cell, or `None` outside the canvas.
+    #[must_use]
+    pub fn char_at(&self, col: u32, row: u32) -> Option<char> {
+        if col >= self.width || row >= self.height {
+            return None;
+        }
+        Some(braille_char(
+            self.cells[row as usize * self.width as usize + col as usize],
+        ))
+    }
+
+    /// Raise the dots on the line between two dots, inclusive.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        // Bresenham, in i64 so the deltas can't overflow.
+        let (mut x, mut y) = (i64::from(x0), i64::from(y0));
+        let (x1, y1) = (i64::from(x1), i64::from(y1));
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.set_point(x as i32, y as i32);
+            if x == x1 && y == y1 {
+                return;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Connect dot-space points with lines.
+    ///
+    /// Points are rounded to the nearest dot. Non-finite points break the
+    /// line, leaving a gap.
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)]) {
+        let mut last = None;
+        for &(x, y) in points {
+            if !x.is_finite() || !y.is_finite() {
+                last = None;
+                continue;
+            }
+            let point = (x.round() as i32, y.round() as i32);
+            match last {
+                Some((x0, y0)) => self.draw_line(x0, y0, point.0, point.1),
+                None => self.set_point(point.0, point.1),
+            }
+            last = Some(point);
+        }
+    }
+
+    /// Map a data-space point onto the canvas's dots.
+    ///
+    /// `x_bounds` spans the canvas from left to right and `y_bounds` from
+    /// bottom to top, so larger y values plot higher. Bounds whose ends are
+    /// equal, or not finite, map to the middle of the canvas.
+    #[must_use]
+    pub fn data_to_dots(
+        &self,
+        (x, y): (f32, f32),
+        x_bounds: (f32, f32),
+        y_bounds: (f32, f32),
+    ) -> (f32, f32) {
+        let scale = |value: f32, (min, max): (f32, f32), dots: u32| {
+            let last = dots.saturating_sub(1) as f32;
+            let range = max - min;
+            if range.abs() > 0.0 && range.is_finite() {
+                (value - min) / range * last
+            } else {
+                last / 2.0
+            }
+        };
+        let y = scale(y, y_bounds, self.dot_height());
+        (
+            scale(x, x_bounds, self.dot_width()),
+            self.dot_height().saturating_sub(1) as f32 - y,
+        )
+    }
+
+    /// Plot data-space points as a polyline; see [`Self::data_to_dots`].
+    pub fn plot(&mut self, data: &[(f32, f32)], x_bounds: (f32, f32), y_bounds: (f32, f32)) {
+        let points: Vec<(f32, f32)> = data
+            .iter()
+            .map(|&point| self.data_to_dots(point, x_bounds, y_bounds))
+            .collect();
+        self.draw_polyline(&points);
+    }
+
+    /// Draw the canvas with its top-left cell at (`x`, `y`).
+    ///
+    /// Cells without raised dots are skipped, leaving what's underneath.
+    /// Cells are drawn like [`OptimizedBuffer::set_blended`].
+    pub fn blit(&self, buffer: &mut OptimizedBuffer, x: u32, y: u32, style: Style) {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let bits = self.cells[row as usize * self.width as usize + col as usize];
+                if bits != 0 {
+                    let cell = Cell::new(braille_char(bits), style);
+                    buffer.set_blended(x.saturating_add(col), y.saturating_add(row), cell);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+    use crate::color::Rgba;
+
+    fn rows(canvas: &BrailleCanvas) -> String {
+        let mut out = String::new();
+        for row in 0..canvas.height() {
+            out.extend((0..canvas.width()).filter_map(|col| canvas.char_at(col, row)));
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_dot_bits_follow_unicode_numbering() {
+        // (x, y, dot number)
+        let dots = [
+            (0, 0, 1),
+            (0, 1, 2),
+            (0, 2, 3),
+            (1, 0, 4),
+            (1, 1, 5),
+            (1, 2, 6),
+            (0, 3, 7),
+            (1, 3, 8),
+        ];
+        let mut all = BrailleCanvas::new(1, 1);
+        for (x, y, dot) in dots {
+            let mut canvas = BrailleCanvas::new(1, 1);
+            canvas.set_point(x, y);
+            let expected = char::from_u32(0x2800 + (1 << (dot - 1))).unwrap();
+            assert_eq!(canvas.char_at(0, 0), Some(expected), "dot {dot}");
+            all.set_point(x, y);
+        }
+        assert_eq!(all.char_at(0, 0), Some('⣿'));
+
+        // Dots accumulate per cell; the second cell starts at x = 2.
+        let mut canvas = BrailleCanvas::new(2, 2);
+        canvas.set_point(0, 0);
+        canvas.set_point(1, 3);
+        canvas.set_point(2, 4);
+        canvas.set_point(-1, 0);
+        canvas.set_point(4, 0);
+        assert_eq!(rows(&canvas), "⢁⠀\n⠀⠁\n");
+        assert!(canvas.is_set(2, 4));
+        assert!(!canvas.is_set(3, 4));
+
+        canvas.clear();
+        assert_eq!(rows(&canvas), "⠀⠀\n⠀⠀\n");
+    }
+
+    #[test]
+    fn test_draw_line_is_symmetric() {
+        let dots = |canvas: &BrailleCanvas| -> Vec<(i32, i32)> {
+            (0..4)
+                .flat_map(|y| (0..4).map(move |x| (x, y)))
+                .filter(|&(x, y)| canvas.is_set(x, y))
+                .collect()
+        };
+        for ((x0, y0, x1, y1), expected) in [
+            ((0, 0, 3, 1), vec![(0, 0), (1, 0), (2, 1), (3, 1)]),
+            ((0, 0, 1, 3), vec![(0, 0), (0, 1), (1, 2), (1, 3)]),
+        ] {
+            let mut forward = BrailleCanvas::new(2, 1);
+            forward.draw_line(x0, y0, x1, y1);
+            let mut backward = BrailleCanvas::new(2, 1);
+            backward.draw_line(x1, y1, x0, y0);
+            assert_eq!(dots(&forward), expected);
+            assert_eq!(forward, backward);
+        }
+    }
+
+    #[test]
+    fn test_sine_wave_plot() {
+        let mut canvas = BrailleCanvas::new(60, 20);
+        let data: Vec<(f32, f32)> = (0..=240)
+            .map(|i| {
+                let x = i as f32 / 240.0 * std::f32::consts::TAU * 2.0;
+                (x, x.sin())
+            })
+            .collect();
+        canvas.plot(&data, (0.0, std::f32::consts::TAU * 2.0), (-1.0, 1.0));
+        insta::assert_snapshot!(rows(&canvas));
+
+        // Crests and troughs reach the top and bottom rows of dots.
+        assert!((0..canvas.dot_width() as i32).any(|x| canvas.is_set(x, 0)));
+        assert!((0..canvas.dot_width() as i32).any(|x| canvas.is_set(x, 79)));
+
+        let mut buffer = OptimizedBuffer::new(62, 20);
+        canvas.blit(&mut buffer, 1, 0, Style::fg(Rgba::GREEN));
+        for y in 0..20 {
+            // Blank cells leave the buffer alone.
+            assert_eq!(buffer.get(0, y).unwrap().content, CellContent::Empty);
+            for x in 1..61 {
+                let cell = buffer.get(x, y).unwrap();
+                match canvas.char_at(x - 1, y) {
+                    Some('⠀') => assert_eq!(cell.content, CellContent::Empty),
+                    ch => {
+                        assert_eq!(Some(cell.content), ch.map(CellContent::Char));
+                        assert_eq!(cell.fg, Rgba::GREEN);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Row-level change summaries between two buffers.
+
+use std::ops::Range;
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::renderer::BufferDiff;
+
+/// Changed columns of one row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowDiff {
+    pub y: u32,
+    /// Sorted, non-overlapping, non-adjacent column ranges.
+    pub ranges: Vec<Range<u32>>,
+}
+
+/// What changed between two buffers, row by row.
+///
+/// Ranges are widened so that a wide character is never split: a range that
+/// touches any cell of a wide character covers all of it, matching the cells
+/// the renderer rewrites for the same change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BufferDiffSummary {
+    /// Rows with at least one changed cell, in ascending order.
+    pub rows: Vec<RowDiff>,
+    /// Number of cells covered by all ranges.
+    pub changed_cells: usize,
+    /// The buffers differ in size; every row of the new buffer is reported
+    /// as fully changed.
+    pub size_changed: bool,
+}
+
+impl BufferDiffSummary {
+    /// Whether the buffers are identical.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && !self.size_changed
+    }
+
+    /// Indices of the changed rows.
+    pub fn changed_rows(&self) -> impl Iterator<Item = u32> + '_ {
+        self.rows.iter().map(|row| row.y)
+    }
+
+    /// Changed column ranges of row `y`, empty if it is unchanged.
+    #[must_use]
+    pub fn row(&self, y: u32) -> &[Range<u32>] {
+        self.rows
+            .binary_search_by_key(&y, |row| row.y)
+            .map_or(&[], |i| &self.rows[i].ranges)
+    }
+}
+
+/// Compare `old` against `new` and summarize which rows and columns differ.
+///
+/// Buffers of different sizes don't panic; every row of `new` is reported
+/// as changed across its full width.
+///
+/// # Example
+///
+/// ```
+/// use opentui_rust::buffer::diff;
+/// use opentui_rust::{OptimizedBuffer, Style};
+///
+/// let old = OptimizedBuffer::new(20, 4);
+/// let mut new = old.clone();
+/// new.draw_text(3, 2, "hi", Style::NONE);
+///
+/// let summary = diff(&old, &new);
+/// assert_eq!(summary.changed_rows().collect::<Vec<_>>(), [2]);
+/// assert_eq!(summary.row(2), [3..5]);
+/// assert_eq!(summary.changed_cells, 2);
+/// ```
+#[must_use]
+pub fn diff(old: &OptimizedBuffer, new: &OptimizedBuffer) -> BufferDiffSummary {
+    let Ok(cell_diff) = BufferDiff::try_compute(old, new) else {
+        let (width, height) = new.size();
+        let full = 0..width;
+        return BufferDiffSummary {
+            rows: (0..height)
+                .map(|y| RowDiff {
+                    y,
+                    ranges: vec![full.clone()],
+                })
+                .collect(),
+            changed_cells: width as usize * height as usize,
+            size_changed: true,
+        };
+    };
+
+    let width = new.width() as usize;
+    let mut summary = BufferDiffSummary::default();
+    for region in &cell_diff.dirty_regions {
+        let start = region.y as usize * width;
+        let rows = (
+            &old.cells()[start..start + width],
+            &new.cells()[start..start + width],
+        );
+        let range = widen(rows, region.x..region.x + region.width);
+
+        match summary.rows.last_mut() {
+            Some(row) if row.y == region.y => match row.ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => row.ranges.push(range),
+            },
+            _ => summary.rows.push(RowDiff {
+                y: region.y,
+                ranges: vec![range],
+            }),
+        }
+    }
+    summary.changed_cells = summary
+        .rows
+        .iter()
+        .flat_map(|row| &row.ranges)
+        .map(ExactSizeIterator::len)
+        .sum();
+    summary
+}
+
+/// Widen `range` so it starts at the head of a wide character and ends
+/// after its last continuation cell, in either buffer's row.
+fn widen((old, new): (&[Cell], &[Cell]), range: Range<u32>) -> Range<u32> {
+    let continues = |x: u32| old[x as usize].is_continuation() || new[x as usize].is_continuation();
+    let mut start = range.start;
+    while start > 0 && continues(start) {
+        start -= 1;
+    }
+    let mut end = range.end;
+    for x in start..range.end {
+        let cell_width = old[x as usize]
+            .display_width()
+            .max(new[x as usize].display_width());
+        end = end.max(x + cell_width as u32);
+    }
+    while (end as usize) < new.len() && continues(end) {
+        end += 1;
+    }
+    start..end.min(new.len() as u32)
+}
+
+#[cfg(test)]
+// Row ranges are compared as lists, usually of one range.
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+    use crate::color::Rgba;
+    use crate::style::Style;
+
+    #[test]
+    fn test_identical_buffers() {
+        let mut buffer = OptimizedBuffer::new(10, 3);
+        buffer.draw_text(0, 1, "same", Style::NONE);
+        let summary = diff(&buffer, &buffer.clone());
+        assert!(summary.is_empty());
+        assert_eq!(summary.changed_cells, 0);
+        assert_eq!(summary.changed_rows().count(), 0);
+    }
+
+    #[test]
+    fn test_single_cell_change() {
+        let old = OptimizedBuffer::new(10, 3);
+        let mut new = old.clone();
+        new.set(7, 2, Cell::new('x', Style::NONE));
+
+        let summary = diff(&old, &new);
+        assert!(!summary.is_empty());
+        assert!(!summary.size_changed);
+        assert_eq!(
+            summary.rows,
+            [RowDiff {
+                y: 2,
+                ranges: vec![7..8],
+            }]
+        );
+        assert_eq!(summary.changed_cells, 1);
+        assert!(summary.row(1).is_empty());
+    }
+
+    #[test]
+    fn test_separate_ranges_in_one_row() {
+        let old = OptimizedBuffer::new(20, 1);
+        let mut new = old.clone();
+        new.draw_text(1, 0, "ab", Style::NONE);
+        new.draw_text(10, 0, "c", Style::NONE);
+
+        let summary = diff(&old, &new);
+        assert_eq!(summary.row(0), [1..3, 10..11]);
+        assert_eq!(summary.changed_cells, 3);
+    }
+
+    #[test]
+    fn test_dimension_mismatch_is_full_change() {
+        let old = OptimizedBuffer::new(10, 3);
+        let new = OptimizedBuffer::new(12, 2);
+
+        let summary = diff(&old, &new);
+        assert!(summary.size_changed);
+        assert!(!summary.is_empty());
+        assert_eq!(summary.changed_rows().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(summary.row(1), [0..12]);
+        assert_eq!(summary.changed_cells, 24);
+    }
+
+    #[test]
+    fn test_wide_char_change_covers_whole_glyph() {
+        let mut old = OptimizedBuffer::new(10, 1);
+        old.draw_text(2, 0, "世", Style::NONE);
+
+        // Only the head's style changes; its continuation stays the same.
+        let mut new = old.clone();
+        new.draw_text(2, 0, "世", Style::bold());
+        assert_eq!(diff(&old, &new).row(0), [2..4]);
+
+        // A narrow char over the continuation changes only that cell in the
+        // grid, but the renderer rewrites the wide char it breaks.
+        let mut new = old.clone();
+        new.set(3, 0, Cell::new('x', Style::NONE));
+        let summary = diff(&old, &new);
+        assert_eq!(summary.row(0), [2..4]);
+        assert_eq!(summary.changed_cells, 2);
+    }
+
+    #[test]
+    fn test_wide_char_ranges_merge() {
+        let old = OptimizedBuffer::new(10, 1);
+        let mut new = old.clone();
+        new.draw_text(0, 0, "a世b", Style::NONE);
+        assert_eq!(diff(&old, &new).row(0), [0..4]);
+    }
+
+    #[test]
+    fn test_default_colors_differ_from_concrete_colors() {
+        let mut old = OptimizedBuffer::new(10, 1);
+        old.clear(Rgba::TRANSPARENT);
+        let mut new = old.clone();
+        new.set(4, 0, Cell::clear_default());
+        assert_eq!(diff(&old, &new).row(0), [4..5]);
+
+        let mut old = new.clone();
+        old.set(4, 0, Cell::clear(Rgba::DEFAULT_FG));
+        assert_eq!(diff(&old, &new).row(0), [4..5]);
+    }
+}
@@ -1,21 +1,80 @@
-//! Text and box drawing operations.
+//! Text, box, and progress drawing operations.
 
 use crate::buffer::OptimizedBuffer;
+use crate::buffer::lines::set_line_glyph;
 use crate::cell::{Cell, CellContent};
 use crate::color::Rgba;
 use crate::grapheme_pool::GraphemePool;
-use crate::style::Style;
+use crate::renderer::Rect;
+use crate::style::{Style, TextAttributes};
+use crate::unicode::is_ascii_only_fast;
 use unicode_segmentation::UnicodeSegmentation;
 
-/// Box drawing style with corner and edge characters.
-#[derive(Clone, Debug)]
-pub struct BoxStyle {
+/// The eight glyphs making up a box border.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxChars {
     pub top_left: char,
     pub top_right: char,
     pub bottom_left: char,
     pub bottom_right: char,
-    pub horizontal: char,
-    pub vertical: char,
+    pub top: char,
+    pub bottom: char,
+    pub left: char,
+    pub right: char,
+}
+
+impl BoxChars {
+    /// `┌─┐` single-line glyphs.
+    pub const SINGLE: Self = Self::uniform(['┌', '┐', '└', '┘'], '─', '│');
+    /// `╔═╗` double-line glyphs.
+    pub const DOUBLE: Self = Self::uniform(['╔', '╗', '╚', '╝'], '═', '║');
+    /// `╭─╮` rounded-corner glyphs.
+    pub const ROUNDED: Self = Self::uniform(['╭', '╮', '╰', '╯'], '─', '│');
+    /// `┏━┓` heavy (bold) glyphs.
+    pub const HEAVY: Self = Self::uniform(['┏', '┓', '┗', '┛'], '━', '┃');
+    /// `+-+` ASCII glyphs (work in all terminals).
+    pub const ASCII: Self = Self::uniform(['+', '+', '+', '+'], '-', '|');
+
+    /// Glyphs with the same character on opposite edges.
+    ///
+    /// Corners are given as `[top_left, top_right, bottom_left, bottom_right]`.
+    #[must_use]
+    pub const fn uniform(corners: [char; 4], horizontal: char, vertical: char) -> Self {
+        let [top_left, top_right, bottom_left, bottom_right] = corners;
+        Self {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            top: horizontal,
+            bottom: horizontal,
+            left: vertical,
+            right: vertical,
+        }
+    }
+
+    /// These glyphs with each box-drawing character replaced by its ASCII
+    /// stand-in (`+`, `-`, `|`).
+    #[must_use]
+    pub fn into_ascii(self) -> Self {
+        let ascii = |ch: char| crate::ansi::charset::ascii_fallback(ch).unwrap_or(ch);
+        Self {
+            top_left: ascii(self.top_left),
+            top_right: ascii(self.top_right),
+            bottom_left: ascii(self.bottom_left),
+            bottom_right: ascii(self.bottom_right),
+            top: ascii(self.top),
+            bottom: ascii(self.bottom),
+            left: ascii(self.left),
+            right: ascii(self.right),
+        }
+    }
+}
+
+/// Box drawing style: border glyphs plus the style they're drawn in.
+#[derive(Clone, Debug)]
+pub struct BoxStyle {
+    pub chars: BoxChars,
     pub style: Style,
 }
 
@@ -48,14 +107,43 @@ pub enum TitleAlign {
     Right,
 }
 
+/// Drop shadow drawn one cell right of and below a box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowOptions {
+    /// Blended over whatever is under the shadow; use a translucent color.
+    pub color: Rgba,
+}
+
+impl Default for ShadowOptions {
+    fn default() -> Self {
+        Self {
+            color: Rgba::BLACK.with_alpha(0.5),
+        }
+    }
+}
+
 /// Extended box drawing options.
 #[derive(Clone, Debug)]
 pub struct BoxOptions {
     pub style: BoxStyle,
     pub sides: BoxSides,
+    /// Interior background, blended like [`OptimizedBuffer::fill_rect`].
     pub fill: Option<Rgba>,
+    pub shadow: Option<ShadowOptions>,
+    /// Drawn over the top edge; truncated with `…` if it doesn't fit.
+    ///
+    /// Shorthand for a single span in the border style; ignored when
+    /// `title_spans` is non-empty.
     pub title: Option<String>,
+    /// Styled pieces of the top title, each merged over the border style.
+    pub title_spans: Vec<(String, Style)>,
     pub title_align: TitleAlign,
+    /// A second label on the top edge, such as a `3 errors` badge, kept one
+    /// border cell away from the title. The title wins when both don't fit:
+    /// this one is truncated or dropped.
+    pub secondary_title: Option<(Vec<(String, Style)>, TitleAlign)>,
+    /// Drawn over the bottom edge, like the title.
+    pub bottom_title: Option<(Vec<(String, Style)>, TitleAlign)>,
 }
 
 impl BoxOptions {
@@ -65,81 +153,58 @@ impl BoxOptions {
             style,
             sides: BoxSides::default(),
             fill: None,
+            shadow: None,
             title: None,
+            title_spans: Vec::new(),
             title_align: TitleAlign::Left,
+            secondary_title: None,
+            bottom_title: None,
         }
     }
 }
 
 impl BoxStyle {
+    /// Box drawn with custom glyphs.
+    #[must_use]
+    pub const fn custom(chars: BoxChars, style: Style) -> Self {
+        Self { chars, style }
+    }
+
     /// Single-line box drawing characters.
     #[must_use]
-    pub fn single(style: Style) -> Self {
-        Self {
-            top_left: '┌',
-            top_right: '┐',
-            bottom_left: '└',
-            bottom_right: '┘',
-            horizontal: '─',
-            vertical: '│',
-            style,
-        }
+    pub const fn single(style: Style) -> Self {
+        Self::custom(BoxChars::SINGLE, style)
     }
 
     /// Double-line box drawing characters.
     #[must_use]
-    pub fn double(style: Style) -> Self {
-        Self {
-            top_left: '╔',
-            top_right: '╗',
-            bottom_left: '╚',
-            bottom_right: '╝',
-            horizontal: '═',
-            vertical: '║',
-            style,
-        }
+    pub const fn double(style: Style) -> Self {
+        Self::custom(BoxChars::DOUBLE, style)
     }
 
     /// Rounded corner box drawing characters.
     #[must_use]
-    pub fn rounded(style: Style) -> Self {
-        Self {
-            top_left: '╭',
-            top_right: '╮',
-            bottom_left: '╰',
-            bottom_right: '╯',
-            horizontal: '─',
-            vertical: '│',
-            style,
-        }
+    pub const fn rounded(style: Style) -> Self {
+        Self::custom(BoxChars::ROUNDED, style)
     }
 
     /// Heavy (bold) box drawing characters.
     #[must_use]
-    pub fn heavy(style: Style) -> Self {
-        Self {
-            top_left: '┏',
-            top_right: '┓',
-            bottom_left: '┗',
-            bottom_right: '┛',
-            horizontal: '━',
-            vertical: '┃',
-            style,
-        }
+    pub const fn heavy(style: Style) -> Self {
+        Self::custom(BoxChars::HEAVY, style)
+    }
+
+    /// This box with every glyph replaced by its ASCII stand-in (`+`, `-`,
+    /// `|`), for terminals that can't draw lines at all.
+    #[must_use]
+    pub fn into_ascii(self) -> Self {
+        Self::custom(self.chars.into_ascii(), self.style)
     }
 
     /// ASCII box drawing characters (works in all terminals).
     #[must_use]
-    pub fn ascii(style: Style) -> Self {
-        Self {
-            top_left: '+',
-            top_right: '+',
-            bottom_left: '+',
-            bottom_right: '+',
-            horizontal: '-',
-            vertical: '|',
-            style,
-        }
+    pub const fn ascii(style: Style) -> Self {
+        Self::custom(BoxChars::ASCII, style)
     }
 }
 
@@ -149,6 +214,107 @@ impl Default for BoxStyle {
     }
 }
 
+/// Progress bar appearance.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProgressStyle {
+    /// Style of the filled part; its foreground colors the bar.
+    pub filled: Style,
+    /// Style of the unfilled part, drawn as spaces.
+    pub empty: Style,
+    /// Draw the percentage centered over the bar.
+    pub show_percent: bool,
+}
+
+impl ProgressStyle {
+    #[must_use]
+    pub const fn new(filled: Style, empty: Style) -> Self {
+        Self {
+            filled,
+            empty,
+            show_percent: false,
+        }
+    }
+
+    /// Draw the percentage centered over the bar.
+    #[must_use]
+    pub const fn with_percent(mut self) -> Self {
+        self.show_percent = true;
+        self
+    }
+}
+
+/// Sparkline appearance and scaling.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SparkStyle {
+    pub style: Style,
+    /// Values mapped to the lowest and highest bar; `None` scales to the
+    /// samples' own range.
+    pub bounds: Option<(f32, f32)>,
+}
+
+/// Bordered progress bar appearance.
+#[derive(Clone, Debug, Default)]
+pub struct GaugeStyle {
+    pub border: BoxStyle,
+    /// The bar filling the interior; the percentage goes on its middle row.
+    pub bar: ProgressStyle,
+}
+
+/// Set `cell` with [`OptimizedBuffer::set_blended`], then put back the
+/// colors `style` preserves from the cell that was there.
+///
+/// Preserved colors are restored exactly, so opacity and blending only
+/// affect the components the style replaces.
+pub fn set_styled(buffer: &mut OptimizedBuffer, x: u32, y: u32, cell: Cell, style: Style) {
+    let before = preserved_cell(buffer, x, y, style);
+    buffer.set_blended(x, y, cell);
+    restore_preserved(buffer, x, y, style, before);
+}
+
+/// [`set_styled`] with [`OptimizedBuffer::set_blended_with_pool`].
+pub fn set_styled_with_pool(
+    buffer: &mut OptimizedBuffer,
+    pool: &mut GraphemePool,
+    x: u32,
+    y: u32,
+    cell: Cell,
+    style: Style,
+) {
+    let before = preserved_cell(buffer, x, y, style);
+    buffer.set_blended_with_pool(pool, x, y, cell);
+    restore_preserved(buffer, x, y, style, before);
+}
+
+/// The cell at `(x, y)`, if `style` preserves any of its colors.
+fn preserved_cell(buffer: &OptimizedBuffer, x: u32, y: u32, style: Style) -> Option<Cell> {
+    if style.preserves_colors() {
+        buffer.get(x, y).copied()
+    } else {
+        None
+    }
+}
+
+fn restore_preserved(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    style: Style,
+    before: Option<Cell>,
+) {
+    let Some(before) = before else {
+        return;
+    };
+    // A clipped cell is unchanged, so restoring it is a no-op.
+    if let Some(cell) = buffer.get_mut(x, y) {
+        if style.preserve_fg {
+            cell.fg = before.fg;
+        }
+        if style.preserve_bg {
+            cell.bg = before.bg;
+        }
+    }
+}
+
 /// Draw text at position, handling grapheme clusters and wide characters.
 ///
 /// Newlines (`\n`) advance to the next row, resetting to the starting X column.
@@ -157,11 +323,38 @@ impl Default for BoxStyle {
 /// **Note:** Multi-codepoint graphemes are stored with placeholder IDs.
 /// For proper grapheme pool integration, use [`draw_text_with_pool`].
 pub fn draw_text(buffer: &mut OptimizedBuffer, x: u32, y: u32, text: &str, style: Style) {
+    // Fastest path: printable ASCII on one row, the bulk of real UI labels.
+    // Control characters (newlines included) take the general path.
+    if is_ascii_only_fast(text)
+        && !text.bytes().any(|byte| byte.is_ascii_control())
+        && !style.preserves_colors()
+    {
+        let template = Cell {
+            content: CellContent::Empty,
+            fg: style.fg.unwrap_or(Rgba::WHITE),
+            bg: style.bg.unwrap_or(Rgba::TRANSPARENT),
+            attributes: style.attributes,
+            underline_style: style.underline_style,
+            underline_color: style.underline_color,
+        };
+        buffer.set_ascii_run_blended(x, y, text.as_bytes(), template);
+        return;
+    }
+    draw_text_general(buffer, x, y, text, style);
+}
+
+/// [`draw_text`] for text that may contain control characters or Unicode.
+fn draw_text_general(buffer: &mut OptimizedBuffer, x: u32, y: u32, text: &str, style: Style) {
+    #[cfg(test)]
+    tests::GENERAL_PATH_CALLS.with(|calls| calls.set(calls.get() + 1));
+
     let mut col = x;
     let mut row = y;
     let fg = style.fg.unwrap_or(Rgba::WHITE);
     let bg = style.bg.unwrap_or(Rgba::TRANSPARENT);
     let attrs = style.attributes;
+    let underline_style = style.underline_style;
+    let underline_color = style.underline_color;
 
     // Fast path: pure ASCII text (very common case)
     if text.is_ascii() {
@@ -182,8 +375,10 @@ pub fn draw_text(buffer: &mut OptimizedBuffer, x: u32, y: u32, text: &str, style
                 fg,
                 bg,
                 attributes: attrs,
+                underline_style,
+                underline_color,
             };
-            buffer.set_blended(col, row, cell);
+            set_styled(buffer, col, row, cell, style);
             col += width;
         }
         return;
@@ -204,11 +399,11 @@ pub fn draw_text(buffer: &mut OptimizedBuffer, x: u32, y: u32, text: &str, style
         let cell = Cell::from_grapheme(grapheme, style);
         let width = cell.display_width();
 
-        buffer.set_blended(col, row, cell);
+        set_styled(buffer, col, row, cell, style);
 
         // Add continuation cells for wide characters
         for i in 1..width {
-            buffer.set_blended(col + i as u32, row, Cell::continuation(bg));
+            set_styled(buffer, col + i as u32, row, Cell::continuation(bg), style);
         }
 
         col += width as u32;
@@ -244,6 +439,8 @@ pub fn draw_text_with_pool(
     let fg = style.fg.unwrap_or(Rgba::WHITE);
     let bg = style.bg.unwrap_or(Rgba::TRANSPARENT);
     let attrs = style.attributes;
+    let underline_style = style.underline_style;
+    let underline_color = style.underline_color;
 
     for grapheme in text.graphemes(true) {
         if grapheme == "\n" {
@@ -284,13 +481,16 @@ pub fn draw_text_with_pool(
             fg,
             bg,
             attributes: attrs,
+            underline_style,
+            underline_color,
         };
 
-        buffer.set_blended_with_pool(pool, col, row, cell);
+        set_styled_with_pool(buffer, pool, col, row, cell, style);
 
         // Add continuation cells for wide characters
         for i in 1..width {
-            buffer.set_blended_with_pool(pool, col + i as u32, row, Cell::continuation(bg));
+            let continuation = Cell::continuation(bg);
+            set_styled_with_pool(buffer, pool, col + i as u32, row, continuation, style);
         }
 
         col += width as u32;
@@ -300,7 +500,7 @@ pub fn draw_text_with_pool(
 /// Draw a single character at position, allocating from pool if needed.
 ///
 /// For single codepoints, stores directly. For multi-codepoint graphemes,
-/// allocates from the pool.
+/// allocates from the pool. Returns the grapheme's display width.
 pub fn draw_char_with_pool(
     buffer: &mut OptimizedBuffer,
     pool: &mut GraphemePool,
@@ -308,10 +508,12 @@ pub fn draw_char_with_pool(
     y: u32,
     grapheme: &str,
     style: Style,
-) {
+) -> usize {
     let fg = style.fg.unwrap_or(Rgba::WHITE);
     let bg = style.bg.unwrap_or(Rgba::TRANSPARENT);
     let attrs = style.attributes;
+    let underline_style = style.underline_style;
+    let underline_color = style.underline_color;
 
     // Fast path: ASCII single-byte characters are always single codepoint
     let (content, width) = if grapheme.len() == 1 {
@@ -337,44 +539,48 @@ pub fn draw_char_with_pool(
         fg,
         bg,
         attributes: attrs,
+        underline_style,
+        underline_color,
     };
 
-    buffer.set_blended_with_pool(pool, x, y, cell);
+    set_styled_with_pool(buffer, pool, x, y, cell, style);
 
     // Add continuation cells for wide characters
     for i in 1..width {
-        buffer.set_blended_with_pool(pool, x + i as u32, y, Cell::continuation(bg));
+        set_styled_with_pool(buffer, pool, x + i as u32, y, Cell::continuation(bg), style);
     }
+    width
 }
 
-/// Draw a box border.
+/// Draw a box border, merging corners and edges with any line glyphs
+/// already in the buffer.
+///
+/// Boxes narrower or shorter than 2 cells draw nothing.
 pub fn draw_box(buffer: &mut OptimizedBuffer, x: u32, y: u32, w: u32, h: u32, box_style: BoxStyle) {
     if w < 2 || h < 2 {
         return;
     }
 
+    let chars = box_style.chars;
     let style = box_style.style;
+    let (right, bottom) = (x + w - 1, y + h - 1);
 
     // Corners
-    buffer.set_blended(x, y, Cell::new(box_style.top_left, style));
-    buffer.set_blended(x + w - 1, y, Cell::new(box_style.top_right, style));
-    buffer.set_blended(x, y + h - 1, Cell::new(box_style.bottom_left, style));
-    buffer.set_blended(
-        x + w - 1,
-        y + h - 1,
-        Cell::new(box_style.bottom_right, style),
-    );
+    set_line_glyph(buffer, x, y, chars.top_left, style);
+    set_line_glyph(buffer, right, y, chars.top_right, style);
+    set_line_glyph(buffer, x, bottom, chars.bottom_left, style);
+    set_line_glyph(buffer, right, bottom, chars.bottom_right, style);
 
     // Horizontal edges
-    for col in (x + 1)..(x + w - 1) {
-        buffer.set_blended(col, y, Cell::new(box_style.horizontal, style));
-        buffer.set_blended(col, y + h - 1, Cell::new(box_style.horizontal, style));
+    for col in (x + 1)..right {
+        set_line_glyph(buffer, col, y, chars.top, style);
+        set_line_glyph(buffer, col, bottom, chars.bottom, style);
     }
 
     // Vertical edges
-    for row in (y + 1)..(y + h - 1) {
-        buffer.set_blended(x, row, Cell::new(box_style.vertical, style));
-        buffer.set_blended(x + w - 1, row, Cell::new(box_style.vertical, style));
+    for row in (y + 1)..bottom {
+        set_line_glyph(buffer, x, row, chars.left, style);
+        set_line_glyph(buffer, right, row, chars.right, style);
     }
 }
 
@@ -391,7 +597,10 @@ pub fn draw_box_with_options(
         return;
     }
 
+    let chars = options.style.chars;
     let style = options.style.style;
+    let sides = options.sides;
+    let (right, bottom) = (x + w - 1, y + h - 1);
 
     // Optional fill
     if let Some(bg) = options.fill {
@@ -401,97 +610,341 @@ pub fn draw_box_with_options(
     }
 
     // Corners
-    if options.sides.top && options.sides.left {
-        buffer.set_blended(x, y, Cell::new(options.style.top_left, style));
+    if sides.top && sides.left {
+        set_line_glyph(buffer, x, y, chars.top_left, style);
     }
-    if options.sides.top && options.sides.right {
-        buffer.set_blended(x + w - 1, y, Cell::new(options.style.top_right, style));
+    if sides.top && sides.right {
+        set_line_glyph(buffer, right, y, chars.top_right, style);
     }
-    if options.sides.bottom && options.sides.left {
-        buffer.set_blended(x, y + h - 1, Cell::new(options.style.bottom_left, style));
+    if sides.bottom && sides.left {
+        set_line_glyph(buffer, x, bottom, chars.bottom_left, style);
     }
-    if options.sides.bottom && options.sides.right {
-        buffer.set_blended(
-            x + w - 1,
-            y + h - 1,
-            Cell::new(options.style.bottom_right, style),
-        );
+    if sides.bottom && sides.right {
+        set_line_glyph(buffer, right, bottom, chars.bottom_right, style);
     }
 
     // Horizontal edges
-    if options.sides.top {
-        for col in (x + 1)..(x + w - 1) {
-            buffer.set_blended(col, y, Cell::new(options.style.horizontal, style));
+    if sides.top {
+        for col in (x + 1)..right {
+            set_line_glyph(buffer, col, y, chars.top, style);
         }
     }
-    if options.sides.bottom {
-        for col in (x + 1)..(x + w - 1) {
-            buffer.set_blended(col, y + h - 1, Cell::new(options.style.horizontal, style));
+    if sides.bottom {
+        for col in (x + 1)..right {
+            set_line_glyph(buffer, col, bottom, chars.bottom, style);
         }
     }
 
     // Vertical edges
-    if options.sides.left {
-        for row in (y + 1)..(y + h - 1) {
-            buffer.set_blended(x, row, Cell::new(options.style.vertical, style));
+    if sides.left {
+        for row in (y + 1)..bottom {
+            set_line_glyph(buffer, x, row, chars.left, style);
+        }
+    }
+    if sides.right {
+        for row in (y + 1)..bottom {
+            set_line_glyph(buffer, right, row, chars.right, style);
         }
     }
-    if options.sides.right {
-        for row in (y + 1)..(y + h - 1) {
-            buffer.set_blended(x + w - 1, row, Cell::new(options.style.vertical, style));
+
+    // Shadow: one column to the right and one row below, offset by a cell
+    if let Some(shadow) = options.shadow {
+        buffer.fill_rect(x + w, y + 1, 1, h, shadow.color);
+        buffer.fill_rect(x + 1, y + h, w - 1, 1, shadow.color);
+    }
+
+    // Titles, leaving a corner and one edge cell clear on each side
+    let title_spans = if options.title_spans.is_empty() {
+        options
+            .title
+            .map(|title| vec![(title, Style::NONE)])
+            .unwrap_or_default()
+    } else {
+        options.title_spans
+    };
+    if sides.top {
+        let secondary = options
+            .secondary_title
+            .as_ref()
+            .map(|(spans, align)| (spans.as_slice(), *align));
+        let primary = (title_spans.as_slice(), options.title_align);
+        draw_edge_titles(buffer, (x, w), y, style, primary, secondary);
+    }
+    if sides.bottom {
+        if let Some((spans, align)) = &options.bottom_title {
+            draw_edge_titles(buffer, (x, w), bottom, style, (spans, *align), None);
         }
     }
+}
 
-    // Title
-    if let Some(title) = options.title {
-        if options.sides.top && w > 2 {
-            let title_width = crate::unicode::display_width(&title) as i32;
-            let box_width = w as i32;
-            let min_title_space = 4;
+/// Cells kept clear of titles at each end of an edge: the corner and one
+/// edge cell.
+const TITLE_PADDING: u32 = 2;
 
-            if title_width > 0 && box_width >= title_width + min_title_space {
-                let padding = 2;
-                let start_x = x as i32;
-                let end_x = start_x + box_width - 1;
+/// Title spans and their alignment.
+type EdgeTitle<'a> = (&'a [(String, Style)], TitleAlign);
 
-                let mut title_x = match options.title_align {
-                    TitleAlign::Left => start_x + padding,
-                    TitleAlign::Center => {
-                        let centered = (box_width - title_width) / 2;
-                        start_x + padding.max(centered)
-                    }
-                    TitleAlign::Right => start_x + box_width - padding - title_width,
-                };
+/// Draw `primary`, and optionally `secondary`, over row `y` of a box
+/// edge starting at column `x` and `w` columns wide.
+///
+/// The primary title is truncated to the edge and placed per its
+/// alignment. The secondary goes in the stretch of edge left free on its
+/// aligned side of the primary (the wider one when centered), one border
+/// cell away, and is truncated to fit or dropped.
+fn draw_edge_titles(
+    buffer: &mut OptimizedBuffer,
+    (x, w): (u32, u32),
+    y: u32,
+    border: Style,
+    primary: EdgeTitle<'_>,
+    secondary: Option<EdgeTitle<'_>>,
+) {
+    if w <= 2 * TITLE_PADDING {
+        return;
+    }
+    let (lo, hi) = (x + TITLE_PADDING, x + w - TITLE_PADDING);
+
+    let spans = fit_spans(primary.0, hi - lo);
+    let width = spans_width(&spans);
+    let taken = (width > 0).then(|| {
+        let start = match primary.1 {
+            TitleAlign::Left => lo,
+            TitleAlign::Center => x + (w - width) / 2,
+            TitleAlign::Right => hi - width,
+        }
+        .clamp(lo, hi - width);
+        draw_title_spans(buffer, start, y, border, &spans);
+        start..start + width
+    });
 
-                // Clamp title position to respect padding on both sides.
-                // min_x ensures left padding, max_x ensures right padding.
-                let min_x = start_x + padding;
-                let max_x = end_x - padding - title_width + 1;
-                title_x = title_x.clamp(min_x, max_x);
+    let Some((spans, align)) = secondary else {
+        return;
+    };
+    let (left, right) = taken.map_or((lo..hi, hi..hi), |taken| {
+        (
+            lo..taken.start.saturating_sub(1).max(lo),
+            (taken.end + 1).min(hi)..hi,
+        )
+    });
+    let use_right = match align {
+        TitleAlign::Left => left.is_empty(),
+        TitleAlign::Right => !right.is_empty(),
+        TitleAlign::Center => right.len() > left.len(),
+    };
+    let segment = if use_right { right } else { left };
+    let spans = fit_spans(spans, segment.end - segment.start);
+    let width = spans_width(&spans);
+    if width == 0 {
+        return;
+    }
+    let start = match align {
+        TitleAlign::Left => segment.start,
+        TitleAlign::Center => segment.start + (segment.end - segment.start - width) / 2,
+        TitleAlign::Right => segment.end - width,
+    };
+    draw_title_spans(buffer, start, y, border, &spans);
+}
 
-                buffer.draw_text(title_x as u32, y, &title, style);
+/// `spans` cut to at most `width` columns, ending in `…` (in the style of
+/// the span it cuts) if anything was cut. A wide char that doesn't fit
+/// before the ellipsis is dropped rather than split.
+fn fit_spans(spans: &[(String, Style)], width: u32) -> Vec<(&str, Style)> {
+    let spans: Vec<(&str, Style)> = spans
+        .iter()
+        .map(|(text, style)| (text.as_str(), *style))
+        .collect();
+    if spans_width(&spans) <= width {
+        return spans;
+    }
+    let Some(mut budget) = width.checked_sub(1) else {
+        return Vec::new();
+    };
+
+    let mut fitted = Vec::new();
+    for (text, style) in spans {
+        let mut end = 0;
+        for (i, grapheme) in text.grapheme_indices(true) {
+            let grapheme_width = crate::unicode::display_width(grapheme) as u32;
+            if grapheme_width > budget {
+                if end > 0 {
+                    fitted.push((&text[..end], style));
+                }
+                fitted.push(("…", style));
+                return fitted;
             }
+            budget -= grapheme_width;
+            end = i + grapheme.len();
         }
+        fitted.push((text, style));
     }
+    fitted
+}
+
+fn spans_width(spans: &[(&str, Style)]) -> u32 {
+    spans
+        .iter()
+        .map(|(text, _)| crate::unicode::display_width(text) as u32)
+        .sum()
 }
 
-/// Draw a horizontal line.
-pub fn draw_hline(buffer: &mut OptimizedBuffer, x: u32, y: u32, len: u32, ch: char, style: Style) {
-    for col in x..x.saturating_add(len) {
-        buffer.set_blended(col, y, Cell::new(ch, style));
+/// Draw `spans` left to right from `(x, y)`, each merged over `border`.
+fn draw_title_spans(
+    buffer: &mut OptimizedBuffer,
+    mut x: u32,
+    y: u32,
+    border: Style,
+    spans: &[(&str, Style)],
+) {
+    for (text, style) in spans {
+        buffer.draw_text(x, y, text, border.merge(*style));
+        x += crate::unicode::display_width(text) as u32;
     }
 }
 
-/// Draw a vertical line.
-pub fn draw_vline(buffer: &mut OptimizedBuffer, x: u32, y: u32, len: u32, ch: char, style: Style) {
-    for row in y..y.saturating_add(len) {
-        buffer.set_blended(x, row, Cell::new(ch, style));
+/// Partial blocks for 1/8 through 7/8 of a cell, filled from the left.
+pub(super) const LEFT_EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Bars for sparkline levels, lowest to highest.
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw a one-row progress bar `width` cells wide.
+///
+/// `fraction` is clamped to `0.0..=1.0`, with NaN treated as empty. The
+/// boundary cell uses an eighth block, so the bar resolves `width * 8` steps.
+pub fn draw_progress_bar(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    width: u32,
+    fraction: f32,
+    style: ProgressStyle,
+) {
+    let fraction = if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0)
+    };
+    let eighths = (f64::from(fraction) * f64::from(width) * 8.0).round() as u32;
+    let (full, partial) = (eighths / 8, eighths % 8);
+
+    let partial_style = Style {
+        fg: style.filled.fg,
+        ..style.empty
+    };
+    for col in 0..width {
+        let cell = if col < full {
+            Cell::new('█', style.filled)
+        } else if col == full && partial > 0 {
+            Cell::new(LEFT_EIGHTHS[partial as usize - 1], partial_style)
+        } else {
+            Cell::new(' ', style.empty)
+        };
+        buffer.set_blended(x + col, y, cell);
+    }
+
+    if style.show_percent {
+        let label = format!("{:.0}%", fraction * 100.0);
+        let label_width = label.len() as u32;
+        if label_width <= width {
+            // Inverted over the filled part, in the bar's color elsewhere.
+            let over_filled = Style {
+                attributes: style.filled.attributes | TextAttributes::INVERSE,
+                ..style.filled
+            };
+            let start = (width - label_width) / 2;
+            for (col, ch) in (start..).zip(label.chars()) {
+                let label_style = if col < full {
+                    over_filled
+                } else {
+                    partial_style
+                };
+                buffer.set_blended(x + col, y, Cell::new(ch, label_style));
+            }
+        }
+    }
+}
+
+/// Draw `samples` as a one-row sparkline of eighth-height bars.
+///
+/// Only the last `width` samples are drawn, left-aligned. Non-finite
+/// samples are left as gaps, and samples outside fixed bounds are clamped.
+/// A flat series draws at the lowest level.
+pub fn draw_sparkline(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    width: u32,
+    samples: &[f32],
+    style: SparkStyle,
+) {
+    let samples = &samples[samples.len().saturating_sub(width as usize)..];
+    let (min, max) = style.bounds.unwrap_or_else(|| {
+        samples
+            .iter()
+            .filter(|sample| sample.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &sample| {
+                (min.min(sample), max.max(sample))
+            })
+    });
+    let range = max - min;
+
+    for (col, &sample) in (0..).zip(samples) {
+        let ch = if !sample.is_finite() {
+            ' '
+        } else if range > 0.0 && range.is_finite() {
+            let level = ((sample - min) / range).clamp(0.0, 1.0) * 7.0;
+            SPARK_LEVELS[level.round() as usize]
+        } else {
+            SPARK_LEVELS[0]
+        };
+        buffer.set_blended(x + col, y, Cell::new(ch, style.style));
+    }
+}
+
+/// Draw a progress bar filling the interior of a bordered box.
+///
+/// Rects smaller than 3x3 have no interior and draw nothing.
+pub fn draw_gauge(buffer: &mut OptimizedBuffer, rect: Rect, fraction: f32, style: GaugeStyle) {
+    if rect.width < 3 || rect.height < 3 {
+        return;
+    }
+
+    draw_box(
+        buffer,
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        style.border,
+    );
+    let rows = rect.height - 2;
+    for row in 0..rows {
+        let bar = ProgressStyle {
+            show_percent: style.bar.show_percent && row == (rows - 1) / 2,
+            ..style.bar
+        };
+        draw_progress_bar(
+            buffer,
+            rect.x + 1,
+            rect.y + 1 + row,
+            rect.width - 2,
+            fraction,
+            bar,
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::buffer::ClipRect;
+    use crate::buffer::glyphs;
+
+    thread_local! {
+        /// Calls into the general (segmenting) `draw_text` path.
+        pub(super) static GENERAL_PATH_CALLS: std::cell::Cell<usize> =
+            const { std::cell::Cell::new(0) };
+    }
 
     #[test]
     fn test_draw_text() {
@@ -657,6 +1110,322 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_box_style_into_ascii() {
+        let ascii = BoxStyle::double(Style::NONE).into_ascii();
+        assert_eq!(ascii.chars, BoxChars::ASCII);
+    }
+
+    #[test]
+    fn test_box_charsets() {
+        let custom = BoxChars {
+            top_left: '▛',
+            top_right: '▜',
+            bottom_left: '▙',
+            bottom_right: '▟',
+            top: '▀',
+            bottom: '▄',
+            left: '▌',
+            right: '▐',
+        };
+        let styles = [
+            BoxStyle::single(Style::NONE),
+            BoxStyle::rounded(Style::NONE),
+            BoxStyle::heavy(Style::NONE),
+            BoxStyle::double(Style::NONE),
+            BoxStyle::ascii(Style::NONE),
+            BoxStyle::custom(custom, Style::NONE),
+        ];
+        let mut buffer = OptimizedBuffer::new(6 * 6, 3);
+        for (i, style) in (0..).zip(styles) {
+            draw_box(&mut buffer, i * 6, 0, 5, 3, style);
+        }
+        insta::assert_snapshot!(glyphs(&buffer, ' '));
+    }
+
+    #[test]
+    fn test_degenerate_boxes() {
+        let mut buffer = OptimizedBuffer::new(8, 3);
+        let options = || BoxOptions {
+            fill: Some(Rgba::BLUE),
+            shadow: Some(ShadowOptions::default()),
+            title: Some("Title".to_string()),
+            ..BoxOptions::new(BoxStyle::rounded(Style::NONE))
+        };
+        draw_box_with_options(&mut buffer, 0, 0, 1, 1, options());
+        draw_box_with_options(&mut buffer, 2, 0, 2, 2, options());
+        draw_box(&mut buffer, 5, 0, 1, 1, BoxStyle::single(Style::NONE));
+        draw_box(&mut buffer, 5, 1, 2, 2, BoxStyle::single(Style::NONE));
+        insta::assert_snapshot!(glyphs(&buffer, ' '));
+        // The 2x2 box has no interior to fill, but still casts a shadow.
+        assert_eq!(buffer.get(2, 0).unwrap().bg, Rgba::TRANSPARENT);
+        assert!(buffer.get(4, 1).unwrap().bg.a > 0.0);
+        assert!(buffer.get(0, 0).unwrap().bg.a.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_box_fill_and_shadow() {
+        let mut buffer = OptimizedBuffer::new(10, 6);
+        buffer.clear(Rgba::WHITE);
+        let options = BoxOptions {
+            fill: Some(Rgba::BLUE),
+            shadow: Some(ShadowOptions::default()),
+            ..BoxOptions::new(BoxStyle::rounded(Style::NONE))
+        };
+        draw_box_with_options(&mut buffer, 1, 1, 6, 4, options);
+
+        let shadow = Rgba::BLACK.with_alpha(0.5).blend_over(Rgba::WHITE);
+        let (width, height) = buffer.size();
+        let mut backgrounds = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                let bg = buffer.get(x, y).unwrap().bg;
+                backgrounds.push(match bg {
+                    _ if bg == Rgba::WHITE => '.',
+                    _ if bg == Rgba::BLUE => 'F',
+                    _ if bg.to_rgb_u8() == shadow.to_rgb_u8() => 'S',
+                    _ => '?',
+                });
+            }
+            backgrounds.push('\n');
+        }
+        insta::assert_snapshot!(format!("{}\n{backgrounds}", glyphs(&buffer, ' ')));
+    }
+
+    #[test]
+    fn test_title_truncates_to_usable_width() {
+        let mut buffer = OptimizedBuffer::new(12, 3);
+        for (row, w) in [(0, 12), (1, 8), (2, 5)] {
+            let options = BoxOptions {
+                sides: BoxSides {
+                    bottom: false,
+                    ..BoxSides::default()
+                },
+                title: Some("Long title".to_string()),
+                ..BoxOptions::new(BoxStyle::rounded(Style::NONE))
+            };
+            draw_box_with_options(&mut buffer, 0, row, w, 2, options);
+        }
+        let lines: Vec<String> = glyphs(&buffer, ' ').lines().map(str::to_string).collect();
+        assert_eq!(lines[0], "╭─Long ti…─╮");
+        assert_eq!(lines[1], "╭─Lon…─╮    ");
+        assert_eq!(lines[2], "╭─…─╮       ");
+    }
+
+    /// Rows as a terminal shows them: continuation cells are skipped, so
+    /// wide chars line up with the border.
+    fn screen(buffer: &OptimizedBuffer) -> String {
+        let (width, height) = buffer.size();
+        let mut out = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                match buffer.get(x, y).unwrap().content {
+                    CellContent::Char(ch) => out.push(ch),
+                    CellContent::Continuation => {}
+                    _ => out.push(' '),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn spans(text: &str, style: Style) -> Vec<(String, Style)> {
+        vec![(text.to_string(), style)]
+    }
+
+    #[test]
+    fn test_title_and_badge_crowding() {
+        let error = Style::fg(Rgba::RED).with_bold();
+        let widths = [30, 22, 18, 15, 12, 8, 6];
+        let mut buffer = OptimizedBuffer::new(30, widths.len() as u32);
+        for (row, w) in (0..).zip(widths) {
+            let options = BoxOptions {
+                sides: BoxSides {
+                    bottom: false,
+                    ..BoxSides::default()
+                },
+                title_spans: vec![
+                    ("Build".to_string(), Style::bold()),
+                    (" output".to_string(), Style::NONE),
+                ],
+                secondary_title: Some((spans("3 errors", error), TitleAlign::Right)),
+                ..BoxOptions::new(BoxStyle::single(Style::fg(Rgba::WHITE)))
+            };
+            draw_box_with_options(&mut buffer, 0, row, w, 2, options);
+        }
+        insta::assert_snapshot!(screen(&buffer));
+
+        // Each span keeps its own style over the border's.
+        let cell = |x| *buffer.get(x, 0).unwrap();
+        assert!(cell(2).attributes.contains(TextAttributes::BOLD));
+        assert!(cell(2).fg.bits_eq(Rgba::WHITE));
+        assert!(!cell(7).attributes.contains(TextAttributes::BOLD));
+        assert_eq!(cell(20).content, CellContent::Char('3'));
+        assert!(cell(20).fg.bits_eq(Rgba::RED));
+        assert_eq!(cell(19).content, CellContent::Char('─'));
+    }
+
+    #[test]
+    fn test_bottom_titles() {
+        let mut buffer = OptimizedBuffer::new(24, 10);
+        for (row, align) in (0..).zip([TitleAlign::Left, TitleAlign::Center]) {
+            let options = BoxOptions {
+                title: Some("Files".to_string()),
+                title_align: TitleAlign::Center,
+                bottom_title: Some((spans("q quit", Style::dim()), align)),
+                ..BoxOptions::new(BoxStyle::single(Style::NONE))
+            };
+            draw_box_with_options(&mut buffer, 0, row * 4, 24, 3, options);
+        }
+        // No bottom edge, no bottom title.
+        let options = BoxOptions {
+            sides: BoxSides {
+                bottom: false,
+                ..BoxSides::default()
+            },
+            bottom_title: Some((spans("hidden", Style::NONE), TitleAlign::Left)),
+            ..BoxOptions::new(BoxStyle::single(Style::NONE))
+        };
+        draw_box_with_options(&mut buffer, 0, 8, 24, 2, options);
+        insta::assert_snapshot!(screen(&buffer));
+    }
+
+    #[test]
+    fn test_wide_titles_in_rounded_boxes() {
+        let widths = [14, 11, 9, 7, 5];
+        let mut buffer = OptimizedBuffer::new(14, widths.len() as u32);
+        for (row, w) in (0..).zip(widths) {
+            let options = BoxOptions {
+                sides: BoxSides {
+                    bottom: false,
+                    ..BoxSides::default()
+                },
+                title: Some("日本語".to_string()),
+                secondary_title: Some((spans("界!", Style::NONE), TitleAlign::Right)),
+                ..BoxOptions::new(BoxStyle::rounded(Style::NONE))
+            };
+            draw_box_with_options(&mut buffer, 0, row, w, 2, options);
+            // Titles never reach the corners or the edge cells beside them.
+            for x in [0, 1, w - 2, w - 1] {
+                let ch = match buffer.get(x, row).unwrap().content {
+                    CellContent::Char(ch) => ch,
+                    other => panic!("{other:?} at {x},{row}"),
+                };
+                assert!("╭╮─".contains(ch), "{ch} at {x},{row}");
+            }
+        }
+        insta::assert_snapshot!(screen(&buffer));
+    }
+
+    #[test]
+    fn test_progress_bar_fractional_fills() {
+        let fractions = [0.0, 0.05, 1.0 / 3.0, 0.5, 0.99, 1.0, 1.5, f32::NAN];
+        let widths = [1, 4, 10];
+        let mut out = String::new();
+        for fraction in fractions {
+            for width in widths {
+                let mut buffer = OptimizedBuffer::new(10, 1);
+                draw_progress_bar(&mut buffer, 0, 0, width, fraction, ProgressStyle::default());
+                let bar = glyphs(&buffer, ' ');
+                let label = format!("{fraction:.3} w{width}");
+                out.push_str(&format!("{label:<10} |{}|\n", bar.trim_end_matches('\n')));
+            }
+        }
+        insta::assert_snapshot!(out);
+    }
+
+    #[test]
+    fn test_progress_bar_percent_label() {
+        let mut buffer = OptimizedBuffer::new(10, 2);
+        let style =
+            ProgressStyle::new(Style::fg(Rgba::GREEN), Style::bg(Rgba::BLACK)).with_percent();
+        draw_progress_bar(&mut buffer, 0, 0, 10, 0.42, style);
+        draw_progress_bar(&mut buffer, 0, 1, 2, 0.42, style);
+
+        let lines: Vec<String> = glyphs(&buffer, ' ').lines().map(str::to_string).collect();
+        assert_eq!(lines[0], "███42%    ");
+        // Labels that don't fit are left out.
+        assert_eq!(lines[1], "▉         ");
+
+        // Over the filled part the label is inverted; past it, it keeps the
+        // bar's color on the empty background.
+        let cell = |x| *buffer.get(x, 0).unwrap();
+        assert!(cell(3).attributes.contains(TextAttributes::INVERSE));
+        assert_eq!(cell(3).fg, Rgba::GREEN);
+        assert!(!cell(4).attributes.contains(TextAttributes::INVERSE));
+        assert_eq!((cell(4).fg, cell(4).bg), (Rgba::GREEN, Rgba::BLACK));
+    }
+
+    #[test]
+    fn test_sparkline_scaling_and_gaps() {
+        let mut buffer = OptimizedBuffer::new(10, 5);
+        let auto = SparkStyle::default();
+        let fixed = SparkStyle {
+            bounds: Some((0.0, 100.0)),
+            ..SparkStyle::default()
+        };
+        // Fewer samples than columns.
+        draw_sparkline(&mut buffer, 0, 0, 10, &[1.0, 5.0, 3.0, 8.0], auto);
+        // More samples than columns keeps the newest.
+        let ramp: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        draw_sparkline(&mut buffer, 0, 1, 8, &ramp, auto);
+        draw_sparkline(
+            &mut buffer,
+            0,
+            2,
+            10,
+            &[-10.0, 0.0, 50.0, 100.0, 250.0],
+            fixed,
+        );
+        draw_sparkline(
+            &mut buffer,
+            0,
+            3,
+            10,
+            &[2.0, f32::NAN, 4.0, f32::INFINITY, 3.0],
+            auto,
+        );
+        draw_sparkline(&mut buffer, 0, 4, 10, &[7.0, 7.0, 7.0], auto);
+        draw_sparkline(&mut buffer, 0, 4, 10, &[], auto);
+
+        let bars: Vec<String> = glyphs(&buffer, ' ')
+            .lines()
+            .map(|line| format!("|{line}|"))
+            .collect();
+        insta::assert_snapshot!(bars.join("\n"));
+    }
+
+    #[test]
+    fn test_gauge_and_scissor() {
+        let mut buffer = OptimizedBuffer::new(12, 6);
+        let style = GaugeStyle {
+            border: BoxStyle::rounded(Style::NONE),
+            bar: ProgressStyle::default().with_percent(),
+        };
+        draw_gauge(&mut buffer, Rect::new(0, 0, 12, 5), 0.6, style.clone());
+        // Too small to have an interior.
+        draw_gauge(&mut buffer, Rect::new(0, 5, 12, 2), 0.6, style);
+
+        buffer.push_scissor(crate::buffer::ClipRect::new(0, 5, 4, 1));
+        draw_progress_bar(&mut buffer, 0, 5, 12, 1.0, ProgressStyle::default());
+        draw_sparkline(&mut buffer, 6, 5, 6, &[1.0, 2.0], SparkStyle::default());
+        buffer.pop_scissor();
+
+        let lines: Vec<String> = glyphs(&buffer, ' ').lines().map(str::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "╭──────────╮",
+                "│██████    │",
+                "│███60%    │",
+                "│██████    │",
+                "╰──────────╯",
+                "████        ",
+            ]
+        );
+    }
+
     #[test]
     fn test_draw_box_with_options_title() {
         let mut buffer = OptimizedBuffer::new(20, 5);
@@ -664,8 +1433,12 @@ mod tests {
             style: BoxStyle::single(Style::NONE),
             sides: BoxSides::default(),
             fill: None,
+            shadow: None,
             title: Some("Title".to_string()),
             title_align: TitleAlign::Left,
+            title_spans: Vec::new(),
+            secondary_title: None,
+            bottom_title: None,
         };
         draw_box_with_options(&mut buffer, 0, 0, 10, 4, options);
         assert_eq!(
@@ -830,4 +1603,215 @@ mod tests {
         assert_eq!(buf1.get(1, 0).unwrap().content, CellContent::Char('B'));
         assert_eq!(buf2.get(1, 0).unwrap().content, CellContent::Char('B'));
     }
+
+    // =========================================================================
+    // Printable ASCII fast path
+    // =========================================================================
+
+    fn general_path_calls() -> usize {
+        GENERAL_PATH_CALLS.with(std::cell::Cell::get)
+    }
+
+    #[test]
+    fn test_draw_text_ascii_skips_general_path() {
+        let mut buffer = OptimizedBuffer::new(40, 4);
+        let before = general_path_calls();
+
+        draw_text(&mut buffer, 0, 0, "Save  [Ctrl+S]", Style::bold());
+        draw_text(&mut buffer, 38, 1, "clipped label", Style::NONE);
+        assert_eq!(general_path_calls(), before);
+        assert_eq!(buffer.get(6, 0).unwrap().content, CellContent::Char('['));
+
+        // Control characters and Unicode still segment.
+        draw_text(&mut buffer, 0, 2, "a\nb", Style::NONE);
+        draw_text(&mut buffer, 0, 3, "naïve", Style::NONE);
+        assert_eq!(general_path_calls(), before + 2);
+    }
+
+    /// Draw `text` through both paths on copies of `setup`'s buffer and
+    /// check the results match cell for cell.
+    fn assert_paths_agree(
+        setup: impl Fn() -> OptimizedBuffer,
+        x: u32,
+        y: u32,
+        text: &str,
+        style: Style,
+    ) {
+        let mut fast = setup();
+        let mut general = setup();
+        draw_text(&mut fast, x, y, text, style);
+        draw_text_general(&mut general, x, y, text, style);
+
+        let (width, height) = fast.size();
+        for row in 0..height {
+            for col in 0..width {
+                let (a, b) = (fast.get(col, row).unwrap(), general.get(col, row).unwrap());
+                assert!(a.bits_eq(b), "{text:?} at ({x},{y}): cell ({col},{row})");
+            }
+        }
+        assert_eq!(fast.orphaned_graphemes, general.orphaned_graphemes);
+    }
+
+    #[test]
+    fn test_draw_text_ascii_fast_path_matches_general_path() {
+        fn plain() -> OptimizedBuffer {
+            let mut buffer = OptimizedBuffer::new(20, 3);
+            buffer.fill_rect(0, 0, 20, 3, Rgba::new(0.2, 0.2, 0.2, 1.0));
+            buffer
+        }
+        fn scissored() -> OptimizedBuffer {
+            let mut buffer = plain();
+            buffer.push_scissor_multi(&[ClipRect::new(2, 0, 4, 3), ClipRect::new(9, 1, 5, 1)]);
+            buffer
+        }
+        fn translucent() -> OptimizedBuffer {
+            let mut buffer = plain();
+            buffer.push_opacity(0.4);
+            buffer
+        }
+        fn overwrite() -> OptimizedBuffer {
+            let mut buffer = plain();
+            buffer.set_respect_alpha(false);
+            buffer
+        }
+        fn pooled() -> OptimizedBuffer {
+            let mut buffer = plain();
+            let mut pool = GraphemePool::new();
+            buffer.draw_text_with_pool(&mut pool, 3, 1, "👍🏽👍🏽", Style::NONE);
+            buffer
+        }
+
+        let setups: [fn() -> OptimizedBuffer; 5] =
+            [plain, scissored, translucent, overwrite, pooled];
+        let styles = [
+            Style::NONE,
+            Style::fg(Rgba::GREEN).with_bold(),
+            Style::fg(Rgba::GREEN).with_bg(Rgba::BLACK),
+            Style::fg(Rgba::new(1.0, 0.5, 0.0, 0.8))
+                .with_bg(Rgba::new(0.0, 0.0, 1.0, 0.5))
+                .with_underline(),
+        ];
+        for setup in setups {
+            for style in styles {
+                for (x, y) in [(0, 0), (1, 1), (15, 1), (19, 2), (25, 1), (0, 3)] {
+                    for text in ["", "x", "Hello, world!", "  spaced  out  ", "~`!@#$%^&*()"] {
+                        assert_paths_agree(setup, x, y, text, style);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A 12x2 buffer with a red-to-blue gradient background and its
+    /// per-cell backgrounds.
+    fn gradient_buffer() -> (OptimizedBuffer, Vec<Rgba>) {
+        use crate::buffer::{GradientDirection, GradientSpec};
+        use crate::color::Gradient;
+
+        let mut buffer = OptimizedBuffer::new(12, 2);
+        let spec = GradientSpec::new(
+            Gradient::new(Rgba::RED, Rgba::BLUE),
+            GradientDirection::Horizontal,
+        );
+        buffer.fill_rect_gradient(0, 0, 12, 2, &spec);
+        let backgrounds = (0..12).map(|x| buffer.get(x, 0).unwrap().bg).collect();
+        (buffer, backgrounds)
+    }
+
+    #[test]
+    fn test_preserve_bg_keeps_gradient() {
+        let style = Style::fg(Rgba::GREEN)
+            .with_bg(Rgba::BLACK)
+            .with_bold()
+            .with_preserve_bg();
+        let (mut buffer, backgrounds) = gradient_buffer();
+        buffer.draw_text(1, 0, "label 世", style);
+
+        for (x, bg) in (0..12).zip(&backgrounds) {
+            let cell = buffer.get(x, 0).unwrap();
+            assert!(cell.bg.bits_eq(*bg), "bg of cell {x}");
+            if (1..8).contains(&x) {
+                assert!(cell.fg.bits_eq(Rgba::GREEN), "fg of cell {x}");
+                assert!(cell.attributes.contains(TextAttributes::BOLD));
+            }
+        }
+        assert_eq!(buffer.get(1, 0).unwrap().content, CellContent::Char('l'));
+        assert!(buffer.get(8, 0).unwrap().is_continuation());
+
+        // Without the flag the style's background wins.
+        let (mut buffer, _) = gradient_buffer();
+        buffer.draw_text(1, 0, "label", Style::fg(Rgba::GREEN).with_bg(Rgba::BLACK));
+        assert!(buffer.get(1, 0).unwrap().bg.bits_eq(Rgba::BLACK));
+    }
+
+    #[test]
+    fn test_preserve_bg_ignores_opacity_and_alpha_mode() {
+        let style = Style::fg(Rgba::WHITE)
+            .with_bg(Rgba::BLACK)
+            .with_preserve_bg();
+
+        let (mut buffer, backgrounds) = gradient_buffer();
+        buffer.push_opacity(0.5);
+        buffer.draw_text(0, 1, "abc", style);
+        buffer.pop_opacity();
+        for x in 0..3 {
+            let cell = buffer.get(x, 1).unwrap();
+            assert!(cell.bg.bits_eq(backgrounds[x as usize]));
+            // The foreground is still blended at half opacity.
+            assert!(!cell.fg.bits_eq(Rgba::GREEN));
+        }
+
+        let (mut buffer, backgrounds) = gradient_buffer();
+        buffer.set_respect_alpha(false);
+        buffer.draw_text(0, 1, "abc", style);
+        assert!(buffer.get(2, 1).unwrap().bg.bits_eq(backgrounds[2]));
+    }
+
+    #[test]
+    fn test_preserve_fg_keeps_foreground() {
+        let mut buffer = OptimizedBuffer::new(6, 1);
+        buffer.draw_text(0, 0, "xxxxxx", Style::fg(Rgba::RED));
+        let mut pool = GraphemePool::new();
+        let style = Style::bg(Rgba::BLUE).with_italic().with_preserve_fg();
+        buffer.draw_text_with_pool(&mut pool, 0, 0, "ab", style);
+        draw_char_with_pool(&mut buffer, &mut pool, 3, 0, "c", style);
+
+        for x in [0, 1, 3] {
+            let cell = buffer.get(x, 0).unwrap();
+            assert!(cell.fg.bits_eq(Rgba::RED));
+            assert!(cell.bg.bits_eq(Rgba::BLUE));
+            assert!(cell.attributes.contains(TextAttributes::ITALIC));
+        }
+        assert_eq!(buffer.get(3, 0).unwrap().content, CellContent::Char('c'));
+        assert!(!buffer.get(2, 0).unwrap().bg.bits_eq(Rgba::BLUE));
+    }
+
+    #[test]
+    fn test_preserve_bg_in_spans_and_clipped_cells() {
+        use crate::buffer::Span;
+        use crate::link::LinkPool;
+
+        let (mut buffer, backgrounds) = gradient_buffer();
+        let mut pool = GraphemePool::new();
+        buffer.push_scissor(ClipRect::new(0, 0, 4, 2));
+        let spans = [
+            Span::new("ab", Style::fg(Rgba::GREEN).with_preserve_bg()),
+            Span::new("cd", Style::bg_inherit()),
+        ];
+        buffer.draw_spans(0, 0, &spans, &mut pool, &LinkPool::new());
+        buffer.pop_scissor();
+        buffer.draw_text(6, 0, "z", Style::bg_inherit());
+
+        for x in 0..12 {
+            assert!(
+                buffer
+                    .get(x, 0)
+                    .unwrap()
+                    .bg
+                    .bits_eq(backgrounds[x as usize])
+            );
+        }
+        assert_eq!(buffer.get(3, 0).unwrap().content, CellContent::Char('d'));
+        assert_eq!(buffer.get(6, 0).unwrap().content, CellContent::Char('z'));
+    }
 }
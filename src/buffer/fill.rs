@@ -0,0 +1,240 @@
+//! Gradient and pattern fills.
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::color::{Gradient, Rgba};
+use crate::renderer::Rect;
+use crate::style::Style;
+
+/// Axis a gradient runs along.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// Left to right.
+    #[default]
+    Horizontal,
+    /// Top to bottom.
+    Vertical,
+    /// Top-left to bottom-right, at 45° in cells.
+    Diagonal,
+}
+
+/// A background gradient for [`OptimizedBuffer::fill_rect_gradient`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientSpec {
+    pub gradient: Gradient,
+    pub direction: GradientDirection,
+}
+
+impl GradientSpec {
+    #[must_use]
+    pub const fn new(gradient: Gradient, direction: GradientDirection) -> Self {
+        Self {
+            gradient,
+            direction,
+        }
+    }
+}
+
+/// A repeating fill for [`OptimizedBuffer::fill_rect_pattern`].
+///
+/// Patterns are anchored at the rect's top-left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// Alternating cells, starting with the first at the rect's origin.
+    Checkerboard(Cell, Cell),
+    /// The shade glyph (` ░▒▓█`) nearest `brightness` in 0.0..=1.0,
+    /// drawn in `style`.
+    Shade { brightness: f32, style: Style },
+    /// Rows of cells repeated in both directions. Rows may differ in length.
+    Tile(Vec<Vec<Cell>>),
+}
+
+/// The shade glyph for a brightness, with NaN as empty.
+fn shade_glyph(brightness: f32) -> char {
+    const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+    if brightness.is_nan() {
+        return ' ';
+    }
+    SHADES[(brightness.clamp(0.0, 1.0) * 4.0).round() as usize]
+}
+
+/// Fill a rect's background with a gradient.
+///
+/// The first and last stops land on the first and last cells along the
+/// gradient's direction. Cells are filled like [`OptimizedBuffer::fill_rect`].
+pub fn fill_rect_gradient(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    spec: &GradientSpec,
+) {
+    let at = |step: u32, steps: u32| -> Rgba {
+        let position = if steps > 1 {
+            step as f32 / (steps - 1) as f32
+        } else {
+            0.0
+        };
+        spec.gradient.sample(position)
+    };
+
+    // Only cells inside the buffer need sampling.
+    let cols = w.min(buffer.width().saturating_sub(x));
+    let rows = h.min(buffer.height().saturating_sub(y));
+    match spec.direction {
+        GradientDirection::Horizontal => {
+            for col in 0..cols {
+                buffer.fill_rect(x + col, y, 1, h, at(col, w));
+            }
+        }
+        GradientDirection::Vertical => {
+            for row in 0..rows {
+                buffer.fill_rect(x, y + row, w, 1, at(row, h));
+            }
+        }
+        GradientDirection::Diagonal => {
+            let steps = w.saturating_add(h).saturating_sub(1);
+            for row in 0..rows {
+                for col in 0..cols {
+                    buffer.fill_rect(x + col, y + row, 1, 1, at(col + row, steps));
+                }
+            }
+        }
+    }
+}
+
+/// Fill a rect with a repeating pattern.
+///
+/// Pattern cells are drawn like [`OptimizedBuffer::set_blended`], so
+/// translucent colors blend over what's underneath.
+pub fn fill_rect_pattern(buffer: &mut OptimizedBuffer, rect: Rect, pattern: &Pattern) {
+    let cell_at = |col: u32, row: u32| -> Option<Cell> {
+        match pattern {
+            Pattern::Checkerboard(even, odd) => {
+                Some(if (col + row) % 2 == 0 { *even } else { *odd })
+            }
+            Pattern::Shade { brightness, style } => {
+                Some(Cell::new(shade_glyph(*brightness), *style))
+            }
+            Pattern::Tile(rows) => {
+                let tile_row = rows.get(row as usize % rows.len().max(1))?;
+                tile_row.get(col as usize % tile_row.len().max(1)).copied()
+            }
+        }
+    };
+
+    let cols = rect.width.min(buffer.width().saturating_sub(rect.x));
+    let rows = rect.height.min(buffer.height().saturating_sub(rect.y));
+    for row in 0..rows {
+        for col in 0..cols {
+            if let Some(cell) = cell_at(col, row) {
+                buffer.set_blended(rect.x + col, rect.y + row, cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::ClipRect;
+    use crate::buffer::glyphs;
+
+    fn backgrounds(buffer: &OptimizedBuffer, rect: Rect) -> String {
+        let mut out = String::new();
+        for y in rect.y..rect.y + rect.height {
+            let row: Vec<String> = (rect.x..rect.x + rect.width)
+                .map(|x| buffer.get(x, y).unwrap().bg.to_string())
+                .collect();
+            out.push_str(&row.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_gradient_stops_land_on_columns() {
+        let gradient =
+            Gradient::from_stops([(0.0, Rgba::RED), (0.5, Rgba::GREEN), (1.0, Rgba::BLUE)]);
+        let mut out = String::new();
+        for (direction, w, h) in [
+            (GradientDirection::Horizontal, 5, 1),
+            (GradientDirection::Vertical, 1, 3),
+            (GradientDirection::Diagonal, 3, 3),
+        ] {
+            let mut buffer = OptimizedBuffer::new(6, 4);
+            let spec = GradientSpec::new(gradient.clone(), direction);
+            buffer.fill_rect_gradient(1, 1, w, h, &spec);
+            out.push_str(&format!("{direction:?}\n"));
+            out.push_str(&backgrounds(&buffer, Rect::new(1, 1, w, h)));
+        }
+        insta::assert_snapshot!(out);
+
+        // Horizontal: column 0 is the first stop, the middle column the
+        // second, and the last column the last.
+        let mut buffer = OptimizedBuffer::new(11, 1);
+        let spec = GradientSpec::new(gradient, GradientDirection::Horizontal);
+        buffer.fill_rect_gradient(0, 0, 11, 1, &spec);
+        assert_eq!(buffer.get(0, 0).unwrap().bg, Rgba::RED);
+        assert_eq!(buffer.get(5, 0).unwrap().bg, Rgba::GREEN);
+        assert_eq!(buffer.get(10, 0).unwrap().bg, Rgba::BLUE);
+    }
+
+    #[test]
+    fn test_gradient_respects_scissor_and_opacity() {
+        let mut buffer = OptimizedBuffer::new(4, 1);
+        buffer.clear(Rgba::WHITE);
+        let spec = GradientSpec::new(
+            Gradient::new(Rgba::BLACK, Rgba::BLACK),
+            GradientDirection::Horizontal,
+        );
+        buffer.push_scissor(ClipRect::new(0, 0, 2, 1));
+        buffer.push_opacity(0.5);
+        buffer.fill_rect_gradient(0, 0, 4, 1, &spec);
+        buffer.pop_opacity();
+        buffer.pop_scissor();
+
+        let gray = Rgba::BLACK.with_alpha(0.5).blend_over(Rgba::WHITE);
+        assert_eq!(buffer.get(1, 0).unwrap().bg.to_rgb_u8(), gray.to_rgb_u8());
+        assert_eq!(buffer.get(2, 0).unwrap().bg, Rgba::WHITE);
+    }
+
+    #[test]
+    fn test_patterns_anchor_at_rect_origin() {
+        let cell = |ch| Cell::new(ch, Style::NONE);
+        let tile = Pattern::Tile(vec![
+            vec![cell('a'), cell('b'), cell('c')],
+            vec![cell('d'), cell('e')],
+        ]);
+        let mut buffer = OptimizedBuffer::new(9, 8);
+        buffer.fill_rect_pattern(
+            Rect::new(1, 0, 5, 2),
+            &Pattern::Checkerboard(cell('#'), cell('.')),
+        );
+        buffer.fill_rect_pattern(Rect::new(2, 2, 7, 3), &tile);
+        for (x, brightness) in (0..).zip([f32::NAN, 0.0, 0.2, 0.5, 0.7, 1.0, 2.0]) {
+            let shade = Pattern::Shade {
+                brightness,
+                style: Style::NONE,
+            };
+            buffer.fill_rect_pattern(Rect::new(x, 6, 1, 2), &shade);
+        }
+        // Empty tiles draw nothing.
+        buffer.fill_rect_pattern(Rect::new(0, 5, 9, 1), &Pattern::Tile(vec![vec![]]));
+        insta::assert_snapshot!(glyphs(&buffer, '·'));
+    }
+
+    #[test]
+    fn test_translucent_pattern_blends() {
+        let mut buffer = OptimizedBuffer::new(2, 1);
+        buffer.clear(Rgba::WHITE);
+        let glass = Cell::clear(Rgba::BLUE.with_alpha(0.5));
+        let clear = Cell::clear(Rgba::TRANSPARENT);
+        buffer.fill_rect_pattern(Rect::new(0, 0, 2, 1), &Pattern::Checkerboard(glass, clear));
+
+        let tinted = Rgba::BLUE.with_alpha(0.5).blend_over(Rgba::WHITE);
+        assert_eq!(buffer.get(0, 0).unwrap().bg.to_rgb_u8(), tinted.to_rgb_u8());
+        assert_eq!(buffer.get(1, 0).unwrap().bg, Rgba::WHITE);
+    }
+}
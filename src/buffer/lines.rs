@@ -0,0 +1,314 @@
+//! Line drawing with automatic junction merging.
+//!
+//! Every box-drawing glyph is modelled as up to four arms (up, right, down,
+//! left), each with a weight. Drawing a line or box edge over an existing
+//! glyph merges their arms and writes the glyph for the union, so crossing
+//! lines become `┼`, a line meeting a box edge becomes `├`, and so on.
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+use crate::style::Style;
+
+/// Stroke used by [`OptimizedBuffer::draw_hline`] and
+/// [`OptimizedBuffer::draw_vline`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineStyle {
+    /// `─` / `│`
+    #[default]
+    Light,
+    /// `━` / `┃`
+    Heavy,
+    /// `═` / `║`
+    Double,
+    /// `┄` / `┆`, merging like [`Self::Light`] at junctions.
+    Dashed,
+}
+
+impl LineStyle {
+    fn horizontal(self) -> char {
+        match self {
+            Self::Light => '─',
+            Self::Heavy => '━',
+            Self::Double => '═',
+            Self::Dashed => '┄',
+        }
+    }
+
+    fn vertical(self) -> char {
+        match self {
+            Self::Light => '│',
+            Self::Heavy => '┃',
+            Self::Double => '║',
+            Self::Dashed => '┆',
+        }
+    }
+}
+
+// Arm weights, packed two bits per direction.
+const N: u8 = 0;
+const L: u8 = 1;
+const H: u8 = 2;
+const D: u8 = 3;
+
+const fn arms(up: u8, right: u8, down: u8, left: u8) -> u8 {
+    up | (right << 2) | (down << 4) | (left << 6)
+}
+
+/// Glyphs that can be produced by merging, with their arms.
+#[rustfmt::skip]
+const GLYPHS: &[(char, u8)] = &[
+    ('─', arms(N, L, N, L)), ('━', arms(N, H, N, H)),
+    ('│', arms(L, N, L, N)), ('┃', arms(H, N, H, N)),
+    ('┌', arms(N, L, L, N)), ('┍', arms(N, H, L, N)), ('┎', arms(N, L, H, N)), ('┏', arms(N, H, H, N)),
+    ('┐', arms(N, N, L, L)), ('┑', arms(N, N, L, H)), ('┒', arms(N, N, H, L)), ('┓', arms(N, N, H, H)),
+    ('└', arms(L, L, N, N)), ('┕', arms(L, H, N, N)), ('┖', arms(H, L, N, N)), ('┗', arms(H, H, N, N)),
+    ('┘', arms(L, N, N, L)), ('┙', arms(L, N, N, H)), ('┚', arms(H, N, N, L)), ('┛', arms(H, N, N, H)),
+    ('├', arms(L, L, L, N)), ('┝', arms(L, H, L, N)), ('┞', arms(H, L, L, N)), ('┟', arms(L, L, H, N)),
+    ('┠', arms(H, L, H, N)), ('┡', arms(H, H, L, N)), ('┢', arms(L, H, H, N)), ('┣', arms(H, H, H, N)),
+    ('┤', arms(L, N, L, L)), ('┥', arms(L, N, L, H)), ('┦', arms(H, N, L, L)), ('┧', arms(L, N, H, L)),
+    ('┨', arms(H, N, H, L)), ('┩', arms(H, N, L, H)), ('┪', arms(L, N, H, H)), ('┫', arms(H, N, H, H)),
+    ('┬', arms(N, L, L, L)), ('┭', arms(N, L, L, H)), ('┮', arms(N, H, L, L)), ('┯', arms(N, H, L, H)),
+    ('┰', arms(N, L, H, L)), ('┱', arms(N, L, H, H)), ('┲', arms(N, H, H, L)), ('┳', arms(N, H, H, H)),
+    ('┴', arms(L, L, N, L)), ('┵', arms(L, L, N, H)), ('┶', arms(L, H, N, L)), ('┷', arms(L, H, N, H)),
+    ('┸', arms(H, L, N, L)), ('┹', arms(H, L, N, H)), ('┺', arms(H, H, N, L)), ('┻', arms(H, H, N, H)),
+    ('┼', arms(L, L, L, L)), ('┽', arms(L, L, L, H)), ('┾', arms(L, H, L, L)), ('┿', arms(L, H, L, H)),
+    ('╀', arms(H, L, L, L)), ('╁', arms(L, L, H, L)), ('╂', arms(H, L, H, L)), ('╃', arms(H, L, L, H)),
+    ('╄', arms(H, H, L, L)), ('╅', arms(L, L, H, H)), ('╆', arms(L, H, H, L)), ('╇', arms(H, H, L, H)),
+    ('╈', arms(L, H, H, H)), ('╉', arms(H, L, H, H)), ('╊', arms(H, H, H, L)), ('╋', arms(H, H, H, H)),
+    ('═', arms(N, D, N, D)), ('║', arms(D, N, D, N)),
+    ('╒', arms(N, D, L, N)), ('╓', arms(N, L, D, N)), ('╔', arms(N, D, D, N)),
+    ('╕', arms(N, N, L, D)), ('╖', arms(N, N, D, L)), ('╗', arms(N, N, D, D)),
+    ('╘', arms(L, D, N, N)), ('╙', arms(D, L, N, N)), ('╚', arms(D, D, N, N)),
+    ('╛', arms(L, N, N, D)), ('╜', arms(D, N, N, L)), ('╝', arms(D, N, N, D)),
+    ('╞', arms(L, D, L, N)), ('╟', arms(D, L, D, N)), ('╠', arms(D, D, D, N)),
+    ('╡', arms(L, N, L, D)), ('╢', arms(D, N, D, L)), ('╣', arms(D, N, D, D)),
+    ('╤', arms(N, D, L, D)), ('╥', arms(N, L, D, L)), ('╦', arms(N, D, D, D)),
+    ('╧', arms(L, D, N, D)), ('╨', arms(D, L, N, L)), ('╩', arms(D, D, N, D)),
+    ('╪', arms(L, D, L, D)), ('╫', arms(D, L, D, L)), ('╬', arms(D, D, D, D)),
+    ('╴', arms(N, N, N, L)), ('╵', arms(L, N, N, N)), ('╶', arms(N, L, N, N)), ('╷', arms(N, N, L, N)),
+    ('╸', arms(N, N, N, H)), ('╹', arms(H, N, N, N)), ('╺', arms(N, H, N, N)), ('╻', arms(N, N, H, N)),
+    ('╼', arms(N, H, N, L)), ('╽', arms(L, N, H, N)), ('╾', arms(N, L, N, H)), ('╿', arms(H, N, L, N)),
+];
+
+/// Glyphs recognised when merging but never produced by it.
+#[rustfmt::skip]
+const ALIASES: &[(char, u8)] = &[
+    ('╭', arms(N, L, L, N)), ('╮', arms(N, N, L, L)), ('╯', arms(L, N, N, L)), ('╰', arms(L, L, N, N)),
+    ('┄', arms(N, L, N, L)), ('┈', arms(N, L, N, L)), ('╌', arms(N, L, N, L)),
+    ('┅', arms(N, H, N, H)), ('┉', arms(N, H, N, H)), ('╍', arms(N, H, N, H)),
+    ('┆', arms(L, N, L, N)), ('┊', arms(L, N, L, N)), ('╎', arms(L, N, L, N)),
+    ('┇', arms(H, N, H, N)), ('┋', arms(H, N, H, N)), ('╏', arms(H, N, H, N)),
+];
+
+/// The arms of a box-drawing glyph, or `None` for anything else.
+fn arms_of(ch: char) -> Option<u8> {
+    if !('\u{2500}'..='\u{257f}').contains(&ch) {
+        return None;
+    }
+    GLYPHS
+        .iter()
+        .chain(ALIASES)
+        .find(|&&(glyph, _)| glyph == ch)
+        .map(|&(_, arms)| arms)
+}
+
+/// Whether `ch` is a box-drawing glyph that merges at junctions.
+pub(super) fn is_line_glyph(ch: char) -> bool {
+    arms_of(ch).is_some()
+}
+
+/// Which directions have an arm, ignoring weight.
+const fn presence(arms: u8) -> u8 {
+    (arms | (arms >> 1)) & 0b0101_0101
+}
+
+/// How many directions differ in weight.
+const fn mismatches(a: u8, b: u8) -> u32 {
+    presence(a ^ b).count_ones()
+}
+
+/// The glyph for a set of arms.
+///
+/// Unicode lacks many mixed-weight glyphs (heavy with double, double
+/// half-lines, ...). Those fall back to the glyph with the same arms and the
+/// fewest weights changed.
+fn glyph_for(arms: u8) -> Option<char> {
+    GLYPHS
+        .iter()
+        .filter(|&&(_, glyph_arms)| presence(glyph_arms) == presence(arms))
+        .min_by_key(|&&(_, glyph_arms)| mismatches(glyph_arms, arms))
+        .map(|&(glyph, _)| glyph)
+}
+
+/// Draw a box-drawing glyph, merging it with any glyph already in the cell.
+///
+/// Non-box characters on either side are simply overwritten.
+pub(super) fn set_line_glyph(buffer: &mut OptimizedBuffer, x: u32, y: u32, ch: char, style: Style) {
+    merge_line_glyph(buffer, x, y, ch, arms_of(ch), style);
+}
+
+/// Draw `ch`, or the junction for `arms` merged with the cell's existing
+/// glyph if that differs from `ch`.
+fn merge_line_glyph(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    ch: char,
+    arms: Option<u8>,
+    style: Style,
+) {
+    let merged = match (buffer.get(x, y).map(|cell| cell.content), arms) {
+        (Some(CellContent::Char(existing)), Some(new_arms)) => arms_of(existing)
+            .map(|old_arms| merge_arms(old_arms, new_arms))
+            .filter(|&merged| Some(merged) != arms_of(ch))
+            .and_then(glyph_for),
+        _ => None,
+    };
+    buffer.set_blended(x, y, Cell::new(merged.unwrap_or(ch), style));
+}
+
+/// Union two arm sets, with `new` winning where both have an arm.
+const fn merge_arms(old: u8, new: u8) -> u8 {
+    let mut out = 0;
+    let mut shift = 0;
+    while shift < 8 {
+        let weight = (new >> shift) & 0b11;
+        out |= (if weight == N {
+            (old >> shift) & 0b11
+        } else {
+            weight
+        }) << shift;
+        shift += 2;
+    }
+    out
+}
+
+/// Draw a horizontal line, merging junctions with existing line glyphs.
+///
+/// The end cells only reach inwards, so a line drawn between two vertical
+/// edges joins them with `├` and `┤` rather than `┼`.
+pub fn draw_hline(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    len: u32,
+    line: LineStyle,
+    style: Style,
+) {
+    draw_hline_glyph(buffer, x, y, len, line.horizontal(), style);
+}
+
+/// Draw a horizontal run of `ch` like [`draw_hline`]. Characters that
+/// aren't box-drawing glyphs are written without merging.
+pub(super) fn draw_hline_glyph(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    len: u32,
+    ch: char,
+    style: Style,
+) {
+    let end = x.saturating_add(len);
+    for col in x..end {
+        let mut reach = arms_of(ch);
+        if len > 1 && col == x {
+            reach = reach.map(|reach| reach & !arms(N, N, N, D));
+        }
+        if len > 1 && col + 1 == end {
+            reach = reach.map(|reach| reach & !arms(N, D, N, N));
+        }
+        merge_line_glyph(buffer, col, y, ch, reach, style);
+    }
+}
+
+/// Draw a vertical line, merging junctions with existing line glyphs.
+///
+/// Like [`draw_hline`], the end cells only reach inwards.
+pub fn draw_vline(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    len: u32,
+    line: LineStyle,
+    style: Style,
+) {
+    draw_vline_glyph(buffer, x, y, len, line.vertical(), style);
+}
+
+/// Draw a vertical run of `ch` like [`draw_vline`].
+pub(super) fn draw_vline_glyph(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    len: u32,
+    ch: char,
+    style: Style,
+) {
+    let end = y.saturating_add(len);
+    for row in y..end {
+        let mut reach = arms_of(ch);
+        if len > 1 && row == y {
+            reach = reach.map(|reach| reach & !arms(D, N, N, N));
+        }
+        if len > 1 && row + 1 == end {
+            reach = reach.map(|reach| reach & !arms(N, N, D, N));
+        }
+        merge_line_glyph(buffer, x, row, ch, reach, style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::BoxStyle;
+    use crate::buffer::glyphs;
+
+    #[test]
+    fn test_every_glyph_round_trips() {
+        for &(glyph, arms) in GLYPHS {
+            assert_eq!(arms_of(glyph), Some(arms), "{glyph}");
+            assert_eq!(glyph_for(arms), Some(glyph), "{glyph}");
+        }
+        for &(glyph, arms) in ALIASES {
+            assert_eq!(arms_of(glyph), Some(arms), "{glyph}");
+        }
+    }
+
+    #[test]
+    fn test_mixed_weights_degrade() {
+        // Heavy meeting double has no glyph: one of them changes.
+        assert_eq!(glyph_for(arms(H, D, H, D)), Some('╂'));
+        // Double half-lines don't exist.
+        assert_eq!(glyph_for(arms(N, D, N, N)), Some('╶'));
+        // A double corner meeting a light line keeps as much as it can.
+        assert_eq!(glyph_for(arms(D, D, N, L)), Some('╨'));
+        assert_eq!(glyph_for(arms(D, L, L, L)), Some('┼'));
+        assert_eq!(glyph_for(0), None);
+    }
+
+    #[test]
+    fn test_lines_keep_their_own_glyph_when_not_crossing() {
+        let mut buffer = OptimizedBuffer::new(4, 1);
+        draw_hline(&mut buffer, 0, 0, 4, LineStyle::Dashed, Style::NONE);
+        draw_hline(&mut buffer, 0, 0, 2, LineStyle::Light, Style::NONE);
+        assert_eq!(glyphs(&buffer, ' '), "──┄┄\n");
+
+        let mut buffer = OptimizedBuffer::new(3, 1);
+        buffer.draw_text(0, 0, "a-b", Style::NONE);
+        draw_hline(&mut buffer, 0, 0, 3, LineStyle::Heavy, Style::NONE);
+        assert_eq!(glyphs(&buffer, ' '), "━━━\n");
+    }
+
+    #[test]
+    fn test_grid_of_lines_and_boxes() {
+        let mut buffer = OptimizedBuffer::new(13, 9);
+        buffer.draw_box(0, 0, 13, 9, BoxStyle::single(Style::NONE));
+        draw_hline(&mut buffer, 0, 4, 13, LineStyle::Light, Style::NONE);
+        draw_vline(&mut buffer, 6, 0, 9, LineStyle::Heavy, Style::NONE);
+        draw_vline(&mut buffer, 3, 0, 5, LineStyle::Double, Style::NONE);
+        draw_hline(&mut buffer, 6, 2, 7, LineStyle::Dashed, Style::NONE);
+        buffer.draw_box(8, 4, 5, 5, BoxStyle::rounded(Style::NONE));
+        buffer.draw_box(1, 6, 4, 3, BoxStyle::double(Style::NONE));
+        insta::assert_snapshot!(glyphs(&buffer, ' '));
+    }
+}
@@ -3,7 +3,8 @@
 //! This module provides [`OptimizedBuffer`], the primary drawing surface for
 //! terminal rendering. Buffers are 2D grids of cells that support:
 //!
-//! - **Basic drawing**: Set individual cells, draw text, draw boxes
+//! - **Basic drawing**: Set individual cells, draw text, boxes, and lines
+//!   that merge into junctions where they meet
 //! - **Scissor clipping**: Restrict drawing to rectangular regions
 //! - **Opacity stacking**: Apply transparency to groups of operations
 //! - **Alpha blending**: Composite cells using Porter-Duff "over"
@@ -37,21 +38,57 @@
 // Buffer operations naturally have many parameters for region copying
 #![allow(clippy::too_many_arguments)]
 
+mod aligned;
+mod big_text;
+mod braille;
+mod diff;
 mod drawing;
+mod fill;
+mod lines;
 mod opacity;
 mod pixel;
+mod raster;
+mod row_size;
 mod scissor;
-
-pub use drawing::{BoxOptions, BoxSides, BoxStyle, TitleAlign};
-pub use opacity::OpacityStack;
-pub use pixel::{GrayscaleBuffer, PixelBuffer};
+mod scrollbar;
+mod spans;
+mod spinner;
+mod sprite;
+mod status_line;
+mod table;
+mod text_block;
+
+pub use aligned::{Align, Truncate};
+pub use big_text::{BigFont, big_text_size, draw_big_text};
+pub use braille::BrailleCanvas;
+pub use diff::{BufferDiffSummary, RowDiff, diff};
+pub use drawing::{
+    BoxChars, BoxOptions, BoxSides, BoxStyle, GaugeStyle, ProgressStyle, ShadowOptions, SparkStyle,
+    TitleAlign,
+};
+pub use fill::{GradientDirection, GradientSpec, Pattern};
+pub use lines::LineStyle;
+pub use opacity::{Opacity, OpacityStack};
+pub use pixel::{AsciiArtOptions, CellMode, GrayscaleBuffer, PixelBuffer};
+pub use row_size::RowSize;
 pub use scissor::{ClipRect, ScissorStack};
+pub use scrollbar::{ScrollInfo, ScrollbarStyle, draw_scrollbar, scrollbar_offset_for_click};
+pub use spans::Span;
+pub use spinner::{INDETERMINATE_PERIOD, Spinner, draw_indeterminate_bar};
+pub use sprite::{Sprite, SpriteOptions};
+pub use status_line::{StatusHit, StatusItem, StatusLine, StatusSection};
+pub use table::{ColumnSpec, Table, draw_table};
+pub use text_block::{TextBlockMetrics, TextBlockOptions, VAlign};
+
+use std::ops::Range;
 
 use crate::cell::{Cell, CellContent, GraphemeId};
-use crate::color::Rgba;
+use crate::color::{BlendMode, Rgba};
 use crate::grapheme_pool::GraphemePool;
+use crate::link::LinkPool;
+use crate::renderer::Rect;
 use crate::style::Style;
-use crate::text::{EditorView, TextBufferView};
+use crate::text::{EditorView, TextBufferView, WrapMode};
 
 /// Optimized cell buffer for terminal rendering.
 ///
@@ -73,6 +110,8 @@ pub struct OptimizedBuffer {
     width: u32,
     height: u32,
     cells: Vec<Cell>,
+    /// Size of each row, or empty while every row is normal.
+    row_sizes: Vec<RowSize>,
 
     scissor_stack: ScissorStack,
     opacity_stack: OpacityStack,
@@ -83,6 +122,13 @@ pub struct OptimizedBuffer {
     /// Grapheme IDs that were overwritten by non-pool operations.
     /// These need to be cleaned up when a pool becomes available.
     orphaned_graphemes: Vec<GraphemeId>,
+
+    /// [`GraphemePool::id`] of the pool this buffer's grapheme IDs belong
+    /// to, once bound.
+    grapheme_pool: Option<u32>,
+
+    /// Bumped by every operation that may write cells.
+    mutations: u64,
 }
 
 impl OptimizedBuffer {
@@ -100,11 +146,14 @@ impl OptimizedBuffer {
             width,
             height,
             cells: vec![Cell::clear(Rgba::TRANSPARENT); size],
+            row_sizes: Vec::new(),
             scissor_stack: ScissorStack::new(),
             opacity_stack: OpacityStack::new(),
             id: String::new(),
             respect_alpha: true,
             orphaned_graphemes: Vec::new(),
+            grapheme_pool: None,
+            mutations: 0,
         }
     }
 
@@ -115,6 +164,30 @@ impl OptimizedBuffer {
         self
     }
 
+    /// Bind the buffer to the grapheme pool its cells will reference.
+    ///
+    /// Pool-aware methods bind an unbound buffer to the pool they are given,
+    /// so this is only needed to bind before the first of them.
+    #[must_use]
+    pub fn with_grapheme_pool(mut self, pool: &GraphemePool) -> Self {
+        self.grapheme_pool = Some(pool.id());
+        self
+    }
+
+    /// [`GraphemePool::id`] of the pool this buffer is bound to, if any.
+    ///
+    /// Blits from a buffer bound to another pool translate or drop its
+    /// graphemes; see [`Self::draw_buffer_region_from_pool`].
+    #[must_use]
+    pub fn grapheme_pool_id(&self) -> Option<u32> {
+        self.grapheme_pool
+    }
+
+    /// Bind the buffer to `pool` unless it is already bound.
+    fn bind_pool(&mut self, pool: &GraphemePool) {
+        self.grapheme_pool.get_or_insert_with(|| pool.id());
+    }
+
     /// Get buffer dimensions.
     #[must_use]
     pub fn size(&self) -> (u32, u32) {
@@ -174,6 +247,7 @@ impl OptimizedBuffer {
 
     /// Get mutable cell at position.
     pub fn get_mut(&mut self, x: u32, y: u32) -> Option<&mut Cell> {
+        self.touch();
         self.cell_index(x, y).map(|idx| &mut self.cells[idx])
     }
 
@@ -183,12 +257,13 @@ impl OptimizedBuffer {
     /// grapheme ID is tracked for later cleanup via [`Self::clear_with_pool`] or
     /// [`Self::set_with_pool`].
     pub fn set(&mut self, x: u32, y: u32, mut cell: Cell) {
+        self.touch();
         if !self.is_visible(x, y) {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
-        if opacity < 1.0 {
+        let opacity = self.opacity_stack.channels();
+        if !opacity.is_opaque() {
             cell.blend_with_opacity(opacity);
         }
 
@@ -208,6 +283,7 @@ impl OptimizedBuffer {
     ///
     /// Also releases any orphaned graphemes from prior non-pool operations.
     pub fn set_with_pool(&mut self, pool: &mut GraphemePool, x: u32, y: u32, mut cell: Cell) {
+        self.bind_pool(pool);
         // First, release any orphaned graphemes from non-pool operations
         self.drain_orphaned_graphemes(pool);
 
@@ -215,8 +291,8 @@ impl OptimizedBuffer {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
-        if opacity < 1.0 {
+        let opacity = self.opacity_stack.channels();
+        if !opacity.is_opaque() {
             cell.blend_with_opacity(opacity);
         }
 
@@ -247,12 +323,13 @@ impl OptimizedBuffer {
     /// grapheme ID is tracked for later cleanup via [`Self::clear_with_pool`] or
     /// [`Self::set_blended_with_pool`].
     pub fn set_blended(&mut self, x: u32, y: u32, mut cell: Cell) {
+        self.touch();
         if !self.is_visible(x, y) {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
-        if opacity < 1.0 {
+        let opacity = self.opacity_stack.channels();
+        if !opacity.is_opaque() {
             cell.blend_with_opacity(opacity);
         }
 
@@ -283,6 +360,7 @@ impl OptimizedBuffer {
         y: u32,
         mut cell: Cell,
     ) {
+        self.bind_pool(pool);
         // First, release any orphaned graphemes from non-pool operations
         self.drain_orphaned_graphemes(pool);
 
@@ -290,8 +368,8 @@ impl OptimizedBuffer {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
-        if opacity < 1.0 {
+        let opacity = self.opacity_stack.channels();
+        if !opacity.is_opaque() {
             cell.blend_with_opacity(opacity);
         }
 
@@ -327,7 +405,7 @@ impl OptimizedBuffer {
 
     /// Check if position is within current scissor rect.
     fn is_visible(&self, x: u32, y: u32) -> bool {
-        if x >= self.width || y >= self.height {
+        if y >= self.height || x >= self.row_width(y) {
             return false;
         }
         self.scissor_stack.contains(x as i32, y as i32)
@@ -347,20 +425,30 @@ impl OptimizedBuffer {
         }
     }
 
+    /// Grapheme references to release on the next pool-aware operation.
+    #[cfg(feature = "pool-audit")]
+    pub(crate) fn orphaned_graphemes(&self) -> &[GraphemeId] {
+        &self.orphaned_graphemes
+    }
+
     /// Clear entire buffer with background color.
     pub fn clear(&mut self, bg: Rgba) {
+        self.touch();
         // Create the clear cell once and fill the entire buffer
         // This is more efficient than creating Cell::clear(bg) per cell
         let clear_cell = Cell::clear(bg);
         self.cells.fill(clear_cell);
+        self.row_sizes.clear();
     }
 
     /// Clear entire buffer with background color, updating grapheme pool counts.
     ///
     /// Also releases any orphaned graphemes from prior non-pool operations.
     pub fn clear_with_pool(&mut self, pool: &mut GraphemePool, bg: Rgba) {
+        self.touch();
         // First, release any orphaned graphemes from non-pool operations
         self.drain_orphaned_graphemes(pool);
+        self.row_sizes.clear();
 
         let clear_cell = Cell::clear(bg);
         for cell in &mut self.cells {
@@ -378,7 +466,9 @@ impl OptimizedBuffer {
     /// Unlike `clear_with_pool(..., Rgba::TRANSPARENT)`, this does not tint the
     /// underlying foreground when composited over another buffer.
     pub fn clear_transparent_with_pool(&mut self, pool: &mut GraphemePool) {
+        self.touch();
         self.drain_orphaned_graphemes(pool);
+        self.row_sizes.clear();
 
         let clear_cell = Cell::transparent();
         for cell in &mut self.cells {
@@ -393,69 +483,124 @@ impl OptimizedBuffer {
 
     /// Fill a rectangular region with background color.
     pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, bg: Rgba) {
-        if w == 0 || h == 0 || self.width == 0 || self.height == 0 {
-            return;
-        }
-
-        let mut x0 = x.min(self.width);
-        let mut y0 = y.min(self.height);
-        let mut x1 = x.saturating_add(w).min(self.width);
-        let mut y1 = y.saturating_add(h).min(self.height);
-
-        if x0 >= x1 || y0 >= y1 {
-            return;
-        }
-
-        let scissor = self.scissor_stack.current();
-        if scissor.is_empty() {
-            return;
-        }
-
-        let scissor_start_x = scissor.x.max(0) as u32;
-        let scissor_start_y = scissor.y.max(0) as u32;
-        let scissor_end_x = scissor.x.saturating_add_unsigned(scissor.width).max(0) as u32;
-        let scissor_end_y = scissor.y.saturating_add_unsigned(scissor.height).max(0) as u32;
-
-        x0 = x0.max(scissor_start_x);
-        y0 = y0.max(scissor_start_y);
-        x1 = x1.min(scissor_end_x);
-        y1 = y1.min(scissor_end_y);
-
-        if x0 >= x1 || y0 >= y1 {
+        self.touch();
+        let Some(area) = self.clamp_fill_area(x, y, w, h) else {
             return;
-        }
+        };
 
-        let opacity = self.opacity_stack.current();
-        let needs_blend = opacity < 1.0 || !bg.is_opaque();
+        let opacity = self.opacity_stack.channels();
+        let needs_blend = !opacity.is_opaque() || !bg.is_opaque();
         let mut cell = Cell::clear(bg);
-        if opacity < 1.0 {
+        if !opacity.is_opaque() {
             cell.blend_with_opacity(opacity);
         }
 
         // Optimized path for opaque fill (erasure) or when alpha is disabled
-        if !needs_blend || !self.respect_alpha {
-            let row_width = self.width as usize;
-            for row in y0..y1 {
-                let row_start = row as usize * row_width;
-                let start = row_start + x0 as usize;
-                let end = row_start + x1 as usize;
-                self.cells[start..end].fill(cell);
+        let overwrite = !needs_blend || !self.respect_alpha;
+        let row_width = self.width as usize;
+        // A multi-rect scissor splits each row into one span per sub-rect.
+        for span in self.scissor_stack.rects() {
+            let Some(span) = span.intersect(&area) else {
+                continue;
+            };
+            let (x0, x1) = (span.x as usize, rect_end(span).0);
+            for row in span.y as usize..rect_end(span).1 {
+                let x1 = x1.min(self.row_width(row as u32) as usize);
+                if x1 <= x0 {
+                    continue;
+                }
+                let row_cells = &mut self.cells[row * row_width + x0..row * row_width + x1];
+                if overwrite {
+                    row_cells.fill(cell);
+                } else {
+                    // Blending path for transparent fill (overlay/tint)
+                    for dest_cell in row_cells {
+                        *dest_cell = cell.blend_over(dest_cell);
+                    }
+                }
             }
+        }
+    }
+
+    /// Write one cell per byte of printable ASCII `text` on row `y`,
+    /// blended like [`Self::set_blended`].
+    ///
+    /// Every byte is one column, so the visible part of the run is found
+    /// once per scissor span rather than checked per cell.
+    fn set_ascii_run_blended(&mut self, x: u32, y: u32, text: &[u8], mut template: Cell) {
+        self.touch();
+        let len = u32::try_from(text.len()).unwrap_or(u32::MAX);
+        let Some(area) = self.clamp_fill_area(x, y, len, 1) else {
             return;
+        };
+
+        let opacity = self.opacity_stack.channels();
+        if !opacity.is_opaque() {
+            template.blend_with_opacity(opacity);
         }
 
-        // Blending path for transparent fill (overlay/tint)
-        let row_width = self.width as usize;
-        for row in y0..y1 {
-            let row_start = row as usize * row_width;
-            for col in x0..x1 {
-                let dest_idx = row_start + col as usize;
-                let dest_cell = &mut self.cells[dest_idx];
-                *dest_cell = cell.blend_over(dest_cell);
+        let respect_alpha = self.respect_alpha;
+        // An opaque foreground over a clear background blends to the
+        // template with the destination's background, so the common case
+        // skips per-cell color compositing.
+        let keep_bg = template.fg.a >= 1.0 && template.bg.a <= 0.0;
+        let row_start = y as usize * self.width as usize;
+        let row_width = self.row_width(y) as usize;
+        for span in self.scissor_stack.rects() {
+            let Some(span) = span.intersect(&area) else {
+                continue;
+            };
+            let (x0, x1) = (span.x as usize, rect_end(span).0.min(row_width));
+            if x1 <= x0 {
+                continue;
+            }
+            let bytes = &text[x0 - x as usize..x1 - x as usize];
+            let cells = &mut self.cells[row_start + x0..row_start + x1];
+            for dest in cells.iter() {
+                if let CellContent::Grapheme(id) = dest.content {
+                    if id.pool_id() != 0 {
+                        self.orphaned_graphemes.push(id);
+                    }
+                }
+            }
+            let glyphs = cells.iter_mut().zip(bytes.iter().map(|&b| char::from(b)));
+            if !respect_alpha {
+                for (dest, ch) in glyphs {
+                    *dest = Cell {
+                        content: CellContent::Char(ch),
+                        ..template
+                    };
+                }
+            } else if keep_bg {
+                for (dest, ch) in glyphs {
+                    *dest = Cell {
+                        content: CellContent::Char(ch),
+                        bg: dest.bg,
+                        ..template
+                    };
+                }
+            } else {
+                for (dest, ch) in glyphs {
+                    let cell = Cell {
+                        content: CellContent::Char(ch),
+                        ..template
+                    };
+                    *dest = cell.blend_over(dest);
+                }
             }
         }
     }
 
+    /// Clamp a fill rectangle to the buffer, returning `None` if nothing of
+    /// it is on screen.
+    fn clamp_fill_area(&self, x: u32, y: u32, w: u32, h: u32) -> Option<ClipRect> {
+        let x0 = x.min(self.width);
+        let y0 = y.min(self.height);
+        let x1 = x.saturating_add(w).min(self.width);
+        let y1 = y.saturating_add(h).min(self.height);
+        (x0 < x1 && y0 < y1).then(|| ClipRect::new(x0 as i32, y0 as i32, x1 - x0, y1 - y0))
+    }
+
     /// Fill a rectangular region with background color, updating grapheme pool counts.
     pub fn fill_rect_with_pool(
         &mut self,
@@ -466,85 +611,203 @@ impl OptimizedBuffer {
         h: u32,
         bg: Rgba,
     ) {
-        if w == 0 || h == 0 || self.width == 0 || self.height == 0 {
+        self.touch();
+        let Some(area) = self.clamp_fill_area(x, y, w, h) else {
             return;
+        };
+
+        let opacity = self.opacity_stack.channels();
+        let needs_blend = !opacity.is_opaque() || !bg.is_opaque();
+        let mut cell = Cell::clear(bg);
+        if !opacity.is_opaque() {
+            cell.blend_with_opacity(opacity);
         }
 
-        let mut x0 = x.min(self.width);
-        let mut y0 = y.min(self.height);
-        let mut x1 = x.saturating_add(w).min(self.width);
-        let mut y1 = y.saturating_add(h).min(self.height);
+        let row_width = self.width as usize;
+        let overwrite = !needs_blend || !self.respect_alpha;
 
-        if x0 >= x1 || y0 >= y1 {
-            return;
-        }
+        for span in self.scissor_stack.rects() {
+            let Some(span) = span.intersect(&area) else {
+                continue;
+            };
+            let (x0, x1) = (span.x as usize, rect_end(span).0);
+            for row in span.y as usize..rect_end(span).1 {
+                let x1 = x1.min(self.row_width(row as u32) as usize);
+                if x1 <= x0 {
+                    continue;
+                }
+                for dest_cell in &mut self.cells[row * row_width + x0..row * row_width + x1] {
+                    let old_content = dest_cell.content;
+                    // Optimized path for opaque fill (erasure) or when alpha is disabled;
+                    // otherwise blend for transparent fill (overlay/tint)
+                    let new_cell = if overwrite {
+                        cell
+                    } else {
+                        cell.blend_over(dest_cell)
+                    };
+
+                    if old_content != new_cell.content {
+                        if let CellContent::Grapheme(id) = old_content {
+                            if id.pool_id() != 0 {
+                                pool.decref(id);
+                            }
+                        }
+                    }
 
-        let scissor = self.scissor_stack.current();
-        if scissor.is_empty() {
-            return;
+                    *dest_cell = new_cell;
+                }
+            }
         }
+    }
 
-        let scissor_start_x = scissor.x.max(0) as u32;
-        let scissor_start_y = scissor.y.max(0) as u32;
-        let scissor_end_x = scissor.x.saturating_add_unsigned(scissor.width).max(0) as u32;
-        let scissor_end_y = scissor.y.saturating_add_unsigned(scissor.height).max(0) as u32;
+    /// Fill a rectangular region's background with a gradient.
+    ///
+    /// Each cell is filled like [`Self::fill_rect`], respecting the scissor,
+    /// opacity, and alpha blending.
+    pub fn fill_rect_gradient(&mut self, x: u32, y: u32, w: u32, h: u32, spec: &GradientSpec) {
+        fill::fill_rect_gradient(self, x, y, w, h, spec);
+    }
 
-        x0 = x0.max(scissor_start_x);
-        y0 = y0.max(scissor_start_y);
-        x1 = x1.min(scissor_end_x);
-        y1 = y1.min(scissor_end_y);
+    /// Fill a rect with a pattern anchored at its top-left corner.
+    pub fn fill_rect_pattern(&mut self, rect: Rect, pattern: &Pattern) {
+        fill::fill_rect_pattern(self, rect, pattern);
+    }
 
-        if x0 >= x1 || y0 >= y1 {
+    /// Move the contents of `rect` by `dx` columns and `dy` rows.
+    ///
+    /// Positive deltas move content right and down. Content moved past the
+    /// rect's edge is dropped, and the cells it exposes are set to `fill`.
+    /// Wide characters split by the move, inside the rect or straddling its
+    /// edge, have their orphaned halves blanked. The rect is clipped to the
+    /// buffer and current scissor (its bounding box, for a multi-rect
+    /// scissor); opacity doesn't apply.
+    ///
+    /// Note: Pooled graphemes dropped from the rect are tracked for later
+    /// cleanup; [`Self::scroll_rect_with_pool`] releases them immediately.
+    /// `fill` is copied as-is, so it shouldn't hold a pooled grapheme.
+    pub fn scroll_rect(&mut self, rect: ClipRect, dy: i32, dx: i32, fill: Cell) {
+        self.touch();
+        let bounds = ClipRect::new(0, 0, self.width, self.height);
+        let Some(rect) = rect
+            .intersect(&bounds)
+            .and_then(|rect| rect.intersect(&self.scissor_stack.current()))
+        else {
             return;
-        }
-
-        let opacity = self.opacity_stack.current();
-        let needs_blend = opacity < 1.0 || !bg.is_opaque();
-        let mut cell = Cell::clear(bg);
-        if opacity < 1.0 {
-            cell.blend_with_opacity(opacity);
-        }
+        };
 
+        let (x0, y0) = (rect.x as usize, rect.y as usize);
+        let (w, h) = (rect.width as usize, rect.height as usize);
         let row_width = self.width as usize;
-
-        // Optimized path for opaque fill (erasure) or when alpha is disabled
-        if !needs_blend || !self.respect_alpha {
-            for row in y0..y1 {
-                let row_start = row as usize * row_width;
-                for col in x0..x1 {
-                    let idx = row_start + col as usize;
-                    if let CellContent::Grapheme(id) = self.cells[idx].content {
-                        if id.pool_id() != 0 {
-                            pool.decref(id);
-                        }
+        let shift_x = (dx.unsigned_abs() as usize).min(w);
+        let shift_y = (dy.unsigned_abs() as usize).min(h);
+
+        // Content that lands outside the rect is dropped.
+        for row in 0..h {
+            for col in 0..w {
+                let kept_row = if dy >= 0 {
+                    row + shift_y < h
+                } else {
+                    row >= shift_y
+                };
+                let kept_col = if dx >= 0 {
+                    col + shift_x < w
+                } else {
+                    col >= shift_x
+                };
+                if kept_row && kept_col {
+                    continue;
+                }
+                if let CellContent::Grapheme(id) =
+                    self.cells[(y0 + row) * row_width + x0 + col].content
+                {
+                    if id.pool_id() != 0 {
+                        self.orphaned_graphemes.push(id);
                     }
-                    self.cells[idx] = cell;
                 }
             }
-            return;
         }
 
-        // Blending path for transparent fill (overlay/tint)
-        for row in y0..y1 {
-            let row_start = row as usize * row_width;
-            for col in x0..x1 {
-                let dest_idx = row_start + col as usize;
-                let dest_cell = &mut self.cells[dest_idx];
-                let old_content = dest_cell.content;
-                let new_cell = cell.blend_over(dest_cell);
-                let new_content = new_cell.content;
-
-                if old_content != new_content {
-                    if let CellContent::Grapheme(id) = old_content {
-                        if id.pool_id() != 0 {
-                            pool.decref(id);
-                        }
-                    }
-                }
+        // Copy rows in the order that reads each source before it's overwritten.
+        let (src_col, dest_col) = if dx >= 0 { (0, shift_x) } else { (shift_x, 0) };
+        let copy_width = w - shift_x;
+        for i in 0..h - shift_y {
+            let (src_row, dest_row) = if dy >= 0 {
+                let row = h - shift_y - 1 - i;
+                (row, row + shift_y)
+            } else {
+                (i + shift_y, i)
+            };
+            let src = (y0 + src_row) * row_width + x0 + src_col;
+            let dest = (y0 + dest_row) * row_width + x0 + dest_col;
+            self.cells.copy_within(src..src + copy_width, dest);
+        }
 
-                *dest_cell = new_cell;
+        let exposed_rows = if dy >= 0 { 0..shift_y } else { h - shift_y..h };
+        let exposed_cols = if dx >= 0 { 0..shift_x } else { w - shift_x..w };
+        for row in 0..h {
+            let row_start = (y0 + row) * row_width + x0;
+            if exposed_rows.contains(&row) {
+                self.cells[row_start..row_start + w].fill(fill);
+            } else {
+                let cols = row_start + exposed_cols.start..row_start + exposed_cols.end;
+                self.cells[cols].fill(fill);
             }
         }
+
+        for row in y0..y0 + h {
+            self.blank_split_wide_chars(row);
+        }
+    }
+
+    /// Scroll a region like [`Self::scroll_rect`], releasing pooled graphemes
+    /// dropped from the rect.
+    ///
+    /// Also releases any orphaned graphemes from prior non-pool operations.
+    pub fn scroll_rect_with_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        rect: ClipRect,
+        dy: i32,
+        dx: i32,
+        fill: Cell,
+    ) {
+        self.scroll_rect(rect, dy, dx, fill);
+        self.drain_orphaned_graphemes(pool);
+    }
+
+    /// Move the contents of `rect` up by `lines` rows, filling the bottom.
+    pub fn scroll_up(&mut self, rect: ClipRect, lines: u32, fill: Cell) {
+        self.scroll_rect(rect, -i32::try_from(lines).unwrap_or(i32::MAX), 0, fill);
+    }
+
+    /// Move the contents of `rect` down by `lines` rows, filling the top.
+    pub fn scroll_down(&mut self, rect: ClipRect, lines: u32, fill: Cell) {
+        self.scroll_rect(rect, i32::try_from(lines).unwrap_or(i32::MAX), 0, fill);
+    }
+
+    /// Blank wide characters in row `y` that are missing continuation cells,
+    /// and continuation cells that are missing their wide character.
+    fn blank_split_wide_chars(&mut self, y: usize) {
+        self.touch();
+        let row_width = self.width as usize;
+        let row = &mut self.cells[y * row_width..(y + 1) * row_width];
+        let mut x = 0;
+        while x < row.len() {
+            let width = row[x].display_width().max(1);
+            if !row[x].is_continuation()
+                && (1..width).all(|i| row.get(x + i).is_some_and(Cell::is_continuation))
+            {
+                x += width;
+                continue;
+            }
+            if let CellContent::Grapheme(id) = row[x].content {
+                if id.pool_id() != 0 {
+                    self.orphaned_graphemes.push(id);
+                }
+            }
+            row[x].content = CellContent::Empty;
+            x += 1;
+        }
     }
 
     /// Draw text at position with style.
@@ -570,6 +833,68 @@ impl OptimizedBuffer {
         drawing::draw_text_with_pool(self, pool, x, y, text, style);
     }
 
+    /// Draw single-line text in a `width`-column field.
+    ///
+    /// Text narrower than the field is placed per `align`; wider text is cut
+    /// at grapheme boundaries per `truncate`, with `ellipsis` marking the
+    /// cut. A wide char straddling the cut becomes a space. The rest of the
+    /// field is padded with spaces in `style`. Returns the columns the text
+    /// itself occupies.
+    ///
+    /// **Note:** Multi-codepoint graphemes are stored with placeholder IDs.
+    /// For emoji, use [`Self::draw_text_aligned_with_pool`].
+    pub fn draw_text_aligned(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        text: &str,
+        style: Style,
+        align: Align,
+        truncate: Truncate,
+        ellipsis: &str,
+    ) -> Range<u32> {
+        aligned::draw_text_aligned(self, x, y, width, text, style, align, truncate, ellipsis)
+    }
+
+    /// Draw single-line text in a field like [`Self::draw_text_aligned`],
+    /// allocating grapheme IDs from the pool.
+    pub fn draw_text_aligned_with_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        x: u32,
+        y: u32,
+        width: u32,
+        text: &str,
+        style: Style,
+        align: Align,
+        truncate: Truncate,
+        ellipsis: &str,
+    ) -> Range<u32> {
+        aligned::draw_text_aligned_with_pool(
+            self, pool, x, y, width, text, style, align, truncate, ellipsis,
+        )
+    }
+
+    /// Draw `text` as a paragraph inside `rect`.
+    ///
+    /// Lines break at `\n` and wrap per `opts.wrap`: word wrapping breaks at
+    /// spaces and punctuation, and splits CJK text and long URLs at the rect
+    /// edge. Tabs expand to `opts.tab_width` stops. Lines that don't fit
+    /// vertically are dropped, and `opts.overflow` ends the last line shown.
+    /// Every cell of `rect` is drawn, so the block replaces what was under it.
+    ///
+    /// **Note:** Multi-codepoint graphemes are stored with placeholder IDs.
+    pub fn draw_text_block(
+        &mut self,
+        rect: Rect,
+        text: &str,
+        style: Style,
+        opts: &TextBlockOptions,
+    ) -> TextBlockMetrics {
+        text_block::draw_text_block(self, rect, text, style, opts)
+    }
+
     /// Draw a single grapheme at position, allocating from pool if needed.
     pub fn draw_char_with_pool(
         &mut self,
@@ -582,7 +907,41 @@ impl OptimizedBuffer {
         drawing::draw_char_with_pool(self, pool, x, y, grapheme, style);
     }
 
+    /// Draw styled spans left to right from `(x, y)`.
+    ///
+    /// Each span's link ID (if it's live in `links`) is applied to its cells.
+    /// Cells outside the scissor are clipped. Returns the columns the spans
+    /// advanced, including any clipped ones.
+    pub fn draw_spans(
+        &mut self,
+        x: u32,
+        y: u32,
+        spans: &[Span<'_>],
+        pool: &mut GraphemePool,
+        links: &LinkPool,
+    ) -> u32 {
+        spans::draw_spans(self, x, y, spans, pool, links)
+    }
+
+    /// Draw styled spans as a paragraph inside `rect`, wrapping per `wrap`.
+    ///
+    /// Returns the number of rows used.
+    pub fn draw_spans_wrapped(
+        &mut self,
+        rect: Rect,
+        spans: &[Span<'_>],
+        wrap: WrapMode,
+        pool: &mut GraphemePool,
+        links: &LinkPool,
+    ) -> u32 {
+        spans::draw_spans_wrapped(self, rect, spans, wrap, pool, links)
+    }
+
     /// Draw a box border.
+    ///
+    /// Corners and edges landing on existing line glyphs merge into
+    /// junctions, so a box drawn across a splitter gets `├`/`┤` where they
+    /// meet.
     pub fn draw_box(&mut self, x: u32, y: u32, w: u32, h: u32, style: BoxStyle) {
         drawing::draw_box(self, x, y, w, h, style);
     }
@@ -592,6 +951,102 @@ impl OptimizedBuffer {
         drawing::draw_box_with_options(self, x, y, w, h, options);
     }
 
+    /// Draw a horizontal line of `len` cells.
+    ///
+    /// Where the line crosses or meets existing box-drawing glyphs, the cell
+    /// becomes the matching junction (`┼`, `├`, `╤`, ...).
+    pub fn draw_hline(&mut self, x: u32, y: u32, len: u32, line: LineStyle, style: Style) {
+        lines::draw_hline(self, x, y, len, line, style);
+    }
+
+    /// Draw a vertical line of `len` cells, merging junctions like
+    /// [`Self::draw_hline`].
+    pub fn draw_vline(&mut self, x: u32, y: u32, len: u32, line: LineStyle, style: Style) {
+        lines::draw_vline(self, x, y, len, line, style);
+    }
+
+    /// Draw a one-row progress bar with eighth-block precision.
+    pub fn draw_progress_bar(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        fraction: f32,
+        style: ProgressStyle,
+    ) {
+        drawing::draw_progress_bar(self, x, y, width, fraction, style);
+    }
+
+    /// Draw a scrollbar filling `rect`, returning the rows or columns the
+    /// thumb covers (`None` when the content fits its viewport).
+    pub fn draw_scrollbar(
+        &mut self,
+        rect: Rect,
+        info: ScrollInfo,
+        style: ScrollbarStyle,
+    ) -> Option<Range<u32>> {
+        scrollbar::draw_scrollbar(self, rect, info, style)
+    }
+
+    /// Draw a one-row indeterminate progress bar whose highlight slides
+    /// across the track as `t` (seconds) advances.
+    pub fn draw_indeterminate_bar(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        t: f32,
+        style: ProgressStyle,
+    ) {
+        spinner::draw_indeterminate_bar(self, x, y, width, t, style);
+    }
+
+    /// Draw the last `width` samples as a one-row sparkline.
+    pub fn draw_sparkline(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        samples: &[f32],
+        style: SparkStyle,
+    ) {
+        drawing::draw_sparkline(self, x, y, width, samples, style);
+    }
+
+    /// Draw a progress bar filling a bordered box.
+    pub fn draw_gauge(&mut self, rect: Rect, fraction: f32, style: GaugeStyle) {
+        drawing::draw_gauge(self, rect, fraction, style);
+    }
+
+    /// Draw a table inside `rect`, returning the x range of each column.
+    ///
+    /// See [`draw_table`] for how columns are sized.
+    pub fn draw_table(&mut self, rect: Rect, table: &Table<'_>) -> Vec<Range<u32>> {
+        table::draw_table(self, rect, table)
+    }
+
+    /// Draw `text` in large block glyphs with its top-left corner at
+    /// (`x`, `y`).
+    ///
+    /// See [`draw_big_text`] for how glyphs are drawn and scaled.
+    pub fn draw_big_text(
+        &mut self,
+        x: u32,
+        y: u32,
+        text: &str,
+        style: Style,
+        font: BigFont,
+        scale: u32,
+    ) {
+        big_text::draw_big_text(self, x, y, text, style, font, scale);
+    }
+
+    /// Stamp a sprite with its hotspot at (`x`, `y`), skipping its
+    /// transparent and color-keyed cells.
+    pub fn draw_sprite(&mut self, x: i32, y: i32, sprite: &Sprite, opts: &SpriteOptions<'_>) {
+        sprite::draw_sprite(self, x, y, sprite, opts);
+    }
+
     /// Draw a text buffer view to this buffer.
     ///
     /// This is a convenience method that calls [`TextBufferView::render_to`].
@@ -643,6 +1098,14 @@ impl OptimizedBuffer {
         self.scissor_stack.push(rect);
     }
 
+    /// Push a scissor made of several rectangles.
+    ///
+    /// Drawing is visible inside any of `rects` that also lies inside the
+    /// current scissor. See [`ScissorStack::push_multi`].
+    pub fn push_scissor_multi(&mut self, rects: &[ClipRect]) {
+        self.scissor_stack.push_multi(rects);
+    }
+
     /// Pop the top scissor rectangle.
     pub fn pop_scissor(&mut self) {
         self.scissor_stack.pop();
@@ -655,11 +1118,24 @@ impl OptimizedBuffer {
 
     // Opacity stack operations
 
-    /// Push an opacity value onto the stack.
+    /// Push an opacity value onto the stack, scaling both foreground and
+    /// background colors.
     pub fn push_opacity(&mut self, opacity: f32) {
         self.opacity_stack.push(opacity);
     }
 
+    /// Push an opacity that dims only foreground colors, leaving
+    /// backgrounds solid.
+    pub fn push_opacity_fg(&mut self, opacity: f32) {
+        self.opacity_stack.push_fg(opacity);
+    }
+
+    /// Push an opacity that fades only background colors, leaving text
+    /// solid.
+    pub fn push_opacity_bg(&mut self, opacity: f32) {
+        self.opacity_stack.push_bg(opacity);
+    }
+
     /// Pop the top opacity value.
     pub fn pop_opacity(&mut self) {
         self.opacity_stack.pop();
@@ -671,6 +1147,12 @@ impl OptimizedBuffer {
         self.opacity_stack.current()
     }
 
+    /// Get the current combined foreground and background opacity.
+    #[must_use]
+    pub fn current_opacity_channels(&self) -> Opacity {
+        self.opacity_stack.channels()
+    }
+
     /// Draw another buffer onto this one.
     pub fn draw_buffer(&mut self, x: i32, y: i32, src: &OptimizedBuffer) {
         self.draw_buffer_region(x, y, src, 0, 0, src.width, src.height, true);
@@ -687,8 +1169,50 @@ impl OptimizedBuffer {
         self.draw_buffer_region_with_pool(pool, x, y, src, 0, 0, src.width, src.height, true);
     }
 
+    /// Draw a buffer built against another grapheme pool onto this one,
+    /// translating its graphemes from `src_pool` into `pool`.
+    pub fn draw_buffer_from_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        src_pool: &GraphemePool,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+    ) {
+        self.draw_buffer_region_from_pool(
+            pool, src_pool, x, y, src, 0, 0, src.width, src.height, true,
+        );
+    }
+
+    /// Draw another buffer onto this one, blending with `mode`.
+    ///
+    /// Only blends when alpha is respected; see [`Self::set_respect_alpha`].
+    pub fn draw_buffer_with_mode(
+        &mut self,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+        mode: BlendMode,
+    ) {
+        self.blit_region(x, y, src, 0, 0, src.width, src.height, true, mode);
+    }
+
+    /// Draw another buffer onto this one, blending with `mode` and updating
+    /// grapheme pool counts.
+    pub fn draw_buffer_with_pool_and_mode(
+        &mut self,
+        pool: &mut GraphemePool,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+        mode: BlendMode,
+    ) {
+        self.blit_region_with_pool(
+            pool, None, x, y, src, 0, 0, src.width, src.height, true, mode,
+        );
+    }
+
     /// Draw a region of another buffer onto this one.
-    #[allow(clippy::similar_names)] // dest_x_start/dest_y_start are standard coordinate names
     pub fn draw_buffer_region(
         &mut self,
         x: i32,
@@ -700,6 +1224,33 @@ impl OptimizedBuffer {
         src_h: u32,
         respect_alpha: bool,
     ) {
+        self.blit_region(
+            x,
+            y,
+            src,
+            src_x,
+            src_y,
+            src_w,
+            src_h,
+            respect_alpha,
+            BlendMode::Over,
+        );
+    }
+
+    #[allow(clippy::similar_names)] // dest_x_start/dest_y_start are standard coordinate names
+    fn blit_region(
+        &mut self,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        respect_alpha: bool,
+        mode: BlendMode,
+    ) {
+        self.touch();
         // Clamp source region to source buffer dimensions
         let copy_w = src_w.min(src.width.saturating_sub(src_x));
         let copy_h = src_h.min(src.height.saturating_sub(src_y));
@@ -722,7 +1273,7 @@ impl OptimizedBuffer {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
+        let opacity = self.opacity_stack.channels();
         let use_blend = respect_alpha && self.respect_alpha;
 
         for dest_y in dest_y_start..dest_y_end {
@@ -736,8 +1287,8 @@ impl OptimizedBuffer {
             };
 
             for dest_x in dest_x_start..dest_x_end {
-                // Check scissor clip
-                if !self.scissor_stack.contains(dest_x as i32, dest_y as i32) {
+                // Check scissor clip and double-size rows
+                if !self.is_visible(dest_x, dest_y) {
                     continue;
                 }
 
@@ -752,28 +1303,23 @@ impl OptimizedBuffer {
                 if src_idx >= src.cells.len() || dest_idx >= self.cells.len() {
                     continue;
                 }
-                let src_cell = &src.cells[src_idx];
-                let dest_cell = &mut self.cells[dest_idx];
-
-                if use_blend {
-                    let mut blended = *src_cell;
-                    if opacity < 1.0 {
-                        blended.blend_with_opacity(opacity);
-                    }
-                    *dest_cell = blended.blend_over(dest_cell);
-                } else if opacity < 1.0 {
-                    let mut blended = *src_cell;
-                    blended.blend_with_opacity(opacity);
-                    *dest_cell = blended;
-                } else {
-                    *dest_cell = *src_cell;
-                }
+                blit_cell(
+                    src.cells[src_idx],
+                    &mut self.cells[dest_idx],
+                    opacity,
+                    use_blend,
+                    mode,
+                );
             }
         }
     }
 
     /// Draw a region of another buffer onto this one, updating grapheme pool counts.
-    #[allow(clippy::similar_names)] // dest_x_start/dest_y_start are standard coordinate names
+    ///
+    /// `src` must reference `pool` too. If it is bound to another pool (see
+    /// [`Self::grapheme_pool_id`]) its graphemes can't be resolved here and
+    /// are drawn as blank placeholders of the same width; use
+    /// [`Self::draw_buffer_region_from_pool`] to translate them instead.
     pub fn draw_buffer_region_with_pool(
         &mut self,
         pool: &mut GraphemePool,
@@ -786,6 +1332,71 @@ impl OptimizedBuffer {
         src_h: u32,
         respect_alpha: bool,
     ) {
+        self.blit_region_with_pool(
+            pool,
+            None,
+            x,
+            y,
+            src,
+            src_x,
+            src_y,
+            src_w,
+            src_h,
+            respect_alpha,
+            BlendMode::Over,
+        );
+    }
+
+    /// Draw a region of a buffer built against `src_pool` onto this one.
+    ///
+    /// Graphemes are translated from `src_pool` into `pool` (interning them
+    /// as needed), so the cells drawn hold references in `pool` only and
+    /// `src_pool` is left untouched. When both are the same pool this is
+    /// [`Self::draw_buffer_region_with_pool`].
+    pub fn draw_buffer_region_from_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        src_pool: &GraphemePool,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        respect_alpha: bool,
+    ) {
+        self.blit_region_with_pool(
+            pool,
+            Some(src_pool),
+            x,
+            y,
+            src,
+            src_x,
+            src_y,
+            src_w,
+            src_h,
+            respect_alpha,
+            BlendMode::Over,
+        );
+    }
+
+    #[allow(clippy::similar_names)] // dest_x_start/dest_y_start are standard coordinate names
+    fn blit_region_with_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        src_pool: Option<&GraphemePool>,
+        x: i32,
+        y: i32,
+        src: &OptimizedBuffer,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        respect_alpha: bool,
+        mode: BlendMode,
+    ) {
+        self.touch();
         // Clamp source region to source buffer dimensions
         let copy_w = src_w.min(src.width.saturating_sub(src_x));
         let copy_h = src_h.min(src.height.saturating_sub(src_y));
@@ -808,7 +1419,14 @@ impl OptimizedBuffer {
             return;
         }
 
-        let opacity = self.opacity_stack.current();
+        self.bind_pool(pool);
+        // Source graphemes from another pool are translated when that pool
+        // is at hand and dropped to placeholders otherwise.
+        let src_pool_id = src.grapheme_pool.or_else(|| src_pool.map(GraphemePool::id));
+        let foreign = src_pool_id.is_some_and(|id| id != pool.id());
+        let src_pool = src_pool.filter(|src_pool| Some(src_pool.id()) == src_pool_id);
+
+        let opacity = self.opacity_stack.channels();
         let use_blend = respect_alpha && self.respect_alpha;
 
         for dest_y in dest_y_start..dest_y_end {
@@ -822,8 +1440,8 @@ impl OptimizedBuffer {
             };
 
             for dest_x in dest_x_start..dest_x_end {
-                // Check scissor clip
-                if !self.scissor_stack.contains(dest_x as i32, dest_y as i32) {
+                // Check scissor clip and double-size rows
+                if !self.is_visible(dest_x, dest_y) {
                     continue;
                 }
 
@@ -838,58 +1456,143 @@ impl OptimizedBuffer {
                 if src_idx >= src.cells.len() || dest_idx >= self.cells.len() {
                     continue;
                 }
-                let src_cell = &src.cells[src_idx];
-                let dest_cell = &mut self.cells[dest_idx];
-
-                let old_content = dest_cell.content;
-                let mut new_cell = *src_cell;
-                if use_blend {
-                    if opacity < 1.0 {
-                        new_cell.blend_with_opacity(opacity);
-                    }
-                    new_cell = new_cell.blend_over(dest_cell);
-                } else if opacity < 1.0 {
-                    new_cell.blend_with_opacity(opacity);
-                }
-
-                let new_content = new_cell.content;
-                let new_from_src = !use_blend || !src_cell.content.is_empty();
-
-                if new_from_src {
-                    if let CellContent::Grapheme(id) = new_content {
-                        if id.pool_id() != 0 {
-                            pool.incref(id);
-                        }
-                    }
+                let mut src_cell = src.cells[src_idx];
+                let translated = if foreign {
+                    adopt_grapheme(&mut src_cell, pool, src_pool)
+                } else {
+                    None
+                };
+                blit_cell_with_pool(
+                    pool,
+                    src_cell,
+                    &mut self.cells[dest_idx],
+                    opacity,
+                    use_blend,
+                    mode,
+                );
+                if let Some(id) = translated {
+                    pool.decref(id);
                 }
+            }
+        }
+    }
 
-                if old_content != new_content {
-                    if let CellContent::Grapheme(id) = old_content {
-                        if id.pool_id() != 0 {
-                            pool.decref(id);
-                        }
-                    }
-                } else if new_from_src {
-                    if let CellContent::Grapheme(id) = new_content {
-                        if id.pool_id() != 0 {
-                            pool.decref(id);
-                        }
-                    }
-                }
+    /// Copy a region of this buffer onto an overlapping or disjoint
+    /// destination in the same buffer.
+    ///
+    /// Behaves like copying from a snapshot of the buffer taken before the
+    /// copy, as [`Self::draw_buffer_region`] would with a clone as `src`,
+    /// without the clone. The source is clamped to the buffer; writes are
+    /// clamped to the buffer and respect the scissor and opacity.
+    pub fn copy_region(&mut self, src: ClipRect, dest_x: i32, dest_y: i32, respect_alpha: bool) {
+        self.touch();
+        let opacity = self.opacity_stack.channels();
+        let use_blend = respect_alpha && self.respect_alpha;
+        for (src_idx, dest_idx) in self.region_copy_indices(src, dest_x, dest_y) {
+            if !self.index_in_scissor(dest_idx) {
+                continue;
+            }
+            let src_cell = self.cells[src_idx];
+            blit_cell(
+                src_cell,
+                &mut self.cells[dest_idx],
+                opacity,
+                use_blend,
+                BlendMode::Over,
+            );
+        }
+    }
 
-                *dest_cell = new_cell;
+    /// Copy a region within this buffer like [`Self::copy_region`], updating
+    /// grapheme pool counts for cells duplicated or overwritten.
+    pub fn copy_region_with_pool(
+        &mut self,
+        pool: &mut GraphemePool,
+        src: ClipRect,
+        dest_x: i32,
+        dest_y: i32,
+        respect_alpha: bool,
+    ) {
+        self.touch();
+        let opacity = self.opacity_stack.channels();
+        let use_blend = respect_alpha && self.respect_alpha;
+        for (src_idx, dest_idx) in self.region_copy_indices(src, dest_x, dest_y) {
+            if !self.index_in_scissor(dest_idx) {
+                continue;
             }
+            let src_cell = self.cells[src_idx];
+            blit_cell_with_pool(
+                pool,
+                src_cell,
+                &mut self.cells[dest_idx],
+                opacity,
+                use_blend,
+                BlendMode::Over,
+            );
         }
     }
 
+    /// Check a cell index against the exact scissor region; the index range
+    /// from [`Self::region_copy_indices`] is only clipped to its bounding box.
+    fn index_in_scissor(&self, idx: usize) -> bool {
+        let width = self.width as usize;
+        self.is_visible((idx % width) as u32, (idx / width) as u32)
+    }
+
+    /// Source and destination cell indices for copying `src` to
+    /// `(dest_x, dest_y)`, ordered like `memmove` so every source cell is
+    /// read before it's overwritten.
+    fn region_copy_indices(
+        &self,
+        src: ClipRect,
+        dest_x: i32,
+        dest_y: i32,
+    ) -> impl Iterator<Item = (usize, usize)> + use<> {
+        let bounds = ClipRect::new(0, 0, self.width, self.height);
+        let clip = bounds
+            .intersect(&self.scissor_stack.current())
+            .unwrap_or(ClipRect::new(0, 0, 0, 0));
+        let (off_x, off_y) = (
+            i64::from(dest_x) - i64::from(src.x),
+            i64::from(dest_y) - i64::from(src.y),
+        );
+        let end = |start: i32, len: u32| i64::from(start) + i64::from(len);
+
+        // Destination span: the source clamped to the buffer, shifted, then
+        // clamped to the clip rect.
+        let x0 = (i64::from(src.x).max(0) + off_x).max(i64::from(clip.x));
+        let x1 =
+            (end(src.x, src.width).min(i64::from(self.width)) + off_x).min(end(clip.x, clip.width));
+        let y0 = (i64::from(src.y).max(0) + off_y).max(i64::from(clip.y));
+        let y1 = (end(src.y, src.height).min(i64::from(self.height)) + off_y)
+            .min(end(clip.y, clip.height));
+        let (cols, rows) = ((x1 - x0).max(0), (y1 - y0).max(0));
+
+        let row_width = i64::from(self.width);
+        (0..rows).flat_map(move |i| {
+            let y = if off_y > 0 { y1 - 1 - i } else { y0 + i };
+            (0..cols).map(move |j| {
+                let x = if off_y == 0 && off_x > 0 {
+                    x1 - 1 - j
+                } else {
+                    x0 + j
+                };
+                let src_idx = (y - off_y) * row_width + x - off_x;
+                (src_idx as usize, (y * row_width + x) as usize)
+            })
+        })
+    }
+
     /// Resize buffer, clearing contents.
     ///
     /// Uses saturating multiplication to prevent overflow for extremely large dimensions.
     pub fn resize(&mut self, width: u32, height: u32) {
+        self.touch();
         self.width = width;
         self.height = height;
         let size = (width as usize).saturating_mul(height as usize);
         self.cells = vec![Cell::clear(Rgba::TRANSPARENT); size];
+        self.row_sizes.clear();
         self.scissor_stack.clear();
         self.opacity_stack.clear();
         self.respect_alpha = true;
@@ -923,6 +1626,20 @@ impl OptimizedBuffer {
         self.respect_alpha
     }
 
+    /// Counter bumped by every operation that may change cells.
+    ///
+    /// Equal counts mean the contents are unchanged; a count that moved says
+    /// nothing either way. [`Self::cells_mut`] and [`Self::get_mut`] count as
+    /// changes, as do drawing calls clipped away entirely.
+    #[must_use]
+    pub fn mutation_count(&self) -> u64 {
+        self.mutations
+    }
+
+    fn touch(&mut self) {
+        self.mutations = self.mutations.wrapping_add(1);
+    }
+
     /// Get raw cell slice.
     #[must_use]
     pub fn cells(&self) -> &[Cell] {
@@ -931,6 +1648,7 @@ impl OptimizedBuffer {
 
     /// Get mutable raw cell slice.
     pub fn cells_mut(&mut self) -> &mut [Cell] {
+        self.touch();
         &mut self.cells
     }
 
@@ -944,12 +1662,130 @@ impl OptimizedBuffer {
     }
 }
 
+/// Exclusive column and row end of an on-buffer rect, as indices.
+fn rect_end(rect: ClipRect) -> (usize, usize) {
+    (
+        rect.x as usize + rect.width as usize,
+        rect.y as usize + rect.height as usize,
+    )
+}
+
+/// Composite a source cell onto a destination cell for buffer blits.
+fn blit_cell(src: Cell, dest: &mut Cell, opacity: Opacity, use_blend: bool, mode: BlendMode) {
+    if use_blend {
+        let mut blended = src;
+        if !opacity.is_opaque() {
+            blended.blend_with_opacity(opacity);
+        }
+        *dest = blended.blend_over_mode(dest, mode);
+    } else if !opacity.is_opaque() {
+        let mut blended = src;
+        blended.blend_with_opacity(opacity);
+        *dest = blended;
+    } else {
+        *dest = src;
+    }
+}
+
+/// Point a source cell's grapheme, issued by another pool, at `pool`.
+///
+/// The grapheme is translated from `src_pool` when given and becomes a
+/// placeholder otherwise. Returns the translated ID, whose reference from
+/// [`GraphemePool::translate`] the caller must release.
+fn adopt_grapheme(
+    cell: &mut Cell,
+    pool: &mut GraphemePool,
+    src_pool: Option<&GraphemePool>,
+) -> Option<GraphemeId> {
+    let CellContent::Grapheme(id) = cell.content else {
+        return None;
+    };
+    if id.pool_id() == 0 {
+        return None;
+    }
+    let new_id = src_pool.map_or_else(
+        || GraphemeId::placeholder(id.width() as u8),
+        |src_pool| pool.translate(src_pool, id),
+    );
+    cell.content = CellContent::Grapheme(new_id);
+    (new_id.pool_id() != 0).then_some(new_id)
+}
+
+/// Like [`blit_cell`], updating grapheme pool counts.
+fn blit_cell_with_pool(
+    pool: &mut GraphemePool,
+    src: Cell,
+    dest: &mut Cell,
+    opacity: Opacity,
+    use_blend: bool,
+    mode: BlendMode,
+) {
+    let old_content = dest.content;
+    let mut new_cell = src;
+    if use_blend {
+        if !opacity.is_opaque() {
+            new_cell.blend_with_opacity(opacity);
+        }
+        new_cell = new_cell.blend_over_mode(dest, mode);
+    } else if !opacity.is_opaque() {
+        new_cell.blend_with_opacity(opacity);
+    }
+
+    let new_content = new_cell.content;
+    let new_from_src = !use_blend || !src.content.is_empty();
+
+    if new_from_src {
+        if let CellContent::Grapheme(id) = new_content {
+            if id.pool_id() != 0 {
+                pool.incref(id);
+            }
+        }
+    }
+
+    if old_content != new_content {
+        if let CellContent::Grapheme(id) = old_content {
+            if id.pool_id() != 0 {
+                pool.decref(id);
+            }
+        }
+    } else if new_from_src {
+        if let CellContent::Grapheme(id) = new_content {
+            if id.pool_id() != 0 {
+                pool.decref(id);
+            }
+        }
+    }
+
+    *dest = new_cell;
+}
+
 impl Default for OptimizedBuffer {
     fn default() -> Self {
         Self::new(80, 24)
     }
 }
 
+/// Row `y` as text for drawing tests: continuation cells are skipped and
+/// empty or grapheme cells show as `blank`.
+#[cfg(test)]
+fn glyph_row(buffer: &OptimizedBuffer, y: u32, blank: char) -> String {
+    (0..buffer.width())
+        .filter_map(|x| match buffer.get(x, y).unwrap().content {
+            CellContent::Char(ch) => Some(ch),
+            CellContent::Continuation => None,
+            CellContent::Empty | CellContent::Grapheme(_) => Some(blank),
+        })
+        .collect()
+}
+
+/// Every row as [`glyph_row`] text, each ending in a newline.
+#[cfg(test)]
+fn glyphs(buffer: &OptimizedBuffer, blank: char) -> String {
+    (0..buffer.height())
+        .map(|y| glyph_row(buffer, y, blank) + "\n")
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::float_cmp)] // Exact float comparison is intentional in tests
@@ -973,6 +1809,34 @@ mod tests {
         assert_eq!(buf.cells().len(), 120 * 40);
     }
 
+    #[test]
+    fn test_mutation_count_tracks_writes() {
+        let mut buf = OptimizedBuffer::new(10, 4);
+        let count = buf.mutation_count();
+        let _ = buf.get(1, 1);
+        let _ = buf.cells();
+        buf.push_scissor(ClipRect::new(0, 0, 2, 2));
+        buf.pop_scissor();
+        assert_eq!(buf.mutation_count(), count);
+
+        let steps: [fn(&mut OptimizedBuffer); 7] = [
+            |buf| buf.set(1, 1, Cell::new('a', Style::NONE)),
+            |buf| buf.set_blended(2, 1, Cell::new('b', Style::NONE)),
+            |buf| buf.fill_rect(0, 0, 3, 3, Rgba::RED),
+            |buf| buf.draw_text(0, 2, "text", Style::NONE),
+            |buf| buf.clear(Rgba::BLACK),
+            |buf| buf.draw_buffer(0, 0, &OptimizedBuffer::new(2, 2)),
+            |buf| {
+                buf.cells_mut();
+            },
+        ];
+        for step in steps {
+            let count = buf.mutation_count();
+            step(&mut buf);
+            assert!(buf.mutation_count() > count);
+        }
+    }
+
     #[test]
     fn test_buffer_resize_larger() {
         let mut buf = OptimizedBuffer::new(10, 10);
@@ -1159,6 +2023,329 @@ mod tests {
         assert_eq!(buf.get(9, 9).unwrap().bg, Rgba::BLUE);
     }
 
+    // =========================================================================
+    // Region Scrolling
+    // =========================================================================
+
+    fn patterned(width: u32, height: u32) -> OptimizedBuffer {
+        let mut buf = OptimizedBuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let ch = char::from(b'a' + ((x + y * width) % 26) as u8);
+                buf.set(x, y, Cell::new(ch, Style::NONE));
+            }
+        }
+        buf
+    }
+
+    /// Every wide char is followed by its continuations, and vice versa.
+    fn assert_no_split_wide_chars(buf: &OptimizedBuffer) {
+        for y in 0..buf.height() {
+            let mut pending = 0;
+            for x in 0..buf.width() {
+                let cell = buf.get(x, y).unwrap();
+                if cell.is_continuation() {
+                    assert!(pending > 0, "orphaned continuation at ({x}, {y})");
+                    pending -= 1;
+                } else {
+                    assert_eq!(pending, 0, "wide char cut short before ({x}, {y})");
+                    pending = cell.display_width().saturating_sub(1);
+                }
+            }
+            assert_eq!(pending, 0, "wide char cut short at end of row {y}");
+        }
+    }
+
+    /// Each pooled grapheme is referenced exactly once per cell holding it.
+    fn assert_refcounts_match_cells(buf: &OptimizedBuffer, pool: &GraphemePool) {
+        let mut counts = std::collections::HashMap::new();
+        for cell in buf.cells() {
+            if let CellContent::Grapheme(id) = cell.content {
+                *counts.entry(id.pool_id()).or_insert(0) += 1;
+            }
+        }
+        for (&pool_id, &count) in &counts {
+            assert_eq!(pool.refcount(GraphemeId::new(pool_id, 2)), count);
+        }
+        assert_eq!(pool.active_count(), counts.len());
+    }
+
+    #[test]
+    fn test_scroll_rect_all_directions() {
+        let rect = ClipRect::new(1, 1, 5, 4);
+        let fill = Cell::new('.', Style::NONE);
+        for (dx, dy) in [(0, -1), (0, 2), (-2, 0), (1, 0), (2, -3), (-9, 0)] {
+            let original = patterned(8, 6);
+            let mut buf = original.clone();
+            buf.scroll_rect(rect, dy, dx, fill);
+
+            for y in 0..6 {
+                for x in 0..8 {
+                    let inside = rect.contains(x, y);
+                    let (sx, sy) = (x - dx, y - dy);
+                    let expected = if !inside {
+                        original.get(x as u32, y as u32)
+                    } else if rect.contains(sx, sy) {
+                        original.get(sx as u32, sy as u32)
+                    } else {
+                        Some(&fill)
+                    };
+                    assert_eq!(
+                        buf.get(x as u32, y as u32),
+                        expected,
+                        "dx={dx} dy={dy} at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scroll_up_and_down() {
+        let rect = ClipRect::new(0, 1, 4, 3);
+        let fill = Cell::clear(Rgba::BLUE);
+        let original = patterned(4, 5);
+
+        let mut buf = original.clone();
+        buf.scroll_up(rect, 1, fill);
+        assert_eq!(buf.get(2, 1), original.get(2, 2));
+        assert_eq!(buf.get(2, 2), original.get(2, 3));
+        assert_eq!(buf.get(2, 3), Some(&fill));
+        assert_eq!(buf.get(2, 4), original.get(2, 4));
+
+        let mut buf = original.clone();
+        buf.scroll_down(rect, 1, fill);
+        assert_eq!(buf.get(2, 0), original.get(2, 0));
+        assert_eq!(buf.get(2, 1), Some(&fill));
+        assert_eq!(buf.get(2, 2), original.get(2, 1));
+        assert_eq!(buf.get(2, 3), original.get(2, 2));
+
+        // Scrolling by more than the rect's height clears it.
+        let mut buf = original.clone();
+        buf.scroll_up(rect, u32::MAX, fill);
+        assert!((0..4).all(|x| (1..4).all(|y| buf.get(x, y) == Some(&fill))));
+    }
+
+    #[test]
+    fn test_scroll_rect_respects_scissor() {
+        let original = patterned(6, 1);
+        let mut buf = original.clone();
+        buf.push_scissor(ClipRect::new(0, 0, 3, 1));
+        buf.scroll_rect(
+            ClipRect::new(0, 0, 6, 1),
+            0,
+            -1,
+            Cell::new('.', Style::NONE),
+        );
+        buf.pop_scissor();
+
+        assert_eq!(buf.get(0, 0), original.get(1, 0));
+        assert_eq!(buf.get(2, 0).unwrap().content, CellContent::Char('.'));
+        assert_eq!(buf.get(3, 0), original.get(3, 0));
+    }
+
+    #[test]
+    fn test_scroll_rect_blanks_split_wide_chars() {
+        let fill = Cell::new('.', Style::NONE);
+        for dx in [-3, -1, 1, 3] {
+            let mut buf = OptimizedBuffer::new(8, 2);
+            // 日 straddles the rect's left edge and 語 its right edge.
+            buf.draw_text(0, 0, "日本語x", Style::NONE);
+            buf.draw_text(1, 1, "a本b", Style::NONE);
+            buf.scroll_rect(ClipRect::new(1, 0, 4, 2), 0, dx, fill);
+            assert_no_split_wide_chars(&buf);
+        }
+
+        let mut buf = OptimizedBuffer::new(8, 1);
+        buf.draw_text(0, 0, "日本語x", Style::NONE);
+        buf.scroll_rect(ClipRect::new(1, 0, 4, 1), 0, 1, fill);
+        // 日 lost its continuation, 本 its wide char, and 本 moved onto 語.
+        let row: Vec<_> = (0..8).map(|x| buf.get(x, 0).unwrap().content).collect();
+        assert_eq!(
+            row,
+            [
+                CellContent::Empty,
+                CellContent::Char('.'),
+                CellContent::Empty,
+                CellContent::Char('本'),
+                CellContent::Continuation,
+                CellContent::Empty,
+                CellContent::Char('x'),
+                CellContent::Empty,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scroll_rect_with_pool_releases_graphemes() {
+        let mut pool = GraphemePool::new();
+        let mut buf = OptimizedBuffer::new(8, 4);
+        for y in 0..4 {
+            // The last emoji straddles the rect's right edge.
+            buf.draw_text_with_pool(&mut pool, 0, y, "👍🏽👨‍👩‍👧🏳️‍🌈👋🏽", Style::NONE);
+        }
+        let rect = ClipRect::new(0, 0, 7, 4);
+        let fill = Cell::new(' ', Style::NONE);
+
+        assert_refcounts_match_cells(&buf, &pool);
+
+        for (dx, dy) in [(0, -1), (0, 1), (-2, 0), (3, 0), (1, -2)] {
+            buf.scroll_rect_with_pool(&mut pool, rect, dy, dx, fill);
+            assert_no_split_wide_chars(&buf);
+            assert_refcounts_match_cells(&buf, &pool);
+        }
+
+        buf.clear_with_pool(&mut pool, Rgba::BLACK);
+        assert_eq!(pool.active_count(), 0);
+    }
+
+    #[test]
+    fn test_copy_region_matches_copy_from_clone() {
+        let src = ClipRect::new(2, 1, 5, 4);
+        for (respect_alpha, (dx, dy)) in [false, true].into_iter().flat_map(|alpha| {
+            [(1, 1), (-1, -1), (3, 0), (-2, 0), (0, 2), (6, -3)].map(|d| (alpha, d))
+        }) {
+            let mut buf = patterned(10, 7);
+            buf.fill_rect(0, 0, 10, 3, Rgba::RED.with_alpha(0.5));
+            let mut reference = buf.clone();
+            let snapshot = buf.clone();
+
+            buf.copy_region(src, src.x + dx, src.y + dy, respect_alpha);
+            reference.draw_buffer_region(
+                src.x + dx,
+                src.y + dy,
+                &snapshot,
+                src.x as u32,
+                src.y as u32,
+                src.width,
+                src.height,
+                respect_alpha,
+            );
+            assert_eq!(
+                buf.cells(),
+                reference.cells(),
+                "alpha={respect_alpha} dx={dx} dy={dy}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_copy_region_clamps_and_respects_scissor() {
+        let original = patterned(6, 4);
+        let mut buf = original.clone();
+        buf.push_scissor(ClipRect::new(0, 0, 6, 2));
+        // The source hangs off the top-left corner, so only columns 0-2 and
+        // rows 0-2 of it exist; they land at (2, 1), where the scissor keeps
+        // only the first row.
+        buf.copy_region(ClipRect::new(-2, -1, 5, 4), 0, 0, false);
+        buf.pop_scissor();
+
+        for y in 0..4 {
+            for x in 0..6 {
+                let expected = if y == 1 && (2..5).contains(&x) {
+                    original.get(x - 2, 0)
+                } else {
+                    original.get(x, y)
+                };
+                assert_eq!(buf.get(x, y), expected, "at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_copy_region_with_pool_keeps_refcounts() {
+        let mut pool = GraphemePool::new();
+        let mut buf = OptimizedBuffer::new(10, 4);
+        for y in 0..4 {
+            buf.draw_text_with_pool(&mut pool, 0, y, "👍🏽a👨‍👩‍👧b🏳️‍🌈", Style::NONE);
+        }
+        for (dx, dy) in [(1, 1), (-1, -1), (2, 0), (-3, 2)] {
+            buf.copy_region_with_pool(&mut pool, ClipRect::new(1, 1, 7, 3), 1 + dx, 1 + dy, false);
+            assert_refcounts_match_cells(&buf, &pool);
+        }
+
+        buf.clear_with_pool(&mut pool, Rgba::BLACK);
+        assert_eq!(pool.active_count(), 0);
+    }
+
+    #[test]
+    fn test_pool_aware_drawing_binds_buffer() {
+        let mut pool = GraphemePool::new();
+        let mut buf = OptimizedBuffer::new(10, 1);
+        assert_eq!(buf.grapheme_pool_id(), None);
+        buf.draw_text_with_pool(&mut pool, 0, 0, "👨‍👩‍👧", Style::NONE);
+        assert_eq!(buf.grapheme_pool_id(), Some(pool.id()));
+
+        // An existing binding is kept.
+        let other = GraphemePool::new();
+        let bound = OptimizedBuffer::new(4, 1).with_grapheme_pool(&other);
+        let mut bound_copy = bound.clone();
+        bound_copy.draw_text_with_pool(&mut pool, 0, 0, "x", Style::NONE);
+        assert_eq!(bound_copy.grapheme_pool_id(), Some(other.id()));
+    }
+
+    #[test]
+    fn test_draw_buffer_from_pool_translates_graphemes() {
+        let mut src_pool = GraphemePool::new();
+        let mut src = OptimizedBuffer::new(10, 1);
+        src.draw_text_with_pool(&mut src_pool, 0, 0, "a👨‍👩‍👧b🏳️‍🌈", Style::NONE);
+        let src_ids: Vec<_> = src
+            .cells()
+            .iter()
+            .filter_map(|cell| match cell.content {
+                CellContent::Grapheme(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+
+        let mut pool = GraphemePool::new();
+        let _ = pool.alloc("padding");
+        let mut dest = OptimizedBuffer::new(10, 2);
+        dest.draw_buffer_from_pool(&mut pool, &src_pool, 0, 0, &src);
+        dest.draw_buffer_from_pool(&mut pool, &src_pool, 0, 1, &src);
+
+        for x in 0..10 {
+            let (Some(s), Some(d)) = (src.get(x, 0), dest.get(x, 1)) else {
+                unreachable!()
+            };
+            match (s.content, d.content) {
+                (CellContent::Grapheme(s), CellContent::Grapheme(d)) => {
+                    assert_eq!(pool.get(d), src_pool.get(s));
+                    assert_eq!(d.width(), s.width());
+                }
+                (s, d) => assert_eq!(s, d),
+            }
+        }
+        // Two copies of each grapheme, plus the padding entry.
+        assert_eq!(pool.active_count(), src_ids.len() + 1);
+        for id in &src_ids {
+            assert_eq!(src_pool.refcount(*id), 1);
+        }
+
+        dest.clear_with_pool(&mut pool, Rgba::BLACK);
+        assert_eq!(pool.active_count(), 1);
+        src.clear_with_pool(&mut src_pool, Rgba::BLACK);
+        assert_eq!(src_pool.active_count(), 0);
+    }
+
+    #[test]
+    fn test_draw_buffer_with_foreign_pool_drops_graphemes() {
+        let mut src_pool = GraphemePool::new();
+        let mut src = OptimizedBuffer::new(4, 1);
+        src.draw_text_with_pool(&mut src_pool, 0, 0, "👨‍👩‍👧", Style::NONE);
+
+        let mut pool = GraphemePool::new();
+        let mut dest = OptimizedBuffer::new(4, 1);
+        dest.draw_buffer_with_pool(&mut pool, 0, 0, &src);
+
+        let Some(CellContent::Grapheme(id)) = dest.get(0, 0).map(|cell| cell.content) else {
+            panic!("expected a placeholder grapheme");
+        };
+        assert_eq!(id, GraphemeId::placeholder(2));
+        assert_eq!(pool.active_count(), 0);
+        assert_eq!(src_pool.active_count(), 1);
+    }
+
     // =========================================================================
     // Scissor Stack
     // =========================================================================
@@ -1208,6 +2395,55 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_fill_rect_multi_scissor_covers_exact_cells() {
+        let mut buf = OptimizedBuffer::new(8, 6);
+        let l_shape = [ClipRect::new(1, 1, 6, 2), ClipRect::new(1, 3, 2, 2)];
+        let inside = |x: u32, y: u32| l_shape.iter().any(|rect| rect.contains(x as i32, y as i32));
+
+        for bg in [Rgba::RED, Rgba::BLUE.with_alpha(0.5)] {
+            buf.clear(Rgba::BLACK);
+            buf.push_scissor_multi(&l_shape);
+            buf.fill_rect(0, 0, 8, 6, bg);
+            buf.pop_scissor();
+
+            let expected = bg.blend_over(Rgba::BLACK);
+            for y in 0..6 {
+                for x in 0..8 {
+                    let cell_bg = buf.get(x, y).unwrap().bg;
+                    if inside(x, y) {
+                        assert_eq!(cell_bg.to_rgb_u8(), expected.to_rgb_u8(), "({x}, {y})");
+                    } else {
+                        assert_eq!(cell_bg, Rgba::BLACK, "({x}, {y})");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_scissor_nested_in_single_scissor() {
+        let mut buf = OptimizedBuffer::new(10, 4);
+        buf.push_scissor(ClipRect::new(2, 0, 6, 4));
+        buf.push_scissor_multi(&[ClipRect::new(0, 0, 4, 4), ClipRect::new(6, 0, 4, 4)]);
+        buf.fill_rect(0, 0, 10, 4, Rgba::RED);
+        buf.draw_text(0, 1, "0123456789", Style::NONE);
+        buf.pop_scissor();
+        buf.pop_scissor();
+
+        let row: String = (0..10)
+            .map(|x| match buf.get(x, 1).unwrap().content {
+                CellContent::Char(c) => c,
+                _ => '.',
+            })
+            .collect();
+        assert_eq!(row, "..23..67..");
+        for x in 0..10 {
+            let red = buf.get(x, 0).unwrap().bg == Rgba::RED;
+            assert_eq!(red, matches!(x, 2 | 3 | 6 | 7), "column {x}");
+        }
+    }
+
     #[test]
     fn test_scissor_intersection() {
         let mut buf = OptimizedBuffer::new(30, 30);
@@ -1342,6 +2578,38 @@ mod tests {
         buf.pop_opacity();
     }
 
+    #[test]
+    fn test_fg_and_bg_opacity_apply_separately() {
+        let mut buf = OptimizedBuffer::new(10, 3);
+        // Store cells as drawn so the alphas aren't composited away.
+        buf.set_respect_alpha(false);
+        let style = Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+
+        buf.push_opacity_fg(0.25);
+        buf.draw_text(0, 0, "dim", style);
+        buf.pop_opacity();
+
+        buf.push_opacity_bg(0.5);
+        buf.draw_text(0, 1, "glass", style);
+        buf.pop_opacity();
+
+        buf.push_opacity_fg(0.5);
+        buf.push_opacity(0.5);
+        assert_eq!(buf.current_opacity_channels(), Opacity::new(0.25, 0.5));
+        buf.draw_text(0, 2, "both", style);
+        buf.pop_opacity();
+        buf.pop_opacity();
+
+        let alphas = |y| {
+            let cell = buf.get(0, y).unwrap();
+            (cell.fg.a, cell.bg.a)
+        };
+        assert_eq!(alphas(0), (0.25, 1.0));
+        assert_eq!(alphas(1), (1.0, 0.5));
+        assert_eq!(alphas(2), (0.25, 0.5));
+        assert_eq!(buf.current_opacity_channels(), Opacity::OPAQUE);
+    }
+
     #[test]
     fn test_opacity_affects_fill_rect() {
         let mut buf = OptimizedBuffer::new(10, 10);
@@ -1404,6 +2672,20 @@ mod tests {
         assert_eq!(dst.get(0, 0).unwrap().bg, Rgba::TRANSPARENT);
     }
 
+    #[test]
+    fn test_draw_buffer_with_mode() {
+        let mut src = OptimizedBuffer::new(2, 1);
+        src.fill_rect(0, 0, 2, 1, Rgba::rgb(0.5, 0.5, 0.5));
+
+        let mut dst = OptimizedBuffer::new(3, 1);
+        dst.fill_rect(0, 0, 3, 1, Rgba::rgb(0.0, 0.5, 1.0));
+        dst.draw_buffer_with_mode(1, 0, &src, BlendMode::Screen);
+
+        assert_eq!(dst.get(0, 0).unwrap().bg, Rgba::rgb(0.0, 0.5, 1.0));
+        assert_eq!(dst.get(1, 0).unwrap().bg.to_rgb_u8(), (128, 191, 255));
+        assert_eq!(dst.get(2, 0).unwrap().bg.to_rgb_u8(), (128, 191, 255));
+    }
+
     // =========================================================================
     // Alpha Blending
     // =========================================================================
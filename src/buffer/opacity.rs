@@ -1,10 +1,79 @@
 //! Opacity stack for layered rendering.
 
+/// Separate opacity factors for a cell's foreground and background.
+///
+/// A plain `f32` converts into a uniform `Opacity` that scales both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Opacity {
+    /// Factor applied to the foreground (text) color's alpha.
+    pub fg: f32,
+    /// Factor applied to the background color's alpha.
+    pub bg: f32,
+}
+
+impl Opacity {
+    /// Full opacity on both channels.
+    pub const OPAQUE: Self = Self { fg: 1.0, bg: 1.0 };
+
+    /// Create an opacity pair, clamping each factor to `0.0..=1.0`.
+    #[must_use]
+    pub fn new(fg: f32, bg: f32) -> Self {
+        Self {
+            fg: fg.clamp(0.0, 1.0),
+            bg: bg.clamp(0.0, 1.0),
+        }
+    }
+
+    /// The same factor on both channels.
+    #[must_use]
+    pub fn uniform(opacity: f32) -> Self {
+        Self::new(opacity, opacity)
+    }
+
+    /// Check if both channels are fully opaque.
+    #[must_use]
+    pub fn is_opaque(self) -> bool {
+        self.fg >= 1.0 && self.bg >= 1.0
+    }
+
+    /// Check if both channels are fully transparent.
+    #[must_use]
+    pub fn is_transparent(self) -> bool {
+        self.fg <= 0.0 && self.bg <= 0.0
+    }
+}
+
+impl Default for Opacity {
+    fn default() -> Self {
+        Self::OPAQUE
+    }
+}
+
+impl From<f32> for Opacity {
+    fn from(opacity: f32) -> Self {
+        Self::uniform(opacity)
+    }
+}
+
+impl std::ops::Mul for Opacity {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            fg: self.fg * rhs.fg,
+            bg: self.bg * rhs.bg,
+        }
+    }
+}
+
 /// Stack of opacity values that multiply together.
+///
+/// Each entry scales the foreground and background independently; the
+/// effective factors are the per-channel products of all entries.
 #[derive(Clone, Debug)]
 pub struct OpacityStack {
-    stack: Vec<f32>,
-    current: f32,
+    stack: Vec<Opacity>,
+    current: Opacity,
 }
 
 impl OpacityStack {
@@ -13,7 +82,7 @@ impl OpacityStack {
     pub fn new() -> Self {
         Self {
             stack: Vec::new(),
-            current: 1.0,
+            current: Opacity::OPAQUE,
         }
     }
 
@@ -21,8 +90,23 @@ impl OpacityStack {
     ///
     /// The effective opacity is the product of all values on the stack.
     pub fn push(&mut self, opacity: f32) {
+        self.push_channels(Opacity::uniform(opacity));
+    }
+
+    /// Push an opacity that scales only foreground colors.
+    pub fn push_fg(&mut self, opacity: f32) {
+        self.push_channels(Opacity::new(opacity, 1.0));
+    }
+
+    /// Push an opacity that scales only background colors.
+    pub fn push_bg(&mut self, opacity: f32) {
+        self.push_channels(Opacity::new(1.0, opacity));
+    }
+
+    /// Push separate foreground and background factors.
+    pub fn push_channels(&mut self, opacity: Opacity) {
         self.stack.push(self.current);
-        self.current *= opacity.clamp(0.0, 1.0);
+        self.current = self.current * Opacity::new(opacity.fg, opacity.bg);
     }
 
     /// Pop the top opacity value from the stack.
@@ -35,25 +119,35 @@ impl OpacityStack {
     /// Clear the stack, resetting to full opacity.
     pub fn clear(&mut self) {
         self.stack.clear();
-        self.current = 1.0;
+        self.current = Opacity::OPAQUE;
     }
 
     /// Get the current combined opacity value.
+    ///
+    /// When only uniform values were pushed this is the factor applied to
+    /// both channels; otherwise it is the larger of the two. Use
+    /// [`Self::channels`] for the per-channel factors.
     #[must_use]
     pub fn current(&self) -> f32 {
+        self.current.fg.max(self.current.bg)
+    }
+
+    /// Get the current combined foreground and background factors.
+    #[must_use]
+    pub fn channels(&self) -> Opacity {
         self.current
     }
 
     /// Check if current opacity is fully opaque.
     #[must_use]
     pub fn is_opaque(&self) -> bool {
-        self.current >= 1.0
+        self.current.is_opaque()
     }
 
     /// Check if current opacity is fully transparent.
     #[must_use]
     pub fn is_transparent(&self) -> bool {
-        self.current <= 0.0
+        self.current.is_transparent()
     }
 }
 
@@ -355,4 +449,33 @@ mod tests {
         s.push(0.8);
         assert!((s.current() - 0.8).abs() < 1e-6);
     }
+
+    // --- Per-channel factors ---
+
+    #[test]
+    fn test_fg_and_bg_factors_are_independent() {
+        let mut s = OpacityStack::new();
+        s.push_fg(0.5);
+        assert_eq!(s.channels(), Opacity::new(0.5, 1.0));
+        assert!(!s.is_opaque());
+
+        s.push_bg(0.25);
+        s.push(0.5);
+        assert_eq!(s.channels(), Opacity::new(0.25, 0.125));
+        assert_eq!(s.current(), 0.25);
+
+        s.pop();
+        s.pop();
+        assert_eq!(s.channels(), Opacity::new(0.5, 1.0));
+        s.pop();
+        assert_eq!(s.channels(), Opacity::OPAQUE);
+    }
+
+    #[test]
+    fn test_push_channels_clamps_each_factor() {
+        let mut s = OpacityStack::new();
+        s.push_channels(Opacity { fg: 2.0, bg: -1.0 });
+        assert_eq!(s.channels(), Opacity::new(1.0, 0.0));
+        assert!(!s.is_transparent());
+    }
 }
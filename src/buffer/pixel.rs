@@ -6,11 +6,18 @@
 //! - **Quadrant blocks**: 2x2 pixel blocks using Unicode 2580-259F characters
 //! - **Grayscale**: Intensity mapping to ASCII/Unicode shade characters
 //! - **Supersampling**: Averaging 2x2 pixel blocks for smoother rendering
+//! - **Cell modes**: Half-block, best-fit quadrant, and braille rendering
+//!   into a rect
+//! - **ASCII art**: Grayscale ramps with optional dithering and colorizing
+
+use std::ops::Range;
 
 use crate::buffer::OptimizedBuffer;
+use crate::buffer::braille::{braille_char, dot_bit};
 use crate::cell::Cell;
-use crate::color::Rgba;
+use crate::color::{Gradient, Rgba};
 use crate::error::Error;
+use crate::renderer::Rect;
 use crate::style::Style;
 
 /// Unicode block characters for 2x2 quadrant rendering.
@@ -42,6 +49,33 @@ const GRAYSCALE_ASCII: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%',
 /// Unicode shade characters for grayscale (4 levels + space).
 const GRAYSCALE_UNICODE: &[char] = &[' ', '░', '▒', '▓', '█'];
 
+/// How [`PixelBuffer::render_to_cells`] packs pixels into each cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellMode {
+    /// 1x2 pixels per cell, drawn as `▀` with the top pixel as foreground
+    /// and the bottom pixel as background.
+    #[default]
+    HalfBlock,
+    /// 2x2 pixels per cell, drawn as the quadrant glyph and color pair that
+    /// best fit the four pixels.
+    Quadrant,
+    /// 2x4 pixels per cell, drawn as braille dots over the cell's existing
+    /// background. A dot is raised when its pixel is at least half opaque,
+    /// and the foreground is the average of the raised pixels.
+    Braille,
+}
+
+impl CellMode {
+    /// Pixels per cell, as (columns, rows).
+    const fn pixels_per_cell(self) -> (u32, u32) {
+        match self {
+            Self::HalfBlock => (1, 2),
+            Self::Quadrant => (2, 2),
+            Self::Braille => (2, 4),
+        }
+    }
+}
+
 /// A 2D pixel buffer for high-resolution rendering.
 ///
 /// Each pixel has an RGBA color. The buffer can be rendered to an
@@ -146,6 +180,159 @@ impl PixelBuffer {
     pub fn fill(&mut self, color: Rgba) {
         self.pixels.fill(color);
     }
+
+    /// Render the pixels into `rect` of a cell buffer.
+    ///
+    /// Pixels larger than the rect's resolution in `mode` are box-filtered
+    /// down to fit; smaller buffers are drawn at their native size from the
+    /// rect's top-left corner. Translucent pixels are composited over each
+    /// cell's existing background, and cells whose pixels are all fully
+    /// transparent are left alone. Cells are drawn like
+    /// [`OptimizedBuffer::set_blended`].
+    pub fn render_to_cells(&self, buf: &mut OptimizedBuffer, rect: Rect, mode: CellMode) {
+        let (per_x, per_y) = mode.pixels_per_cell();
+        let target_w = rect.width.saturating_mul(per_x);
+        let target_h = rect.height.saturating_mul(per_y);
+        let pixel = |x: u32, y: u32| {
+            self.area_average(
+                source_span(x, self.width, target_w),
+                source_span(y, self.height, target_h),
+            )
+        };
+
+        let cols = rect.width.min(buf.width().saturating_sub(rect.x));
+        let rows = rect.height.min(buf.height().saturating_sub(rect.y));
+        for row in 0..rows {
+            for col in 0..cols {
+                // Pixels in row-major order; for quadrants, TL, TR, BL, BR.
+                let pixels: Vec<Rgba> = (0..per_x * per_y)
+                    .map(|i| pixel(col * per_x + i % per_x, row * per_y + i / per_x))
+                    .collect();
+                if pixels.iter().all(|pixel| pixel.is_transparent()) {
+                    continue;
+                }
+
+                let (x, y) = (rect.x + col, rect.y + row);
+                let under = buf.get(x, y).map_or(Rgba::TRANSPARENT, |cell| cell.bg);
+                let composited: Vec<Rgba> =
+                    pixels.iter().map(|pixel| pixel.blend_over(under)).collect();
+                let (ch, fg, bg) = match mode {
+                    CellMode::HalfBlock => ('▀', composited[0], composited[1]),
+                    CellMode::Quadrant => best_quadrant(&composited),
+                    CellMode::Braille => {
+                        let raised: Vec<bool> = pixels.iter().map(|pixel| pixel.a >= 0.5).collect();
+                        if !raised.contains(&true) {
+                            continue;
+                        }
+                        let bits = (0..8)
+                            .filter(|&i| raised[i as usize])
+                            .fold(0, |bits, i| bits | dot_bit(i % 2, i / 2));
+                        let (fg, _) = average_colors(&composited, &raised);
+                        (braille_char(bits), fg, under)
+                    }
+                };
+                let style = Style::builder().fg(fg).bg(bg).build();
+                buf.set_blended(x, y, Cell::new(ch, style));
+            }
+        }
+    }
+
+    /// Gamma-correct, alpha-weighted average of a block of pixels.
+    ///
+    /// Pixels outside the buffer count as transparent.
+    fn area_average(&self, xs: Range<u32>, ys: Range<u32>) -> Rgba {
+        if xs.len() == 1 && ys.len() == 1 {
+            return self.get(xs.start, ys.start).unwrap_or(Rgba::TRANSPARENT);
+        }
+        let (mut r, mut g, mut b, mut a) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        let mut count = 0u32;
+        for y in ys {
+            for x in xs.clone() {
+                let color = self.get(x, y).unwrap_or(Rgba::TRANSPARENT);
+                r += srgb_to_linear(color.r) * color.a;
+                g += srgb_to_linear(color.g) * color.a;
+                b += srgb_to_linear(color.b) * color.a;
+                a += color.a;
+                count += 1;
+            }
+        }
+        if a <= 0.0 {
+            return Rgba::TRANSPARENT;
+        }
+        Rgba::new(
+            linear_to_srgb(r / a),
+            linear_to_srgb(g / a),
+            linear_to_srgb(b / a),
+            a / count as f32,
+        )
+    }
+}
+
+/// The source pixels covering target pixel `index` of `target`.
+///
+/// Sources no larger than the target map one to one, never upscaling;
+/// target pixels past the source's end get an empty span.
+fn source_span(index: u32, source: u32, target: u32) -> Range<u32> {
+    if source <= target {
+        let index = index.min(source);
+        return index..(index + 1).min(source);
+    }
+    let scale = |index: u32| (u64::from(index) * u64::from(source) / u64::from(target)) as u32;
+    scale(index)..scale(index + 1)
+}
+
+/// The quadrant glyph and (fg, bg) pair closest to four opaque pixels.
+///
+/// Every split of the pixels into lit and unlit is tried, with each side
+/// drawn in its average color; the split with the least squared error wins.
+fn best_quadrant(pixels: &[Rgba]) -> (char, Rgba, Rgba) {
+    let distance =
+        |a: Rgba, b: Rgba| (a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2);
+
+    // A full block first, so uniform cells don't pick an arbitrary split.
+    let mut best = (f32::INFINITY, '█', Rgba::WHITE, Rgba::BLACK);
+    for mask in std::iter::once(0b1111).chain(1..0b1111) {
+        let lit: Vec<bool> = (0..4).map(|bit| mask >> bit & 1 == 1).collect();
+        let (fg, bg) = average_colors(pixels, &lit);
+        let bg = if mask == 0b1111 { fg } else { bg };
+        let error: f32 = pixels
+            .iter()
+            .zip(&lit)
+            .map(|(&pixel, &lit)| distance(pixel, if lit { fg } else { bg }))
+            .sum();
+        // Tolerate rounding from the linear-space round trip.
+        if error < best.0 - 1e-6 {
+            best = (error, QUADRANT_CHARS[mask], fg, bg);
+        }
+    }
+    (best.1, best.2, best.3)
+}
+
+/// Options for [`GrayscaleBuffer::render_ascii`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsciiArtOptions {
+    /// Characters from darkest to lightest.
+    pub ramp: String,
+    /// Apply Floyd-Steinberg dithering across the ramp's levels.
+    pub dither: bool,
+    /// Draw light intensities with the start of the ramp, for light
+    /// terminals.
+    pub invert: bool,
+    /// Foreground color by intensity, overriding `style`'s.
+    pub colorize: Option<Gradient>,
+    pub style: Style,
+}
+
+impl Default for AsciiArtOptions {
+    fn default() -> Self {
+        Self {
+            ramp: GRAYSCALE_ASCII.iter().collect(),
+            dither: false,
+            invert: false,
+            colorize: None,
+            style: Style::NONE,
+        }
+    }
 }
 
 /// A grayscale buffer for intensity-based rendering.
@@ -198,6 +385,109 @@ impl GrayscaleBuffer {
             self.values[idx] = value.clamp(0.0, 1.0);
         }
     }
+
+    /// Convert a pixel buffer to intensities by luma.
+    ///
+    /// Uses [`Rgba::luminance`] scaled by alpha, so transparent pixels are
+    /// black.
+    #[must_use]
+    pub fn from_pixel_buffer(src: &PixelBuffer) -> Self {
+        let values = src
+            .pixels
+            .iter()
+            .map(|pixel| (pixel.luminance() * pixel.a).clamp(0.0, 1.0))
+            .collect();
+        Self {
+            width: src.width,
+            height: src.height,
+            values,
+        }
+    }
+
+    /// Render the intensities into `rect` as characters from a ramp.
+    ///
+    /// Buffers larger than the rect are downscaled by averaging; smaller
+    /// ones are drawn at their native size from the rect's top-left corner.
+    /// Cells are drawn like [`OptimizedBuffer::set_blended`]. An empty ramp
+    /// draws nothing.
+    pub fn render_ascii(&self, buf: &mut OptimizedBuffer, rect: Rect, opts: &AsciiArtOptions) {
+        let ramp: Vec<char> = opts.ramp.chars().collect();
+        if ramp.is_empty() {
+            return;
+        }
+        let top = (ramp.len() - 1) as f32;
+
+        let cols = rect.width.min(self.width);
+        let rows = rect.height.min(self.height);
+        let intensities: Vec<f32> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                self.area_average(
+                    source_span(col, self.width, rect.width),
+                    source_span(row, self.height, rect.height),
+                )
+            })
+            .collect();
+
+        let mut levels: Vec<f32> = intensities
+            .iter()
+            .map(|&value| if opts.invert { 1.0 - value } else { value })
+            .collect();
+        if opts.dither {
+            dither_floyd_steinberg(&mut levels, cols as usize, top);
+        }
+
+        for (i, (&level, &intensity)) in levels.iter().zip(&intensities).enumerate() {
+            let (col, row) = (i as u32 % cols, i as u32 / cols);
+            let ch = ramp[(level.clamp(0.0, 1.0) * top).round() as usize];
+            let mut style = opts.style;
+            if let Some(gradient) = &opts.colorize {
+                style.fg = Some(gradient.sample(intensity));
+            }
+            buf.set_blended(rect.x + col, rect.y + row, Cell::new(ch, style));
+        }
+    }
+
+    /// Mean intensity of a block of pixels, treating values as 0.0..=1.0.
+    fn area_average(&self, xs: Range<u32>, ys: Range<u32>) -> f32 {
+        let mut sum = 0.0;
+        let mut count = 0u32;
+        for y in ys {
+            for x in xs.clone() {
+                sum += self.get(x, y).unwrap_or(0.0).clamp(0.0, 1.0);
+                count += 1;
+            }
+        }
+        if count == 0 { 0.0 } else { sum / count as f32 }
+    }
+}
+
+/// Quantize `values` to `steps` + 1 levels in place, diffusing each cell's
+/// rounding error onto its unvisited neighbors.
+fn dither_floyd_steinberg(values: &mut [f32], width: usize, steps: f32) {
+    if width == 0 || steps <= 0.0 {
+        return;
+    }
+    let height = values.len() / width;
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let value = values[i];
+            let quantized = ((value * steps).round() / steps).clamp(0.0, 1.0);
+            values[i] = quantized;
+            let error = value - quantized;
+            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                let nx = x.checked_add_signed(dx).filter(|&nx| nx < width);
+                if let Some(nx) = nx.filter(|_| y + dy < height) {
+                    values[(y + dy) * width + nx] += error * weight;
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
 }
 
 impl OptimizedBuffer {
@@ -694,4 +984,231 @@ mod tests {
         assert_eq!(buf.width, 5);
         assert_eq!(buf.height, 5);
     }
+
+    fn cell_grid(buf: &OptimizedBuffer, rect: Rect) -> String {
+        let mut out = String::new();
+        for y in rect.y..rect.y + rect.height {
+            let row: Vec<String> = (rect.x..rect.x + rect.width)
+                .map(|x| {
+                    let cell = buf.get(x, y).unwrap();
+                    match cell.content {
+                        crate::cell::CellContent::Char(ch) => {
+                            format!("{ch}{}/{}", cell.fg, cell.bg)
+                        }
+                        _ => "·".to_string(),
+                    }
+                })
+                .collect();
+            out.push_str(&row.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render(src: &PixelBuffer, rect: Rect, mode: CellMode) -> String {
+        let mut buf = OptimizedBuffer::new(rect.width, rect.height);
+        buf.clear(Rgba::BLACK);
+        src.render_to_cells(&mut buf, rect, mode);
+        format!(
+            "{mode:?} {}x{}\n{}",
+            rect.width,
+            rect.height,
+            cell_grid(&buf, rect)
+        )
+    }
+
+    #[test]
+    fn test_render_to_cells_gradients() {
+        let gradient = crate::color::Gradient::new(Rgba::RED, Rgba::BLUE);
+        let mut horizontal = PixelBuffer::new(4, 4);
+        let mut vertical = PixelBuffer::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                horizontal.set(x, y, gradient.sample(x as f32 / 3.0));
+                vertical.set(x, y, gradient.sample(y as f32 / 3.0));
+            }
+        }
+        let out = [
+            render(&horizontal, Rect::new(0, 0, 4, 2), CellMode::HalfBlock),
+            render(&horizontal, Rect::new(0, 0, 2, 2), CellMode::Quadrant),
+            render(&vertical, Rect::new(0, 0, 4, 2), CellMode::HalfBlock),
+            render(&vertical, Rect::new(0, 0, 2, 2), CellMode::Quadrant),
+        ];
+        insta::assert_snapshot!(out.join("\n"));
+    }
+
+    #[test]
+    fn test_render_to_cells_circle_downscaled() {
+        // A white disc filling a 16x16 buffer, rendered at half resolution.
+        let mut circle = PixelBuffer::new(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                let (dx, dy) = (x as f32 - 7.5, y as f32 - 7.5);
+                if dx * dx + dy * dy <= 7.0 * 7.0 {
+                    circle.set(x, y, Rgba::WHITE);
+                }
+            }
+        }
+        let mut out = String::new();
+        for (mode, rect) in [
+            (CellMode::HalfBlock, Rect::new(0, 0, 8, 4)),
+            (CellMode::Quadrant, Rect::new(0, 0, 4, 4)),
+        ] {
+            let mut buf = OptimizedBuffer::new(rect.width, rect.height);
+            buf.clear(Rgba::BLACK);
+            circle.render_to_cells(&mut buf, rect, mode);
+            out.push_str(&format!("{mode:?}\n"));
+            for y in 0..rect.height {
+                for x in 0..rect.width {
+                    match buf.get(x, y).unwrap().content {
+                        crate::cell::CellContent::Char(ch) => out.push(ch),
+                        _ => out.push('·'),
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        out.push_str(&render(&circle, Rect::new(0, 0, 4, 4), CellMode::Quadrant));
+        insta::assert_snapshot!(out);
+    }
+
+    fn ascii(buf: &OptimizedBuffer) -> String {
+        let (width, height) = buf.size();
+        let mut out = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                match buf.get(x, y).unwrap().content {
+                    crate::cell::CellContent::Char(ch) => out.push(ch),
+                    _ => out.push('·'),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Bright at the center, fading to black at the corners.
+    fn radial(width: u32, height: u32) -> GrayscaleBuffer {
+        let mut src = GrayscaleBuffer::new(width, height);
+        let (cx, cy) = ((width - 1) as f32 / 2.0, (height - 1) as f32 / 2.0);
+        let radius = cx.hypot(cy);
+        for y in 0..height {
+            for x in 0..width {
+                let distance = (x as f32 - cx).hypot(y as f32 - cy);
+                src.set(x, y, 1.0 - distance / radius);
+            }
+        }
+        src
+    }
+
+    #[test]
+    fn test_render_ascii_radial_gradient() {
+        let src = radial(48, 24);
+        let mut out = String::new();
+        for (width, height) in [(24, 12), (12, 6)] {
+            for dither in [false, true] {
+                let mut buf = OptimizedBuffer::new(width, height);
+                let opts = AsciiArtOptions {
+                    dither,
+                    ..AsciiArtOptions::default()
+                };
+                src.render_ascii(&mut buf, Rect::new(0, 0, width, height), &opts);
+                out.push_str(&format!("{width}x{height} dither={dither}\n"));
+                out.push_str(&ascii(&buf));
+            }
+        }
+        insta::assert_snapshot!(out);
+    }
+
+    #[test]
+    fn test_render_ascii_options() {
+        let mut src = GrayscaleBuffer::new(4, 1);
+        for x in 0..4 {
+            src.set(x, 0, x as f32 / 3.0);
+        }
+
+        // Smaller sources draw at native size.
+        let mut buf = OptimizedBuffer::new(6, 1);
+        let opts = AsciiArtOptions {
+            ramp: "ab".to_string(),
+            invert: true,
+            colorize: Some(Gradient::new(Rgba::BLACK, Rgba::RED)),
+            ..AsciiArtOptions::default()
+        };
+        src.render_ascii(&mut buf, Rect::new(1, 0, 5, 1), &opts);
+        assert_eq!(ascii(&buf), "·bbaa·\n");
+        assert_eq!(buf.get(1, 0).unwrap().fg, Rgba::BLACK);
+        assert_eq!(buf.get(4, 0).unwrap().fg, Rgba::RED);
+
+        // Downscaling averages pairs of pixels.
+        let mut buf = OptimizedBuffer::new(2, 1);
+        let opts = AsciiArtOptions {
+            ramp: "01234".to_string(),
+            ..AsciiArtOptions::default()
+        };
+        src.render_ascii(&mut buf, Rect::new(0, 0, 2, 1), &opts);
+        assert_eq!(ascii(&buf), "13\n");
+    }
+
+    #[test]
+    fn test_grayscale_from_pixel_buffer() {
+        let src = PixelBuffer::from_pixels(
+            3,
+            1,
+            vec![Rgba::WHITE, Rgba::RED, Rgba::WHITE.with_alpha(0.0)],
+        );
+        let gray = GrayscaleBuffer::from_pixel_buffer(&src);
+        assert_eq!((gray.width, gray.height), (3, 1));
+        assert!((gray.get(0, 0).unwrap() - 1.0).abs() < 0.01);
+        assert!((gray.get(1, 0).unwrap() - 0.299).abs() < 0.01);
+        assert!(gray.get(2, 0).unwrap().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_render_to_cells_braille() {
+        let mut src = PixelBuffer::new(4, 4);
+        for i in 0..4 {
+            src.set(i, i, Rgba::RED);
+        }
+        // Too faint to raise a dot.
+        src.set(0, 3, Rgba::RED.with_alpha(0.4));
+        let mut buf = OptimizedBuffer::new(3, 1);
+        buf.clear(Rgba::BLUE);
+        src.render_to_cells(&mut buf, Rect::new(0, 0, 3, 1), CellMode::Braille);
+
+        // Dots 1 and 5, then dots 3 and 8.
+        let cell = |x| *buf.get(x, 0).unwrap();
+        assert_eq!(cell(0).content, crate::cell::CellContent::Char('⠑'));
+        assert_eq!(cell(1).content, crate::cell::CellContent::Char('⢄'));
+        assert_eq!(cell(2).content, crate::cell::CellContent::Empty);
+        assert_eq!(cell(0).fg.to_rgb_u8(), Rgba::RED.to_rgb_u8());
+        assert_eq!(cell(0).bg, Rgba::BLUE);
+    }
+
+    #[test]
+    fn test_render_to_cells_composites_alpha() {
+        let mut buf = OptimizedBuffer::new(3, 1);
+        buf.clear(Rgba::BLUE);
+        buf.draw_text(2, 0, "x", Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE));
+        let mut src = PixelBuffer::new(3, 2);
+        src.set(0, 0, Rgba::RED.with_alpha(0.5));
+        src.set(0, 1, Rgba::GREEN);
+        src.set(1, 0, Rgba::RED);
+
+        src.render_to_cells(&mut buf, Rect::new(0, 0, 3, 1), CellMode::HalfBlock);
+        let cell = buf.get(0, 0).unwrap();
+        assert_eq!(cell.content, crate::cell::CellContent::Char('▀'));
+        assert_eq!(
+            cell.fg.to_rgb_u8(),
+            Rgba::RED.with_alpha(0.5).blend_over(Rgba::BLUE).to_rgb_u8()
+        );
+        assert_eq!(cell.bg, Rgba::GREEN);
+        // A transparent bottom pixel shows the old background.
+        assert_eq!(buf.get(1, 0).unwrap().bg, Rgba::BLUE);
+        // Fully transparent cells keep their content.
+        assert_eq!(
+            buf.get(2, 0).unwrap().content,
+            crate::cell::CellContent::Char('x')
+        );
+    }
 }
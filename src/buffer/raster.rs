@@ -0,0 +1,485 @@
+//! Raster drawing, image import, and scaling for pixel buffers.
+//!
+//! Shapes take signed coordinates so they can hang off any edge; pixels
+//! outside the buffer are dropped. Colors are composited over existing
+//! pixels with [`Rgba::blend_over`].
+
+use crate::buffer::PixelBuffer;
+use crate::color::Rgba;
+use crate::error::Error;
+
+impl PixelBuffer {
+    /// Create from tightly packed 8-bit RGBA bytes in row-major order.
+    ///
+    /// # Errors
+    /// - [`Error::DimensionOverflow`] if dimensions overflow
+    /// - [`Error::SizeMismatch`] if `bytes` isn't `width * height * 4` long
+    pub fn from_rgba_bytes(width: u32, height: u32, bytes: &[u8]) -> Result<Self, Error> {
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or(Error::DimensionOverflow { width, height })?;
+        if bytes.len() != expected {
+            return Err(Error::SizeMismatch {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let pixels = bytes
+            .chunks_exact(4)
+            .map(|p| Rgba::from_rgba_u8(p[0], p[1], p[2], p[3]))
+            .collect();
+        Self::try_from_pixels(width, height, pixels)
+    }
+
+    /// The pixels as 8-bit RGBA bytes in row-major order.
+    #[must_use]
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| <[u8; 4]>::from(pixel.to_rgba_u8()))
+            .collect()
+    }
+
+    /// Composite `color` over the pixel at (x, y).
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Rgba) {
+        let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+            return;
+        };
+        if let Some(under) = self.get(x, y) {
+            self.set(x, y, color.blend_over(under));
+        }
+    }
+
+    /// Draw a line between two pixels, inclusive.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Rgba) {
+        // Bresenham, in i64 so the deltas can't overflow.
+        let (mut x, mut y) = (i64::from(x0), i64::from(y0));
+        let (x1, y1) = (i64::from(x1), i64::from(y1));
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.blend_pixel(x as i32, y as i32, color);
+            if x == x1 && y == y1 {
+                return;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draw the one-pixel outline of a rect.
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Rgba) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let right = x.saturating_add_unsigned(w - 1);
+        let bottom = y.saturating_add_unsigned(h - 1);
+        for col in x..=right {
+            self.blend_pixel(col, y, color);
+            if bottom != y {
+                self.blend_pixel(col, bottom, color);
+            }
+        }
+        // Corners were drawn with the rows.
+        for row in y.saturating_add(1)..bottom {
+            self.blend_pixel(x, row, color);
+            if right != x {
+                self.blend_pixel(right, row, color);
+            }
+        }
+    }
+
+    /// Fill a rect.
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Rgba) {
+        let clamp = |start: i32, len: u32, max: u32| {
+            let end = i64::from(start) + i64::from(len);
+            let start = i64::from(start).clamp(0, i64::from(max)) as u32;
+            start..end.clamp(0, i64::from(max)) as u32
+        };
+        for row in clamp(y, h, self.height) {
+            for col in clamp(x, w, self.width) {
+                if let Some(under) = self.get(col, row) {
+                    self.set(col, row, color.blend_over(under));
+                }
+            }
+        }
+    }
+
+    /// Draw the outline of a circle.
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: u32, color: Rgba) {
+        if !self.circle_visible(cx, cy, radius) {
+            return;
+        }
+        let (cx, cy) = (i64::from(cx), i64::from(cy));
+        for (x, y) in midpoint_octant(radius) {
+            // Octants meet on the axes and diagonals; blend those points once.
+            let points: &[(i64, i64)] = if x == 0 {
+                &[(0, y), (0, -y), (y, 0), (-y, 0)]
+            } else if x == y {
+                &[(x, y), (-x, y), (x, -y), (-x, -y)]
+            } else {
+                &[
+                    (x, y),
+                    (y, x),
+                    (-x, y),
+                    (-y, x),
+                    (x, -y),
+                    (y, -x),
+                    (-x, -y),
+                    (-y, -x),
+                ]
+            };
+            for &(dx, dy) in points {
+                self.blend_pixel_wide(cx + dx, cy + dy, color);
+            }
+        }
+    }
+
+    /// Fill a circle, covering the same pixels as its
+    /// [outline](Self::draw_circle) and everything inside it.
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: u32, color: Rgba) {
+        if !self.circle_visible(cx, cy, radius) {
+            return;
+        }
+        let (cx, cy, radius) = (i64::from(cx), i64::from(cy), i64::from(radius));
+        // Half-width of each visible row, from the top of the circle.
+        let top = (cy - radius).max(0);
+        let bottom = (cy + radius).min(i64::from(self.height) - 1);
+        let mut spans = vec![None::<i64>; (bottom - top + 1) as usize];
+        let mut widen = |dy: i64, half: i64| {
+            if let Some(span) = usize::try_from(cy + dy - top)
+                .ok()
+                .and_then(|row| spans.get_mut(row))
+            {
+                *span = Some(span.map_or(half, |old| old.max(half)));
+            }
+        };
+        for (x, y) in midpoint_octant(radius as u32) {
+            widen(y, x);
+            widen(-y, x);
+            widen(x, y);
+            widen(-x, y);
+        }
+        for (row, half) in (top..).zip(spans) {
+            let Some(half) = half else { continue };
+            let left = (cx - half).max(0);
+            let right = (cx + half).min(i64::from(self.width) - 1);
+            for col in left..=right {
+                self.blend_pixel_wide(col, row, color);
+            }
+        }
+    }
+
+    /// Whether a circle's bounding box overlaps the buffer.
+    fn circle_visible(&self, cx: i32, cy: i32, radius: u32) -> bool {
+        let overlaps = |center: i32, len: u32| {
+            i64::from(center) + i64::from(radius) >= 0
+                && i64::from(center) - i64::from(radius) < i64::from(len)
+        };
+        overlaps(cx, self.width) && overlaps(cy, self.height)
+    }
+
+    /// Composite `src` over this buffer with its top-left pixel at (x, y).
+    pub fn blit(&mut self, src: &Self, x: i32, y: i32) {
+        for row in 0..src.height {
+            for col in 0..src.width {
+                if let Some(color) = src.get(col, row) {
+                    let dest_x = i64::from(x) + i64::from(col);
+                    let dest_y = i64::from(y) + i64::from(row);
+                    self.blend_pixel_wide(dest_x, dest_y, color);
+                }
+            }
+        }
+    }
+
+    /// Scale to `width` x `height` by picking the nearest source pixel.
+    #[must_use]
+    pub fn resize_nearest(&self, width: u32, height: u32) -> Self {
+        let mut out = Self::new(width, height);
+        if self.width == 0 || self.height == 0 {
+            return out;
+        }
+        let nearest = |dest: u32, dest_len: u32, src_len: u32| {
+            (u64::from(dest) * u64::from(src_len) / u64::from(dest_len)) as u32
+        };
+        for row in 0..height {
+            let src_y = nearest(row, height, self.height);
+            for col in 0..width {
+                let src_x = nearest(col, width, self.width);
+                if let Some(color) = self.get(src_x, src_y) {
+                    out.set(col, row, color);
+                }
+            }
+        }
+        out
+    }
+
+    /// Scale to `width` x `height`, interpolating between the four nearest
+    /// source pixels.
+    ///
+    /// Pixels are interpolated premultiplied, so transparent neighbors
+    /// don't darken edges.
+    #[must_use]
+    pub fn resize_bilinear(&self, width: u32, height: u32) -> Self {
+        let mut out = Self::new(width, height);
+        if self.width == 0 || self.height == 0 {
+            return out;
+        }
+        // Source position of a destination pixel's center, clamped to the
+        // outermost source centers, as (index, next index, weight of next).
+        let sample = |dest: u32, dest_len: u32, src_len: u32| {
+            let center = (dest as f32 + 0.5) * src_len as f32 / dest_len as f32 - 0.5;
+            let center = center.clamp(0.0, (src_len - 1) as f32);
+            let index = center.floor() as u32;
+            (index, (index + 1).min(src_len - 1), center - index as f32)
+        };
+        let premultiplied = |x: u32, y: u32| {
+            let color = self.get(x, y).unwrap_or(Rgba::TRANSPARENT);
+            [
+                color.r * color.a,
+                color.g * color.a,
+                color.b * color.a,
+                color.a,
+            ]
+        };
+
+        for row in 0..height {
+            let (y0, y1, ty) = sample(row, height, self.height);
+            for col in 0..width {
+                let (x0, x1, tx) = sample(col, width, self.width);
+                let corners = [
+                    (premultiplied(x0, y0), (1.0 - tx) * (1.0 - ty)),
+                    (premultiplied(x1, y0), tx * (1.0 - ty)),
+                    (premultiplied(x0, y1), (1.0 - tx) * ty),
+                    (premultiplied(x1, y1), tx * ty),
+                ];
+                let mut sum = [0.0f32; 4];
+                for (channels, weight) in corners {
+                    for (total, channel) in sum.iter_mut().zip(channels) {
+                        *total += channel * weight;
+                    }
+                }
+                let [r, g, b, a] = sum;
+                let color = if a > 0.0 {
+                    Rgba::new(r / a, g / a, b / a, a)
+                } else {
+                    Rgba::TRANSPARENT
+                };
+                out.set(col, row, color);
+            }
+        }
+        out
+    }
+
+    /// [`Self::blend_pixel`] for offsets that may overflow `i32`.
+    fn blend_pixel_wide(&mut self, x: i64, y: i64, color: Rgba) {
+        if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+            self.blend_pixel(x, y, color);
+        }
+    }
+}
+
+/// Points of a circle's outline from the top going clockwise to the
+/// diagonal, as (x, y) offsets from the center with `x <= y`.
+fn midpoint_octant(radius: u32) -> impl Iterator<Item = (i64, i64)> {
+    let (mut x, mut y) = (0i64, i64::from(radius));
+    let mut decision = 1 - y;
+    std::iter::from_fn(move || {
+        if x > y {
+            return None;
+        }
+        let point = (x, y);
+        x += 1;
+        if decision < 0 {
+            decision += 2 * x + 1;
+        } else {
+            y -= 1;
+            decision += 2 * (x - y) + 1;
+        }
+        Some(point)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One character per pixel: `#` opaque, `+` translucent, `.` clear.
+    fn mask(buf: &PixelBuffer) -> String {
+        let mut out = String::new();
+        for y in 0..buf.height {
+            for x in 0..buf.width {
+                let alpha = buf.get(x, y).unwrap().a;
+                out.push(match alpha {
+                    a if a >= 1.0 => '#',
+                    a if a > 0.0 => '+',
+                    _ => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_rgba_bytes_round_trip() {
+        let bytes: Vec<u8> = (0..2 * 3 * 4).map(|i| (i * 11) as u8).collect();
+        let buf = PixelBuffer::from_rgba_bytes(2, 3, &bytes).unwrap();
+        assert_eq!(buf.get(1, 0), Some(Rgba::from_rgba_u8(44, 55, 66, 77)));
+        assert_eq!(buf.to_rgba_bytes(), bytes);
+
+        assert!(matches!(
+            PixelBuffer::from_rgba_bytes(2, 3, &bytes[1..]),
+            Err(Error::SizeMismatch {
+                expected: 24,
+                actual: 23
+            })
+        ));
+        assert!(matches!(
+            PixelBuffer::from_rgba_bytes(u32::MAX, u32::MAX, &[]),
+            Err(Error::DimensionOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lines_and_rects() {
+        let mut buf = PixelBuffer::new(8, 6);
+        buf.draw_line(0, 0, 7, 3, Rgba::RED);
+        buf.draw_rect(2, 3, 4, 3, Rgba::BLUE);
+        // Clipped at the left and bottom edges.
+        buf.fill_rect(-2, 5, 3, 4, Rgba::GREEN);
+        buf.draw_line(-5, -5, 100, -5, Rgba::WHITE);
+        assert_eq!(
+            mask(&buf),
+            "##......\n\
+             ..##....\n\
+             ....##..\n\
+             ..######\n\
+             ..#..#..\n\
+             #.####..\n"
+        );
+        assert_eq!(buf.get(1, 0), Some(Rgba::RED));
+        assert_eq!(buf.get(6, 3), Some(Rgba::RED));
+        assert_eq!(buf.get(2, 4), Some(Rgba::BLUE));
+        assert_eq!(buf.get(0, 5), Some(Rgba::GREEN));
+    }
+
+    #[test]
+    fn test_translucent_shapes_blend_once() {
+        let mut buf = PixelBuffer::new(5, 5);
+        buf.fill(Rgba::WHITE);
+        let glass = Rgba::BLACK.with_alpha(0.5);
+        buf.draw_rect(0, 0, 5, 5, glass);
+        buf.draw_circle(2, 2, 1, glass);
+        let gray = glass.blend_over(Rgba::WHITE);
+        // Corners and octant seams blend once, like every other pixel.
+        assert_eq!(buf.get(0, 0), Some(gray));
+        assert_eq!(buf.get(2, 1), Some(gray));
+        assert_eq!(buf.get(1, 2), Some(gray));
+        assert_eq!(buf.get(2, 2), Some(Rgba::WHITE));
+    }
+
+    #[test]
+    fn test_circles() {
+        let mut outline = PixelBuffer::new(11, 11);
+        outline.draw_circle(5, 5, 4, Rgba::WHITE);
+        let mut filled = PixelBuffer::new(11, 11);
+        filled.fill_circle(5, 5, 4, Rgba::WHITE);
+        let expected_outline = "...........\n\
+                                ....###....\n\
+                                ..##...##..\n\
+                                ..#.....#..\n\
+                                .#.......#.\n\
+                                .#.......#.\n\
+                                .#.......#.\n\
+                                ..#.....#..\n\
+                                ..##...##..\n\
+                                ....###....\n\
+                                ...........\n";
+        assert_eq!(mask(&outline), expected_outline);
+        // The fill covers the outline and its inside.
+        assert_eq!(
+            mask(&filled),
+            "...........\n\
+             ....###....\n\
+             ..#######..\n\
+             ..#######..\n\
+             .#########.\n\
+             .#########.\n\
+             .#########.\n\
+             ..#######..\n\
+             ..#######..\n\
+             ....###....\n\
+             ...........\n"
+        );
+
+        // Circles partly or wholly off the buffer don't panic.
+        let mut buf = PixelBuffer::new(4, 4);
+        buf.fill_circle(0, 0, 2, Rgba::RED);
+        buf.draw_circle(i32::MAX, i32::MIN, u32::MAX / 4, Rgba::RED);
+        buf.fill_circle(i32::MIN, 0, u32::MAX / 4, Rgba::RED);
+        assert_eq!(buf.get(0, 2), Some(Rgba::RED));
+        assert_eq!(buf.get(2, 2), Some(Rgba::TRANSPARENT));
+    }
+
+    #[test]
+    fn test_blit_composites() {
+        let mut dest = PixelBuffer::new(3, 1);
+        dest.fill(Rgba::BLUE);
+        let src =
+            PixelBuffer::from_pixels(2, 1, vec![Rgba::RED.with_alpha(0.5), Rgba::TRANSPARENT]);
+        dest.blit(&src, 1, 0);
+        dest.blit(&src, -1, 0);
+        assert_eq!(dest.get(0, 0), Some(Rgba::BLUE));
+        assert_eq!(
+            dest.get(1, 0),
+            Some(Rgba::RED.with_alpha(0.5).blend_over(Rgba::BLUE))
+        );
+        assert_eq!(dest.get(2, 0), Some(Rgba::BLUE));
+    }
+
+    #[test]
+    fn test_resize() {
+        let src = PixelBuffer::from_pixels(
+            2,
+            2,
+            vec![Rgba::RED, Rgba::GREEN, Rgba::BLUE, Rgba::TRANSPARENT],
+        );
+
+        let nearest = src.resize_nearest(4, 4);
+        assert_eq!(nearest.get(1, 1), Some(Rgba::RED));
+        assert_eq!(nearest.get(2, 1), Some(Rgba::GREEN));
+        assert_eq!(nearest.get(1, 2), Some(Rgba::BLUE));
+        assert_eq!(nearest.get(3, 3), Some(Rgba::TRANSPARENT));
+        assert_eq!(src.resize_nearest(1, 1).get(0, 0), Some(Rgba::RED));
+
+        // Corners keep their source colors; between red and green is
+        // an even mix, not darkened by the transparent pixel.
+        let bilinear = src.resize_bilinear(4, 4);
+        assert_eq!(bilinear.get(0, 0), Some(Rgba::RED));
+        assert_eq!(bilinear.get(3, 0), Some(Rgba::GREEN));
+        let between = bilinear.get(1, 0).unwrap();
+        assert_eq!(between.to_rgb_u8(), (191, 64, 0));
+        let edge = bilinear.get(3, 2).unwrap();
+        assert_eq!(edge.to_rgb_u8(), (0, 255, 0));
+        assert!(edge.a < 1.0 && edge.a > 0.0);
+
+        // Empty sizes don't panic.
+        assert_eq!(src.resize_bilinear(0, 3).pixels.len(), 0);
+        assert_eq!(
+            PixelBuffer::new(0, 0).resize_nearest(2, 2).get(1, 1),
+            Some(Rgba::TRANSPARENT)
+        );
+    }
+}
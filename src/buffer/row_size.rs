@@ -0,0 +1,133 @@
+//! Double-width and double-height rows, shown with DEC line attributes
+//! (DECDWL and DECDHL).
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+
+/// How a terminal shows a row, set with [`OptimizedBuffer::set_row_size`].
+///
+/// Every size but `Normal` shows each cell two columns wide, so the row
+/// only fits half as many cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RowSize {
+    #[default]
+    Normal,
+    /// Cells twice as wide (DECDWL).
+    DoubleWidth,
+    /// The top half of cells twice as wide and tall (DECDHL).
+    DoubleHeightTop,
+    /// The bottom half of cells twice as wide and tall (DECDHL).
+    DoubleHeightBottom,
+}
+
+impl RowSize {
+    /// Cells that fit in a row of this size on a screen `width` columns
+    /// wide.
+    #[must_use]
+    pub const fn columns(self, width: u32) -> u32 {
+        match self {
+            Self::Normal => width,
+            _ => width / 2,
+        }
+    }
+}
+
+impl OptimizedBuffer {
+    /// Show row `y` at `size`.
+    ///
+    /// A double-size row only has the first half of the buffer's columns,
+    /// rounded down: the cells past them are blanked, and drawing there is
+    /// clipped. A double-height headline is drawn twice, on a
+    /// [`RowSize::DoubleHeightTop`] row and the
+    /// [`RowSize::DoubleHeightBottom`] row below it, which the terminal
+    /// shows as one line of text two rows tall.
+    ///
+    /// Terminals without DEC line attributes show every row at normal size;
+    /// see [`Capabilities::dec_line_attributes`](crate::terminal::Capabilities::dec_line_attributes).
+    /// Clearing or resizing the buffer makes every row normal again.
+    pub fn set_row_size(&mut self, y: u32, size: RowSize) {
+        if y >= self.height || self.row_size(y) == size {
+            return;
+        }
+        self.touch();
+        if self.row_sizes.is_empty() {
+            self.row_sizes = vec![RowSize::Normal; self.height as usize];
+        }
+        self.row_sizes[y as usize] = size;
+
+        let width = self.width as usize;
+        let row = &mut self.cells[y as usize * width..(y as usize + 1) * width];
+        for cell in &mut row[size.columns(self.width) as usize..] {
+            if let CellContent::Grapheme(id) = cell.content {
+                if id.pool_id() != 0 {
+                    self.orphaned_graphemes.push(id);
+                }
+            }
+            *cell = Cell::clear(cell.bg);
+        }
+        self.blank_split_wide_chars(y as usize);
+    }
+
+    /// How row `y` is shown.
+    #[must_use]
+    pub fn row_size(&self, y: u32) -> RowSize {
+        self.row_sizes.get(y as usize).copied().unwrap_or_default()
+    }
+
+    /// Cells that exist in row `y`: half the buffer's width on a
+    /// double-size row.
+    #[must_use]
+    pub fn row_width(&self, y: u32) -> u32 {
+        self.row_size(y).columns(self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::ClipRect;
+    use crate::color::Rgba;
+    use crate::style::Style;
+
+    fn text(buffer: &OptimizedBuffer, y: u32) -> String {
+        (0..buffer.width())
+            .map(|x| match buffer.get(x, y).unwrap().content {
+                CellContent::Char(ch) => ch,
+                CellContent::Continuation => '>',
+                _ => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_double_rows_clip_drawing_to_half_width() {
+        let mut buffer = OptimizedBuffer::new(9, 3);
+        buffer.draw_text(0, 0, "abc日defg", Style::NONE);
+        buffer.draw_text(0, 1, "abcdefghi", Style::NONE);
+        buffer.set_row_size(0, RowSize::DoubleWidth);
+        buffer.set_row_size(1, RowSize::DoubleHeightTop);
+        assert_eq!(text(&buffer, 0), "abc......", "wide char split at the edge");
+        assert_eq!(text(&buffer, 1), "abcd.....");
+        assert_eq!(buffer.row_width(1), 4);
+        assert_eq!(buffer.row_width(2), 9);
+
+        let mut stamp = OptimizedBuffer::new(9, 1);
+        stamp.draw_text(0, 0, "#########", Style::NONE);
+        buffer.draw_buffer(0, 0, &stamp);
+        buffer.fill_rect(0, 1, 9, 2, Rgba::RED);
+        buffer.draw_text(0, 1, "ABCDEFGHI", Style::NONE);
+        buffer.push_scissor(ClipRect::new(2, 0, 7, 3));
+        buffer.draw_text(2, 0, "xyzxyz", Style::NONE);
+        buffer.pop_scissor();
+        assert_eq!(text(&buffer, 0), "##xy.....");
+        assert_eq!(text(&buffer, 1), "ABCD.....");
+        assert_eq!(buffer.get(3, 1).unwrap().bg, Rgba::RED);
+        assert_eq!(buffer.get(4, 1).unwrap().bg, Rgba::TRANSPARENT);
+        assert_eq!(buffer.get(4, 2).unwrap().bg, Rgba::RED);
+
+        buffer.clear(Rgba::BLACK);
+        assert_eq!(buffer.row_size(0), RowSize::Normal);
+        buffer.draw_text(0, 0, "abcdefghi", Style::NONE);
+        assert_eq!(text(&buffer, 0), "abcdefghi");
+    }
+}
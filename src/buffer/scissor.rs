@@ -73,11 +73,127 @@ impl Default for ClipRect {
     }
 }
 
+/// Rows `y0..y1` of a multi-rect clip region, visible in the sorted,
+/// disjoint, half-open column spans.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Band {
+    y0: i32,
+    y1: i32,
+    spans: Vec<(i32, i32)>,
+}
+
+impl Band {
+    fn contains_x(&self, x: i32) -> bool {
+        let idx = self.spans.partition_point(|&(_, x1)| x1 <= x);
+        self.spans.get(idx).is_some_and(|&(x0, _)| x0 <= x)
+    }
+
+    fn rects(&self) -> impl Iterator<Item = ClipRect> + '_ {
+        self.spans
+            .iter()
+            .map(|&(x0, x1)| ClipRect::new(x0, self.y0, x1.abs_diff(x0), self.y1.abs_diff(self.y0)))
+    }
+}
+
+/// Half-open row/column range covered by a rectangle.
+fn rect_end(rect: &ClipRect) -> (i32, i32) {
+    (
+        rect.x.saturating_add_unsigned(rect.width),
+        rect.y.saturating_add_unsigned(rect.height),
+    )
+}
+
+/// Intersect two sorted, disjoint span lists.
+fn intersect_spans(a: &[(i32, i32)], b: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            out.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Banded region covering the union of `rects`, clipped to `parent` (and to
+/// `parent_bands` when the parent is itself a multi-rect region).
+fn region_bands(rects: &[ClipRect], parent: ClipRect, parent_bands: Option<&[Band]>) -> Vec<Band> {
+    let clipped: Vec<ClipRect> = rects
+        .iter()
+        .filter_map(|rect| rect.intersect(&parent))
+        .collect();
+
+    let mut edges: Vec<i32> = clipped
+        .iter()
+        .flat_map(|rect| [rect.y, rect_end(rect).1])
+        .chain(
+            parent_bands
+                .into_iter()
+                .flatten()
+                .flat_map(|b| [b.y0, b.y1]),
+        )
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let mut bands: Vec<Band> = Vec::new();
+    for window in edges.windows(2) {
+        let (y0, y1) = (window[0], window[1]);
+        let mut spans: Vec<(i32, i32)> = clipped
+            .iter()
+            .filter(|rect| rect.y <= y0 && y0 < rect_end(rect).1)
+            .map(|rect| (rect.x, rect_end(rect).0))
+            .collect();
+        spans.sort_unstable();
+        let mut merged: Vec<(i32, i32)> = Vec::with_capacity(spans.len());
+        for (x0, x1) in spans {
+            match merged.last_mut() {
+                Some(last) if x0 <= last.1 => last.1 = last.1.max(x1),
+                _ => merged.push((x0, x1)),
+            }
+        }
+
+        if let Some(parent_bands) = parent_bands {
+            let idx = parent_bands.partition_point(|b| b.y1 <= y0);
+            merged = match parent_bands.get(idx) {
+                Some(band) if band.y0 <= y0 => intersect_spans(&merged, &band.spans),
+                _ => Vec::new(),
+            };
+        }
+        if merged.is_empty() {
+            continue;
+        }
+
+        match bands.last_mut() {
+            Some(last) if last.y1 == y0 && last.spans == merged => last.y1 = y1,
+            _ => bands.push(Band {
+                y0,
+                y1,
+                spans: merged,
+            }),
+        }
+    }
+    bands
+}
+
 /// Stack of scissor rectangles with intersection.
+///
+/// Each entry is either a single rectangle or, via [`Self::push_multi`], a
+/// union of rectangles. Either way it is intersected with the entry below.
 #[derive(Clone, Debug, Default)]
 pub struct ScissorStack {
-    stack: Vec<ClipRect>,
+    stack: Vec<(ClipRect, Option<Vec<Band>>)>,
     current: ClipRect,
+    /// Banded region when the current clip isn't a single rectangle; the
+    /// bands then lie within `current`, their bounding box.
+    bands: Option<Vec<Band>>,
 }
 
 impl ScissorStack {
@@ -87,22 +203,57 @@ impl ScissorStack {
         Self {
             stack: Vec::new(),
             current: ClipRect::default(),
+            bands: None,
         }
     }
 
     /// Push a new scissor rectangle, intersecting with current.
     pub fn push(&mut self, rect: ClipRect) {
-        self.stack.push(self.current);
+        if self.bands.is_some() {
+            self.push_multi(std::slice::from_ref(&rect));
+            return;
+        }
+        self.stack.push((self.current, None));
         self.current = self
             .current
             .intersect(&rect)
             .unwrap_or(ClipRect::new(0, 0, 0, 0));
     }
 
+    /// Push a clip made of several rectangles.
+    ///
+    /// A point is visible if it lies inside any of `rects` and inside the
+    /// current clip. Overlapping rects are fine; an empty slice hides
+    /// everything.
+    pub fn push_multi(&mut self, rects: &[ClipRect]) {
+        let bands = region_bands(rects, self.current, self.bands.as_deref());
+        self.stack.push((self.current, self.bands.take()));
+
+        match bands.as_slice() {
+            [] => self.current = ClipRect::new(0, 0, 0, 0),
+            [band] if band.spans.len() == 1 => {
+                self.current = band.rects().next().unwrap_or_default();
+            }
+            _ => {
+                let x0 = bands.iter().map(|b| b.spans[0].0).min().unwrap_or(0);
+                let x1 = bands
+                    .iter()
+                    .filter_map(|b| b.spans.last())
+                    .map(|span| span.1)
+                    .max()
+                    .unwrap_or(0);
+                let (y0, y1) = (bands[0].y0, bands[bands.len() - 1].y1);
+                self.current = ClipRect::new(x0, y0, x1.abs_diff(x0), y1.abs_diff(y0));
+                self.bands = Some(bands);
+            }
+        }
+    }
+
     /// Pop the top scissor rectangle.
     pub fn pop(&mut self) {
-        if let Some(rect) = self.stack.pop() {
+        if let Some((rect, bands)) = self.stack.pop() {
             self.current = rect;
+            self.bands = bands;
         }
     }
 
@@ -110,20 +261,45 @@ impl ScissorStack {
     pub fn clear(&mut self) {
         self.stack.clear();
         self.current = ClipRect::default();
+        self.bands = None;
     }
 
     /// Check if a point is within the current scissor region.
     #[must_use]
     pub fn contains(&self, x: i32, y: i32) -> bool {
-        self.current.contains(x, y)
+        self.bands.as_ref().map_or_else(
+            || self.current.contains(x, y),
+            |bands| {
+                let idx = bands.partition_point(|b| b.y1 <= y);
+                bands
+                    .get(idx)
+                    .is_some_and(|band| band.y0 <= y && band.contains_x(x))
+            },
+        )
     }
 
     /// Get the current effective scissor rectangle.
+    ///
+    /// For a multi-rect clip this is the bounding box of the visible region;
+    /// use [`Self::rects`] for the exact coverage.
     #[must_use]
     pub fn current(&self) -> ClipRect {
         self.current
     }
 
+    /// Disjoint rectangles that exactly cover the current scissor region,
+    /// ordered top to bottom, then left to right.
+    pub fn rects(&self) -> impl Iterator<Item = ClipRect> + '_ {
+        let single = self
+            .bands
+            .is_none()
+            .then_some(self.current)
+            .filter(|rect| !rect.is_empty());
+        single
+            .into_iter()
+            .chain(self.bands.iter().flatten().flat_map(Band::rects))
+    }
+
     /// Check if current scissor region is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -486,6 +662,74 @@ mod tests {
         assert!(s.contains(1000, 1000)); // Back to default
     }
 
+    // ============================================
+    // Multi-rect Scissors
+    // ============================================
+
+    #[test]
+    fn test_stack_push_multi_is_union() {
+        let mut s = ScissorStack::new();
+        // L shape: a full-width top bar plus a left column below it.
+        s.push_multi(&[ClipRect::new(0, 0, 10, 2), ClipRect::new(0, 2, 3, 4)]);
+        assert!(s.contains(9, 1));
+        assert!(s.contains(2, 5));
+        assert!(!s.contains(3, 2));
+        assert!(!s.contains(0, 6));
+        assert_eq!(s.current(), ClipRect::new(0, 0, 10, 6));
+
+        let rects: Vec<ClipRect> = s.rects().collect();
+        assert_eq!(
+            rects,
+            vec![ClipRect::new(0, 0, 10, 2), ClipRect::new(0, 2, 3, 4)]
+        );
+
+        s.pop();
+        assert!(s.contains(1000, 1000));
+        assert_eq!(s.rects().count(), 1);
+    }
+
+    #[test]
+    fn test_stack_push_multi_merges_overlaps() {
+        let mut s = ScissorStack::new();
+        s.push_multi(&[ClipRect::new(0, 0, 6, 4), ClipRect::new(4, 0, 6, 4)]);
+        // Collapses back to a single rectangle.
+        assert_eq!(
+            s.rects().collect::<Vec<_>>(),
+            vec![ClipRect::new(0, 0, 10, 4)]
+        );
+
+        s.push_multi(&[]);
+        assert!(s.is_empty());
+        assert!(!s.contains(1, 1));
+        assert_eq!(s.rects().count(), 0);
+    }
+
+    #[test]
+    fn test_stack_multi_nested_in_single_and_single_in_multi() {
+        let mut s = ScissorStack::new();
+        s.push(ClipRect::new(2, 2, 6, 6));
+        // Two columns with a notch between them, clipped to the parent.
+        s.push_multi(&[ClipRect::new(0, 0, 4, 10), ClipRect::new(6, 0, 4, 10)]);
+        assert!(s.contains(2, 2));
+        assert!(s.contains(7, 7));
+        assert!(!s.contains(1, 2));
+        assert!(!s.contains(4, 4));
+        assert!(!s.contains(8, 4));
+        assert_eq!(s.current(), ClipRect::new(2, 2, 6, 6));
+
+        // A single rect inside the multi clip intersects with each part.
+        s.push(ClipRect::new(3, 3, 4, 1));
+        assert_eq!(
+            s.rects().collect::<Vec<_>>(),
+            vec![ClipRect::new(3, 3, 1, 1), ClipRect::new(6, 3, 1, 1)]
+        );
+
+        s.pop();
+        s.pop();
+        assert_eq!(s.current(), ClipRect::new(2, 2, 6, 6));
+        assert!(s.contains(4, 4));
+    }
+
     // ============================================
     // ClipRect Eq & Clone
     // ============================================
@@ -0,0 +1,320 @@
+//! Scrollbars with a proportional, sub-cell positioned thumb.
+
+use std::ops::Range;
+
+use super::drawing::LEFT_EIGHTHS;
+use crate::buffer::OptimizedBuffer;
+use crate::cell::Cell;
+use crate::color::Rgba;
+use crate::renderer::Rect;
+use crate::style::Style;
+
+/// Partial blocks for 1/8 through 7/8 of a cell, filled from the bottom.
+const LOWER_EIGHTHS: [char; 7] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇'];
+
+/// How much content a pane holds and which part of it is visible.
+///
+/// All values are in the same unit, such as lines or columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScrollInfo {
+    /// Length of the whole content.
+    pub total: u32,
+    /// Length of the visible part.
+    pub viewport: u32,
+    /// Position of the first visible unit.
+    pub offset: u32,
+}
+
+impl ScrollInfo {
+    #[must_use]
+    pub const fn new(total: u32, viewport: u32, offset: u32) -> Self {
+        Self {
+            total,
+            viewport,
+            offset,
+        }
+    }
+
+    /// Largest offset that still fills the viewport.
+    #[must_use]
+    pub const fn max_offset(&self) -> u32 {
+        self.total.saturating_sub(self.viewport)
+    }
+
+    /// Check if the content overflows the viewport.
+    #[must_use]
+    pub const fn is_scrollable(&self) -> bool {
+        self.total > self.viewport
+    }
+}
+
+/// Scrollbar appearance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollbarStyle {
+    /// Color of the track behind the thumb.
+    pub track: Rgba,
+    /// Color of the thumb.
+    pub thumb: Rgba,
+    /// Smallest thumb length in cells (at least 1), so it stays grabbable
+    /// over huge content.
+    pub min_thumb: u32,
+}
+
+impl Default for ScrollbarStyle {
+    fn default() -> Self {
+        Self {
+            track: Rgba::rgb(0.2, 0.2, 0.2),
+            thumb: Rgba::rgb(0.6, 0.6, 0.6),
+            min_thumb: 1,
+        }
+    }
+}
+
+/// Bar geometry along its axis, in eighths of a cell.
+struct Thumb {
+    /// Track length.
+    track: u32,
+    /// Thumb length.
+    len: u32,
+    /// Thumb start, from the top or left of the track.
+    start: u32,
+}
+
+impl Thumb {
+    fn new(length: u32, info: &ScrollInfo, min_thumb: u32) -> Option<Self> {
+        if length == 0 || !info.is_scrollable() {
+            return None;
+        }
+        let track = length.saturating_mul(8);
+        let proportional =
+            (f64::from(track) * f64::from(info.viewport) / f64::from(info.total)).round() as u32;
+        let len = proportional
+            .max(min_thumb.max(1).saturating_mul(8))
+            .min(track);
+        let offset = info.offset.min(info.max_offset());
+        let start = (f64::from(track - len) * f64::from(offset) / f64::from(info.max_offset()))
+            .round() as u32;
+        Some(Self { track, len, start })
+    }
+
+    /// Cells the thumb touches, relative to the track start.
+    fn cells(&self) -> Range<u32> {
+        self.start / 8..(self.start + self.len).div_ceil(8)
+    }
+}
+
+/// Vertical for rects at least as tall as they are wide.
+const fn is_vertical(rect: Rect) -> bool {
+    rect.height >= rect.width
+}
+
+/// Draw a scrollbar filling `rect`.
+///
+/// Rects at least as tall as they are wide get a vertical bar, others a
+/// horizontal one. The thumb length is proportional to the visible part of
+/// the content but never below `style.min_thumb` cells, and its edges are
+/// placed to an eighth of a cell with partial block characters.
+///
+/// Content that fits its viewport draws nothing and returns `None`.
+/// Otherwise returns the rows (vertical) or columns (horizontal) the thumb
+/// touches, in buffer coordinates; the track before and after that range
+/// makes natural page-up and page-down hit areas.
+pub fn draw_scrollbar(
+    buffer: &mut OptimizedBuffer,
+    rect: Rect,
+    info: ScrollInfo,
+    style: ScrollbarStyle,
+) -> Option<Range<u32>> {
+    let vertical = is_vertical(rect);
+    let (origin, length, thickness) = if vertical {
+        (rect.y, rect.height, rect.width)
+    } else {
+        (rect.x, rect.width, rect.height)
+    };
+    let thumb = Thumb::new(length, &info, style.min_thumb)?;
+
+    let plain = Style::fg(style.thumb).with_bg(style.track);
+    let inverted = Style::fg(style.track).with_bg(style.thumb);
+    let thumb_end = thumb.start + thumb.len;
+    for i in 0..length {
+        let (cell_start, cell_end) = (i * 8, i * 8 + 8);
+        let covered = thumb_end
+            .min(cell_end)
+            .saturating_sub(thumb.start.max(cell_start));
+        let cell = match covered {
+            0 => Cell::new(' ', plain),
+            8.. => Cell::new('█', plain),
+            // The thumb's leading edge: it covers the end of this cell.
+            k if thumb.start > cell_start => {
+                if vertical {
+                    Cell::new(LOWER_EIGHTHS[k as usize - 1], plain)
+                } else {
+                    Cell::new(LEFT_EIGHTHS[7 - k as usize], inverted)
+                }
+            }
+            // The trailing edge: it covers the start of this cell.
+            k => {
+                if vertical {
+                    Cell::new(LOWER_EIGHTHS[7 - k as usize], inverted)
+                } else {
+                    Cell::new(LEFT_EIGHTHS[k as usize - 1], plain)
+                }
+            }
+        };
+        for across in 0..thickness {
+            let (x, y) = if vertical {
+                (rect.x + across, origin + i)
+            } else {
+                (origin + i, rect.y + across)
+            };
+            buffer.set_blended(x, y, cell);
+        }
+    }
+
+    let cells = thumb.cells();
+    Some(origin + cells.start..origin + cells.end)
+}
+
+/// Offset that centers the thumb on a click in the scrollbar's track.
+///
+/// `click_pos` is the clicked row for a vertical bar or column for a
+/// horizontal one, in buffer coordinates; clicks past either end clamp to
+/// it. Content that fits its viewport returns 0.
+#[must_use]
+pub fn scrollbar_offset_for_click(
+    rect: Rect,
+    info: ScrollInfo,
+    style: ScrollbarStyle,
+    click_pos: u32,
+) -> u32 {
+    let (origin, length) = if is_vertical(rect) {
+        (rect.y, rect.height)
+    } else {
+        (rect.x, rect.width)
+    };
+    let Some(thumb) = Thumb::new(length, &info, style.min_thumb) else {
+        return 0;
+    };
+    let span = thumb.track - thumb.len;
+    if span == 0 {
+        return 0;
+    }
+
+    let cell = click_pos.saturating_sub(origin).min(length - 1);
+    let center = cell * 8 + 4;
+    let start = center.saturating_sub(thumb.len / 2).min(span);
+    (f64::from(info.max_offset()) * f64::from(start) / f64::from(span)).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+
+    fn column(buffer: &OptimizedBuffer, x: u32, height: u32) -> String {
+        (0..height)
+            .map(|y| match buffer.get(x, y).unwrap().content {
+                CellContent::Char(c) => c,
+                _ => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_content_that_fits_draws_no_bar() {
+        let mut buffer = OptimizedBuffer::new(1, 10);
+        let before = column(&buffer, 0, 10);
+        let rect = Rect::new(0, 0, 1, 10);
+        let style = ScrollbarStyle::default();
+
+        for info in [ScrollInfo::new(5, 10, 0), ScrollInfo::new(10, 10, 3)] {
+            assert_eq!(draw_scrollbar(&mut buffer, rect, info, style), None);
+            assert_eq!(scrollbar_offset_for_click(rect, info, style, 7), 0);
+        }
+        assert_eq!(column(&buffer, 0, 10), before);
+    }
+
+    #[test]
+    fn test_barely_scrollable_thumb_has_eighth_edges() {
+        let style = ScrollbarStyle::default();
+        let rect = Rect::new(0, 0, 1, 10);
+        let draw = |offset| {
+            let mut buffer = OptimizedBuffer::new(1, 10);
+            let range = draw_scrollbar(&mut buffer, rect, ScrollInfo::new(11, 10, offset), style);
+            (range, column(&buffer, 0, 10), buffer)
+        };
+
+        // 73 of 80 eighths: nine full cells and one eighth of the last.
+        let (range, col, buffer) = draw(0);
+        assert_eq!(range, Some(0..10));
+        assert_eq!(col, "█████████▇");
+        let edge = buffer.get(0, 9).unwrap();
+        assert_eq!((edge.fg, edge.bg), (style.track, style.thumb));
+
+        // Scrolled to the end, the thumb starts seven eighths down.
+        let (range, col, buffer) = draw(1);
+        assert_eq!(range, Some(0..10));
+        assert_eq!(col, "▁█████████");
+        let edge = buffer.get(0, 0).unwrap();
+        assert_eq!((edge.fg, edge.bg), (style.thumb, style.track));
+    }
+
+    #[test]
+    fn test_huge_content_clamps_thumb_to_minimum() {
+        let style = ScrollbarStyle {
+            min_thumb: 2,
+            ..ScrollbarStyle::default()
+        };
+        let rect = Rect::new(3, 2, 1, 10);
+        let info = |offset| ScrollInfo::new(1_000_000, 10, offset);
+
+        let mut buffer = OptimizedBuffer::new(4, 12);
+        assert_eq!(
+            draw_scrollbar(&mut buffer, rect, info(0), style),
+            Some(2..4)
+        );
+        assert_eq!(column(&buffer, 3, 12), "..██        ");
+
+        let mut buffer = OptimizedBuffer::new(4, 12);
+        let max = info(0).max_offset();
+        assert_eq!(
+            draw_scrollbar(&mut buffer, rect, info(max / 2), style),
+            Some(6..8)
+        );
+        assert_eq!(
+            draw_scrollbar(&mut buffer, rect, info(u32::MAX), style),
+            Some(10..12)
+        );
+    }
+
+    #[test]
+    fn test_horizontal_bar_and_click_offsets() {
+        let style = ScrollbarStyle::default();
+        let rect = Rect::new(0, 0, 10, 1);
+        let info = ScrollInfo::new(30, 10, 5);
+
+        // 27 of 80 eighths, starting 13 eighths in: cells 1.625..5.
+        let mut buffer = OptimizedBuffer::new(10, 1);
+        assert_eq!(draw_scrollbar(&mut buffer, rect, info, style), Some(1..5));
+        let row: String = (0..10)
+            .map(|x| match buffer.get(x, 0).unwrap().content {
+                CellContent::Char(c) => c,
+                _ => '.',
+            })
+            .collect();
+        assert_eq!(row, " ▋███     ");
+        assert_eq!(buffer.get(1, 0).unwrap().fg, style.track);
+        assert_eq!(buffer.get(4, 0).unwrap().fg, style.thumb);
+
+        assert_eq!(scrollbar_offset_for_click(rect, info, style, 0), 0);
+        assert_eq!(scrollbar_offset_for_click(rect, info, style, 9), 20);
+        assert_eq!(scrollbar_offset_for_click(rect, info, style, 50), 20);
+        assert_eq!(scrollbar_offset_for_click(rect, info, style, 5), 12);
+
+        let vertical = Rect::new(0, 0, 1, 10);
+        let long = ScrollInfo::new(100, 10, 0);
+        assert_eq!(scrollbar_offset_for_click(vertical, long, style, 0), 0);
+        assert_eq!(scrollbar_offset_for_click(vertical, long, style, 9), 90);
+        assert_eq!(scrollbar_offset_for_click(vertical, long, style, 5), 50);
+    }
+}
@@ -0,0 +1,391 @@
+//! Styled text spans.
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::buffer::OptimizedBuffer;
+use crate::buffer::drawing::draw_char_with_pool;
+use crate::grapheme_pool::GraphemePool;
+use crate::link::LinkPool;
+use crate::renderer::Rect;
+use crate::style::Style;
+use crate::text::WrapMode;
+
+/// A run of text drawn in one style, optionally as a hyperlink.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span<'a> {
+    pub text: Cow<'a, str>,
+    pub style: Style,
+    /// Link ID from a [`LinkPool`], applied to every cell of the span.
+    pub link: Option<u32>,
+}
+
+impl<'a> Span<'a> {
+    /// Create a span of styled text.
+    #[must_use]
+    pub fn new(text: impl Into<Cow<'a, str>>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            link: None,
+        }
+    }
+
+    /// Create an unstyled span.
+    #[must_use]
+    pub fn raw(text: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(text, Style::NONE)
+    }
+
+    /// Make this span a hyperlink.
+    #[must_use]
+    pub fn with_link(mut self, link: u32) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// The style cells of this span are drawn with.
+    ///
+    /// Links missing from `links` are dropped rather than left dangling.
+    fn cell_style(&self, links: &LinkPool) -> Style {
+        let mut style = self.style;
+        if let Some(id) = self.link.filter(|&id| links.get(id).is_some()) {
+            style.attributes = style.attributes.with_link_id(id);
+        }
+        style
+    }
+}
+
+/// Line breaks end a row; other control characters have no cell.
+fn is_control(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_control)
+}
+
+fn is_line_break(grapheme: &str) -> bool {
+    matches!(grapheme, "\n" | "\r\n")
+}
+
+/// Draw spans left to right on one row, returning the columns advanced.
+pub fn draw_spans(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    spans: &[Span<'_>],
+    pool: &mut GraphemePool,
+    links: &LinkPool,
+) -> u32 {
+    let mut col = x;
+    for span in spans {
+        let style = span.cell_style(links);
+        for grapheme in span.text.graphemes(true) {
+            if !is_control(grapheme) {
+                let width = draw_char_with_pool(buffer, pool, col, y, grapheme, style);
+                col = col.saturating_add(width as u32);
+            }
+        }
+    }
+    col - x
+}
+
+/// Draw spans as a paragraph inside `rect`, returning the rows used.
+///
+/// `\n` always starts a new row. [`WrapMode::Char`] and [`WrapMode::Word`]
+/// wrap at the rect's width; with [`WrapMode::None`] and
+/// [`WrapMode::Truncate`] rows are cut off instead. Both wrapping modes drop
+/// the spaces a row breaks at rather than starting a row with them, and
+/// word wrapping splits words too long for a whole row. Content past the
+/// rect's height is not drawn.
+pub fn draw_spans_wrapped(
+    buffer: &mut OptimizedBuffer,
+    rect: Rect,
+    spans: &[Span<'_>],
+    wrap: WrapMode,
+    pool: &mut GraphemePool,
+    links: &LinkPool,
+) -> u32 {
+    let graphemes: Vec<(&str, u32, Style)> = spans
+        .iter()
+        .flat_map(|span| {
+            let style = span.cell_style(links);
+            span.text.graphemes(true).map(move |grapheme| {
+                let width = crate::unicode::display_width(grapheme) as u32;
+                (grapheme, width, style)
+            })
+        })
+        .filter(|&(grapheme, ..)| is_line_break(grapheme) || !is_control(grapheme))
+        .collect();
+
+    let mut layout = Layout {
+        buffer,
+        pool,
+        rect,
+        row: 0,
+        col: 0,
+        wrapped: false,
+    };
+    let mut i = 0;
+    while i < graphemes.len() && layout.row < rect.height {
+        let (grapheme, width, style) = graphemes[i];
+        if is_line_break(grapheme) {
+            layout.new_row(false);
+            i += 1;
+            continue;
+        }
+
+        match wrap {
+            WrapMode::None | WrapMode::Truncate { ellipsis: false } => {
+                layout.draw(grapheme, width, style);
+                i += 1;
+            }
+            WrapMode::Truncate { ellipsis: true } => {
+                if layout.col + width > rect.width {
+                    layout.ellipsis(style);
+                } else {
+                    layout.draw(grapheme, width, style);
+                }
+                i += 1;
+            }
+            // Spaces that don't fit hang off the row; the next word wraps.
+            WrapMode::Char | WrapMode::Word if grapheme.trim().is_empty() => {
+                let fits = layout.col + width <= rect.width;
+                if fits && !(layout.col == 0 && layout.wrapped) {
+                    layout.draw(grapheme, width, style);
+                }
+                i += 1;
+            }
+            WrapMode::Char => {
+                layout.draw_wrapping(grapheme, width, style);
+                i += 1;
+            }
+            WrapMode::Word => {
+                let end = graphemes[i..]
+                    .iter()
+                    .position(|&(g, ..)| is_line_break(g) || g.trim().is_empty())
+                    .map_or(graphemes.len(), |len| i + len);
+                let word_width: u32 = graphemes[i..end].iter().map(|&(_, w, _)| w).sum();
+                if layout.col > 0 && layout.col + word_width > rect.width {
+                    layout.new_row(true);
+                    continue;
+                }
+                for &(grapheme, width, style) in &graphemes[i..end] {
+                    layout.draw_wrapping(grapheme, width, style);
+                }
+                i = end;
+            }
+        }
+    }
+
+    (layout.row + u32::from(layout.col > 0)).min(rect.height)
+}
+
+/// Cursor state for [`draw_spans_wrapped`].
+struct Layout<'b> {
+    buffer: &'b mut OptimizedBuffer,
+    pool: &'b mut GraphemePool,
+    rect: Rect,
+    row: u32,
+    col: u32,
+    /// Whether the current row began by wrapping rather than at a `\n`.
+    wrapped: bool,
+}
+
+impl Layout<'_> {
+    fn new_row(&mut self, wrapped: bool) {
+        self.row += 1;
+        self.col = 0;
+        self.wrapped = wrapped;
+    }
+
+    /// Draw at the cursor if the grapheme fits in the row, advancing either way.
+    fn draw(&mut self, grapheme: &str, width: u32, style: Style) {
+        if self.row < self.rect.height && self.col + width <= self.rect.width {
+            draw_char_with_pool(
+                self.buffer,
+                self.pool,
+                self.rect.x + self.col,
+                self.rect.y + self.row,
+                grapheme,
+                style,
+            );
+        }
+        self.col += width;
+    }
+
+    /// Mark the row as cut off with `…` in its last column, once.
+    fn ellipsis(&mut self, style: Style) {
+        if self.col <= self.rect.width && self.rect.width > 0 {
+            self.col = self.rect.width - 1;
+            self.draw("…", 1, style);
+            self.col += 1;
+        }
+    }
+
+    /// Draw at the cursor, moving to a new row first if it doesn't fit.
+    fn draw_wrapping(&mut self, grapheme: &str, width: u32, style: Style) {
+        if self.col > 0 && self.col + width > self.rect.width {
+            self.new_row(true);
+        }
+        self.draw(grapheme, width, style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+    use crate::color::Rgba;
+    use crate::style::TextAttributes;
+
+    fn row_text(buffer: &OptimizedBuffer, pool: &GraphemePool, y: u32) -> String {
+        (0..buffer.width())
+            .filter_map(|x| match buffer.get(x, y).unwrap().content {
+                CellContent::Char(ch) => Some(ch.to_string()),
+                CellContent::Grapheme(id) => pool.get(id).map(str::to_string),
+                CellContent::Empty => Some(" ".to_string()),
+                CellContent::Continuation => None,
+            })
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_draw_spans_styles_widths_and_links() {
+        let mut buffer = OptimizedBuffer::new(20, 1);
+        let mut pool = GraphemePool::new();
+        let mut links = LinkPool::new();
+        let docs = links.alloc("https://example.com/docs");
+
+        let spans = [
+            Span::new("ok ", Style::bold()),
+            Span::new("⚠", Style::fg(Rgba::RED)),
+            Span::raw("👨‍👩‍👧"),
+            Span::new("docs", Style::underline()).with_link(docs),
+            Span::raw("!").with_link(999),
+        ];
+        let width = draw_spans(&mut buffer, 1, 0, &spans, &mut pool, &links);
+
+        // "ok " 3 + ⚠ 1 + family emoji 2 + "docs" 4 + "!" 1
+        assert_eq!(width, 11);
+        assert_eq!(row_text(&buffer, &pool, 0), " ok ⚠👨‍👩‍👧docs!");
+
+        let cell = |x| *buffer.get(x, 0).unwrap();
+        for x in 1..4 {
+            assert!(cell(x).attributes.contains(TextAttributes::BOLD));
+            assert_eq!(cell(x).attributes.link_id(), None);
+        }
+        assert_eq!(cell(4).fg, Rgba::RED);
+        assert!(!cell(4).attributes.contains(TextAttributes::BOLD));
+        assert!(cell(5).content.is_grapheme());
+        assert!(cell(6).is_continuation());
+        for x in 7..11 {
+            assert!(cell(x).attributes.contains(TextAttributes::UNDERLINE));
+            assert_eq!(cell(x).attributes.link_id(), Some(docs));
+        }
+        // Unknown link ids are dropped.
+        assert_eq!(cell(11).attributes.link_id(), None);
+    }
+
+    #[test]
+    fn test_draw_spans_clips_at_scissor() {
+        let mut buffer = OptimizedBuffer::new(10, 1);
+        let mut pool = GraphemePool::new();
+        buffer.push_scissor(crate::buffer::ClipRect::new(0, 0, 4, 1));
+        let spans = [Span::raw("abc"), Span::raw("def")];
+        let width = draw_spans(&mut buffer, 0, 0, &spans, &mut pool, &LinkPool::new());
+        buffer.pop_scissor();
+
+        assert_eq!(width, 6);
+        assert_eq!(row_text(&buffer, &pool, 0), "abcd");
+    }
+
+    #[test]
+    fn test_draw_spans_wrapped() {
+        let spans = [
+            Span::new("The quick ", Style::bold()),
+            Span::raw("brown fox jumps\nover"),
+            Span::new(" extraordinarily", Style::italic()),
+        ];
+        let draw = |wrap| {
+            let mut buffer = OptimizedBuffer::new(12, 6);
+            let mut pool = GraphemePool::new();
+            let rows = draw_spans_wrapped(
+                &mut buffer,
+                Rect::new(1, 0, 10, 5),
+                &spans,
+                wrap,
+                &mut pool,
+                &LinkPool::new(),
+            );
+            let lines: Vec<String> = (0..6).map(|y| row_text(&buffer, &pool, y)).collect();
+            (rows, lines)
+        };
+
+        let (rows, lines) = draw(WrapMode::Word);
+        assert_eq!(rows, 5);
+        assert_eq!(
+            lines,
+            [
+                " The quick",
+                " brown fox",
+                " jumps",
+                " over",
+                " extraordin",
+                ""
+            ]
+        );
+
+        let (rows, lines) = draw(WrapMode::Char);
+        assert_eq!(rows, 5);
+        assert_eq!(lines[..3], [" The quick", " brown fox", " jumps"]);
+
+        let (rows, lines) = draw(WrapMode::None);
+        assert_eq!(rows, 2);
+        assert_eq!(lines[..3], [" The quick", " over extra", ""]);
+    }
+
+    #[test]
+    fn test_draw_spans_wrapped_trailing_space_and_truncate() {
+        let draw = |text: &str, wrap| {
+            let mut buffer = OptimizedBuffer::new(5, 4);
+            let mut pool = GraphemePool::new();
+            let spans = [Span::new(text, Style::bold())];
+            let rows = draw_spans_wrapped(
+                &mut buffer,
+                Rect::new(0, 0, 5, 4),
+                &spans,
+                wrap,
+                &mut pool,
+                &LinkPool::new(),
+            );
+            let lines: Vec<String> = (0..4).map(|y| row_text(&buffer, &pool, y)).collect();
+            (rows, lines, buffer)
+        };
+
+        // A space landing on the wrap point never opens a row of its own.
+        for wrap in [WrapMode::Char, WrapMode::Word] {
+            let (rows, lines, _) = draw("abcde fghij ", wrap);
+            assert_eq!(rows, 2, "{wrap:?}");
+            assert_eq!(lines, ["abcde", "fghij", "", ""], "{wrap:?}");
+            let (rows, lines, _) = draw("abcde   fg", wrap);
+            assert_eq!(rows, 2, "{wrap:?}");
+            assert_eq!(lines[1], "fg", "{wrap:?}");
+        }
+
+        let (rows, lines, buffer) =
+            draw("abcdefg\nab\nabcde", WrapMode::Truncate { ellipsis: true });
+        assert_eq!(rows, 3);
+        assert_eq!(lines, ["abcd…", "ab", "abcde", ""]);
+        assert!(
+            buffer
+                .get(4, 0)
+                .unwrap()
+                .attributes
+                .contains(TextAttributes::BOLD),
+            "ellipsis takes the cut text's style"
+        );
+        let (_, lines, _) = draw("abcdefg", WrapMode::Truncate { ellipsis: false });
+        assert_eq!(lines[0], "abcde");
+    }
+}
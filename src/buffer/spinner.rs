@@ -0,0 +1,234 @@
+//! Spinners and indeterminate progress, animated from an external clock.
+//!
+//! Nothing here keeps time: callers pass the elapsed time `t` in seconds,
+//! so the same `t` always draws the same frame.
+
+use crate::buffer::{OptimizedBuffer, ProgressStyle};
+use crate::cell::Cell;
+use crate::style::Style;
+
+/// A looping sequence of text frames.
+///
+/// Frames are static strings of single-width characters, so drawing one
+/// allocates nothing and never splits a grapheme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spinner {
+    frames: &'static [&'static str],
+    interval: f32,
+}
+
+impl Spinner {
+    /// Braille dots circling one cell (`⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏`), 80 ms per frame.
+    pub const DOTS: Self = Self {
+        frames: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+        interval: 0.08,
+    };
+
+    /// A rotating ASCII line (`|/-\`), 130 ms per frame.
+    pub const LINE: Self = Self {
+        frames: &["|", "/", "-", "\\"],
+        interval: 0.13,
+    };
+
+    /// A one-cell bar growing to full height and shrinking back, 100 ms per
+    /// frame.
+    pub const GROWING_BAR: Self = Self {
+        frames: &[
+            "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█", "▇", "▆", "▅", "▄", "▃", "▂",
+        ],
+        interval: 0.1,
+    };
+
+    /// A dot bouncing across five cells, 100 ms per frame.
+    pub const BOUNCING_DOT: Self = Self {
+        frames: &[
+            "●    ", " ●   ", "  ●  ", "   ● ", "    ●", "   ● ", "  ●  ", " ●   ",
+        ],
+        interval: 0.1,
+    };
+
+    /// Create a spinner showing each of `frames` for `interval` seconds.
+    ///
+    /// Non-positive or non-finite intervals fall back to 100 ms.
+    #[must_use]
+    pub fn new(frames: &'static [&'static str], interval: f32) -> Self {
+        let interval = if interval > 0.0 && interval.is_finite() {
+            interval
+        } else {
+            0.1
+        };
+        Self { frames, interval }
+    }
+
+    /// The same frames shown for `interval` seconds each.
+    #[must_use]
+    pub fn with_interval(self, interval: f32) -> Self {
+        Self::new(self.frames, interval)
+    }
+
+    /// The frames, in display order.
+    #[must_use]
+    pub const fn frames(&self) -> &'static [&'static str] {
+        self.frames
+    }
+
+    /// Seconds each frame is shown.
+    #[must_use]
+    pub const fn interval(&self) -> f32 {
+        self.interval
+    }
+
+    /// Index of the frame shown at `t` seconds.
+    ///
+    /// Frame `n` covers `n * interval <= t < (n + 1) * interval`, looping
+    /// after the last frame. Negative times count backwards from frame 0;
+    /// NaN shows frame 0.
+    #[must_use]
+    pub fn frame_index(&self, t: f32) -> usize {
+        if self.frames.is_empty() {
+            return 0;
+        }
+        let tick = (f64::from(t) / f64::from(self.interval)).floor() as i64;
+        tick.rem_euclid(self.frames.len() as i64) as usize
+    }
+
+    /// The frame shown at `t` seconds (empty for a spinner without frames).
+    #[must_use]
+    pub fn frame(&self, t: f32) -> &'static str {
+        self.frames
+            .get(self.frame_index(t))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Draw the frame shown at `t` seconds with its first cell at `(x, y)`.
+    pub fn draw(&self, buffer: &mut OptimizedBuffer, x: u32, y: u32, style: Style, t: f32) {
+        buffer.draw_text(x, y, self.frame(t), style);
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::DOTS
+    }
+}
+
+/// Seconds for the indeterminate bar's highlight to cross the track once.
+pub const INDETERMINATE_PERIOD: f32 = 1.5;
+
+/// Draw a one-row indeterminate progress bar `width` cells wide.
+///
+/// A highlight a quarter of the track wide (at least one cell) slides in
+/// from the left and out past the right edge every
+/// [`INDETERMINATE_PERIOD`] seconds, then starts over. The highlight uses
+/// `style.filled` and the rest of the track `style.empty`;
+/// `style.show_percent` is ignored.
+pub fn draw_indeterminate_bar(
+    buffer: &mut OptimizedBuffer,
+    x: u32,
+    y: u32,
+    width: u32,
+    t: f32,
+    style: ProgressStyle,
+) {
+    let segment = (width / 4).max(1);
+    let travel = width + segment;
+    let phase = (f64::from(t) / f64::from(INDETERMINATE_PERIOD)).rem_euclid(1.0);
+    let phase = if phase.is_finite() { phase } else { 0.0 };
+    // Column just past the highlight's right edge, from 0 (hidden on the
+    // left) up to `travel` (hidden on the right).
+    let end = (phase * f64::from(travel)).floor() as u32;
+    let start = end.saturating_sub(segment);
+
+    for col in 0..width {
+        let cell = if (start..end).contains(&col) {
+            Cell::new('█', style.filled)
+        } else {
+            Cell::new(' ', style.empty)
+        };
+        buffer.set_blended(x + col, y, cell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+    use crate::color::Rgba;
+
+    fn row(buffer: &OptimizedBuffer, width: u32) -> String {
+        (0..width)
+            .map(|x| match buffer.get(x, 0).unwrap().content {
+                CellContent::Char(c) => c,
+                _ => ' ',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_frame_selection_is_deterministic() {
+        let dots = Spinner::DOTS;
+        assert_eq!(dots.frame(0.0), "⠋");
+        assert_eq!(dots.frame(0.079), "⠋");
+        assert_eq!(dots.frame(0.081), "⠙");
+        assert_eq!(dots.frame(0.5), "⠦");
+        // Ten 80 ms frames loop every 800 ms.
+        assert_eq!(dots.frame(0.85), dots.frame(0.05));
+        assert_eq!(dots.frame(-0.01), "⠏");
+        assert_eq!(dots.frame(f32::NAN), "⠋");
+
+        let line = Spinner::LINE.with_interval(1.0);
+        let frames: Vec<&str> = (0..6).map(|s| line.frame(s as f32 + 0.5)).collect();
+        assert_eq!(frames, ["|", "/", "-", "\\", "|", "/"]);
+    }
+
+    #[test]
+    fn test_ticks_advance_one_frame_per_interval() {
+        for spinner in [
+            Spinner::DOTS,
+            Spinner::LINE,
+            Spinner::GROWING_BAR,
+            Spinner::BOUNCING_DOT,
+        ] {
+            let len = spinner.frames().len();
+            // Sample mid-frame to stay clear of rounding at the boundaries.
+            let tick = |n: usize| (n as f32 + 0.5) * spinner.interval();
+            for n in 0..len * 2 {
+                assert_eq!(spinner.frame_index(tick(n)), n % len);
+            }
+            let width = crate::unicode::display_width(spinner.frames()[0]);
+            for frame in spinner.frames() {
+                assert_eq!(crate::unicode::display_width(frame), width);
+                assert_eq!(frame.chars().count(), width);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spinner_draw_and_fallbacks() {
+        let mut buffer = OptimizedBuffer::new(6, 1);
+        Spinner::BOUNCING_DOT.draw(&mut buffer, 0, 0, Style::fg(Rgba::GREEN), 0.25);
+        assert_eq!(row(&buffer, 6), "  ●   ");
+        assert_eq!(buffer.get(2, 0).unwrap().fg, Rgba::GREEN);
+
+        assert_eq!(Spinner::new(&[], 0.1).frame(1.0), "");
+        assert!((Spinner::new(&["a"], -1.0).interval() - 0.1).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_indeterminate_bar_slides_and_wraps() {
+        let style = ProgressStyle::default();
+        let at = |t: f32| {
+            let mut buffer = OptimizedBuffer::new(8, 1);
+            draw_indeterminate_bar(&mut buffer, 0, 0, 8, t, style);
+            row(&buffer, 8)
+        };
+        // 8 + 2 columns of travel over 1.5 s: 0.15 s per column.
+        assert_eq!(at(0.0), "        ");
+        assert_eq!(at(0.16), "█       ");
+        assert_eq!(at(0.31), "██      ");
+        assert_eq!(at(0.76), "   ██   ");
+        assert_eq!(at(1.36), "       █");
+        assert_eq!(at(1.5 + 0.31), at(0.31));
+    }
+}
@@ -0,0 +1,315 @@
+//! Pre-built cell sprites stamped with transparency and flipping.
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+use crate::color::Rgba;
+
+/// Glyph pairs swapped by a horizontal flip.
+const MIRROR_H: &[(char, char)] = &[
+    ('┌', '┐'),
+    ('└', '┘'),
+    ('├', '┤'),
+    ('┏', '┓'),
+    ('┗', '┛'),
+    ('┣', '┫'),
+    ('╔', '╗'),
+    ('╚', '╝'),
+    ('╠', '╣'),
+    ('╭', '╮'),
+    ('╰', '╯'),
+    ('╱', '╲'),
+    ('▌', '▐'),
+    ('▘', '▝'),
+    ('▖', '▗'),
+    ('▛', '▜'),
+    ('▙', '▟'),
+    ('←', '→'),
+    ('↖', '↗'),
+    ('↙', '↘'),
+    ('◀', '▶'),
+    ('◄', '►'),
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('<', '>'),
+    ('/', '\\'),
+];
+
+/// Glyph pairs swapped by a vertical flip.
+const MIRROR_V: &[(char, char)] = &[
+    ('┌', '└'),
+    ('┐', '┘'),
+    ('┬', '┴'),
+    ('┏', '┗'),
+    ('┓', '┛'),
+    ('┳', '┻'),
+    ('╔', '╚'),
+    ('╗', '╝'),
+    ('╦', '╩'),
+    ('╭', '╰'),
+    ('╮', '╯'),
+    ('╱', '╲'),
+    ('▀', '▄'),
+    ('▘', '▖'),
+    ('▝', '▗'),
+    ('▛', '▙'),
+    ('▜', '▟'),
+    ('↑', '↓'),
+    ('↖', '↙'),
+    ('↗', '↘'),
+    ('▲', '▼'),
+    ('/', '\\'),
+];
+
+/// The glyph `ch` becomes when flipped, or `ch` if it has no mirror.
+fn mirror(ch: char, pairs: &[(char, char)]) -> char {
+    pairs
+        .iter()
+        .find_map(|&(a, b)| match ch {
+            _ if ch == a => Some(b),
+            _ if ch == b => Some(a),
+            _ => None,
+        })
+        .unwrap_or(ch)
+}
+
+/// A block of cells drawn as a unit by [`OptimizedBuffer::draw_sprite`].
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    pub cells: OptimizedBuffer,
+    /// The cell drawn at the position passed to
+    /// [`OptimizedBuffer::draw_sprite`]. Flipping mirrors it too.
+    pub hotspot: (u32, u32),
+}
+
+impl Sprite {
+    /// Create a sprite with its hotspot at the top-left cell.
+    #[must_use]
+    pub fn new(cells: OptimizedBuffer) -> Self {
+        Self {
+            cells,
+            hotspot: (0, 0),
+        }
+    }
+
+    /// Set the cell drawn at the sprite's position.
+    #[must_use]
+    pub const fn with_hotspot(mut self, x: u32, y: u32) -> Self {
+        self.hotspot = (x, y);
+        self
+    }
+}
+
+/// How [`OptimizedBuffer::draw_sprite`] stamps a sprite.
+///
+/// Cells with empty content and a fully transparent background are always
+/// skipped, leaving what's underneath.
+#[derive(Clone, Copy, Default)]
+pub struct SpriteOptions<'a> {
+    /// Also skip cells with exactly this background.
+    pub key: Option<Rgba>,
+    pub flip_h: bool,
+    pub flip_v: bool,
+    /// Applied to the foreground and background of every drawn cell.
+    pub remap: Option<&'a dyn Fn(Rgba) -> Rgba>,
+}
+
+impl SpriteOptions<'_> {
+    fn is_skipped(&self, cell: &Cell) -> bool {
+        self.key == Some(cell.bg) || (cell.is_empty() && cell.bg.is_transparent())
+    }
+
+    /// The cell as drawn: mirrored and remapped.
+    fn transform(&self, mut cell: Cell) -> Cell {
+        if let CellContent::Char(ch) = cell.content {
+            let ch = if self.flip_h {
+                mirror(ch, MIRROR_H)
+            } else {
+                ch
+            };
+            let ch = if self.flip_v {
+                mirror(ch, MIRROR_V)
+            } else {
+                ch
+            };
+            cell.content = CellContent::Char(ch);
+        }
+        if let Some(remap) = self.remap {
+            cell.fg = remap(cell.fg);
+            cell.bg = remap(cell.bg);
+        }
+        cell
+    }
+}
+
+/// Stamp `sprite` with its hotspot at (`x`, `y`).
+///
+/// Cells are drawn like [`OptimizedBuffer::set_blended`]. A horizontal flip
+/// keeps wide characters in order, head before continuation. Like
+/// [`OptimizedBuffer::draw_buffer`], pooled graphemes are copied without
+/// touching their reference counts.
+pub fn draw_sprite(
+    buffer: &mut OptimizedBuffer,
+    x: i32,
+    y: i32,
+    sprite: &Sprite,
+    opts: &SpriteOptions<'_>,
+) {
+    let (width, height) = sprite.cells.size();
+    let (hot_x, hot_y) = sprite.hotspot;
+    let hot_x = if opts.flip_h {
+        width.saturating_sub(1).saturating_sub(hot_x)
+    } else {
+        hot_x
+    };
+    let hot_y = if opts.flip_v {
+        height.saturating_sub(1).saturating_sub(hot_y)
+    } else {
+        hot_y
+    };
+    let origin_x = i64::from(x) - i64::from(hot_x);
+    let origin_y = i64::from(y) - i64::from(hot_y);
+
+    let mut put = |col: u32, row: u32, cell: Cell| {
+        let (dest_x, dest_y) = (origin_x + i64::from(col), origin_y + i64::from(row));
+        if let (Ok(dest_x), Ok(dest_y)) = (u32::try_from(dest_x), u32::try_from(dest_y)) {
+            buffer.set_blended(dest_x, dest_y, opts.transform(cell));
+        }
+    };
+
+    for row in 0..height {
+        let dest_row = if opts.flip_v { height - 1 - row } else { row };
+        for col in 0..width {
+            let Some(&head) = sprite.cells.get(col, row) else {
+                continue;
+            };
+            // Continuations are drawn along with their head.
+            if head.is_continuation() || opts.is_skipped(&head) {
+                continue;
+            }
+            let span = (head.display_width() as u32).clamp(1, width - col);
+            let dest_col = if opts.flip_h { width - col - span } else { col };
+            // A wide char cut off at the left edge would leave only its
+            // continuation behind.
+            if origin_x + i64::from(dest_col) < 0 {
+                continue;
+            }
+            put(dest_col, dest_row, head);
+            for offset in 1..span {
+                if let Some(&tail) = sprite.cells.get(col + offset, row) {
+                    put(dest_col + offset, dest_row, tail);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::glyphs;
+    use crate::buffer::{ClipRect, Pattern};
+    use crate::renderer::Rect;
+    use crate::style::Style;
+
+    fn background(width: u32, height: u32) -> OptimizedBuffer {
+        let mut buffer = OptimizedBuffer::new(width, height);
+        let cell = |ch| Cell::new(ch, Style::bg(Rgba::BLACK));
+        buffer.fill_rect_pattern(
+            Rect::new(0, 0, width, height),
+            &Pattern::Checkerboard(cell('.'), cell(':')),
+        );
+        buffer
+    }
+
+    /// A 5x3 arrow with magenta-keyed and transparent holes.
+    fn arrow() -> Sprite {
+        let mut cells = OptimizedBuffer::new(5, 3);
+        let style = Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+        cells.draw_text(0, 0, "┌─", style);
+        cells.draw_text(0, 1, "├─日→", style);
+        cells.draw_text(0, 2, "└▀", style);
+        cells.set(2, 0, Cell::clear(Rgba::from_hex("#ff00ff").unwrap()));
+        Sprite::new(cells).with_hotspot(4, 1)
+    }
+
+    #[test]
+    fn test_flipped_sprite_keeps_keyed_background() {
+        let key = Rgba::from_hex("#ff00ff").unwrap();
+        let sprite = arrow();
+        let mut out = String::new();
+        for (flip_h, flip_v) in [(false, false), (true, false), (false, true), (true, true)] {
+            let mut buffer = background(9, 5);
+            let before = buffer.clone();
+            let opts = SpriteOptions {
+                key: Some(key),
+                flip_h,
+                flip_v,
+                ..SpriteOptions::default()
+            };
+            buffer.draw_sprite(4, 2, &sprite, &opts);
+            out.push_str(&format!("flip_h={flip_h} flip_v={flip_v}\n"));
+            out.push_str(&glyphs(&buffer, '·'));
+
+            // Keyed and transparent cells leave the background untouched.
+            let mut skipped = 0;
+            for y in 0..5 {
+                for x in 0..9 {
+                    let cell = buffer.get(x, y).unwrap();
+                    if cell.bg != Rgba::BLUE {
+                        assert_eq!(cell, before.get(x, y).unwrap(), "({x}, {y})");
+                        skipped += 1;
+                    }
+                }
+            }
+            // 45 cells minus 5 + 2 + 2 stamped.
+            assert_eq!(skipped, 36);
+        }
+        insta::assert_snapshot!(out);
+    }
+
+    #[test]
+    fn test_horizontal_flip_keeps_wide_chars_ordered() {
+        let mut buffer = OptimizedBuffer::new(5, 3);
+        let opts = SpriteOptions {
+            flip_h: true,
+            ..SpriteOptions::default()
+        };
+        // The hotspot mirrors to the top-right corner.
+        buffer.draw_sprite(4, 0, &arrow().with_hotspot(0, 0), &opts);
+        // "├─日→" mirrored is "←日─┤", with 日 still head then continuation.
+        let cell = |x| *buffer.get(x, 1).unwrap();
+        assert_eq!(cell(0).content, CellContent::Char('←'));
+        assert_eq!(cell(1).content, CellContent::Char('日'));
+        assert!(cell(2).is_continuation());
+        assert_eq!(cell(4).content, CellContent::Char('┤'));
+    }
+
+    #[test]
+    fn test_remap_and_clipping() {
+        let mut buffer = background(4, 3);
+        buffer.push_scissor(ClipRect::new(0, 0, 4, 2));
+        let remap = |color: Rgba| {
+            if color == Rgba::BLUE {
+                Rgba::RED
+            } else {
+                color
+            }
+        };
+        let opts = SpriteOptions {
+            remap: Some(&remap),
+            ..SpriteOptions::default()
+        };
+        // Hotspot (4, 1) at (1, 1) puts the sprite's origin at (-3, 0),
+        // cutting 日 off at the left edge.
+        buffer.draw_sprite(1, 1, &arrow(), &opts);
+        buffer.pop_scissor();
+
+        assert_eq!(glyphs(&buffer, '·'), ".:.:\n:→:.\n.:.:\n");
+        assert_eq!(buffer.get(0, 1).unwrap().bg, Rgba::BLACK);
+        assert_eq!(buffer.get(1, 1).unwrap().bg, Rgba::RED);
+        assert_eq!(buffer.get(1, 1).unwrap().fg, Rgba::WHITE);
+        // Row 2 is outside the scissor.
+        assert_eq!(buffer.get(0, 2).unwrap().bg, Rgba::BLACK);
+    }
+}
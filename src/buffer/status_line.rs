@@ -0,0 +1,356 @@
+//! Single-row status bars with left, center, and right sections.
+
+use std::borrow::Cow;
+
+use crate::buffer::OptimizedBuffer;
+use crate::buffer::aligned::{Align, Truncate, draw_text_aligned};
+use crate::buffer::spans::Span;
+use crate::renderer::Rect;
+use crate::style::Style;
+use crate::unicode::display_width;
+
+/// Narrowest an ellipsized span gets: one column of text and the ellipsis.
+const MIN_ELLIPSIZED: u32 = 2;
+/// Columns kept free between adjacent non-empty sections.
+const SECTION_GAP: u32 = 1;
+
+/// One of the three sections of a [`StatusLine`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusSection {
+    Left,
+    Center,
+    Right,
+}
+
+/// A span in a [`StatusLine`] section.
+#[derive(Clone, Debug)]
+pub struct StatusItem<'a> {
+    pub span: Span<'a>,
+    /// When the line is too narrow, lower priorities are ellipsized or
+    /// dropped first.
+    pub priority: u8,
+}
+
+impl<'a> StatusItem<'a> {
+    #[must_use]
+    pub const fn new(span: Span<'a>, priority: u8) -> Self {
+        Self { span, priority }
+    }
+}
+
+/// Where [`StatusLine::render`] drew a span, for registering hit areas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatusHit {
+    pub section: StatusSection,
+    /// Index of the span within its section.
+    pub index: usize,
+    pub rect: Rect,
+}
+
+/// A status bar: spans flush left, centered, and flush right.
+///
+/// Spans within a section are joined by `separator`. When the sections
+/// don't fit, spans give way in ascending priority, the later span first
+/// among equals: each is ellipsized if that makes the line fit, and
+/// dropped otherwise.
+#[derive(Clone, Debug)]
+pub struct StatusLine<'a> {
+    pub left: Vec<StatusItem<'a>>,
+    pub center: Vec<StatusItem<'a>>,
+    pub right: Vec<StatusItem<'a>>,
+    /// Drawn between adjacent spans of a section.
+    pub separator: Cow<'a, str>,
+}
+
+impl Default for StatusLine<'_> {
+    fn default() -> Self {
+        Self {
+            left: Vec::new(),
+            center: Vec::new(),
+            right: Vec::new(),
+            separator: Cow::Borrowed(" │ "),
+        }
+    }
+}
+
+/// A surviving span and the columns it gets.
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    section: StatusSection,
+    index: usize,
+    width: u32,
+    priority: u8,
+}
+
+impl<'a> StatusLine<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn left(mut self, items: Vec<StatusItem<'a>>) -> Self {
+        self.left = items;
+        self
+    }
+
+    #[must_use]
+    pub fn center(mut self, items: Vec<StatusItem<'a>>) -> Self {
+        self.center = items;
+        self
+    }
+
+    #[must_use]
+    pub fn right(mut self, items: Vec<StatusItem<'a>>) -> Self {
+        self.right = items;
+        self
+    }
+
+    #[must_use]
+    pub fn with_separator(mut self, separator: impl Into<Cow<'a, str>>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    fn items(&self, section: StatusSection) -> &[StatusItem<'a>] {
+        match section {
+            StatusSection::Left => &self.left,
+            StatusSection::Center => &self.center,
+            StatusSection::Right => &self.right,
+        }
+    }
+
+    fn separator_width(&self) -> u32 {
+        display_width(&self.separator) as u32
+    }
+
+    /// Columns taken by the slots of `section`, separators included.
+    fn section_width(&self, slots: &[Slot], section: StatusSection) -> u32 {
+        let (count, width) = slots
+            .iter()
+            .filter(|slot| slot.section == section)
+            .fold((0u32, 0), |(count, width), slot| {
+                (count + 1, width + slot.width)
+            });
+        width + self.separator_width() * count.saturating_sub(1)
+    }
+
+    fn needed_width(&self, slots: &[Slot]) -> u32 {
+        let sections = [
+            StatusSection::Left,
+            StatusSection::Center,
+            StatusSection::Right,
+        ];
+        let present = sections
+            .iter()
+            .filter(|&&section| slots.iter().any(|slot| slot.section == section))
+            .count() as u32;
+        let spans: u32 = sections
+            .iter()
+            .map(|&section| self.section_width(slots, section))
+            .sum();
+        spans + SECTION_GAP * present.saturating_sub(1)
+    }
+
+    /// The spans that fit in `width` columns and their fitted widths.
+    fn layout(&self, width: u32) -> Vec<Slot> {
+        let mut slots: Vec<Slot> = [
+            StatusSection::Left,
+            StatusSection::Center,
+            StatusSection::Right,
+        ]
+        .into_iter()
+        .flat_map(|section| {
+            self.items(section)
+                .iter()
+                .enumerate()
+                .map(move |(index, item)| Slot {
+                    section,
+                    index,
+                    width: display_width(&item.span.text) as u32,
+                    priority: item.priority,
+                })
+        })
+        .collect();
+
+        loop {
+            let over = self.needed_width(&slots).saturating_sub(width);
+            if over == 0 {
+                break;
+            }
+            // `min_by_key` keeps the first minimum, so reversing picks the
+            // last span among equal priorities.
+            let Some(victim) = (0..slots.len()).rev().min_by_key(|&i| slots[i].priority) else {
+                break;
+            };
+            let fitted = slots[victim].width.saturating_sub(over);
+            if fitted >= MIN_ELLIPSIZED {
+                slots[victim].width = fitted;
+            } else {
+                slots.remove(victim);
+            }
+        }
+        slots
+    }
+
+    /// Draw the status line across columns `0..width` of row `y`, filling
+    /// it with `base_style`, which span styles are merged over.
+    ///
+    /// Returns where each surviving span was drawn, left to right.
+    pub fn render(
+        &self,
+        buffer: &mut OptimizedBuffer,
+        y: u32,
+        width: u32,
+        base_style: Style,
+    ) -> Vec<StatusHit> {
+        draw_text_aligned(
+            buffer,
+            0,
+            y,
+            width,
+            "",
+            base_style,
+            Align::Left,
+            Truncate::None,
+            "",
+        );
+        let slots = self.layout(width);
+
+        let left = self.section_width(&slots, StatusSection::Left);
+        let center = self.section_width(&slots, StatusSection::Center);
+        let right = self.section_width(&slots, StatusSection::Right);
+        let right_x = width - right;
+        // Centered on the line, nudged aside by the other sections.
+        let lowest = if left > 0 { left + SECTION_GAP } else { 0 };
+        let highest = right_x.saturating_sub(if right > 0 { SECTION_GAP } else { 0 }) - center;
+        let center_x = ((width - center) / 2).min(highest).max(lowest);
+
+        let mut hits = Vec::with_capacity(slots.len());
+        let separator_width = self.separator_width();
+        let mut previous: Option<StatusSection> = None;
+        let mut x = 0;
+        for slot in &slots {
+            if previous == Some(slot.section) {
+                draw_text_aligned(
+                    buffer,
+                    x,
+                    y,
+                    separator_width,
+                    &self.separator,
+                    base_style,
+                    Align::Left,
+                    Truncate::None,
+                    "",
+                );
+                x += separator_width;
+            } else {
+                x = match slot.section {
+                    StatusSection::Left => 0,
+                    StatusSection::Center => center_x,
+                    StatusSection::Right => right_x,
+                };
+            }
+            previous = Some(slot.section);
+
+            let span = &self.items(slot.section)[slot.index].span;
+            let mut style = base_style.merge(span.style);
+            if let Some(link) = span.link {
+                style = style.with_link(link);
+            }
+            draw_text_aligned(
+                buffer,
+                x,
+                y,
+                slot.width,
+                &span.text,
+                style,
+                Align::Left,
+                Truncate::End,
+                "…",
+            );
+            hits.push(StatusHit {
+                section: slot.section,
+                index: slot.index,
+                rect: Rect::new(x, y, slot.width, 1),
+            });
+            x += slot.width;
+        }
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::glyph_row;
+    use crate::color::Rgba;
+
+    fn editor_status() -> StatusLine<'static> {
+        let item = |text: &'static str, priority| StatusItem::new(Span::raw(text), priority);
+        StatusLine::new()
+            .left(vec![
+                item("NORMAL", 9),
+                item("src/main.rs", 7),
+                item("main", 2),
+            ])
+            .center(vec![item("Ln 12, Col 4", 5)])
+            .right(vec![item("UTF-8", 1), item("rust", 3), item("12:45", 6)])
+    }
+
+    #[test]
+    fn test_spans_give_way_by_priority() {
+        let status = editor_status();
+        let mut out = String::new();
+        for width in [64, 50, 42, 36, 30, 24, 18, 12, 6, 2, 0] {
+            let mut buffer = OptimizedBuffer::new(64, 1);
+            let hits = status.render(&mut buffer, 0, width, Style::NONE);
+            let row: String = glyph_row(&buffer, 0, ' ')
+                .chars()
+                .take(width as usize)
+                .collect();
+            let survivors: Vec<String> = hits
+                .iter()
+                .map(|hit| {
+                    let section = match hit.section {
+                        StatusSection::Left => 'L',
+                        StatusSection::Center => 'C',
+                        StatusSection::Right => 'R',
+                    };
+                    format!("{section}{}@{}+{}", hit.index, hit.rect.x, hit.rect.width)
+                })
+                .collect();
+            out.push_str(&format!("{width:>2} |{row}| {}\n", survivors.join(" ")));
+
+            for hit in &hits {
+                assert!(hit.rect.x + hit.rect.width <= width, "width {width}");
+            }
+            if let Some(last) = hits.iter().rfind(|hit| hit.section == StatusSection::Right) {
+                assert_eq!(last.rect.x + last.rect.width, width, "width {width}");
+            }
+            let rerun = status.render(&mut OptimizedBuffer::new(64, 1), 0, width, Style::NONE);
+            assert_eq!(rerun, hits, "width {width}");
+        }
+        insta::assert_snapshot!(out);
+    }
+
+    #[test]
+    fn test_base_style_and_links() {
+        let status = StatusLine::new()
+            .left(vec![StatusItem::new(Span::new("ok", Style::bold()), 0)])
+            .right(vec![StatusItem::new(Span::raw("docs").with_link(7), 0)])
+            .with_separator("|");
+        let base = Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+        let mut buffer = OptimizedBuffer::new(10, 1);
+        let hits = status.render(&mut buffer, 0, 10, base);
+        assert_eq!(glyph_row(&buffer, 0, ' '), "ok    docs");
+        assert_eq!(hits[1].rect, Rect::new(6, 0, 4, 1));
+
+        let gap = buffer.get(3, 0).unwrap();
+        assert_eq!(gap.bg, Rgba::BLUE);
+        let bold = buffer.get(0, 0).unwrap();
+        assert_eq!((bold.fg, bold.bg), (Rgba::WHITE, Rgba::BLUE));
+        assert!(bold.attributes.contains(crate::style::TextAttributes::BOLD));
+        assert_eq!(buffer.get(6, 0).unwrap().attributes.link_id(), Some(7));
+    }
+}
@@ -0,0 +1,434 @@
+//! Tables of aligned, truncated text.
+
+use std::ops::Range;
+
+use crate::buffer::OptimizedBuffer;
+use crate::buffer::aligned::{Align, Truncate, draw_text_aligned};
+use crate::buffer::drawing::{BoxStyle, draw_box};
+use crate::buffer::lines::{draw_hline_glyph, draw_vline_glyph, is_line_glyph};
+use crate::buffer::spans::Span;
+use crate::cell::Cell;
+use crate::color::Rgba;
+use crate::renderer::Rect;
+
+/// Sizing and text placement for one table column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnSpec {
+    /// Columns whose minimums don't fit in the table are hidden.
+    pub min: u32,
+    pub max: Option<u32>,
+    /// Share of the width left over after minimums; 0 never grows.
+    pub weight: u32,
+    pub align: Align,
+    pub truncate: Truncate,
+}
+
+impl Default for ColumnSpec {
+    fn default() -> Self {
+        Self {
+            min: 1,
+            max: None,
+            weight: 1,
+            align: Align::Left,
+            truncate: Truncate::End,
+        }
+    }
+}
+
+impl ColumnSpec {
+    /// A column of exactly `width` cells.
+    #[must_use]
+    pub fn fixed(width: u32) -> Self {
+        Self {
+            min: width,
+            max: Some(width),
+            weight: 0,
+            ..Self::default()
+        }
+    }
+
+    const fn max_width(&self) -> u32 {
+        match self.max {
+            Some(max) => max,
+            None => u32::MAX,
+        }
+    }
+
+    fn min_width(&self) -> u32 {
+        self.min.min(self.max_width())
+    }
+}
+
+/// Rows of text laid out in columns by [`draw_table`].
+///
+/// Each row holds one span per column; missing spans leave the cell empty
+/// and extra spans are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct Table<'a> {
+    pub columns: Vec<ColumnSpec>,
+    /// Header row; empty for none.
+    pub headers: Vec<Span<'a>>,
+    pub rows: Vec<Vec<Span<'a>>>,
+    /// Outer border, column separators, and header separator. Without a
+    /// border, columns are separated by a space.
+    pub border: Option<BoxStyle>,
+    /// Background of every other body row, starting with the second.
+    pub stripe: Option<Rgba>,
+    /// Index of the first body row shown, for scrolling.
+    pub row_offset: usize,
+}
+
+impl Table<'_> {
+    #[must_use]
+    pub fn new(columns: Vec<ColumnSpec>) -> Self {
+        Self {
+            columns,
+            ..Self::default()
+        }
+    }
+}
+
+/// Split `inner` columns of width among the columns that fit, `gap` cells
+/// apart.
+fn column_widths(columns: &[ColumnSpec], inner: u32, gap: u32) -> Vec<u32> {
+    if inner == 0 {
+        return Vec::new();
+    }
+
+    // Show columns left to right while their minimums fit; the first column
+    // is always shown, squeezed if need be.
+    let mut visible = 0usize;
+    let mut needed = 0u32;
+    for spec in columns {
+        let need = needed
+            .saturating_add(spec.min_width())
+            .saturating_add(if visible > 0 { gap } else { 0 });
+        if need > inner && visible > 0 {
+            break;
+        }
+        needed = need;
+        visible += 1;
+    }
+
+    let available = inner.saturating_sub(gap * visible.saturating_sub(1) as u32);
+    let mut widths: Vec<u32> = columns[..visible]
+        .iter()
+        .map(ColumnSpec::min_width)
+        .collect();
+    if let Some(first) = widths.first_mut() {
+        *first = (*first).min(available);
+    }
+
+    let mut extra = available.saturating_sub(widths.iter().sum());
+    while extra > 0 {
+        let growable: Vec<usize> = (0..visible)
+            .filter(|&i| columns[i].weight > 0 && widths[i] < columns[i].max_width())
+            .collect();
+        if growable.is_empty() {
+            break;
+        }
+
+        let total: u64 = growable.iter().map(|&i| u64::from(columns[i].weight)).sum();
+        let mut given = 0;
+        for &i in &growable {
+            let share = u64::from(extra) * u64::from(columns[i].weight) / total;
+            let grow = (share as u32).min(columns[i].max_width() - widths[i]);
+            widths[i] += grow;
+            given += grow;
+        }
+        // Hand out rounding leftovers one cell at a time.
+        if given == 0 {
+            for &i in &growable {
+                if given < extra && widths[i] < columns[i].max_width() {
+                    widths[i] += 1;
+                    given += 1;
+                }
+            }
+        }
+        extra -= given;
+    }
+    widths
+}
+
+/// Draw `table` inside `rect`, returning the x range of each column.
+///
+/// Column widths start at their minimums, then share the remaining width by
+/// weight up to their maximums. Columns that don't fit get an empty range at
+/// the table's right edge. Cell text is aligned and truncated per column.
+/// Body rows past the bottom of the rect are not drawn.
+pub fn draw_table(buffer: &mut OptimizedBuffer, rect: Rect, table: &Table<'_>) -> Vec<Range<u32>> {
+    let edge = u32::from(table.border.is_some());
+    let inner = rect.width.saturating_sub(2 * edge);
+    let widths = column_widths(&table.columns, inner, 1);
+
+    let mut ranges = Vec::with_capacity(table.columns.len());
+    let mut x = rect.x + edge;
+    for &width in &widths {
+        ranges.push(x..x + width);
+        x += width + 1;
+    }
+    let hidden_x = rect.x + rect.width.saturating_sub(edge);
+    ranges.resize(table.columns.len(), hidden_x..hidden_x);
+    if inner == 0 || rect.height == 0 {
+        return ranges;
+    }
+
+    let bottom = rect.y + rect.height - 1;
+    let mut y = rect.y + edge;
+    let header_y = (!table.headers.is_empty()).then_some(y);
+    if header_y.is_some() {
+        y += 1 + edge;
+    }
+    let body_y = y;
+
+    let draw_row = |buffer: &mut OptimizedBuffer, y: u32, spans: &[Span<'_>]| {
+        for ((spec, range), span) in table.columns.iter().zip(&ranges).zip(spans) {
+            if range.is_empty() {
+                continue;
+            }
+            let mut style = span.style;
+            if let Some(link) = span.link {
+                style = style.with_link(link);
+            }
+            draw_text_aligned(
+                buffer,
+                range.start,
+                y,
+                range.end - range.start,
+                &span.text,
+                style,
+                spec.align,
+                spec.truncate,
+                "…",
+            );
+        }
+    };
+
+    if let Some(header_y) = header_y.filter(|&header_y| header_y + edge <= bottom) {
+        draw_row(buffer, header_y, &table.headers);
+    }
+    let body_end = bottom + 1 - edge;
+    for (y, (index, row)) in
+        (body_y..body_end).zip(table.rows.iter().enumerate().skip(table.row_offset))
+    {
+        if let Some(stripe) = table.stripe.filter(|_| index % 2 == 1) {
+            buffer.fill_rect(rect.x + edge, y, inner, 1, stripe);
+        }
+        draw_row(buffer, y, row);
+    }
+
+    if let Some(border) = &table.border {
+        draw_border(buffer, rect, border, header_y, &ranges[..widths.len()]);
+    }
+    ranges
+}
+
+/// Draw the outer border and separators, merging them into junctions.
+fn draw_border(
+    buffer: &mut OptimizedBuffer,
+    rect: Rect,
+    border: &BoxStyle,
+    header_y: Option<u32>,
+    columns: &[Range<u32>],
+) {
+    let chars = border.chars;
+    let style = border.style;
+    draw_box(
+        buffer,
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        border.clone(),
+    );
+
+    let bottom = rect.y + rect.height - 1;
+    let separator_y = header_y
+        .map(|header_y| header_y + 1)
+        .filter(|&y| y < bottom);
+    if let Some(y) = separator_y {
+        draw_hline_glyph(buffer, rect.x, y, rect.width, chars.top, style);
+    }
+    let separators: Vec<u32> = columns
+        .iter()
+        .skip(1)
+        .map(|range| range.start - 1)
+        .collect();
+    for &x in &separators {
+        draw_vline_glyph(buffer, x, rect.y, rect.height, chars.left, style);
+    }
+
+    // ASCII-style charsets don't merge; mark their junctions with the corner.
+    if !is_line_glyph(chars.top_left) {
+        let junction = Cell::new(chars.top_left, style);
+        let rows = [Some(rect.y), separator_y, Some(bottom)];
+        for y in rows.into_iter().flatten() {
+            for &x in &separators {
+                buffer.set_blended(x, y, junction);
+            }
+        }
+        if let Some(y) = separator_y {
+            buffer.set_blended(rect.x, y, junction);
+            buffer.set_blended(rect.x + rect.width - 1, y, junction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::glyphs;
+    use crate::style::Style;
+
+    fn processes() -> Table<'static> {
+        let row = |pid: &'static str, name: &'static str, cpu: &'static str, cmd: &'static str| {
+            vec![
+                Span::raw(pid),
+                Span::raw(name),
+                Span::raw(cpu),
+                Span::raw(cmd),
+            ]
+        };
+        Table {
+            headers: ["PID", "NAME", "CPU%", "COMMAND"]
+                .map(|header| Span::new(header, Style::bold()))
+                .to_vec(),
+            rows: vec![
+                row("1", "init", "0.0", "/sbin/init splash"),
+                row("412", "sshd", "0.1", "/usr/sbin/sshd -D"),
+                row("1337", "ターミナル", "12.5", "/opt/term/bin/term --gpu"),
+                row(
+                    "2048",
+                    "cargo",
+                    "98.0",
+                    "cargo test --workspace --all-features",
+                ),
+                row("4096", "rust-analyzer", "3.2", "rust-analyzer"),
+            ],
+            border: Some(BoxStyle::single(Style::NONE)),
+            stripe: Some(Rgba::BLUE),
+            ..Table::new(vec![
+                ColumnSpec {
+                    align: Align::Right,
+                    ..ColumnSpec::fixed(5)
+                },
+                ColumnSpec {
+                    min: 4,
+                    max: Some(10),
+                    weight: 1,
+                    ..ColumnSpec::default()
+                },
+                ColumnSpec {
+                    align: Align::Right,
+                    ..ColumnSpec::fixed(5)
+                },
+                ColumnSpec {
+                    min: 8,
+                    weight: 2,
+                    truncate: Truncate::Middle,
+                    ..ColumnSpec::default()
+                },
+            ])
+        }
+    }
+
+    #[test]
+    fn test_column_widths() {
+        let spec = |min, max, weight| ColumnSpec {
+            min,
+            max,
+            weight,
+            ..ColumnSpec::default()
+        };
+        // Leftover width shared 1:2, with the rounding remainder handed out.
+        assert_eq!(
+            column_widths(&[spec(2, None, 1), spec(2, None, 2)], 11, 1),
+            [4, 6]
+        );
+        // Capped columns pass their share on.
+        assert_eq!(
+            column_widths(&[spec(2, Some(3), 5), spec(2, None, 1)], 11, 1),
+            [3, 7]
+        );
+        // Weightless columns stay at their minimum, leaving slack unused.
+        assert_eq!(
+            column_widths(&[spec(2, None, 0), spec(2, Some(4), 1)], 11, 1),
+            [2, 4]
+        );
+        // Columns whose minimums don't fit are dropped; the first is squeezed.
+        assert_eq!(
+            column_widths(&[spec(4, None, 1), spec(4, None, 1)], 8, 1),
+            [8]
+        );
+        assert_eq!(
+            column_widths(&[spec(4, None, 1), spec(4, None, 1)], 3, 1),
+            [3]
+        );
+        assert!(column_widths(&[], 10, 1).is_empty());
+    }
+
+    #[test]
+    fn test_process_table() {
+        let mut table = processes();
+        table.row_offset = 1;
+        let mut buffer = OptimizedBuffer::new(42, 8);
+        let columns = draw_table(&mut buffer, Rect::new(1, 0, 40, 8), &table);
+
+        let stripes: String = (0..8)
+            .map(|y| {
+                if buffer.get(2, y).unwrap().bg == Rgba::BLUE {
+                    '#'
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        let lines: Vec<String> = glyphs(&buffer, ' ')
+            .lines()
+            .zip(stripes.chars())
+            .map(|(line, stripe)| format!("{stripe}{line}"))
+            .collect();
+        insta::assert_snapshot!(format!("{columns:?}\n{}", lines.join("\n")));
+    }
+
+    #[test]
+    fn test_too_narrow_rects() {
+        let table = processes();
+        for (width, visible) in [(20, 3), (8, 1), (3, 1), (2, 0), (0, 0)] {
+            let mut buffer = OptimizedBuffer::new(22, 4);
+            let columns = draw_table(&mut buffer, Rect::new(0, 0, width, 4), &table);
+            assert_eq!(columns.len(), 4);
+            let shown = columns.iter().filter(|range| !range.is_empty()).count();
+            assert_eq!(shown, visible, "width {width}");
+            for range in &columns {
+                assert!(range.end <= width, "width {width}: {columns:?}");
+            }
+        }
+
+        let mut buffer = OptimizedBuffer::new(8, 5);
+        draw_table(&mut buffer, Rect::new(0, 0, 8, 5), &table);
+        assert_eq!(
+            glyphs(&buffer, ' ').lines().collect::<Vec<_>>(),
+            ["┌──────┐", "│  PID │", "├──────┤", "│    1 │", "└──────┘"]
+        );
+    }
+
+    #[test]
+    fn test_wide_chars_and_ascii_border() {
+        let table = Table {
+            rows: vec![
+                vec![Span::raw("日本語"), Span::raw("ok")],
+                vec![Span::raw("中文字符"), Span::raw("界")],
+            ],
+            border: Some(BoxStyle::ascii(Style::NONE)),
+            ..Table::new(vec![ColumnSpec::fixed(5), ColumnSpec::fixed(1)])
+        };
+        let mut buffer = OptimizedBuffer::new(9, 4);
+        let columns = draw_table(&mut buffer, Rect::new(0, 0, 9, 4), &table);
+        assert_eq!(columns, [1..6, 7..8]);
+        assert_eq!(
+            glyphs(&buffer, ' ').lines().collect::<Vec<_>>(),
+            ["+-----+-+", "|日本…|…|", "|中文…|…|", "+-----+-+"]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Multi-line text laid out inside a rect.
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::buffer::OptimizedBuffer;
+use crate::buffer::aligned::{Align, Truncate, draw_text_aligned};
+use crate::renderer::Rect;
+use crate::style::Style;
+use crate::text::WrapMode;
+use crate::unicode::{calculate_text_width, find_position_by_width, find_wrap_position};
+
+/// Vertical placement of lines shorter than their rect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Layout options for [`OptimizedBuffer::draw_text_block`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextBlockOptions {
+    pub wrap: WrapMode,
+    /// Placement of each line within the rect's width.
+    pub align: Align,
+    pub valign: VAlign,
+    /// Blank rows between lines.
+    pub line_spacing: u32,
+    /// Ends the last visible line when lines are cut off at the bottom.
+    pub overflow: Option<String>,
+    pub tab_width: u8,
+}
+
+impl Default for TextBlockOptions {
+    fn default() -> Self {
+        Self {
+            wrap: WrapMode::Word,
+            align: Align::Left,
+            valign: VAlign::Top,
+            line_spacing: 0,
+            overflow: Some("…".to_string()),
+            tab_width: 4,
+        }
+    }
+}
+
+/// What [`OptimizedBuffer::draw_text_block`] managed to draw.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextBlockMetrics {
+    /// Lines drawn, after wrapping.
+    pub lines: u32,
+    /// Whether any text was left out, vertically or horizontally.
+    pub truncated: bool,
+}
+
+fn expand_tabs(line: &str, tab_width: u8) -> Cow<'_, str> {
+    if !line.contains('\t') {
+        return Cow::Borrowed(line);
+    }
+    let tab_width = u32::from(tab_width).max(1);
+    let mut out = String::with_capacity(line.len());
+    let mut col = 0;
+    for grapheme in line.graphemes(true) {
+        if grapheme == "\t" {
+            let spaces = tab_width - col % tab_width;
+            out.extend((0..spaces).map(|_| ' '));
+            col += spaces;
+        } else {
+            out.push_str(grapheme);
+            col += crate::unicode::display_width(grapheme) as u32;
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Split one tab-free line into rows of at most `width` columns.
+fn wrap_line<'a>(line: &'a str, width: u32, wrap: WrapMode, rows: &mut Vec<&'a str>) {
+    let mut rest = line;
+    loop {
+        if wrap == WrapMode::None || calculate_text_width(rest, 1) <= width {
+            rows.push(rest);
+            return;
+        }
+
+        // The word break helper may break after punctuation that itself
+        // overflows; fall back to a hard break then.
+        let at = find_wrap_position(rest, width, 1)
+            .filter(|_| wrap == WrapMode::Word)
+            .filter(|&at| calculate_text_width(rest[..at].trim_end_matches(' '), 1) <= width)
+            .unwrap_or_else(|| find_position_by_width(rest, width, 1));
+        // A grapheme wider than the whole row still takes a row of its own.
+        let at = if at == 0 {
+            rest.graphemes(true).next().map_or(rest.len(), str::len)
+        } else {
+            at
+        };
+
+        let (row, tail) = rest.split_at(at);
+        if wrap == WrapMode::Word {
+            rows.push(row.trim_end_matches(' '));
+            rest = tail.trim_start_matches(' ');
+            if rest.is_empty() {
+                return;
+            }
+        } else {
+            rows.push(row);
+            rest = tail;
+        }
+    }
+}
+
+/// Rows taken by `lines` lines separated by `spacing` blank rows.
+const fn rows_for(lines: u32, spacing: u32) -> u32 {
+    if lines == 0 {
+        0
+    } else {
+        lines + (lines - 1) * spacing
+    }
+}
+
+/// Draw `text` as a paragraph filling `rect`.
+///
+/// Every cell of the rect is drawn, with spaces in `style` around the text.
+pub fn draw_text_block(
+    buffer: &mut OptimizedBuffer,
+    rect: Rect,
+    text: &str,
+    style: Style,
+    opts: &TextBlockOptions,
+) -> TextBlockMetrics {
+    let source: Vec<Cow<'_, str>> = text
+        .lines()
+        .map(|line| expand_tabs(line, opts.tab_width))
+        .collect();
+    let mut lines = Vec::new();
+    for line in &source {
+        wrap_line(line, rect.width, opts.wrap, &mut lines);
+    }
+
+    let spacing = opts.line_spacing;
+    let total = lines.len() as u32;
+    let shown = total.min((rect.height + spacing) / (1 + spacing));
+    let cut_off = shown < total;
+    let slack = rect.height - rows_for(shown, spacing);
+    let top = match opts.valign {
+        VAlign::Top => 0,
+        VAlign::Middle => slack / 2,
+        VAlign::Bottom => slack,
+    };
+
+    let mut truncated = cut_off;
+    for row in 0..rect.height {
+        let y = rect.y + row;
+        let offset = row
+            .checked_sub(top)
+            .filter(|offset| offset % (1 + spacing) == 0);
+        let index = offset.map(|offset| offset / (1 + spacing));
+        let Some(line) = index
+            .filter(|&index| index < shown)
+            .map(|index| lines[index as usize])
+        else {
+            draw_text_aligned(
+                buffer,
+                rect.x,
+                y,
+                rect.width,
+                "",
+                style,
+                opts.align,
+                Truncate::None,
+                "",
+            );
+            continue;
+        };
+
+        match opts.overflow.as_deref() {
+            // Appending the marker and truncating the end keeps it visible
+            // whether or not the line itself fills the row.
+            Some(marker) if cut_off && index == Some(shown - 1) => {
+                let line = format!("{line}{marker}");
+                draw_text_aligned(
+                    buffer,
+                    rect.x,
+                    y,
+                    rect.width,
+                    &line,
+                    style,
+                    opts.align,
+                    Truncate::End,
+                    marker,
+                );
+            }
+            _ => {
+                truncated |= calculate_text_width(line, 1) > rect.width;
+                draw_text_aligned(
+                    buffer,
+                    rect.x,
+                    y,
+                    rect.width,
+                    line,
+                    style,
+                    opts.align,
+                    Truncate::None,
+                    "",
+                );
+            }
+        }
+    }
+
+    TextBlockMetrics {
+        lines: shown,
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellContent;
+
+    const PARAGRAPH: &str = "Press\tEnter to open https://example.com/a/really/long/path.\n\
+                             日本語のテキストも折り返す。\n\nDone.";
+
+    fn render(width: u32, height: u32, opts: &TextBlockOptions) -> String {
+        let mut buffer = OptimizedBuffer::new(width + 2, height);
+        let metrics = draw_text_block(
+            &mut buffer,
+            Rect::new(1, 0, width, height),
+            PARAGRAPH,
+            Style::NONE,
+            opts,
+        );
+        let mut out = format!("{width}x{height} {metrics:?}\n");
+        for y in 0..height {
+            out.push('|');
+            for x in 0..buffer.width() {
+                match buffer.get(x, y).unwrap().content {
+                    CellContent::Char(ch) => out.push(ch),
+                    CellContent::Empty => out.push('·'),
+                    CellContent::Grapheme(_) | CellContent::Continuation => {}
+                }
+            }
+            out.push_str("|\n");
+        }
+        out
+    }
+
+    #[test]
+    fn test_text_block_sizes() {
+        let opts = TextBlockOptions::default();
+        let out: Vec<String> = [(40, 6), (16, 12), (16, 5), (5, 4), (16, 0)]
+            .into_iter()
+            .map(|(width, height)| render(width, height, &opts))
+            .collect();
+        insta::assert_snapshot!(out.join("\n"));
+    }
+
+    #[test]
+    fn test_text_block_alignment_and_spacing() {
+        let opts = TextBlockOptions {
+            align: Align::Center,
+            valign: VAlign::Bottom,
+            line_spacing: 1,
+            overflow: Some("...".to_string()),
+            ..TextBlockOptions::default()
+        };
+        let centered = render(16, 9, &opts);
+        let opts = TextBlockOptions {
+            wrap: WrapMode::None,
+            align: Align::Right,
+            valign: VAlign::Middle,
+            overflow: None,
+            ..TextBlockOptions::default()
+        };
+        let unwrapped = render(16, 6, &opts);
+        insta::assert_snapshot!(format!("{centered}\n{unwrapped}"));
+    }
+
+    #[test]
+    fn test_text_block_fits_exactly() {
+        let mut buffer = OptimizedBuffer::new(8, 2);
+        let metrics = draw_text_block(
+            &mut buffer,
+            Rect::new(0, 0, 8, 2),
+            "two\nlines\n",
+            Style::NONE,
+            &TextBlockOptions::default(),
+        );
+        assert_eq!(
+            metrics,
+            TextBlockMetrics {
+                lines: 2,
+                truncated: false
+            }
+        );
+    }
+}
@@ -26,10 +26,16 @@
 //! let empty = Cell::clear(Rgba::BLACK);
 //! ```
 
-use crate::color::Rgba;
-use crate::style::{Style, TextAttributes};
+use crate::buffer::Opacity;
+use crate::color::{BlendMode, Rgba};
+use crate::style::{Style, TextAttributes, UnderlineStyle};
 use std::borrow::Cow;
 
+#[cfg(feature = "serde")]
+mod repr;
+#[cfg(feature = "serde")]
+pub use repr::{CellRepr, ContentRepr};
+
 /// Encoded grapheme reference with cached display width.
 ///
 /// Graphemes (multi-codepoint characters like emoji and ZWJ sequences) are stored
@@ -222,6 +228,7 @@ impl CellContent {
 /// - Content: A character, grapheme cluster, or empty/continuation marker
 /// - Foreground and background colors (with alpha for blending)
 /// - Text attributes (bold, italic, etc.)
+/// - Underline shape and color
 /// - Hyperlink ID packed into attributes for OSC 8 links
 ///
 /// # Alpha Blending
@@ -239,6 +246,10 @@ pub struct Cell {
     pub bg: Rgba,
     /// Text rendering attributes (includes packed link ID).
     pub attributes: TextAttributes,
+    /// Underline shape, used when [`TextAttributes::UNDERLINE`] is set.
+    pub underline_style: UnderlineStyle,
+    /// Underline color (None = same as the foreground).
+    pub underline_color: Option<Rgba>,
 }
 
 impl Cell {
@@ -250,6 +261,8 @@ impl Cell {
             fg: style.fg.unwrap_or(Rgba::WHITE),
             bg: style.bg.unwrap_or(Rgba::TRANSPARENT),
             attributes: style.attributes,
+            underline_style: style.underline_style,
+            underline_color: style.underline_color,
         }
     }
 
@@ -277,6 +290,8 @@ impl Cell {
             fg: style.fg.unwrap_or(Rgba::WHITE),
             bg: style.bg.unwrap_or(Rgba::TRANSPARENT),
             attributes: style.attributes,
+            underline_style: style.underline_style,
+            underline_color: style.underline_color,
         }
     }
 
@@ -295,6 +310,8 @@ impl Cell {
             fg: Rgba::TRANSPARENT,
             bg: Rgba::TRANSPARENT,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
         }
     }
 
@@ -306,6 +323,20 @@ impl Cell {
             fg: Rgba::WHITE,
             bg,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
+        }
+    }
+
+    /// Create a cleared/empty cell in the terminal's default colors.
+    ///
+    /// Written as SGR 39/49, so a translucent or themed terminal background
+    /// shows through.
+    #[must_use]
+    pub fn clear_default() -> Self {
+        Self {
+            fg: Rgba::DEFAULT_FG,
+            ..Self::clear(Rgba::DEFAULT_BG)
         }
     }
 
@@ -317,6 +348,8 @@ impl Cell {
             fg: Rgba::WHITE,
             bg,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
         }
     }
 
@@ -394,12 +427,20 @@ impl Cell {
             self.bg = bg;
         }
         self.attributes = self.attributes.merge(style.attributes);
+        self.underline_style = style.underline_style_over(self.underline_style);
+        if let Some(color) = style.underline_color {
+            self.underline_color = Some(color);
+        }
     }
 
-    /// Blend this cell's colors with a global opacity factor.
-    pub fn blend_with_opacity(&mut self, opacity: f32) {
-        self.fg = self.fg.multiply_alpha(opacity);
-        self.bg = self.bg.multiply_alpha(opacity);
+    /// Blend this cell's colors with an opacity factor.
+    ///
+    /// Accepts a single `f32` for both colors or an [`Opacity`] pair that
+    /// scales the foreground and background separately.
+    pub fn blend_with_opacity(&mut self, opacity: impl Into<Opacity>) {
+        let opacity = opacity.into();
+        self.fg = self.fg.multiply_alpha(opacity.fg);
+        self.bg = self.bg.multiply_alpha(opacity.bg);
     }
 
     /// Fast bitwise equality check for cell diffing.
@@ -417,22 +458,40 @@ impl Cell {
             && self.fg.bits_eq(other.fg)
             && self.bg.bits_eq(other.bg)
             && self.attributes == other.attributes
+            && self.underline_style == other.underline_style
+            && match (self.underline_color, other.underline_color) {
+                (Some(a), Some(b)) => a.bits_eq(b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
     }
 
     /// Blend this cell over a background cell using alpha compositing.
     #[must_use]
     pub fn blend_over(self, background: &Cell) -> Cell {
-        let (content, attributes) = if self.content.is_empty() {
-            (background.content, background.attributes)
+        self.blend_over_mode(background, BlendMode::Over)
+    }
+
+    /// Blend this cell over a background cell with `mode`.
+    ///
+    /// Foreground and background colors are each combined with
+    /// [`Rgba::blend`]. As with [`Self::blend_over`], an empty cell keeps the
+    /// background's content and attributes, so a layer of empty cells can
+    /// tint everything beneath it, text included.
+    #[must_use]
+    pub fn blend_over_mode(self, background: &Cell, mode: BlendMode) -> Cell {
+        let text = if self.content.is_empty() {
+            background
         } else {
-            (self.content, self.attributes)
+            &self
         };
 
         Cell {
-            content,
-            fg: self.fg.blend_over(background.fg),
-            bg: self.bg.blend_over(background.bg),
-            attributes,
+            content: text.content,
+            fg: self.fg.blend(background.fg, mode),
+            bg: self.bg.blend(background.bg, mode),
+            attributes: text.attributes,
+            underline_style: text.underline_style,
+            underline_color: text.underline_color,
         }
     }
 }
@@ -584,6 +643,22 @@ mod tests {
         assert_eq!(blended.attributes, fg_attrs);
     }
 
+    #[test]
+    fn test_blend_over_mode_tints_background_text() {
+        let bg = Cell::new('A', Style::builder().fg(Rgba::WHITE).bg(Rgba::RED).build());
+        let mut dim = Cell::clear(Rgba::rgb(0.5, 0.5, 0.5));
+        dim.fg = Rgba::rgb(0.5, 0.5, 0.5);
+
+        let blended = dim.blend_over_mode(&bg, BlendMode::Multiply);
+        assert_eq!(blended.content, CellContent::Char('A'));
+        assert_eq!(blended.fg.to_rgb_u8(), (128, 128, 128));
+        assert_eq!(blended.bg.to_rgb_u8(), (128, 0, 0));
+        assert_eq!(
+            dim.blend_over_mode(&bg, BlendMode::Over),
+            dim.blend_over(&bg)
+        );
+    }
+
     #[test]
     fn test_blend_over_empty_preserves_background_attrs_and_link() {
         let bg = Cell::new(
@@ -635,6 +710,8 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
         };
         buf.clear();
         grapheme_cell
@@ -823,6 +900,14 @@ mod tests {
         assert!(cell.bg.a < 1.0);
     }
 
+    #[test]
+    fn test_cell_blend_with_opacity_pair() {
+        let mut cell = Cell::new('A', Style::fg(Rgba::WHITE).with_bg(Rgba::BLACK));
+        cell.blend_with_opacity(Opacity::new(0.25, 1.0));
+        assert!((cell.fg.a - 0.25).abs() < f32::EPSILON);
+        assert!((cell.bg.a - 1.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn test_cell_bits_eq_vs_eq() {
         // bits_eq should behave same as PartialEq for normal cases
@@ -859,6 +944,8 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
         };
         let mut buf = Vec::new();
         cell.write_content(&mut buf).unwrap();
@@ -894,6 +981,8 @@ mod tests {
             fg: Rgba::WHITE,
             bg: Rgba::BLACK,
             attributes: TextAttributes::empty(),
+            underline_style: UnderlineStyle::Single,
+            underline_color: None,
         };
         assert_eq!(cell.display_width(), 4);
     }
@@ -925,4 +1014,20 @@ mod tests {
         assert_eq!(blended.fg, bg.fg);
         assert_eq!(blended.bg, bg.bg);
     }
+
+    #[test]
+    fn test_clear_default_cell() {
+        let cell = Cell::clear_default();
+        assert!(cell.fg.bits_eq(Rgba::DEFAULT_FG));
+        assert!(cell.bg.bits_eq(Rgba::DEFAULT_BG));
+        assert!(!cell.bits_eq(&Cell::clear(Rgba::BLACK)));
+        assert!(!cell.bits_eq(&Cell::transparent()));
+
+        // Default colors are transparent when composited over a concrete cell.
+        let under = Cell::new('x', Style::fg(Rgba::RED).with_bg(Rgba::BLUE));
+        let blended = cell.blend_over(&under);
+        assert!(blended.bg.bits_eq(Rgba::BLUE));
+        assert!(blended.fg.bits_eq(Rgba::RED));
+        assert_eq!(blended.content, CellContent::Char('x'));
+    }
 }
@@ -0,0 +1,175 @@
+//! Serializable form of [`Cell`] (`serde` feature).
+//!
+//! A cell's grapheme content is only an ID into a [`GraphemePool`], so
+//! cells are not serialized directly. Convert them through [`CellRepr`],
+//! which carries the grapheme as its string and allocates it back into a
+//! pool on the way in.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Cell, CellContent, GraphemeId};
+use crate::color::Rgba;
+use crate::grapheme_pool::GraphemePool;
+use crate::style::{TextAttributes, UnderlineStyle};
+
+/// Cell content with graphemes resolved to their text.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentRepr {
+    /// A character or grapheme cluster.
+    Text(String),
+    /// A grapheme the pool could not resolve, kept as a blank of this width.
+    Placeholder(u8),
+    /// Empty/cleared cell.
+    Empty,
+    /// Continuation of a wide character.
+    Continuation,
+}
+
+/// A [`Cell`] with its content resolved against a [`GraphemePool`].
+///
+/// ```
+/// use opentui_rust::cell::CellRepr;
+/// use opentui_rust::{Cell, CellContent, GraphemePool, Rgba};
+///
+/// let mut pool = GraphemePool::new();
+/// let cell = Cell {
+///     content: CellContent::Grapheme(pool.alloc("👨‍👩‍👧")),
+///     ..Cell::clear(Rgba::BLACK)
+/// };
+///
+/// let json = serde_json::to_string(&CellRepr::from_cell(&cell, &pool)).unwrap();
+/// let mut other = GraphemePool::new();
+/// let back = serde_json::from_str::<CellRepr>(&json).unwrap().into_cell(&mut other);
+/// assert_eq!(other.get(back.content.grapheme_id().unwrap()), Some("👨‍👩‍👧"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CellRepr {
+    pub content: ContentRepr,
+    pub fg: Rgba,
+    pub bg: Rgba,
+    #[serde(default, skip_serializing_if = "TextAttributes::is_empty")]
+    pub attributes: TextAttributes,
+    #[serde(default, skip_serializing_if = "is_single")]
+    pub underline_style: UnderlineStyle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underline_color: Option<Rgba>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde passes a reference
+fn is_single(style: &UnderlineStyle) -> bool {
+    *style == UnderlineStyle::Single
+}
+
+impl CellRepr {
+    /// Capture `cell`, resolving grapheme content through `pool`.
+    #[must_use]
+    pub fn from_cell(cell: &Cell, pool: &GraphemePool) -> Self {
+        let content = match cell.content {
+            CellContent::Char(ch) => ContentRepr::Text(ch.to_string()),
+            CellContent::Grapheme(id) => pool.get(id).map_or_else(
+                || ContentRepr::Placeholder(id.width() as u8),
+                |text| ContentRepr::Text(text.to_string()),
+            ),
+            CellContent::Empty => ContentRepr::Empty,
+            CellContent::Continuation => ContentRepr::Continuation,
+        };
+        Self {
+            content,
+            fg: cell.fg,
+            bg: cell.bg,
+            attributes: cell.attributes,
+            underline_style: cell.underline_style,
+            underline_color: cell.underline_color,
+        }
+    }
+
+    /// Rebuild the cell, allocating multi-codepoint text in `pool`.
+    ///
+    /// The returned cell owns one reference to any grapheme it holds.
+    #[must_use]
+    pub fn into_cell(self, pool: &mut GraphemePool) -> Cell {
+        let content = match self.content {
+            ContentRepr::Text(text) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => CellContent::Char(ch),
+                    (None, _) => CellContent::Empty,
+                    _ => CellContent::Grapheme(pool.alloc(&text)),
+                }
+            }
+            ContentRepr::Placeholder(width) => {
+                CellContent::Grapheme(GraphemeId::placeholder(width))
+            }
+            ContentRepr::Empty => CellContent::Empty,
+            ContentRepr::Continuation => CellContent::Continuation,
+        };
+        Cell {
+            content,
+            fg: self.fg,
+            bg: self.bg,
+            attributes: self.attributes,
+            underline_style: self.underline_style,
+            underline_color: self.underline_color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Style;
+
+    fn round_trip(cell: &Cell, pool: &GraphemePool, into: &mut GraphemePool) -> (String, Cell) {
+        let json = serde_json::to_string(&CellRepr::from_cell(cell, pool)).unwrap();
+        let repr: CellRepr = serde_json::from_str(&json).unwrap();
+        (json, repr.into_cell(into))
+    }
+
+    #[test]
+    fn char_cell_round_trip() {
+        let pool = GraphemePool::new();
+        let style = Style::fg(Rgba::RED).with_bold().with_link(3);
+        let cell = Cell::new('A', style);
+        let (json, back) = round_trip(&cell, &pool, &mut GraphemePool::new());
+        assert_eq!(
+            json,
+            r##"{"content":{"text":"A"},"fg":"#ff0000","bg":"#00000000","attributes":["bold","link:3"]}"##
+        );
+        assert_eq!(back, cell);
+    }
+
+    #[test]
+    fn grapheme_moves_between_pools() {
+        let mut pool = GraphemePool::new();
+        let id = pool.alloc("👨‍👩‍👧");
+        let cell = Cell {
+            content: CellContent::Grapheme(id),
+            ..Cell::clear(Rgba::BLACK)
+        };
+
+        let mut other = GraphemePool::new();
+        let (_, back) = round_trip(&cell, &pool, &mut other);
+        let back_id = back.content.grapheme_id().unwrap();
+        assert_eq!(other.get(back_id), Some("👨‍👩‍👧"));
+        assert_eq!(back_id.width(), id.width());
+        assert_eq!(other.refcount(back_id), 1);
+    }
+
+    #[test]
+    fn unresolved_grapheme_stays_a_placeholder() {
+        let cell = Cell::from_grapheme("👍🏽", Style::NONE);
+        let (json, back) = round_trip(&cell, &GraphemePool::new(), &mut GraphemePool::new());
+        assert!(json.contains(r#""content":{"placeholder":2}"#), "{json}");
+        assert_eq!(back, cell);
+    }
+
+    #[test]
+    fn empty_and_continuation_round_trip() {
+        let pool = GraphemePool::new();
+        for cell in [Cell::clear(Rgba::BLUE), Cell::continuation(Rgba::BLUE)] {
+            let (_, back) = round_trip(&cell, &pool, &mut GraphemePool::new());
+            assert_eq!(back, cell);
+        }
+    }
+}
@@ -5,8 +5,10 @@
 //!
 //! - **Color creation**: From f32/u8 components, hex strings, or HSV values
 //! - **Alpha blending**: Porter-Duff "over" compositing for layered rendering
-//! - **Color conversion**: To/from 256-color and 16-color terminal palettes
-//! - **Interpolation**: Linear interpolation between colors
+//! - **Color conversion**: To/from 256-color and 16-color terminal palettes,
+//!   matching perceptually in Oklab
+//! - **Interpolation**: Linear and Oklab interpolation, and multi-stop
+//!   [`Gradient`]s in sRGB, linear RGB, Oklab, or Oklch
 //!
 //! # Examples
 //!
@@ -26,6 +28,15 @@
 //! ```
 
 use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::error::{Error, Result};
+
+mod names;
+#[cfg(feature = "serde")]
+pub mod repr;
 
 /// RGBA color with f32 components in range [0.0, 1.0].
 ///
@@ -107,6 +118,29 @@ impl Rgba {
         a: 1.0,
     };
 
+    /// The terminal's default foreground color, written as SGR 39.
+    ///
+    /// A sentinel rather than a real color: it is fully transparent, so
+    /// blending it over a color leaves that color, and its out-of-range
+    /// channels keep it distinct from every concrete color under
+    /// [`Self::bits_eq`].
+    pub const DEFAULT_FG: Self = Self {
+        r: -1.0,
+        g: -1.0,
+        b: -1.0,
+        a: 0.0,
+    };
+
+    /// The terminal's default background color, written as SGR 49.
+    ///
+    /// See [`Self::DEFAULT_FG`].
+    pub const DEFAULT_BG: Self = Self {
+        r: -2.0,
+        g: -2.0,
+        b: -2.0,
+        a: 0.0,
+    };
+
     /// Create a new RGBA color from f32 components.
     #[must_use]
     pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
@@ -143,44 +177,118 @@ impl Rgba {
 
     /// Parse a hex color string (e.g., "#FF0000" or "FF0000").
     ///
-    /// Supports 3-char (#RGB), 6-char (#RRGGBB), and 8-char (#RRGGBBAA) formats.
+    /// Supports `#RGB`, `#RGBA`, `#RRGGBB`, and `#RRGGBBAA`, with or without
+    /// the `#`, in either case.
     #[must_use]
     pub fn from_hex(hex: &str) -> Option<Self> {
         let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let nibble = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok().map(|n| n * 17);
 
         match hex.len() {
-            3 => {
-                // #RGB -> #RRGGBB
-                let r = u8::from_str_radix(&hex[0..1], 16).ok()?;
-                let g = u8::from_str_radix(&hex[1..2], 16).ok()?;
-                let b = u8::from_str_radix(&hex[2..3], 16).ok()?;
-                Some(Self::from_rgb_u8(r * 17, g * 17, b * 17))
-            }
-            6 => {
-                let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-                let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-                let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-                Some(Self::from_rgb_u8(r, g, b))
-            }
-            8 => {
-                let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-                let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-                let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-                let a = u8::from_str_radix(&hex[6..8], 16).ok()?;
-                Some(Self::from_rgba_u8(r, g, b, a))
-            }
+            3 => Some(Self::from_rgb_u8(nibble(0)?, nibble(1)?, nibble(2)?)),
+            4 => Some(Self::from_rgba_u8(
+                nibble(0)?,
+                nibble(1)?,
+                nibble(2)?,
+                nibble(3)?,
+            )),
+            6 => Some(Self::from_rgb_u8(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Some(Self::from_rgba_u8(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
             _ => None,
         }
     }
 
+    /// Format as lowercase `#rrggbb`, or `#rrggbbaa` when not fully opaque.
+    ///
+    /// The inverse of [`Self::from_hex`], up to 8-bit rounding.
+    #[must_use]
+    pub fn to_hex(self) -> String {
+        let (r, g, b, a) = self.to_rgba_u8();
+        if a == u8::MAX {
+            format!("#{r:02x}{g:02x}{b:02x}")
+        } else {
+            format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+        }
+    }
+
+    /// Look up a CSS named color such as `"dodgerblue"`, ignoring case.
+    ///
+    /// Covers the full CSS/X11 set, including both `gray` and `grey`
+    /// spellings and `transparent`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        names::lookup(name)
+    }
+
+    /// Parse a color from hex, `rgb()`/`rgba()` notation, or a CSS name.
+    ///
+    /// Surrounding whitespace is ignored. Functional channels are 0-255 or
+    /// percentages, alpha is 0-1 or a percentage, and out-of-range values
+    /// are clamped. The keywords `default` (or `default-fg`) and
+    /// `default-bg` parse as [`Self::DEFAULT_FG`] and [`Self::DEFAULT_BG`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidColor`] with the input if no format matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentui_rust::Rgba;
+    ///
+    /// assert_eq!(Rgba::parse("#f00").unwrap(), Rgba::RED);
+    /// assert_eq!(Rgba::parse("rgb(255, 0, 0)").unwrap(), Rgba::RED);
+    /// assert_eq!(Rgba::parse(" Red ").unwrap(), Rgba::RED);
+    /// assert!(Rgba::parse("#ggg").is_err());
+    /// assert!(Rgba::parse("default").unwrap().bits_eq(Rgba::DEFAULT_FG));
+    /// ```
+    pub fn parse(input: &str) -> Result<Self> {
+        let trimmed = input.trim();
+        Self::from_default_keyword(trimmed)
+            .or_else(|| Self::from_hex(trimmed))
+            .or_else(|| parse_functional(trimmed))
+            .or_else(|| Self::from_name(trimmed))
+            .ok_or_else(|| Error::InvalidColor(input.to_string()))
+    }
+
+    /// The default color sentinel named by `default`, `default-fg`, or
+    /// `default-bg`, case-insensitively.
+    pub(crate) fn from_default_keyword(keyword: &str) -> Option<Self> {
+        if keyword.eq_ignore_ascii_case("default") || keyword.eq_ignore_ascii_case("default-fg") {
+            Some(Self::DEFAULT_FG)
+        } else if keyword.eq_ignore_ascii_case("default-bg") {
+            Some(Self::DEFAULT_BG)
+        } else {
+            None
+        }
+    }
+
+    /// Hex for concrete colors, or `default` / `default-bg` for the
+    /// sentinels, so [`Self::parse`] reads it back bit for bit.
+    pub(crate) fn to_color_string(self) -> String {
+        if self.bits_eq(Self::DEFAULT_FG) {
+            "default".to_string()
+        } else if self.bits_eq(Self::DEFAULT_BG) {
+            "default-bg".to_string()
+        } else {
+            self.to_hex()
+        }
+    }
+
     /// Create a color from HSV values.
     ///
-    /// - h: Hue in degrees [0, 360)
-    /// - s: Saturation [0, 1]
-    /// - v: Value [0, 1]
+    /// - h: Hue in degrees, wrapped into [0, 360)
+    /// - s: Saturation, clamped to [0, 1]
+    /// - v: Value, clamped to [0, 1]
     #[must_use]
     #[allow(clippy::many_single_char_names)]
     pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
         if s <= 0.0 {
             return Self::rgb(v, v, v);
         }
@@ -203,6 +311,100 @@ impl Rgba {
         }
     }
 
+    /// Create a color from HSL values.
+    ///
+    /// - h: Hue in degrees, wrapped into [0, 360)
+    /// - s: Saturation, clamped to [0, 1]
+    /// - l: Lightness, clamped to [0, 1]
+    #[must_use]
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let v = s.mul_add(l.min(1.0 - l), l);
+        let s_v = if v > 0.0 { 2.0 * (1.0 - l / v) } else { 0.0 };
+        Self::from_hsv(h, s_v, v)
+    }
+
+    /// Convert to HSV as `(hue, saturation, value)`.
+    ///
+    /// Hue is in degrees [0, 360), and 0 for grays where it is undefined.
+    /// Alpha is ignored.
+    #[must_use]
+    // `max` is always exactly one of the channels
+    #[allow(clippy::float_cmp)]
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let r = self.r.clamp(0.0, 1.0);
+        let g = self.g.clamp(0.0, 1.0);
+        let b = self.b.clamp(0.0, 1.0);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+
+        let hue = if delta <= 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max > 0.0 { delta / max } else { 0.0 };
+        (wrap_hue(hue), saturation, max)
+    }
+
+    /// Convert to HSL as `(hue, saturation, lightness)`.
+    ///
+    /// Hue is in degrees [0, 360), and 0 for grays where it is undefined.
+    /// Alpha is ignored.
+    #[must_use]
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, s_v, v) = self.to_hsv();
+        let lightness = v * (1.0 - s_v / 2.0);
+        let saturation = if lightness > 0.0 && lightness < 1.0 {
+            (v - lightness) / lightness.min(1.0 - lightness)
+        } else {
+            0.0
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Rebuild from HSL after applying `f`, keeping alpha.
+    fn map_hsl(self, f: impl FnOnce(f32, f32, f32) -> (f32, f32, f32)) -> Self {
+        let (h, s, l) = self.to_hsl();
+        let (h, s, l) = f(h, s, l);
+        Self::from_hsl(h, s, l).with_alpha(self.a)
+    }
+
+    /// Return this color with its HSL lightness set to `lightness`.
+    #[must_use]
+    pub fn with_lightness(self, lightness: f32) -> Self {
+        self.map_hsl(|h, s, _| (h, s, lightness))
+    }
+
+    /// Raise HSL lightness by `amount`, e.g. `lighten(0.1)` for a hover color.
+    #[must_use]
+    pub fn lighten(self, amount: f32) -> Self {
+        self.map_hsl(|h, s, l| (h, s, l + amount))
+    }
+
+    /// Lower HSL lightness by `amount`.
+    #[must_use]
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Add `delta` to HSL saturation; a negative delta desaturates.
+    #[must_use]
+    pub fn saturate(self, delta: f32) -> Self {
+        self.map_hsl(|h, s, l| (h, s + delta, l))
+    }
+
+    /// Rotate the hue by `degrees`, wrapping around the color wheel.
+    #[must_use]
+    pub fn rotate_hue(self, degrees: f32) -> Self {
+        self.map_hsl(|h, s, l| (h + degrees, s, l))
+    }
+
     /// Blend this color over another using standard alpha compositing (Porter-Duff "over").
     ///
     /// `self` is the foreground (on top), `other` is the background.
@@ -236,6 +438,35 @@ impl Rgba {
         }
     }
 
+    /// Blend this color onto `dst` with `mode`.
+    ///
+    /// `self` is the source (on top). Alpha works the same way for every
+    /// mode, following the W3C compositing model: the mode's mixed color is
+    /// used where the backdrop is opaque and the plain source color where it
+    /// is transparent, and the result is then composited over `dst` with
+    /// the source's alpha. So a transparent source leaves `dst` unchanged,
+    /// and [`BlendMode::Over`] is exactly [`Self::blend_over`].
+    #[must_use]
+    pub fn blend(self, dst: Self, mode: BlendMode) -> Self {
+        if mode == BlendMode::Over {
+            return self.blend_over(dst);
+        }
+        let channel = |src: f32, backdrop: f32| {
+            mix(
+                src,
+                mode.mix(backdrop.clamp(0.0, 1.0), src.clamp(0.0, 1.0)),
+                dst.a.clamp(0.0, 1.0),
+            )
+        };
+        Self {
+            r: channel(self.r, dst.r),
+            g: channel(self.g, dst.g),
+            b: channel(self.b, dst.b),
+            a: self.a,
+        }
+        .blend_over(dst)
+    }
+
     /// Return a new color with the specified alpha value.
     #[must_use]
     pub const fn with_alpha(self, alpha: f32) -> Self {
@@ -260,6 +491,13 @@ impl Rgba {
         (to_u8(self.r), to_u8(self.g), to_u8(self.b))
     }
 
+    /// Convert to 16-bit-per-channel RGB, clamping values to [0, 65535].
+    #[must_use]
+    pub fn to_rgb_u16(self) -> (u16, u16, u16) {
+        let to_u16 = |value: f32| (value * 65535.0).round().clamp(0.0, 65535.0) as u16;
+        (to_u16(self.r), to_u16(self.g), to_u16(self.b))
+    }
+
     /// Convert to u8 RGBA tuple, clamping values to [0, 255].
     #[must_use]
     pub fn to_rgba_u8(self) -> (u8, u8, u8, u8) {
@@ -280,6 +518,16 @@ impl Rgba {
         self.a >= 1.0
     }
 
+    /// Whether this is [`Self::DEFAULT_FG`] or [`Self::DEFAULT_BG`].
+    ///
+    /// Either sentinel means the terminal default for whichever color it is
+    /// used as.
+    #[inline]
+    #[must_use]
+    pub const fn is_default(self) -> bool {
+        self.bits_eq(Self::DEFAULT_FG) || self.bits_eq(Self::DEFAULT_BG)
+    }
+
     /// Convert to packed u64 for fast integer comparison.
     ///
     /// This packs all 4 f32 components into a single u128 by reinterpreting
@@ -317,6 +565,111 @@ impl Rgba {
         0.299 * self.r + 0.587 * self.g + 0.114 * self.b
     }
 
+    /// WCAG 2.1 relative luminance: 0 for black, 1 for white. Alpha is ignored.
+    ///
+    /// Unlike [`Self::luminance`], channels are linearized first, as the
+    /// contrast formulas require.
+    #[must_use]
+    pub fn relative_luminance(self) -> f32 {
+        0.0722f32.mul_add(
+            srgb_to_linear(self.b),
+            0.2126f32.mul_add(srgb_to_linear(self.r), 0.7152 * srgb_to_linear(self.g)),
+        )
+    }
+
+    /// Convert to the Oklab perceptual color space as `[L, a, b]`, ignoring alpha.
+    #[must_use]
+    pub fn to_oklab(self) -> [f32; 3] {
+        let (red, green, blue) = (
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        );
+
+        let long = 0.051_457_565f32
+            .mul_add(blue, 0.412_221_47f32.mul_add(red, 0.536_332_55 * green))
+            .cbrt();
+        let medium = 0.107_406_58f32
+            .mul_add(blue, 0.211_903_5f32.mul_add(red, 0.680_699_5 * green))
+            .cbrt();
+        let short = 0.629_978_7f32
+            .mul_add(blue, 0.088_302_46f32.mul_add(red, 0.281_718_85 * green))
+            .cbrt();
+
+        [
+            0.210_454_26f32.mul_add(long, 0.793_617_8f32.mul_add(medium, -0.004_072_047 * short)),
+            1.977_998_5f32.mul_add(long, (-2.428_592_2f32).mul_add(medium, 0.450_593_7 * short)),
+            0.025_904_037f32.mul_add(long, 0.782_771_77f32.mul_add(medium, -0.808_675_77 * short)),
+        ]
+    }
+
+    /// Create an opaque color from Oklab `[L, a, b]`, clamping to the sRGB gamut.
+    #[must_use]
+    pub fn from_oklab(lab: [f32; 3]) -> Self {
+        let [lightness, a, b] = lab;
+        let long = 0.215_803_76f32
+            .mul_add(b, 0.396_337_78f32.mul_add(a, lightness))
+            .powi(3);
+        let medium = (-0.063_854_17f32)
+            .mul_add(b, (-0.105_561_346f32).mul_add(a, lightness))
+            .powi(3);
+        let short = (-1.291_485_5f32)
+            .mul_add(b, (-0.089_484_18f32).mul_add(a, lightness))
+            .powi(3);
+
+        Self::rgb(
+            linear_to_srgb(
+                0.230_969_94f32.mul_add(short, 4.076_741_7f32.mul_add(long, -3.307_711_6 * medium)),
+            ),
+            linear_to_srgb(
+                (-0.341_319_4f32)
+                    .mul_add(short, (-1.268_438f32).mul_add(long, 2.609_757_4 * medium)),
+            ),
+            linear_to_srgb(1.707_614_7f32.mul_add(
+                short,
+                (-0.004_196_086_3f32).mul_add(long, -0.703_418_6 * medium),
+            )),
+        )
+    }
+
+    /// Convert to Oklch as `(L, chroma, hue)`, ignoring alpha.
+    ///
+    /// Hue is in degrees [0, 360), and 0 for grays where it is undefined.
+    #[must_use]
+    pub fn to_oklch(self) -> (f32, f32, f32) {
+        let [lightness, a, b] = self.to_oklab();
+        (lightness, a.hypot(b), wrap_hue(b.atan2(a).to_degrees()))
+    }
+
+    /// Create an opaque color from Oklch, clamping to the sRGB gamut.
+    #[must_use]
+    pub fn from_oklch(lightness: f32, chroma: f32, hue: f32) -> Self {
+        let (sin, cos) = hue.to_radians().sin_cos();
+        Self::from_oklab([lightness, chroma * cos, chroma * sin])
+    }
+
+    /// Interpolate between two colors in Oklab.
+    ///
+    /// Unlike [`Self::lerp`], the midpoint between complementary colors such
+    /// as blue and yellow stays colorful instead of passing through gray.
+    /// Alpha is interpolated linearly.
+    #[must_use]
+    pub fn lerp_oklab(self, other: Self, t: f32) -> Self {
+        Interpolation::Oklab.interpolate(self, other, t)
+    }
+
+    /// Perceptual distance between two colors (Euclidean distance in Oklab).
+    ///
+    /// Unlike RGB distance, equal steps roughly match equal perceived
+    /// differences. Black to white is 1.0.
+    #[must_use]
+    pub fn perceptual_distance(self, other: Self) -> f32 {
+        let [l1, a1, b1] = self.to_oklab();
+        let [l2, a2, b2] = other.to_oklab();
+        let (dl, da, db) = (l1 - l2, a1 - a2, b1 - b2);
+        dl.mul_add(dl, da.mul_add(da, db * db)).sqrt()
+    }
+
     /// Linearly interpolate between two colors.
     #[must_use]
     pub fn lerp(self, other: Self, t: f32) -> Self {
@@ -329,13 +682,42 @@ impl Rgba {
         }
     }
 
-    /// Convert to nearest 256-color palette index.
+    /// Convert to the perceptually nearest 256-color palette index.
     ///
-    /// Uses the 6x6x6 color cube (colors 16-231) or grayscale ramp (232-255)
-    /// depending on which provides the closest match.
+    /// Distance is measured in Oklab against the xterm color cube (16-231)
+    /// and grayscale ramp (232-255), so tinted dark backgrounds land on the
+    /// ramp instead of black or navy. Exact grays only search the ramp and
+    /// the cube's grays. Results are memoized per process; see
+    /// [`set_quantization_cache`]. [`Self::to_256_color_fast`] keeps the
+    /// older per-channel mapping.
     #[must_use]
     pub fn to_256_color(self) -> u8 {
         let (r, g, b) = self.to_rgb_u8();
+        let rgb = (u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b);
+        if !QUANTIZE_CACHE_ENABLED.load(Ordering::Relaxed) {
+            return nearest_xterm_index(r, g, b);
+        }
+
+        let slot = &QUANTIZE_CACHE[quantize_cache_slot(rgb)];
+        let entry = slot.load(Ordering::Relaxed);
+        // Indices are always >= 16, so a zero entry is an empty slot.
+        if entry != 0 && entry >> 8 == rgb {
+            return (entry & 0xFF) as u8;
+        }
+        let index = nearest_xterm_index(r, g, b);
+        slot.store((rgb << 8) | u32::from(index), Ordering::Relaxed);
+        index
+    }
+
+    /// Convert to a 256-color palette index by rounding each channel.
+    ///
+    /// Uses the 6x6x6 color cube (colors 16-231) or grayscale ramp (232-255)
+    /// depending on which provides the closest match. Cheaper than
+    /// [`Self::to_256_color`], but saturated and dark tinted colors can land
+    /// on visibly different hues.
+    #[must_use]
+    pub fn to_256_color_fast(self) -> u8 {
+        let (r, g, b) = self.to_rgb_u8();
 
         // Check if grayscale would be a better match
         let gray = ((r as u16 + g as u16 + b as u16) / 3) as u8;
@@ -411,56 +793,18 @@ impl Rgba {
         232 + idx.min(23)
     }
 
-    /// Convert to nearest 16-color (basic ANSI) palette index.
+    /// Convert to the perceptually nearest 16-color (basic ANSI) palette index.
     ///
     /// Returns a value 0-15 for the standard ANSI colors:
     /// 0-7: black, red, green, yellow, blue, magenta, cyan, white (normal)
     /// 8-15: bright versions of the above
+    ///
+    /// Distance is measured in Oklab, like [`Self::to_256_color`].
     #[must_use]
     pub fn to_16_color(self) -> u8 {
         let (r, g, b) = self.to_rgb_u8();
-        let r = i32::from(r);
-        let g = i32::from(g);
-        let b = i32::from(b);
-
-        // Standard ANSI palette (approximate values)
-        #[rustfmt::skip]
-        const PALETTE: [(i32, i32, i32); 16] = [
-            (0, 0, 0),       // 0 Black
-            (128, 0, 0),     // 1 Red
-            (0, 128, 0),     // 2 Green
-            (128, 128, 0),   // 3 Yellow
-            (0, 0, 128),     // 4 Blue
-            (128, 0, 128),   // 5 Magenta
-            (0, 128, 128),   // 6 Cyan
-            (192, 192, 192), // 7 White
-            (128, 128, 128), // 8 Bright Black
-            (255, 0, 0),     // 9 Bright Red
-            (0, 255, 0),     // 10 Bright Green
-            (255, 255, 0),   // 11 Bright Yellow
-            (0, 0, 255),     // 12 Bright Blue
-            (255, 0, 255),   // 13 Bright Magenta
-            (0, 255, 255),   // 14 Bright Cyan
-            (255, 255, 255), // 15 Bright White
-        ];
-
-        let mut best_idx = 0;
-        let mut min_dist = i32::MAX;
-
-        for (i, &(pr, pg, pb)) in PALETTE.iter().enumerate() {
-            let dr = r - pr;
-            let dg = g - pg;
-            let db = b - pb;
-            // Squared Euclidean distance
-            let dist = dr * dr + dg * dg + db * db;
-
-            if dist < min_dist {
-                min_dist = dist;
-                best_idx = i;
-            }
-        }
-
-        best_idx as u8
+        let lab = Self::from_rgb_u8(r, g, b).to_oklab();
+        nearest_oklab(lab, ansi16_oklab().iter().copied().zip(0..16))
     }
 
     /// Create an Rgba from a 256-color palette index.
@@ -512,6 +856,488 @@ impl Rgba {
     }
 }
 
+impl FromStr for Rgba {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Parse `rgb(r, g, b)` or `rgba(r, g, b, a)`; either accepts an optional alpha.
+fn parse_functional(input: &str) -> Option<Rgba> {
+    let (name, rest) = input.split_once('(')?;
+    if !(name.trim_end().eq_ignore_ascii_case("rgb")
+        || name.trim_end().eq_ignore_ascii_case("rgba"))
+    {
+        return None;
+    }
+    let mut args = rest.strip_suffix(')')?.split(',').map(str::trim);
+    let mut channel = || -> Option<f32> {
+        let arg = args.next()?;
+        match arg.strip_suffix('%') {
+            Some(percent) => Some(percent.trim_end().parse::<f32>().ok()? / 100.0),
+            None => Some(arg.parse::<f32>().ok()? / 255.0),
+        }
+    };
+    let (r, g, b) = (channel()?, channel()?, channel()?);
+    let a = match args.next() {
+        None => 1.0,
+        Some(arg) => match arg.strip_suffix('%') {
+            Some(percent) => percent.trim_end().parse::<f32>().ok()? / 100.0,
+            None => arg.parse::<f32>().ok()?,
+        },
+    };
+    if args.next().is_some() || ![r, g, b, a].iter().all(|c| c.is_finite()) {
+        return None;
+    }
+    Some(Rgba::new(
+        r.clamp(0.0, 1.0),
+        g.clamp(0.0, 1.0),
+        b.clamp(0.0, 1.0),
+        a.clamp(0.0, 1.0),
+    ))
+}
+
+/// WCAG 2.1 contrast ratio between two colors, from 1 (identical) to 21
+/// (black on white). The order of the arguments does not matter.
+#[must_use]
+pub fn contrast_ratio(a: Rgba, b: Rgba) -> f32 {
+    let (a, b) = (a.relative_luminance(), b.relative_luminance());
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The candidate with the highest contrast against `bg`; the first wins ties.
+///
+/// With no candidates, picks black or white.
+#[must_use]
+pub fn best_foreground(bg: Rgba, candidates: &[Rgba]) -> Rgba {
+    let candidates = if candidates.is_empty() {
+        &[Rgba::BLACK, Rgba::WHITE]
+    } else {
+        candidates
+    };
+    let mut best = candidates[0];
+    let mut best_ratio = contrast_ratio(best, bg);
+    for &candidate in &candidates[1..] {
+        let ratio = contrast_ratio(candidate, bg);
+        if ratio > best_ratio {
+            best = candidate;
+            best_ratio = ratio;
+        }
+    }
+    best
+}
+
+/// Adjust `fg` until its contrast against `bg` is at least `min_ratio`.
+///
+/// `fg` is returned unchanged if it already qualifies. Otherwise its HSL
+/// lightness is moved toward whichever of white or black contrasts more
+/// with `bg`, keeping hue and saturation, and stopping as close to the
+/// original as the ratio allows. If even black or white falls short, the
+/// better of the two is returned. Alpha is preserved; `min_ratio` is clamped
+/// to the achievable range [1, 21].
+#[must_use]
+pub fn ensure_contrast(fg: Rgba, bg: Rgba, min_ratio: f32) -> Rgba {
+    let min_ratio = if min_ratio.is_nan() {
+        1.0
+    } else {
+        min_ratio.clamp(1.0, 21.0)
+    };
+    if contrast_ratio(fg, bg) >= min_ratio {
+        return fg;
+    }
+
+    let (hue, saturation, lightness) = fg.to_hsl();
+    let toward_white = contrast_ratio(Rgba::WHITE, bg) >= contrast_ratio(Rgba::BLACK, bg);
+    for end in [toward_white, !toward_white].map(|white| if white { 1.0 } else { 0.0 }) {
+        if contrast_ratio(Rgba::from_hsl(hue, saturation, end), bg) < min_ratio {
+            continue;
+        }
+        // Bisect between the failing original and the passing extreme
+        let (mut failing, mut passing) = (lightness, end);
+        for _ in 0..24 {
+            let mid = f32::midpoint(failing, passing);
+            if contrast_ratio(Rgba::from_hsl(hue, saturation, mid), bg) >= min_ratio {
+                passing = mid;
+            } else {
+                failing = mid;
+            }
+        }
+        return Rgba::from_hsl(hue, saturation, passing).with_alpha(fg.a);
+    }
+    best_foreground(bg, &[Rgba::WHITE, Rgba::BLACK]).with_alpha(fg.a)
+}
+
+/// How a source color combines with the color beneath it.
+///
+/// Modes follow the W3C compositing spec, operating per channel on sRGB
+/// values. See [`Rgba::blend`] for how alpha is handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Porter-Duff "over": the source covers the backdrop.
+    #[default]
+    Over,
+    /// Multiply channels; darkens, and white is neutral. Dims a UI behind a modal.
+    Multiply,
+    /// Inverse of multiply; lightens, and black is neutral.
+    Screen,
+    /// Multiply dark backdrop channels and screen light ones, boosting contrast.
+    Overlay,
+    /// Add channels, saturating at 1. For glows.
+    Additive,
+    /// Keep the lighter of each channel.
+    Lighten,
+    /// Keep the darker of each channel.
+    Darken,
+}
+
+impl BlendMode {
+    /// Mix one channel of the backdrop with one of the source.
+    fn mix(self, backdrop: f32, src: f32) -> f32 {
+        match self {
+            Self::Over => src,
+            Self::Multiply => backdrop * src,
+            Self::Screen => backdrop + src - backdrop * src,
+            Self::Overlay => {
+                if backdrop <= 0.5 {
+                    2.0 * backdrop * src
+                } else {
+                    1.0 - 2.0 * (1.0 - backdrop) * (1.0 - src)
+                }
+            }
+            Self::Additive => (backdrop + src).min(1.0),
+            Self::Lighten => backdrop.max(src),
+            Self::Darken => backdrop.min(src),
+        }
+    }
+}
+
+/// Decode an sRGB channel to linear light.
+fn srgb_to_linear(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear-light channel as sRGB.
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055f32.mul_add(c.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+/// Linear interpolation between two scalars.
+fn mix(from: f32, to: f32, t: f32) -> f32 {
+    (to - from).mul_add(t, from)
+}
+
+/// Slots in the [`Rgba::to_256_color`] memoization cache.
+const QUANTIZE_CACHE_SLOTS: usize = 4096;
+
+/// Direct-mapped cache of `to_256_color` results.
+///
+/// Each slot packs the 24-bit RGB key above the palette index. Collisions
+/// simply overwrite, so the cache never grows.
+static QUANTIZE_CACHE: [AtomicU32; QUANTIZE_CACHE_SLOTS] =
+    [const { AtomicU32::new(0) }; QUANTIZE_CACHE_SLOTS];
+
+static QUANTIZE_CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable memoization of [`Rgba::to_256_color`] (on by default).
+///
+/// The cache is a fixed 16 KiB table shared by the whole process. Disabling
+/// it also clears it.
+pub fn set_quantization_cache(enabled: bool) {
+    QUANTIZE_CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        for slot in &QUANTIZE_CACHE {
+            slot.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+fn quantize_cache_slot(rgb: u32) -> usize {
+    (rgb.wrapping_mul(0x9E37_79B1) >> 20) as usize % QUANTIZE_CACHE_SLOTS
+}
+
+/// How much chroma differences count against lightness differences when
+/// matching palette colors.
+///
+/// Plain Oklab distance happily trades hue for lightness, which sends
+/// saturated colors to grays on small palettes.
+const PALETTE_CHROMA_WEIGHT: f32 = 2.0;
+
+/// Oklab coordinates of xterm colors 16-255, indexed by `index - 16`.
+fn xterm_oklab() -> &'static [[f32; 3]; 240] {
+    static TABLE: LazyLock<[[f32; 3]; 240]> =
+        LazyLock::new(|| std::array::from_fn(|i| Rgba::from_256_color(i as u8 + 16).to_oklab()));
+    &TABLE
+}
+
+/// Oklab coordinates of the 16 basic ANSI colors.
+fn ansi16_oklab() -> &'static [[f32; 3]; 16] {
+    static TABLE: LazyLock<[[f32; 3]; 16]> =
+        LazyLock::new(|| std::array::from_fn(|i| Rgba::from_16_color(i as u8).to_oklab()));
+    &TABLE
+}
+
+/// The xterm index (16-255) perceptually nearest to an sRGB color.
+fn nearest_xterm_index(r: u8, g: u8, b: u8) -> u8 {
+    let lab = Rgba::from_rgb_u8(r, g, b).to_oklab();
+    let table = xterm_oklab();
+    let candidates = table.iter().copied().zip(16..=255);
+    if r == g && g == b {
+        // Grays only match the ramp or the cube's diagonal.
+        let is_gray = |&(_, index): &([f32; 3], u8)| index >= 232 || (index - 16) % 43 == 0;
+        nearest_oklab(lab, candidates.filter(is_gray))
+    } else {
+        nearest_oklab(lab, candidates)
+    }
+}
+
+/// The index of the candidate nearest to `lab`, preferring the first on ties.
+fn nearest_oklab(lab: [f32; 3], candidates: impl Iterator<Item = ([f32; 3], u8)>) -> u8 {
+    let weight = PALETTE_CHROMA_WEIGHT * PALETTE_CHROMA_WEIGHT;
+    let mut best = (f32::INFINITY, 0);
+    for ([l, a, b], index) in candidates {
+        let (dl, da, db) = (lab[0] - l, lab[1] - a, lab[2] - b);
+        let dist = da.mul_add(da, db * db).mul_add(weight, dl * dl);
+        if dist < best.0 {
+            best = (dist, index);
+        }
+    }
+    best.1
+}
+
+/// Which way round the color wheel Oklch interpolation goes.
+///
+/// Matches the CSS Color 4 hue interpolation methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HueDirection {
+    /// Take the shorter arc between the two hues.
+    #[default]
+    Shorter,
+    /// Take the longer arc between the two hues.
+    Longer,
+    /// Go counterclockwise (increasing hue), wrapping at 360.
+    Increasing,
+    /// Go clockwise (decreasing hue), wrapping at 0.
+    Decreasing,
+}
+
+impl HueDirection {
+    /// Interpolate between hues in degrees.
+    fn interpolate(self, from: f32, to: f32, t: f32) -> f32 {
+        let diff = to - from;
+        let diff = match self {
+            Self::Shorter if diff > 180.0 => diff - 360.0,
+            Self::Shorter if diff < -180.0 => diff + 360.0,
+            Self::Longer if diff > 0.0 && diff < 180.0 => diff - 360.0,
+            Self::Longer if diff > -180.0 && diff <= 0.0 => diff + 360.0,
+            Self::Increasing if diff < 0.0 => diff + 360.0,
+            Self::Decreasing if diff > 0.0 => diff - 360.0,
+            _ => diff,
+        };
+        wrap_hue(diff.mul_add(t, from))
+    }
+}
+
+/// Color space a [`Gradient`] interpolates in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight sRGB channel interpolation, as [`Rgba::lerp`].
+    Srgb,
+    /// Linear-light RGB; brighter midpoints than sRGB.
+    LinearRgb,
+    /// Oklab; perceptually even steps without graying out.
+    #[default]
+    Oklab,
+    /// Oklch; interpolates hue around the color wheel.
+    Oklch(HueDirection),
+}
+
+impl Interpolation {
+    /// Interpolate from `from` to `to` in this space. Alpha is interpolated
+    /// linearly, and `t` is clamped to [0, 1].
+    #[must_use]
+    pub fn interpolate(self, from: Rgba, to: Rgba, t: f32) -> Rgba {
+        if t.is_nan() || t <= 0.0 {
+            return from;
+        }
+        if t >= 1.0 {
+            return to;
+        }
+        let color = match self {
+            Self::Srgb => return from.lerp(to, t),
+            Self::LinearRgb => {
+                let channel =
+                    |a: f32, b: f32| linear_to_srgb(mix(srgb_to_linear(a), srgb_to_linear(b), t));
+                Rgba::rgb(
+                    channel(from.r, to.r),
+                    channel(from.g, to.g),
+                    channel(from.b, to.b),
+                )
+            }
+            Self::Oklab => {
+                let [l1, a1, b1] = from.to_oklab();
+                let [l2, a2, b2] = to.to_oklab();
+                Rgba::from_oklab([mix(l1, l2, t), mix(a1, a2, t), mix(b1, b2, t)])
+            }
+            Self::Oklch(direction) => {
+                let (l1, c1, mut h1) = from.to_oklch();
+                let (l2, c2, mut h2) = to.to_oklch();
+                // A gray has no hue; borrow the other end's so only chroma changes
+                if c1 < OKLCH_ACHROMATIC {
+                    h1 = h2;
+                }
+                if c2 < OKLCH_ACHROMATIC {
+                    h2 = h1;
+                }
+                Rgba::from_oklch(
+                    mix(l1, l2, t),
+                    mix(c1, c2, t),
+                    direction.interpolate(h1, h2, t),
+                )
+            }
+        };
+        color.with_alpha(mix(from.a, to.a, t))
+    }
+}
+
+/// Oklch chroma below which a color counts as gray.
+const OKLCH_ACHROMATIC: f32 = 1e-4;
+
+/// A color ramp through positioned stops.
+///
+/// # Example
+///
+/// ```
+/// use opentui_rust::Rgba;
+/// use opentui_rust::color::{Gradient, Interpolation};
+///
+/// let ramp = Gradient::new(Rgba::BLUE, Rgba::rgb(1.0, 1.0, 0.0))
+///     .with_stop(0.5, Rgba::WHITE)
+///     .with_interpolation(Interpolation::Oklab);
+/// assert_eq!(ramp.sample(0.5), Rgba::WHITE);
+/// assert_eq!(ramp.steps(8).len(), 8);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    // Sorted by position, each in [0, 1]
+    stops: Vec<(f32, Rgba)>,
+    interpolation: Interpolation,
+}
+
+impl Gradient {
+    /// Create a gradient from `start` at 0 to `end` at 1, interpolated in Oklab.
+    #[must_use]
+    pub fn new(start: Rgba, end: Rgba) -> Self {
+        Self::from_stops([(0.0, start), (1.0, end)])
+    }
+
+    /// Create a gradient from `(position, color)` stops.
+    ///
+    /// Positions are clamped to [0, 1] and sorted; stops at the same position
+    /// make a hard edge. Before the first stop and after the last, the
+    /// gradient is the nearest stop's color.
+    #[must_use]
+    pub fn from_stops(stops: impl IntoIterator<Item = (f32, Rgba)>) -> Self {
+        let mut gradient = Self {
+            stops: Vec::new(),
+            interpolation: Interpolation::default(),
+        };
+        for (position, color) in stops {
+            gradient.add_stop(position, color);
+        }
+        gradient
+    }
+
+    /// Add a stop at `position`, after any existing stops there.
+    #[must_use]
+    pub fn with_stop(mut self, position: f32, color: Rgba) -> Self {
+        self.add_stop(position, color);
+        self
+    }
+
+    /// Set the color space to interpolate in.
+    #[must_use]
+    pub const fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    fn add_stop(&mut self, position: f32, color: Rgba) {
+        let position = if position.is_nan() {
+            0.0
+        } else {
+            position.clamp(0.0, 1.0)
+        };
+        let index = self.stops.partition_point(|&(p, _)| p <= position);
+        self.stops.insert(index, (position, color));
+    }
+
+    /// The stops as `(position, color)`, sorted by position.
+    #[must_use]
+    pub fn stops(&self) -> &[(f32, Rgba)] {
+        &self.stops
+    }
+
+    /// The color space the gradient interpolates in.
+    #[must_use]
+    pub const fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// The color at `t`, clamped to [0, 1].
+    ///
+    /// Stop colors are returned exactly at their positions. A gradient with
+    /// no stops is transparent.
+    #[must_use]
+    pub fn sample(&self, t: f32) -> Rgba {
+        let Some(&(first_position, first)) = self.stops.first() else {
+            return Rgba::TRANSPARENT;
+        };
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        if t <= first_position {
+            return first;
+        }
+        for pair in self.stops.windows(2) {
+            let ((from_position, from), (to_position, to)) = (pair[0], pair[1]);
+            if t < to_position {
+                let local = (t - from_position) / (to_position - from_position);
+                return self.interpolation.interpolate(from, to, local);
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+
+    /// `n` colors evenly spaced from 0 to 1 inclusive, e.g. one per column.
+    #[must_use]
+    pub fn steps(&self, n: usize) -> Vec<Rgba> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.sample(0.0)],
+            _ => (0..n)
+                .map(|i| self.sample(i as f32 / (n - 1) as f32))
+                .collect(),
+        }
+    }
+}
+
+/// Wrap a hue in degrees into [0, 360).
+fn wrap_hue(hue: f32) -> f32 {
+    let hue = hue.rem_euclid(360.0);
+    // rem_euclid can round up to exactly 360 for tiny negative inputs
+    if hue >= 360.0 { 0.0 } else { hue }
+}
+
 impl fmt::Display for Rgba {
     #[allow(clippy::many_single_char_names)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -569,6 +1395,12 @@ mod tests {
         assert_eq!(Rgba::BLACK.to_rgb_u8(), (0, 0, 0));
     }
 
+    #[test]
+    fn test_to_rgb_u16_clamps() {
+        assert_eq!(Rgba::rgb(1.0, 0.5, 0.0).to_rgb_u16(), (0xffff, 0x8000, 0));
+        assert_eq!(Rgba::rgb(2.0, -1.0, 1.0).to_rgb_u16(), (0xffff, 0, 0xffff));
+    }
+
     #[test]
     fn test_display() {
         assert_eq!(format!("{}", Rgba::RED), "#FF0000");
@@ -641,6 +1473,409 @@ mod tests {
         assert!((gray.b - 0.5).abs() < 0.01);
     }
 
+    fn assert_triple(actual: (f32, f32, f32), expected: (f32, f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3
+                && (actual.1 - expected.1).abs() < 1e-4
+                && (actual.2 - expected.2).abs() < 1e-4,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_hsl_hsv_reference_triples() {
+        let cases = [
+            // color, hsl, hsv
+            (Rgba::RED, (0.0, 1.0, 0.5), (0.0, 1.0, 1.0)),
+            (Rgba::GREEN, (120.0, 1.0, 0.5), (120.0, 1.0, 1.0)),
+            (Rgba::BLUE, (240.0, 1.0, 0.5), (240.0, 1.0, 1.0)),
+            (Rgba::rgb(1.0, 1.0, 0.0), (60.0, 1.0, 0.5), (60.0, 1.0, 1.0)),
+            (
+                Rgba::rgb(0.5, 0.25, 0.75),
+                (270.0, 0.5, 0.5),
+                (270.0, 2.0 / 3.0, 0.75),
+            ),
+            // Grays: hue undefined, reported as 0
+            (Rgba::BLACK, (0.0, 0.0, 0.0), (0.0, 0.0, 0.0)),
+            (Rgba::WHITE, (0.0, 0.0, 1.0), (0.0, 0.0, 1.0)),
+            (Rgba::rgb(0.5, 0.5, 0.5), (0.0, 0.0, 0.5), (0.0, 0.0, 0.5)),
+        ];
+        for (color, hsl, hsv) in cases {
+            assert_triple(color.to_hsl(), hsl);
+            assert_triple(color.to_hsv(), hsv);
+            let from_hsl = Rgba::from_hsl(hsl.0, hsl.1, hsl.2);
+            assert!(color.perceptual_distance(from_hsl) < 1e-3, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_hsl_wraps_hue_and_clamps() {
+        assert_eq!(Rgba::from_hsl(-120.0, 1.0, 0.5).to_rgb_u8(), (0, 0, 255));
+        assert_eq!(Rgba::from_hsl(480.0, 1.0, 0.5).to_rgb_u8(), (0, 255, 0));
+        assert_eq!(Rgba::from_hsl(0.0, 2.0, 0.5).to_rgb_u8(), (255, 0, 0));
+        assert_eq!(Rgba::from_hsl(0.0, 1.0, 1.5).to_rgb_u8(), (255, 255, 255));
+        assert_eq!(Rgba::from_hsl(0.0, -1.0, 0.5).to_rgb_u8(), (128, 128, 128));
+    }
+
+    #[test]
+    fn test_from_hex_all_lengths() {
+        let expected = Rgba::from_rgba_u8(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(Rgba::from_hex("#1234"), Some(expected));
+        assert_eq!(Rgba::from_hex("11223344"), Some(expected));
+        assert_eq!(Rgba::from_hex("#AbCdEf"), Rgba::from_hex("abcdef"));
+        assert_eq!(Rgba::from_hex("#123"), Rgba::from_hex("#112233"));
+        assert_eq!(Rgba::from_hex("#123f"), Rgba::from_hex("#112233"));
+        // from_str_radix alone would accept a sign
+        assert_eq!(Rgba::from_hex("+f+f+f"), None);
+        assert_eq!(Rgba::from_hex("é1"), None);
+    }
+
+    #[test]
+    fn test_to_hex_round_trips() {
+        assert_eq!(Rgba::from_rgb_u8(0xff, 0x88, 0x00).to_hex(), "#ff8800");
+        let translucent = Rgba::from_rgba_u8(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(translucent.to_hex(), "#11223344");
+        assert_eq!(Rgba::from_hex(&translucent.to_hex()), Some(translucent));
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Rgba::from_name("dodgerblue"), Rgba::from_hex("#1e90ff"));
+        assert_eq!(Rgba::from_name("REBECCAPURPLE"), Rgba::from_hex("#663399"));
+        assert_eq!(Rgba::from_name("lightgrey"), Rgba::from_name("lightgray"));
+        assert_eq!(Rgba::from_name("not-a-color"), None);
+    }
+
+    #[test]
+    fn test_parse_accepted_formats() {
+        let cases = [
+            ("#f00", Rgba::RED),
+            ("f00f", Rgba::RED),
+            ("#FF0000", Rgba::RED),
+            ("ff000080", Rgba::from_rgba_u8(255, 0, 0, 0x80)),
+            ("rgb(255, 0, 0)", Rgba::RED),
+            ("RGB(255,0,0)", Rgba::RED),
+            ("rgb(100%, 0%, 0%)", Rgba::RED),
+            ("rgba(255, 0, 0, 0.5)", Rgba::RED.with_alpha(0.5)),
+            ("rgba(255, 0, 0, 50%)", Rgba::RED.with_alpha(0.5)),
+            ("rgb(255, 0, 0, 0.25)", Rgba::RED.with_alpha(0.25)),
+            ("rgba(300, -5, 0)", Rgba::RED),
+            ("red", Rgba::RED),
+            ("  Red\t", Rgba::RED),
+            (" #f00 ", Rgba::RED),
+            ("\nrgb( 255 , 0 , 0 )\n", Rgba::RED),
+            ("transparent", Rgba::TRANSPARENT),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Rgba::parse(input).ok(), Some(expected), "{input:?}");
+        }
+        assert_eq!("grey".parse::<Rgba>().ok(), Rgba::from_hex("#808080"));
+    }
+
+    #[test]
+    fn test_parse_errors_carry_input() {
+        for input in [
+            "",
+            "#ggg",
+            "#12345",
+            "#",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, b, c)",
+            "rgb(1, 2, 3",
+            "hsl(0, 0%, 0%)",
+            "seafoam",
+        ] {
+            match Rgba::parse(input) {
+                Err(Error::InvalidColor(reported)) => assert_eq!(reported, input),
+                other => panic!("{input:?} parsed as {other:?}"),
+            }
+        }
+        let err = Rgba::parse("#ggg").unwrap_err();
+        assert_eq!(err.to_string(), "invalid color format: #ggg");
+    }
+
+    #[test]
+    fn test_blend_modes_hand_computed() {
+        let src = Rgba::rgb(0.5, 0.25, 1.0);
+        let dst = Rgba::rgb(0.5, 0.75, 0.0);
+        let cases = [
+            (BlendMode::Over, Rgba::rgb(0.5, 0.25, 1.0)),
+            (BlendMode::Multiply, Rgba::rgb(0.25, 0.1875, 0.0)),
+            (BlendMode::Screen, Rgba::rgb(0.75, 0.8125, 1.0)),
+            (BlendMode::Overlay, Rgba::rgb(0.5, 0.625, 0.0)),
+            (BlendMode::Additive, Rgba::rgb(1.0, 1.0, 1.0)),
+            (BlendMode::Lighten, Rgba::rgb(0.5, 0.75, 1.0)),
+            (BlendMode::Darken, Rgba::rgb(0.5, 0.25, 0.0)),
+        ];
+        for (mode, expected) in cases {
+            let blended = src.blend(dst, mode);
+            assert!(
+                blended.perceptual_distance(expected) < 1e-4,
+                "{mode:?}: {blended:?}"
+            );
+            assert!((blended.a - 1.0).abs() < 1e-6, "{mode:?}");
+        }
+
+        // Dimming a white backdrop with 50% gray
+        let dimmed = Rgba::rgb(0.5, 0.5, 0.5).blend(Rgba::WHITE, BlendMode::Multiply);
+        assert_eq!(dimmed.to_rgb_u8(), (128, 128, 128));
+    }
+
+    #[test]
+    fn test_blend_mode_alpha_handling() {
+        let dst = Rgba::rgb(0.5, 0.75, 0.0);
+        // Source alpha scales the effect: half of multiply's (0.25, 0.1875, 0)
+        let half = Rgba::rgb(0.5, 0.25, 1.0)
+            .with_alpha(0.5)
+            .blend(dst, BlendMode::Multiply);
+        assert!((half.r - 0.375).abs() < 1e-6);
+        assert!((half.g - 0.468_75).abs() < 1e-6);
+        assert!(half.b.abs() < 1e-6);
+        assert!((half.a - 1.0).abs() < 1e-6);
+
+        for mode in [
+            BlendMode::Over,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Overlay,
+            BlendMode::Additive,
+            BlendMode::Lighten,
+            BlendMode::Darken,
+        ] {
+            // A transparent source is a no-op
+            assert_eq!(Rgba::TRANSPARENT.blend(dst, mode), dst, "{mode:?}");
+            // Over a transparent backdrop, the source shows as-is
+            let src = Rgba::RED.with_alpha(0.5);
+            assert_eq!(src.blend(Rgba::TRANSPARENT, mode), src, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn test_contrast_ratio_known_values() {
+        assert!((contrast_ratio(Rgba::BLACK, Rgba::WHITE) - 21.0).abs() < 1e-3);
+        assert!((contrast_ratio(Rgba::WHITE, Rgba::BLACK) - 21.0).abs() < 1e-3);
+        assert!((contrast_ratio(Rgba::RED, Rgba::RED) - 1.0).abs() < 1e-6);
+        // Reference values from the WCAG contrast checker
+        let gray = Rgba::from_hex("#767676").unwrap();
+        assert!((contrast_ratio(gray, Rgba::WHITE) - 4.54).abs() < 0.01);
+        assert!((contrast_ratio(Rgba::BLUE, Rgba::WHITE) - 8.59).abs() < 0.01);
+        assert!((Rgba::WHITE.relative_luminance() - 1.0).abs() < 1e-6);
+        assert!((Rgba::GREEN.relative_luminance() - 0.7152).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_best_foreground() {
+        let navy = Rgba::from_name("navy").unwrap();
+        let candidates = [Rgba::BLUE, Rgba::WHITE, Rgba::BLACK];
+        assert_eq!(best_foreground(navy, &candidates), Rgba::WHITE);
+        assert_eq!(
+            best_foreground(Rgba::from_name("khaki").unwrap(), &candidates),
+            Rgba::BLACK
+        );
+        assert_eq!(best_foreground(Rgba::WHITE, &[]), Rgba::BLACK);
+        // Ties go to the first candidate
+        assert_eq!(
+            best_foreground(Rgba::BLACK, &[Rgba::WHITE, Rgba::WHITE.with_alpha(0.5)]),
+            Rgba::WHITE
+        );
+    }
+
+    #[test]
+    fn test_ensure_contrast_meets_ratio() {
+        let backgrounds = [
+            Rgba::BLACK,
+            Rgba::WHITE,
+            Rgba::from_hex("#808080").unwrap(),
+            Rgba::from_hex("#1e90ff").unwrap(),
+            Rgba::from_hex("#ffd700").unwrap(),
+            Rgba::from_hex("#282a36").unwrap(),
+        ];
+        let foregrounds = [
+            Rgba::from_hex("#6272a4").unwrap(),
+            Rgba::from_hex("#777777").unwrap().with_alpha(0.5),
+            Rgba::RED,
+            Rgba::from_hex("#ffd700").unwrap(),
+        ];
+        for bg in backgrounds {
+            for fg in foregrounds {
+                for min_ratio in [3.0, 4.5, 7.0] {
+                    let adjusted = ensure_contrast(fg, bg, min_ratio);
+                    assert!(
+                        contrast_ratio(adjusted, bg) >= min_ratio
+                            || adjusted.with_alpha(1.0) == best_foreground(bg, &[]),
+                        "{fg} on {bg} at {min_ratio}: {adjusted}"
+                    );
+                    assert!((adjusted.a - fg.a).abs() < f32::EPSILON);
+                    assert_eq!(
+                        adjusted,
+                        ensure_contrast(fg, bg, min_ratio),
+                        "deterministic"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ensure_contrast_keeps_readable_and_hue() {
+        let fg = Rgba::from_hex("#f8f8f2").unwrap();
+        let bg = Rgba::from_hex("#282a36").unwrap();
+        assert_eq!(ensure_contrast(fg, bg, 4.5), fg);
+
+        // Dark blue on black is lightened, staying blue
+        let dark_blue = Rgba::from_hex("#000080").unwrap();
+        let adjusted = ensure_contrast(dark_blue, Rgba::BLACK, 4.5);
+        assert!(contrast_ratio(adjusted, Rgba::BLACK) >= 4.5);
+        assert!(
+            contrast_ratio(adjusted, Rgba::BLACK) < 4.6,
+            "minimal change"
+        );
+        assert!((adjusted.to_hsl().0 - 240.0).abs() < 0.5);
+
+        // Mid gray can't reach 21:1; the extreme is returned
+        let gray = Rgba::from_hex("#808080").unwrap();
+        assert_eq!(ensure_contrast(gray, gray, 50.0), Rgba::BLACK);
+    }
+
+    #[test]
+    fn test_oklab_round_trip() {
+        for color in [
+            Rgba::RED,
+            Rgba::GREEN,
+            Rgba::BLUE,
+            Rgba::WHITE,
+            Rgba::BLACK,
+            Rgba::from_rgb_u8(100, 149, 237),
+        ] {
+            assert_eq!(
+                Rgba::from_oklab(color.to_oklab()).to_rgb_u8(),
+                color.to_rgb_u8()
+            );
+            let (l, c, h) = color.to_oklch();
+            assert_eq!(Rgba::from_oklch(l, c, h).to_rgb_u8(), color.to_rgb_u8());
+        }
+    }
+
+    #[test]
+    fn test_lerp_oklab_stays_colorful() {
+        let blue = Rgba::BLUE;
+        let yellow = Rgba::rgb(1.0, 1.0, 0.0);
+        let (_, srgb_chroma, _) = blue.lerp(yellow, 0.5).to_oklch();
+        let (_, oklab_chroma, _) = blue.lerp_oklab(yellow, 0.5).to_oklch();
+        assert!(srgb_chroma < 0.01, "{srgb_chroma}");
+        assert!(oklab_chroma > 0.05, "{oklab_chroma}");
+        assert!((blue.with_alpha(0.0).lerp_oklab(yellow, 0.5).a - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gradient_endpoints_are_exact() {
+        let start = Rgba::from_rgb_u8(12, 34, 56).with_alpha(0.5);
+        let end = Rgba::from_rgb_u8(250, 128, 3);
+        for interpolation in [
+            Interpolation::Srgb,
+            Interpolation::LinearRgb,
+            Interpolation::Oklab,
+            Interpolation::Oklch(HueDirection::Longer),
+        ] {
+            let gradient = Gradient::new(start, end)
+                .with_stop(0.25, Rgba::WHITE)
+                .with_interpolation(interpolation);
+            assert_eq!(gradient.sample(0.0), start);
+            assert_eq!(gradient.sample(0.25), Rgba::WHITE);
+            assert_eq!(gradient.sample(1.0), end);
+            assert_eq!(gradient.sample(-1.0), start);
+            assert_eq!(gradient.sample(2.0), end);
+            let steps = gradient.steps(5);
+            assert_eq!(steps.len(), 5);
+            assert_eq!(steps[0], start);
+            assert_eq!(steps[4], end);
+        }
+    }
+
+    #[test]
+    fn test_gradient_stops_are_sorted_and_clamped() {
+        let gradient = Gradient::from_stops([
+            (1.5, Rgba::WHITE),
+            (0.5, Rgba::RED),
+            (-1.0, Rgba::BLACK),
+            (0.5, Rgba::BLUE),
+        ]);
+        let positions: Vec<f32> = gradient.stops().iter().map(|&(p, _)| p).collect();
+        assert_eq!(positions, [0.0, 0.5, 0.5, 1.0]);
+        // Stops sharing a position make a hard edge
+        assert_eq!(gradient.sample(0.499).to_rgb_u8().2, 0);
+        assert_eq!(gradient.sample(0.5), Rgba::BLUE);
+
+        assert_eq!(Gradient::from_stops([]).sample(0.5), Rgba::TRANSPARENT);
+        assert!(Gradient::new(Rgba::RED, Rgba::BLUE).steps(0).is_empty());
+        assert_eq!(Gradient::new(Rgba::RED, Rgba::BLUE).steps(1), [Rgba::RED]);
+    }
+
+    #[test]
+    fn test_gradient_oklab_lightness_is_monotonic() {
+        let ramp = Gradient::new(Rgba::BLACK, Rgba::WHITE).steps(64);
+        let lightness: Vec<f32> = ramp.iter().map(|c| c.to_oklab()[0]).collect();
+        assert!(lightness.windows(2).all(|pair| pair[0] <= pair[1]));
+        // Evenly spaced: the midpoint is at half lightness, not half sRGB
+        let mid = Gradient::new(Rgba::BLACK, Rgba::WHITE).sample(0.5);
+        assert!((mid.to_oklab()[0] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gradient_oklch_hue_direction() {
+        let hue = |direction| {
+            Gradient::new(
+                Rgba::from_oklch(0.7, 0.1, 350.0),
+                Rgba::from_oklch(0.7, 0.1, 10.0),
+            )
+            .with_interpolation(Interpolation::Oklch(direction))
+            .sample(0.5)
+            .to_oklch()
+            .2
+        };
+        let near = |actual: f32, expected: f32| {
+            let error = (actual - expected).rem_euclid(360.0);
+            error.min(360.0 - error) < 1.0
+        };
+        assert!(near(hue(HueDirection::default()), 0.0));
+        assert!(near(hue(HueDirection::Longer), 180.0));
+        assert!(near(hue(HueDirection::Increasing), 0.0));
+        assert!(near(hue(HueDirection::Decreasing), 180.0));
+    }
+
+    #[test]
+    fn test_gradient_oklch_gray_end_keeps_hue() {
+        let (_, _, red_hue) = Rgba::RED.to_oklch();
+        let mid = Gradient::new(Rgba::RED, Rgba::WHITE)
+            .with_interpolation(Interpolation::Oklch(HueDirection::Shorter))
+            .sample(0.5);
+        let (_, chroma, hue) = mid.to_oklch();
+        assert!(chroma > 0.05);
+        assert!((hue - red_hue).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_hsl_adjustments_preserve_alpha() {
+        let base = Rgba::from_hsl(200.0, 0.5, 0.4).with_alpha(0.25);
+
+        let hover = base.lighten(0.1);
+        assert_triple(hover.to_hsl(), (200.0, 0.5, 0.5));
+        assert!((hover.a - 0.25).abs() < f32::EPSILON);
+        assert_triple(base.darken(0.1).to_hsl(), (200.0, 0.5, 0.3));
+        assert_triple(base.with_lightness(0.8).to_hsl(), (200.0, 0.5, 0.8));
+        assert_triple(base.saturate(0.25).to_hsl(), (200.0, 0.75, 0.4));
+        assert_triple(base.saturate(-1.0).to_hsl(), (0.0, 0.0, 0.4));
+        assert_triple(base.rotate_hue(200.0).to_hsl(), (40.0, 0.5, 0.4));
+        assert_triple(base.rotate_hue(-250.0).to_hsl(), (310.0, 0.5, 0.4));
+
+        assert_eq!(base.lighten(1.0).to_rgb_u8(), (255, 255, 255));
+        assert_eq!(base.darken(1.0).to_rgb_u8(), (0, 0, 0));
+        for adjusted in [base.darken(1.0), base.saturate(0.3), base.rotate_hue(90.0)] {
+            assert!((adjusted.a - 0.25).abs() < f32::EPSILON);
+        }
+    }
+
     #[test]
     fn test_to_256_color() {
         // Pure red should map to bright red in cube
@@ -668,6 +1903,86 @@ mod tests {
         assert_eq!(black_idx, 0);
     }
 
+    #[test]
+    fn test_perceptual_quantization_problem_colors() {
+        // [r, g, b, to_256_color_fast, to_256_color, to_16_color]
+        #[rustfmt::skip]
+        let table: [[u8; 6]; 7] = [
+            [30, 30, 46, 16, 234, 0],      // Tinted dark background: gray, not black
+            [46, 52, 64, 23, 236, 8],      // Nord background: gray, not dark teal
+            [230, 120, 20, 172, 208, 9],   // Orange: bright red, not 16-color brown
+            [30, 144, 255, 33, 33, 12],    // Dodger blue: bright blue, not cyan
+            [255, 105, 180, 205, 205, 13], // Hot pink: bright magenta, not gray
+            [127, 255, 212, 122, 122, 14], // Aquamarine: bright cyan, not gray
+            [128, 0, 255, 93, 93, 12],     // Violet: bright blue, not dark magenta
+        ];
+        for [r, g, b, fast, idx256, idx16] in table {
+            let color = Rgba::from_rgb_u8(r, g, b);
+            assert_eq!(color.to_256_color_fast(), fast, "{r},{g},{b}");
+            assert_eq!(color.to_256_color(), idx256, "{r},{g},{b}");
+            assert_eq!(color.to_16_color(), idx16, "{r},{g},{b}");
+        }
+    }
+
+    #[test]
+    fn test_to_256_color_palette_entries_map_to_themselves() {
+        for index in 16..=255 {
+            assert_eq!(Rgba::from_256_color(index).to_256_color(), index);
+        }
+        for index in 0..16 {
+            assert_eq!(Rgba::from_16_color(index).to_16_color(), index);
+        }
+    }
+
+    #[test]
+    fn test_quantization_cache_hits_match_a_fresh_search() {
+        let colors: Vec<Rgba> = (0u8..64)
+            .map(|i| Rgba::from_rgb_u8(i.wrapping_mul(37), i.wrapping_mul(91), i * 4))
+            .collect();
+        // The first pass fills the cache, the second reads it back.
+        for _ in 0..2 {
+            for &c in &colors {
+                let (r, g, b) = c.to_rgb_u8();
+                assert_eq!(c.to_256_color(), nearest_xterm_index(r, g, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_disabled_quantization_cache_is_cleared_and_bypassed() {
+        // A color no other test quantizes, so its slot is ours to inspect.
+        let color = Rgba::from_rgb_u8(0x03, 0x8d, 0x3b);
+        let rgb = 0x03_8d3b;
+        let slot = &QUANTIZE_CACHE[quantize_cache_slot(rgb)];
+        let expected = nearest_xterm_index(0x03, 0x8d, 0x3b);
+
+        assert_eq!(color.to_256_color(), expected);
+        assert_eq!(slot.load(Ordering::Relaxed) >> 8, rgb);
+
+        set_quantization_cache(false);
+        assert_ne!(slot.load(Ordering::Relaxed) >> 8, rgb);
+        assert_eq!(color.to_256_color(), expected);
+        let stored = slot.load(Ordering::Relaxed) >> 8;
+        set_quantization_cache(true);
+        assert_ne!(stored, rgb, "a disabled cache must not be filled");
+
+        assert_eq!(color.to_256_color(), expected);
+        assert_eq!(slot.load(Ordering::Relaxed) >> 8, rgb);
+    }
+
+    #[test]
+    fn test_perceptual_distance() {
+        assert!((Rgba::BLACK.perceptual_distance(Rgba::WHITE) - 1.0).abs() < 0.01);
+        assert!(Rgba::RED.perceptual_distance(Rgba::RED) < 1e-6);
+        // Equal RGB steps are not equal perceptual steps: dark blues are
+        // closer together than dark greens.
+        let blue_step =
+            Rgba::from_rgb_u8(0, 0, 40).perceptual_distance(Rgba::from_rgb_u8(0, 0, 80));
+        let green_step =
+            Rgba::from_rgb_u8(0, 40, 0).perceptual_distance(Rgba::from_rgb_u8(0, 80, 0));
+        assert!(blue_step < green_step);
+    }
+
     #[test]
     fn test_from_256_color_roundtrip() {
         // Standard colors
@@ -843,6 +2158,41 @@ mod proptests {
             prop_assert!((c_neg.b - c_pos.b).abs() < 1e-5, "B mismatch at -hue {h}");
         }
 
+        /// from_hsl then to_hsl is the identity, away from the ends of the
+        /// lightness range where hue and saturation are lost.
+        #[test]
+        fn hsl_round_trip(
+            h in 0.0f32..360.0f32,
+            s in 0.01f32..=1.0f32,
+            l in 0.01f32..=0.99f32
+        ) {
+            let (h2, s2, l2) = Rgba::from_hsl(h, s, l).to_hsl();
+            let hue_error = (h - h2).abs().min(360.0 - (h - h2).abs());
+            prop_assert!(hue_error < 0.1, "hue {h} -> {h2}");
+            prop_assert!((s - s2).abs() < 1e-3, "saturation {s} -> {s2}");
+            prop_assert!((l - l2).abs() < 1e-4, "lightness {l} -> {l2}");
+        }
+
+        /// to_hsl then from_hsl reproduces the color.
+        #[test]
+        fn rgb_hsl_round_trip(color in rgba_strategy()) {
+            let (h, s, l) = color.to_hsl();
+            let back = Rgba::from_hsl(h, s, l);
+            prop_assert!((color.r - back.r).abs() < 1e-4);
+            prop_assert!((color.g - back.g).abs() < 1e-4);
+            prop_assert!((color.b - back.b).abs() < 1e-4);
+        }
+
+        /// to_hsv then from_hsv reproduces the color.
+        #[test]
+        fn rgb_hsv_round_trip(color in rgba_strategy()) {
+            let (h, s, v) = color.to_hsv();
+            let back = Rgba::from_hsv(h, s, v);
+            prop_assert!((color.r - back.r).abs() < 1e-4);
+            prop_assert!((color.g - back.g).abs() < 1e-4);
+            prop_assert!((color.b - back.b).abs() < 1e-4);
+        }
+
         /// HSV with s=0 produces grayscale (r=g=b=v).
         #[test]
         fn hsv_zero_saturation_is_gray(
@@ -1240,7 +2590,6 @@ mod edge_case_tests {
         assert_eq!(Rgba::from_hex("#"), None);
         assert_eq!(Rgba::from_hex("#F"), None);
         assert_eq!(Rgba::from_hex("#FF"), None);
-        assert_eq!(Rgba::from_hex("#FFFF"), None);
         assert_eq!(Rgba::from_hex("#FFFFF"), None);
         assert_eq!(Rgba::from_hex("#FFFFFFF"), None);
         assert_eq!(Rgba::from_hex("#FFFFFFFFF"), None);
@@ -1478,18 +2827,18 @@ mod edge_case_tests {
 
     #[test]
     fn test_grayscale_ramp_boundary_values() {
-        // Test boundary values between grayscale levels
+        // Test the per-channel mapping's boundaries between grayscale levels
         // Midpoint between black (0) and first gray (8) is 4
         let very_dark = Rgba::from_rgb_u8(3, 3, 3);
         assert_eq!(
-            very_dark.to_256_color(),
+            very_dark.to_256_color_fast(),
             16,
             "Very dark gray should use cube black"
         );
 
         let first_gray = Rgba::from_rgb_u8(4, 4, 4);
         assert_eq!(
-            first_gray.to_256_color(),
+            first_gray.to_256_color_fast(),
             232,
             "Gray 4+ should start grayscale ramp"
         );
@@ -1497,14 +2846,14 @@ mod edge_case_tests {
         // Midpoint between last gray (238) and white (255) is ~246
         let almost_white = Rgba::from_rgb_u8(247, 247, 247);
         assert_eq!(
-            almost_white.to_256_color(),
+            almost_white.to_256_color_fast(),
             231,
             "Almost white should use cube white"
         );
 
         let last_gray = Rgba::from_rgb_u8(238, 238, 238);
         assert_eq!(
-            last_gray.to_256_color(),
+            last_gray.to_256_color_fast(),
             255,
             "Gray 238 should be last grayscale level"
         );
@@ -1589,6 +2938,31 @@ mod edge_case_tests {
         }
     }
 
+    // --- DEFAULT_FG / DEFAULT_BG sentinels ---
+
+    #[test]
+    fn test_default_sentinels_are_distinct() {
+        assert!(Rgba::DEFAULT_FG.is_default());
+        assert!(Rgba::DEFAULT_BG.is_default());
+        assert!(!Rgba::DEFAULT_FG.bits_eq(Rgba::DEFAULT_BG));
+        for color in [Rgba::TRANSPARENT, Rgba::BLACK, Rgba::WHITE] {
+            assert!(!color.is_default());
+            assert!(!color.bits_eq(Rgba::DEFAULT_BG));
+        }
+    }
+
+    #[test]
+    fn test_default_sentinels_blend_as_transparent() {
+        let red = Rgba::RED;
+        assert!(Rgba::DEFAULT_BG.blend_over(red).bits_eq(red));
+        assert!(
+            Rgba::DEFAULT_FG
+                .blend(red, BlendMode::Multiply)
+                .bits_eq(red)
+        );
+        assert!(Rgba::DEFAULT_BG.multiply_alpha(0.5).is_default());
+    }
+
     // --- is_transparent() and is_opaque() ---
 
     #[test]
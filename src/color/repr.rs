@@ -0,0 +1,164 @@
+//! Serde support for [`Rgba`] (`serde` feature).
+//!
+//! `Rgba` serializes as a hex string (`"#ff8800"`, or `"#ff880080"` when
+//! translucent), which reads well in config files but rounds channels to
+//! 8 bits. [`Rgba::DEFAULT_FG`] and [`Rgba::DEFAULT_BG`] serialize as
+//! `"default"` and `"default-bg"`. Deserialization accepts anything
+//! [`Rgba::parse`] does, or an `[r, g, b]` / `[r, g, b, a]` array of floats
+//! in 0.0-1.0.
+//!
+//! Fields that must keep full float precision can opt into the array form
+//! with the [`array`] helper:
+//!
+//! ```
+//! use opentui_rust::Rgba;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Accent {
+//!     #[serde(with = "opentui_rust::color::repr::array")]
+//!     color: Rgba,
+//! }
+//!
+//! let json = serde_json::to_string(&Accent { color: Rgba::new(0.1, 0.2, 0.3, 1.0) }).unwrap();
+//! assert_eq!(json, r#"{"color":[0.1,0.2,0.3,1.0]}"#);
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Rgba;
+
+impl Serialize for Rgba {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgba {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RgbaVisitor)
+    }
+}
+
+struct RgbaVisitor;
+
+impl<'de> Visitor<'de> for RgbaVisitor {
+    type Value = Rgba;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a color string or an array of 3 or 4 floats")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Rgba, E> {
+        Rgba::parse(value).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Rgba, A::Error> {
+        let mut channels = [0.0, 0.0, 0.0, 1.0];
+        let mut len = 0;
+        while let Some(value) = seq.next_element::<f32>()? {
+            if len == channels.len() {
+                return Err(de::Error::invalid_length(len + 1, &self));
+            }
+            channels[len] = value;
+            len += 1;
+        }
+        if len < 3 {
+            return Err(de::Error::invalid_length(len, &self));
+        }
+        let [r, g, b, a] = channels;
+        Ok(Rgba::new(r, g, b, a))
+    }
+}
+
+/// `#[serde(with)]` helper for the default hex string form.
+pub mod hex {
+    use super::{Deserialize, Deserializer, Rgba, Serializer};
+
+    /// Serialize as `#rrggbb` or `#rrggbbaa`, or a keyword for the default
+    /// color sentinels.
+    pub fn serialize<S: Serializer>(color: &Rgba, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_color_string())
+    }
+
+    /// Deserialize from a color string or float array.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rgba, D::Error> {
+        Rgba::deserialize(deserializer)
+    }
+}
+
+/// `#[serde(with)]` helper that writes `[r, g, b, a]` floats, keeping full
+/// precision.
+pub mod array {
+    use super::{Deserialize, Deserializer, Rgba, Serialize, Serializer};
+
+    /// Serialize as `[r, g, b, a]`.
+    pub fn serialize<S: Serializer>(color: &Rgba, serializer: S) -> Result<S::Ok, S::Error> {
+        [color.r, color.g, color.b, color.a].serialize(serializer)
+    }
+
+    /// Deserialize from a color string or float array.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rgba, D::Error> {
+        Rgba::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Precise {
+        #[serde(with = "array")]
+        color: Rgba,
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let color = Rgba::from_rgba_u8(255, 136, 0, 128);
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(json, "\"#ff880080\"");
+        assert_eq!(serde_json::from_str::<Rgba>(&json).unwrap(), color);
+    }
+
+    #[test]
+    fn default_sentinels_round_trip_as_keywords() {
+        for (color, json) in [
+            (Rgba::DEFAULT_FG, "\"default\""),
+            (Rgba::DEFAULT_BG, "\"default-bg\""),
+        ] {
+            assert_eq!(serde_json::to_string(&color).unwrap(), json);
+            let back = serde_json::from_str::<Rgba>(json).unwrap();
+            assert!(back.bits_eq(color), "{json} reloaded as {back:?}");
+        }
+        assert!(
+            !serde_json::from_str::<Rgba>("\"#00000000\"")
+                .unwrap()
+                .is_default()
+        );
+    }
+
+    #[test]
+    fn array_round_trip_keeps_precision() {
+        let value = Precise {
+            color: Rgba::new(0.123, 0.456, 0.789, 0.5),
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"color":[0.123,0.456,0.789,0.5]}"#);
+        assert_eq!(serde_json::from_str::<Precise>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn accepts_any_parseable_color_or_rgb_array() {
+        let parse = |json: &str| serde_json::from_str::<Rgba>(json);
+        assert_eq!(parse("\"red\"").unwrap(), Rgba::RED);
+        assert_eq!(parse("\"rgb(255, 0, 0)\"").unwrap(), Rgba::RED);
+        assert_eq!(parse("[1.0, 0.0, 0.0]").unwrap(), Rgba::RED);
+        assert!(parse("\"#ggg\"").is_err());
+        assert!(parse("[1.0, 0.0]").is_err());
+        assert!(parse("[1.0, 0.0, 0.0, 1.0, 1.0]").is_err());
+    }
+}
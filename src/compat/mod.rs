@@ -0,0 +1,5 @@
+//! Adapters that let applications written for other TUI libraries draw
+//! through the OpenTUI renderer.
+
+#[cfg(feature = "ratatui-backend")]
+pub mod ratatui;
@@ -0,0 +1,485 @@
+//! A [ratatui](https://ratatui.rs) backend drawing through the OpenTUI
+//! [`Renderer`].
+//!
+//! Enabled by the `ratatui-backend` feature. Wrap a renderer in an
+//! [`OpenTuiBackend`] and hand it to `ratatui::Terminal`; existing ratatui
+//! widgets and layouts then render unchanged, with OpenTUI doing the diffing
+//! and ANSI output:
+//!
+//! ```no_run
+//! use opentui_rust::Renderer;
+//! use opentui_rust::compat::ratatui::OpenTuiBackend;
+//! use ratatui::Terminal;
+//! use ratatui::widgets::Paragraph;
+//!
+//! let renderer = Renderer::new(80, 24)?;
+//! let mut terminal = Terminal::new(OpenTuiBackend::new(renderer))?;
+//! terminal.draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ratatui only sends the cells that changed since its last frame, while
+//! the renderer starts every frame from a cleared back buffer. The backend
+//! therefore keeps its own copy of the screen, applies ratatui's updates to
+//! it, and copies it into the back buffer on each flush.
+
+use std::io;
+
+use ::ratatui::backend::{Backend, WindowSize};
+use ::ratatui::buffer::Cell as RatatuiCell;
+use ::ratatui::layout::{Position, Size};
+use ::ratatui::style::{Color, Modifier};
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+use crate::color::Rgba;
+use crate::grapheme_pool::GraphemePool;
+use crate::renderer::Renderer;
+use crate::style::{Style, TextAttributes};
+
+/// Foreground used for [`Color::Reset`].
+pub const RESET_FG: Rgba = Rgba::WHITE;
+
+/// Background used for [`Color::Reset`]: transparent, so the renderer's
+/// background shows through.
+pub const RESET_BG: Rgba = Rgba::TRANSPARENT;
+
+/// Pairs of ratatui modifiers and the attributes they map to.
+const MODIFIER_ATTRIBUTES: [(Modifier, TextAttributes); 9] = [
+    (Modifier::BOLD, TextAttributes::BOLD),
+    (Modifier::DIM, TextAttributes::DIM),
+    (Modifier::ITALIC, TextAttributes::ITALIC),
+    (Modifier::UNDERLINED, TextAttributes::UNDERLINE),
+    (Modifier::SLOW_BLINK, TextAttributes::BLINK),
+    (Modifier::RAPID_BLINK, TextAttributes::BLINK),
+    (Modifier::REVERSED, TextAttributes::INVERSE),
+    (Modifier::HIDDEN, TextAttributes::HIDDEN),
+    (Modifier::CROSSED_OUT, TextAttributes::STRIKETHROUGH),
+];
+
+/// Convert a ratatui color, using `reset` for [`Color::Reset`].
+///
+/// Named and indexed colors use the standard 256-color palette.
+#[must_use]
+pub fn color_to_rgba(color: Color, reset: Rgba) -> Rgba {
+    let index = match color {
+        Color::Reset => return reset,
+        Color::Rgb(r, g, b) => return Rgba::from_rgb_u8(r, g, b),
+        Color::Indexed(index) => index,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    Rgba::from_256_color(index)
+}
+
+/// Convert a color to ratatui: [`Color::Reset`] when fully transparent,
+/// otherwise [`Color::Rgb`] ignoring alpha.
+#[must_use]
+pub fn rgba_to_color(color: Rgba) -> Color {
+    if color.a <= 0.0 {
+        return Color::Reset;
+    }
+    let (r, g, b) = color.to_rgb_u8();
+    Color::Rgb(r, g, b)
+}
+
+/// Convert ratatui modifiers to text attributes; both blink speeds map to
+/// [`TextAttributes::BLINK`].
+#[must_use]
+pub fn modifier_to_attributes(modifier: Modifier) -> TextAttributes {
+    MODIFIER_ATTRIBUTES
+        .iter()
+        .filter(|(m, _)| modifier.contains(*m))
+        .fold(TextAttributes::empty(), |acc, (_, a)| acc | *a)
+}
+
+/// Convert text attributes to ratatui modifiers; blink becomes
+/// [`Modifier::SLOW_BLINK`]. Overline, superscript, and subscript have no
+/// modifier and are dropped.
+#[must_use]
+pub fn attributes_to_modifier(attributes: TextAttributes) -> Modifier {
+    MODIFIER_ATTRIBUTES
+        .iter()
+        .filter(|(m, a)| attributes.contains(*a) && *m != Modifier::RAPID_BLINK)
+        .fold(Modifier::empty(), |acc, (m, _)| acc | *m)
+}
+
+/// Convert a ratatui cell, interning multi-codepoint symbols in `pool`.
+///
+/// The returned cell holds a reference to any interned grapheme; store it
+/// with a pool-aware setter such as [`OptimizedBuffer::set_with_pool`].
+/// Empty symbols become a space.
+#[must_use]
+pub fn cell_from_ratatui(pool: &mut GraphemePool, cell: &RatatuiCell) -> Cell {
+    let style = Style::fg(color_to_rgba(cell.fg, RESET_FG))
+        .with_bg(color_to_rgba(cell.bg, RESET_BG))
+        .with_attributes(modifier_to_attributes(cell.modifier));
+    let symbol = cell.symbol();
+    let mut chars = symbol.chars();
+    let mut out = match (chars.next(), chars.next()) {
+        (None, _) => Cell::new(' ', style),
+        (Some(c), None) => Cell::new(c, style),
+        _ => Cell::from_grapheme(symbol, style),
+    };
+    if out.content.is_grapheme() {
+        out.content = CellContent::Grapheme(pool.intern(symbol));
+    }
+    out
+}
+
+/// Convert a cell back to ratatui, resolving graphemes through `pool`.
+///
+/// Empty cells become a space. The trailing halves of wide characters
+/// become unstyled spaces, as ratatui stores them.
+#[must_use]
+pub fn cell_to_ratatui(cell: &Cell, pool: &GraphemePool) -> RatatuiCell {
+    let mut out = RatatuiCell::default();
+    match cell.content {
+        CellContent::Char(c) => {
+            out.set_char(c);
+        }
+        CellContent::Grapheme(id) => {
+            out.set_symbol(pool.get(id).unwrap_or(" "));
+        }
+        CellContent::Empty => {}
+        CellContent::Continuation => return out,
+    }
+    out.fg = rgba_to_color(cell.fg);
+    out.bg = rgba_to_color(cell.bg);
+    out.modifier = attributes_to_modifier(cell.attributes);
+    out
+}
+
+/// Write a ratatui cell at `(x, y)`, covering the columns a wide symbol
+/// spans with continuation cells.
+fn write_cell(
+    screen: &mut OptimizedBuffer,
+    pool: &mut GraphemePool,
+    x: u32,
+    y: u32,
+    cell: &RatatuiCell,
+) {
+    let cell = cell_from_ratatui(pool, cell);
+    let width = cell.display_width() as u32;
+    let bg = cell.bg;
+    screen.set_with_pool(pool, x, y, cell);
+    for dx in 1..width {
+        screen.set_with_pool(pool, x + dx, y, Cell::continuation(bg));
+    }
+}
+
+/// A ratatui [`Backend`] that draws into an OpenTUI [`Renderer`].
+///
+/// [`Backend::draw`] updates the backend's copy of the screen and
+/// [`Backend::flush`] presents it through [`Renderer::present`]. The cursor
+/// position and visibility requested by ratatui are applied after each
+/// present. The size is the renderer's; call [`Self::resize`] when the
+/// terminal changes size.
+pub struct OpenTuiBackend {
+    renderer: Renderer,
+    /// What ratatui has drawn so far, with graphemes in the renderer's pool.
+    screen: OptimizedBuffer,
+    cursor: Position,
+    cursor_visible: bool,
+}
+
+impl OpenTuiBackend {
+    /// Wrap `renderer`; the screen starts cleared.
+    #[must_use]
+    pub fn new(renderer: Renderer) -> Self {
+        let (width, height) = renderer.size();
+        let mut screen = OptimizedBuffer::new(width, height);
+        screen.clear(RESET_BG);
+        Self {
+            renderer,
+            screen,
+            cursor: Position::ORIGIN,
+            cursor_visible: false,
+        }
+    }
+
+    /// The wrapped renderer.
+    #[must_use]
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+
+    /// The wrapped renderer, for settings such as the color mode.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// What ratatui has drawn so far; graphemes resolve through the
+    /// renderer's pool.
+    #[must_use]
+    pub fn screen(&self) -> &OptimizedBuffer {
+        &self.screen
+    }
+
+    /// Unwrap the renderer.
+    #[must_use]
+    pub fn into_inner(self) -> Renderer {
+        self.renderer
+    }
+
+    /// Resize the renderer and the screen.
+    ///
+    /// ratatui notices the new size on its next draw and redraws everything.
+    pub fn resize(&mut self, width: u32, height: u32) -> crate::Result<()> {
+        let pool = self.renderer.grapheme_pool();
+        self.screen.resize_with_pool(pool, width, height);
+        self.screen.clear_with_pool(pool, RESET_BG);
+        self.renderer.resize(width, height)
+    }
+}
+
+impl Backend for OpenTuiBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a RatatuiCell)>,
+    {
+        let pool = self.renderer.grapheme_pool();
+        for (x, y, cell) in content {
+            if !cell.skip {
+                write_cell(&mut self.screen, pool, u32::from(x), u32::from(y), cell);
+            }
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.cursor = position.into();
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        let pool = self.renderer.grapheme_pool();
+        self.screen.clear_with_pool(pool, RESET_BG);
+        self.renderer.invalidate();
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        let (width, height) = self.renderer.size();
+        Ok(Size::new(
+            u16::try_from(width).unwrap_or(u16::MAX),
+            u16::try_from(height).unwrap_or(u16::MAX),
+        ))
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        let columns_rows = self.size()?;
+        let (cell_width, cell_height) = self.renderer.cell_pixel_size();
+        let pixels = Size::new(
+            u16::try_from(u32::from(columns_rows.width) * cell_width).unwrap_or(u16::MAX),
+            u16::try_from(u32::from(columns_rows.height) * cell_height).unwrap_or(u16::MAX),
+        );
+        Ok(WindowSize {
+            columns_rows,
+            pixels,
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let (buffer, pool) = self.renderer.buffer_with_pool();
+        buffer.draw_buffer_with_pool(pool, 0, 0, &self.screen);
+        self.renderer.present()?;
+        self.renderer.set_cursor(
+            u32::from(self.cursor.x),
+            u32::from(self.cursor.y),
+            self.cursor_visible,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::RendererOptions;
+    use ::ratatui::buffer::Buffer;
+    use ::ratatui::layout::Rect;
+    use ::ratatui::style::Style as RatatuiStyle;
+
+    fn test_backend(width: u32, height: u32) -> OpenTuiBackend {
+        let renderer = Renderer::new_with_options(
+            width,
+            height,
+            RendererOptions {
+                use_alt_screen: false,
+                hide_cursor: false,
+                enable_mouse: false,
+                query_capabilities: false,
+                capabilities: None,
+            },
+        )
+        .expect("test renderer creation should succeed with disabled options");
+        OpenTuiBackend::new(renderer)
+    }
+
+    /// A ratatui buffer with wide characters, a ZWJ emoji and mixed styles.
+    fn sample() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 2));
+        buffer.set_string(
+            0,
+            0,
+            "Hi 世界",
+            RatatuiStyle::new()
+                .fg(Color::Indexed(208))
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        );
+        buffer.set_string(8, 0, "ok", RatatuiStyle::new().fg(Color::Rgb(1, 2, 3)));
+        buffer.set_string(
+            0,
+            1,
+            "👩‍🚀 x",
+            RatatuiStyle::new()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::ITALIC | Modifier::CROSSED_OUT | Modifier::REVERSED),
+        );
+        buffer
+    }
+
+    /// `cell` as it should come back: colors passed through OpenTUI's
+    /// palette, as RGB.
+    fn expected(cell: &RatatuiCell) -> RatatuiCell {
+        let mut cell = cell.clone();
+        cell.fg = rgba_to_color(color_to_rgba(cell.fg, RESET_FG));
+        cell.bg = rgba_to_color(color_to_rgba(cell.bg, RESET_BG));
+        cell
+    }
+
+    #[test]
+    fn test_color_conversion() {
+        assert_eq!(
+            color_to_rgba(Color::Rgb(10, 20, 30), RESET_FG).to_rgb_u8(),
+            (10, 20, 30)
+        );
+        assert_eq!(color_to_rgba(Color::Red, RESET_FG), Rgba::from_256_color(1));
+        assert_eq!(
+            color_to_rgba(Color::White, RESET_FG),
+            Rgba::from_256_color(15)
+        );
+        assert_eq!(
+            color_to_rgba(Color::Indexed(196), RESET_FG),
+            Rgba::from_256_color(196)
+        );
+        assert_eq!(color_to_rgba(Color::Reset, RESET_BG), RESET_BG);
+        assert_eq!(rgba_to_color(RESET_BG), Color::Reset);
+        assert_eq!(
+            rgba_to_color(Rgba::from_256_color(196)),
+            Color::Rgb(255, 0, 0)
+        );
+
+        let all = MODIFIER_ATTRIBUTES
+            .iter()
+            .fold(Modifier::empty(), |acc, (m, _)| acc | *m);
+        assert_eq!(
+            modifier_to_attributes(all),
+            TextAttributes::all()
+                - (TextAttributes::OVERLINE
+                    | TextAttributes::SUPERSCRIPT
+                    | TextAttributes::SUBSCRIPT)
+        );
+        assert_eq!(
+            attributes_to_modifier(modifier_to_attributes(Modifier::RAPID_BLINK)),
+            Modifier::SLOW_BLINK
+        );
+    }
+
+    #[test]
+    fn test_buffer_round_trips_through_backend() {
+        let source = sample();
+        let mut backend = test_backend(12, 2);
+        let updates = Buffer::empty(source.area).diff(&source);
+        backend.draw(updates.into_iter()).unwrap();
+
+        let screen = backend.screen();
+        let pool = backend.renderer().grapheme_pool_ref();
+        assert_eq!(screen.get(4, 0).unwrap().content, CellContent::Continuation);
+        assert!(screen.get(0, 1).unwrap().content.is_grapheme());
+
+        for y in 0..2u16 {
+            for x in 0..12u16 {
+                let cell = screen.get(u32::from(x), u32::from(y)).unwrap();
+                // Trailing halves of wide characters come back untouched.
+                let expected = if cell.is_continuation() {
+                    source[(x, y)].clone()
+                } else {
+                    expected(&source[(x, y)])
+                };
+                assert_eq!(cell_to_ratatui(cell, pool), expected, "cell ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_flush_presents_screen_and_keeps_graphemes_alive() {
+        let mut backend = test_backend(12, 2);
+        let source = sample();
+        let previous = Buffer::empty(source.area);
+        backend.draw(previous.diff(&source).into_iter()).unwrap();
+        backend.flush().unwrap();
+        backend.flush().unwrap();
+
+        let renderer = backend.renderer();
+        let front = renderer.front_buffer();
+        let pool = renderer.grapheme_pool_ref();
+        assert_eq!(front.get(1, 0).unwrap().content, CellContent::Char('i'));
+        assert_eq!(
+            cell_to_ratatui(front.get(0, 1).unwrap(), pool).symbol(),
+            "👩‍🚀"
+        );
+
+        // Replacing the wide characters frees nothing the screen still uses.
+        let mut next = source.clone();
+        next.set_string(0, 1, "ab", RatatuiStyle::new());
+        backend.draw(source.diff(&next).into_iter()).unwrap();
+        backend.flush().unwrap();
+        let pool = backend.renderer().grapheme_pool_ref();
+        let front = backend.renderer().front_buffer();
+        assert_eq!(front.get(1, 1).unwrap().content, CellContent::Char('b'));
+        assert_eq!(pool.active_count(), 0);
+    }
+
+    #[test]
+    fn test_size_and_cursor() {
+        let mut backend = test_backend(7, 3);
+        assert_eq!(backend.size().unwrap(), Size::new(7, 3));
+        backend.set_cursor_position((2, 1)).unwrap();
+        assert_eq!(backend.get_cursor_position().unwrap(), Position::new(2, 1));
+
+        backend.resize(9, 4).unwrap();
+        assert_eq!(backend.size().unwrap(), Size::new(9, 4));
+        assert_eq!(backend.screen().size(), (9, 4));
+    }
+}
@@ -6,6 +6,77 @@ use std::io;
 /// Result type alias for OpenTUI operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Terminal operations that can fail, for [`Error::Terminal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalOp {
+    EnterRawMode,
+    QueryCapabilities,
+    AltScreen,
+    Mouse,
+    Cursor,
+    Palette,
+    Clear,
+    Scroll,
+    SetTitle,
+    Reset,
+    Sync,
+    Flush,
+}
+
+impl TerminalOp {
+    const fn describe(self) -> &'static str {
+        match self {
+            Self::EnterRawMode => "enter raw mode (is stdin a terminal?)",
+            Self::QueryCapabilities => "send capability queries",
+            Self::AltScreen => "switch the alternate screen",
+            Self::Mouse => "switch mouse tracking",
+            Self::Cursor => "update the cursor",
+            Self::Palette => "update the color palette",
+            Self::Clear => "clear the screen",
+            Self::Scroll => "scroll the screen",
+            Self::SetTitle => "set the window title",
+            Self::Reset => "reset terminal attributes",
+            Self::Sync => "write a synchronized-update sequence",
+            Self::Flush => "flush terminal output",
+        }
+    }
+}
+
+/// Stages of presenting a frame, for [`Error::Render`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderPhase {
+    /// Running a hook added with
+    /// [`on_before_present`](crate::Renderer::on_before_present) or
+    /// [`on_after_present`](crate::Renderer::on_after_present).
+    Hook,
+    /// Opening the synchronized update around a frame.
+    BeginSync,
+    /// Writing the frame's cells to stdout.
+    Write,
+    /// Flushing stdout after the frame.
+    Flush,
+    /// Closing the synchronized update.
+    EndSync,
+    /// Clearing the screen after a resize.
+    Resize,
+    /// Restoring the terminal on exit.
+    Cleanup,
+}
+
+impl RenderPhase {
+    const fn describe(self) -> &'static str {
+        match self {
+            Self::Hook => "run a present hook",
+            Self::BeginSync => "begin a synchronized update",
+            Self::Write => "write the frame to stdout",
+            Self::Flush => "flush the frame to stdout",
+            Self::EndSync => "end a synchronized update",
+            Self::Resize => "clear the screen after resizing",
+            Self::Cleanup => "restore the terminal",
+        }
+    }
+}
+
 /// Error type for OpenTUI operations.
 #[derive(Debug)]
 pub enum Error {
@@ -15,13 +86,24 @@ pub enum Error {
     InvalidColor(String),
     /// Buffer dimension error (e.g., zero width/height).
     InvalidDimensions { width: u32, height: u32 },
-    /// Position out of bounds.
+    /// A terminal operation failed.
+    Terminal { op: TerminalOp, source: io::Error },
+    /// Writing a frame failed.
+    Render {
+        phase: RenderPhase,
+        source: io::Error,
+    },
+    /// The terminal or the pipe the renderer writes to is gone, and the
+    /// renderer has stopped writing.
+    OutputClosed,
+    /// An index past the end of something `len` long.
     OutOfBounds {
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
+        what: &'static str,
+        index: usize,
+        len: usize,
     },
+    /// An argument that can never be valid, such as a reversed range.
+    InvalidInput(String),
     /// Pixel count doesn't match expected dimensions.
     SizeMismatch { expected: usize, actual: usize },
     /// Dimension overflow (width * height exceeds usize).
@@ -31,6 +113,23 @@ pub enum Error {
         old_size: (u32, u32),
         new_size: (u32, u32),
     },
+    /// Malformed theme file. `line` is 1-based when the location is known.
+    ThemeParse {
+        line: Option<usize>,
+        message: String,
+    },
+    /// Malformed style spec such as `"bold #ff8800 on black"`. `offset` is
+    /// the byte offset of the offending `token` in the spec.
+    StyleParse {
+        token: String,
+        offset: usize,
+        message: String,
+    },
+    /// Frame validation found malformed cells; nothing was written. See
+    /// [`Renderer::set_validation`](crate::Renderer::set_validation).
+    InvalidFrame {
+        issues: Vec<crate::renderer::FrameIssue>,
+    },
 }
 
 impl fmt::Display for Error {
@@ -41,17 +140,20 @@ impl fmt::Display for Error {
             Self::InvalidDimensions { width, height } => {
                 write!(f, "invalid dimensions: {width}x{height}")
             }
-            Self::OutOfBounds {
-                x,
-                y,
-                width,
-                height,
-            } => {
-                write!(
-                    f,
-                    "position ({x}, {y}) out of bounds for {width}x{height} buffer"
-                )
+            Self::Terminal { op, source } => {
+                write!(f, "failed to {}: {source}", op.describe())
+            }
+            Self::Render { phase, source } => {
+                write!(f, "rendering failed to {}: {source}", phase.describe())
             }
+            Self::OutputClosed => write!(
+                f,
+                "output closed: the terminal or pipe went away, so nothing more can be rendered"
+            ),
+            Self::OutOfBounds { what, index, len } => {
+                write!(f, "{what} {index} out of bounds (length is {len})")
+            }
+            Self::InvalidInput(message) => write!(f, "invalid input: {message}"),
             Self::SizeMismatch { expected, actual } => {
                 write!(
                     f,
@@ -68,14 +170,73 @@ impl fmt::Display for Error {
                     old_size.0, old_size.1, new_size.0, new_size.1
                 )
             }
+            Self::ThemeParse {
+                line: Some(line),
+                message,
+            } => write!(f, "theme parse error at line {line}: {message}"),
+            Self::ThemeParse {
+                line: None,
+                message,
+            } => write!(f, "theme parse error: {message}"),
+            Self::StyleParse {
+                token,
+                offset,
+                message,
+            } => write!(
+                f,
+                "invalid style spec at byte {offset} (`{token}`): {message}"
+            ),
+            Self::InvalidFrame { issues } => {
+                write!(f, "invalid frame: {} malformed cells", issues.len())?;
+                if let Some(first) = issues.first() {
+                    write!(f, ", first at {first}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) | Self::Terminal { source: e, .. } | Self::Render { source: e, .. } => {
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Wrap an I/O error from `op`, for use with `map_err`.
+    pub(crate) fn terminal(op: TerminalOp) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self::Terminal { op, source }
+    }
+
+    /// Attach the render `phase` an error happened in, for use with
+    /// `map_err`. Other crate errors are kept whole as the I/O source.
+    pub(crate) fn render<E: Into<io::Error>>(phase: RenderPhase) -> impl FnOnce(E) -> Self {
+        move |source| Self::Render {
+            phase,
+            source: source.into(),
+        }
+    }
+}
+
+impl Error {
+    /// The I/O error at the bottom of this one, looking through render
+    /// phases wrapped around terminal errors.
+    pub(crate) fn io_source(&self) -> Option<&io::Error> {
         match self {
             Self::Io(e) => Some(e),
+            Self::Terminal { source, .. } | Self::Render { source, .. } => Some(
+                source
+                    .get_ref()
+                    .and_then(|inner| inner.downcast_ref::<Self>())
+                    .and_then(Self::io_source)
+                    .unwrap_or(source),
+            ),
             _ => None,
         }
     }
@@ -87,6 +248,25 @@ impl From<io::Error> for Error {
     }
 }
 
+/// Lets `?` pass crate errors up through functions returning
+/// [`io::Result`]. I/O failures keep their [`io::ErrorKind`].
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(e) => e,
+            Error::Terminal { ref source, .. } | Error::Render { ref source, .. } => {
+                Self::new(source.kind(), err)
+            }
+            Error::OutputClosed => Self::new(io::ErrorKind::BrokenPipe, err),
+            Error::InvalidInput(_)
+            | Error::OutOfBounds { .. }
+            | Error::InvalidColor(_)
+            | Error::StyleParse { .. } => Self::new(io::ErrorKind::InvalidInput, err),
+            _ => Self::other(err),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,12 +283,20 @@ mod tests {
         assert!(err.to_string().contains("0x100"));
 
         let err = Error::OutOfBounds {
-            x: 10,
-            y: 20,
-            width: 5,
-            height: 5,
+            what: "char index",
+            index: 12,
+            len: 5,
+        };
+        assert_eq!(err.to_string(), "char index 12 out of bounds (length is 5)");
+
+        let err = Error::ThemeParse {
+            line: Some(3),
+            message: "invalid color `#zz`".to_string(),
         };
-        assert!(err.to_string().contains("(10, 20)"));
+        assert_eq!(
+            err.to_string(),
+            "theme parse error at line 3: invalid color `#zz`"
+        );
     }
 
     #[test]
@@ -117,4 +305,63 @@ mod tests {
         let err: Error = io_err.into();
         assert!(matches!(err, Error::Io(_)));
     }
+
+    #[test]
+    fn test_error_chain_keeps_operation_and_source() {
+        use std::error::Error as _;
+
+        let err = Error::terminal(TerminalOp::Sync)(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(
+            err.to_string()
+                .starts_with("failed to write a synchronized-update sequence: ")
+        );
+        let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::BrokenPipe);
+
+        // A render phase wraps the terminal error without losing it.
+        let err = Error::render(RenderPhase::BeginSync)(err);
+        let Error::Render { phase, source } = &err else {
+            panic!("expected a render error, got {err:?}");
+        };
+        assert_eq!(*phase, RenderPhase::BeginSync);
+        assert_eq!(source.kind(), io::ErrorKind::BrokenPipe);
+        let inner = source.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+        assert!(matches!(
+            inner,
+            Error::Terminal {
+                op: TerminalOp::Sync,
+                ..
+            }
+        ));
+        assert!(err.to_string().starts_with(
+            "rendering failed to begin a synchronized update: failed to write a synchronized-update sequence"
+        ));
+    }
+
+    #[test]
+    fn test_question_mark_interop_with_io_result() {
+        fn downstream() -> io::Result<()> {
+            let err: Result<()> = Err(Error::OutOfBounds {
+                what: "line",
+                index: 3,
+                len: 2,
+            });
+            err?;
+            Ok(())
+        }
+        fn upstream() -> Result<()> {
+            Err(io::Error::other("disk full"))?;
+            Ok(())
+        }
+
+        let err = downstream().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "line 3 out of bounds (length is 2)");
+        assert!(matches!(upstream(), Err(Error::Io(_))));
+
+        // I/O errors come back out unwrapped.
+        let io_err: io::Error = Error::Io(io::Error::from(io::ErrorKind::NotFound)).into();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+        assert!(io_err.get_ref().is_none());
+    }
 }
@@ -1,6 +1,21 @@
 //! Event and log callback system.
+//!
+//! [`EventBus`] delivers typed events such as [`RendererEvent`] to any
+//! number of subscribers. The older global callback set by
+//! [`set_event_callback`] takes one listener of string events; buses
+//! forward their renderer and terminal events to it.
+//!
+//! Log messages carry a level, a target naming the module that logged them,
+//! and optional key-value fields. With the `log` feature, `install_logger`
+//! routes the `log` crate's records into this system and
+//! `install_log_forwarder` sends this system's messages to the `log` crate.
 
-use std::sync::{Mutex, OnceLock};
+mod bus;
+
+use std::cell::Cell;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+pub use bus::{EventBus, RendererEvent, SubscriptionId, TerminalEvent};
 
 /// Log level for debug callbacks.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -11,8 +26,50 @@ pub enum LogLevel {
     Error,
 }
 
+/// Target of messages logged without one: the crate name.
+pub const LOG_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// A log message with its target and structured fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogRecord<'a> {
+    pub level: LogLevel,
+    /// Module or component that logged the message, such as
+    /// `opentui_rust::terminal::capabilities`.
+    pub target: &'a str,
+    pub message: &'a str,
+    /// Key-value pairs describing the message.
+    pub fields: &'a [(&'a str, &'a str)],
+}
+
+impl<'a> LogRecord<'a> {
+    /// Create a record with the [`LOG_TARGET`] target and no fields.
+    #[must_use]
+    pub const fn new(level: LogLevel, message: &'a str) -> Self {
+        Self {
+            level,
+            target: LOG_TARGET,
+            message,
+            fields: &[],
+        }
+    }
+
+    /// Set the target, usually `module_path!()`.
+    #[must_use]
+    pub const fn with_target(mut self, target: &'a str) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set the key-value fields.
+    #[must_use]
+    pub const fn with_fields(mut self, fields: &'a [(&'a str, &'a str)]) -> Self {
+        self.fields = fields;
+        self
+    }
+}
+
 type EventCallback = Box<dyn Fn(&str, &str) + Send + Sync + 'static>;
-type LogCallback = Box<dyn Fn(LogLevel, &str) + Send + Sync + 'static>;
+type LogCallback = Arc<dyn Fn(&LogRecord<'_>) + Send + Sync + 'static>;
 
 fn event_callback() -> &'static Mutex<Option<EventCallback>> {
     static CALLBACK: OnceLock<Mutex<Option<EventCallback>>> = OnceLock::new();
@@ -24,6 +81,28 @@ fn log_callback() -> &'static Mutex<Option<LogCallback>> {
     CALLBACK.get_or_init(|| Mutex::new(None))
 }
 
+thread_local! {
+    /// Set while this thread runs the log callback.
+    static IN_LOG_CALLBACK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks this thread as running the log callback until dropped, so the
+/// mark is cleared even if the callback panics.
+struct InLogCallback;
+
+impl InLogCallback {
+    fn enter() -> Self {
+        IN_LOG_CALLBACK.with(|flag| flag.set(true));
+        Self
+    }
+}
+
+impl Drop for InLogCallback {
+    fn drop(&mut self) {
+        IN_LOG_CALLBACK.with(|flag| flag.set(false));
+    }
+}
+
 /// Set the global event callback.
 pub fn set_event_callback<F>(callback: F)
 where
@@ -33,6 +112,12 @@ where
     *guard = Some(Box::new(callback));
 }
 
+/// Remove the global event callback.
+pub fn clear_event_callback() {
+    let mut guard = event_callback().lock().expect("event callback lock");
+    *guard = None;
+}
+
 /// Emit an event to the registered callback.
 pub fn emit_event(name: &str, data: &str) {
     if let Ok(guard) = event_callback().lock() {
@@ -42,49 +127,475 @@ pub fn emit_event(name: &str, data: &str) {
     }
 }
 
-/// Set the global log callback.
+/// Set the global log callback, receiving only each message's level and
+/// text.
+///
+/// Use [`set_log_record_callback`] to also receive targets and fields.
 pub fn set_log_callback<F>(callback: F)
 where
     F: Fn(LogLevel, &str) + Send + Sync + 'static,
 {
-    let mut guard = log_callback().lock().expect("log callback lock");
-    *guard = Some(Box::new(callback));
+    set_log_record_callback(move |record| callback(record.level, record.message));
+}
+
+/// Set the global log callback, receiving whole records.
+///
+/// Replaces any callback set by [`set_log_callback`].
+pub fn set_log_record_callback<F>(callback: F)
+where
+    F: Fn(&LogRecord<'_>) + Send + Sync + 'static,
+{
+    let mut guard = log_callback()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    *guard = Some(Arc::new(callback));
+}
+
+/// Remove the global log callback; messages are dropped until a new one
+/// is set.
+pub fn clear_log_callback() {
+    let mut guard = log_callback()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    *guard = None;
 }
 
-/// Emit a log event.
+/// Emit a log event with the [`LOG_TARGET`] target and no fields.
 pub fn emit_log(level: LogLevel, message: &str) {
-    if let Ok(guard) = log_callback().lock() {
-        if let Some(callback) = guard.as_ref() {
-            callback(level, message);
+    emit_log_record(&LogRecord::new(level, message));
+}
+
+/// Emit a log record.
+///
+/// Records emitted from inside the log callback itself are dropped, so a
+/// callback that logs (or a forwarder looping back through
+/// `install_logger`) cannot recurse. The callback runs without the lock
+/// held, so it may replace or clear itself.
+pub fn emit_log_record(record: &LogRecord<'_>) {
+    if IN_LOG_CALLBACK.with(Cell::get) {
+        return;
+    }
+    let callback = log_callback()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(callback) = callback {
+        let _in_callback = InLogCallback::enter();
+        callback(record);
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Self::Error,
+            log::Level::Warn => Self::Warn,
+            log::Level::Info => Self::Info,
+            log::Level::Debug | log::Level::Trace => Self::Debug,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => Self::Debug,
+            LogLevel::Info => Self::Info,
+            LogLevel::Warn => Self::Warn,
+            LogLevel::Error => Self::Error,
         }
     }
 }
 
+/// A `log::Log` implementation forwarding records to [`emit_log_record`].
+///
+/// Trace records arrive as [`LogLevel::Debug`]; key-value pairs become
+/// fields, with values formatted as text.
+#[cfg(feature = "log")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpenTuiLogger;
+
+#[cfg(feature = "log")]
+impl log::Log for OpenTuiLogger {
+    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        struct Fields(Vec<(String, String)>);
+
+        impl<'kvs> log::kv::VisitSource<'kvs> for Fields {
+            fn visit_pair(
+                &mut self,
+                key: log::kv::Key<'kvs>,
+                value: log::kv::Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let mut fields = Fields(Vec::new());
+        // Visiting only fails if the visitor does, and ours never does.
+        let _ = record.key_values().visit(&mut fields);
+        let fields: Vec<(&str, &str)> = fields
+            .0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let message = record.args().to_string();
+        emit_log_record(&LogRecord {
+            level: record.level().into(),
+            target: record.target(),
+            message: &message,
+            fields: &fields,
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install [`OpenTuiLogger`] as the `log` crate's logger and enable all
+/// levels.
+///
+/// Fails if another logger is already installed.
+#[cfg(feature = "log")]
+pub fn install_logger() -> Result<(), log::SetLoggerError> {
+    static LOGGER: OpenTuiLogger = OpenTuiLogger;
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(log::LevelFilter::Trace);
+    Ok(())
+}
+
+/// Set the log callback to forward every record to the `log` crate's
+/// logger, with its target and fields.
+///
+/// Replaces any existing log callback.
+#[cfg(feature = "log")]
+pub fn install_log_forwarder() {
+    set_log_record_callback(|record| {
+        let logger = log::logger();
+        let level = log::Level::from(record.level);
+        if level > log::max_level() {
+            return;
+        }
+        let fields = record.fields;
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(record.target)
+                .args(format_args!("{}", record.message))
+                .key_values(&fields)
+                .build(),
+        );
+    });
+}
+
+/// Serializes tests that replace the global log callback.
+#[cfg(test)]
+pub(crate) fn test_log_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// A log record with owned strings, as collected by [`capture_logs`].
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CapturedLog {
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+/// Run `f` with a collecting log callback and return what this thread
+/// logged meanwhile.
+#[cfg(test)]
+pub(crate) fn capture_logs(f: impl FnOnce()) -> Vec<CapturedLog> {
+    let _lock = test_log_lock();
+    let thread = std::thread::current().id();
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&logs);
+    set_log_record_callback(move |record| {
+        if std::thread::current().id() != thread {
+            return;
+        }
+        sink.lock().unwrap().push(CapturedLog {
+            level: record.level,
+            target: record.target.to_string(),
+            message: record.message.to_string(),
+            fields: record
+                .fields
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+        });
+    });
+    f();
+    clear_log_callback();
+    logs.lock().unwrap().clone()
+}
+
+/// Run `f` with a collecting event callback and return the `(name, data)`
+/// pairs this thread emitted meanwhile.
+#[cfg(test)]
+pub(crate) fn capture_events(f: impl FnOnce()) -> Vec<(String, String)> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _lock = LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let thread = std::thread::current().id();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    set_event_callback(move |name, data| {
+        if std::thread::current().id() == thread {
+            sink.lock()
+                .unwrap()
+                .push((name.to_string(), data.to_string()));
+        }
+    });
+    f();
+    clear_event_callback();
+    events.lock().unwrap().clone()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_event_callback() {
-        use std::sync::Arc;
-        use std::sync::atomic::{AtomicBool, Ordering};
-
-        let called = Arc::new(AtomicBool::new(false));
-        let called_clone = Arc::clone(&called);
-        set_event_callback(move |name, _data| {
-            assert_eq!(name, "test");
-            called_clone.store(true, Ordering::SeqCst);
-        });
-        emit_event("test", "{}");
-        assert!(called.load(Ordering::SeqCst));
+        let events = capture_events(|| emit_event("test", "{}"));
+        assert_eq!(events, [("test".to_string(), "{}".to_string())]);
     }
 
     #[test]
     fn test_log_callback() {
-        set_log_callback(|level, msg| {
-            assert_eq!(level, LogLevel::Info);
-            assert_eq!(msg, "hello");
+        let _lock = test_log_lock();
+        // Other tests may log through the global callback concurrently, so
+        // record messages instead of asserting inside the callback.
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = Arc::clone(&received);
+        set_log_callback(move |level, msg| {
+            received_clone
+                .lock()
+                .unwrap()
+                .push((level, msg.to_string()));
         });
         emit_log(LogLevel::Info, "hello");
+        clear_log_callback();
+        assert!(
+            received
+                .lock()
+                .unwrap()
+                .contains(&(LogLevel::Info, "hello".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_log_record_target_and_fields() {
+        let logs = capture_logs(|| {
+            emit_log(LogLevel::Warn, "plain");
+            emit_log_record(
+                &LogRecord::new(LogLevel::Error, "structured")
+                    .with_target("app::net")
+                    .with_fields(&[("host", "example.com"), ("attempt", "3")]),
+            );
+        });
+        assert_eq!(logs.len(), 2);
+        assert_eq!(
+            (logs[0].level, logs[0].target.as_str()),
+            (LogLevel::Warn, "opentui_rust")
+        );
+        assert!(logs[0].fields.is_empty());
+        assert_eq!(logs[1].target, "app::net");
+        assert_eq!(
+            logs[1].fields,
+            [
+                ("host".to_string(), "example.com".to_string()),
+                ("attempt".to_string(), "3".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logging_inside_callback_does_not_recurse() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _lock = test_log_lock();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let thread = std::thread::current().id();
+        set_log_callback(move |_, _| {
+            if std::thread::current().id() == thread {
+                counter.fetch_add(1, Ordering::SeqCst);
+                emit_log(LogLevel::Error, "from inside the callback");
+            }
+        });
+        emit_log(LogLevel::Info, "outer");
+        clear_log_callback();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_panicking_log_callback_does_not_silence_later_logs() {
+        {
+            let _lock = test_log_lock();
+            let thread = std::thread::current().id();
+            set_log_callback(move |_, _| {
+                assert_ne!(std::thread::current().id(), thread, "callback panicked");
+            });
+            let result = std::panic::catch_unwind(|| emit_log(LogLevel::Info, "boom"));
+            clear_log_callback();
+            assert!(result.is_err());
+        }
+
+        let logs = capture_logs(|| emit_log(LogLevel::Info, "after"));
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].message, "after");
+    }
+
+    #[test]
+    fn test_log_callback_can_replace_itself() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _lock = test_log_lock();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let thread = std::thread::current().id();
+        set_log_callback(move |_, _| {
+            if std::thread::current().id() == thread {
+                counter.fetch_add(1, Ordering::SeqCst);
+                set_log_callback(|_, _| {});
+            }
+        });
+        emit_log(LogLevel::Info, "first");
+        emit_log(LogLevel::Info, "second");
+        clear_log_callback();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_renderer_reports_detected_capabilities() {
+        let logs = capture_logs(|| {
+            let _renderer = crate::Renderer::new_with_options(
+                4,
+                2,
+                crate::RendererOptions {
+                    use_alt_screen: false,
+                    hide_cursor: false,
+                    enable_mouse: false,
+                    query_capabilities: false,
+                    capabilities: None,
+                },
+            )
+            .unwrap();
+        });
+        let detected = logs
+            .iter()
+            .find(|log| log.message == "detected terminal capabilities")
+            .expect("capability detection should log");
+        assert_eq!(detected.level, LogLevel::Debug);
+        assert_eq!(detected.target, "opentui_rust::terminal::capabilities");
+        assert!(detected.fields.iter().any(|(key, _)| key == "color"));
+    }
+
+    #[cfg(feature = "log")]
+    mod log_bridge {
+        use super::*;
+        use log::Log;
+
+        /// Collects records reaching the `log` crate; installed once per
+        /// test process.
+        struct Collector(Mutex<Vec<(log::Level, String, String, String)>>);
+
+        impl Log for Collector {
+            fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                let mut fields = String::new();
+                let _ = record.key_values().visit(&mut FieldText(&mut fields));
+                self.0.lock().unwrap().push((
+                    record.level(),
+                    record.target().to_string(),
+                    record.args().to_string(),
+                    fields,
+                ));
+            }
+
+            fn flush(&self) {}
+        }
+
+        struct FieldText<'a>(&'a mut String);
+
+        impl<'kvs> log::kv::VisitSource<'kvs> for FieldText<'_> {
+            fn visit_pair(
+                &mut self,
+                key: log::kv::Key<'kvs>,
+                value: log::kv::Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                self.0.push_str(&format!("{key}={value};"));
+                Ok(())
+            }
+        }
+
+        fn collector() -> &'static Collector {
+            static COLLECTOR: OnceLock<&'static Collector> = OnceLock::new();
+            COLLECTOR.get_or_init(|| {
+                let collector = Box::leak(Box::new(Collector(Mutex::new(Vec::new()))));
+                log::set_logger(collector).expect("no other logger in tests");
+                log::set_max_level(log::LevelFilter::Trace);
+                collector
+            })
+        }
+
+        #[test]
+        fn test_logger_forwards_log_records() {
+            let logs = capture_logs(|| {
+                OpenTuiLogger.log(
+                    &log::Record::builder()
+                        .level(log::Level::Trace)
+                        .target("app::db")
+                        .args(format_args!("query took {}ms", 12))
+                        .key_values(&[("table", "users")])
+                        .build(),
+                );
+            });
+            assert_eq!(
+                logs,
+                [CapturedLog {
+                    level: LogLevel::Debug,
+                    target: "app::db".to_string(),
+                    message: "query took 12ms".to_string(),
+                    fields: vec![("table".to_string(), "users".to_string())],
+                }]
+            );
+        }
+
+        #[test]
+        fn test_forwarder_sends_records_to_log() {
+            let collector = collector();
+            let _lock = test_log_lock();
+            install_log_forwarder();
+            emit_log_record(
+                &LogRecord::new(LogLevel::Warn, "forwarded")
+                    .with_target("opentui_rust::test")
+                    .with_fields(&[("id", "7")]),
+            );
+            clear_log_callback();
+
+            let forwarded = collector.0.lock().unwrap().contains(&(
+                log::Level::Warn,
+                "opentui_rust::test".to_string(),
+                "forwarded".to_string(),
+                "id=7;".to_string(),
+            ));
+            assert!(forwarded);
+        }
     }
 }
@@ -0,0 +1,253 @@
+//! Typed publish/subscribe event bus.
+
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::emit_event;
+use crate::renderer::RenderStats;
+use crate::terminal::Capabilities;
+
+/// Events published by a [`Renderer`](crate::Renderer).
+#[derive(Clone, Debug)]
+pub enum RendererEvent {
+    /// The renderer was resized to `width` x `height` cells.
+    Resized { width: u32, height: u32 },
+    /// A frame was presented; carries the stats after it.
+    FramePresented(RenderStats),
+    /// The frame about to be presented is redrawn in full rather than
+    /// diffed, after a resize, [`invalidate`](crate::Renderer::invalidate),
+    /// or a diff too large to be worth it.
+    ForcedRedraw,
+}
+
+impl RendererEvent {
+    /// Name used when bridging to the global event callback.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Resized { .. } => "renderer.resized",
+            Self::FramePresented(_) => "renderer.frame_presented",
+            Self::ForcedRedraw => "renderer.forced_redraw",
+        }
+    }
+
+    /// JSON payload used when bridging to the global event callback.
+    #[must_use]
+    pub fn data(&self) -> String {
+        match self {
+            Self::Resized { width, height } => format!("{{\"width\":{width},\"height\":{height}}}"),
+            Self::FramePresented(stats) => format!(
+                "{{\"frame\":{},\"cells\":{}}}",
+                stats.frames, stats.last_frame_cells
+            ),
+            Self::ForcedRedraw => "{}".to_string(),
+        }
+    }
+}
+
+/// Events published by a [`Terminal`](crate::Terminal).
+#[derive(Clone, Debug)]
+pub enum TerminalEvent {
+    /// Capabilities were detected or updated from a query response.
+    CapabilityDetected(Capabilities),
+    /// Raw mode was turned on (`true`) or off (`false`).
+    RawModeChanged(bool),
+}
+
+impl TerminalEvent {
+    /// Name used when bridging to the global event callback.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::CapabilityDetected(_) => "terminal.capability_detected",
+            Self::RawModeChanged(_) => "terminal.raw_mode_changed",
+        }
+    }
+
+    /// JSON payload used when bridging to the global event callback.
+    #[must_use]
+    pub fn data(&self) -> String {
+        match self {
+            Self::CapabilityDetected(caps) => format!(
+                "{{\"color\":\"{:?}\",\"sync_output\":{},\"hyperlinks\":{}}}",
+                caps.color, caps.sync_output, caps.hyperlinks
+            ),
+            Self::RawModeChanged(enabled) => format!("{{\"enabled\":{enabled}}}"),
+        }
+    }
+}
+
+/// Identifies a subscription for [`EventBus::unsubscribe`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Handler = Arc<dyn Fn(&dyn Any) + Send + Sync + 'static>;
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    /// Subscribers in subscription order.
+    subscribers: Vec<(SubscriptionId, TypeId, Handler)>,
+}
+
+/// A publish/subscribe bus keyed by event type.
+///
+/// Subscribers receive every published value of the type they subscribed
+/// to, synchronously and in subscription order. Clones share the same
+/// subscribers, so one bus can be handed to the renderer, the terminal, and
+/// the application.
+///
+/// A new bus forwards [`RendererEvent`]s and [`TerminalEvent`]s to the
+/// global callback set by [`set_event_callback`](super::set_event_callback),
+/// using their [`name`](RendererEvent::name) and
+/// [`data`](RendererEvent::data).
+#[derive(Clone)]
+pub struct EventBus {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl EventBus {
+    /// Create a bus bridged to the global event callback.
+    #[must_use]
+    pub fn new() -> Self {
+        let bus = Self {
+            registry: Arc::new(Mutex::new(Registry::default())),
+        };
+        bus.subscribe(|event: &RendererEvent| emit_event(event.name(), &event.data()));
+        bus.subscribe(|event: &TerminalEvent| emit_event(event.name(), &event.data()));
+        bus
+    }
+
+    /// Call `handler` with every `T` published from now on.
+    pub fn subscribe<T, F>(&self, handler: F) -> SubscriptionId
+    where
+        T: Any,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let handler: Handler = Arc::new(move |event: &dyn Any| {
+            if let Some(event) = event.downcast_ref::<T>() {
+                handler(event);
+            }
+        });
+        let mut registry = self.registry.lock().expect("event bus lock");
+        let id = SubscriptionId(registry.next_id);
+        registry.next_id += 1;
+        registry.subscribers.push((id, TypeId::of::<T>(), handler));
+        id
+    }
+
+    /// Remove a subscription. Returns false if it was already removed.
+    #[allow(clippy::must_use_candidate)] // Callers usually don't care
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut registry = self.registry.lock().expect("event bus lock");
+        let before = registry.subscribers.len();
+        registry.subscribers.retain(|(sub, _, _)| *sub != id);
+        registry.subscribers.len() != before
+    }
+
+    /// Deliver `event` to the subscribers of `T`.
+    ///
+    /// Handlers run after the bus is unlocked, so they may subscribe,
+    /// unsubscribe, or publish; such changes apply from the next publish.
+    pub fn publish<T: Any>(&self, event: &T) {
+        let handlers: Vec<Handler> = {
+            let registry = self.registry.lock().expect("event bus lock");
+            registry
+                .subscribers
+                .iter()
+                .filter(|(_, ty, _)| *ty == TypeId::of::<T>())
+                .map(|(_, _, handler)| Arc::clone(handler))
+                .collect()
+        };
+        for handler in handlers {
+            handler(event);
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let subscribers = self
+            .registry
+            .lock()
+            .map_or(0, |registry| registry.subscribers.len());
+        f.debug_struct("EventBus")
+            .field("subscribers", &subscribers)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribers_receive_only_their_type_in_order() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&seen);
+        bus.subscribe(move |n: &u32| log.lock().unwrap().push(format!("a{n}")));
+        let log = Arc::clone(&seen);
+        bus.subscribe(move |s: &&str| log.lock().unwrap().push(format!("s{s}")));
+        let log = Arc::clone(&seen);
+        let b = bus.subscribe(move |n: &u32| log.lock().unwrap().push(format!("b{n}")));
+
+        bus.publish(&1u32);
+        bus.publish(&"x");
+        assert!(bus.unsubscribe(b));
+        assert!(!bus.unsubscribe(b));
+        bus.clone().publish(&2u32);
+
+        assert_eq!(*seen.lock().unwrap(), ["a1", "b1", "sx", "a2"]);
+    }
+
+    #[test]
+    fn test_handlers_may_use_the_bus() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let inner = bus.clone();
+        let log = Arc::clone(&seen);
+        bus.subscribe(move |n: &u32| {
+            log.lock().unwrap().push(*n);
+            if *n == 1 {
+                inner.publish(&2u32);
+            }
+        });
+        bus.publish(&1u32);
+        assert_eq!(*seen.lock().unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_legacy_callback_bridge() {
+        let events = super::super::capture_events(|| {
+            let bus = EventBus::new();
+            bus.publish(&RendererEvent::Resized {
+                width: 80,
+                height: 24,
+            });
+            bus.publish(&TerminalEvent::RawModeChanged(true));
+        });
+        assert_eq!(
+            events,
+            [
+                (
+                    "renderer.resized".to_string(),
+                    "{\"width\":80,\"height\":24}".to_string()
+                ),
+                (
+                    "terminal.raw_mode_changed".to_string(),
+                    "{\"enabled\":true}".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,741 @@
+//! C ABI for driving a [`Renderer`] from other languages (`ffi` feature).
+//!
+//! Renderers are addressed by integer handles rather than pointers, so a
+//! stale or made-up handle is reported as [`OTUI_ERR_INVALID_HANDLE`]
+//! instead of corrupting memory. A renderer is not `Send`, so a handle only
+//! works on the thread that created it.
+//!
+//! Every function returns an `OTUI_*` status: [`OTUI_OK`] or another
+//! non-negative value on success, a negative error code otherwise. Panics
+//! are caught at the boundary and reported as [`OTUI_ERR_PANIC`]; this
+//! needs `panic = "unwind"`, so do not build the library with this crate's
+//! release profile, which aborts.
+//!
+//! The C header `include/opentui.h` is generated from this module:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! cbindgen --config cbindgen.toml --output include/opentui.h
+//! ```
+
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::color::Rgba;
+use crate::error::Error;
+use crate::renderer::{Renderer, RendererOptions};
+use crate::style::{Style, TextAttributes};
+
+/// Identifies a renderer created by `otui_renderer_create*`. Never 0.
+pub type OtuiHandle = u64;
+
+/// Output callback: write `len` bytes from `data`, returning the number of
+/// bytes written or a negative value on failure.
+pub type OtuiWriteFn =
+    Option<unsafe extern "C" fn(userdata: *mut c_void, data: *const u8, len: usize) -> isize>;
+
+/// Success.
+pub const OTUI_OK: i32 = 0;
+/// `otui_hit_test` found no hit area at the point.
+pub const OTUI_NO_HIT: i32 = 1;
+/// The handle does not name a live renderer on this thread.
+pub const OTUI_ERR_INVALID_HANDLE: i32 = -1;
+/// A required pointer was null.
+pub const OTUI_ERR_NULL_POINTER: i32 = -2;
+/// Text was not valid UTF-8.
+pub const OTUI_ERR_INVALID_UTF8: i32 = -3;
+/// An argument was out of range, such as a zero dimension.
+pub const OTUI_ERR_INVALID_ARGUMENT: i32 = -4;
+/// Writing to the output failed.
+pub const OTUI_ERR_IO: i32 = -5;
+/// The output is gone and the renderer has stopped writing.
+pub const OTUI_ERR_OUTPUT_CLOSED: i32 = -6;
+/// The call panicked; the renderer may be left mid-frame.
+pub const OTUI_ERR_PANIC: i32 = -7;
+/// The call re-entered the renderer, e.g. from an output callback.
+pub const OTUI_ERR_BUSY: i32 = -8;
+
+/// Switch to the alternate screen on create.
+pub const OTUI_INIT_ALT_SCREEN: u32 = 1 << 0;
+/// Hide the cursor on create.
+pub const OTUI_INIT_HIDE_CURSOR: u32 = 1 << 1;
+/// Enable mouse tracking on create.
+pub const OTUI_INIT_MOUSE: u32 = 1 << 2;
+/// Query terminal capabilities on create.
+pub const OTUI_INIT_QUERY_CAPABILITIES: u32 = 1 << 3;
+
+/// [`OtuiStyle::fg`] is set; otherwise the terminal default is used.
+pub const OTUI_STYLE_FG: u32 = 1 << 0;
+/// [`OtuiStyle::bg`] is set; otherwise the terminal default is used.
+pub const OTUI_STYLE_BG: u32 = 1 << 1;
+
+/// Plain-data text style.
+///
+/// Colors are packed `0xRRGGBBAA`. `attributes` holds
+/// [`TextAttributes`] flag bits (bold = 1, dim = 2, italic = 4, ...);
+/// higher bits are ignored. `flags` says which colors are set.
+/// `extra_attributes` holds the flag bits above 31, shifted down by 32
+/// (overline = 1, superscript = 2, subscript = 4).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OtuiStyle {
+    pub fg: u32,
+    pub bg: u32,
+    pub attributes: u32,
+    pub flags: u32,
+    pub extra_attributes: u32,
+}
+
+impl From<OtuiStyle> for Style {
+    fn from(style: OtuiStyle) -> Self {
+        Self {
+            fg: (style.flags & OTUI_STYLE_FG != 0).then(|| unpack(style.fg)),
+            bg: (style.flags & OTUI_STYLE_BG != 0).then(|| unpack(style.bg)),
+            attributes: TextAttributes::from_bits_truncate(
+                (u64::from(style.extra_attributes) << 32 | u64::from(style.attributes))
+                    & TextAttributes::FLAGS_MASK,
+            ),
+            ..Self::NONE
+        }
+    }
+}
+
+fn unpack(color: u32) -> Rgba {
+    let [r, g, b, a] = color.to_be_bytes();
+    Rgba::from_rgba_u8(r, g, b, a)
+}
+
+/// Status code for a crate error.
+fn error_code(err: &Error) -> i32 {
+    match err {
+        Error::OutputClosed => OTUI_ERR_OUTPUT_CLOSED,
+        Error::Io(_) | Error::Terminal { .. } | Error::Render { .. } => OTUI_ERR_IO,
+        Error::InvalidColor(_)
+        | Error::InvalidDimensions { .. }
+        | Error::OutOfBounds { .. }
+        | Error::InvalidInput(_)
+        | Error::SizeMismatch { .. }
+        | Error::DimensionOverflow { .. }
+        | Error::BufferSizeMismatch { .. }
+        | Error::ThemeParse { .. }
+        | Error::StyleParse { .. }
+        | Error::InvalidFrame { .. } => OTUI_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/// An error status on its way out of a call.
+struct Code(i32);
+
+impl From<Error> for Code {
+    fn from(err: Error) -> Self {
+        Self(error_code(&err))
+    }
+}
+
+type Status = Result<i32, Code>;
+
+thread_local! {
+    static RENDERERS: RefCell<HashMap<OtuiHandle, Renderer>> = RefCell::new(HashMap::new());
+}
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Run `body`, turning errors and panics into status codes.
+fn guard(body: impl FnOnce() -> Status) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(body)).map_or(OTUI_ERR_PANIC, |status| {
+        status.unwrap_or_else(|Code(code)| code)
+    })
+}
+
+fn with_renderer<T>(
+    handle: OtuiHandle,
+    body: impl FnOnce(&mut Renderer) -> Result<T, Code>,
+) -> Result<T, Code> {
+    RENDERERS.with(|renderers| {
+        let mut renderers = renderers
+            .try_borrow_mut()
+            .map_err(|_| Code(OTUI_ERR_BUSY))?;
+        let renderer = renderers
+            .get_mut(&handle)
+            .ok_or(Code(OTUI_ERR_INVALID_HANDLE))?;
+        body(renderer)
+    })
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), Code> {
+    if width == 0 || height == 0 {
+        Err(Code(OTUI_ERR_INVALID_ARGUMENT))
+    } else {
+        Ok(())
+    }
+}
+
+fn options(flags: u32) -> RendererOptions {
+    RendererOptions {
+        use_alt_screen: flags & OTUI_INIT_ALT_SCREEN != 0,
+        hide_cursor: flags & OTUI_INIT_HIDE_CURSOR != 0,
+        enable_mouse: flags & OTUI_INIT_MOUSE != 0,
+        query_capabilities: flags & OTUI_INIT_QUERY_CAPABILITIES != 0,
+        capabilities: None,
+    }
+}
+
+/// Register a new renderer and write its handle to `out_handle`.
+///
+/// # Safety
+///
+/// `out_handle` must be null or valid for a write.
+unsafe fn register(
+    width: u32,
+    height: u32,
+    flags: u32,
+    output: Box<dyn Write>,
+    out_handle: *mut OtuiHandle,
+) -> Status {
+    if out_handle.is_null() {
+        return Err(Code(OTUI_ERR_NULL_POINTER));
+    }
+    check_size(width, height)?;
+    let renderer = Renderer::with_output(width, height, options(flags), output)?;
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    RENDERERS.with(|renderers| {
+        renderers
+            .try_borrow_mut()
+            .map_err(|_| Code(OTUI_ERR_BUSY))?
+            .insert(handle, renderer);
+        Ok::<_, Code>(())
+    })?;
+    // SAFETY: checked non-null above; the caller guarantees it is writable.
+    unsafe { out_handle.write(handle) };
+    Ok(OTUI_OK)
+}
+
+/// Create a renderer that writes to stdout.
+///
+/// `flags` combines `OTUI_INIT_*` values. On success the new handle is
+/// written to `out_handle`.
+///
+/// # Safety
+///
+/// `out_handle` must be null or valid for a write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn otui_renderer_create(
+    width: u32,
+    height: u32,
+    flags: u32,
+    out_handle: *mut OtuiHandle,
+) -> i32 {
+    // SAFETY: forwarded from the caller.
+    guard(|| unsafe { register(width, height, flags, Box::new(io::stdout()), out_handle) })
+}
+
+/// Create a renderer that sends its output to `write`.
+///
+/// `userdata` is passed back to every `write` call, including the cleanup
+/// output written by `otui_renderer_destroy`.
+///
+/// # Safety
+///
+/// `out_handle` must be null or valid for a write. `write` must be safe to
+/// call with `userdata` until the renderer is destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn otui_renderer_create_with_writer(
+    width: u32,
+    height: u32,
+    flags: u32,
+    write: OtuiWriteFn,
+    userdata: *mut c_void,
+    out_handle: *mut OtuiHandle,
+) -> i32 {
+    guard(|| {
+        let write = write.ok_or(Code(OTUI_ERR_NULL_POINTER))?;
+        let output = Box::new(CallbackWriter { write, userdata });
+        // SAFETY: forwarded from the caller.
+        unsafe { register(width, height, flags, output, out_handle) }
+    })
+}
+
+/// Destroy a renderer, restoring the terminal state it changed.
+#[unsafe(no_mangle)]
+pub extern "C" fn otui_renderer_destroy(handle: OtuiHandle) -> i32 {
+    guard(|| {
+        let renderer = RENDERERS.with(|renderers| {
+            renderers
+                .try_borrow_mut()
+                .map_err(|_| Code(OTUI_ERR_BUSY))?
+                .remove(&handle)
+                .ok_or(Code(OTUI_ERR_INVALID_HANDLE))
+        })?;
+        // Dropped outside the registry borrow: cleanup output may call back.
+        drop(renderer);
+        Ok(OTUI_OK)
+    })
+}
+
+/// Write the back buffer's width and height.
+///
+/// # Safety
+///
+/// `out_width` and `out_height` must each be null or valid for a write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn otui_buffer_size(
+    handle: OtuiHandle,
+    out_width: *mut u32,
+    out_height: *mut u32,
+) -> i32 {
+    guard(|| {
+        if out_width.is_null() || out_height.is_null() {
+            return Err(Code(OTUI_ERR_NULL_POINTER));
+        }
+        let (width, height) = with_renderer(handle, |renderer| Ok(renderer.size()))?;
+        // SAFETY: checked non-null above; the caller guarantees writability.
+        unsafe {
+            out_width.write(width);
+            out_height.write(height);
+        }
+        Ok(OTUI_OK)
+    })
+}
+
+/// Draw `len` bytes of UTF-8 text at a cell position.
+///
+/// Text past the buffer edge is clipped. `text` may be null when `len`
+/// is 0.
+///
+/// # Safety
+///
+/// `text` must be null or valid for reads of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn otui_draw_text(
+    handle: OtuiHandle,
+    x: u32,
+    y: u32,
+    text: *const u8,
+    len: usize,
+    style: OtuiStyle,
+) -> i32 {
+    guard(|| {
+        let bytes: &[u8] = if len == 0 {
+            &[]
+        } else if text.is_null() {
+            return Err(Code(OTUI_ERR_NULL_POINTER));
+        } else if isize::try_from(len).is_err() {
+            return Err(Code(OTUI_ERR_INVALID_ARGUMENT));
+        } else {
+            // SAFETY: non-null, at most isize::MAX long, and readable for
+            // `len` bytes per the caller's contract.
+            unsafe { std::slice::from_raw_parts(text, len) }
+        };
+        let text = std::str::from_utf8(bytes).map_err(|_| Code(OTUI_ERR_INVALID_UTF8))?;
+        with_renderer(handle, |renderer| {
+            let (buffer, pool) = renderer.buffer_with_pool();
+            buffer.draw_text_with_pool(pool, x, y, text, Style::from(style));
+            Ok(OTUI_OK)
+        })
+    })
+}
+
+/// Fill a rectangle of the back buffer with a `0xRRGGBBAA` background.
+#[unsafe(no_mangle)]
+pub extern "C" fn otui_fill_rect(
+    handle: OtuiHandle,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    bg: u32,
+) -> i32 {
+    guard(|| {
+        with_renderer(handle, |renderer| {
+            renderer.buffer().fill_rect(x, y, width, height, unpack(bg));
+            Ok(OTUI_OK)
+        })
+    })
+}
+
+/// Write the changes since the last frame to the output.
+#[unsafe(no_mangle)]
+pub extern "C" fn otui_present(handle: OtuiHandle) -> i32 {
+    guard(|| {
+        with_renderer(handle, |renderer| {
+            renderer.present()?;
+            Ok(OTUI_OK)
+        })
+    })
+}
+
+/// Resize the renderer's buffers. Both dimensions must be non-zero.
+#[unsafe(no_mangle)]
+pub extern "C" fn otui_resize(handle: OtuiHandle, width: u32, height: u32) -> i32 {
+    guard(|| {
+        check_size(width, height)?;
+        with_renderer(handle, |renderer| {
+            renderer.resize(width, height)?;
+            Ok(OTUI_OK)
+        })
+    })
+}
+
+/// Register a hit area with `id` for the next presented frame.
+#[unsafe(no_mangle)]
+pub extern "C" fn otui_register_hit(
+    handle: OtuiHandle,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    id: u32,
+) -> i32 {
+    guard(|| {
+        with_renderer(handle, |renderer| {
+            renderer.register_hit_area(x, y, width, height, id);
+            Ok(OTUI_OK)
+        })
+    })
+}
+
+/// Find the hit area at a cell in the last presented frame.
+///
+/// Writes its id to `out_id` and returns `OTUI_OK`, or returns
+/// `OTUI_NO_HIT` and leaves `out_id` alone.
+///
+/// # Safety
+///
+/// `out_id` must be null or valid for a write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn otui_hit_test(
+    handle: OtuiHandle,
+    x: u32,
+    y: u32,
+    out_id: *mut u32,
+) -> i32 {
+    guard(|| {
+        if out_id.is_null() {
+            return Err(Code(OTUI_ERR_NULL_POINTER));
+        }
+        let hit = with_renderer(handle, |renderer| Ok(renderer.hit_test(x, y)))?;
+        let Some(id) = hit else {
+            return Ok(OTUI_NO_HIT);
+        };
+        // SAFETY: checked non-null above; the caller guarantees writability.
+        unsafe { out_id.write(id) };
+        Ok(OTUI_OK)
+    })
+}
+
+/// [`Write`] over a C output callback.
+struct CallbackWriter {
+    write: unsafe extern "C" fn(*mut c_void, *const u8, usize) -> isize,
+    userdata: *mut c_void,
+}
+
+impl Write for CallbackWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // SAFETY: the creator promised `write` accepts `userdata` for the
+        // renderer's lifetime; `buf` is valid for `buf.len()` bytes.
+        let written = unsafe { (self.write)(self.userdata, buf.as_ptr(), buf.len()) };
+        usize::try_from(written)
+            .ok()
+            .filter(|&n| n <= buf.len())
+            .ok_or_else(|| io::Error::other("output callback failed"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    unsafe extern "C" fn capture(userdata: *mut c_void, data: *const u8, len: usize) -> isize {
+        // SAFETY: tests pass a live `Vec<u8>` as userdata.
+        let out = unsafe { &mut *userdata.cast::<Vec<u8>>() };
+        // SAFETY: the renderer passes a valid buffer.
+        out.extend_from_slice(unsafe { std::slice::from_raw_parts(data, len) });
+        len as isize
+    }
+
+    unsafe extern "C" fn failing(_: *mut c_void, _: *const u8, _: usize) -> isize {
+        -1
+    }
+
+    fn create(width: u32, height: u32, out: &mut Vec<u8>) -> OtuiHandle {
+        let mut handle = 0;
+        let userdata = ptr::from_mut(out).cast::<c_void>();
+        // SAFETY: `out` outlives the renderer in every test.
+        let status = unsafe {
+            otui_renderer_create_with_writer(
+                width,
+                height,
+                0,
+                Some(capture),
+                userdata,
+                &raw mut handle,
+            )
+        };
+        assert_eq!(status, OTUI_OK);
+        assert_ne!(handle, 0);
+        handle
+    }
+
+    fn draw(handle: OtuiHandle, x: u32, y: u32, text: &[u8], style: OtuiStyle) -> i32 {
+        // SAFETY: `text` is a live slice.
+        unsafe { otui_draw_text(handle, x, y, text.as_ptr(), text.len(), style) }
+    }
+
+    fn hit(handle: OtuiHandle, x: u32, y: u32) -> (i32, u32) {
+        let mut id = 0;
+        // SAFETY: `id` is a live local.
+        let status = unsafe { otui_hit_test(handle, x, y, &raw mut id) };
+        (status, id)
+    }
+
+    #[test]
+    fn draw_and_present_reach_the_writer() {
+        let mut out = Vec::new();
+        let handle = create(20, 4, &mut out);
+        let style = OtuiStyle {
+            fg: 0xff00_00ff,
+            attributes: 1, // bold
+            flags: OTUI_STYLE_FG,
+            ..OtuiStyle::default()
+        };
+        assert_eq!(draw(handle, 1, 1, "héllo 👍".as_bytes(), style), OTUI_OK);
+        assert_eq!(otui_fill_rect(handle, 0, 3, 20, 1, 0x0000_ffff), OTUI_OK);
+        assert_eq!(otui_present(handle), OTUI_OK);
+        assert_eq!(otui_renderer_destroy(handle), OTUI_OK);
+
+        let text = String::from_utf8_lossy(&out);
+        assert!(text.contains("héllo 👍"), "{text:?}");
+        assert!(text.contains("\x1b[38;2;255;0;0m"), "{text:?}");
+        assert!(text.contains("48;2;0;0;255"), "{text:?}");
+    }
+
+    #[test]
+    fn extra_attributes_reach_the_high_flag_bits() {
+        let style = Style::from(OtuiStyle {
+            attributes: 0xFFFF_FF00 | 4, // a stray link ID, italic
+            extra_attributes: 1 | 4 | 8, // overline, subscript, an unknown bit
+            ..OtuiStyle::default()
+        });
+        assert_eq!(
+            style.attributes,
+            TextAttributes::ITALIC | TextAttributes::OVERLINE | TextAttributes::SUBSCRIPT
+        );
+    }
+
+    #[test]
+    fn size_and_resize() {
+        let mut out = Vec::new();
+        let handle = create(20, 4, &mut out);
+        let (mut width, mut height) = (0, 0);
+        // SAFETY: live locals.
+        assert_eq!(
+            unsafe { otui_buffer_size(handle, &raw mut width, &raw mut height) },
+            OTUI_OK
+        );
+        assert_eq!((width, height), (20, 4));
+
+        assert_eq!(otui_resize(handle, 30, 6), OTUI_OK);
+        // SAFETY: live locals.
+        unsafe { otui_buffer_size(handle, &raw mut width, &raw mut height) };
+        assert_eq!((width, height), (30, 6));
+
+        assert_eq!(otui_resize(handle, 0, 6), OTUI_ERR_INVALID_ARGUMENT);
+        otui_renderer_destroy(handle);
+    }
+
+    #[test]
+    fn hit_areas_apply_after_present() {
+        let mut out = Vec::new();
+        let handle = create(10, 5, &mut out);
+        assert_eq!(otui_register_hit(handle, 2, 1, 3, 2, 7), OTUI_OK);
+        assert_eq!(hit(handle, 3, 2).0, OTUI_NO_HIT);
+
+        otui_present(handle);
+        assert_eq!(hit(handle, 3, 2), (OTUI_OK, 7));
+        assert_eq!(hit(handle, 0, 0).0, OTUI_NO_HIT);
+        otui_renderer_destroy(handle);
+    }
+
+    #[test]
+    fn bad_handles_are_rejected() {
+        let mut out = Vec::new();
+        let handle = create(10, 2, &mut out);
+        assert_eq!(otui_renderer_destroy(handle), OTUI_OK);
+
+        for bad in [0, handle, u64::MAX] {
+            assert_eq!(otui_present(bad), OTUI_ERR_INVALID_HANDLE);
+            assert_eq!(otui_renderer_destroy(bad), OTUI_ERR_INVALID_HANDLE);
+            assert_eq!(
+                draw(bad, 0, 0, b"x", OtuiStyle::default()),
+                OTUI_ERR_INVALID_HANDLE
+            );
+            assert_eq!(otui_fill_rect(bad, 0, 0, 1, 1, 0), OTUI_ERR_INVALID_HANDLE);
+            assert_eq!(otui_resize(bad, 5, 5), OTUI_ERR_INVALID_HANDLE);
+            assert_eq!(
+                otui_register_hit(bad, 0, 0, 1, 1, 1),
+                OTUI_ERR_INVALID_HANDLE
+            );
+            assert_eq!(hit(bad, 0, 0).0, OTUI_ERR_INVALID_HANDLE);
+        }
+    }
+
+    #[test]
+    fn handles_do_not_cross_threads() {
+        let mut out = Vec::new();
+        let handle = create(10, 2, &mut out);
+        let status = std::thread::spawn(move || otui_present(handle))
+            .join()
+            .unwrap();
+        assert_eq!(status, OTUI_ERR_INVALID_HANDLE);
+        otui_renderer_destroy(handle);
+    }
+
+    #[test]
+    fn pointers_and_arguments_are_validated() {
+        let mut handle = 0;
+        // SAFETY: null and live pointers only.
+        unsafe {
+            assert_eq!(
+                otui_renderer_create(10, 2, 0, ptr::null_mut()),
+                OTUI_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                otui_renderer_create_with_writer(10, 2, 0, None, ptr::null_mut(), &raw mut handle),
+                OTUI_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                otui_renderer_create_with_writer(
+                    0,
+                    2,
+                    0,
+                    Some(failing),
+                    ptr::null_mut(),
+                    &raw mut handle
+                ),
+                OTUI_ERR_INVALID_ARGUMENT
+            );
+        }
+        assert_eq!(handle, 0);
+
+        let mut out = Vec::new();
+        let handle = create(10, 2, &mut out);
+        // SAFETY: null pointers only, which must be rejected before use.
+        unsafe {
+            let style = OtuiStyle::default();
+            assert_eq!(
+                otui_draw_text(handle, 0, 0, ptr::null(), 3, style),
+                OTUI_ERR_NULL_POINTER
+            );
+            assert_eq!(otui_draw_text(handle, 0, 0, ptr::null(), 0, style), OTUI_OK);
+            assert_eq!(
+                otui_buffer_size(handle, ptr::null_mut(), ptr::null_mut()),
+                OTUI_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                otui_hit_test(handle, 0, 0, ptr::null_mut()),
+                OTUI_ERR_NULL_POINTER
+            );
+        }
+        assert_eq!(
+            draw(handle, 0, 0, b"\xff\xfe", OtuiStyle::default()),
+            OTUI_ERR_INVALID_UTF8
+        );
+        otui_renderer_destroy(handle);
+    }
+
+    #[test]
+    fn output_failures_map_to_io_errors() {
+        let mut handle = 0;
+        // SAFETY: `failing` ignores its userdata.
+        let status = unsafe {
+            otui_renderer_create_with_writer(
+                10,
+                2,
+                0,
+                Some(failing),
+                ptr::null_mut(),
+                &raw mut handle,
+            )
+        };
+        assert_eq!(status, OTUI_OK);
+        draw(handle, 0, 0, b"x", OtuiStyle::default());
+        assert_eq!(otui_present(handle), OTUI_ERR_IO);
+        otui_renderer_destroy(handle);
+    }
+
+    #[test]
+    fn panics_become_status_codes() {
+        assert_eq!(guard(|| panic!("boom")), OTUI_ERR_PANIC);
+        assert_eq!(guard(|| Err(Code(OTUI_ERR_IO))), OTUI_ERR_IO);
+        assert_eq!(error_code(&Error::OutputClosed), OTUI_ERR_OUTPUT_CLOSED);
+    }
+
+    #[test]
+    fn header_matches_exports() {
+        let header = include_str!("../include/opentui.h");
+        let define = |name: &str| {
+            let line = header
+                .lines()
+                .find(|line| line.starts_with(&format!("#define {name} ")))
+                .unwrap_or_else(|| panic!("{name} missing from header"));
+            line[name.len() + 9..].trim().to_string()
+        };
+        for (name, value) in [
+            ("OTUI_OK", OTUI_OK),
+            ("OTUI_NO_HIT", OTUI_NO_HIT),
+            ("OTUI_ERR_INVALID_HANDLE", OTUI_ERR_INVALID_HANDLE),
+            ("OTUI_ERR_NULL_POINTER", OTUI_ERR_NULL_POINTER),
+            ("OTUI_ERR_INVALID_UTF8", OTUI_ERR_INVALID_UTF8),
+            ("OTUI_ERR_INVALID_ARGUMENT", OTUI_ERR_INVALID_ARGUMENT),
+            ("OTUI_ERR_IO", OTUI_ERR_IO),
+            ("OTUI_ERR_OUTPUT_CLOSED", OTUI_ERR_OUTPUT_CLOSED),
+            ("OTUI_ERR_PANIC", OTUI_ERR_PANIC),
+            ("OTUI_ERR_BUSY", OTUI_ERR_BUSY),
+        ] {
+            assert_eq!(define(name), value.to_string(), "{name}");
+        }
+        for (name, shift) in [
+            ("OTUI_INIT_ALT_SCREEN", OTUI_INIT_ALT_SCREEN),
+            ("OTUI_INIT_HIDE_CURSOR", OTUI_INIT_HIDE_CURSOR),
+            ("OTUI_INIT_MOUSE", OTUI_INIT_MOUSE),
+            ("OTUI_INIT_QUERY_CAPABILITIES", OTUI_INIT_QUERY_CAPABILITIES),
+            ("OTUI_STYLE_FG", OTUI_STYLE_FG),
+            ("OTUI_STYLE_BG", OTUI_STYLE_BG),
+        ] {
+            let expected = format!("(1 << {})", shift.trailing_zeros());
+            assert_eq!(define(name), expected, "{name}");
+        }
+        for signature in [
+            "int32_t otui_renderer_create(uint32_t width, uint32_t height, uint32_t flags, OtuiHandle *out_handle);",
+            "int32_t otui_renderer_destroy(OtuiHandle handle);",
+            "int32_t otui_buffer_size(OtuiHandle handle, uint32_t *out_width, uint32_t *out_height);",
+            "int32_t otui_fill_rect(OtuiHandle handle,",
+            "int32_t otui_present(OtuiHandle handle);",
+            "int32_t otui_resize(OtuiHandle handle, uint32_t width, uint32_t height);",
+            "int32_t otui_register_hit(OtuiHandle handle,",
+            "int32_t otui_hit_test(OtuiHandle handle, uint32_t x, uint32_t y, uint32_t *out_id);",
+            "int32_t otui_renderer_create_with_writer(uint32_t width,",
+            "int32_t otui_draw_text(OtuiHandle handle,",
+            "typedef uint64_t OtuiHandle;",
+            "typedef intptr_t (*OtuiWriteFn)(void *userdata, const uint8_t *data, uintptr_t len);",
+        ] {
+            assert!(
+                header.contains(signature),
+                "header is missing `{signature}`"
+            );
+        }
+        assert!(header.contains("  uint32_t extra_attributes;\n} OtuiStyle;"));
+        assert_eq!(size_of::<OtuiStyle>(), 20);
+    }
+}
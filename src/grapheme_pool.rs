@@ -11,7 +11,8 @@
 //! - 24-bit ID allows ~16M unique graphemes
 //! - Reference counting for memory reuse
 //! - Free-list for O(1) slot reuse
-//! - HashMap index for O(1) intern() lookup (avoiding O(n) linear scan)
+//! - HashMap index deduplicating alloc() and intern() in O(1): each distinct
+//!   grapheme occupies one slot, and repeats share it by refcount
 //!
 //! # Usage
 //!
@@ -38,9 +39,32 @@
 //! - Refcount starts at 1 on alloc
 //! - decref returns `true` if references remain, `false` if freed
 //! - get returns `None` for freed or invalid IDs
+//!
+//! # Multiple Pools
+//!
+//! A [`GraphemeId`] is only meaningful in the pool that issued it. Each pool
+//! gets a unique nonzero [`id`](GraphemePool::id) at construction, which
+//! buffers record (see [`OptimizedBuffer::grapheme_pool_id`]) so a blit can
+//! tell foreign cells apart and [`translate`](GraphemePool::translate) them.
+//!
+//! [`OptimizedBuffer::grapheme_pool_id`]: crate::buffer::OptimizedBuffer::grapheme_pool_id
 
 use crate::cell::GraphemeId;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+#[cfg(feature = "pool-audit")]
+mod audit;
+
+#[cfg(feature = "pool-audit")]
+pub use audit::{AllocSite, AuditAction, AuditIssue};
+
+/// Source of [`GraphemePool::id`] values; 0 is never handed out.
+static NEXT_POOL_ID: AtomicU32 = AtomicU32::new(1);
+
+fn next_pool_id() -> u32 {
+    NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed)
+}
 
 /// Maximum pool ID (24-bit limit).
 pub const MAX_POOL_ID: u32 = 0x00FF_FFFF;
@@ -117,6 +141,12 @@ pub struct PoolStats {
     pub total_allocations: u64,
     /// Total number of frees over pool lifetime.
     pub total_frees: u64,
+    /// Sum of the refcounts of all active slots.
+    pub live_refs: u64,
+    /// UTF-8 bytes held by active slots.
+    pub bytes: usize,
+    /// Allocations answered by an existing slot over pool lifetime.
+    pub dedup_hits: u64,
 }
 
 impl PoolStats {
@@ -127,6 +157,18 @@ impl PoolStats {
     }
 }
 
+/// An entry still referenced, as listed by [`GraphemePool::leak_report`].
+#[cfg(debug_assertions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeakedGrapheme {
+    /// Slot ID of the entry.
+    pub pool_id: u32,
+    /// References still held.
+    pub refcount: u32,
+    /// The grapheme cluster.
+    pub grapheme: String,
+}
+
 /// Internal slot in the grapheme pool.
 #[derive(Clone, Debug)]
 struct Slot {
@@ -165,12 +207,16 @@ impl Slot {
 /// synchronization primitives (e.g., `Mutex` or `RwLock`).
 #[derive(Clone, Debug)]
 pub struct GraphemePool {
+    /// Unique nonzero pool identity. Clones keep it, as their IDs resolve
+    /// to the same graphemes.
+    id: u32,
     /// Storage for grapheme slots. Index 0 is reserved (invalid).
     slots: Vec<Slot>,
     /// Stack of free slot indices for reuse.
     free_list: Vec<u32>,
     /// O(1) lookup index: grapheme string → slot ID.
-    /// Kept in sync with slots: entries are added on alloc/intern, removed on decref to 0.
+    /// Holds exactly the active slots: entries are added when a slot is
+    /// allocated and removed on decref to 0.
     index: HashMap<String, u32>,
     /// Configurable soft limit for pool size (advisory, not enforced by alloc).
     soft_limit: usize,
@@ -180,9 +226,14 @@ pub struct GraphemePool {
     total_allocations: u64,
     /// Total number of frees over pool lifetime.
     total_frees: u64,
+    /// Allocations answered by an existing slot over pool lifetime.
+    dedup_hits: u64,
     /// Configurable fragmentation ratio threshold for should_compact().
     /// Default is COMPACTION_FRAGMENTATION_THRESHOLD (0.5).
     compact_threshold: f32,
+    /// Allocation sites and misuse reporting.
+    #[cfg(feature = "pool-audit")]
+    audit: audit::PoolAudit,
 }
 
 impl Default for GraphemePool {
@@ -198,6 +249,7 @@ impl GraphemePool {
     #[must_use]
     pub fn new() -> Self {
         Self {
+            id: next_pool_id(),
             // Reserve slot 0 as invalid placeholder
             slots: vec![Slot {
                 bytes: String::new(),
@@ -210,7 +262,10 @@ impl GraphemePool {
             peak_usage: 0,
             total_allocations: 0,
             total_frees: 0,
+            dedup_hits: 0,
             compact_threshold: COMPACTION_FRAGMENTATION_THRESHOLD,
+            #[cfg(feature = "pool-audit")]
+            audit: audit::PoolAudit::default(),
         }
     }
 
@@ -229,6 +284,7 @@ impl GraphemePool {
             width: 0,
         });
         Self {
+            id: next_pool_id(),
             slots,
             free_list: Vec::new(),
             index: HashMap::with_capacity(capacity),
@@ -236,7 +292,10 @@ impl GraphemePool {
             peak_usage: 0,
             total_allocations: 0,
             total_frees: 0,
+            dedup_hits: 0,
             compact_threshold: COMPACTION_FRAGMENTATION_THRESHOLD,
+            #[cfg(feature = "pool-audit")]
+            audit: audit::PoolAudit::default(),
         }
     }
 
@@ -252,6 +311,7 @@ impl GraphemePool {
     #[must_use]
     pub fn with_soft_limit(soft_limit: usize) -> Self {
         Self {
+            id: next_pool_id(),
             slots: vec![Slot {
                 bytes: String::new(),
                 refcount: 0,
@@ -263,10 +323,22 @@ impl GraphemePool {
             peak_usage: 0,
             total_allocations: 0,
             total_frees: 0,
+            dedup_hits: 0,
             compact_threshold: COMPACTION_FRAGMENTATION_THRESHOLD,
+            #[cfg(feature = "pool-audit")]
+            audit: audit::PoolAudit::default(),
         }
     }
 
+    /// Unique nonzero identity of this pool, assigned at construction.
+    ///
+    /// Not to be confused with [`GraphemeId::pool_id`], the slot within a
+    /// pool.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
     /// Set the soft limit for this pool.
     ///
     /// Returns `&mut self` for builder-style chaining.
@@ -281,10 +353,12 @@ impl GraphemePool {
         self.soft_limit
     }
 
-    /// Allocate a new grapheme in the pool.
+    /// Allocate a grapheme in the pool, or take another reference to it.
     ///
     /// Returns a [`GraphemeId`] with the pool slot ID and cached display width.
-    /// The initial reference count is 1.
+    /// If the grapheme is already stored, its refcount is incremented and the
+    /// existing ID is returned; otherwise a new slot starts with refcount 1.
+    /// Either way the caller owns one reference.
     ///
     /// # Arguments
     ///
@@ -293,13 +367,28 @@ impl GraphemePool {
     /// # Panics
     ///
     /// Panics if the pool exceeds 16M entries (24-bit ID limit).
-    ///
-    /// # Note
-    ///
-    /// This method does NOT deduplicate. If you want to reuse existing graphemes,
-    /// use [`intern()`](Self::intern) instead.
     #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn alloc(&mut self, grapheme: &str) -> GraphemeId {
+        if let Some(id) = self.reuse(grapheme) {
+            return id;
+        }
+        self.alloc_slot(grapheme)
+    }
+
+    /// Take another reference to `grapheme` if it is already stored.
+    fn reuse(&mut self, grapheme: &str) -> Option<GraphemeId> {
+        let pool_id = *self.index.get(grapheme)?;
+        let slot = &mut self.slots[pool_id as usize];
+        debug_assert!(!slot.is_free(), "index points at a freed slot");
+        slot.refcount = slot.refcount.saturating_add(1);
+        self.dedup_hits = self.dedup_hits.saturating_add(1);
+        Some(GraphemeId::new(pool_id, slot.width))
+    }
+
+    /// Store `grapheme` in a fresh slot with refcount 1.
+    #[cfg_attr(feature = "pool-audit", track_caller)]
+    fn alloc_slot(&mut self, grapheme: &str) -> GraphemeId {
         let width = crate::unicode::display_width(grapheme);
         // Saturate width to u8 range, then GraphemeId::new() will saturate to 127
         let width_u8 = width.min(u8::MAX as usize) as u8;
@@ -322,8 +411,9 @@ impl GraphemePool {
             id
         };
 
-        // Add to index for O(1) intern() lookup
         self.index.insert(grapheme_owned, pool_id);
+        #[cfg(feature = "pool-audit")]
+        self.audit.record(pool_id, std::panic::Location::caller());
 
         // Update lifetime statistics
         self.total_allocations = self.total_allocations.saturating_add(1);
@@ -337,32 +427,34 @@ impl GraphemePool {
 
     /// Intern a grapheme, returning an existing ID if already allocated.
     ///
-    /// If the grapheme already exists in the pool (with refcount > 0), increments
-    /// its refcount and returns the existing ID. Otherwise, allocates a new slot.
-    ///
-    /// This is useful for deduplicating repeated graphemes.
-    ///
-    /// # Performance
-    ///
-    /// Uses O(1) HashMap lookup instead of linear scan.
+    /// The same as [`alloc()`](Self::alloc), which deduplicates.
     #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn intern(&mut self, grapheme: &str) -> GraphemeId {
-        // O(1) lookup via HashMap index
-        if let Some(&pool_id) = self.index.get(grapheme) {
-            // Verify slot is still active (not freed)
-            if let Some(slot) = self.slots.get(pool_id as usize) {
-                if !slot.is_free() {
-                    let width = slot.width; // Save before mutable borrow
-                    self.incref_by_pool_id(pool_id);
-                    return GraphemeId::new(pool_id, width);
-                }
+        self.alloc(grapheme)
+    }
+
+    /// Translate an ID issued by `src` into an ID in this pool.
+    ///
+    /// The grapheme is interned here, so the caller owns one reference to
+    /// the returned ID, as with [`alloc()`](Self::alloc); `src` is untouched.
+    /// When `src` is this pool (same [`id`](Self::id)) the ID is kept and
+    /// gains a reference. IDs that `src` cannot resolve become a
+    /// [`GraphemeId::placeholder`] of the same width.
+    #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
+    pub fn translate(&mut self, src: &GraphemePool, id: GraphemeId) -> GraphemeId {
+        if src.id == self.id {
+            if !self.is_valid(id) {
+                return GraphemeId::placeholder(id.width() as u8);
             }
-            // Index entry is stale (slot was freed) - remove it and allocate fresh
-            self.index.remove(grapheme);
+            self.incref(id);
+            return id;
         }
-
-        // Not found in index - allocate new (which also adds to index)
-        self.alloc(grapheme)
+        src.get(id).map_or_else(
+            || GraphemeId::placeholder(id.width() as u8),
+            |grapheme| self.alloc(grapheme),
+        )
     }
 
     /// Increment the reference count for a grapheme ID.
@@ -390,11 +482,13 @@ impl GraphemePool {
     /// # Safety
     ///
     /// If the ID is invalid or already freed, returns `false` without modification.
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn decref(&mut self, id: GraphemeId) -> bool {
         self.decref_by_pool_id(id.pool_id())
     }
 
     /// Decrement refcount by pool ID directly.
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     fn decref_by_pool_id(&mut self, pool_id: u32) -> bool {
         if let Some(slot) = self.slots.get_mut(pool_id as usize) {
             if slot.refcount > 0 {
@@ -405,6 +499,8 @@ impl GraphemePool {
                     // Free the slot
                     slot.bytes.clear();
                     self.free_list.push(pool_id);
+                    #[cfg(feature = "pool-audit")]
+                    self.audit.forget(pool_id);
                     // Update lifetime statistics
                     self.total_frees = self.total_frees.saturating_add(1);
                     return false;
@@ -412,6 +508,13 @@ impl GraphemePool {
                 return true;
             }
         }
+        #[cfg(feature = "pool-audit")]
+        if pool_id != 0 {
+            self.audit.report(&format!(
+                "decref of free grapheme {pool_id} at {}",
+                std::panic::Location::caller()
+            ));
+        }
         false
     }
 
@@ -498,6 +601,8 @@ impl GraphemePool {
         self.slots.truncate(1);
         self.free_list.clear();
         self.index.clear();
+        #[cfg(feature = "pool-audit")]
+        self.audit.clear();
         // Note: We preserve lifetime statistics (peak_usage, total_allocations, total_frees)
         // as they track the pool's entire lifetime, not just current state.
     }
@@ -511,10 +616,10 @@ impl GraphemePool {
         self.peak_usage
     }
 
-    /// Get the total number of allocations over the pool's lifetime.
+    /// Get the total number of slot allocations over the pool's lifetime.
     ///
-    /// This counts every call to [`alloc()`](Self::alloc) and
-    /// [`alloc_batch()`](Self::alloc_batch), not unique graphemes.
+    /// Allocations answered by an existing slot are counted in
+    /// [`PoolStats::dedup_hits`] instead.
     #[must_use]
     pub fn total_allocations(&self) -> u64 {
         self.total_allocations
@@ -543,6 +648,14 @@ impl GraphemePool {
             .checked_div(self.soft_limit)
             .unwrap_or(0);
 
+        let (live_refs, bytes) = self
+            .slots
+            .iter()
+            .skip(1)
+            .fold((0u64, 0usize), |(refs, bytes), slot| {
+                (refs + u64::from(slot.refcount), bytes + slot.bytes.len())
+            });
+
         PoolStats {
             total_slots,
             active_slots,
@@ -552,6 +665,9 @@ impl GraphemePool {
             peak_usage: self.peak_usage,
             total_allocations: self.total_allocations,
             total_frees: self.total_frees,
+            live_refs,
+            bytes,
+            dedup_hits: self.dedup_hits,
         }
     }
 
@@ -818,9 +934,8 @@ impl GraphemePool {
     /// Allocate multiple graphemes at once.
     ///
     /// This is more efficient than calling [`alloc()`](Self::alloc) individually
-    /// as it can pre-size internal structures. Like `alloc()`, this does NOT
-    /// deduplicate - each grapheme gets its own slot even if duplicates exist.
-    /// Use [`intern()`](Self::intern) if you want deduplication.
+    /// as it can pre-size internal structures. Like `alloc()`, duplicates
+    /// share a slot.
     ///
     /// # Arguments
     ///
@@ -849,6 +964,7 @@ impl GraphemePool {
     /// assert_eq!(pool.get(ids[2]), Some("gamma"));
     /// ```
     #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn alloc_batch(&mut self, graphemes: &[&str]) -> Vec<GraphemeId> {
         // Pre-allocate result vector
         let mut result = Vec::with_capacity(graphemes.len());
@@ -914,8 +1030,8 @@ impl GraphemePool {
     /// Try to allocate a grapheme, returning `None` if the pool is at soft limit.
     ///
     /// Unlike [`alloc()`](Self::alloc), this respects the soft limit and returns
-    /// `None` instead of allocating when the pool is full. It still allows
-    /// reuse of freed slots.
+    /// `None` instead of allocating when the pool is full. Graphemes already
+    /// stored always succeed, and freed slots can still be reused.
     ///
     /// # Arguments
     ///
@@ -926,7 +1042,11 @@ impl GraphemePool {
     /// `Some(GraphemeId)` if allocation succeeded, `None` if at soft limit
     /// and no free slots are available for reuse.
     #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn try_alloc(&mut self, grapheme: &str) -> Option<GraphemeId> {
+        if let Some(id) = self.reuse(grapheme) {
+            return Some(id);
+        }
         // Allow allocation if:
         // 1. There are free slots to reuse, OR
         // 2. We're below the soft limit
@@ -935,31 +1055,59 @@ impl GraphemePool {
             return None;
         }
 
-        Some(self.alloc(grapheme))
+        Some(self.alloc_slot(grapheme))
     }
 
     /// Try to intern a grapheme, returning `None` if new allocation would exceed soft limit.
     ///
-    /// If the grapheme already exists, always succeeds (just increments refcount).
-    /// Only returns `None` when a new allocation would be needed and soft limit is reached.
+    /// The same as [`try_alloc()`](Self::try_alloc), which deduplicates.
     #[must_use]
+    #[cfg_attr(feature = "pool-audit", track_caller)]
     pub fn try_intern(&mut self, grapheme: &str) -> Option<GraphemeId> {
-        // O(1) lookup via HashMap index
-        if let Some(&pool_id) = self.index.get(grapheme) {
-            // Verify slot is still active (not freed)
-            if let Some(slot) = self.slots.get(pool_id as usize) {
-                if !slot.is_free() {
-                    let width = slot.width;
-                    self.incref_by_pool_id(pool_id);
-                    return Some(GraphemeId::new(pool_id, width));
-                }
+        self.try_alloc(grapheme)
+    }
+
+    /// Release memory held for freed slots without changing any live ID.
+    ///
+    /// Free slots at the end of the pool are dropped and the remaining
+    /// storage is trimmed to fit. Unlike [`compact()`](Self::compact), no IDs
+    /// move, so nothing needs remapping; free slots between live ones stay.
+    pub fn shrink_to_fit(&mut self) {
+        let mut len = self.slots.len();
+        while len > 1 && self.slots[len - 1].is_free() {
+            len -= 1;
+        }
+        if len < self.slots.len() {
+            self.slots.truncate(len);
+            self.free_list.retain(|&id| (id as usize) < len);
+        }
+        for slot in &mut self.slots {
+            if slot.is_free() {
+                slot.bytes = String::new();
             }
-            // Index entry is stale - remove it
-            self.index.remove(grapheme);
         }
+        self.slots.shrink_to_fit();
+        self.free_list.shrink_to_fit();
+        self.index.shrink_to_fit();
+    }
 
-        // Need to allocate - use try_alloc which respects soft limit
-        self.try_alloc(grapheme)
+    /// List every entry that still has references, for tracking down
+    /// refcount leaks: after all buffers using the pool are released, the
+    /// report should be empty. Only available in debug builds.
+    #[cfg(debug_assertions)]
+    #[must_use]
+    pub fn leak_report(&self) -> Vec<LeakedGrapheme> {
+        self.slots
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, slot)| !slot.is_free())
+            .map(|(pool_id, slot)| LeakedGrapheme {
+                pool_id: pool_id as u32,
+                refcount: slot.refcount,
+                grapheme: slot.bytes.clone(),
+            })
+            .collect()
     }
 
     /// Compact the pool by removing gaps from freed slots.
@@ -1076,6 +1224,8 @@ impl GraphemePool {
         self.slots = new_slots;
         self.free_list.clear();
         self.index = new_index;
+        #[cfg(feature = "pool-audit")]
+        self.audit.remap(&old_to_new);
 
         CompactionResult {
             old_to_new,
@@ -1534,6 +1684,7 @@ mod tests {
             peak_usage: 90,
             total_allocations: 100,
             total_frees: 15,
+            ..PoolStats::default()
         };
 
         assert!(stats.is_above_threshold(80));
@@ -1984,15 +2135,15 @@ mod tests {
     fn test_alloc_batch_with_duplicates() {
         let mut pool = GraphemePool::new();
 
-        // Unlike intern, alloc_batch should NOT deduplicate
         let ids = pool.alloc_batch(&["dup", "dup", "dup"]);
 
         assert_eq!(ids.len(), 3);
-        assert_eq!(pool.active_count(), 3);
+        assert_eq!(pool.active_count(), 1);
 
-        // Each should be a different slot
-        assert_ne!(ids[0].pool_id(), ids[1].pool_id());
-        assert_ne!(ids[1].pool_id(), ids[2].pool_id());
+        // Duplicates share one slot, holding a reference each
+        assert_eq!(ids[0].pool_id(), ids[1].pool_id());
+        assert_eq!(ids[1].pool_id(), ids[2].pool_id());
+        assert_eq!(pool.refcount(ids[0]), 3);
 
         // But all should retrieve the same string
         assert_eq!(pool.get(ids[0]), Some("dup"));
@@ -2638,9 +2789,142 @@ mod tests {
         let _ = pool.intern("new");
         assert_eq!(pool.total_allocations(), 1);
 
-        // But alloc() always counts even for duplicates
+        // Neither does alloc(), which deduplicates the same way
         let _ = pool.alloc("new");
-        assert_eq!(pool.total_allocations(), 2);
+        assert_eq!(pool.total_allocations(), 1);
+        assert_eq!(pool.stats().dedup_hits, 2);
+    }
+
+    // ========== Deduplication, shrinking, and leak reports ==========
+
+    #[test]
+    fn test_repeated_alloc_shares_one_entry() {
+        let mut pool = GraphemePool::new();
+        let ids: Vec<_> = (0..1000).map(|_| pool.alloc("👩‍🚀")).collect();
+
+        assert!(ids.iter().all(|id| *id == ids[0]));
+        assert_eq!(pool.active_count(), 1);
+        assert_eq!(pool.refcount(ids[0]), 1000);
+
+        let stats = pool.stats();
+        assert_eq!(stats.active_slots, 1);
+        assert_eq!(stats.live_refs, 1000);
+        assert_eq!(stats.bytes, "👩‍🚀".len());
+        assert_eq!(stats.dedup_hits, 999);
+        assert_eq!(stats.total_allocations, 1);
+    }
+
+    #[test]
+    fn test_dedup_index_forgets_freed_entries() {
+        let mut pool = GraphemePool::new();
+        let first = pool.alloc("é");
+        assert!(!pool.decref(first));
+
+        // A freed grapheme gets a fresh slot with a fresh refcount.
+        let other = pool.alloc("👍🏽");
+        let again = pool.alloc("é");
+        assert_eq!(pool.refcount(again), 1);
+        assert_ne!(again.pool_id(), other.pool_id());
+        assert_eq!(pool.stats().dedup_hits, 0);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_keeps_live_ids() {
+        let mut pool = GraphemePool::new();
+        let ids: Vec<_> = (0..100).map(|i| pool.alloc(&format!("g{i}"))).collect();
+        for id in &ids[1..] {
+            pool.decref(*id);
+        }
+        pool.decref(ids[0]);
+        let keep = pool.alloc("keep");
+        let tail = pool.alloc("tail");
+        pool.decref(tail);
+
+        pool.shrink_to_fit();
+        assert!(pool.total_slots() < 100);
+        assert_eq!(pool.get(keep), Some("keep"));
+        assert_eq!(pool.active_count(), 1);
+
+        // Slots freed before the shrink are still reused.
+        let next = pool.alloc("next");
+        assert!(next.pool_id() as usize <= pool.total_slots());
+        assert_eq!(pool.get(next), Some("next"));
+        assert_eq!(pool.get(keep), Some("keep"));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_leak_report_empty_after_buffers_release() {
+        use crate::buffer::OptimizedBuffer;
+        use crate::style::Style;
+
+        let mut pool = GraphemePool::new();
+        let mut a = OptimizedBuffer::new(20, 2);
+        let mut b = OptimizedBuffer::new(20, 2);
+        for y in 0..2 {
+            a.draw_text_with_pool(&mut pool, 0, y, "👩‍🚀 👩‍🚀 é", Style::NONE);
+            b.draw_text_with_pool(&mut pool, 0, y, "👩‍🚀 👍🏽", Style::NONE);
+        }
+
+        let report = pool.leak_report();
+        assert_eq!(report.len(), 2);
+        let astronaut = report.iter().find(|e| e.grapheme == "👩‍🚀").unwrap();
+        assert_eq!(astronaut.refcount, 6);
+
+        a.release_graphemes(&mut pool);
+        assert_eq!(pool.leak_report().len(), 2);
+        b.release_graphemes(&mut pool);
+        assert_eq!(pool.leak_report(), []);
+        assert_eq!(pool.stats().live_refs, 0);
+    }
+
+    // ========== Pool identity and translation ==========
+
+    #[test]
+    fn test_pool_ids_are_unique_and_nonzero() {
+        let a = GraphemePool::new();
+        let b = GraphemePool::with_capacity(4);
+        let c = GraphemePool::with_soft_limit(4);
+        assert_ne!(a.id(), 0);
+        assert_ne!(a.id(), b.id());
+        assert_ne!(b.id(), c.id());
+        assert_eq!(a.clone().id(), a.id());
+    }
+
+    #[test]
+    fn test_translate_interns_into_destination() {
+        let mut src = GraphemePool::new();
+        let family = src.alloc("👨‍👩‍👧");
+        let mut dst = GraphemePool::new();
+        let _ = dst.alloc("🏳️‍🌈");
+
+        let translated = dst.translate(&src, family);
+        assert_eq!(dst.get(translated), Some("👨‍👩‍👧"));
+        assert_eq!(translated.width(), family.width());
+        assert_eq!(dst.refcount(translated), 1);
+        assert_eq!(src.refcount(family), 1);
+
+        // Translating again shares the entry.
+        assert_eq!(dst.translate(&src, family), translated);
+        assert_eq!(dst.refcount(translated), 2);
+    }
+
+    #[test]
+    fn test_translate_within_same_pool_increfs() {
+        let mut pool = GraphemePool::new();
+        let id = pool.alloc("👨‍👩‍👧");
+        let snapshot = pool.clone();
+        assert_eq!(pool.translate(&snapshot, id), id);
+        assert_eq!(pool.refcount(id), 2);
+    }
+
+    #[test]
+    fn test_translate_unknown_id_gives_placeholder() {
+        let src = GraphemePool::new();
+        let mut dst = GraphemePool::new();
+        let stale = GraphemeId::new(7, 2);
+        assert_eq!(dst.translate(&src, stale), GraphemeId::placeholder(2));
+        assert_eq!(dst.active_count(), 0);
     }
 
     // ========== Compact threshold tests ==========
@@ -0,0 +1,380 @@
+//! Refcount auditing for [`GraphemePool`], behind the `pool-audit` feature.
+//!
+//! The pool remembers where each entry was allocated and reports refcount
+//! misuse as it happens: a release of an entry that is already free, and a
+//! pool dropped while entries are still referenced. Reports go to
+//! [`emit_log`](crate::event::emit_log) or panic, per [`AuditAction`].
+//!
+//! Misuse that doesn't touch the pool, such as a cell overwritten without
+//! releasing its grapheme, is found by cross-checking refcounts against the
+//! cells that hold them with [`GraphemePool::verify_against`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+
+use super::GraphemePool;
+use crate::buffer::OptimizedBuffer;
+use crate::cell::CellContent;
+use crate::event::{LogLevel, LogRecord, emit_log_record};
+
+/// What the pool does when it sees refcount misuse.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuditAction {
+    /// Log a warning and carry on.
+    #[default]
+    Log,
+    /// Panic, except while already unwinding.
+    Panic,
+}
+
+/// Where a pool entry was first allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocSite {
+    /// The caller of the allocating pool method.
+    pub location: &'static Location<'static>,
+    /// Tag set with [`GraphemePool::set_audit_tag`] at the time.
+    pub tag: Option<&'static str>,
+}
+
+impl fmt::Display for AllocSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tag {
+            Some(tag) => write!(f, "{tag} ({})", self.location),
+            None => write!(f, "{}", self.location),
+        }
+    }
+}
+
+/// A disagreement between pool refcounts and the cells referencing them,
+/// found by [`GraphemePool::verify_against`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuditIssue {
+    /// More references counted than held: the entry will never be freed.
+    Leaked {
+        pool_id: u32,
+        grapheme: String,
+        refcount: u32,
+        referenced: u32,
+        site: Option<AllocSite>,
+    },
+    /// Fewer references counted than held: the entry will be freed while
+    /// cells still show it.
+    Undercounted {
+        pool_id: u32,
+        grapheme: String,
+        refcount: u32,
+        referenced: u32,
+        site: Option<AllocSite>,
+    },
+    /// A buffer references an entry that is free.
+    Dangling {
+        pool_id: u32,
+        /// Index of the buffer in the list checked.
+        buffer: usize,
+        /// The cell, or `None` for a release the buffer has pending.
+        position: Option<(u32, u32)>,
+    },
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let site = |site: &Option<AllocSite>| {
+            site.map_or_else(String::new, |site| format!(", allocated at {site}"))
+        };
+        match self {
+            Self::Leaked {
+                pool_id,
+                grapheme,
+                refcount,
+                referenced,
+                site: origin,
+            } => write!(
+                f,
+                "grapheme {pool_id} {grapheme:?} leaked: refcount {refcount}, \
+                 {referenced} references{}",
+                site(origin)
+            ),
+            Self::Undercounted {
+                pool_id,
+                grapheme,
+                refcount,
+                referenced,
+                site: origin,
+            } => write!(
+                f,
+                "grapheme {pool_id} {grapheme:?} undercounted: refcount {refcount}, \
+                 {referenced} references{}",
+                site(origin)
+            ),
+            Self::Dangling {
+                pool_id,
+                buffer,
+                position: Some((x, y)),
+            } => write!(
+                f,
+                "buffer {buffer} cell ({x}, {y}) references freed grapheme {pool_id}"
+            ),
+            Self::Dangling {
+                pool_id,
+                buffer,
+                position: None,
+            } => write!(f, "buffer {buffer} will release freed grapheme {pool_id}"),
+        }
+    }
+}
+
+/// Per-pool audit state.
+#[derive(Clone, Debug, Default)]
+pub(super) struct PoolAudit {
+    sites: HashMap<u32, AllocSite>,
+    tag: Option<&'static str>,
+    action: AuditAction,
+}
+
+impl PoolAudit {
+    pub(super) fn record(&mut self, pool_id: u32, location: &'static Location<'static>) {
+        let site = AllocSite {
+            location,
+            tag: self.tag,
+        };
+        self.sites.insert(pool_id, site);
+    }
+
+    pub(super) fn forget(&mut self, pool_id: u32) {
+        self.sites.remove(&pool_id);
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.sites.clear();
+    }
+
+    pub(super) fn remap(&mut self, old_to_new: &HashMap<u32, u32>) {
+        self.sites = self
+            .sites
+            .drain()
+            .filter_map(|(old, site)| old_to_new.get(&old).map(|&new| (new, site)))
+            .collect();
+    }
+
+    pub(super) fn site(&self, pool_id: u32) -> Option<AllocSite> {
+        self.sites.get(&pool_id).copied()
+    }
+
+    pub(super) fn report(&self, message: &str) {
+        match self.action {
+            AuditAction::Panic if !std::thread::panicking() => panic!("{message}"),
+            _ => emit_log_record(
+                &LogRecord::new(LogLevel::Warn, message).with_target(module_path!()),
+            ),
+        }
+    }
+}
+
+impl GraphemePool {
+    /// Set what happens when this pool sees refcount misuse.
+    pub fn set_audit_action(&mut self, action: AuditAction) -> &mut Self {
+        self.audit.action = action;
+        self
+    }
+
+    /// Tag entries allocated from now on, to tell their allocation sites
+    /// apart in audit reports; `None` stops tagging.
+    pub fn set_audit_tag(&mut self, tag: Option<&'static str>) -> &mut Self {
+        self.audit.tag = tag;
+        self
+    }
+
+    /// Where the entry `pool_id` was allocated, if it is live.
+    #[must_use]
+    pub fn alloc_site(&self, pool_id: u32) -> Option<AllocSite> {
+        self.audit.site(pool_id)
+    }
+
+    /// Cross-check refcounts against the references held by `buffers`,
+    /// which should be every buffer holding references into this pool.
+    ///
+    /// Each grapheme cell holds one reference, as does each release a
+    /// buffer has pending from a cell overwritten without the pool. Buffers
+    /// bound to another pool are skipped. Issues are listed with the
+    /// dangling references first, in buffer order, then by entry.
+    #[must_use]
+    pub fn verify_against(&self, buffers: &[&OptimizedBuffer]) -> Vec<AuditIssue> {
+        let mut issues = Vec::new();
+        let mut referenced: HashMap<u32, u32> = HashMap::new();
+        let mut count = |pool_id: u32, buffer: usize, position: Option<(u32, u32)>| {
+            if self.get_by_pool_id(pool_id).is_some() {
+                *referenced.entry(pool_id).or_default() += 1;
+            } else {
+                issues.push(AuditIssue::Dangling {
+                    pool_id,
+                    buffer,
+                    position,
+                });
+            }
+        };
+
+        for (index, buffer) in buffers.iter().enumerate() {
+            if buffer.grapheme_pool_id().is_some_and(|id| id != self.id) {
+                continue;
+            }
+            let width = buffer.width();
+            for (i, cell) in buffer.cells().iter().enumerate() {
+                if let CellContent::Grapheme(id) = cell.content {
+                    if id.pool_id() != 0 {
+                        let (x, y) = (i as u32 % width, i as u32 / width);
+                        count(id.pool_id(), index, Some((x, y)));
+                    }
+                }
+            }
+            for id in buffer.orphaned_graphemes() {
+                count(id.pool_id(), index, None);
+            }
+        }
+
+        for (pool_id, slot) in self.slots.iter().enumerate().skip(1) {
+            if slot.is_free() {
+                continue;
+            }
+            let pool_id = pool_id as u32;
+            let held = referenced.get(&pool_id).copied().unwrap_or(0);
+            let (grapheme, refcount, site) =
+                (slot.bytes.clone(), slot.refcount, self.alloc_site(pool_id));
+            if slot.refcount > held {
+                issues.push(AuditIssue::Leaked {
+                    pool_id,
+                    grapheme,
+                    refcount,
+                    referenced: held,
+                    site,
+                });
+            } else if slot.refcount < held {
+                issues.push(AuditIssue::Undercounted {
+                    pool_id,
+                    grapheme,
+                    refcount,
+                    referenced: held,
+                    site,
+                });
+            }
+        }
+        issues
+    }
+}
+
+impl Drop for GraphemePool {
+    fn drop(&mut self) {
+        let live: Vec<String> = self
+            .slots
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, slot)| !slot.is_free())
+            .map(|(pool_id, slot)| {
+                let site = self
+                    .alloc_site(pool_id as u32)
+                    .map_or_else(String::new, |site| format!(" from {site}"));
+                format!("{pool_id} {:?} x{}{site}", slot.bytes, slot.refcount)
+            })
+            .collect();
+        if !live.is_empty() {
+            self.audit.report(&format!(
+                "grapheme pool dropped with {} live entries: {}",
+                live.len(),
+                live.join(", ")
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgba;
+    use crate::style::Style;
+
+    #[test]
+    fn test_alloc_sites_follow_tags_and_compaction() {
+        let mut pool = GraphemePool::new();
+        pool.set_audit_tag(Some("header"));
+        let family = pool.alloc("👨‍👩‍👧");
+        pool.set_audit_tag(None);
+        let flag = pool.alloc("🇯🇵");
+
+        let site = pool.alloc_site(family.pool_id()).unwrap();
+        assert_eq!(site.tag, Some("header"));
+        assert_eq!(site.location.file(), file!());
+        assert_eq!(pool.alloc_site(flag.pool_id()).unwrap().tag, None);
+
+        pool.decref(family);
+        assert_eq!(pool.alloc_site(family.pool_id()), None);
+        let result = pool.compact();
+        let moved = result.remap(flag.pool_id()).unwrap();
+        assert_eq!(pool.alloc_site(moved).unwrap().location.file(), file!());
+    }
+
+    #[test]
+    #[should_panic(expected = "decref of free grapheme 1")]
+    fn test_decref_below_zero_panics() {
+        let mut pool = GraphemePool::new();
+        pool.set_audit_action(AuditAction::Panic);
+        let id = pool.alloc("👍🏽");
+        pool.decref(id);
+        pool.decref(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "grapheme pool dropped with 1 live entries: 1 \"é\" x2")]
+    fn test_drop_with_live_entries_panics() {
+        let mut pool = GraphemePool::new();
+        pool.set_audit_action(AuditAction::Panic);
+        let id = pool.alloc("é");
+        pool.incref(id);
+    }
+
+    #[test]
+    fn test_verify_reports_buggy_sequence() {
+        let mut pool = GraphemePool::new();
+        let mut buffer = OptimizedBuffer::new(4, 1).with_grapheme_pool(&pool);
+        buffer.draw_text_with_pool(&mut pool, 0, 0, "👍🏽🇯🇵", Style::NONE);
+        assert_eq!(pool.verify_against(&[&buffer]), []);
+
+        // A cell copied without taking a reference, then the original
+        // cleared without the pool, which forgets its references.
+        let thumbs = *buffer.get(0, 0).unwrap();
+        let mut copy = OptimizedBuffer::new(2, 1).with_grapheme_pool(&pool);
+        copy.cells_mut()[0] = thumbs;
+        buffer.clear(Rgba::BLACK);
+        pool.decref(thumbs.content.grapheme_id().unwrap());
+
+        let issues = pool.verify_against(&[&buffer, &copy]);
+        assert_eq!(
+            issues,
+            [
+                AuditIssue::Dangling {
+                    pool_id: 1,
+                    buffer: 1,
+                    position: Some((0, 0)),
+                },
+                AuditIssue::Leaked {
+                    pool_id: 2,
+                    grapheme: "🇯🇵".to_string(),
+                    refcount: 1,
+                    referenced: 0,
+                    site: pool.alloc_site(2),
+                },
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "buffer 1 cell (0, 0) references freed grapheme 1"
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            format!(
+                "grapheme 2 \"🇯🇵\" leaked: refcount 1, 0 references, allocated at {}",
+                pool.alloc_site(2).unwrap()
+            )
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Bracket matching and nesting depth, aware of string and comment tokens.
+
+use crate::highlight::highlighted_buffer::HighlightedBuffer;
+use crate::highlight::token::{Token, TokenKind};
+
+/// Default number of chars [`bracket_match`] scans before giving up.
+pub const DEFAULT_BRACKET_SCAN_LIMIT: usize = 100_000;
+
+/// The closing bracket for an opening one.
+const fn closing(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// The opening bracket for a closing one.
+const fn opening(close: char) -> Option<char> {
+    match close {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+/// Token kinds whose brackets are text rather than code.
+const fn is_literal(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::String
+            | TokenKind::StringEscape
+            | TokenKind::Comment
+            | TokenKind::CommentBlock
+            | TokenKind::CommentDoc
+    )
+}
+
+/// Brackets in a line outside string and comment tokens, as
+/// `(char column, bracket)` pairs in line order.
+pub(crate) fn code_brackets(line: &str, tokens: &[Token]) -> Vec<(usize, char)> {
+    let literals: Vec<_> = tokens
+        .iter()
+        .filter(|token| is_literal(token.kind))
+        .map(Token::range)
+        .collect();
+
+    line.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, ch))| {
+            (closing(*ch).is_some() || opening(*ch).is_some())
+                && !literals.iter().any(|range| range.contains(byte))
+        })
+        .map(|(col, (_, ch))| (col, ch))
+        .collect()
+}
+
+/// Nesting depth after a line's brackets, starting from `depth`.
+///
+/// Returns the depth of each bracket alongside: an opening bracket gets the
+/// depth outside it, a closing bracket the depth of its opener. Stray closing
+/// brackets clamp at zero.
+pub(crate) fn bracket_depths(brackets: &[(usize, char)], mut depth: u32) -> (Vec<u32>, u32) {
+    let depths = brackets
+        .iter()
+        .map(|&(_, ch)| {
+            if closing(ch).is_some() {
+                depth += 1;
+                depth - 1
+            } else {
+                depth = depth.saturating_sub(1);
+                depth
+            }
+        })
+        .collect();
+    (depths, depth)
+}
+
+/// Find the bracket pair for the bracket at `pos`, or just before it.
+///
+/// `pos` and the returned `(open, close)` offsets are char offsets. Brackets
+/// inside string and comment tokens (from the last
+/// [`HighlightedBuffer::update_highlighting`]) are ignored, both as the
+/// starting bracket and while scanning. Gives up after
+/// [`DEFAULT_BRACKET_SCAN_LIMIT`] chars; see [`bracket_match_within`].
+#[must_use]
+pub fn bracket_match(buffer: &HighlightedBuffer, pos: usize) -> Option<(usize, usize)> {
+    bracket_match_within(buffer, pos, DEFAULT_BRACKET_SCAN_LIMIT)
+}
+
+/// [`bracket_match`] with an explicit limit on the chars scanned.
+#[must_use]
+pub fn bracket_match_within(
+    buffer: &HighlightedBuffer,
+    pos: usize,
+    scan_limit: usize,
+) -> Option<(usize, usize)> {
+    let len = buffer.len_chars();
+    [Some(pos), pos.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter(|&offset| offset < len)
+        .find_map(|offset| {
+            let line = buffer.rope().char_to_line(offset);
+            let col = offset - buffer.rope().line_to_char(line);
+            let (_, ch) = line_brackets(buffer, line)
+                .into_iter()
+                .find(|&(bracket_col, _)| bracket_col == col)?;
+            Some(match closing(ch) {
+                Some(close) => (
+                    offset,
+                    scan_forward(buffer, line, col, ch, close, scan_limit)?,
+                ),
+                None => (scan_backward(buffer, line, col, ch, scan_limit)?, offset),
+            })
+        })
+}
+
+fn line_brackets(buffer: &HighlightedBuffer, line: usize) -> Vec<(usize, char)> {
+    buffer.line(line).map_or_else(Vec::new, |text| {
+        code_brackets(
+            text.trim_end_matches(['\n', '\r']),
+            buffer.tokens_for_line(line),
+        )
+    })
+}
+
+fn line_len(buffer: &HighlightedBuffer, line: usize) -> usize {
+    let rope = buffer.rope();
+    let end = if line + 1 < rope.len_lines() {
+        rope.line_to_char(line + 1)
+    } else {
+        rope.len_chars()
+    };
+    end - rope.line_to_char(line)
+}
+
+fn scan_forward(
+    buffer: &HighlightedBuffer,
+    start_line: usize,
+    start_col: usize,
+    open: char,
+    close: char,
+    scan_limit: usize,
+) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut scanned = 0usize;
+    for line in start_line..buffer.len_lines() {
+        for (col, ch) in line_brackets(buffer, line) {
+            if line == start_line && col <= start_col {
+                continue;
+            }
+            if ch == open {
+                depth += 1;
+            } else if ch == close {
+                if depth == 0 {
+                    return Some(buffer.rope().line_to_char(line) + col);
+                }
+                depth -= 1;
+            }
+        }
+        scanned += line_len(buffer, line);
+        if scanned > scan_limit {
+            return None;
+        }
+    }
+    None
+}
+
+fn scan_backward(
+    buffer: &HighlightedBuffer,
+    start_line: usize,
+    start_col: usize,
+    close: char,
+    scan_limit: usize,
+) -> Option<usize> {
+    let open = opening(close)?;
+    let mut depth = 0usize;
+    let mut scanned = 0usize;
+    for line in (0..=start_line).rev() {
+        for (col, ch) in line_brackets(buffer, line).into_iter().rev() {
+            if line == start_line && col >= start_col {
+                continue;
+            }
+            if ch == close {
+                depth += 1;
+            } else if ch == open {
+                if depth == 0 {
+                    return Some(buffer.rope().line_to_char(line) + col);
+                }
+                depth -= 1;
+            }
+        }
+        scanned += line_len(buffer, line);
+        if scanned > scan_limit {
+            return None;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlight::languages::rust::RustTokenizer;
+    use crate::text::TextBuffer;
+    use std::sync::Arc;
+
+    fn rust_buffer(text: &str) -> HighlightedBuffer {
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text(text));
+        buffer.set_tokenizer(Some(Arc::new(RustTokenizer::new())));
+        buffer.update_highlighting();
+        buffer
+    }
+
+    #[test]
+    fn test_nested_pairs_across_lines() {
+        let text = "fn f() {\n    g(a[0], { b });\n}";
+        let buffer = rust_buffer(text);
+        let open_brace = text.find('{').unwrap();
+        let close_brace = text.rfind('}').unwrap();
+
+        assert_eq!(
+            bracket_match(&buffer, open_brace),
+            Some((open_brace, close_brace))
+        );
+        assert_eq!(
+            bracket_match(&buffer, close_brace),
+            Some((open_brace, close_brace))
+        );
+
+        let open_paren = text.find("g(").unwrap() + 1;
+        let close_paren = text.find(");").unwrap();
+        assert_eq!(
+            bracket_match(&buffer, open_paren),
+            Some((open_paren, close_paren))
+        );
+    }
+
+    #[test]
+    fn test_position_just_after_bracket() {
+        let buffer = rust_buffer("(a) b");
+        // Cursor right after `)`.
+        assert_eq!(bracket_match(&buffer, 3), Some((0, 2)));
+        // Not at or next to a bracket.
+        assert_eq!(bracket_match(&buffer, 5), None);
+    }
+
+    #[test]
+    fn test_brackets_in_strings_and_comments_are_skipped() {
+        let text = "f(\")\", \"(\") // (\n";
+        let buffer = rust_buffer(text);
+        assert_eq!(
+            bracket_match(&buffer, 1),
+            Some((1, text.find(") //").unwrap()))
+        );
+        // A bracket inside a string has no pair.
+        assert_eq!(bracket_match(&buffer, 3), None);
+        assert_eq!(bracket_match(&buffer, text.rfind('(').unwrap()), None);
+    }
+
+    #[test]
+    fn test_unbalanced_returns_none() {
+        let buffer = rust_buffer("fn f() {\n    (\n");
+        assert_eq!(bracket_match(&buffer, 7), None);
+        let buffer = rust_buffer("}}\n)");
+        assert_eq!(bracket_match(&buffer, 1), None);
+        assert_eq!(bracket_match(&buffer, 3), None);
+    }
+
+    #[test]
+    fn test_scan_limit_bounds_search() {
+        let text = format!("({})", "\n".repeat(50));
+        let buffer = rust_buffer(&text);
+        assert_eq!(bracket_match_within(&buffer, 0, 100), Some((0, 51)));
+        assert_eq!(bracket_match_within(&buffer, 0, 10), None);
+        assert_eq!(bracket_match_within(&buffer, 51, 10), None);
+    }
+
+    #[test]
+    fn test_depths_clamp_stray_closers() {
+        let brackets = [(0, ')'), (1, '('), (2, '['), (3, ']'), (4, ')')];
+        assert_eq!(bracket_depths(&brackets, 0), (vec![0, 0, 1, 1, 0], 0));
+        assert_eq!(bracket_depths(&brackets[1..3], 2), (vec![2, 3], 4));
+    }
+}
@@ -0,0 +1,335 @@
+//! Per-language keyword and TODO-word customizations layered over a tokenizer.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::highlight::token::{Token, TokenKind};
+use crate::highlight::tokenizer::{LineState, Tokenizer};
+
+/// Extra classifications applied after a language's own tokenization.
+///
+/// See [`TokenizerRegistry::customize`](super::TokenizerRegistry::customize).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Customization {
+    /// Words to reclassify, with the kind they become (e.g. project macros
+    /// or DSL keywords).
+    pub extra_keywords: Vec<(String, TokenKind)>,
+    /// Words reclassified as [`TokenKind::Todo`], in code and inside comments.
+    pub todo_words: Vec<String>,
+    /// Match both lists ignoring case.
+    pub case_insensitive: bool,
+}
+
+/// A [`Customization`] turned into lookup tables.
+#[derive(Debug, Default)]
+struct Compiled {
+    keywords: HashMap<String, TokenKind>,
+    todo_words: HashSet<String>,
+    case_insensitive: bool,
+}
+
+impl Compiled {
+    fn new(customization: &Customization) -> Self {
+        let case_insensitive = customization.case_insensitive;
+        let key = |word: &str| {
+            if case_insensitive {
+                word.to_lowercase()
+            } else {
+                word.to_string()
+            }
+        };
+        Self {
+            keywords: customization
+                .extra_keywords
+                .iter()
+                .map(|(word, kind)| (key(word), *kind))
+                .collect(),
+            todo_words: customization
+                .todo_words
+                .iter()
+                .map(|word| key(word))
+                .collect(),
+            case_insensitive,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.keywords.is_empty() && self.todo_words.is_empty()
+    }
+
+    fn normalize<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if self.case_insensitive {
+            Cow::Owned(word.to_lowercase())
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+
+    fn is_todo(&self, word: &str) -> bool {
+        self.todo_words.contains(self.normalize(word).as_ref())
+    }
+
+    fn reclassify(&self, word: &str) -> Option<TokenKind> {
+        let word = self.normalize(word);
+        if self.todo_words.contains(word.as_ref()) {
+            Some(TokenKind::Todo)
+        } else {
+            self.keywords.get(word.as_ref()).copied()
+        }
+    }
+
+    fn apply(&self, line: &str, tokens: Vec<Token>) -> Vec<Token> {
+        let mut out = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let Some(text) = line.get(token.range()) else {
+                out.push(token);
+                continue;
+            };
+            if is_word_kind(token.kind) {
+                let kind = self.reclassify(text).unwrap_or(token.kind);
+                out.push(Token::new(kind, token.start, token.end));
+            } else if is_comment_kind(token.kind) && !self.todo_words.is_empty() {
+                self.split_comment(text, &token, &mut out);
+            } else {
+                out.push(token);
+            }
+        }
+        out
+    }
+
+    /// Split a comment token around the TODO words it contains.
+    fn split_comment(&self, text: &str, token: &Token, out: &mut Vec<Token>) {
+        let mut rest = token.start;
+        for (start, word) in words(text) {
+            if !self.is_todo(word) {
+                continue;
+            }
+            let start = token.start + start;
+            let end = start + word.len();
+            if start > rest {
+                out.push(Token::new(token.kind, rest, start));
+            }
+            out.push(Token::new(TokenKind::Todo, start, end));
+            rest = end;
+        }
+        if rest < token.end {
+            out.push(Token::new(token.kind, rest, token.end));
+        }
+    }
+}
+
+/// Token kinds holding a single word that a customization may reclassify.
+const fn is_word_kind(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Identifier
+            | TokenKind::Type
+            | TokenKind::Constant
+            | TokenKind::Function
+            | TokenKind::Text
+    )
+}
+
+const fn is_comment_kind(kind: TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Comment | TokenKind::CommentBlock | TokenKind::CommentDoc
+    )
+}
+
+/// Words (runs of alphanumerics and `_`) in `text`, with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let rest = &text[offset..];
+        let start = offset + rest.find(is_word)?;
+        let len = text[start..]
+            .find(|ch| !is_word(ch))
+            .unwrap_or(text.len() - start);
+        offset = start + len;
+        Some((start, &text[start..offset]))
+    })
+}
+
+/// A tokenizer with a hot-swappable [`Customization`] applied to its output.
+///
+/// Swapping the customization bumps the [`generation`](Tokenizer::generation),
+/// so every [`HighlightedBuffer`](super::HighlightedBuffer) sharing this
+/// tokenizer re-tokenizes on its next update.
+pub struct CustomizedTokenizer {
+    base: Arc<dyn Tokenizer>,
+    compiled: RwLock<Arc<Compiled>>,
+    generation: AtomicU64,
+}
+
+impl CustomizedTokenizer {
+    /// Wrap `base` with a customization.
+    #[must_use]
+    pub fn new(base: Arc<dyn Tokenizer>, customization: &Customization) -> Self {
+        Self {
+            base,
+            compiled: RwLock::new(Arc::new(Compiled::new(customization))),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped tokenizer.
+    #[must_use]
+    pub fn base(&self) -> &Arc<dyn Tokenizer> {
+        &self.base
+    }
+
+    /// Replace the customization.
+    pub fn set_customization(&self, customization: &Customization) {
+        let compiled = Arc::new(Compiled::new(customization));
+        *self
+            .compiled
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = compiled;
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    fn compiled(&self) -> Arc<Compiled> {
+        self.compiled
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl Tokenizer for CustomizedTokenizer {
+    fn name(&self) -> &'static str {
+        self.base.name()
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        self.base.extensions()
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        self.base.aliases()
+    }
+
+    fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+        let (tokens, state) = self.base.tokenize_line(line, state);
+        let compiled = self.compiled();
+        if compiled.is_empty() {
+            return (tokens, state);
+        }
+        (compiled.apply(line, tokens), state)
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+            .load(Ordering::Acquire)
+            .wrapping_add(self.base.generation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlight::languages::rust::RustTokenizer;
+    use std::fmt::Write as _;
+
+    const SOURCE: &str = "\
+// TODO: drop this once ready. todos are fine.
+fn main() {
+    let ctx = Context::new(); /* fixme later */
+    ensure!(ctx.ready);
+    spawn_task(ctx);
+}
+";
+
+    fn customization() -> Customization {
+        Customization {
+            extra_keywords: vec![
+                ("Context".to_string(), TokenKind::KeywordType),
+                ("spawn_task".to_string(), TokenKind::Keyword),
+                ("ctx".to_string(), TokenKind::Constant),
+            ],
+            todo_words: vec!["todo".to_string(), "FIXME".to_string()],
+            case_insensitive: true,
+        }
+    }
+
+    /// Tokens whose kind differs between the base and customized output.
+    fn changed_kinds(base: &dyn Tokenizer, customized: &dyn Tokenizer) -> String {
+        let mut out = String::new();
+        let mut base_state = LineState::Normal;
+        let mut state = LineState::Normal;
+        for (row, line) in SOURCE.lines().enumerate() {
+            let (base_tokens, next_base) = base.tokenize_line(line, base_state);
+            let (tokens, next) = customized.tokenize_line(line, state);
+            base_state = next_base;
+            state = next;
+            for token in tokens {
+                let before = base_tokens
+                    .iter()
+                    .find(|base| base.start <= token.start && token.end <= base.end)
+                    .map(|base| base.kind);
+                if before != Some(token.kind) {
+                    writeln!(
+                        out,
+                        "{row}:{}..{} {:?} {:?} -> {}",
+                        token.start,
+                        token.end,
+                        &line[token.range()],
+                        before.map_or("none", TokenKind::name),
+                        token.kind.name()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_customized_rust_token_kinds() {
+        let base: Arc<dyn Tokenizer> = Arc::new(RustTokenizer::new());
+        let customized = CustomizedTokenizer::new(base.clone(), &customization());
+        insta::assert_snapshot!(changed_kinds(base.as_ref(), &customized));
+    }
+
+    #[test]
+    fn test_case_sensitive_matching() {
+        let base: Arc<dyn Tokenizer> = Arc::new(RustTokenizer::new());
+        let customization = Customization {
+            case_insensitive: false,
+            ..customization()
+        };
+        let customized = CustomizedTokenizer::new(base, &customization);
+
+        let (tokens, _) = customized.tokenize_line("// todo TODO FIXME", LineState::Normal);
+        let todos: Vec<_> = tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Todo)
+            .map(Token::range)
+            .collect();
+        assert_eq!(todos, [3..7, 13..18]);
+    }
+
+    #[test]
+    fn test_swapping_customization_bumps_generation() {
+        let customized =
+            CustomizedTokenizer::new(Arc::new(RustTokenizer::new()), &Customization::default());
+        let before = customized.generation();
+        let (tokens, _) = customized.tokenize_line("// TODO", LineState::Normal);
+        assert!(tokens.iter().all(|token| token.kind == TokenKind::Comment));
+
+        customized.set_customization(&customization());
+        assert_ne!(customized.generation(), before);
+        let (tokens, _) = customized.tokenize_line("// TODO", LineState::Normal);
+        assert_eq!(tokens.last().map(|token| token.kind), Some(TokenKind::Todo));
+    }
+
+    #[test]
+    fn test_words_split_on_non_word_chars() {
+        let found: Vec<_> = words("a TODO:b_c  ünï").collect();
+        assert_eq!(found, [(0, "a"), (2, "TODO"), (7, "b_c"), (12, "ünï")]);
+    }
+}
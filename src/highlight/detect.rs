@@ -0,0 +1,122 @@
+//! Signals for [`TokenizerRegistry::detect`](super::TokenizerRegistry::detect).
+//!
+//! Each helper turns one signal (file name, extension, shebang, content)
+//! into a candidate language name; the registry decides which candidates
+//! it can serve.
+
+/// Well-known file names without a telling extension, matched
+/// case-insensitively.
+pub const FILENAMES: &[(&str, &str)] = &[
+    ("makefile", "make"),
+    ("gnumakefile", "make"),
+    ("dockerfile", "dockerfile"),
+    ("containerfile", "dockerfile"),
+    ("cmakelists.txt", "cmake"),
+    ("cargo.lock", "toml"),
+    ("poetry.lock", "toml"),
+    ("pipfile", "toml"),
+    ("gemfile", "ruby"),
+    ("rakefile", "ruby"),
+    ("pkgbuild", "shell"),
+    (".bashrc", "shell"),
+    (".bash_profile", "shell"),
+    (".profile", "shell"),
+    (".zshrc", "shell"),
+];
+
+/// Extensions several languages use, with their candidates in order of
+/// preference. Only consulted when no registered tokenizer claims the
+/// extension.
+pub const AMBIGUOUS_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("h", &["c", "cpp", "objective-c"]),
+    ("m", &["objective-c", "matlab"]),
+];
+
+/// Interpreters whose name differs from the language they run.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "shell"),
+    ("dash", "shell"),
+    ("ash", "shell"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("bun", "javascript"),
+    ("deno", "typescript"),
+    ("ts-node", "typescript"),
+    ("pypy", "python"),
+];
+
+/// The last path component of `path`.
+pub fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The extension of a file name. Dotfiles like `.bashrc` have none.
+pub fn extension(file_name: &str) -> Option<&str> {
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => Some(ext),
+        _ => None,
+    }
+}
+
+/// The language named by a `#!` line, looking through `env` and its flags.
+///
+/// Version suffixes are dropped, so `python3.12` gives `python`.
+pub fn shebang_language(text: &str) -> Option<&str> {
+    let line = text.trim_start_matches('\u{feff}').lines().next()?;
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = file_name(words.next()?);
+    if program == "env" {
+        program = file_name(words.find(|word| !word.starts_with('-') && !word.contains('='))?);
+    }
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == program)
+        .map_or(program, |&(_, language)| language);
+    (!language.is_empty()).then_some(language)
+}
+
+/// A language guessed from the opening of a file's content.
+pub fn content_language(text: &str) -> Option<&'static str> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let first_line = text.lines().next().unwrap_or_default();
+    if text.starts_with("<?xml") {
+        Some("xml")
+    } else if starts_with_ignore_case(text, "<!doctype html")
+        || starts_with_ignore_case(text, "<html")
+    {
+        Some("html")
+    } else if first_line.starts_with("diff --git ")
+        || (first_line.starts_with("--- ")
+            && text
+                .lines()
+                .nth(1)
+                .is_some_and(|line| line.starts_with("+++ ")))
+    {
+        Some("diff")
+    } else if first_line.starts_with("%YAML") {
+        Some("yaml")
+    } else if looks_like_json(text) {
+        Some("json")
+    } else {
+        None
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+/// An object whose first member is a quoted key, or an array of objects.
+fn looks_like_json(text: &str) -> bool {
+    let body = text.strip_prefix('[').map_or(text, str::trim_start);
+    let Some(rest) = body.strip_prefix('{') else {
+        return false;
+    };
+    let Some(rest) = rest.trim_start().strip_prefix('"') else {
+        return false;
+    };
+    rest.split_once('"')
+        .is_some_and(|(_, after)| after.trim_start().starts_with(':'))
+}
@@ -0,0 +1,137 @@
+//! Diagnostic spans (errors, warnings) layered over syntax highlighting.
+
+use std::ops::Range;
+
+/// Diagnostic severity, ordered from least to most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+impl DiagnosticSeverity {
+    pub const ALL: [Self; 4] = [Self::Hint, Self::Info, Self::Warning, Self::Error];
+
+    pub const COUNT: usize = Self::ALL.len();
+
+    #[must_use]
+    pub const fn as_usize(self) -> usize {
+        self as usize
+    }
+}
+
+/// A diagnostic attached to a character range of a [`HighlightedBuffer`].
+///
+/// `range` is in char offsets from the start of the buffer, matching the
+/// offsets used by [`EditBuffer`](crate::text::EditBuffer) edits.
+///
+/// [`HighlightedBuffer`]: super::HighlightedBuffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    #[must_use]
+    pub fn new(
+        range: Range<usize>,
+        severity: DiagnosticSeverity,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            range,
+            severity,
+            message: message.into(),
+        }
+    }
+
+    /// Check whether a char offset falls inside the diagnostic.
+    #[must_use]
+    pub fn contains(&self, offset: usize) -> bool {
+        self.range.contains(&offset)
+    }
+
+    /// Shift the range for an edit that replaced `removed` chars at `offset`
+    /// with `inserted` chars.
+    ///
+    /// Text inserted at the start of the range pushes it right; text inserted
+    /// at its end does not extend it. Returns `false` if the edit deleted the
+    /// whole (previously non-empty) range.
+    pub fn apply_edit(&mut self, offset: usize, removed: usize, inserted: usize) -> bool {
+        shift_range(&mut self.range, offset, removed, inserted)
+    }
+}
+
+/// Shift a char range for an edit; see [`Diagnostic::apply_edit`].
+pub(crate) fn shift_range(
+    range: &mut Range<usize>,
+    offset: usize,
+    removed: usize,
+    inserted: usize,
+) -> bool {
+    let was_empty = range.end <= range.start;
+    let removed_end = offset + removed;
+
+    let start = range.start;
+    range.start = if start >= removed_end {
+        start - removed + inserted
+    } else if start > offset {
+        offset + inserted
+    } else {
+        start
+    };
+
+    let end = range.end;
+    range.end = if end > removed_end {
+        end - removed + inserted
+    } else if end > offset {
+        offset
+    } else {
+        end
+    };
+    range.end = range.end.max(range.start);
+
+    was_empty || range.end > range.start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edited(range: Range<usize>, offset: usize, removed: usize, inserted: usize) -> Range<usize> {
+        let mut diagnostic = Diagnostic::new(range, DiagnosticSeverity::Error, "e");
+        diagnostic.apply_edit(offset, removed, inserted);
+        diagnostic.range
+    }
+
+    #[test]
+    fn severity_order() {
+        assert!(DiagnosticSeverity::Error > DiagnosticSeverity::Warning);
+        assert!(DiagnosticSeverity::Warning > DiagnosticSeverity::Info);
+        assert!(DiagnosticSeverity::Info > DiagnosticSeverity::Hint);
+    }
+
+    #[test]
+    fn insertions_shift_or_grow() {
+        assert_eq!(edited(10..15, 0, 0, 3), 13..18);
+        assert_eq!(edited(10..15, 10, 0, 3), 13..18);
+        assert_eq!(edited(10..15, 12, 0, 3), 10..18);
+        assert_eq!(edited(10..15, 15, 0, 3), 10..15);
+        assert_eq!(edited(10..15, 20, 0, 3), 10..15);
+    }
+
+    #[test]
+    fn deletions_shrink_or_remove() {
+        assert_eq!(edited(10..15, 0, 5, 0), 5..10);
+        assert_eq!(edited(10..15, 8, 4, 0), 8..11);
+        assert_eq!(edited(10..15, 12, 5, 0), 10..12);
+
+        let mut gone = Diagnostic::new(10..15, DiagnosticSeverity::Warning, "w");
+        assert!(!gone.apply_edit(9, 7, 0));
+        assert!(gone.range.is_empty());
+    }
+}
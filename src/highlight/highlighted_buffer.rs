@@ -1,11 +1,17 @@
+use crate::error::Result;
+use crate::highlight::bracket::{bracket_depths, code_brackets};
+use crate::highlight::diagnostic::{Diagnostic, DiagnosticSeverity};
+use crate::highlight::layer::{HighlightLayers, LayerMerge};
+use crate::highlight::semantic::SemanticToken;
 use crate::highlight::theme::Theme;
-use crate::highlight::token::Token;
+use crate::highlight::token::{BufferToken, Token};
 use crate::highlight::tokenizer::{LineState, Tokenizer};
 use crate::style::Style;
-use crate::text::{StyledSegment, TextBuffer};
+use crate::text::{RopeWrapper, StyledSegment, TextBuffer};
 use std::sync::Arc;
 
 const SYNTAX_HIGHLIGHT_REF_ID: u16 = 1;
+const DIAGNOSTIC_HIGHLIGHT_REF_ID: u16 = 2;
 
 /// Text buffer with syntax highlighting support.
 ///
@@ -14,15 +20,32 @@ const SYNTAX_HIGHLIGHT_REF_ID: u16 = 1;
 pub struct HighlightedBuffer {
     buffer: TextBuffer,
     tokenizer: Option<Arc<dyn Tokenizer>>,
+    // Tokenizer generation the cached tokens were produced under
+    tokenizer_generation: u64,
     theme: Theme,
 
     // Per-line token cache
     line_tokens: Vec<Vec<Token>>,
-    line_states: Vec<LineState>, // State at END of each line
+    line_states: Vec<LineState>,   // State at END of each line
+    line_bracket_depths: Vec<u32>, // Bracket depth at END of each line
+
+    rainbow_brackets: bool,
+
+    // Diagnostics layered over syntax styles, in char offsets
+    diagnostics: Vec<Diagnostic>,
+
+    // Semantic tokens overriding lexical styles, in char offsets, sorted by start
+    semantic_tokens: Vec<SemanticToken>,
+
+    // Render-time layers (selection, search, ...), in char offsets
+    layers: HighlightLayers,
 
     // Dirty tracking for incremental updates
     dirty_span: Option<std::ops::Range<usize>>,
     theme_dirty: bool,
+    diagnostics_dirty: bool,
+    // Every line must be restyled for changed semantic tokens
+    semantic_dirty: bool,
 }
 
 impl HighlightedBuffer {
@@ -36,11 +59,19 @@ impl HighlightedBuffer {
         Self {
             buffer,
             tokenizer: None,
+            tokenizer_generation: 0,
             theme,
             line_tokens: vec![Vec::new(); line_count],
             line_states: vec![LineState::default(); line_count],
+            line_bracket_depths: vec![0; line_count],
+            rainbow_brackets: false,
+            diagnostics: Vec::new(),
+            semantic_tokens: Vec::new(),
+            layers: HighlightLayers::default(),
             dirty_span: Some(0..line_count),
             theme_dirty: false,
+            diagnostics_dirty: false,
+            semantic_dirty: false,
         }
     }
 
@@ -60,6 +91,9 @@ impl HighlightedBuffer {
 
     /// Set the tokenizer. Triggers a full re-highlight on next update.
     pub fn set_tokenizer(&mut self, tokenizer: Option<Arc<dyn Tokenizer>>) {
+        self.tokenizer_generation = tokenizer
+            .as_ref()
+            .map_or(0, |tokenizer| tokenizer.generation());
         self.tokenizer = tokenizer;
         self.clear_syntax_highlights();
         let len = self.buffer.len_lines();
@@ -72,6 +106,25 @@ impl HighlightedBuffer {
         self.theme = theme;
         self.buffer.set_default_style(self.theme.default_style());
         self.theme_dirty = true;
+        self.diagnostics_dirty = true;
+    }
+
+    /// Color bracket tokens by nesting depth using the theme's
+    /// [`bracket_palette`](Theme::bracket_palette).
+    ///
+    /// Brackets inside strings and comments are not colored or counted.
+    /// Takes effect on the next [`update_highlighting`](Self::update_highlighting).
+    pub fn set_rainbow_brackets(&mut self, enabled: bool) {
+        if self.rainbow_brackets != enabled {
+            self.rainbow_brackets = enabled;
+            self.theme_dirty = true;
+        }
+    }
+
+    /// Returns true if rainbow brackets are enabled.
+    #[must_use]
+    pub fn rainbow_brackets(&self) -> bool {
+        self.rainbow_brackets
     }
 
     /// Get the current theme.
@@ -95,11 +148,182 @@ impl HighlightedBuffer {
     /// Get mutable access to the underlying text buffer.
     ///
     /// **Note:** Modifications must be followed by `mark_dirty` if not done via
-    /// `HighlightedBuffer` methods.
+    /// `HighlightedBuffer` methods. Edits made here do not shift diagnostics,
+    /// semantic tokens, or layer ranges; use [`insert`](Self::insert) and
+    /// [`remove`](Self::remove), or set them again afterwards.
     pub fn buffer_mut(&mut self) -> &mut TextBuffer {
         &mut self.buffer
     }
 
+    /// Replace the diagnostics shown over the text.
+    ///
+    /// Diagnostic styles come from [`Theme::diagnostic_style`] and keep the
+    /// syntax foreground. Where diagnostics overlap, the most severe wins.
+    /// Takes effect on the next [`update_highlighting`](Self::update_highlighting).
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = diagnostics;
+        self.diagnostics_dirty = true;
+    }
+
+    /// Current diagnostics, with ranges adjusted for [`insert`](Self::insert)
+    /// and [`remove`](Self::remove) edits made since they were set.
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Most severe diagnostic covering the char at `line`/`col`, if any.
+    #[must_use]
+    pub fn diagnostic_at(&self, line: usize, col: usize) -> Option<&Diagnostic> {
+        if line >= self.buffer.len_lines() {
+            return None;
+        }
+        let offset = self.buffer.rope().line_to_char(line) + col;
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.contains(offset))
+            .reduce(|best, next| {
+                if next.severity > best.severity {
+                    next
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Replace the semantic tokens overlaid on the lexical tokens.
+    ///
+    /// Where a semantic token covers text, its [`Theme::semantic_style`]
+    /// replaces the lexical style, splitting lexical tokens it covers only in
+    /// part. The tokens shift with [`insert`](Self::insert) and
+    /// [`remove`](Self::remove) until the next call. They must not overlap,
+    /// which LSP servers guarantee.
+    pub fn set_semantic_tokens(&mut self, mut tokens: Vec<SemanticToken>) {
+        tokens.retain(|token| !token.range.is_empty());
+        tokens.sort_by_key(|token| token.range.start);
+        self.semantic_tokens = tokens;
+        self.semantic_dirty = true;
+    }
+
+    /// Current semantic tokens, with ranges adjusted for edits made since they were set.
+    #[must_use]
+    pub fn semantic_tokens(&self) -> &[SemanticToken] {
+        &self.semantic_tokens
+    }
+
+    /// Remove all semantic tokens, going back to lexical styles.
+    pub fn clear_semantic_tokens(&mut self) {
+        if !self.semantic_tokens.is_empty() {
+            self.semantic_tokens.clear();
+            self.semantic_dirty = true;
+        }
+    }
+
+    /// Drop the semantic tokens touching `line`, for when an edit has made
+    /// them stale. The line shows lexical styles until the next
+    /// [`set_semantic_tokens`](Self::set_semantic_tokens).
+    pub fn invalidate_semantic_line(&mut self, line: usize) {
+        let rope = self.buffer.rope();
+        if line >= rope.len_lines() {
+            return;
+        }
+        let start = rope.line_to_char(line);
+        let end = if line + 1 < rope.len_lines() {
+            rope.line_to_char(line + 1)
+        } else {
+            rope.len_chars()
+        };
+        // Tokens may span lines; restyle every line a dropped token touched.
+        let mut lines: Option<std::ops::Range<usize>> = None;
+        self.semantic_tokens.retain(|token| {
+            if token.range.end <= start || end <= token.range.start {
+                return true;
+            }
+            let first = rope.char_to_line(token.range.start);
+            let last = rope.char_to_line(token.range.end - 1) + 1;
+            lines = Some(lines.as_ref().map_or(first..last, |lines| {
+                lines.start.min(first)..lines.end.max(last)
+            }));
+            false
+        });
+        if let Some(lines) = lines {
+            self.mark_dirty(lines.start, lines.end);
+        }
+    }
+
+    /// Add a highlight layer composed over syntax styles when rendering.
+    ///
+    /// Replaces any layer with the same name. See [`crate::highlight::layer`]
+    /// for the composition rules; the built-in
+    /// [`SEARCH_LAYER`](crate::highlight::SEARCH_LAYER) and
+    /// [`SELECTION_LAYER`](crate::highlight::SELECTION_LAYER) always exist
+    /// unless removed.
+    pub fn add_highlight_layer(&mut self, name: &str, priority: u8, merge: LayerMerge) {
+        self.layers.add(name, priority, merge);
+    }
+
+    /// Replace a layer's styled char ranges. Returns `false` if the layer
+    /// does not exist.
+    pub fn set_layer_ranges(
+        &mut self,
+        name: &str,
+        ranges: Vec<(std::ops::Range<usize>, Style)>,
+    ) -> bool {
+        self.layers.set_ranges(name, ranges)
+    }
+
+    /// Highlight layers, for passing to [`TextBufferView::highlight_layers`].
+    ///
+    /// [`TextBufferView::highlight_layers`]: crate::text::TextBufferView::highlight_layers
+    #[must_use]
+    pub fn layers(&self) -> &HighlightLayers {
+        &self.layers
+    }
+
+    /// Mutable access to the highlight layers.
+    pub fn layers_mut(&mut self) -> &mut HighlightLayers {
+        &mut self.layers
+    }
+
+    /// Insert text at a char offset, shifting diagnostics, semantic tokens, and
+    /// layer ranges after it.
+    ///
+    /// Like [`rope_mut`](Self::rope_mut) edits, the caller marks the affected
+    /// lines dirty. Fails as [`RopeWrapper::insert`](crate::text::RopeWrapper::insert)
+    /// does, changing nothing.
+    pub fn insert(&mut self, char_idx: usize, text: &str) -> Result<()> {
+        self.buffer.insert(char_idx, text)?;
+        self.shift_ranges(char_idx, 0, text.chars().count());
+        Ok(())
+    }
+
+    /// Remove a char range, shifting or dropping diagnostics, semantic tokens,
+    /// and layer ranges it touches.
+    ///
+    /// Like [`rope_mut`](Self::rope_mut) edits, the caller marks the affected
+    /// lines dirty. Fails as [`RopeWrapper::remove`](crate::text::RopeWrapper::remove)
+    /// does, changing nothing.
+    pub fn remove(&mut self, range: std::ops::Range<usize>) -> Result<()> {
+        let removed = range.len();
+        let start = range.start;
+        self.buffer.remove(range)?;
+        self.shift_ranges(start, removed, 0);
+        Ok(())
+    }
+
+    fn shift_ranges(&mut self, offset: usize, removed: usize, inserted: usize) {
+        self.layers.apply_edit(offset, removed, inserted);
+        // The caller marks the edited lines dirty, which restyles them.
+        self.semantic_tokens
+            .retain_mut(|token| token.apply_edit(offset, removed, inserted));
+        if self.diagnostics.is_empty() {
+            return;
+        }
+        self.diagnostics
+            .retain_mut(|diagnostic| diagnostic.apply_edit(offset, removed, inserted));
+        self.diagnostics_dirty = true;
+    }
+
     /// Mark a range of lines as dirty.
     pub fn mark_dirty(&mut self, start: usize, end: usize) {
         if start >= end {
@@ -114,9 +338,17 @@ impl HighlightedBuffer {
 
     /// Re-tokenize dirty lines and update highlight segments.
     ///
+    /// Lines in the dirty span are always re-tokenized. Past the span,
+    /// re-tokenization continues forward only while the incoming line state
+    /// differs from the one the cached tokens were produced with (e.g. an edit
+    /// that opened or closed a multi-line string), and stops as soon as the
+    /// states converge again.
+    ///
     /// Should be called before rendering if the buffer has changed.
     pub fn update_highlighting(&mut self) {
         let Some(tokenizer) = self.tokenizer.clone() else {
+            self.apply_semantic_without_tokenizer();
+            self.apply_diagnostics_if_dirty();
             return;
         };
 
@@ -125,110 +357,158 @@ impl HighlightedBuffer {
 
         let line_tokens = &mut self.line_tokens;
         let line_states = &mut self.line_states;
-        let count_changed = line_count != line_tokens.len();
+        let line_bracket_depths = &mut self.line_bracket_depths;
 
-        if count_changed {
+        if line_count != line_tokens.len() {
             line_tokens.resize(line_count, Vec::new());
             line_states.resize(line_count, LineState::default());
+            line_bracket_depths.resize(line_count, 0);
             // Full re-tokenize if line count changed
             self.dirty_span = Some(0..line_count);
         }
 
-        let retokenize = self.dirty_span.is_some();
-        let mut start_line = if retokenize {
-            self.dirty_span.as_ref().unwrap().start
-        } else if self.theme_dirty {
+        let generation = tokenizer.generation();
+        if generation != self.tokenizer_generation {
+            // The tokenizer changed behind our back (e.g. a swapped customization)
+            self.tokenizer_generation = generation;
+            self.dirty_span = Some(0..line_count);
+        }
+
+        // A theme or semantic token change re-applies styles to every line,
+        // reusing cached tokens.
+        let restyle = self.theme_dirty || self.semantic_dirty;
+        if self.dirty_span.is_none() && !restyle {
+            self.apply_diagnostics_if_dirty();
+            return;
+        }
+        let loop_start = if restyle {
             0
         } else {
-            return;
+            self.dirty_span
+                .as_ref()
+                .map_or(0, |span| span.start.min(line_count))
         };
 
-        // Clamp start line
-        start_line = start_line.min(line_count);
-
-        // If theme changed, we need to re-apply styles even if tokens are valid
-        // But for simplicity, we treat theme dirty as a full re-process pass logic
-        // reusing tokens if not dirty.
-        // Actually, if theme changed, we might not need to re-tokenize, just re-apply highlights.
-        // But apply_line_highlights is called inside the loop.
-        // So we can just iterate.
-
-        if retokenize || self.theme_dirty {
-            // Determine end of mandatory processing
-            let mandatory_end = if retokenize {
-                self.dirty_span.as_ref().unwrap().end.min(line_count)
-            } else {
-                0
-            };
-
-            // If theme dirty, we must process EVERYTHING to update styles?
-            // Yes, apply_line_highlights uses the theme.
-            // So if theme dirty, treat as if dirty_span is full?
-            // Or just iterate all and skip tokenize if not dirty?
-            // To keep logic simple: if theme dirty, we iterate 0..line_count.
-
-            let (loop_start, loop_end) = if self.theme_dirty {
-                (0, line_count)
-            } else {
-                (start_line, line_count)
-            };
-
-            let mut state = if loop_start > 0 {
-                line_states[loop_start - 1]
-            } else {
-                LineState::Normal
-            };
+        let mut state = if loop_start > 0 {
+            line_states[loop_start - 1]
+        } else {
+            LineState::Normal
+        };
+        // True when the previous line's end state changed during this pass.
+        let mut incoming_changed = false;
+        let mut depth = if loop_start > 0 {
+            line_bracket_depths[loop_start - 1]
+        } else {
+            0
+        };
+        // Like `incoming_changed`, for rainbow bracket depth (restyle only).
+        let mut depth_changed = false;
+
+        for i in loop_start..line_count {
+            let in_dirty_span = self
+                .dirty_span
+                .as_ref()
+                .is_some_and(|span| i >= span.start && i < span.end);
+            let must_tokenize = in_dirty_span || incoming_changed;
+
+            if !must_tokenize && !restyle && !depth_changed {
+                break;
+            }
 
-            for i in loop_start..loop_end {
-                // Check if this line is in the dirty span
-                let in_dirty_span = self
-                    .dirty_span
-                    .as_ref()
-                    .is_some_and(|span| i >= span.start && i < span.end);
-
-                let new_state;
-
-                if in_dirty_span || self.theme_dirty {
-                    // Early exit: if past mandatory range, state unchanged, and theme clean
-                    if i >= mandatory_end && state == line_states[i] && !self.theme_dirty {
-                        break;
-                    }
-
-                    // Tokenize if in dirty span or state changed
-                    if i < mandatory_end || state != line_states[i] {
-                        let Some(line_str) = buffer.line(i) else {
-                            break;
-                        };
-                        let line_content = line_str.trim_end_matches(['\n', '\r']);
-                        let (tokens, ns) = tokenizer.tokenize_line(line_content, state);
-                        new_state = ns;
-                        if line_tokens[i] != tokens {
-                            line_tokens[i] = tokens;
-                        }
-                    } else {
-                        // Re-using cached tokens (state matched, not dirty content)
-                        new_state = line_states[i];
-                    }
-
-                    if line_states[i] != new_state {
-                        line_states[i] = new_state;
-                    }
-                } else {
-                    // Not in dirty span, theme not dirty.
-                    // Why are we here?
-                    // Because loop_end is line_count.
-                    // logic above handles breaking.
+            if must_tokenize {
+                let Some(line_str) = buffer.line(i) else {
                     break;
+                };
+                let line_content = line_str.trim_end_matches(['\n', '\r']);
+                let (tokens, new_state) = tokenizer.tokenize_line(line_content, state);
+                incoming_changed = line_states[i] != new_state;
+                line_states[i] = new_state;
+                if line_tokens[i] != tokens {
+                    line_tokens[i] = tokens;
                 }
+            }
 
-                // Always apply highlights if we are here (dirty, state change, or theme change)
-                Self::apply_line_highlights(buffer, &self.theme, i, &line_tokens[i]);
-                state = new_state;
+            Self::apply_line_highlights(
+                buffer,
+                &self.theme,
+                i,
+                &line_tokens[i],
+                &self.semantic_tokens,
+            );
+            if self.rainbow_brackets {
+                depth =
+                    Self::apply_rainbow_brackets(buffer, &self.theme, i, &line_tokens[i], depth);
+                depth_changed = line_bracket_depths[i] != depth;
+                line_bracket_depths[i] = depth;
             }
+            state = line_states[i];
         }
 
         self.theme_dirty = false;
+        self.semantic_dirty = false;
         self.dirty_span = None;
+        // Syntax segments were re-added after the diagnostic ones; re-add the
+        // diagnostics so they stay last and layer on top.
+        self.diagnostics_dirty |= !self.diagnostics.is_empty();
+        self.apply_diagnostics_if_dirty();
+    }
+
+    /// Without a tokenizer there are no syntax styles, but semantic tokens
+    /// still style the lines they cover.
+    fn apply_semantic_without_tokenizer(&mut self) {
+        let dirty_span = self.dirty_span.take();
+        if !self.semantic_dirty && self.semantic_tokens.is_empty() {
+            return;
+        }
+        let line_count = self.buffer.len_lines();
+        let lines = if self.semantic_dirty || line_count != self.line_tokens.len() {
+            self.line_tokens.resize(line_count, Vec::new());
+            self.line_states.resize(line_count, LineState::default());
+            self.line_bracket_depths.resize(line_count, 0);
+            0..line_count
+        } else if let Some(span) = dirty_span {
+            span.start.min(line_count)..span.end.min(line_count)
+        } else {
+            return;
+        };
+        self.semantic_dirty = false;
+        for line in lines {
+            Self::apply_line_highlights(
+                &mut self.buffer,
+                &self.theme,
+                line,
+                &[],
+                &self.semantic_tokens,
+            );
+        }
+    }
+
+    fn apply_diagnostics_if_dirty(&mut self) {
+        if !self.diagnostics_dirty {
+            return;
+        }
+        self.diagnostics_dirty = false;
+        self.buffer
+            .remove_highlights_by_ref(DIAGNOSTIC_HIGHLIGHT_REF_ID);
+
+        let len_chars = self.buffer.len_chars();
+        for diagnostic in &self.diagnostics {
+            let start = diagnostic.range.start.min(len_chars);
+            let end = diagnostic.range.end.min(len_chars);
+            if start >= end {
+                continue;
+            }
+            // Priority grows with severity so `style_at` keeps the most severe
+            // of overlapping diagnostics.
+            let priority = 1 + diagnostic.severity as u8;
+            self.buffer.add_highlight_by_char_range(
+                start,
+                end,
+                self.theme.diagnostic_style(diagnostic.severity),
+                priority,
+                Some(DIAGNOSTIC_HIGHLIGHT_REF_ID),
+            );
+        }
     }
 
     /// Get tokens for a line.
@@ -237,7 +517,72 @@ impl HighlightedBuffer {
         self.line_tokens.get(line).map_or(&[], Vec::as_slice)
     }
 
+    /// Cached tokens overlapping a char range, in buffer order, located by
+    /// the rope's line index rather than a scan from the start.
+    ///
+    /// An empty range finds the token containing its offset. Tokens are as
+    /// of the last [`update_highlighting`](Self::update_highlighting); ones
+    /// no longer fitting their line are skipped.
+    pub fn tokens_in_range(
+        &self,
+        range: std::ops::Range<usize>,
+    ) -> impl Iterator<Item = BufferToken<'_>> + '_ {
+        let rope = self.buffer.rope();
+        let end = range.end.min(rope.len_chars());
+        let start = range.start.min(end);
+        let first_line = rope.char_to_line(start);
+        let last_line = rope.char_to_line(end.saturating_sub(1).max(start));
+        (first_line..=last_line)
+            .flat_map(move |line| self.line_buffer_tokens(line))
+            .filter(move |token| {
+                token.char_start < end && start < token.char_end
+                    || (start == end && token.chars().contains(&start))
+            })
+    }
+
+    /// The cached token covering char column `col` of `line`, if any.
+    #[must_use]
+    pub fn token_at(&self, line: usize, col: usize) -> Option<BufferToken<'_>> {
+        self.line_buffer_tokens(line)
+            .find(|token| token.columns().contains(&col))
+    }
+
+    fn line_buffer_tokens(&self, line: usize) -> impl Iterator<Item = BufferToken<'_>> + '_ {
+        let rope = self.buffer.rope();
+        let (line_start, line_start_byte, line_byte_len) = if line < rope.len_lines() {
+            let line_start = rope.line_to_char(line);
+            let line_end = if line + 1 < rope.len_lines() {
+                rope.line_to_char(line + 1)
+            } else {
+                rope.len_chars()
+            };
+            let start_byte = rope.char_to_byte(line_start);
+            (
+                line_start,
+                start_byte,
+                rope.char_to_byte(line_end) - start_byte,
+            )
+        } else {
+            (0, 0, 0)
+        };
+        self.tokens_for_line(line)
+            .iter()
+            .filter(move |token| token.start <= token.end && token.end <= line_byte_len)
+            .map(move |token| BufferToken {
+                token,
+                line,
+                line_start,
+                char_start: rope.byte_to_char(line_start_byte + token.start),
+                char_end: rope.byte_to_char(line_start_byte + token.end),
+            })
+    }
+
     /// Get styled segments for a line, merging highlighting with existing styles.
+    ///
+    /// Semantic tokens replace the lexical styles they cover. Diagnostics
+    /// split token segments where they start and end; the covered parts keep
+    /// the token's foreground and take underline and background from the most
+    /// severe diagnostic.
     #[must_use]
     pub fn styled_line(&self, line: usize) -> Vec<StyledSegment> {
         let mut segments = Vec::new();
@@ -245,23 +590,65 @@ impl HighlightedBuffer {
             return segments;
         };
 
-        let line_start = self.buffer.rope().line_to_char(line);
-        let line_start_byte = self.buffer.rope().char_to_byte(line_start);
-        let line_byte_len = line_str.len();
-
-        if let Some(tokens) = self.line_tokens.get(line) {
-            for token in tokens {
-                // Validate token bounds: skip malformed tokens
-                if token.start > token.end || token.end > line_byte_len {
-                    continue;
-                }
+        let rope = self.buffer.rope();
+        let line_start = rope.line_to_char(line);
+        let line_start_byte = rope.char_to_byte(line_start);
+        let pieces = Self::line_styles(
+            rope,
+            &self.theme,
+            line,
+            self.tokens_for_line(line),
+            &self.semantic_tokens,
+        );
 
-                let style = self.theme.style_for(token.kind);
-                if *style != Style::default() {
-                    let start = line_start_byte + token.start;
-                    let end = line_start_byte + token.end;
-                    segments.push(StyledSegment::new(start..end, *style));
-                }
+        // Diagnostics clipped to this line, as line-relative byte ranges.
+        let line_end = line_start + line_str.chars().count();
+        let diagnostics: Vec<(std::ops::Range<usize>, DiagnosticSeverity)> = self
+            .diagnostics
+            .iter()
+            .filter(|d| d.range.start < line_end && line_start < d.range.end)
+            .map(|d| {
+                let start = rope.char_to_byte(d.range.start.max(line_start)) - line_start_byte;
+                let end = rope.char_to_byte(d.range.end.min(line_end)) - line_start_byte;
+                (start..end, d.severity)
+            })
+            .collect();
+
+        let mut cuts: Vec<usize> = pieces
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .chain(
+                diagnostics
+                    .iter()
+                    .flat_map(|(range, _)| [range.start, range.end]),
+            )
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for window in cuts.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let token_style = pieces
+                .iter()
+                .find(|(range, _)| range.start <= start && end <= range.end)
+                .map(|(_, style)| *style);
+            let diagnostic_style = diagnostics
+                .iter()
+                .filter(|(range, _)| range.start <= start && end <= range.end)
+                .map(|(_, severity)| *severity)
+                .max()
+                .map(|severity| self.theme.diagnostic_style(severity));
+
+            let style = match (token_style, diagnostic_style) {
+                (Some(token), Some(diagnostic)) => token.merge(diagnostic),
+                (Some(style), None) | (None, Some(style)) => style,
+                (None, None) => continue,
+            };
+            if style != Style::default() {
+                segments.push(StyledSegment::new(
+                    line_start_byte + start..line_start_byte + end,
+                    style,
+                ));
             }
         }
 
@@ -276,7 +663,10 @@ impl HighlightedBuffer {
 
     /// Get mutable access to the rope.
     ///
-    /// **Note:** Caller must call `mark_dirty` after modifications!
+    /// **Note:** Caller must call `mark_dirty` after modifications! Edits made
+    /// here do not shift diagnostics, semantic tokens, or layer ranges; use
+    /// [`insert`](Self::insert) and [`remove`](Self::remove), or set them
+    /// again afterwards.
     pub fn rope_mut(&mut self) -> &mut crate::text::RopeWrapper {
         self.buffer.rope_mut()
     }
@@ -305,7 +695,8 @@ impl HighlightedBuffer {
         self.buffer.to_string()
     }
 
-    /// Set the text content.
+    /// Set the text content. Clears diagnostics and semantic tokens, which
+    /// referred to the old text.
     pub fn set_text(&mut self, text: &str) {
         self.buffer.set_text(text);
         let line_count = self.buffer.len_lines();
@@ -313,7 +704,13 @@ impl HighlightedBuffer {
         self.line_tokens.resize(line_count, Vec::new());
         self.line_states.clear();
         self.line_states.resize(line_count, LineState::default());
+        self.line_bracket_depths.clear();
+        self.line_bracket_depths.resize(line_count, 0);
         self.dirty_span = Some(0..line_count);
+        self.diagnostics.clear();
+        self.diagnostics_dirty = true;
+        self.clear_semantic_tokens();
+        self.layers.clear_ranges();
     }
 
     fn clear_syntax_highlights(&mut self) {
@@ -326,36 +723,21 @@ impl HighlightedBuffer {
         theme: &Theme,
         line: usize,
         tokens: &[Token],
+        semantic: &[SemanticToken],
     ) {
         buffer.clear_line_highlights_by_ref(line, SYNTAX_HIGHLIGHT_REF_ID);
 
+        let pieces = Self::line_styles(buffer.rope(), theme, line, tokens, semantic);
         let line_start_char = buffer.rope().line_to_char(line);
         let line_start_byte = buffer.rope().char_to_byte(line_start_char);
 
-        // Calculate line byte length for bounds validation
-        let line_end_char = if line + 1 < buffer.rope().len_lines() {
-            buffer.rope().line_to_char(line + 1)
-        } else {
-            buffer.rope().len_chars()
-        };
-        let line_end_byte = buffer.rope().char_to_byte(line_end_char);
-        let line_byte_len = line_end_byte.saturating_sub(line_start_byte);
-
-        for token in tokens {
-            let style = theme.style_for(token.kind);
-            if *style == Style::default() {
-                continue;
-            }
-
-            // Validate token bounds: skip malformed tokens
-            if token.start > token.end || token.end > line_byte_len {
+        for (range, style) in pieces {
+            if style == Style::default() {
                 continue;
             }
 
-            let start_byte = line_start_byte + token.start;
-            let end_byte = line_start_byte + token.end;
-            let start_char = buffer.rope().byte_to_char(start_byte);
-            let end_char = buffer.rope().byte_to_char(end_byte);
+            let start_char = buffer.rope().byte_to_char(line_start_byte + range.start);
+            let end_char = buffer.rope().byte_to_char(line_start_byte + range.end);
             let col_start = start_char.saturating_sub(line_start_char);
             let col_end = end_char.saturating_sub(line_start_char);
 
@@ -367,12 +749,106 @@ impl HighlightedBuffer {
                 line,
                 col_start,
                 col_end,
-                *style,
+                style,
                 0,
                 Some(SYNTAX_HIGHLIGHT_REF_ID),
             );
         }
     }
+
+    /// Styled line-relative byte ranges for a line: the lexical tokens, cut
+    /// around the semantic tokens covering the line, plus those semantic
+    /// tokens. Sorted by start; malformed lexical tokens are skipped.
+    fn line_styles(
+        rope: &RopeWrapper,
+        theme: &Theme,
+        line: usize,
+        tokens: &[Token],
+        semantic: &[SemanticToken],
+    ) -> Vec<(std::ops::Range<usize>, Style)> {
+        if line >= rope.len_lines() {
+            return Vec::new();
+        }
+        let line_start = rope.line_to_char(line);
+        let line_end = if line + 1 < rope.len_lines() {
+            rope.line_to_char(line + 1)
+        } else {
+            rope.len_chars()
+        };
+        let line_start_byte = rope.char_to_byte(line_start);
+        let line_byte_len = rope.char_to_byte(line_end) - line_start_byte;
+
+        // Non-overlapping and sorted by start, so also sorted by end.
+        let first = semantic.partition_point(|token| token.range.end <= line_start);
+        let mut pieces: Vec<(std::ops::Range<usize>, Style)> = semantic[first..]
+            .iter()
+            .take_while(|token| token.range.start < line_end)
+            .map(|token| {
+                let start = rope.char_to_byte(token.range.start.max(line_start));
+                let end = rope.char_to_byte(token.range.end.min(line_end));
+                (
+                    start - line_start_byte..end - line_start_byte,
+                    theme.semantic_style(token.kind, token.modifiers),
+                )
+            })
+            .collect();
+        let covered: Vec<std::ops::Range<usize>> =
+            pieces.iter().map(|(range, _)| range.clone()).collect();
+
+        // Validate token bounds: skip malformed tokens
+        for token in tokens
+            .iter()
+            .filter(|token| token.start <= token.end && token.end <= line_byte_len)
+        {
+            let style = *theme.style_for(token.kind);
+            let mut start = token.start;
+            for range in &covered {
+                if range.end <= start || token.end <= range.start {
+                    continue;
+                }
+                if start < range.start {
+                    pieces.push((start..range.start, style));
+                }
+                start = range.end;
+            }
+            if start < token.end {
+                pieces.push((start..token.end, style));
+            }
+        }
+        pieces.sort_by_key(|(range, _)| range.start);
+        pieces
+    }
+
+    /// Color the line's brackets by depth, returning the depth at line end.
+    ///
+    /// Segments share the syntax ref id and are added after the token
+    /// segments, so they are cleared with them and override their foreground.
+    fn apply_rainbow_brackets(
+        buffer: &mut TextBuffer,
+        theme: &Theme,
+        line: usize,
+        tokens: &[Token],
+        depth: u32,
+    ) -> u32 {
+        let Some(text) = buffer.line(line) else {
+            return depth;
+        };
+        let brackets = code_brackets(text.trim_end_matches(['\n', '\r']), tokens);
+        let (depths, end_depth) = bracket_depths(&brackets, depth);
+        for (&(col, _), depth) in brackets.iter().zip(depths) {
+            if let Some(color) = theme.bracket_color(depth) {
+                buffer.add_highlight_line(
+                    line,
+                    col,
+                    col + 1,
+                    Style::fg(color),
+                    0,
+                    Some(SYNTAX_HIGHLIGHT_REF_ID),
+                );
+            }
+        }
+        end_depth
+    }
 }
 
 impl Default for HighlightedBuffer {
@@ -384,8 +860,12 @@ impl Default for HighlightedBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::Rgba;
+    use crate::highlight::DiagnosticSeverity;
     use crate::highlight::languages::rust::RustTokenizer;
+    use crate::highlight::semantic::SemanticModifiers;
     use crate::highlight::token::TokenKind;
+    use crate::style::TextAttributes;
 
     #[test]
     fn test_highlighted_buffer_basic() {
@@ -420,6 +900,36 @@ mod tests {
         assert_eq!(updated_style, expected);
     }
 
+    #[test]
+    fn test_customization_swap_invalidates_cache() {
+        use crate::highlight::{Customization, TokenizerRegistry};
+
+        let mut registry = TokenizerRegistry::with_builtins();
+        assert!(registry.customize("rust", Customization::default()));
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text("let ctx = 1; // TODO"));
+        buffer.set_tokenizer(registry.by_name_shared("rust"));
+        buffer.update_highlighting();
+        let kinds = |buffer: &HighlightedBuffer| -> Vec<TokenKind> {
+            buffer.tokens_for_line(0).iter().map(|t| t.kind).collect()
+        };
+        assert!(!kinds(&buffer).contains(&TokenKind::Todo));
+
+        assert!(registry.customize(
+            "rust",
+            Customization {
+                extra_keywords: vec![("ctx".to_string(), TokenKind::Constant)],
+                todo_words: vec!["TODO".to_string()],
+                case_insensitive: false,
+            },
+        ));
+        // No edit: the swap alone must trigger re-tokenization.
+        buffer.update_highlighting();
+        let kinds = kinds(&buffer);
+        assert!(kinds.contains(&TokenKind::Todo));
+        assert!(kinds.contains(&TokenKind::Constant));
+        assert!(!registry.customize("cobol", Customization::default()));
+    }
+
     #[test]
     fn test_incremental_update_single_line() {
         let mut buffer = HighlightedBuffer::new(TextBuffer::with_text("let a = 1;\nlet b = 2;"));
@@ -427,7 +937,7 @@ mod tests {
         buffer.update_highlighting();
         let tokens_before = buffer.tokens_for_line(1).to_vec();
 
-        buffer.buffer_mut().rope_mut().insert(0, "const ");
+        buffer.buffer_mut().rope_mut().insert(0, "const ").unwrap();
         buffer.mark_dirty(0, 1);
         buffer.update_highlighting();
 
@@ -435,6 +945,66 @@ mod tests {
         assert_eq!(tokens_before, tokens_after);
     }
 
+    #[test]
+    fn test_buffer_token_bytes_and_chars_agree() {
+        let source = "let 名前 = \"😀x\"; // 注釈\nfn 関数() {}";
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text(source))
+            .with_tokenizer(Box::new(RustTokenizer::new()));
+        buffer.update_highlighting();
+
+        let all: Vec<_> = buffer.tokens_in_range(0..buffer.len_chars()).collect();
+        assert!(all.len() > 6);
+        let rope = buffer.rope();
+        for token in &all {
+            let line = buffer.line(token.line).unwrap();
+            let by_bytes = &line[token.bytes()];
+            let by_chars = rope.slice(token.chars()).to_string();
+            assert_eq!(by_bytes, by_chars, "{token:?}");
+            let by_columns: String = line
+                .chars()
+                .skip(token.columns().start)
+                .take(token.columns().len())
+                .collect();
+            assert_eq!(by_bytes, by_columns);
+        }
+
+        let string = buffer.token_at(0, 9).unwrap();
+        assert_eq!(string.kind, TokenKind::String);
+        assert_eq!((string.bytes(), string.chars()), (13..20, 9..13));
+        let function = buffer.token_at(1, 4).unwrap();
+        assert_eq!(function.kind, TokenKind::Function);
+        assert_eq!(&buffer.line(1).unwrap()[function.bytes()], "関数");
+        assert!(buffer.token_at(5, 0).is_none());
+    }
+
+    #[test]
+    fn test_tokens_in_range_crosses_lines_in_order() {
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text("let a = 1;\nlet b = 2;"))
+            .with_tokenizer(Box::new(RustTokenizer::new()));
+        buffer.update_highlighting();
+
+        // From `1;` on line 0 through `let b` on line 1.
+        let tokens: Vec<_> = buffer
+            .tokens_in_range(8..16)
+            .map(|token| (token.line, token.kind, token.chars()))
+            .collect();
+        let lines: Vec<usize> = tokens.iter().map(|(line, _, _)| *line).collect();
+        assert!(lines.contains(&0) && lines.contains(&1), "{tokens:?}");
+        assert!(lines.is_sorted());
+        assert!(
+            tokens
+                .windows(2)
+                .all(|pair| pair[0].2.start < pair[1].2.start)
+        );
+        assert_eq!(tokens.first().unwrap().1, TokenKind::Number);
+        assert_eq!(tokens.last().unwrap().2, 15..16);
+
+        // An empty range finds the token around it.
+        let at: Vec<_> = buffer.tokens_in_range(12..12).collect();
+        assert_eq!(at.len(), 1);
+        assert_eq!((at[0].line, at[0].kind), (1, TokenKind::Keyword));
+    }
+
     /// Mock tokenizer that produces malformed tokens for testing bounds validation.
     struct MalformedTokenizer;
 
@@ -492,4 +1062,217 @@ mod tests {
             "Only valid tokens should produce segments"
         );
     }
+
+    #[test]
+    fn test_diagnostics_layer_over_half_covered_token() {
+        // The string token spans bytes 8..15; the error covers its first
+        // three chars, the warning overlaps it and runs past the token.
+        let source = "let s = \"hello\"; x";
+        let theme = Theme::dark();
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text(source))
+            .with_tokenizer(Box::new(RustTokenizer::new()))
+            .with_theme(theme.clone());
+        buffer.set_diagnostics(vec![
+            Diagnostic::new(10..17, DiagnosticSeverity::Warning, "unused"),
+            Diagnostic::new(8..11, DiagnosticSeverity::Error, "bad literal"),
+        ]);
+        buffer.update_highlighting();
+
+        let string = *theme.style_for(TokenKind::String);
+        let error = theme.diagnostic_style(DiagnosticSeverity::Error);
+        let warning = theme.diagnostic_style(DiagnosticSeverity::Warning);
+        let base = theme.default_style();
+
+        let styles: Vec<Style> = [8, 10, 12, 15, 17]
+            .into_iter()
+            .map(|pos| buffer.buffer().style_at(pos))
+            .collect();
+        assert_eq!(styles[0], base.merge(string).merge(error));
+        assert_eq!(styles[1], base.merge(string).merge(error));
+        assert_eq!(styles[2], base.merge(string).merge(warning));
+        assert_eq!(styles[3], base.merge(warning));
+        assert_eq!(styles[4], base);
+        for style in &styles[..3] {
+            assert_eq!(style.fg, string.fg, "syntax foreground is kept");
+        }
+        for style in &styles[..4] {
+            assert!(style.attributes.contains(TextAttributes::UNDERLINE));
+        }
+
+        let segments = buffer.styled_line(0);
+        let style_of = |range: std::ops::Range<usize>| {
+            segments
+                .iter()
+                .find(|seg| seg.range == range)
+                .map(|seg| seg.style)
+        };
+        assert_eq!(style_of(8..10), Some(string.merge(error)));
+        assert_eq!(style_of(10..11), Some(string.merge(error)));
+        assert_eq!(style_of(11..15), Some(string.merge(warning)));
+        assert_eq!(style_of(16..17), Some(warning));
+
+        let hover = buffer.diagnostic_at(0, 10).expect("diagnostic at col 10");
+        assert_eq!(hover.severity, DiagnosticSeverity::Error);
+        assert_eq!(buffer.diagnostic_at(0, 13).unwrap().message, "unused");
+        assert!(buffer.diagnostic_at(0, 18).is_none());
+    }
+
+    #[test]
+    fn test_edits_shift_diagnostics() {
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text("ab\ncd bad\n"))
+            .with_tokenizer(Box::new(RustTokenizer::new()));
+        buffer.set_diagnostics(vec![Diagnostic::new(6..9, DiagnosticSeverity::Error, "e")]);
+        buffer.update_highlighting();
+        assert!(buffer.diagnostic_at(1, 3).is_some());
+
+        // Insert a line above: the diagnostic moves down with its text.
+        buffer.insert(0, "new\n").unwrap();
+        buffer.mark_dirty(0, 2);
+        buffer.update_highlighting();
+        assert_eq!(buffer.diagnostics()[0].range, 10..13);
+        assert!(buffer.diagnostic_at(1, 3).is_none());
+        assert!(buffer.diagnostic_at(2, 3).is_some());
+        let bad = buffer.buffer().rope().char_to_byte(10);
+        assert!(
+            buffer
+                .buffer()
+                .style_at(bad)
+                .attributes
+                .contains(TextAttributes::UNDERLINE)
+        );
+
+        // Deleting the flagged text drops the diagnostic.
+        buffer.remove(10..13).unwrap();
+        buffer.mark_dirty(2, 3);
+        buffer.update_highlighting();
+        assert!(buffer.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_rope_edits_leave_diagnostics_in_place() {
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text("ab\ncd bad\n"));
+        buffer.set_diagnostics(vec![Diagnostic::new(6..9, DiagnosticSeverity::Error, "e")]);
+
+        // Untracked edits keep the old offsets until diagnostics are reset.
+        buffer.rope_mut().insert(0, "new\n").unwrap();
+        buffer.mark_dirty(0, 2);
+        assert_eq!(buffer.diagnostics()[0].range, 6..9);
+
+        buffer.set_diagnostics(vec![Diagnostic::new(
+            10..13,
+            DiagnosticSeverity::Error,
+            "e",
+        )]);
+        buffer.update_highlighting();
+        assert!(buffer.diagnostic_at(2, 3).is_some());
+    }
+
+    fn describe_segments(buffer: &HighlightedBuffer) -> String {
+        use std::fmt::Write;
+
+        let text = buffer.to_string();
+        let mut out = String::new();
+        for line in 0..buffer.len_lines() {
+            for segment in buffer.styled_line(line) {
+                writeln!(
+                    out,
+                    "{line}:{:?} {:?} {} {:?}",
+                    segment.range,
+                    &text[segment.range.clone()],
+                    segment.style.fg.map_or_else(String::new, Rgba::to_hex),
+                    segment.style.attributes,
+                )
+                .unwrap();
+            }
+        }
+        out
+    }
+
+    fn semantic_buffer() -> HighlightedBuffer {
+        let source = "fn main() {\n    let mut total_count = 0;\n    total_count += 1;\n}\n";
+        let theme = Theme::dark()
+            .with_style(TokenKind::Identifier, Style::fg(Rgba::WHITE))
+            .with_style(TokenKind::Constant, Style::fg(Rgba::GREEN))
+            .with_style(TokenKind::Function, Style::fg(Rgba::BLUE));
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text(source))
+            .with_tokenizer(Box::new(RustTokenizer::new()))
+            .with_theme(theme);
+        // `total` of the declaration, splitting the `total_count` identifier,
+        // and the whole `total_count` of the use.
+        let declaration = source.find("total").unwrap();
+        let usage = source.rfind("total_count").unwrap();
+        buffer.set_semantic_tokens(vec![
+            SemanticToken::new(
+                usage..usage + 11,
+                TokenKind::Function,
+                SemanticModifiers::DEPRECATED,
+            ),
+            SemanticToken::new(
+                declaration..declaration + 5,
+                TokenKind::Constant,
+                SemanticModifiers::MUTABLE | SemanticModifiers::DECLARATION,
+            ),
+        ]);
+        buffer.update_highlighting();
+        buffer
+    }
+
+    #[test]
+    fn test_semantic_tokens_split_lexical_token() {
+        let mut buffer = semantic_buffer();
+        insta::assert_snapshot!("semantic_before_edit", describe_segments(&buffer));
+
+        let total = buffer.to_string().find("total").unwrap();
+        let constant = Style::fg(Rgba::GREEN).with_underline();
+        let base = buffer.theme().default_style();
+        assert_eq!(buffer.buffer().style_at(total), base.merge(constant));
+        assert_eq!(
+            buffer.buffer().style_at(total + 5),
+            base.merge(Style::fg(Rgba::WHITE)),
+            "the rest of the identifier keeps its lexical style"
+        );
+
+        // An edit above moves the overlay with its text.
+        buffer.insert(0, "// header\n").unwrap();
+        buffer.mark_dirty(0, 2);
+        buffer.update_highlighting();
+        assert_eq!(buffer.semantic_tokens()[0].range, total + 10..total + 15);
+        insta::assert_snapshot!("semantic_after_edit", describe_segments(&buffer));
+        assert_eq!(buffer.buffer().style_at(total + 10), base.merge(constant));
+    }
+
+    #[test]
+    fn test_semantic_tokens_clear_and_invalidate() {
+        let mut buffer = semantic_buffer();
+        let text = buffer.to_string();
+        let declaration = text.find("total").unwrap();
+        let usage = text.rfind("total_count").unwrap();
+        let base = buffer.theme().default_style();
+        let lexical = base.merge(Style::fg(Rgba::WHITE));
+
+        buffer.invalidate_semantic_line(1);
+        buffer.update_highlighting();
+        assert_eq!(buffer.semantic_tokens().len(), 1);
+        assert_eq!(buffer.buffer().style_at(declaration), lexical);
+        assert_ne!(buffer.buffer().style_at(usage), lexical);
+
+        buffer.clear_semantic_tokens();
+        buffer.update_highlighting();
+        assert!(buffer.semantic_tokens().is_empty());
+        assert_eq!(buffer.buffer().style_at(usage), lexical);
+
+        // Without a tokenizer the overlay alone styles the text.
+        buffer.set_tokenizer(None);
+        buffer.set_semantic_tokens(vec![SemanticToken::new(
+            usage..usage + 5,
+            TokenKind::Function,
+            SemanticModifiers::empty(),
+        )]);
+        buffer.update_highlighting();
+        assert_eq!(
+            buffer.buffer().style_at(usage),
+            base.merge(Style::fg(Rgba::BLUE))
+        );
+        assert_eq!(buffer.buffer().style_at(usage + 5), base);
+    }
 }
@@ -0,0 +1,291 @@
+use crate::highlight::token::{Token, TokenKind};
+use crate::highlight::tokenizer::{LineState, Tokenizer};
+
+pub struct DiffTokenizer;
+
+impl Default for DiffTokenizer {
+    fn default() -> Self {
+        Self
+    }
+}
+
+/// Git extended header keywords, longest first so prefixes don't shadow them.
+const GIT_HEADERS: &[&str] = &[
+    "deleted file mode",
+    "new file mode",
+    "similarity index",
+    "dissimilarity index",
+    "rename from",
+    "rename to",
+    "copy from",
+    "copy to",
+    "old mode",
+    "new mode",
+    "index",
+];
+
+impl DiffTokenizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse a hunk range (`-12,3` / `+7`) into its line count.
+    fn range_count(range: &str) -> Option<u32> {
+        let digits = range.get(1..)?;
+        let Some((start, count)) = digits.split_once(',') else {
+            digits.parse::<u32>().ok()?;
+            return Some(1);
+        };
+        start.parse::<u32>().ok()?;
+        count.parse().ok()
+    }
+
+    /// Tokenize `@@ -a,b +c,d @@ section` and return the hunk body state.
+    fn hunk_header(line: &str, tokens: &mut Vec<Token>) -> LineState {
+        tokens.push(Token::new(TokenKind::DiffHunk, 0, 2));
+
+        let Some(close) = line[2..].find("@@").map(|idx| idx + 2) else {
+            if line.len() > 2 {
+                tokens.push(Token::new(TokenKind::Text, 2, line.len()));
+            }
+            return LineState::Normal;
+        };
+
+        let mut old = None;
+        let mut new = None;
+        let mut pos = 2;
+        for range in line[2..close].split(' ') {
+            let start = pos;
+            pos += range.len() + 1;
+            if range.is_empty() {
+                continue;
+            }
+            match range.as_bytes()[0] {
+                b'-' => old = Self::range_count(range),
+                b'+' => new = Self::range_count(range),
+                _ => {}
+            }
+            tokens.push(Token::new(TokenKind::Number, start, start + range.len()));
+        }
+
+        tokens.push(Token::new(TokenKind::DiffHunk, close, close + 2));
+        let section = line[close + 2..].trim_start();
+        if !section.is_empty() {
+            let start = line.len() - section.len();
+            tokens.push(Token::new(TokenKind::Function, start, line.len()));
+        }
+
+        match (old, new) {
+            (Some(0), Some(0)) => LineState::Normal,
+            (Some(old), Some(new)) => LineState::InDiffHunk { old, new },
+            // Malformed ranges: treat the body as open-ended until the next header.
+            _ => LineState::InDiffHunk {
+                old: u32::MAX,
+                new: u32::MAX,
+            },
+        }
+    }
+
+    /// Tokenize a header line (outside any hunk body).
+    fn header_line(line: &str, tokens: &mut Vec<Token>) -> LineState {
+        if line.starts_with("@@") {
+            return Self::hunk_header(line, tokens);
+        }
+
+        if let Some(rest) = line.strip_prefix("diff ") {
+            let keyword_end = line.len() - rest.len() - 1;
+            tokens.push(Token::new(TokenKind::Keyword, 0, keyword_end));
+            let mut pos = keyword_end + 1;
+            for word in rest.split(' ') {
+                if !word.is_empty() {
+                    let kind = if word.starts_with('-') {
+                        TokenKind::Attribute
+                    } else {
+                        TokenKind::DiffFile
+                    };
+                    tokens.push(Token::new(kind, pos, pos + word.len()));
+                }
+                pos += word.len() + 1;
+            }
+            return LineState::Normal;
+        }
+
+        if line.starts_with("--- ") || line.starts_with("+++ ") {
+            tokens.push(Token::new(TokenKind::DiffFile, 0, line.len()));
+            return LineState::Normal;
+        }
+
+        if let Some(keyword) = GIT_HEADERS.iter().find(|keyword| {
+            line.strip_prefix(**keyword)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        }) {
+            tokens.push(Token::new(TokenKind::Keyword, 0, keyword.len()));
+            let value = line[keyword.len()..].trim_start();
+            if !value.is_empty() {
+                let start = line.len() - value.len();
+                let kind = if keyword.ends_with("from") || keyword.ends_with("to") {
+                    TokenKind::DiffFile
+                } else {
+                    TokenKind::Constant
+                };
+                tokens.push(Token::new(kind, start, line.len()));
+            }
+            return LineState::Normal;
+        }
+
+        if line.starts_with("Binary files ") {
+            tokens.push(Token::new(TokenKind::Comment, 0, line.len()));
+            return LineState::Normal;
+        }
+
+        // Bare `+`/`-` lines outside a hunk still read as changes (e.g. snippets
+        // pasted without headers); anything else is commit message or prose.
+        let kind = match line.as_bytes().first() {
+            Some(b'+') => TokenKind::DiffAdd,
+            Some(b'-') if line != "---" => TokenKind::DiffRemove,
+            Some(b'\\') => TokenKind::Comment,
+            _ => TokenKind::Text,
+        };
+        if !line.is_empty() {
+            tokens.push(Token::new(kind, 0, line.len()));
+        }
+        LineState::Normal
+    }
+}
+
+impl Tokenizer for DiffTokenizer {
+    fn name(&self) -> &'static str {
+        "Diff"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["diff", "patch"]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["patch", "udiff"]
+    }
+
+    fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+        let mut tokens = Vec::new();
+
+        let LineState::InDiffHunk { mut old, mut new } = state else {
+            let next = Self::header_line(line, &mut tokens);
+            return (tokens, next);
+        };
+
+        // A new header always wins, even if the previous hunk's counts were off.
+        if line.starts_with("@@") || line.starts_with("diff ") {
+            let next = Self::header_line(line, &mut tokens);
+            return (tokens, next);
+        }
+
+        // The first byte alone decides the line's kind; `+`/`-` later in the
+        // line is just content.
+        let kind = match line.as_bytes().first() {
+            Some(b'+') => {
+                new = new.saturating_sub(1);
+                TokenKind::DiffAdd
+            }
+            Some(b'-') => {
+                old = old.saturating_sub(1);
+                TokenKind::DiffRemove
+            }
+            // "\ No newline at end of file" applies to the previous line.
+            Some(b'\\') => TokenKind::Comment,
+            Some(b' ') | None => {
+                old = old.saturating_sub(1);
+                new = new.saturating_sub(1);
+                TokenKind::Text
+            }
+            Some(_) => {
+                let next = Self::header_line(line, &mut tokens);
+                return (tokens, next);
+            }
+        };
+        if !line.is_empty() {
+            tokens.push(Token::new(kind, 0, line.len()));
+        }
+
+        let next = if old == 0 && new == 0 {
+            LineState::Normal
+        } else {
+            LineState::InDiffHunk { old, new }
+        };
+        (tokens, next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(line: &str, state: LineState) -> (Vec<(TokenKind, &str)>, LineState) {
+        let (tokens, next) = DiffTokenizer::new().tokenize_line(line, state);
+        let spans = tokens
+            .iter()
+            .map(|token| (token.kind, &line[token.range()]))
+            .collect();
+        (spans, next)
+    }
+
+    #[test]
+    fn test_diff_hunk_header_ranges() {
+        let (tokens, state) = spans("@@ -10,3 +10,4 @@ fn main() {", LineState::Normal);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::DiffHunk, "@@"),
+                (TokenKind::Number, "-10,3"),
+                (TokenKind::Number, "+10,4"),
+                (TokenKind::DiffHunk, "@@"),
+                (TokenKind::Function, "fn main() {"),
+            ]
+        );
+        assert_eq!(state, LineState::InDiffHunk { old: 3, new: 4 });
+    }
+
+    #[test]
+    fn test_diff_single_line_range_defaults_to_one() {
+        let (_, state) = spans("@@ -1 +0,0 @@", LineState::Normal);
+        assert_eq!(state, LineState::InDiffHunk { old: 1, new: 0 });
+    }
+
+    #[test]
+    fn test_diff_hunk_counts_decide_when_headers_resume() {
+        let state = LineState::InDiffHunk { old: 1, new: 1 };
+        // A removed line whose content starts with "-- " is still a removal.
+        let (tokens, state) = spans("--- not a header", state);
+        assert_eq!(tokens, vec![(TokenKind::DiffRemove, "--- not a header")]);
+        assert_eq!(state, LineState::InDiffHunk { old: 0, new: 1 });
+
+        let (tokens, state) = spans("+++ not a header", state);
+        assert_eq!(tokens, vec![(TokenKind::DiffAdd, "+++ not a header")]);
+        assert_eq!(state, LineState::Normal);
+
+        let (tokens, _) = spans("--- a/next.rs", state);
+        assert_eq!(tokens, vec![(TokenKind::DiffFile, "--- a/next.rs")]);
+    }
+
+    #[test]
+    fn test_diff_git_extended_headers() {
+        let (tokens, _) = spans("rename from src/old.rs", LineState::Normal);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "rename from"),
+                (TokenKind::DiffFile, "src/old.rs"),
+            ]
+        );
+
+        let (tokens, _) = spans("index 3b18e51..a9c5e2f 100644", LineState::Normal);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "index"),
+                (TokenKind::Constant, "3b18e51..a9c5e2f 100644"),
+            ]
+        );
+    }
+}
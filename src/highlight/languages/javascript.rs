@@ -550,7 +550,7 @@ impl Tokenizer for JavaScriptTokenizer {
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' || c == '.' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -565,7 +565,7 @@ impl Tokenizer for JavaScriptTokenizer {
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -637,11 +637,11 @@ impl Tokenizer for JavaScriptTokenizer {
 
                 c if c.is_alphabetic() || c == '_' => {
                     let start = idx;
-                    let mut end = idx + 1;
+                    let mut end = idx + ch.len_utf8();
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -710,7 +710,7 @@ impl Tokenizer for JavaScriptTokenizer {
                 }
 
                 _ => {
-                    tokens.push(Token::new(TokenKind::Text, idx, idx + 1));
+                    tokens.push(Token::new(TokenKind::Text, idx, idx + ch.len_utf8()));
                     can_start_regex = false;
                 }
             }
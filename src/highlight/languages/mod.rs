@@ -1,9 +1,12 @@
+pub mod diff;
 pub mod javascript;
 pub mod json;
 pub mod markdown;
 pub mod python;
 pub mod rust;
+pub mod shell;
 pub mod toml;
+pub mod yaml;
 
 #[cfg(test)]
 mod tests;
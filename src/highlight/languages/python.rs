@@ -39,126 +39,316 @@ impl PythonTokenizer {
         }
     }
 
-    fn parse_prefixed_string_start(line: &str, idx: usize) -> Option<(usize, char, bool, bool)> {
-        let bytes = line.as_bytes();
-        if idx >= bytes.len() {
-            return None;
+    fn builtin_kind(word: &str) -> Option<TokenKind> {
+        match word {
+            "bool" | "bytearray" | "bytes" | "classmethod" | "complex" | "dict" | "float"
+            | "frozenset" | "int" | "list" | "memoryview" | "object" | "property" | "range"
+            | "set" | "slice" | "staticmethod" | "str" | "tuple" | "type" => {
+                Some(TokenKind::KeywordType)
+            }
+
+            "abs" | "aiter" | "all" | "anext" | "any" | "ascii" | "bin" | "breakpoint"
+            | "callable" | "chr" | "compile" | "delattr" | "dir" | "divmod" | "enumerate"
+            | "eval" | "exec" | "filter" | "format" | "getattr" | "globals" | "hasattr"
+            | "hash" | "help" | "hex" | "id" | "input" | "isinstance" | "issubclass" | "iter"
+            | "len" | "locals" | "map" | "max" | "min" | "next" | "oct" | "open" | "ord"
+            | "pow" | "print" | "repr" | "reversed" | "round" | "setattr" | "sorted" | "sum"
+            | "super" | "vars" | "zip" | "__import__" => Some(TokenKind::Function),
+
+            "NotImplemented" | "Ellipsis" | "__name__" | "__file__" | "__doc__" => {
+                Some(TokenKind::Constant)
+            }
+            _ => None,
         }
+    }
 
+    /// Parse a string prefix (`r`, `b`, `f`, `rb`, ...) followed by a quote.
+    /// Returns the prefix length and the string shape.
+    fn parse_prefixed_string_start(line: &str, idx: usize) -> Option<(usize, StringSpec)> {
+        let bytes = line.as_bytes();
         let mut pos = idx;
         let mut raw = false;
-        let mut saw_prefix = false;
-
-        while pos < bytes.len() {
-            let lower = bytes[pos].to_ascii_lowercase();
-            if matches!(lower, b'r' | b'u' | b'b' | b'f') {
-                saw_prefix = true;
-                if lower == b'r' {
-                    raw = true;
-                }
-                pos += 1;
-            } else {
-                break;
+        let mut format = false;
+
+        while pos < bytes.len() && pos - idx < 2 {
+            match bytes[pos].to_ascii_lowercase() {
+                b'r' => raw = true,
+                b'f' => format = true,
+                b'u' | b'b' => {}
+                _ => break,
             }
+            pos += 1;
         }
 
-        if !saw_prefix || pos >= bytes.len() {
+        if pos == idx || pos >= bytes.len() {
             return None;
         }
 
         let quote = bytes[pos];
-        if quote == b'\'' || quote == b'"' {
-            let triple =
-                pos + 2 < bytes.len() && bytes[pos + 1] == quote && bytes[pos + 2] == quote;
-            Some((pos - idx, quote as char, triple, raw))
-        } else {
-            None
+        if quote != b'\'' && quote != b'"' {
+            return None;
         }
+        let spec = StringSpec {
+            quote,
+            triple: Self::is_triple_at(bytes, pos, quote),
+            raw,
+            format,
+        };
+        Some((pos - idx, spec))
     }
 
-    fn scan_string_body(
+    fn is_triple_at(bytes: &[u8], pos: usize, quote: u8) -> bool {
+        pos + 2 < bytes.len() && bytes[pos + 1] == quote && bytes[pos + 2] == quote
+    }
+
+    /// Scan a string body starting at `from` (just past the opening quotes).
+    ///
+    /// Plain strings produce a single `String` token starting at `token_start`.
+    /// Format strings split out `{{`/`}}` escapes and tokenize `{...}` fields
+    /// as code. Returns (end, closed).
+    fn scan_string(
+        &self,
         line: &str,
-        chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
-        quote: char,
-        triple: bool,
-        raw: bool,
+        token_start: usize,
+        from: usize,
+        spec: StringSpec,
+        tokens: &mut Vec<Token>,
     ) -> (usize, bool) {
-        let mut end_idx = 0;
-        let mut found_end = false;
-        let mut escaped = false;
-        let triple_seq = if quote == '\'' { "'''" } else { "\"\"\"" };
+        let bytes = line.as_bytes();
+        let mut segment = token_start;
+        let mut pos = from;
 
-        while let Some((idx, ch)) = chars.next() {
-            end_idx = idx + ch.len_utf8();
-
-            if triple {
-                if ch == quote && line[idx..].starts_with(triple_seq) {
-                    chars.next();
-                    chars.next();
-                    end_idx = idx + 3;
-                    found_end = true;
-                    break;
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            if b == b'\\' {
+                // Raw strings keep the backslash, but it still shields the quote.
+                let end = Self::escape_end(line, pos);
+                if !spec.raw {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, end));
+                    segment = end;
                 }
+                pos = end;
                 continue;
             }
-
-            if !raw {
-                if escaped {
-                    escaped = false;
-                    continue;
+            if b == spec.quote && (!spec.triple || Self::is_triple_at(bytes, pos, b)) {
+                let end = pos + if spec.triple { 3 } else { 1 };
+                tokens.push(Token::new(TokenKind::String, segment, end));
+                return (end, true);
+            }
+            if spec.format && (b == b'{' || b == b'}') {
+                if segment < pos {
+                    tokens.push(Token::new(TokenKind::String, segment, pos));
                 }
-                if ch == '\\' {
-                    escaped = true;
-                    continue;
+                if bytes.get(pos + 1) == Some(&b) {
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, pos + 2));
+                    pos += 2;
+                } else if b == b'{' {
+                    pos = self.scan_format_field(line, pos, tokens);
+                } else {
+                    tokens.push(Token::new(TokenKind::Error, pos, pos + 1));
+                    pos += 1;
                 }
+                segment = pos;
+                continue;
             }
+            pos += 1;
+        }
 
-            if ch == quote {
-                found_end = true;
-                break;
+        if segment < bytes.len() {
+            tokens.push(Token::new(TokenKind::String, segment, bytes.len()));
+        }
+        (bytes.len(), false)
+    }
+
+    /// End of the escape sequence whose backslash is at `pos`: the next
+    /// character, plus the digits of a `\x`, `\u`, `\U`, or octal escape.
+    fn escape_end(line: &str, pos: usize) -> usize {
+        let bytes = line.as_bytes();
+        let mut end = pos + 1 + line[pos + 1..].chars().next().map_or(0, char::len_utf8);
+        let (digits, is_digit): (usize, fn(&u8) -> bool) = match bytes.get(pos + 1) {
+            Some(b'x') => (2, u8::is_ascii_hexdigit),
+            Some(b'u') => (4, u8::is_ascii_hexdigit),
+            Some(b'U') => (8, u8::is_ascii_hexdigit),
+            Some(b'0'..=b'7') => (2, |b| (b'0'..=b'7').contains(b)),
+            _ => (0, u8::is_ascii_hexdigit),
+        };
+        for _ in 0..digits {
+            if bytes.get(end).is_some_and(is_digit) {
+                end += 1;
             }
         }
+        end
+    }
+
+    /// Tokenize a replacement field `{expr!conv:spec}` starting at the `{`.
+    /// Returns the position just past the closing `}` (or the line end).
+    fn scan_format_field(&self, line: &str, open: usize, tokens: &mut Vec<Token>) -> usize {
+        let bytes = line.as_bytes();
+        tokens.push(Token::new(TokenKind::Punctuation, open, open + 1));
+
+        let expr_start = open + 1;
+        let expr_end = Self::find_field_expr_end(bytes, expr_start);
+        let (expr_tokens, _) = self.tokenize_line(&line[expr_start..expr_end], LineState::Normal);
+        tokens.extend(
+            expr_tokens
+                .into_iter()
+                .map(|t| Token::new(t.kind, t.start + expr_start, t.end + expr_start)),
+        );
 
-        if !found_end {
-            end_idx = line.len();
+        let mut pos = expr_end;
+        if pos < bytes.len() && bytes[pos] == b'!' {
+            let mut end = pos + 1;
+            while end < bytes.len() && bytes[end].is_ascii_alphabetic() {
+                end += 1;
+            }
+            tokens.push(Token::new(TokenKind::Operator, pos, end));
+            pos = end;
+        }
+
+        if pos < bytes.len() && bytes[pos] == b':' {
+            tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+            pos += 1;
+            let mut segment = pos;
+            while pos < bytes.len() && bytes[pos] != b'}' {
+                if bytes[pos] == b'{' {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    pos = self.scan_format_field(line, pos, tokens);
+                    segment = pos;
+                    continue;
+                }
+                pos += 1;
+            }
+            if segment < pos {
+                tokens.push(Token::new(TokenKind::String, segment, pos));
+            }
+        }
+
+        if pos < bytes.len() && bytes[pos] == b'}' {
+            tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Find where the expression part of a replacement field ends: the first
+    /// top-level `}`, `:`, or `!` conversion, skipping brackets and strings.
+    fn find_field_expr_end(bytes: &[u8], from: usize) -> usize {
+        let mut depth = 0usize;
+        let mut pos = from;
+
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                b'}' | b':' if depth == 0 => return pos,
+                b'}' => depth -= 1,
+                b'!' if depth == 0 && bytes.get(pos + 1) != Some(&b'=') => return pos,
+                quote @ (b'\'' | b'"') => {
+                    pos += 1;
+                    while pos < bytes.len() && bytes[pos] != quote {
+                        pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                _ => {}
+            }
+            pos += 1;
         }
 
-        (end_idx, found_end)
+        bytes.len()
     }
 
-    fn scan_any_triple_end(
+    /// Scan a string literal starting at `start` (prefix included) and advance
+    /// `chars` past it. Returns the continuation state if it runs off the line.
+    fn push_string(
+        &self,
         line: &str,
+        start: usize,
+        prefix_len: usize,
+        spec: StringSpec,
         chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
-    ) -> (usize, bool) {
-        let mut end_idx = 0;
-        let mut found_end = false;
+        tokens: &mut Vec<Token>,
+    ) -> Option<LineState> {
+        let from = start + prefix_len + if spec.triple { 3 } else { 1 };
+        let (end, closed) = self.scan_string(line, start, from, spec, tokens);
+        while chars.peek().is_some_and(|&(i, _)| i < end) {
+            chars.next();
+        }
+        (!closed).then(|| Self::string_state(spec))
+    }
 
-        while let Some((idx, ch)) = chars.next() {
-            end_idx = idx + ch.len_utf8();
-            if ch == '\'' && line[idx..].starts_with("'''") {
-                chars.next();
-                chars.next();
-                end_idx = idx + 3;
-                found_end = true;
-                break;
+    fn string_state(spec: StringSpec) -> LineState {
+        if spec.raw {
+            let mut flags = 0;
+            if spec.quote == b'\'' {
+                flags |= RAW_SINGLE;
             }
-            if ch == '"' && line[idx..].starts_with("\"\"\"") {
-                chars.next();
-                chars.next();
-                end_idx = idx + 3;
-                found_end = true;
-                break;
+            if spec.triple {
+                flags |= RAW_TRIPLE;
             }
+            if spec.format {
+                flags |= RAW_FORMAT;
+            }
+            return LineState::InRawString(flags);
         }
+        let kind = match (spec.triple, spec.format, spec.quote) {
+            (true, true, b'"') => StringKind::FormatTriple,
+            (true, true, _) => StringKind::FormatTripleSingle,
+            (true, false, b'"') => StringKind::Triple,
+            (true, false, _) => StringKind::TripleSingle,
+            (false, _, b'"') => StringKind::Double,
+            (false, _, _) => StringKind::Single,
+        };
+        LineState::InString(kind)
+    }
 
-        if !found_end {
-            end_idx = line.len();
-        }
-
-        (end_idx, found_end)
+    /// The string a line starting in `state` continues, if any.
+    fn resume_spec(state: LineState) -> Option<StringSpec> {
+        let (quote, triple, format) = match state {
+            LineState::InString(StringKind::Double) => (b'"', false, false),
+            LineState::InString(StringKind::Single) => (b'\'', false, false),
+            LineState::InString(StringKind::Triple) => (b'"', true, false),
+            LineState::InString(StringKind::TripleSingle) => (b'\'', true, false),
+            LineState::InString(StringKind::FormatTriple) => (b'"', true, true),
+            LineState::InString(StringKind::FormatTripleSingle) => (b'\'', true, true),
+            LineState::InRawString(flags) => {
+                return Some(StringSpec {
+                    quote: if flags & RAW_SINGLE == 0 { b'"' } else { b'\'' },
+                    triple: flags & RAW_TRIPLE != 0,
+                    raw: true,
+                    format: flags & RAW_FORMAT != 0,
+                });
+            }
+            _ => return None,
+        };
+        Some(StringSpec {
+            quote,
+            triple,
+            raw: false,
+            format,
+        })
     }
 }
 
+/// Raw strings carry on in [`LineState::InRawString`], whose byte holds
+/// these flags since [`StringKind`] has no raw variants.
+const RAW_SINGLE: u8 = 1;
+const RAW_TRIPLE: u8 = 2;
+const RAW_FORMAT: u8 = 4;
+
+/// Shape of a Python string literal.
+#[derive(Clone, Copy, Debug)]
+struct StringSpec {
+    quote: u8,
+    triple: bool,
+    raw: bool,
+    format: bool,
+}
+
 impl Tokenizer for PythonTokenizer {
     fn name(&self) -> &'static str {
         "Python"
@@ -174,41 +364,18 @@ impl Tokenizer for PythonTokenizer {
         let mut chars = line.char_indices().peekable();
         let mut line_start = true;
         let mut resumed = false;
+        // Set after `def`/`class` so the next name is tagged as a definition.
+        let mut definition = None;
 
-        match state {
-            LineState::InString(StringKind::Double) => {
-                let (end_idx, found_end) =
-                    Self::scan_string_body(line, &mut chars, '"', false, false);
-                if found_end {
-                    tokens.push(Token::new(TokenKind::String, 0, end_idx));
-                    resumed = true;
-                } else {
-                    tokens.push(Token::new(TokenKind::String, 0, line.len()));
-                    return (tokens, LineState::InString(StringKind::Double));
-                }
+        if let Some(spec) = Self::resume_spec(state) {
+            let (end, closed) = self.scan_string(line, 0, 0, spec, &mut tokens);
+            if !closed {
+                return (tokens, state);
             }
-            LineState::InString(StringKind::Single) => {
-                let (end_idx, found_end) =
-                    Self::scan_string_body(line, &mut chars, '\'', false, false);
-                if found_end {
-                    tokens.push(Token::new(TokenKind::String, 0, end_idx));
-                    resumed = true;
-                } else {
-                    tokens.push(Token::new(TokenKind::String, 0, line.len()));
-                    return (tokens, LineState::InString(StringKind::Single));
-                }
-            }
-            LineState::InString(StringKind::Triple) => {
-                let (end_idx, found_end) = Self::scan_any_triple_end(line, &mut chars);
-                if found_end {
-                    tokens.push(Token::new(TokenKind::String, 0, end_idx));
-                    resumed = true;
-                } else {
-                    tokens.push(Token::new(TokenKind::String, 0, line.len()));
-                    return (tokens, LineState::InString(StringKind::Triple));
-                }
+            while chars.peek().is_some_and(|&(i, _)| i < end) {
+                chars.next();
             }
-            _ => {}
+            resumed = true;
         }
 
         if resumed {
@@ -226,7 +393,7 @@ impl Tokenizer for PythonTokenizer {
                 while let Some(&(i, c)) = chars.peek() {
                     if c.is_alphanumeric() || c == '_' || c == '.' {
                         chars.next();
-                        end = i + 1;
+                        end = i + c.len_utf8();
                     } else {
                         break;
                     }
@@ -237,6 +404,9 @@ impl Tokenizer for PythonTokenizer {
             }
 
             line_start = false;
+            if !(ch.is_alphabetic() || ch == '_') {
+                definition = None;
+            }
 
             match ch {
                 '#' => {
@@ -245,65 +415,33 @@ impl Tokenizer for PythonTokenizer {
                 }
 
                 '\'' | '"' => {
-                    let start = idx;
-                    let quote = ch;
-                    let triple = if quote == '\'' {
-                        line[idx..].starts_with("'''")
-                    } else {
-                        line[idx..].starts_with("\"\"\"")
+                    let quote = ch as u8;
+                    let spec = StringSpec {
+                        quote,
+                        triple: Self::is_triple_at(line.as_bytes(), idx, quote),
+                        raw: false,
+                        format: false,
                     };
-
-                    if triple {
-                        chars.next();
-                        chars.next();
-                    }
-
-                    let (end_idx, found_end) =
-                        Self::scan_string_body(line, &mut chars, quote, triple, false);
-                    if found_end {
-                        tokens.push(Token::new(TokenKind::String, start, end_idx));
-                    } else {
-                        tokens.push(Token::new(TokenKind::String, start, line.len()));
-                        let next_state = if triple {
-                            LineState::InString(StringKind::Triple)
-                        } else if quote == '"' {
-                            LineState::InString(StringKind::Double)
-                        } else {
-                            LineState::InString(StringKind::Single)
-                        };
+                    if let Some(next_state) =
+                        self.push_string(line, idx, 0, spec, &mut chars, &mut tokens)
+                    {
                         return (tokens, next_state);
                     }
                 }
 
                 c if c.is_alphabetic() || c == '_' => {
                     if matches!(c, 'r' | 'R' | 'u' | 'U' | 'b' | 'B' | 'f' | 'F') {
-                        if let Some((prefix_len, quote, triple, raw)) =
+                        if let Some((prefix_len, spec)) =
                             Self::parse_prefixed_string_start(line, idx)
                         {
-                            let start = idx;
-                            for _ in 0..prefix_len.saturating_sub(1) {
-                                chars.next();
-                            }
-
-                            let _ = chars.next();
-                            if triple {
-                                chars.next();
-                                chars.next();
-                            }
-
-                            let (end_idx, found_end) =
-                                Self::scan_string_body(line, &mut chars, quote, triple, raw);
-                            if found_end {
-                                tokens.push(Token::new(TokenKind::String, start, end_idx));
-                            } else {
-                                tokens.push(Token::new(TokenKind::String, start, line.len()));
-                                let next_state = if triple {
-                                    LineState::InString(StringKind::Triple)
-                                } else if quote == '"' {
-                                    LineState::InString(StringKind::Double)
-                                } else {
-                                    LineState::InString(StringKind::Single)
-                                };
+                            if let Some(next_state) = self.push_string(
+                                line,
+                                idx,
+                                prefix_len,
+                                spec,
+                                &mut chars,
+                                &mut tokens,
+                            ) {
                                 return (tokens, next_state);
                             }
                             continue;
@@ -311,11 +449,11 @@ impl Tokenizer for PythonTokenizer {
                     }
 
                     let start = idx;
-                    let mut end = idx + 1;
+                    let mut end = idx + ch.len_utf8();
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -324,6 +462,18 @@ impl Tokenizer for PythonTokenizer {
 
                     if let Some(kind) = Self::is_keyword(word) {
                         tokens.push(Token::new(kind, start, end));
+                        definition = match word {
+                            "def" => Some(TokenKind::Function),
+                            "class" => Some(TokenKind::Type),
+                            _ => None,
+                        };
+                        continue;
+                    }
+
+                    if let Some(kind) = definition.take() {
+                        tokens.push(Token::new(kind, start, end));
+                    } else if let Some(kind) = Self::builtin_kind(word) {
+                        tokens.push(Token::new(kind, start, end));
                     } else if word.chars().next().is_some_and(char::is_uppercase) {
                         tokens.push(Token::new(TokenKind::Type, start, end));
                     } else if let Some(&(_, '(')) = chars.peek() {
@@ -508,7 +658,7 @@ impl Tokenizer for PythonTokenizer {
                 }
 
                 _ => {
-                    tokens.push(Token::new(TokenKind::Text, idx, idx + 1));
+                    tokens.push(Token::new(TokenKind::Text, idx, idx + ch.len_utf8()));
                 }
             }
         }
@@ -579,7 +729,119 @@ mod tests {
             .iter()
             .filter(|token| token.kind == TokenKind::String)
             .collect();
-        assert_eq!(strings.len(), 5);
+        // The f-string splits around its `{a}` field.
+        assert_eq!(strings.len(), 6);
+        assert!(
+            tokens
+                .iter()
+                .any(|token| token.kind == TokenKind::Identifier)
+        );
+    }
+
+    #[test]
+    fn test_definition_names() {
+        let tokenizer = PythonTokenizer::new();
+        let line = "class parser(Base): def run(self): return len(x)";
+        let (tokens, _) = tokenizer.tokenize_line(line, LineState::Normal);
+        let kind_of = |text: &str| {
+            tokens
+                .iter()
+                .find(|token| &line[token.range()] == text)
+                .map(|token| token.kind)
+        };
+
+        assert_eq!(kind_of("parser"), Some(TokenKind::Type));
+        assert_eq!(kind_of("run"), Some(TokenKind::Function));
+        assert_eq!(kind_of("len"), Some(TokenKind::Function));
+        assert_eq!(kind_of("self"), Some(TokenKind::Identifier));
+    }
+
+    #[test]
+    fn test_builtins() {
+        let tokenizer = PythonTokenizer::new();
+        let (tokens, _) =
+            tokenizer.tokenize_line("int str print NotImplemented", LineState::Normal);
+
+        assert_eq!(tokens[0].kind, TokenKind::KeywordType);
+        assert_eq!(tokens[1].kind, TokenKind::KeywordType);
+        assert_eq!(tokens[2].kind, TokenKind::Function);
+        assert_eq!(tokens[3].kind, TokenKind::Constant);
+    }
+
+    #[test]
+    fn test_triple_quote_kinds_do_not_cross_close() {
+        let tokenizer = PythonTokenizer::new();
+
+        let (_, state) = tokenizer.tokenize_line("s = \'\'\'start", LineState::Normal);
+        assert_eq!(state, LineState::InString(StringKind::TripleSingle));
+
+        // A double-quoted triple does not close a single-quoted one.
+        let (_, state) = tokenizer.tokenize_line("still \"\"\" inside", state);
+        assert_eq!(state, LineState::InString(StringKind::TripleSingle));
+
+        let (_, state) = tokenizer.tokenize_line("done\'\'\'", state);
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn test_raw_and_byte_prefixes() {
+        let tokenizer = PythonTokenizer::new();
+        let line = r#"rb"\d{2}" Rb'x' br"""y""""#;
+        let (tokens, state) = tokenizer.tokenize_line(line, LineState::Normal);
+
+        assert_eq!(state, LineState::Normal);
+        let strings: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.kind, &line[token.range()]))
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                (TokenKind::String, r#"rb"\d{2}""#),
+                (TokenKind::String, "Rb'x'"),
+                (TokenKind::String, r#"br"""y""""#),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escapes_are_highlighted_outside_raw_strings() {
+        let tokenizer = PythonTokenizer::new();
+        let kinds = |line: &'static str| -> Vec<(TokenKind, &'static str)> {
+            let (tokens, state) = tokenizer.tokenize_line(line, LineState::Normal);
+            assert_eq!(state, LineState::Normal);
+            tokens
+                .iter()
+                .map(|token| (token.kind, &line[token.range()]))
+                .collect()
+        };
+
+        assert_eq!(kinds(r"r'\d\n'"), vec![(TokenKind::String, r"r'\d\n'")]);
+        assert_eq!(kinds(r"r'\''"), vec![(TokenKind::String, r"r'\''")]);
+        assert_eq!(
+            kinds(r"'\x41\n\''"),
+            vec![
+                (TokenKind::String, "'"),
+                (TokenKind::StringEscape, r"\x41"),
+                (TokenKind::StringEscape, r"\n"),
+                (TokenKind::StringEscape, r"\'"),
+                (TokenKind::String, "'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_triple_string_stays_raw_across_lines() {
+        let tokenizer = PythonTokenizer::new();
+        let (_, state) = tokenizer.tokenize_line("pattern = r'''\\d+", LineState::Normal);
+        assert!(matches!(state, LineState::InRawString(_)));
+
+        let line = r"\n still raw'''";
+        let (tokens, state) = tokenizer.tokenize_line(line, state);
+        assert_eq!(state, LineState::Normal);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, TokenKind::String);
+        assert_eq!(&line[tokens[0].range()], line);
     }
 
     #[test]
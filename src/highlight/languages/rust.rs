@@ -343,7 +343,7 @@ impl Tokenizer for RustTokenizer {
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -428,7 +428,7 @@ impl Tokenizer for RustTokenizer {
                                 while let Some(&(i, c)) = chars.peek() {
                                     if c.is_alphanumeric() || c == '_' {
                                         chars.next();
-                                        end = i + 1;
+                                        end = i + c.len_utf8();
                                     } else {
                                         break;
                                     }
@@ -443,7 +443,7 @@ impl Tokenizer for RustTokenizer {
                             while let Some(&(i, c)) = chars.peek() {
                                 if c.is_alphanumeric() || c == '_' {
                                     chars.next();
-                                    end = i + 1;
+                                    end = i + c.len_utf8();
                                 } else {
                                     break;
                                 }
@@ -464,11 +464,11 @@ impl Tokenizer for RustTokenizer {
                 // Identifiers and Keywords
                 c if c.is_alphabetic() || c == '_' => {
                     let start = idx;
-                    let mut end = idx + 1;
+                    let mut end = idx + ch.len_utf8();
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -577,7 +577,7 @@ impl Tokenizer for RustTokenizer {
                             while let Some(&(j, s)) = chars.peek() {
                                 if s.is_alphanumeric() {
                                     chars.next();
-                                    end = j + 1;
+                                    end = j + s.len_utf8();
                                 } else {
                                     break;
                                 }
@@ -689,11 +689,11 @@ impl Tokenizer for RustTokenizer {
                     if let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                             while let Some(&(j, s)) = chars.peek() {
                                 if s.is_alphanumeric() || s == '_' {
                                     chars.next();
-                                    end = j + 1;
+                                    end = j + s.len_utf8();
                                 } else {
                                     break;
                                 }
@@ -708,7 +708,7 @@ impl Tokenizer for RustTokenizer {
                 }
 
                 _ => {
-                    tokens.push(Token::new(TokenKind::Text, idx, idx + 1));
+                    tokens.push(Token::new(TokenKind::Text, idx, idx + ch.len_utf8()));
                 }
             }
         }
@@ -0,0 +1,703 @@
+use crate::highlight::token::{Token, TokenKind};
+use crate::highlight::tokenizer::{HeredocKind, LineState, StringKind, Tokenizer};
+
+pub struct ShellTokenizer;
+
+impl Default for ShellTokenizer {
+    fn default() -> Self {
+        Self
+    }
+}
+
+/// Per-line scanner state that is not carried across lines.
+#[derive(Default)]
+struct LineContext {
+    /// The next word is in command position (a command name or keyword).
+    command_position: bool,
+    /// The next word is a loop variable (`for x`, `select x`).
+    expect_variable: bool,
+    /// `in` is currently a keyword (after `for x` / `case word`).
+    expect_in: bool,
+    /// The next word names a function (`function name`).
+    expect_function_name: bool,
+    /// First heredoc opened on this line; its body starts on the next line.
+    heredoc: Option<HeredocKind>,
+}
+
+impl ShellTokenizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn keyword_kind(word: &str) -> Option<TokenKind> {
+        match word {
+            "if" | "then" | "else" | "elif" | "fi" | "for" | "while" | "until" | "do" | "done"
+            | "case" | "esac" | "select" | "break" | "continue" | "return" | "exit" => {
+                Some(TokenKind::KeywordControl)
+            }
+            "function" | "time" | "coproc" | "[[" | "]]" | "!" => Some(TokenKind::Keyword),
+            "local" | "export" | "readonly" | "declare" | "typeset" | "unset" => {
+                Some(TokenKind::KeywordModifier)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_meta(byte: u8) -> bool {
+        matches!(
+            byte,
+            b' ' | b'\t' | b'|' | b'&' | b';' | b'(' | b')' | b'<' | b'>'
+        )
+    }
+
+    fn is_word_end(byte: u8) -> bool {
+        Self::is_meta(byte) || matches!(byte, b'\'' | b'"' | b'`' | b'$' | b'\\')
+    }
+
+    fn is_name_start(byte: u8) -> bool {
+        byte.is_ascii_alphabetic() || byte == b'_'
+    }
+
+    fn is_name_char(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || byte == b'_'
+    }
+
+    fn is_blank_or_end(bytes: &[u8], idx: usize) -> bool {
+        idx >= bytes.len() || matches!(bytes[idx], b' ' | b'\t' | b';')
+    }
+
+    fn char_len_at(line: &str, pos: usize) -> usize {
+        line[pos..].chars().next().map_or(0, char::len_utf8)
+    }
+
+    /// Find the index of the `close` byte matching an already-consumed `open`,
+    /// skipping quoted text and escapes. Returns `None` if unbalanced.
+    fn find_matching(bytes: &[u8], from: usize, open: u8, close: u8) -> Option<usize> {
+        let mut depth = 1usize;
+        let mut pos = from;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'\\' => pos += 1,
+                b'\'' => {
+                    pos += 1;
+                    while pos < bytes.len() && bytes[pos] != b'\'' {
+                        pos += 1;
+                    }
+                }
+                b'"' => {
+                    pos += 1;
+                    while pos < bytes.len() && bytes[pos] != b'"' {
+                        if bytes[pos] == b'\\' {
+                            pos += 1;
+                        }
+                        pos += 1;
+                    }
+                }
+                b if b == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(pos);
+                    }
+                }
+                b if b == open => depth += 1,
+                _ => {}
+            }
+            pos += 1;
+        }
+        None
+    }
+
+    /// Tokenize `line[start..end]` as nested shell code and append the tokens.
+    fn push_nested_code(&self, line: &str, start: usize, end: usize, tokens: &mut Vec<Token>) {
+        let (inner, _) = self.tokenize_line(&line[start..end], LineState::Normal);
+        tokens.extend(
+            inner
+                .into_iter()
+                .map(|t| Token::new(t.kind, t.start + start, t.end + start)),
+        );
+    }
+
+    /// Scan a `$` expansion starting at `pos`. Returns the position after it.
+    fn scan_dollar(&self, line: &str, pos: usize, tokens: &mut Vec<Token>) -> usize {
+        let bytes = line.as_bytes();
+        match bytes.get(pos + 1) {
+            Some(b'(') => {
+                // `$((` arithmetic and `$(` command substitution both hold code.
+                let open_len = if bytes.get(pos + 2) == Some(&b'(') {
+                    3
+                } else {
+                    2
+                };
+                tokens.push(Token::new(TokenKind::Punctuation, pos, pos + open_len));
+                let inner_start = pos + open_len;
+                let Some(close) = Self::find_matching(bytes, pos + 2, b'(', b')') else {
+                    self.push_nested_code(line, inner_start, bytes.len(), tokens);
+                    return bytes.len();
+                };
+                // For `$((`, the first of the two closing parens ends the inner group.
+                let inner_end = if open_len == 3 {
+                    close.saturating_sub(1).max(inner_start)
+                } else {
+                    close
+                };
+                self.push_nested_code(line, inner_start, inner_end, tokens);
+                tokens.push(Token::new(TokenKind::Punctuation, inner_end, close + 1));
+                close + 1
+            }
+            Some(b'{') => {
+                let end = Self::find_matching(bytes, pos + 2, b'{', b'}')
+                    .map_or(bytes.len(), |close| close + 1);
+                tokens.push(Token::new(TokenKind::Identifier, pos, end));
+                end
+            }
+            Some(b'\'') => {
+                // ANSI-C quoting: `$'...'` with backslash escapes.
+                let mut end = pos + 2;
+                while end < bytes.len() && bytes[end] != b'\'' {
+                    if bytes[end] == b'\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                let end = (end + 1).min(bytes.len());
+                tokens.push(Token::new(TokenKind::String, pos, end));
+                end
+            }
+            Some(&b) if Self::is_name_start(b) => {
+                let mut end = pos + 2;
+                while end < bytes.len() && Self::is_name_char(bytes[end]) {
+                    end += 1;
+                }
+                tokens.push(Token::new(TokenKind::Identifier, pos, end));
+                end
+            }
+            Some(&b) if b.is_ascii_digit() || b"@*#?$!-".contains(&b) => {
+                tokens.push(Token::new(TokenKind::Identifier, pos, pos + 2));
+                pos + 2
+            }
+            _ => {
+                tokens.push(Token::new(TokenKind::Text, pos, pos + 1));
+                pos + 1
+            }
+        }
+    }
+
+    /// Scan a backtick command substitution starting at `pos`.
+    fn scan_backtick(&self, line: &str, pos: usize, tokens: &mut Vec<Token>) -> usize {
+        let bytes = line.as_bytes();
+        tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+        let mut end = pos + 1;
+        while end < bytes.len() && bytes[end] != b'`' {
+            if bytes[end] == b'\\' {
+                end += 1;
+            }
+            end += 1;
+        }
+        let end = end.min(bytes.len());
+        self.push_nested_code(line, pos + 1, end, tokens);
+        if end < bytes.len() {
+            tokens.push(Token::new(TokenKind::Punctuation, end, end + 1));
+            end + 1
+        } else {
+            end
+        }
+    }
+
+    /// Scan text with double-quote interpolation rules from `from`.
+    ///
+    /// With `quote` set, the scan stops after the closing `"` and returns
+    /// `(end, true)`; heredoc bodies pass `false` and always run to the end of
+    /// the line.
+    fn scan_interpolated(
+        &self,
+        line: &str,
+        segment_start: usize,
+        from: usize,
+        quote: bool,
+        tokens: &mut Vec<Token>,
+    ) -> (usize, bool) {
+        let bytes = line.as_bytes();
+        let mut segment = segment_start;
+        let mut pos = from;
+
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            match b {
+                b'\\'
+                    if pos + 1 < bytes.len()
+                        && matches!(bytes[pos + 1], b'$' | b'`' | b'"' | b'\\') =>
+                {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, pos + 2));
+                    pos += 2;
+                    segment = pos;
+                }
+                b'$' | b'`' => {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    pos = if b == b'$' {
+                        self.scan_dollar(line, pos, tokens)
+                    } else {
+                        self.scan_backtick(line, pos, tokens)
+                    };
+                    segment = pos;
+                }
+                b'"' if quote => {
+                    tokens.push(Token::new(TokenKind::String, segment, pos + 1));
+                    return (pos + 1, true);
+                }
+                _ => pos += 1,
+            }
+        }
+
+        if segment < bytes.len() {
+            tokens.push(Token::new(TokenKind::String, segment, bytes.len()));
+        }
+        (bytes.len(), false)
+    }
+
+    /// Scan a heredoc operator (`<<`, `<<-`) and its delimiter word.
+    fn scan_heredoc_start(
+        pos: usize,
+        bytes: &[u8],
+        ctx: &mut LineContext,
+        tokens: &mut Vec<Token>,
+    ) -> usize {
+        let strip_tabs = bytes.get(pos + 2) == Some(&b'-');
+        let op_end = pos + if strip_tabs { 3 } else { 2 };
+        tokens.push(Token::new(TokenKind::Operator, pos, op_end));
+
+        let mut start = op_end;
+        while start < bytes.len() && matches!(bytes[start], b' ' | b'\t') {
+            start += 1;
+        }
+
+        let mut end = start;
+        let mut quote = None;
+        let mut literal = false;
+        let mut delimiter = Vec::new();
+        while end < bytes.len() {
+            let b = bytes[end];
+            match quote {
+                Some(q) if b == q => quote = None,
+                None if b == b'\'' || b == b'"' => {
+                    quote = Some(b);
+                    literal = true;
+                }
+                None if b == b'\\' => literal = true,
+                None if Self::is_meta(b) => break,
+                _ => delimiter.push(b),
+            }
+            end += 1;
+        }
+
+        if end > start {
+            tokens.push(Token::new(TokenKind::Label, start, end));
+            if ctx.heredoc.is_none() {
+                let delimiter = String::from_utf8_lossy(&delimiter);
+                ctx.heredoc = Some(HeredocKind::shell(&delimiter, strip_tabs, literal));
+            }
+        }
+        end
+    }
+
+    /// Classify a plain word and update the command-position context.
+    fn push_word(
+        line: &str,
+        start: usize,
+        end: usize,
+        ctx: &mut LineContext,
+        tokens: &mut Vec<Token>,
+    ) {
+        let word = &line[start..end];
+        let bytes = word.as_bytes();
+
+        if ctx.expect_variable {
+            ctx.expect_variable = false;
+            ctx.expect_in = true;
+            tokens.push(Token::new(TokenKind::Identifier, start, end));
+            return;
+        }
+
+        if ctx.expect_function_name {
+            ctx.expect_function_name = false;
+            tokens.push(Token::new(TokenKind::Function, start, end));
+            return;
+        }
+
+        // `]]` closes a test expression opened in command position.
+        if word == "]]" {
+            tokens.push(Token::new(TokenKind::Keyword, start, end));
+            return;
+        }
+
+        if word == "in" && ctx.expect_in {
+            ctx.expect_in = false;
+            tokens.push(Token::new(TokenKind::KeywordControl, start, end));
+            return;
+        }
+
+        if ctx.command_position {
+            if let Some(kind) = Self::keyword_kind(word) {
+                tokens.push(Token::new(kind, start, end));
+                match word {
+                    "for" | "select" => {
+                        ctx.command_position = false;
+                        ctx.expect_variable = true;
+                    }
+                    "case" => {
+                        ctx.command_position = false;
+                        ctx.expect_in = true;
+                    }
+                    "function" => {
+                        ctx.command_position = false;
+                        ctx.expect_function_name = true;
+                    }
+                    "fi" | "done" | "esac" | "[[" | "]]" | "break" | "continue" | "return"
+                    | "exit" | "local" | "export" | "readonly" | "declare" | "typeset"
+                    | "unset" => {
+                        ctx.command_position = false;
+                    }
+                    _ => {}
+                }
+                return;
+            }
+
+            // Assignment prefix: NAME=value keeps command position.
+            if let Some(eq) = word.find('=') {
+                if eq > 0
+                    && Self::is_name_start(bytes[0])
+                    && bytes[..eq].iter().all(|&b| Self::is_name_char(b))
+                {
+                    tokens.push(Token::new(TokenKind::Identifier, start, start + eq));
+                    tokens.push(Token::new(TokenKind::Operator, start + eq, start + eq + 1));
+                    if start + eq + 1 < end {
+                        tokens.push(Token::new(TokenKind::Text, start + eq + 1, end));
+                    }
+                    return;
+                }
+            }
+
+            ctx.command_position = false;
+            tokens.push(Token::new(TokenKind::Function, start, end));
+            return;
+        }
+
+        let kind = if bytes.len() > 1 && bytes[0] == b'-' && !bytes[1].is_ascii_digit() {
+            TokenKind::Attribute
+        } else if bytes.iter().all(u8::is_ascii_digit) {
+            TokenKind::Number
+        } else {
+            TokenKind::Text
+        };
+        tokens.push(Token::new(kind, start, end));
+    }
+
+    fn heredoc_body(&self, line: &str, kind: HeredocKind) -> (Vec<Token>, LineState) {
+        let mut tokens = Vec::new();
+        if kind.is_terminator(line) {
+            let start = line.len() - line.trim_start_matches('\t').len();
+            if start < line.len() {
+                tokens.push(Token::new(TokenKind::Label, start, line.len()));
+            }
+            return (tokens, LineState::Normal);
+        }
+
+        let literal = matches!(kind, HeredocKind::Shell { literal: true, .. });
+        if literal {
+            if !line.is_empty() {
+                tokens.push(Token::new(TokenKind::String, 0, line.len()));
+            }
+        } else {
+            self.scan_interpolated(line, 0, 0, false, &mut tokens);
+        }
+        (tokens, LineState::InHeredoc(kind))
+    }
+}
+
+impl Tokenizer for ShellTokenizer {
+    fn name(&self) -> &'static str {
+        "Shell"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["sh", "bash", "zsh", "ksh"]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["sh", "bash", "zsh"]
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+        let mut tokens = Vec::new();
+        let bytes = line.as_bytes();
+        let mut pos = 0usize;
+
+        match state {
+            LineState::InHeredoc(kind) => return self.heredoc_body(line, kind),
+            LineState::InString(StringKind::Single) => {
+                let Some(close) = line.find('\'') else {
+                    if !line.is_empty() {
+                        tokens.push(Token::new(TokenKind::String, 0, line.len()));
+                    }
+                    return (tokens, state);
+                };
+                tokens.push(Token::new(TokenKind::String, 0, close + 1));
+                pos = close + 1;
+            }
+            LineState::InString(StringKind::Double) => {
+                let (end, closed) = self.scan_interpolated(line, 0, 0, true, &mut tokens);
+                if !closed {
+                    return (tokens, state);
+                }
+                pos = end;
+            }
+            _ => {}
+        }
+
+        let mut ctx = LineContext {
+            command_position: pos == 0,
+            ..LineContext::default()
+        };
+
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            match b {
+                b' ' | b'\t' => pos += 1,
+
+                b'#' => {
+                    tokens.push(Token::new(TokenKind::Comment, pos, line.len()));
+                    break;
+                }
+
+                b'\'' => {
+                    if let Some(close) = line[pos + 1..].find('\'') {
+                        let end = pos + 1 + close + 1;
+                        tokens.push(Token::new(TokenKind::String, pos, end));
+                        pos = end;
+                    } else {
+                        tokens.push(Token::new(TokenKind::String, pos, line.len()));
+                        return (tokens, LineState::InString(StringKind::Single));
+                    }
+                    ctx.command_position = false;
+                }
+
+                b'"' => {
+                    let (end, closed) =
+                        self.scan_interpolated(line, pos, pos + 1, true, &mut tokens);
+                    if !closed {
+                        return (tokens, LineState::InString(StringKind::Double));
+                    }
+                    pos = end;
+                    ctx.command_position = false;
+                }
+
+                b'$' => {
+                    pos = self.scan_dollar(line, pos, &mut tokens);
+                    ctx.command_position = false;
+                }
+
+                b'`' => {
+                    pos = self.scan_backtick(line, pos, &mut tokens);
+                    ctx.command_position = false;
+                }
+
+                b'\\' => {
+                    let end = (pos + 1 + Self::char_len_at(line, (pos + 1).min(line.len())))
+                        .min(line.len());
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, end));
+                    pos = end;
+                }
+
+                b'<' if bytes.get(pos + 1) == Some(&b'<') => {
+                    if bytes.get(pos + 2) == Some(&b'<') {
+                        tokens.push(Token::new(TokenKind::Operator, pos, pos + 3));
+                        pos += 3;
+                    } else {
+                        pos = Self::scan_heredoc_start(pos, bytes, &mut ctx, &mut tokens);
+                    }
+                }
+
+                b'|' | b'&' | b';' => {
+                    let mut end = pos + 1;
+                    if bytes.get(end) == Some(&b) || (b == b'|' && bytes.get(end) == Some(&b'&')) {
+                        end += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Operator, pos, end));
+                    pos = end;
+                    ctx.command_position = true;
+                    ctx.expect_in = false;
+                }
+
+                b'<' | b'>' => {
+                    let mut end = pos + 1;
+                    while end < bytes.len() && matches!(bytes[end], b'>' | b'&' | b'|') {
+                        end += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Operator, pos, end));
+                    pos = end;
+                }
+
+                b'(' | b')' => {
+                    tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+                    pos += 1;
+                    ctx.command_position = b == b'(';
+                }
+
+                b'{' | b'}' if Self::is_blank_or_end(bytes, pos + 1) => {
+                    tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+                    pos += 1;
+                    ctx.command_position = b == b'{';
+                }
+
+                _ => {
+                    let mut end = pos;
+                    while end < bytes.len() && !Self::is_word_end(bytes[end]) {
+                        end += 1;
+                    }
+                    // File-descriptor prefix of a redirection: `2>&1`.
+                    if end < bytes.len()
+                        && matches!(bytes[end], b'<' | b'>')
+                        && bytes[pos..end].iter().all(u8::is_ascii_digit)
+                    {
+                        tokens.push(Token::new(TokenKind::Number, pos, end));
+                        pos = end;
+                        continue;
+                    }
+                    Self::push_word(line, pos, end, &mut ctx, &mut tokens);
+                    pos = end;
+                }
+            }
+        }
+
+        let next_state = ctx.heredoc.map_or(LineState::Normal, LineState::InHeredoc);
+        (tokens, next_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(line: &str) -> Vec<(TokenKind, &str)> {
+        let (tokens, _) = ShellTokenizer::new().tokenize_line(line, LineState::Normal);
+        tokens
+            .iter()
+            .map(|token| (token.kind, &line[token.range()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_shell_command_and_flags() {
+        assert_eq!(
+            spans("grep -rn --color=auto pattern src | wc -l"),
+            vec![
+                (TokenKind::Function, "grep"),
+                (TokenKind::Attribute, "-rn"),
+                (TokenKind::Attribute, "--color=auto"),
+                (TokenKind::Text, "pattern"),
+                (TokenKind::Text, "src"),
+                (TokenKind::Operator, "|"),
+                (TokenKind::Function, "wc"),
+                (TokenKind::Attribute, "-l"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_quoting_rules() {
+        assert_eq!(
+            spans(r#"echo '$HOME' "$HOME/${DIR%/}" \$"#),
+            vec![
+                (TokenKind::Function, "echo"),
+                (TokenKind::String, "'$HOME'"),
+                (TokenKind::String, "\""),
+                (TokenKind::Identifier, "$HOME"),
+                (TokenKind::String, "/"),
+                (TokenKind::Identifier, "${DIR%/}"),
+                (TokenKind::String, "\""),
+                (TokenKind::StringEscape, "\\$"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_nested_command_substitution() {
+        assert_eq!(
+            spans("x=$(basename $(pwd))"),
+            vec![
+                (TokenKind::Identifier, "x"),
+                (TokenKind::Operator, "="),
+                (TokenKind::Punctuation, "$("),
+                (TokenKind::Function, "basename"),
+                (TokenKind::Punctuation, "$("),
+                (TokenKind::Function, "pwd"),
+                (TokenKind::Punctuation, ")"),
+                (TokenKind::Punctuation, ")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_keywords_and_loop_variable() {
+        assert_eq!(
+            spans("for f in *.rs; do echo $f; done"),
+            vec![
+                (TokenKind::KeywordControl, "for"),
+                (TokenKind::Identifier, "f"),
+                (TokenKind::KeywordControl, "in"),
+                (TokenKind::Text, "*.rs"),
+                (TokenKind::Operator, ";"),
+                (TokenKind::KeywordControl, "do"),
+                (TokenKind::Function, "echo"),
+                (TokenKind::Identifier, "$f"),
+                (TokenKind::Operator, ";"),
+                (TokenKind::KeywordControl, "done"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_comment_only_at_word_start() {
+        let tokens = spans("echo a#b $# # real");
+        assert_eq!(tokens[1], (TokenKind::Text, "a#b"));
+        assert_eq!(tokens[2], (TokenKind::Identifier, "$#"));
+        assert_eq!(tokens[3], (TokenKind::Comment, "# real"));
+    }
+
+    #[test]
+    fn test_shell_redirections() {
+        assert_eq!(
+            spans("make 2>&1 >>log"),
+            vec![
+                (TokenKind::Function, "make"),
+                (TokenKind::Number, "2"),
+                (TokenKind::Operator, ">&"),
+                (TokenKind::Number, "1"),
+                (TokenKind::Operator, ">>"),
+                (TokenKind::Text, "log"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_heredoc_states() {
+        let tokenizer = ShellTokenizer::new();
+        let (_, state) = tokenizer.tokenize_line("cat <<-'END' > out", LineState::Normal);
+        assert_eq!(
+            state,
+            LineState::InHeredoc(HeredocKind::shell("END", true, true))
+        );
+
+        let (tokens, state) = tokenizer.tokenize_line("\t$not_expanded", state);
+        assert_eq!(tokens, vec![Token::new(TokenKind::String, 0, 14)]);
+        let (tokens, state) = tokenizer.tokenize_line("\tEND", state);
+        assert_eq!(tokens, vec![Token::new(TokenKind::Label, 1, 4)]);
+        assert_eq!(state, LineState::Normal);
+    }
+}
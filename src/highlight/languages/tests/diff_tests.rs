@@ -0,0 +1,115 @@
+use std::sync::Once;
+
+use tracing::{debug, info};
+
+use crate::highlight::languages::diff::DiffTokenizer;
+use crate::highlight::{LineState, TokenKind, Tokenizer, TokenizerRegistry};
+
+fn setup_test_logging() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_test_writer()
+            .try_init();
+    });
+}
+
+/// Tokenize a patch line by line, returning the token kinds of each line.
+fn line_kinds(text: &str) -> Vec<Vec<TokenKind>> {
+    let tokenizer = DiffTokenizer::new();
+    let mut state = LineState::Normal;
+    text.lines()
+        .map(|line| {
+            info!(line, ?state, "tokenizing");
+            let (tokens, next) = tokenizer.tokenize_line(line, state);
+            debug!(?tokens, ?next, "tokenization result");
+            state = next;
+            tokens.iter().map(|token| token.kind).collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_diff_multi_file_git_patch_snapshot() {
+    use TokenKind::{
+        Attribute, Comment, Constant, DiffAdd, DiffFile, DiffHunk, DiffRemove, Function, Keyword,
+        Number, Text,
+    };
+
+    setup_test_logging();
+    let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 3b18e51..a9c5e2f 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,4 @@ pub mod app;
+ pub mod app;
+-pub fn add(a: i32, b: i32) -> i32 { a - b }
++pub fn add(a: i32, b: i32) -> i32 { a + b }
+
+ pub mod util;
+diff --git a/old.txt b/new.txt
+similarity index 90%
+rename from old.txt
+rename to new.txt
+--- a/old.txt
++++ b/new.txt
+@@ -1 +1 @@
+-last line
++last line, changed
+\\ No newline at end of file";
+
+    let expected: Vec<Vec<TokenKind>> = vec![
+        vec![Keyword, Attribute, DiffFile, DiffFile],
+        vec![Keyword, Constant],
+        vec![DiffFile],
+        vec![DiffFile],
+        vec![DiffHunk, Number, Number, DiffHunk, Function],
+        vec![Text],
+        vec![DiffRemove],
+        vec![DiffAdd],
+        vec![],
+        vec![Text],
+        vec![Keyword, Attribute, DiffFile, DiffFile],
+        vec![Keyword, Constant],
+        vec![Keyword, DiffFile],
+        vec![Keyword, DiffFile],
+        vec![DiffFile],
+        vec![DiffFile],
+        vec![DiffHunk, Number, Number, DiffHunk],
+        vec![DiffRemove],
+        vec![DiffAdd],
+        vec![Comment],
+    ];
+
+    assert_eq!(line_kinds(patch), expected);
+}
+
+#[test]
+fn test_diff_classification_uses_first_column_only() {
+    setup_test_logging();
+    let patch = "\
+@@ -1,3 +1,3 @@
+ let x = a - b + c;
+-x -= 1; // + one
++x += 1; // - one
+ ---";
+    let kinds = line_kinds(patch);
+    assert_eq!(kinds[1], vec![TokenKind::Text]);
+    assert_eq!(kinds[2], vec![TokenKind::DiffRemove]);
+    assert_eq!(kinds[3], vec![TokenKind::DiffAdd]);
+    assert_eq!(kinds[4], vec![TokenKind::Text]);
+}
+
+#[test]
+fn test_diff_registered_names() {
+    let registry = TokenizerRegistry::with_builtins();
+    assert_eq!(registry.by_name("diff").map(Tokenizer::name), Some("Diff"));
+    for ext in ["diff", "patch", ".PATCH"] {
+        assert_eq!(
+            registry.for_extension(ext).map(Tokenizer::name),
+            Some("Diff")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Once;
+
+use tracing::{debug, info};
+
+use crate::highlight::languages::json::JsonTokenizer;
+use crate::highlight::{LineState, TokenKind, Tokenizer};
+
+fn setup_test_logging() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_test_writer()
+            .try_init();
+    });
+}
+
+/// Tokenize a document line by line, returning (kind, text) pairs per line.
+fn tokenize_document(text: &str) -> Vec<Vec<(TokenKind, &str)>> {
+    let tokenizer = JsonTokenizer::new();
+    let mut state = LineState::Normal;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        info!(line, ?state, "tokenizing");
+        let (tokens, next) = tokenizer.tokenize_line(line, state);
+        debug!(?tokens, ?next, "tokenization result");
+        lines.push(
+            tokens
+                .iter()
+                .map(|token| (token.kind, &line[token.range()]))
+                .collect(),
+        );
+        state = next;
+    }
+    lines
+}
+
+#[test]
+fn test_json_document_snapshot() {
+    setup_test_logging();
+    let doc = r#"{
+  "name": "opentui",
+  "version": 2.1e0,
+  "tags": ["tui", "a\"b"],
+  "nested": {"ok": true, "none": null}
+}"#;
+
+    use TokenKind::{
+        Boolean, Constant, Delimiter, Identifier, Number, Punctuation, String, StringEscape,
+    };
+    let expected: Vec<Vec<(TokenKind, &str)>> = vec![
+        vec![(Punctuation, "{")],
+        vec![
+            (Identifier, "\"name\""),
+            (Delimiter, ":"),
+            (String, "\"opentui\""),
+            (Delimiter, ","),
+        ],
+        vec![
+            (Identifier, "\"version\""),
+            (Delimiter, ":"),
+            (Number, "2.1e0"),
+            (Delimiter, ","),
+        ],
+        vec![
+            (Identifier, "\"tags\""),
+            (Delimiter, ":"),
+            (Punctuation, "["),
+            (String, "\"tui\""),
+            (Delimiter, ","),
+            (String, "\"a"),
+            (StringEscape, "\\\""),
+            (String, "b\""),
+            (Punctuation, "]"),
+            (Delimiter, ","),
+        ],
+        vec![
+            (Identifier, "\"nested\""),
+            (Delimiter, ":"),
+            (Punctuation, "{"),
+            (Identifier, "\"ok\""),
+            (Delimiter, ":"),
+            (Boolean, "true"),
+            (Delimiter, ","),
+            (Identifier, "\"none\""),
+            (Delimiter, ":"),
+            (Constant, "null"),
+            (Punctuation, "}"),
+        ],
+        vec![(Punctuation, "}")],
+    ];
+
+    assert_eq!(tokenize_document(doc), expected);
+}
+
+#[test]
+fn test_json_key_with_escape_is_identifier() {
+    setup_test_logging();
+    let line = r#""a\nb" : 1"#;
+    let (tokens, _) = JsonTokenizer::new().tokenize_line(line, LineState::Normal);
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Identifier,
+            TokenKind::StringEscape,
+            TokenKind::Identifier,
+            TokenKind::Delimiter,
+            TokenKind::Number,
+        ]
+    );
+}
@@ -1,2 +1,6 @@
+mod diff_tests;
+mod json_tests;
 mod python_tests;
 mod rust_tests;
+mod shell_tests;
+mod yaml_tests;
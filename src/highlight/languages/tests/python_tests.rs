@@ -64,3 +64,195 @@ fn test_python_number_formats() {
             >= 3
     );
 }
+
+fn spans(line: &str, state: LineState) -> (Vec<(TokenKind, &str)>, LineState) {
+    let (tokens, state) = PythonTokenizer::new().tokenize_line(line, state);
+    debug!(?tokens, ?state, "tokenization result");
+    let spans = tokens
+        .iter()
+        .map(|token| (token.kind, &line[token.range()]))
+        .collect();
+    (spans, state)
+}
+
+#[test]
+fn test_python_fstring_nesting_snapshot() {
+    setup_test_logging();
+    let line = r#"f"{user['name']!r:>{width}} {{x}} {n + 1:.2f}""#;
+    let (tokens, state) = spans(line, LineState::Normal);
+
+    use TokenKind::{Identifier, Number, Operator, Punctuation, String, StringEscape};
+    assert_eq!(
+        tokens,
+        vec![
+            (String, "f\""),
+            (Punctuation, "{"),
+            (Identifier, "user"),
+            (Punctuation, "["),
+            (String, "'name'"),
+            (Punctuation, "]"),
+            (Operator, "!r"),
+            (Punctuation, ":"),
+            (String, ">"),
+            (Punctuation, "{"),
+            (Identifier, "width"),
+            (Punctuation, "}"),
+            (Punctuation, "}"),
+            (String, " "),
+            (StringEscape, "{{"),
+            (String, "x"),
+            (StringEscape, "}}"),
+            (String, " "),
+            (Punctuation, "{"),
+            (Identifier, "n"),
+            (Operator, "+"),
+            (Number, "1"),
+            (Punctuation, ":"),
+            (String, ".2f"),
+            (Punctuation, "}"),
+            (String, "\""),
+        ]
+    );
+    assert_eq!(state, LineState::Normal);
+}
+
+#[test]
+fn test_python_nested_fstring_in_field() {
+    setup_test_logging();
+    let line = r#"f'{f"{a!s}"}'"#;
+    let (tokens, _) = spans(line, LineState::Normal);
+
+    use TokenKind::{Identifier, Operator, Punctuation, String};
+    assert_eq!(
+        tokens,
+        vec![
+            (String, "f'"),
+            (Punctuation, "{"),
+            (String, "f\""),
+            (Punctuation, "{"),
+            (Identifier, "a"),
+            (Operator, "!s"),
+            (Punctuation, "}"),
+            (String, "\""),
+            (Punctuation, "}"),
+            (String, "'"),
+        ]
+    );
+}
+
+#[test]
+fn test_python_multiline_fstring_keeps_fields() {
+    setup_test_logging();
+    let (_, state) = spans("msg = f\"\"\"Hello", LineState::Normal);
+    assert_eq!(state, LineState::InString(StringKind::FormatTriple));
+
+    let (tokens, state) = spans("  {name.title()}!", state);
+    assert_eq!(state, LineState::InString(StringKind::FormatTriple));
+    assert!(tokens.contains(&(TokenKind::Function, "title")));
+    assert!(tokens.contains(&(TokenKind::Identifier, "name")));
+
+    let (tokens, state) = spans("\"\"\" + tail", state);
+    assert_eq!(state, LineState::Normal);
+    assert_eq!(tokens[0], (TokenKind::String, "\"\"\""));
+    assert_eq!(tokens.last(), Some(&(TokenKind::Identifier, "tail")));
+}
+
+mod line_state_round_trip {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::highlight::languages::python::PythonTokenizer;
+    use crate::highlight::{HighlightedBuffer, LineState, Token, Tokenizer};
+    use crate::text::TextBuffer;
+
+    /// Wraps the Python tokenizer and counts how many lines it tokenizes.
+    struct CountingTokenizer {
+        inner: PythonTokenizer,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Tokenizer for CountingTokenizer {
+        fn name(&self) -> &'static str {
+            "counting-python"
+        }
+
+        fn extensions(&self) -> &'static [&'static str] {
+            &[]
+        }
+
+        fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.tokenize_line(line, state)
+        }
+    }
+
+    const SOURCE: &str = "x = 1\ns = \"\"\"\nalpha\nbeta\n\"\"\"\ny = 2\nz = 3";
+
+    fn highlighted() -> (HighlightedBuffer, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut buffer = HighlightedBuffer::new(TextBuffer::with_text(SOURCE));
+        buffer.set_tokenizer(Some(Arc::new(CountingTokenizer {
+            inner: PythonTokenizer::new(),
+            calls: Arc::clone(&calls),
+        })));
+        buffer.update_highlighting();
+        assert_eq!(calls.load(Ordering::SeqCst), 7);
+        calls.store(0, Ordering::SeqCst);
+        (buffer, calls)
+    }
+
+    fn replace_line(buffer: &mut HighlightedBuffer, line: usize, text: &str) {
+        let rope = buffer.rope_mut();
+        let start = rope.line_to_char(line);
+        let len = rope
+            .line(line)
+            .map_or(0, |l| l.to_string().trim_end().chars().count());
+        rope.remove(start..start + len).unwrap();
+        rope.insert(start, text).unwrap();
+        buffer.mark_dirty(line, line + 1);
+    }
+
+    #[test]
+    fn edit_inside_triple_string_retokenizes_one_line() {
+        let (mut buffer, calls) = highlighted();
+        replace_line(&mut buffer, 2, "alpha = not code");
+        buffer.update_highlighting();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(
+            buffer
+                .tokens_for_line(2)
+                .iter()
+                .all(|t| t.kind == crate::highlight::TokenKind::String)
+        );
+    }
+
+    #[test]
+    fn changing_string_kind_retokenizes_until_string_closes() {
+        let (mut buffer, calls) = highlighted();
+        // `"""` -> `f"""` changes the state of every line inside the string,
+        // but the closing line ends in `Normal` either way, so the pass stops
+        // there without touching `y = 2` / `z = 3`.
+        replace_line(&mut buffer, 1, "s = f\"\"\"");
+        buffer.update_highlighting();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn closing_string_early_propagates_to_end() {
+        let (mut buffer, calls) = highlighted();
+        replace_line(&mut buffer, 2, "alpha\"\"\"");
+        buffer.update_highlighting();
+
+        // Lines 2..=6 all change state: beta becomes code, the old closing
+        // quote opens a new string that swallows the rest of the file.
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        assert!(
+            buffer
+                .tokens_for_line(3)
+                .iter()
+                .any(|t| t.kind == crate::highlight::TokenKind::Identifier)
+        );
+    }
+}
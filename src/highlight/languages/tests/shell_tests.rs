@@ -0,0 +1,166 @@
+use std::sync::Once;
+
+use tracing::{debug, info};
+
+use crate::highlight::languages::shell::ShellTokenizer;
+use crate::highlight::{
+    HeredocKind, LineState, StringKind, TokenKind, Tokenizer, TokenizerRegistry,
+};
+
+fn setup_test_logging() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_test_writer()
+            .try_init();
+    });
+}
+
+type ScriptTokens<'a> = (Vec<Vec<(TokenKind, &'a str)>>, Vec<LineState>);
+
+/// Tokenize a script line by line, returning (kind, text) pairs and end states.
+fn tokenize_script(text: &str) -> ScriptTokens<'_> {
+    let tokenizer = ShellTokenizer::new();
+    let mut state = LineState::Normal;
+    let mut lines = Vec::new();
+    let mut states = Vec::new();
+    for line in text.lines() {
+        info!(line, ?state, "tokenizing");
+        let (tokens, next) = tokenizer.tokenize_line(line, state);
+        debug!(?tokens, ?next, "tokenization result");
+        lines.push(
+            tokens
+                .iter()
+                .map(|token| (token.kind, &line[token.range()]))
+                .collect(),
+        );
+        states.push(next);
+        state = next;
+    }
+    (lines, states)
+}
+
+#[test]
+fn test_shell_script_snapshot() {
+    use TokenKind::{
+        Attribute, Comment, Function, Identifier, Keyword, KeywordControl, Label, Operator,
+        Punctuation, String, Text,
+    };
+
+    setup_test_logging();
+    let script = r#"#!/usr/bin/env bash
+set -euo pipefail
+NAME="${1%.txt}"
+if [[ -n "$NAME" ]]; then
+  cat <<EOF > greeting.txt
+if [ "$x" ]; then rm -rf /; fi # still heredoc
+Hello, $NAME from $(hostname)!
+EOF
+fi"#;
+
+    let expected: Vec<Vec<(TokenKind, &str)>> = vec![
+        vec![(Comment, "#!/usr/bin/env bash")],
+        vec![(Function, "set"), (Attribute, "-euo"), (Text, "pipefail")],
+        vec![
+            (Identifier, "NAME"),
+            (Operator, "="),
+            (String, "\""),
+            (Identifier, "${1%.txt}"),
+            (String, "\""),
+        ],
+        vec![
+            (KeywordControl, "if"),
+            (Keyword, "[["),
+            (Attribute, "-n"),
+            (String, "\""),
+            (Identifier, "$NAME"),
+            (String, "\""),
+            (Keyword, "]]"),
+            (Operator, ";"),
+            (KeywordControl, "then"),
+        ],
+        vec![
+            (Function, "cat"),
+            (Operator, "<<"),
+            (Label, "EOF"),
+            (Operator, ">"),
+            (Text, "greeting.txt"),
+        ],
+        // Heredoc body: shell-looking text stays string content.
+        vec![
+            (String, "if [ \""),
+            (Identifier, "$x"),
+            (String, "\" ]; then rm -rf /; fi # still heredoc"),
+        ],
+        vec![
+            (String, "Hello, "),
+            (Identifier, "$NAME"),
+            (String, " from "),
+            (Punctuation, "$("),
+            (Function, "hostname"),
+            (Punctuation, ")"),
+            (String, "!"),
+        ],
+        vec![(Label, "EOF")],
+        vec![(KeywordControl, "fi")],
+    ];
+
+    let (lines, states) = tokenize_script(script);
+    assert_eq!(lines, expected);
+    let heredoc = LineState::InHeredoc(HeredocKind::shell("EOF", false, false));
+    assert_eq!(&states[4..7], &[heredoc, heredoc, heredoc]);
+    assert_eq!(states[7], LineState::Normal);
+}
+
+#[test]
+fn test_shell_quoted_heredoc_suppresses_interpolation() {
+    setup_test_logging();
+    let script = "python3 - <<\"PY\"\nprint(f\"{$HOME}\")  # $(not run)\nPY";
+    let (lines, states) = tokenize_script(script);
+
+    assert_eq!(
+        lines[1],
+        vec![(TokenKind::String, "print(f\"{$HOME}\")  # $(not run)")]
+    );
+    assert_eq!(lines[2], vec![(TokenKind::Label, "PY")]);
+    assert_eq!(states[2], LineState::Normal);
+}
+
+#[test]
+fn test_shell_heredoc_terminator_must_match_exactly() {
+    setup_test_logging();
+    // Without `<<-`, an indented delimiter does not end the heredoc.
+    let (_, states) = tokenize_script("cat <<END\n  END\nEND");
+    assert!(matches!(states[1], LineState::InHeredoc(_)));
+    assert_eq!(states[2], LineState::Normal);
+}
+
+#[test]
+fn test_shell_multiline_double_quote() {
+    setup_test_logging();
+    let (lines, states) = tokenize_script("echo \"one\n$two\" three");
+    assert_eq!(states[0], LineState::InString(StringKind::Double));
+    assert_eq!(
+        lines[1],
+        vec![
+            (TokenKind::Identifier, "$two"),
+            (TokenKind::String, "\""),
+            (TokenKind::Text, "three"),
+        ]
+    );
+}
+
+#[test]
+fn test_shell_registered_names() {
+    let registry = TokenizerRegistry::with_builtins();
+    for name in ["sh", "bash", "zsh", "shell"] {
+        assert_eq!(registry.by_name(name).map(Tokenizer::name), Some("Shell"));
+    }
+    for ext in ["sh", "bash", "zsh"] {
+        assert_eq!(
+            registry.for_extension(ext).map(Tokenizer::name),
+            Some("Shell")
+        );
+    }
+}
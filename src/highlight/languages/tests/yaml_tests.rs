@@ -0,0 +1,159 @@
+use std::sync::Once;
+
+use tracing::{debug, info};
+
+use crate::highlight::languages::yaml::YamlTokenizer;
+use crate::highlight::{LineState, TokenKind, Tokenizer};
+
+fn setup_test_logging() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_test_writer()
+            .try_init();
+    });
+}
+
+/// Tokenize a document line by line, returning (kind, text) pairs per line.
+fn tokenize_document(text: &str) -> Vec<Vec<(TokenKind, &str)>> {
+    let tokenizer = YamlTokenizer::new();
+    let mut state = LineState::Normal;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        info!(line, ?state, "tokenizing");
+        let (tokens, next) = tokenizer.tokenize_line(line, state);
+        debug!(?tokens, ?next, "tokenization result");
+        lines.push(
+            tokens
+                .iter()
+                .map(|token| (token.kind, &line[token.range()]))
+                .collect(),
+        );
+        state = next;
+    }
+    lines
+}
+
+#[test]
+fn test_yaml_document_snapshot() {
+    setup_test_logging();
+    let doc = "\
+%YAML 1.2
+---
+# Deployment config
+name: \"web\"
+replicas: 3
+defaults: &defaults
+  image: nginx:1.25
+  env: [prod, {debug: false}]
+service:
+  <<: *defaults
+  port: !!int \"8080\"
+- item
+...";
+
+    use TokenKind::{
+        Attribute, Boolean, Comment, Delimiter, Identifier, Label, Number, Punctuation, String,
+    };
+    let expected: Vec<Vec<(TokenKind, &str)>> = vec![
+        vec![(Attribute, "%YAML 1.2")],
+        vec![(Punctuation, "---")],
+        vec![(Comment, "# Deployment config")],
+        vec![(Identifier, "name"), (Delimiter, ":"), (String, "\"web\"")],
+        vec![(Identifier, "replicas"), (Delimiter, ":"), (Number, "3")],
+        vec![
+            (Identifier, "defaults"),
+            (Delimiter, ":"),
+            (Label, "&defaults"),
+        ],
+        vec![
+            (Identifier, "image"),
+            (Delimiter, ":"),
+            (String, "nginx:1.25"),
+        ],
+        vec![
+            (Identifier, "env"),
+            (Delimiter, ":"),
+            (Punctuation, "["),
+            (String, "prod"),
+            (Delimiter, ","),
+            (Punctuation, "{"),
+            (Identifier, "debug"),
+            (Delimiter, ":"),
+            (Boolean, "false"),
+            (Punctuation, "}"),
+            (Punctuation, "]"),
+        ],
+        vec![(Identifier, "service"), (Delimiter, ":")],
+        vec![(Identifier, "<<"), (Delimiter, ":"), (Label, "*defaults")],
+        vec![
+            (Identifier, "port"),
+            (Delimiter, ":"),
+            (Attribute, "!!int"),
+            (String, "\"8080\""),
+        ],
+        vec![(Punctuation, "-"), (String, "item")],
+        vec![(Punctuation, "...")],
+    ];
+
+    assert_eq!(tokenize_document(doc), expected);
+}
+
+#[test]
+fn test_yaml_block_scalars_survive_line_by_line() {
+    setup_test_logging();
+    let doc = "\
+steps:
+  - run: |
+      echo \"key: value\" # not a comment
+        indented: still text
+
+      - not a list item
+  - name: >-
+      folded
+  - last: 1";
+
+    use TokenKind::{Delimiter, Identifier, Number, Operator, Punctuation, String};
+    let expected: Vec<Vec<(TokenKind, &str)>> = vec![
+        vec![(Identifier, "steps"), (Delimiter, ":")],
+        vec![
+            (Punctuation, "-"),
+            (Identifier, "run"),
+            (Delimiter, ":"),
+            (Operator, "|"),
+        ],
+        vec![(String, "echo \"key: value\" # not a comment")],
+        vec![(String, "indented: still text")],
+        vec![],
+        vec![(String, "- not a list item")],
+        vec![
+            (Punctuation, "-"),
+            (Identifier, "name"),
+            (Delimiter, ":"),
+            (Operator, ">-"),
+        ],
+        vec![(String, "folded")],
+        vec![
+            (Punctuation, "-"),
+            (Identifier, "last"),
+            (Delimiter, ":"),
+            (Number, "1"),
+        ],
+    ];
+
+    assert_eq!(tokenize_document(doc), expected);
+}
+
+#[test]
+fn test_yaml_block_scalar_ends_at_sibling_key() {
+    setup_test_logging();
+    let tokenizer = YamlTokenizer::new();
+    let (_, state) = tokenizer.tokenize_line("- key: |", LineState::Normal);
+    assert_eq!(state, LineState::InBlockScalar(2));
+
+    // A sibling key in the same sequence item is not scalar content.
+    let (tokens, state) = tokenizer.tokenize_line("  other: x", state);
+    assert_eq!(tokens[0].kind, TokenKind::Identifier);
+    assert_eq!(state, LineState::Normal);
+}
@@ -294,14 +294,14 @@ impl Tokenizer for TomlTokenizer {
                         while let Some(&(i, c)) = chars.peek() {
                             if c.is_alphanumeric() || c == '_' || c == '-' {
                                 chars.next();
-                                end = i + 1;
+                                end = i + c.len_utf8();
                             } else {
                                 break;
                             }
                         }
                         tokens.push(Token::new(TokenKind::Identifier, idx, end));
                     } else {
-                        tokens.push(Token::new(TokenKind::Error, idx, idx + 1));
+                        tokens.push(Token::new(TokenKind::Error, idx, idx + ch.len_utf8()));
                     }
                 }
 
@@ -316,23 +316,23 @@ impl Tokenizer for TomlTokenizer {
                         while let Some(&(i, c)) = chars.peek() {
                             if c.is_alphanumeric() || c == '_' || c == '-' {
                                 chars.next();
-                                end = i + 1;
+                                end = i + c.len_utf8();
                             } else {
                                 break;
                             }
                         }
                         tokens.push(Token::new(TokenKind::Identifier, idx, end));
                     } else {
-                        tokens.push(Token::new(TokenKind::Error, idx, idx + 1));
+                        tokens.push(Token::new(TokenKind::Error, idx, idx + ch.len_utf8()));
                     }
                 }
 
                 c if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' => {
-                    let mut end = idx + 1;
+                    let mut end = idx + ch.len_utf8();
                     while let Some(&(i, c)) = chars.peek() {
                         if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
                             chars.next();
-                            end = i + 1;
+                            end = i + c.len_utf8();
                         } else {
                             break;
                         }
@@ -345,7 +345,7 @@ impl Tokenizer for TomlTokenizer {
                 }
 
                 _ => {
-                    tokens.push(Token::new(TokenKind::Text, idx, idx + 1));
+                    tokens.push(Token::new(TokenKind::Text, idx, idx + ch.len_utf8()));
                 }
             }
         }
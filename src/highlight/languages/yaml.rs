@@ -0,0 +1,549 @@
+use crate::highlight::token::{Token, TokenKind};
+use crate::highlight::tokenizer::{LineState, StringKind, Tokenizer};
+
+pub struct YamlTokenizer;
+
+impl Default for YamlTokenizer {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl YamlTokenizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn indent_of(line: &str) -> usize {
+        line.bytes().take_while(|&b| b == b' ').count()
+    }
+
+    fn is_blank_at(bytes: &[u8], idx: usize) -> bool {
+        idx >= bytes.len() || matches!(bytes[idx], b' ' | b'\t')
+    }
+
+    fn is_flow_indicator(byte: u8) -> bool {
+        matches!(byte, b',' | b'[' | b']' | b'{' | b'}')
+    }
+
+    /// A `:` is a mapping value indicator when followed by whitespace, end of
+    /// line, or (inside flow collections) a flow indicator.
+    fn is_value_colon(bytes: &[u8], idx: usize, in_flow: bool) -> bool {
+        bytes[idx] == b':'
+            && (Self::is_blank_at(bytes, idx + 1)
+                || (in_flow && Self::is_flow_indicator(bytes[idx + 1])))
+    }
+
+    /// Returns true if the next non-blank byte at or after `idx` is a value `:`.
+    fn followed_by_colon(bytes: &[u8], idx: usize, in_flow: bool) -> bool {
+        let mut pos = idx;
+        while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t') {
+            pos += 1;
+        }
+        pos < bytes.len() && Self::is_value_colon(bytes, pos, in_flow)
+    }
+
+    /// Scan a double-quoted scalar body starting at `from`, splitting escape
+    /// sequences into their own tokens. Returns (end, closed).
+    fn scan_double_quoted(
+        line: &str,
+        segment_start: usize,
+        from: usize,
+        tokens: &mut Vec<Token>,
+    ) -> (usize, bool) {
+        let bytes = line.as_bytes();
+        let mut segment = segment_start;
+        let mut pos = from;
+
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'\\' => {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    let mut end =
+                        (pos + 1 + line[pos + 1..].chars().next().map_or(0, char::len_utf8))
+                            .min(bytes.len());
+                    let hex_digits = match bytes.get(pos + 1) {
+                        Some(b'x') => 2,
+                        Some(b'u') => 4,
+                        Some(b'U') => 8,
+                        _ => 0,
+                    };
+                    for _ in 0..hex_digits {
+                        if end < bytes.len() && bytes[end].is_ascii_hexdigit() {
+                            end += 1;
+                        }
+                    }
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, end));
+                    pos = end;
+                    segment = end;
+                }
+                b'"' => {
+                    tokens.push(Token::new(TokenKind::String, segment, pos + 1));
+                    return (pos + 1, true);
+                }
+                _ => pos += 1,
+            }
+        }
+
+        if segment < bytes.len() {
+            tokens.push(Token::new(TokenKind::String, segment, bytes.len()));
+        }
+        (bytes.len(), false)
+    }
+
+    /// Scan a single-quoted scalar body starting at `from`; `''` is the only
+    /// escape. Returns (end, closed).
+    fn scan_single_quoted(
+        line: &str,
+        segment_start: usize,
+        from: usize,
+        tokens: &mut Vec<Token>,
+    ) -> (usize, bool) {
+        let bytes = line.as_bytes();
+        let mut segment = segment_start;
+        let mut pos = from;
+
+        while pos < bytes.len() {
+            if bytes[pos] == b'\'' {
+                if bytes.get(pos + 1) == Some(&b'\'') {
+                    if segment < pos {
+                        tokens.push(Token::new(TokenKind::String, segment, pos));
+                    }
+                    tokens.push(Token::new(TokenKind::StringEscape, pos, pos + 2));
+                    pos += 2;
+                    segment = pos;
+                    continue;
+                }
+                tokens.push(Token::new(TokenKind::String, segment, pos + 1));
+                return (pos + 1, true);
+            }
+            pos += 1;
+        }
+
+        if segment < bytes.len() {
+            tokens.push(Token::new(TokenKind::String, segment, bytes.len()));
+        }
+        (bytes.len(), false)
+    }
+
+    /// Scan an anchor, alias, or tag name until whitespace or a flow indicator.
+    fn scan_name(bytes: &[u8], from: usize, in_flow: bool) -> usize {
+        let mut pos = from;
+        while pos < bytes.len()
+            && !matches!(bytes[pos], b' ' | b'\t')
+            && !(in_flow && Self::is_flow_indicator(bytes[pos]))
+        {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Scan a plain (unquoted) scalar. Returns (end, is_key) where `end` has
+    /// trailing whitespace trimmed.
+    fn scan_plain(bytes: &[u8], from: usize, in_flow: bool) -> (usize, bool) {
+        let mut pos = from;
+        let mut is_key = false;
+
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            if b == b':' && Self::is_value_colon(bytes, pos, in_flow) {
+                is_key = true;
+                break;
+            }
+            if b == b'#' && pos > from && matches!(bytes[pos - 1], b' ' | b'\t') {
+                break;
+            }
+            if in_flow && Self::is_flow_indicator(b) {
+                break;
+            }
+            pos += 1;
+        }
+
+        while pos > from && matches!(bytes[pos - 1], b' ' | b'\t') {
+            pos -= 1;
+        }
+        (pos, is_key)
+    }
+
+    /// Classify a plain scalar value per the YAML core schema (plus the common
+    /// YAML 1.1 boolean spellings).
+    fn classify_plain(text: &str) -> TokenKind {
+        match text {
+            "~" | "null" | "Null" | "NULL" => TokenKind::Constant,
+            "true" | "True" | "TRUE" | "false" | "False" | "FALSE" | "yes" | "Yes" | "YES"
+            | "no" | "No" | "NO" | "on" | "On" | "ON" | "off" | "Off" | "OFF" => TokenKind::Boolean,
+            _ if Self::is_number(text) => TokenKind::Number,
+            _ => TokenKind::String,
+        }
+    }
+
+    fn is_number(text: &str) -> bool {
+        let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+        if matches!(unsigned, ".inf" | ".Inf" | ".INF") {
+            return true;
+        }
+        if matches!(text, ".nan" | ".NaN" | ".NAN") {
+            return true;
+        }
+        if let Some(hex) = text.strip_prefix("0x") {
+            return !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit() || b == b'_');
+        }
+        if let Some(oct) = text.strip_prefix("0o") {
+            return !oct.is_empty() && oct.bytes().all(|b| matches!(b, b'0'..=b'7' | b'_'));
+        }
+
+        let bytes = unsigned.as_bytes();
+        let mut pos = 0;
+        let mut mantissa_digits = 0;
+        while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'_') {
+            mantissa_digits += usize::from(bytes[pos] != b'_');
+            pos += 1;
+        }
+        if pos < bytes.len() && bytes[pos] == b'.' {
+            pos += 1;
+            while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'_') {
+                mantissa_digits += usize::from(bytes[pos] != b'_');
+                pos += 1;
+            }
+        }
+        if mantissa_digits == 0 {
+            return false;
+        }
+        if pos < bytes.len() && matches!(bytes[pos], b'e' | b'E') {
+            pos += 1;
+            if pos < bytes.len() && matches!(bytes[pos], b'+' | b'-') {
+                pos += 1;
+            }
+            let exp_start = pos;
+            while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            if pos == exp_start {
+                return false;
+            }
+        }
+        pos == bytes.len()
+    }
+
+    /// Mark the string tokens of a quoted scalar as a mapping key.
+    fn retag_as_key(tokens: &mut [Token]) {
+        for token in tokens.iter_mut().rev() {
+            if token.kind == TokenKind::String {
+                token.kind = TokenKind::Identifier;
+            } else if token.kind != TokenKind::StringEscape {
+                break;
+            }
+        }
+    }
+}
+
+impl Tokenizer for YamlTokenizer {
+    fn name(&self) -> &'static str {
+        "YAML"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["yaml", "yml"]
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState) {
+        let mut tokens = Vec::new();
+        let bytes = line.as_bytes();
+        let mut pos = 0usize;
+
+        match state {
+            LineState::InBlockScalar(parent) => {
+                // Blank lines never terminate a block scalar; content lines are
+                // those indented deeper than the node that introduced it.
+                if line.trim().is_empty() {
+                    return (tokens, state);
+                }
+                let indent = Self::indent_of(line);
+                if indent > usize::from(parent) {
+                    tokens.push(Token::new(TokenKind::String, indent, line.len()));
+                    return (tokens, state);
+                }
+            }
+            LineState::InString(StringKind::Double) => {
+                let (end, closed) = Self::scan_double_quoted(line, 0, 0, &mut tokens);
+                if !closed {
+                    return (tokens, state);
+                }
+                pos = end;
+            }
+            LineState::InString(StringKind::Single) => {
+                let (end, closed) = Self::scan_single_quoted(line, 0, 0, &mut tokens);
+                if !closed {
+                    return (tokens, state);
+                }
+                pos = end;
+            }
+            _ => {}
+        }
+
+        if pos == 0 {
+            if line.starts_with('%') {
+                let (end, _) = Self::scan_plain(bytes, 0, false);
+                tokens.push(Token::new(TokenKind::Attribute, 0, end));
+                pos = end;
+            } else if (line.starts_with("---") || line.starts_with("..."))
+                && Self::is_blank_at(bytes, 3)
+            {
+                tokens.push(Token::new(TokenKind::Punctuation, 0, 3));
+                pos = 3;
+            }
+        }
+
+        let mut flow_depth = 0usize;
+        let mut node_indent = Self::indent_of(line);
+        let mut block_scalar = false;
+
+        while pos < bytes.len() {
+            let in_flow = flow_depth > 0;
+            let b = bytes[pos];
+
+            match b {
+                b' ' | b'\t' => pos += 1,
+
+                b'#' if pos == 0 || matches!(bytes[pos - 1], b' ' | b'\t') => {
+                    tokens.push(Token::new(TokenKind::Comment, pos, line.len()));
+                    break;
+                }
+
+                b'-' | b'?' if !in_flow && Self::is_blank_at(bytes, pos + 1) => {
+                    tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+                    node_indent = pos;
+                    pos += 1;
+                }
+
+                b':' if Self::is_value_colon(bytes, pos, in_flow) => {
+                    tokens.push(Token::new(TokenKind::Delimiter, pos, pos + 1));
+                    pos += 1;
+                }
+
+                b',' if in_flow => {
+                    tokens.push(Token::new(TokenKind::Delimiter, pos, pos + 1));
+                    pos += 1;
+                }
+
+                b'[' | b'{' => {
+                    tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+                    flow_depth += 1;
+                    pos += 1;
+                }
+
+                b']' | b'}' => {
+                    tokens.push(Token::new(TokenKind::Punctuation, pos, pos + 1));
+                    flow_depth = flow_depth.saturating_sub(1);
+                    pos += 1;
+                }
+
+                b'&' | b'*' => {
+                    let end = Self::scan_name(bytes, pos + 1, in_flow);
+                    tokens.push(Token::new(TokenKind::Label, pos, end));
+                    pos = end;
+                }
+
+                b'!' => {
+                    let end = Self::scan_name(bytes, pos + 1, in_flow);
+                    tokens.push(Token::new(TokenKind::Attribute, pos, end));
+                    pos = end;
+                }
+
+                b'|' | b'>' if !in_flow => {
+                    let mut end = pos + 1;
+                    while end < bytes.len() && matches!(bytes[end], b'+' | b'-' | b'1'..=b'9') {
+                        end += 1;
+                    }
+                    tokens.push(Token::new(TokenKind::Operator, pos, end));
+                    block_scalar = true;
+                    pos = end;
+                }
+
+                b'"' => {
+                    let first = tokens.len();
+                    let (end, closed) = Self::scan_double_quoted(line, pos, pos + 1, &mut tokens);
+                    if !closed {
+                        return (tokens, LineState::InString(StringKind::Double));
+                    }
+                    if Self::followed_by_colon(bytes, end, in_flow) {
+                        Self::retag_as_key(&mut tokens[first..]);
+                        node_indent = pos;
+                    }
+                    pos = end;
+                }
+
+                b'\'' => {
+                    let first = tokens.len();
+                    let (end, closed) = Self::scan_single_quoted(line, pos, pos + 1, &mut tokens);
+                    if !closed {
+                        return (tokens, LineState::InString(StringKind::Single));
+                    }
+                    if Self::followed_by_colon(bytes, end, in_flow) {
+                        Self::retag_as_key(&mut tokens[first..]);
+                        node_indent = pos;
+                    }
+                    pos = end;
+                }
+
+                _ => {
+                    let (end, is_key) = Self::scan_plain(bytes, pos, in_flow);
+                    if end == pos {
+                        // Stray indicator (e.g. `#` without preceding space).
+                        let len = line[pos..].chars().next().map_or(1, char::len_utf8);
+                        tokens.push(Token::new(TokenKind::Text, pos, pos + len));
+                        pos += len;
+                        continue;
+                    }
+                    let kind = if is_key {
+                        node_indent = pos;
+                        TokenKind::Identifier
+                    } else {
+                        Self::classify_plain(&line[pos..end])
+                    };
+                    tokens.push(Token::new(kind, pos, end));
+                    pos = end;
+                }
+            }
+        }
+
+        if block_scalar {
+            let parent = u16::try_from(node_indent).unwrap_or(u16::MAX);
+            return (tokens, LineState::InBlockScalar(parent));
+        }
+        (tokens, LineState::Normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<(TokenKind, &str)> {
+        let (tokens, _) = YamlTokenizer::new().tokenize_line(line, LineState::Normal);
+        tokens
+            .iter()
+            .map(|token| (token.kind, &line[token.range()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_key_value() {
+        assert_eq!(
+            kinds("name: opentui # the crate"),
+            vec![
+                (TokenKind::Identifier, "name"),
+                (TokenKind::Delimiter, ":"),
+                (TokenKind::String, "opentui"),
+                (TokenKind::Comment, "# the crate"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_scalar_types() {
+        let line = "[1, -2.5e3, 0x1F, .inf, true, ~, text]";
+        let values: Vec<_> = kinds(line)
+            .into_iter()
+            .filter(|(kind, _)| !matches!(kind, TokenKind::Punctuation | TokenKind::Delimiter))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (TokenKind::Number, "1"),
+                (TokenKind::Number, "-2.5e3"),
+                (TokenKind::Number, "0x1F"),
+                (TokenKind::Number, ".inf"),
+                (TokenKind::Boolean, "true"),
+                (TokenKind::Constant, "~"),
+                (TokenKind::String, "text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_anchor_alias_tag() {
+        assert_eq!(
+            kinds("base: &defaults !!map {a: *other}"),
+            vec![
+                (TokenKind::Identifier, "base"),
+                (TokenKind::Delimiter, ":"),
+                (TokenKind::Label, "&defaults"),
+                (TokenKind::Attribute, "!!map"),
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Identifier, "a"),
+                (TokenKind::Delimiter, ":"),
+                (TokenKind::Label, "*other"),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_quoted_key_and_escapes() {
+        assert_eq!(
+            kinds("\"a b\": 'it''s'"),
+            vec![
+                (TokenKind::Identifier, "\"a b\""),
+                (TokenKind::Delimiter, ":"),
+                (TokenKind::String, "'it"),
+                (TokenKind::StringEscape, "''"),
+                (TokenKind::String, "s'"),
+            ]
+        );
+        assert!(
+            kinds("msg: \"tab\\there \\u00e9\"")
+                .iter()
+                .filter(|(kind, _)| *kind == TokenKind::StringEscape)
+                .map(|(_, text)| *text)
+                .eq(["\\t", "\\u00e9"])
+        );
+    }
+
+    #[test]
+    fn test_yaml_block_scalar_state() {
+        let tokenizer = YamlTokenizer::new();
+        let (tokens, state) = tokenizer.tokenize_line("  script: |-", LineState::Normal);
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Operator);
+        assert_eq!(state, LineState::InBlockScalar(2));
+
+        let (tokens, state) = tokenizer.tokenize_line("    key: not a key", state);
+        assert_eq!(tokens, vec![Token::new(TokenKind::String, 4, 18)]);
+        assert_eq!(state, LineState::InBlockScalar(2));
+
+        let (tokens, state) = tokenizer.tokenize_line("", state);
+        assert!(tokens.is_empty());
+        assert_eq!(state, LineState::InBlockScalar(2));
+
+        let (tokens, state) = tokenizer.tokenize_line("  next: 1", state);
+        assert_eq!(tokens[0].kind, TokenKind::Identifier);
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn test_yaml_multiline_quoted_string() {
+        let tokenizer = YamlTokenizer::new();
+        let (_, state) = tokenizer.tokenize_line("text: \"first", LineState::Normal);
+        assert_eq!(state, LineState::InString(StringKind::Double));
+        let (tokens, state) = tokenizer.tokenize_line("  second\" # done", state);
+        assert_eq!(tokens[0].kind, TokenKind::String);
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Comment);
+        assert_eq!(state, LineState::Normal);
+    }
+
+    #[test]
+    fn test_yaml_plain_scalar_with_colon_and_hash() {
+        assert_eq!(
+            kinds("url: http://example.com/#frag"),
+            vec![
+                (TokenKind::Identifier, "url"),
+                (TokenKind::Delimiter, ":"),
+                (TokenKind::String, "http://example.com/#frag"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Named highlight layers composed over syntax styles at render time.
+//!
+//! Each layer has a priority and a [`LayerMerge`] policy. When rendering a
+//! char, the syntax style is the base and every layer covering the char is
+//! applied on top:
+//!
+//! - Layers apply in ascending priority; equal priorities apply in the order
+//!   the layers were added. Later layers win.
+//! - Within a layer, ranges apply in order of their start offset.
+//! - A layer only touches the parts of the style its policy allows: a set
+//!   `fg`/`bg` replaces the color below it, attributes are OR-merged.
+
+use std::ops::Range;
+
+use bitflags::bitflags;
+
+use crate::highlight::diagnostic::shift_range;
+use crate::style::{Style, StylePatch};
+
+/// Layer holding the editor selection.
+pub const SELECTION_LAYER: &str = "selection";
+/// Layer holding search matches.
+pub const SEARCH_LAYER: &str = "search";
+/// Layer holding the bracket pair at the cursor.
+pub const BRACKET_MATCH_LAYER: &str = "bracket-match";
+
+/// Priority of the built-in [`BRACKET_MATCH_LAYER`].
+pub const BRACKET_MATCH_LAYER_PRIORITY: u8 = 50;
+/// Priority of the built-in [`SEARCH_LAYER`].
+pub const SEARCH_LAYER_PRIORITY: u8 = 100;
+/// Priority of the built-in [`SELECTION_LAYER`].
+pub const SELECTION_LAYER_PRIORITY: u8 = 200;
+
+bitflags! {
+    /// Which parts of a style a layer may override.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct LayerMerge: u8 {
+        /// Replace the foreground color.
+        const FG         = 0x01;
+        /// Replace the background color.
+        const BG         = 0x02;
+        /// OR in text attributes, with the underline shape and color.
+        const ATTRIBUTES = 0x04;
+        /// Foreground, background, and attributes.
+        const ALL        = Self::FG.bits() | Self::BG.bits() | Self::ATTRIBUTES.bits();
+    }
+}
+
+impl LayerMerge {
+    /// Apply the permitted parts of `style` over `base`.
+    #[must_use]
+    pub fn apply(self, base: Style, style: Style) -> Style {
+        let full = StylePatch::from(style);
+        let mut patch = if self.contains(Self::ATTRIBUTES) {
+            full
+        } else {
+            StylePatch::default()
+        };
+        patch.fg = full.fg.filter(|_| self.contains(Self::FG));
+        patch.bg = full.bg.filter(|_| self.contains(Self::BG));
+        base.apply(&patch)
+    }
+}
+
+/// A named set of styled char ranges.
+#[derive(Clone, Debug)]
+pub struct HighlightLayer {
+    name: String,
+    priority: u8,
+    merge: LayerMerge,
+    // Sorted by range start
+    ranges: Vec<(Range<usize>, Style)>,
+}
+
+impl HighlightLayer {
+    /// Layer name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Layer priority; higher priorities apply later.
+    #[must_use]
+    pub const fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Parts of the style this layer may override.
+    #[must_use]
+    pub const fn merge(&self) -> LayerMerge {
+        self.merge
+    }
+
+    /// Styled ranges, in char offsets, sorted by start.
+    #[must_use]
+    pub fn ranges(&self) -> &[(Range<usize>, Style)] {
+        &self.ranges
+    }
+
+    fn apply(&self, offset: usize, mut style: Style) -> Style {
+        let candidates = self
+            .ranges
+            .partition_point(|(range, _)| range.start <= offset);
+        for (range, layer_style) in &self.ranges[..candidates] {
+            if range.contains(&offset) {
+                style = self.merge.apply(style, *layer_style);
+            }
+        }
+        style
+    }
+}
+
+/// Ordered collection of [`HighlightLayer`]s.
+///
+/// The default collection contains the built-in [`BRACKET_MATCH_LAYER`] and
+/// [`SEARCH_LAYER`] (background and attributes, so syntax colors stay
+/// readable) below the [`SELECTION_LAYER`] (full style).
+#[derive(Clone, Debug)]
+pub struct HighlightLayers {
+    // Sorted by priority, insertion order within a priority
+    layers: Vec<HighlightLayer>,
+}
+
+impl Default for HighlightLayers {
+    fn default() -> Self {
+        let mut layers = Self::empty();
+        layers.add(
+            BRACKET_MATCH_LAYER,
+            BRACKET_MATCH_LAYER_PRIORITY,
+            LayerMerge::BG | LayerMerge::ATTRIBUTES,
+        );
+        layers.add(
+            SEARCH_LAYER,
+            SEARCH_LAYER_PRIORITY,
+            LayerMerge::BG | LayerMerge::ATTRIBUTES,
+        );
+        layers.add(SELECTION_LAYER, SELECTION_LAYER_PRIORITY, LayerMerge::ALL);
+        layers
+    }
+}
+
+impl HighlightLayers {
+    /// Create a collection without the built-in layers.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Add a layer, replacing (and clearing) any layer with the same name.
+    pub fn add(&mut self, name: &str, priority: u8, merge: LayerMerge) {
+        self.remove(name);
+        let index = self
+            .layers
+            .partition_point(|layer| layer.priority <= priority);
+        self.layers.insert(
+            index,
+            HighlightLayer {
+                name: name.to_string(),
+                priority,
+                merge,
+                ranges: Vec::new(),
+            },
+        );
+    }
+
+    /// Remove a layer. Returns `false` if no layer has that name.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.layers.len();
+        self.layers.retain(|layer| layer.name != name);
+        self.layers.len() != before
+    }
+
+    /// Look up a layer by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&HighlightLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Replace a layer's ranges. Returns `false` if no layer has that name.
+    pub fn set_ranges(&mut self, name: &str, mut ranges: Vec<(Range<usize>, Style)>) -> bool {
+        let Some(layer) = self.layers.iter_mut().find(|layer| layer.name == name) else {
+            return false;
+        };
+        ranges.retain(|(range, _)| !range.is_empty());
+        ranges.sort_by_key(|(range, _)| range.start);
+        layer.ranges = ranges;
+        true
+    }
+
+    /// Clear the ranges of every layer, keeping the layers themselves.
+    pub fn clear_ranges(&mut self) {
+        for layer in &mut self.layers {
+            layer.ranges.clear();
+        }
+    }
+
+    /// Iterate layers in the order they are applied.
+    pub fn iter(&self) -> impl Iterator<Item = &HighlightLayer> {
+        self.layers.iter()
+    }
+
+    /// Returns true if no layer has any ranges.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|layer| layer.ranges.is_empty())
+    }
+
+    /// Compose every layer covering the char at `offset` over `base`.
+    #[must_use]
+    pub fn style_at(&self, offset: usize, base: Style) -> Style {
+        self.layers
+            .iter()
+            .fold(base, |style, layer| layer.apply(offset, style))
+    }
+
+    /// Shift ranges for an edit that replaced `removed` chars at `offset`
+    /// with `inserted` chars, dropping ranges the edit deleted.
+    pub fn apply_edit(&mut self, offset: usize, removed: usize, inserted: usize) {
+        for layer in &mut self.layers {
+            layer
+                .ranges
+                .retain_mut(|(range, _)| shift_range(range, offset, removed, inserted));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgba;
+
+    const CURRENT_LINE: &str = "current-line";
+
+    fn syntax() -> Style {
+        Style::fg(Rgba::GREEN).with_italic()
+    }
+
+    /// current-line (bg) < search (bg + attrs) < selection (all), all over 0..10.
+    fn stacked() -> HighlightLayers {
+        let mut layers = HighlightLayers::default();
+        layers.add(CURRENT_LINE, 10, LayerMerge::BG);
+        layers.set_ranges(
+            CURRENT_LINE,
+            vec![(0..10, Style::bg(Rgba::BLACK).with_bold())],
+        );
+        layers.set_ranges(
+            SEARCH_LAYER,
+            vec![(2..6, Style::bg(Rgba::rgb(1.0, 1.0, 0.0)).with_underline())],
+        );
+        layers.set_ranges(SELECTION_LAYER, vec![(4..8, Style::bg(Rgba::BLUE))]);
+        layers
+    }
+
+    #[test]
+    fn test_layers_apply_in_priority_order() {
+        let layers = stacked();
+        let names: Vec<_> = layers.iter().map(HighlightLayer::name).collect();
+        assert_eq!(
+            names,
+            [
+                CURRENT_LINE,
+                BRACKET_MATCH_LAYER,
+                SEARCH_LAYER,
+                SELECTION_LAYER
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_layers_compose_per_cell() {
+        let layers = stacked();
+        let yellow = Rgba::rgb(1.0, 1.0, 0.0);
+
+        // Current line only: bg replaced, its bold ignored (BG policy).
+        assert_eq!(layers.style_at(0, syntax()), syntax().with_bg(Rgba::BLACK));
+        // Search over current line: search bg wins, underline added, syntax fg kept.
+        assert_eq!(
+            layers.style_at(2, syntax()),
+            syntax().with_bg(yellow).with_underline()
+        );
+        // Selection over search: selection bg wins, search underline survives.
+        assert_eq!(
+            layers.style_at(4, syntax()),
+            syntax().with_bg(Rgba::BLUE).with_underline()
+        );
+        // Selection without search.
+        assert_eq!(layers.style_at(7, syntax()), syntax().with_bg(Rgba::BLUE));
+        // Outside every layer.
+        assert_eq!(layers.style_at(10, syntax()), syntax());
+    }
+
+    #[test]
+    fn test_fg_only_layer_keeps_background() {
+        let mut layers = HighlightLayers::empty();
+        layers.add("tint", 0, LayerMerge::FG);
+        layers.set_ranges(
+            "tint",
+            vec![(0..3, Style::fg(Rgba::RED).with_bg(Rgba::BLUE))],
+        );
+
+        let base = Style::bg(Rgba::BLACK);
+        assert_eq!(
+            layers.style_at(1, base),
+            Style::fg(Rgba::RED).with_bg(Rgba::BLACK)
+        );
+    }
+
+    #[test]
+    fn test_equal_priority_applies_in_insertion_order() {
+        let mut layers = HighlightLayers::empty();
+        layers.add("first", 5, LayerMerge::BG);
+        layers.add("second", 5, LayerMerge::BG);
+        layers.set_ranges("first", vec![(0..1, Style::bg(Rgba::RED))]);
+        layers.set_ranges("second", vec![(0..1, Style::bg(Rgba::BLUE))]);
+
+        assert_eq!(layers.style_at(0, Style::NONE).bg, Some(Rgba::BLUE));
+    }
+
+    #[test]
+    fn test_unknown_layer_is_rejected() {
+        let mut layers = HighlightLayers::empty();
+        assert!(!layers.set_ranges("missing", vec![(0..1, Style::NONE)]));
+        assert!(layers.is_empty());
+    }
+
+    #[test]
+    fn test_edits_shift_layer_ranges() {
+        let mut layers = HighlightLayers::default();
+        layers.set_ranges(
+            SEARCH_LAYER,
+            vec![
+                (2..4, Style::NONE),
+                (10..12, Style::NONE),
+                (20..22, Style::NONE),
+            ],
+        );
+        layers.apply_edit(0, 0, 3);
+        layers.apply_edit(9, 7, 0);
+
+        let ranges: Vec<_> = layers
+            .get(SEARCH_LAYER)
+            .unwrap()
+            .ranges()
+            .iter()
+            .map(|(range, _)| range.clone())
+            .collect();
+        assert_eq!(ranges, [5..7, 16..18]);
+    }
+}
@@ -1,18 +1,33 @@
 //! Syntax highlighting and style management.
 
+pub mod bracket;
+pub mod customize;
+mod detect;
+pub mod diagnostic;
 pub mod highlighted_buffer;
 pub mod languages;
+pub mod layer;
+pub mod semantic;
 mod syntax;
 pub mod theme;
 pub mod token;
 pub mod tokenizer;
 
+pub use bracket::{DEFAULT_BRACKET_SCAN_LIMIT, bracket_match, bracket_match_within};
+pub use customize::{Customization, CustomizedTokenizer};
+pub use diagnostic::{Diagnostic, DiagnosticSeverity};
 pub use highlighted_buffer::HighlightedBuffer;
+pub use layer::{
+    BRACKET_MATCH_LAYER, HighlightLayer, HighlightLayers, LayerMerge, SEARCH_LAYER, SELECTION_LAYER,
+};
+pub use semantic::{SemanticModifiers, SemanticToken};
 pub use syntax::{SyntaxStyle, SyntaxStyleRegistry};
+#[cfg(feature = "themes")]
+pub use theme::{DEFAULT_WATCH_INTERVAL, ThemeReloaded};
 pub use theme::{Theme, ThemeRegistry};
-pub use token::{Token, TokenKind, TokenSpan};
+pub use token::{BufferToken, Token, TokenKind, TokenSpan};
 pub use tokenizer::{
-    CommentKind, HeredocKind, LineState, StringKind, Tokenizer, TokenizerRegistry,
+    CommentKind, HeredocDelimiter, HeredocKind, LineState, StringKind, Tokenizer, TokenizerRegistry,
 };
 
 #[cfg(test)]
@@ -0,0 +1,107 @@
+//! Semantic tokens (e.g. from a language server) overlaid on lexical tokens.
+
+use std::ops::Range;
+
+use bitflags::bitflags;
+
+use crate::highlight::diagnostic::shift_range;
+use crate::highlight::token::TokenKind;
+use crate::style::TextAttributes;
+
+bitflags! {
+    /// Modifiers of a [`SemanticToken`], after the LSP semantic token
+    /// modifiers plus `MUTABLE`.
+    ///
+    /// Only `DEPRECATED` (strikethrough) and `MUTABLE` (underline) change
+    /// how a token is drawn; the rest are kept for the application.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct SemanticModifiers: u16 {
+        const DECLARATION     = 0x0001;
+        const DEFINITION      = 0x0002;
+        const READONLY        = 0x0004;
+        const STATIC          = 0x0008;
+        const DEPRECATED      = 0x0010;
+        const ABSTRACT        = 0x0020;
+        const ASYNC           = 0x0040;
+        const MODIFICATION    = 0x0080;
+        const DOCUMENTATION   = 0x0100;
+        const DEFAULT_LIBRARY = 0x0200;
+        const MUTABLE         = 0x0400;
+    }
+}
+
+impl SemanticModifiers {
+    /// Text attributes these modifiers add to the token kind's style.
+    #[must_use]
+    pub fn attributes(self) -> TextAttributes {
+        let mut attributes = TextAttributes::empty();
+        if self.contains(Self::DEPRECATED) {
+            attributes |= TextAttributes::STRIKETHROUGH;
+        }
+        if self.contains(Self::MUTABLE) {
+            attributes |= TextAttributes::UNDERLINE;
+        }
+        attributes
+    }
+}
+
+/// A semantic token over a character range of a [`HighlightedBuffer`].
+///
+/// `range` is in char offsets from the start of the buffer, like
+/// [`Diagnostic`](super::Diagnostic) ranges.
+///
+/// [`HighlightedBuffer`]: super::HighlightedBuffer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+    pub modifiers: SemanticModifiers,
+}
+
+impl SemanticToken {
+    #[must_use]
+    pub const fn new(range: Range<usize>, kind: TokenKind, modifiers: SemanticModifiers) -> Self {
+        Self {
+            range,
+            kind,
+            modifiers,
+        }
+    }
+
+    /// Shift the range for an edit that replaced `removed` chars at `offset`
+    /// with `inserted` chars, as [`Diagnostic::apply_edit`] does. Returns
+    /// `false` if the edit deleted the whole token.
+    ///
+    /// [`Diagnostic::apply_edit`]: super::Diagnostic::apply_edit
+    pub fn apply_edit(&mut self, offset: usize, removed: usize, inserted: usize) -> bool {
+        shift_range(&mut self.range, offset, removed, inserted) && !self.range.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_attributes() {
+        assert_eq!(
+            SemanticModifiers::MUTABLE.attributes(),
+            TextAttributes::UNDERLINE
+        );
+        assert_eq!(
+            (SemanticModifiers::DEPRECATED | SemanticModifiers::STATIC).attributes(),
+            TextAttributes::STRIKETHROUGH
+        );
+        assert!(SemanticModifiers::READONLY.attributes().is_empty());
+    }
+
+    #[test]
+    fn edits_shift_or_drop_tokens() {
+        let mut token = SemanticToken::new(4..8, TokenKind::Identifier, SemanticModifiers::empty());
+        assert!(token.apply_edit(0, 0, 2));
+        assert_eq!(token.range, 6..10);
+        assert!(token.apply_edit(7, 1, 0));
+        assert_eq!(token.range, 6..9);
+        assert!(!token.apply_edit(5, 5, 0));
+    }
+}
@@ -372,3 +372,41 @@ fn test_token_kind_text_present() {
     debug!(?tokens, ?state, "text fallback");
     assert!(tokens.iter().any(|t| t.kind == TokenKind::Text));
 }
+
+#[test]
+fn test_non_ascii_tokens_on_char_boundaries() {
+    setup_test_logging();
+    let registry = crate::highlight::TokenizerRegistry::with_builtins();
+    let lines = [
+        "let 名前 = 関数(x); // 注釈",
+        "x.名前 + 'é' r名 b名 0x名 1名 名!",
+        "😀 x😀 \"😀\" '😀' `😀` # 😀",
+        "[名.名]",
+        "名-名 = \"値\"",
+        "$名 ${名} @名",
+    ];
+    for name in [
+        "rust",
+        "python",
+        "javascript",
+        "typescript",
+        "toml",
+        "json",
+        "yaml",
+        "shell",
+        "markdown",
+        "diff",
+    ] {
+        let tokenizer = registry.by_name(name).unwrap();
+        for line in lines {
+            let (tokens, state) = tokenizer.tokenize_line(line, LineState::Normal);
+            debug!(name, line, ?tokens, ?state, "non-ascii line");
+            for token in &tokens {
+                assert!(
+                    line.get(token.range()).is_some(),
+                    "{name}: {token:?} splits a char in {line:?}"
+                );
+            }
+        }
+    }
+}
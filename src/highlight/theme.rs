@@ -1,14 +1,29 @@
 use crate::color::Rgba;
+use crate::highlight::diagnostic::DiagnosticSeverity;
+use crate::highlight::semantic::SemanticModifiers;
 use crate::highlight::token::TokenKind;
 use crate::style::Style;
 use std::collections::HashMap;
 
+mod fallback;
+#[cfg(feature = "themes")]
+mod file;
+#[cfg(feature = "serde")]
+mod repr;
+#[cfg(feature = "themes")]
+mod watch;
+
+#[cfg(feature = "themes")]
+pub use watch::{DEFAULT_WATCH_INTERVAL, ThemeReloaded};
+
 /// A syntax highlighting theme that maps token kinds to styles and editor chrome colors.
 #[derive(Clone, Debug)]
 pub struct Theme {
     name: String,
     styles: [Option<Style>; TokenKind::COUNT],
     default_style: Style,
+    diagnostic_styles: [Style; DiagnosticSeverity::COUNT],
+    bracket_palette: Vec<Rgba>,
 
     background: Rgba,
     foreground: Rgba,
@@ -27,6 +42,17 @@ impl Theme {
             name: name.into(),
             styles: [None; TokenKind::COUNT],
             default_style: Style::default(),
+            diagnostic_styles: [
+                Style::underline(),
+                Style::underline(),
+                Style::underline().with_bg(Rgba::from_rgba_u8(255, 200, 0, 48)),
+                Style::underline().with_bg(Rgba::from_rgba_u8(255, 0, 0, 48)),
+            ],
+            bracket_palette: vec![
+                Rgba::from_rgb_u8(255, 215, 0),
+                Rgba::from_rgb_u8(218, 112, 214),
+                Rgba::from_rgb_u8(23, 159, 255),
+            ],
             background: Rgba::BLACK,
             foreground: Rgba::WHITE,
             selection: Rgba::from_rgb_u8(80, 80, 80),
@@ -43,11 +69,18 @@ impl Theme {
         &self.name
     }
 
-    /// Get the style for a token kind (falls back to default style).
+    /// Get the style for a token kind.
+    ///
+    /// Kinds without a style use their [`TokenKind::fallback`] kind's style,
+    /// then the default style.
     #[must_use]
     pub fn style_for(&self, kind: TokenKind) -> &Style {
         self.styles[kind.as_usize()]
             .as_ref()
+            .or_else(|| {
+                kind.fallback()
+                    .and_then(|fallback| self.styles[fallback.as_usize()].as_ref())
+            })
             .unwrap_or(&self.default_style)
     }
 
@@ -70,6 +103,69 @@ impl Theme {
         self
     }
 
+    /// Style of a semantic token: the kind's style plus the modifiers'
+    /// [attributes](SemanticModifiers::attributes).
+    #[must_use]
+    pub fn semantic_style(&self, kind: TokenKind, modifiers: SemanticModifiers) -> Style {
+        let mut style = *self.style_for(kind);
+        style.attributes |= modifiers.attributes();
+        style
+    }
+
+    /// Style layered over syntax colors for a diagnostic severity.
+    ///
+    /// Defaults to an underline, plus a translucent background tint for
+    /// warnings and errors. The syntax foreground is always kept.
+    #[must_use]
+    pub const fn diagnostic_style(&self, severity: DiagnosticSeverity) -> Style {
+        self.diagnostic_styles[severity.as_usize()]
+    }
+
+    /// Set the style for a diagnostic severity.
+    pub fn set_diagnostic_style(
+        &mut self,
+        severity: DiagnosticSeverity,
+        style: Style,
+    ) -> &mut Self {
+        self.diagnostic_styles[severity.as_usize()] = Style { fg: None, ..style };
+        self
+    }
+
+    /// Builder-style diagnostic style setter.
+    #[must_use]
+    pub fn with_diagnostic_style(mut self, severity: DiagnosticSeverity, style: Style) -> Self {
+        self.set_diagnostic_style(severity, style);
+        self
+    }
+
+    /// Foreground colors for rainbow brackets, cycled by nesting depth.
+    #[must_use]
+    pub fn bracket_palette(&self) -> &[Rgba] {
+        &self.bracket_palette
+    }
+
+    /// Set the rainbow bracket palette. An empty palette disables the colors.
+    pub fn set_bracket_palette(&mut self, palette: Vec<Rgba>) -> &mut Self {
+        self.bracket_palette = palette;
+        self
+    }
+
+    /// Builder-style rainbow bracket palette setter.
+    #[must_use]
+    pub fn with_bracket_palette(mut self, palette: Vec<Rgba>) -> Self {
+        self.set_bracket_palette(palette);
+        self
+    }
+
+    /// Rainbow bracket color for a nesting depth, if the palette is non-empty.
+    #[must_use]
+    pub fn bracket_color(&self, depth: u32) -> Option<Rgba> {
+        if self.bracket_palette.is_empty() {
+            return None;
+        }
+        Some(self.bracket_palette[depth as usize % self.bracket_palette.len()])
+    }
+
     /// Builder-style default style setter.
     #[must_use]
     pub fn with_default_style(mut self, style: Style) -> Self {
@@ -459,6 +555,8 @@ impl Default for Theme {
 pub struct ThemeRegistry {
     themes: HashMap<String, Theme>,
     current: String,
+    #[cfg(feature = "themes")]
+    watches: watch::Watches,
 }
 
 impl ThemeRegistry {
@@ -538,6 +636,23 @@ mod tests {
         assert_eq!(*themed.style_for(TokenKind::String), default_style);
     }
 
+    #[test]
+    fn diff_kinds_use_fallback_until_styled() {
+        let theme = Theme::dark();
+        assert_eq!(
+            theme.style_for(TokenKind::DiffAdd),
+            theme.style_for(TokenKind::String)
+        );
+        assert_eq!(
+            theme.style_for(TokenKind::DiffRemove),
+            theme.style_for(TokenKind::Error)
+        );
+
+        let custom = Style::fg(Rgba::GREEN);
+        let themed = theme.with_style(TokenKind::DiffAdd, custom);
+        assert_eq!(*themed.style_for(TokenKind::DiffAdd), custom);
+    }
+
     #[test]
     fn builtins_define_core_styles() {
         let theme = Theme::dark();
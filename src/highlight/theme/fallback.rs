@@ -0,0 +1,220 @@
+//! Precomputed 256-color and 16-color fallbacks for truecolor themes.
+
+use std::collections::HashMap;
+
+use crate::ansi::{ColorFallbacks, PaletteFallback};
+use crate::color::Rgba;
+use crate::highlight::token::TokenKind;
+
+use super::Theme;
+
+/// How much perceptually further than its nearest entry a token color may
+/// be nudged to stay distinct (Oklab distance; black to white is 1.0).
+const MAX_NUDGE: f32 = 0.25;
+
+/// Palette entries ranked by perceptual distance from a color.
+fn ranked(color: Rgba, palette: &[(u8, Rgba)]) -> Vec<(u8, f32)> {
+    let mut ranked: Vec<_> = palette
+        .iter()
+        .map(|&(index, entry)| (index, color.perceptual_distance(entry)))
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked
+}
+
+/// Pick an index per color: the nearest entry, or for `distinct` colors the
+/// nearest entry within [`MAX_NUDGE`] not already used by another distinct
+/// color or by `reserved`.
+///
+/// Distinct colors are placed in order of how well they fit, so the closest
+/// match keeps its entry and the others move.
+fn assign(
+    distinct: &[Rgba],
+    others: &[Rgba],
+    reserved: &[u8],
+    palette: &[(u8, Rgba)],
+) -> HashMap<(u8, u8, u8), u8> {
+    let mut assigned = HashMap::new();
+    for color in others {
+        assigned.insert(color.to_rgb_u8(), ranked(*color, palette)[0].0);
+    }
+
+    let mut candidates: Vec<_> = distinct
+        .iter()
+        .map(|color| (color.to_rgb_u8(), ranked(*color, palette)))
+        .collect();
+    candidates.sort_by(|a, b| a.1[0].1.total_cmp(&b.1[0].1));
+
+    let mut taken = reserved.to_vec();
+    for (key, ranked) in candidates {
+        let (nearest, nearest_distance) = ranked[0];
+        let index = ranked
+            .iter()
+            .take_while(|(_, distance)| *distance <= nearest_distance + MAX_NUDGE)
+            .find(|(index, _)| !taken.contains(index))
+            .map_or(nearest, |&(index, _)| index);
+        taken.push(index);
+        assigned.insert(key, index);
+    }
+    assigned
+}
+
+impl Theme {
+    /// Precompute 256-color and 16-color fallbacks for the theme's colors.
+    ///
+    /// Each color maps to its perceptually nearest palette entry. Token
+    /// foreground colors that are distinct in truecolor are nudged to
+    /// distinct entries when a close enough alternative exists, and away
+    /// from the background's entry, so token kinds stay distinguishable on
+    /// limited terminals.
+    #[must_use]
+    pub fn derive_fallbacks(&self) -> ColorFallbacks {
+        let mut distinct: Vec<Rgba> = Vec::new();
+        let mut others: Vec<Rgba> = Vec::new();
+        let push = |list: &mut Vec<Rgba>, color: Rgba| {
+            if !list
+                .iter()
+                .any(|seen| seen.to_rgb_u8() == color.to_rgb_u8())
+            {
+                list.push(color);
+            }
+        };
+
+        let default_fg = self.default_style.fg.unwrap_or(self.foreground);
+        push(&mut distinct, default_fg);
+        for kind in TokenKind::ALL {
+            let style = self.style_for(kind);
+            if let Some(fg) = style.fg {
+                push(&mut distinct, fg);
+            }
+            if let Some(bg) = style.bg {
+                push(&mut others, bg);
+            }
+        }
+        for color in [
+            self.background,
+            self.foreground,
+            self.selection,
+            self.cursor,
+            self.line_number,
+            self.line_number_active,
+            self.gutter,
+        ]
+        .into_iter()
+        .chain(self.default_style.bg)
+        .chain(self.diagnostic_styles.iter().filter_map(|style| style.bg))
+        .chain(self.bracket_palette.iter().copied())
+        {
+            push(&mut others, color);
+        }
+        others.retain(|color| {
+            !distinct
+                .iter()
+                .any(|seen| seen.to_rgb_u8() == color.to_rgb_u8())
+        });
+
+        let palette256: Vec<_> = (16..=255u8)
+            .map(|index| (index, Rgba::from_256_color(index)))
+            .collect();
+        let palette16: Vec<_> = (0..16u8)
+            .map(|index| (index, Rgba::from_16_color(index)))
+            .collect();
+
+        let background = self.default_style.bg.unwrap_or(self.background);
+        let reserved256 = [ranked(background, &palette256)[0].0];
+        let reserved16 = [ranked(background, &palette16)[0].0];
+        let map256 = assign(&distinct, &others, &reserved256, &palette256);
+        let map16 = assign(&distinct, &others, &reserved16, &palette16);
+
+        map256
+            .into_iter()
+            .map(|(key @ (r, g, b), color256)| {
+                let color16 = map16[&key];
+                (
+                    Rgba::from_rgb_u8(r, g, b),
+                    PaletteFallback { color256, color16 },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::ColorMode;
+    use crate::style::Style;
+
+    #[test]
+    fn test_default_theme_kinds_stay_distinct_in_16_colors() {
+        let theme = Theme::default();
+        let fallbacks = theme.derive_fallbacks();
+        let index = |kind| {
+            let fg = theme.style_for(kind).fg.expect("themed kind");
+            fallbacks.index_for(fg, ColorMode::Color16).unwrap()
+        };
+
+        let keyword = index(TokenKind::Keyword);
+        let string = index(TokenKind::String);
+        let comment = index(TokenKind::Comment);
+        assert_ne!(keyword, string);
+        assert_ne!(keyword, comment);
+        assert_ne!(string, comment);
+
+        let background = fallbacks
+            .index_for(theme.background(), ColorMode::Color16)
+            .unwrap();
+        assert!(![keyword, string, comment].contains(&background));
+    }
+
+    #[test]
+    fn test_colliding_colors_are_nudged_apart() {
+        // Two reds that both quantize to bright red.
+        let red = Rgba::from_rgb_u8(250, 10, 10);
+        let other_red = Rgba::from_rgb_u8(240, 30, 20);
+        assert_eq!(red.to_16_color(), other_red.to_16_color());
+
+        let theme = Theme::new("reds")
+            .with_style(TokenKind::Keyword, Style::fg(red))
+            .with_style(TokenKind::String, Style::fg(other_red));
+        let fallbacks = theme.derive_fallbacks();
+        let keyword = fallbacks.get(red).unwrap();
+        let string = fallbacks.get(other_red).unwrap();
+        assert_ne!(keyword.color16, string.color16);
+        assert_ne!(keyword.color256, string.color256);
+        // The closer color keeps the exact match.
+        assert_eq!(keyword.color16, 9);
+    }
+
+    #[test]
+    fn test_unrelated_colors_are_not_nudged_far() {
+        // Nothing within reach of pure green except green itself; a second
+        // identical-looking green keeps the nearest entry rather than
+        // becoming some unrelated hue.
+        let green = Rgba::from_rgb_u8(0, 255, 0);
+        let near_green = Rgba::from_rgb_u8(0, 254, 1);
+        let theme = Theme::new("greens")
+            .with_style(TokenKind::Keyword, Style::fg(green))
+            .with_style(TokenKind::String, Style::fg(near_green));
+        let fallbacks = theme.derive_fallbacks();
+        let nudged = Rgba::from_16_color(fallbacks.get(near_green).unwrap().color16);
+        assert!(near_green.perceptual_distance(nudged) <= MAX_NUDGE + 0.01);
+    }
+
+    #[test]
+    fn test_unknown_colors_and_truecolor_are_not_covered() {
+        let fallbacks = Theme::default().derive_fallbacks();
+        assert!(!fallbacks.is_empty());
+        let foreground = Theme::default().foreground();
+        assert!(
+            fallbacks
+                .index_for(foreground, ColorMode::TrueColor)
+                .is_none()
+        );
+        assert!(
+            fallbacks
+                .index_for(Rgba::from_rgb_u8(1, 2, 3), ColorMode::Color256)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,777 @@
+//! Loading and saving themes as TOML or JSON (`themes` feature).
+//!
+//! # Schema
+//!
+//! ```toml
+//! name = "Ocean"                 # required
+//!
+//! [palette]                      # optional named colors
+//! blue = "#1e90ff"
+//! sand = "#f4e4c1"
+//!
+//! [colors]                       # optional editor chrome
+//! background = "#0b1021"
+//! foreground = "sand"            # palette names or any `Rgba::parse` color
+//!
+//! [styles.keyword]               # keys are `TokenKind::name()` values
+//! fg = "blue"
+//! bold = true
+//!
+//! [styles.keyword_control]
+//! inherit = "keyword"            # start from another kind's entry
+//! italic = true
+//!
+//! [styles]
+//! comment = "italic sand on #0b1021"   # or a `Style::parse` spec
+//! ```
+//!
+//! Style entries accept `fg`, `bg`, `bold`, `italic`, `underline`, `dim`,
+//! `blink`, `inverse`, `hidden`, `strikethrough`, `overline`, `superscript`,
+//! `subscript`, `underline_style` (`single`, `double`, `curly`, `dotted`, or
+//! `dashed`), `underline_color`, and `inherit`, or a spec string as read by
+//! [`Style::parse`] whose colors may name palette entries.
+//! JSON files use the same structure. Unknown token kinds are reported
+//! through [`emit_log_record`] and skipped.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Theme, ThemeRegistry};
+use crate::color::Rgba;
+use crate::error::{Error, Result};
+use crate::event::{LogLevel, LogRecord, emit_log_record};
+use crate::highlight::token::TokenKind;
+use crate::style::spec::{self, Spec};
+use crate::style::{Style, StylePatch, TextAttributes, UnderlineStyle};
+
+/// Names of [`UnderlineStyle`]s in theme files.
+const UNDERLINE_STYLES: [(&str, UnderlineStyle); 5] = [
+    ("single", UnderlineStyle::Single),
+    ("double", UnderlineStyle::Double),
+    ("curly", UnderlineStyle::Curly),
+    ("dotted", UnderlineStyle::Dotted),
+    ("dashed", UnderlineStyle::Dashed),
+];
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ThemeFile {
+    name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    palette: BTreeMap<String, String>,
+    #[serde(default)]
+    colors: ChromeColors,
+    #[serde(default)]
+    styles: BTreeMap<String, StyleValue>,
+}
+
+/// A style written as a table or as a spec string.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum StyleValue {
+    Spec(String),
+    Table(StyleEntry),
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ChromeColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    foreground: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_number_active: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gutter: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct StyleEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inherit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dim: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blink: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inverse: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hidden: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    superscript: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subscript: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underline_style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underline_color: Option<String>,
+}
+
+impl StyleEntry {
+    /// Fill unset fields from `base`.
+    fn inherit_from(self, base: &Self) -> Self {
+        Self {
+            inherit: None,
+            fg: self.fg.or_else(|| base.fg.clone()),
+            bg: self.bg.or_else(|| base.bg.clone()),
+            bold: self.bold.or(base.bold),
+            italic: self.italic.or(base.italic),
+            underline: self.underline.or(base.underline),
+            dim: self.dim.or(base.dim),
+            blink: self.blink.or(base.blink),
+            inverse: self.inverse.or(base.inverse),
+            hidden: self.hidden.or(base.hidden),
+            strikethrough: self.strikethrough.or(base.strikethrough),
+            overline: self.overline.or(base.overline),
+            superscript: self.superscript.or(base.superscript),
+            subscript: self.subscript.or(base.subscript),
+            underline_style: self
+                .underline_style
+                .or_else(|| base.underline_style.clone()),
+            underline_color: self
+                .underline_color
+                .or_else(|| base.underline_color.clone()),
+        }
+    }
+
+    /// The entry a spec string stands for, with colors resolved later.
+    fn from_spec(spec: &Spec<'_>) -> Self {
+        let flag = |attr: TextAttributes| spec.attributes.contains(attr).then_some(true);
+        Self {
+            inherit: None,
+            fg: spec.fg.map(str::to_string),
+            bg: spec.bg.map(str::to_string),
+            bold: flag(TextAttributes::BOLD),
+            italic: flag(TextAttributes::ITALIC),
+            underline: flag(TextAttributes::UNDERLINE),
+            dim: flag(TextAttributes::DIM),
+            blink: flag(TextAttributes::BLINK),
+            inverse: flag(TextAttributes::INVERSE),
+            hidden: flag(TextAttributes::HIDDEN),
+            strikethrough: flag(TextAttributes::STRIKETHROUGH),
+            overline: flag(TextAttributes::OVERLINE),
+            superscript: flag(TextAttributes::SUPERSCRIPT),
+            subscript: flag(TextAttributes::SUBSCRIPT),
+            underline_style: None,
+            underline_color: None,
+        }
+    }
+
+    /// The entry as a patch over an empty style.
+    fn patch(&self, resolver: &Resolver<'_>) -> Result<StylePatch> {
+        Ok(StylePatch {
+            fg: resolver.optional(self.fg.as_ref())?,
+            bg: resolver.optional(self.bg.as_ref())?,
+            bold: self.bold,
+            italic: self.italic,
+            underline: self.underline,
+            dim: self.dim,
+            blink: self.blink,
+            inverse: self.inverse,
+            hidden: self.hidden,
+            strikethrough: self.strikethrough,
+            overline: self.overline,
+            superscript: self.superscript,
+            subscript: self.subscript,
+            underline_style: self
+                .underline_style
+                .as_deref()
+                .map(|name| resolver.underline_style(name))
+                .transpose()?,
+            underline_color: resolver.optional(self.underline_color.as_ref())?,
+            ..StylePatch::default()
+        })
+    }
+
+    fn from_style(style: &Style) -> Self {
+        let flag = |attr: TextAttributes| style.attributes.contains(attr).then_some(true);
+        Self {
+            inherit: None,
+            fg: style.fg.map(Rgba::to_color_string),
+            bg: style.bg.map(Rgba::to_color_string),
+            bold: flag(TextAttributes::BOLD),
+            italic: flag(TextAttributes::ITALIC),
+            underline: flag(TextAttributes::UNDERLINE),
+            dim: flag(TextAttributes::DIM),
+            blink: flag(TextAttributes::BLINK),
+            inverse: flag(TextAttributes::INVERSE),
+            hidden: flag(TextAttributes::HIDDEN),
+            strikethrough: flag(TextAttributes::STRIKETHROUGH),
+            overline: flag(TextAttributes::OVERLINE),
+            superscript: flag(TextAttributes::SUPERSCRIPT),
+            subscript: flag(TextAttributes::SUBSCRIPT),
+            underline_style: UNDERLINE_STYLES
+                .iter()
+                .find(|&&(_, shape)| shape == style.underline_style)
+                .filter(|&&(_, shape)| shape != UnderlineStyle::Single)
+                .map(|(name, _)| (*name).to_string()),
+            underline_color: style.underline_color.map(Rgba::to_color_string),
+        }
+    }
+}
+
+/// 1-based line number of a byte offset.
+fn line_of_offset(source: &str, offset: usize) -> usize {
+    source.get(..offset).unwrap_or(source).matches('\n').count() + 1
+}
+
+/// 1-based line of the first quoted occurrence of `value`, if any.
+fn line_of_value(source: &str, value: &str) -> Option<usize> {
+    let quoted = format!("\"{value}\"");
+    source
+        .lines()
+        .position(|line| line.contains(&quoted) || line.contains(&format!("'{value}'")))
+        .map(|idx| idx + 1)
+}
+
+/// Resolves theme-file color references against the palette.
+struct Resolver<'a> {
+    source: &'a str,
+    palette: BTreeMap<String, Rgba>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(source: &'a str, palette: &BTreeMap<String, String>) -> Result<Self> {
+        let mut resolver = Self {
+            source,
+            palette: BTreeMap::new(),
+        };
+        for (name, value) in palette {
+            let color = resolver.literal(value)?;
+            resolver.palette.insert(name.clone(), color);
+        }
+        Ok(resolver)
+    }
+
+    fn error(&self, value: &str, message: String) -> Error {
+        Error::ThemeParse {
+            line: line_of_value(self.source, value),
+            message,
+        }
+    }
+
+    /// A color written out in full: hex, `rgb()`, or a CSS name.
+    fn literal(&self, value: &str) -> Result<Rgba> {
+        Rgba::parse(value).map_err(|_| {
+            let kind = if value.trim_start().starts_with('#') {
+                "hex color"
+            } else {
+                "color"
+            };
+            self.error(value, format!("invalid {kind} `{value}`"))
+        })
+    }
+
+    /// A palette name, which shadows CSS names and the default color
+    /// keywords, or a literal color.
+    fn color(&self, value: &str) -> Result<Rgba> {
+        if let Some(&color) = self.palette.get(value) {
+            return Ok(color);
+        }
+        if let Some(color) = Rgba::from_default_keyword(value.trim()) {
+            return Ok(color);
+        }
+        if value.trim_start().starts_with('#') || value.contains('(') {
+            return self.literal(value);
+        }
+        Rgba::from_name(value)
+            .ok_or_else(|| self.error(value, format!("unknown palette color `{value}`")))
+    }
+
+    fn optional(&self, value: Option<&String>) -> Result<Option<Rgba>> {
+        value.map(|value| self.color(value)).transpose()
+    }
+
+    /// An underline shape by name.
+    fn underline_style(&self, name: &str) -> Result<UnderlineStyle> {
+        UNDERLINE_STYLES
+            .iter()
+            .find(|(known, _)| name.eq_ignore_ascii_case(known))
+            .map(|&(_, shape)| shape)
+            .ok_or_else(|| self.error(name, format!("unknown underline style `{name}`")))
+    }
+}
+
+impl ThemeFile {
+    fn into_theme(self, source: &str) -> Result<Theme> {
+        let resolver = Resolver::new(source, &self.palette)?;
+        let mut theme = Theme::new(self.name.clone());
+
+        let chrome = &self.colors;
+        if let Some(color) = resolver.optional(chrome.background.as_ref())? {
+            theme = theme.with_background(color);
+        }
+        if let Some(color) = resolver.optional(chrome.foreground.as_ref())? {
+            theme = theme.with_foreground(color);
+        }
+        if let Some(color) = resolver.optional(chrome.selection.as_ref())? {
+            theme = theme.with_selection(color);
+        }
+        if let Some(color) = resolver.optional(chrome.cursor.as_ref())? {
+            theme = theme.with_cursor(color);
+        }
+        if let Some(color) = resolver.optional(chrome.line_number.as_ref())? {
+            theme = theme.with_line_number(color);
+        }
+        if let Some(color) = resolver.optional(chrome.line_number_active.as_ref())? {
+            theme = theme.with_line_number_active(color);
+        }
+        if let Some(color) = resolver.optional(chrome.gutter.as_ref())? {
+            theme = theme.with_gutter(color);
+        }
+
+        for (key, entry) in &self.styles {
+            let Some(kind) = TokenKind::from_name(key) else {
+                emit_log_record(
+                    &LogRecord::new(
+                        LogLevel::Warn,
+                        &format!("theme `{}`: skipping unknown token kind `{key}`", self.name),
+                    )
+                    .with_target(module_path!())
+                    .with_fields(&[("theme", &self.name), ("kind", key)]),
+                );
+                continue;
+            };
+            let entry = self.resolve_inherit(key, entry, &resolver)?;
+            theme.set_style(kind, Style::NONE.apply(&entry.patch(&resolver)?));
+        }
+
+        Ok(theme)
+    }
+
+    /// The table form of a style, splitting spec strings.
+    fn entry(key: &str, value: &StyleValue, resolver: &Resolver<'_>) -> Result<StyleEntry> {
+        match value {
+            StyleValue::Table(entry) => Ok(entry.clone()),
+            StyleValue::Spec(spec) => spec::split(spec, |token| resolver.color(token).is_ok())
+                .map(|parsed| StyleEntry::from_spec(&parsed))
+                .map_err(|err| resolver.error(spec, format!("style `{key}`: {err}"))),
+        }
+    }
+
+    /// Flatten an entry's `inherit` chain into a single entry.
+    fn resolve_inherit(
+        &self,
+        key: &str,
+        entry: &StyleValue,
+        resolver: &Resolver<'_>,
+    ) -> Result<StyleEntry> {
+        let mut flattened = Self::entry(key, entry, resolver)?;
+        let mut chain = HashSet::from([key]);
+        while let Some(parent) = flattened.inherit.take() {
+            let Some((parent_key, base)) = self.styles.get_key_value(&parent) else {
+                return Err(resolver.error(
+                    &parent,
+                    format!("style `{key}` inherits from undefined style `{parent}`"),
+                ));
+            };
+            if !chain.insert(parent_key.as_str()) {
+                return Err(resolver.error(
+                    &parent,
+                    format!("style `{key}` has an inherit cycle through `{parent}`"),
+                ));
+            }
+            let base = Self::entry(parent_key, base, resolver)?;
+            flattened = StyleEntry {
+                inherit: base.inherit.clone(),
+                ..flattened.inherit_from(&base)
+            };
+        }
+        Ok(flattened)
+    }
+}
+
+impl Theme {
+    /// Parse a theme from TOML. See the [module schema](self) for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ThemeParse`] for malformed TOML, missing required
+    /// keys, invalid colors or style specs, unknown palette names, or
+    /// inherit cycles.
+    pub fn from_toml(source: &str) -> Result<Self> {
+        let file: ThemeFile = toml::from_str(source).map_err(|err| Error::ThemeParse {
+            line: err.span().map(|span| line_of_offset(source, span.start)),
+            message: err.message().to_string(),
+        })?;
+        file.into_theme(source)
+    }
+
+    /// Parse a theme from JSON using the same schema as [`Theme::from_toml`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ThemeParse`] under the same conditions as
+    /// [`Theme::from_toml`].
+    pub fn from_json(source: &str) -> Result<Self> {
+        let file: ThemeFile = serde_json::from_str(source).map_err(|err| Error::ThemeParse {
+            line: (err.line() > 0).then(|| err.line()),
+            message: err.to_string(),
+        })?;
+        file.into_theme(source)
+    }
+
+    /// Serialize the theme to TOML that [`Theme::from_toml`] reads back.
+    ///
+    /// Colors are written as hex strings, or `default` / `default-bg` for
+    /// the default color sentinels; kinds without an explicit style are
+    /// omitted.
+    #[must_use]
+    pub fn to_toml(&self) -> String {
+        let file = ThemeFile {
+            name: self.name.clone(),
+            palette: BTreeMap::new(),
+            colors: ChromeColors {
+                background: Some(self.background.to_color_string()),
+                foreground: Some(self.foreground.to_color_string()),
+                selection: Some(self.selection.to_color_string()),
+                cursor: Some(self.cursor.to_color_string()),
+                line_number: Some(self.line_number.to_color_string()),
+                line_number_active: Some(self.line_number_active.to_color_string()),
+                gutter: Some(self.gutter.to_color_string()),
+            },
+            styles: TokenKind::ALL
+                .into_iter()
+                .filter_map(|kind| {
+                    let style = self.styles[kind.as_usize()].as_ref()?;
+                    Some((
+                        kind.name().to_string(),
+                        StyleValue::Table(StyleEntry::from_style(style)),
+                    ))
+                })
+                .collect(),
+        };
+        toml::to_string(&file).expect("theme file schema serializes to TOML")
+    }
+}
+
+impl Theme {
+    /// Read a theme file, as JSON for a `.json` extension and TOML otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file cannot be read, and
+    /// [`Error::ThemeParse`] prefixed with the file name if it is malformed.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json,
+            _ => Self::from_toml,
+        };
+        let source = fs::read_to_string(path)?;
+        parse(&source).map_err(|err| match err {
+            Error::ThemeParse { line, message } => Error::ThemeParse {
+                line,
+                message: format!("{}: {message}", path.display()),
+            },
+            other => other,
+        })
+    }
+}
+
+impl ThemeRegistry {
+    /// Load every `.toml` and `.json` theme in `dir` and register it by name.
+    ///
+    /// Files are loaded in file-name order, so a later file with the same
+    /// theme name replaces an earlier one. Returns the number of themes
+    /// loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the directory or a file cannot be read, and
+    /// [`Error::ThemeParse`] (prefixed with the file name) for the first
+    /// malformed theme.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<_>>()?;
+        paths.sort();
+
+        let mut loaded = 0;
+        for path in paths {
+            if !matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("toml" | "json")
+            ) {
+                continue;
+            }
+            let theme = Theme::from_file(&path)?;
+            self.register(theme);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r##"
+name = "Ocean"
+
+[palette]
+blue = "#1e90ff"
+sand = "#f4e4c1"
+
+[colors]
+background = "#0b1021"
+foreground = "sand"
+
+[styles.keyword]
+fg = "blue"
+bold = true
+
+[styles.keyword_control]
+inherit = "keyword"
+italic = true
+
+[styles.comment]
+fg = "#808080"
+bold = false
+"##;
+
+    #[test]
+    fn parses_palette_chrome_and_styles() {
+        let theme = Theme::from_toml(SAMPLE).unwrap();
+        let blue = Rgba::from_hex("#1e90ff").unwrap();
+        assert_eq!(theme.name(), "Ocean");
+        assert_eq!(theme.foreground(), Rgba::from_hex("#f4e4c1").unwrap());
+        assert_eq!(
+            *theme.style_for(TokenKind::Keyword),
+            Style::fg(blue).with_bold()
+        );
+        assert_eq!(
+            *theme.style_for(TokenKind::KeywordControl),
+            Style::fg(blue).with_bold().with_italic()
+        );
+        assert_eq!(
+            *theme.style_for(TokenKind::Comment),
+            Style::fg(Rgba::from_hex("#808080").unwrap())
+        );
+    }
+
+    #[test]
+    fn json_uses_same_schema() {
+        let json = r##"{
+            "name": "Json",
+            "palette": { "red": "#ff0000" },
+            "styles": { "string": { "fg": "red", "underline": true } }
+        }"##;
+        let theme = Theme::from_json(json).unwrap();
+        assert_eq!(
+            *theme.style_for(TokenKind::String),
+            Style::fg(Rgba::RED).with_underline()
+        );
+    }
+
+    #[test]
+    fn accepts_names_functional_and_short_hex() {
+        let source = r##"
+name = "Named"
+
+[palette]
+accent = "rgb(30, 144, 255)"
+blue = "#ff0000"
+
+[colors]
+background = "midnightblue"
+foreground = "#eee8"
+
+[styles.keyword]
+fg = "accent"
+bg = "blue"
+
+[styles.string]
+fg = "rgba(255, 0, 0, 50%)"
+"##;
+        let theme = Theme::from_toml(source).unwrap();
+        assert_eq!(theme.background(), Rgba::from_hex("#191970").unwrap());
+        assert_eq!(theme.foreground(), Rgba::from_hex("#eeeeee88").unwrap());
+        // Palette entries shadow CSS names
+        assert_eq!(
+            *theme.style_for(TokenKind::Keyword),
+            Style::fg(Rgba::from_hex("#1e90ff").unwrap()).with_bg(Rgba::RED)
+        );
+        assert_eq!(
+            *theme.style_for(TokenKind::String),
+            Style::fg(Rgba::RED.with_alpha(0.5))
+        );
+    }
+
+    #[test]
+    fn unknown_token_kind_is_skipped() {
+        let mut theme = None;
+        let logs = crate::event::capture_logs(|| {
+            theme =
+                Some(Theme::from_toml("name = \"T\"\n[styles.not_a_kind]\nbold = true\n").unwrap());
+        });
+        assert_eq!(theme.unwrap().name(), "T");
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].level, LogLevel::Warn);
+        assert_eq!(logs[0].target, "opentui_rust::highlight::theme::file");
+        assert!(
+            logs[0]
+                .fields
+                .contains(&("kind".to_string(), "not_a_kind".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_hex_reports_line() {
+        let err =
+            Theme::from_toml("name = \"T\"\n[styles.keyword]\nfg = \"#12345\"\n").unwrap_err();
+        assert!(
+            matches!(err, Error::ThemeParse { line: Some(3), ref message } if message.contains("#12345")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn accepts_spec_strings() {
+        let source = r##"
+name = "Specs"
+
+[palette]
+sand = "#f4e4c1"
+
+[styles]
+comment = "italic sand on #0b1021"
+string = "inverse hidden"
+
+[styles.keyword]
+inherit = "comment"
+bold = true
+"##;
+        let theme = Theme::from_toml(source).unwrap();
+        let comment = Style::fg(Rgba::from_hex("#f4e4c1").unwrap())
+            .with_bg(Rgba::from_hex("#0b1021").unwrap())
+            .with_italic();
+        assert_eq!(*theme.style_for(TokenKind::Comment), comment);
+        assert_eq!(*theme.style_for(TokenKind::Keyword), comment.with_bold());
+        assert_eq!(
+            *theme.style_for(TokenKind::String),
+            Style::builder().inverse().hidden().build()
+        );
+
+        // Blink, inverse, and hidden survive a TOML round trip.
+        let reloaded = Theme::from_toml(&theme.to_toml()).unwrap();
+        assert_eq!(
+            reloaded.style_for(TokenKind::String),
+            theme.style_for(TokenKind::String)
+        );
+    }
+
+    #[test]
+    fn invalid_spec_reports_line_and_token() {
+        let err =
+            Theme::from_toml("name = \"T\"\n\n[styles]\nkeyword = \"bold bolt\"\n").unwrap_err();
+        assert!(
+            matches!(err, Error::ThemeParse { line: Some(4), ref message } if message.contains("`bolt`")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_name_is_an_error() {
+        let err = Theme::from_toml("[styles.keyword]\nbold = true\n").unwrap_err();
+        assert!(err.to_string().contains("missing field `name`"), "{err}");
+
+        let err = Theme::from_json("{\n  \"styles\": {}\n}").unwrap_err();
+        assert!(
+            matches!(err, Error::ThemeParse { line: Some(3), .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn inherit_cycle_is_an_error() {
+        let source = "name = \"T\"\n[styles.keyword]\ninherit = \"type\"\n[styles.type]\ninherit = \"keyword\"\n";
+        let err = Theme::from_toml(source).unwrap_err();
+        assert!(err.to_string().contains("inherit cycle"), "{err}");
+    }
+
+    #[test]
+    fn underline_shape_color_and_extended_attributes_round_trip() {
+        let diagnostic = Style::fg(Rgba::WHITE)
+            .with_underline()
+            .with_underline_style(UnderlineStyle::Curly)
+            .with_underline_color(Rgba::RED);
+        let theme = Theme::new("Marks")
+            .with_style(TokenKind::Error, diagnostic)
+            .with_style(
+                TokenKind::Comment,
+                Style::builder().overline().superscript().build(),
+            )
+            .with_style(TokenKind::Number, Style::builder().subscript().build());
+
+        let toml = theme.to_toml();
+        assert!(toml.contains("underline_style = \"curly\""), "{toml}");
+        let reloaded = Theme::from_toml(&toml).unwrap();
+        for kind in [TokenKind::Error, TokenKind::Comment, TokenKind::Number] {
+            assert_eq!(reloaded.style_for(kind), theme.style_for(kind), "{kind:?}");
+        }
+
+        let source = "name = \"T\"\n[styles.error]\nunderline = true\nunderline_style = \"wavy\"\n";
+        let err = Theme::from_toml(source).unwrap_err();
+        assert!(
+            matches!(err, Error::ThemeParse { line: Some(4), ref message } if message.contains("`wavy`")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn to_toml_round_trips_builtins() {
+        for theme in [Theme::dark(), Theme::light(), Theme::monochrome()] {
+            let reparsed = Theme::from_toml(&theme.to_toml()).unwrap();
+            assert_eq!(reparsed.name(), theme.name());
+            assert_eq!(reparsed.background(), theme.background());
+            assert_eq!(reparsed.gutter(), theme.gutter());
+            for kind in TokenKind::ALL {
+                assert_eq!(reparsed.style_for(kind), theme.style_for(kind), "{kind:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn to_toml_round_trips_default_colors() {
+        let mut theme = Theme::dark().with_style(
+            TokenKind::Keyword,
+            Style::default_colors().with_underline_color(Rgba::DEFAULT_FG),
+        );
+        theme.background = Rgba::DEFAULT_BG;
+        theme.foreground = Rgba::DEFAULT_FG;
+        let toml = theme.to_toml();
+        assert!(toml.contains("background = \"default-bg\""), "{toml}");
+
+        let reparsed = Theme::from_toml(&toml).unwrap();
+        assert!(reparsed.background().bits_eq(Rgba::DEFAULT_BG));
+        assert!(reparsed.foreground().bits_eq(Rgba::DEFAULT_FG));
+        let style = reparsed.style_for(TokenKind::Keyword);
+        assert!(style.fg.unwrap().bits_eq(Rgba::DEFAULT_FG));
+        assert!(style.bg.unwrap().bits_eq(Rgba::DEFAULT_BG));
+        assert!(style.underline_color.unwrap().bits_eq(Rgba::DEFAULT_FG));
+    }
+}
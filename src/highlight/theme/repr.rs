@@ -0,0 +1,176 @@
+//! Serde support for [`Theme`] (`serde` feature).
+//!
+//! Unlike the hand-written TOML/JSON theme files of the `themes` feature,
+//! this form captures every field of a theme, including diagnostic styles
+//! and the bracket palette, so a theme survives a round trip unchanged.
+//! Token styles are keyed by [`TokenKind::name`] and diagnostic styles by
+//! lowercase severity names. Missing fields take [`Theme::new`] defaults.
+
+use std::collections::BTreeMap;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Theme;
+use crate::color::Rgba;
+use crate::highlight::diagnostic::DiagnosticSeverity;
+use crate::highlight::token::TokenKind;
+use crate::style::Style;
+
+#[derive(Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeRepr {
+    name: String,
+    default_style: Style,
+    styles: BTreeMap<String, Style>,
+    diagnostics: BTreeMap<String, Style>,
+    bracket_palette: Vec<Rgba>,
+    background: Rgba,
+    foreground: Rgba,
+    selection: Rgba,
+    cursor: Rgba,
+    line_number: Rgba,
+    line_number_active: Rgba,
+    gutter: Rgba,
+}
+
+impl Default for ThemeRepr {
+    fn default() -> Self {
+        Self::from(&Theme::new(String::new()))
+    }
+}
+
+const fn severity_name(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::Hint => "hint",
+        DiagnosticSeverity::Info => "info",
+        DiagnosticSeverity::Warning => "warning",
+        DiagnosticSeverity::Error => "error",
+    }
+}
+
+impl From<&Theme> for ThemeRepr {
+    fn from(theme: &Theme) -> Self {
+        Self {
+            name: theme.name.clone(),
+            default_style: theme.default_style,
+            styles: TokenKind::ALL
+                .iter()
+                .filter_map(|&kind| {
+                    theme.styles[kind.as_usize()].map(|style| (kind.name().to_string(), style))
+                })
+                .collect(),
+            diagnostics: DiagnosticSeverity::ALL
+                .iter()
+                .map(|&severity| {
+                    (
+                        severity_name(severity).to_string(),
+                        theme.diagnostic_style(severity),
+                    )
+                })
+                .collect(),
+            bracket_palette: theme.bracket_palette.clone(),
+            background: theme.background,
+            foreground: theme.foreground,
+            selection: theme.selection,
+            cursor: theme.cursor,
+            line_number: theme.line_number,
+            line_number_active: theme.line_number_active,
+            gutter: theme.gutter,
+        }
+    }
+}
+
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ThemeRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ThemeRepr::deserialize(deserializer)?;
+        // Fields are set directly: the chrome setters also touch the
+        // default style, which has its own serialized value.
+        let mut theme = Self::new(repr.name);
+        theme.default_style = repr.default_style;
+        theme.bracket_palette = repr.bracket_palette;
+        theme.background = repr.background;
+        theme.foreground = repr.foreground;
+        theme.selection = repr.selection;
+        theme.cursor = repr.cursor;
+        theme.line_number = repr.line_number;
+        theme.line_number_active = repr.line_number_active;
+        theme.gutter = repr.gutter;
+        for (name, style) in repr.styles {
+            let kind = TokenKind::from_name(&name)
+                .ok_or_else(|| D::Error::custom(format!("unknown token kind `{name}`")))?;
+            theme.set_style(kind, style);
+        }
+        for (name, style) in repr.diagnostics {
+            let severity = DiagnosticSeverity::ALL
+                .into_iter()
+                .find(|&severity| severity_name(severity) == name)
+                .ok_or_else(|| D::Error::custom(format!("unknown diagnostic severity `{name}`")))?;
+            theme.set_diagnostic_style(severity, style);
+        }
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(a: &Theme, b: &Theme) {
+        assert_eq!(
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap()
+        );
+        assert_eq!(a.name(), b.name());
+        for kind in TokenKind::ALL {
+            assert_eq!(a.style_for(kind), b.style_for(kind), "{kind:?}");
+        }
+        for severity in DiagnosticSeverity::ALL {
+            assert_eq!(a.diagnostic_style(severity), b.diagnostic_style(severity));
+        }
+        assert_eq!(a.bracket_palette(), b.bracket_palette());
+        assert_eq!(a.background(), b.background());
+        assert_eq!(a.gutter(), b.gutter());
+    }
+
+    #[test]
+    fn builtin_themes_round_trip() {
+        for theme in [Theme::dark(), Theme::light()] {
+            let json = serde_json::to_string(&theme).unwrap();
+            let back: Theme = serde_json::from_str(&json).unwrap();
+            assert_same(&theme, &back);
+        }
+    }
+
+    #[test]
+    fn customized_theme_round_trips() {
+        let theme = Theme::new("custom")
+            .with_style(TokenKind::Keyword, Style::fg(Rgba::RED).with_link(2))
+            .with_diagnostic_style(DiagnosticSeverity::Error, Style::bold())
+            .with_bracket_palette(vec![Rgba::BLUE]);
+        let back: Theme = serde_json::from_str(&serde_json::to_string(&theme).unwrap()).unwrap();
+        assert_same(&theme, &back);
+        assert_eq!(
+            back.style_for(TokenKind::Keyword).attributes.link_id(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let theme: Theme = serde_json::from_str(r#"{"name":"bare"}"#).unwrap();
+        assert_same(&theme, &Theme::new("bare"));
+    }
+
+    #[test]
+    fn unknown_token_kind_is_an_error() {
+        let err = serde_json::from_str::<Theme>(r#"{"styles":{"sparkle":{}}}"#).unwrap_err();
+        assert!(err.to_string().contains("sparkle"), "{err}");
+    }
+}
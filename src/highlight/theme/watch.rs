@@ -0,0 +1,364 @@
+//! Live reloading of theme files (`themes` feature).
+//!
+//! Each watched file gets a poller thread that compares the file's
+//! modification time and size on an interval. Changed files are parsed on
+//! that thread and parked until [`ThemeRegistry::update`] swaps them in, so
+//! the registry is only ever changed by its owner.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use super::{Theme, ThemeRegistry};
+use crate::error::{Error, Result};
+use crate::event::{EventBus, LogLevel, LogRecord, emit_log_record};
+
+/// How often watched theme files are checked unless
+/// [`ThemeRegistry::set_watch_interval`] says otherwise.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Published on the registry's [`EventBus`] when a watched theme file has
+/// been re-parsed, from the poller thread.
+///
+/// The new theme is applied by the next [`ThemeRegistry::update`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThemeReloaded {
+    /// Name the theme is registered under.
+    pub name: String,
+}
+
+/// Themes parsed by poller threads, keyed like the registry's themes.
+type Pending = Arc<Mutex<HashMap<String, Theme>>>;
+
+/// Watch state of a [`ThemeRegistry`].
+pub struct Watches {
+    active: HashMap<String, Watch>,
+    pending: Pending,
+    interval: Duration,
+    bus: Option<EventBus>,
+    generation: u64,
+}
+
+impl Default for Watches {
+    fn default() -> Self {
+        Self {
+            active: HashMap::new(),
+            pending: Pending::default(),
+            interval: DEFAULT_WATCH_INTERVAL,
+            bus: None,
+            generation: 0,
+        }
+    }
+}
+
+/// A poller thread, stopped and joined on drop.
+struct Watch {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        // Hanging up wakes the poller from its wait.
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// What a poll compares to notice a changed file.
+type Stamp = Option<(Option<SystemTime>, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+impl ThemeRegistry {
+    /// Load the theme file at `path` as `name`, then reload it whenever
+    /// the file changes.
+    ///
+    /// Reloads are parsed in the background and applied by
+    /// [`update`](Self::update), which the application calls once per
+    /// frame; pass the theme to
+    /// [`EditorView::set_highlighting_theme`](crate::EditorView::set_highlighting_theme)
+    /// or [`HighlightedBuffer::set_theme`](crate::HighlightedBuffer::set_theme)
+    /// when it returns `true`. A file that fails to parse keeps the previous
+    /// theme and logs a warning with the file and line. Watching a name
+    /// again replaces its watch.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Theme::from_file`] error if the initial load fails,
+    /// without registering or watching anything.
+    pub fn watch_file(&mut self, path: impl AsRef<Path>, name: &str) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let mut theme = Theme::from_file(&path)?;
+        theme.name = name.to_string();
+        self.register(theme);
+
+        let key = name.to_ascii_lowercase();
+        self.unwatch(name);
+        let (stop, stopped) = mpsc::channel();
+        let poller = Poller {
+            last: stamp(&path),
+            path,
+            name: name.to_string(),
+            key: key.clone(),
+            pending: Arc::clone(&self.watches.pending),
+            bus: self.watches.bus.clone(),
+        };
+        let interval = self.watches.interval;
+        let handle = thread::Builder::new()
+            .name(format!("theme-watch-{key}"))
+            .spawn(move || {
+                poller.run(&stopped, interval);
+            })?;
+        self.watches.active.insert(
+            key,
+            Watch {
+                stop: Some(stop),
+                handle: Some(handle),
+            },
+        );
+        Ok(())
+    }
+
+    /// Stop watching the theme file registered as `name`, keeping the
+    /// theme. Returns `false` if it was not watched.
+    pub fn unwatch(&mut self, name: &str) -> bool {
+        let key = name.to_ascii_lowercase();
+        let watched = self.watches.active.remove(&key).is_some();
+        self.watches
+            .pending
+            .lock()
+            .expect("theme watch lock")
+            .remove(&key);
+        watched
+    }
+
+    /// Apply themes reloaded since the last call. Returns `true` if any
+    /// theme changed.
+    pub fn update(&mut self) -> bool {
+        let reloaded: Vec<Theme> = {
+            let mut pending = self.watches.pending.lock().expect("theme watch lock");
+            pending.drain().map(|(_, theme)| theme).collect()
+        };
+        let changed = !reloaded.is_empty();
+        for theme in reloaded {
+            self.register(theme);
+            self.watches.generation += 1;
+        }
+        changed
+    }
+
+    /// Number of reloads applied by [`update`](Self::update), for telling
+    /// whether styles taken from the registry are stale.
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.watches.generation
+    }
+
+    /// Set how often files watched from now on are checked.
+    pub fn set_watch_interval(&mut self, interval: Duration) {
+        self.watches.interval = interval;
+    }
+
+    /// Publish a [`ThemeReloaded`] on `bus` whenever a file watched from now
+    /// on has been re-parsed.
+    pub fn set_event_bus(&mut self, bus: EventBus) {
+        self.watches.bus = Some(bus);
+    }
+}
+
+/// The poller thread's side of a watch.
+struct Poller {
+    path: PathBuf,
+    name: String,
+    key: String,
+    last: Stamp,
+    pending: Pending,
+    bus: Option<EventBus>,
+}
+
+impl Poller {
+    fn run(mut self, stopped: &mpsc::Receiver<()>, interval: Duration) {
+        while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+            let now = stamp(&self.path);
+            if now == self.last {
+                continue;
+            }
+            self.last = now;
+            // A file caught mid-save is picked up by the write that ends it.
+            if now.is_none() {
+                continue;
+            }
+            match Theme::from_file(&self.path) {
+                Ok(mut theme) => {
+                    theme.name.clone_from(&self.name);
+                    self.pending
+                        .lock()
+                        .expect("theme watch lock")
+                        .insert(self.key.clone(), theme);
+                    if let Some(bus) = &self.bus {
+                        bus.publish(&ThemeReloaded {
+                            name: self.name.clone(),
+                        });
+                    }
+                }
+                Err(err) => self.log_error(&err),
+            }
+        }
+    }
+
+    fn log_error(&self, err: &Error) {
+        let file = self.path.display().to_string();
+        let line = match err {
+            Error::ThemeParse {
+                line: Some(line), ..
+            } => line.to_string(),
+            _ => String::new(),
+        };
+        let message = format!("theme `{}` not reloaded: {err}", self.name);
+        emit_log_record(
+            &LogRecord::new(LogLevel::Warn, &message)
+                .with_target(module_path!())
+                .with_fields(&[("file", &file), ("line", &line)]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{set_log_record_callback, test_log_lock};
+    use crate::highlight::TokenKind;
+    use crate::{Rgba, Style};
+    use std::time::Instant;
+
+    fn write_theme(path: &Path, keyword: &str) {
+        let source = format!("name = \"Live\"\n\n[styles]\nkeyword = \"{keyword}\"\n");
+        fs::write(path, source).unwrap();
+    }
+
+    fn keyword(registry: &ThemeRegistry) -> Style {
+        *registry.get("live").unwrap().style_for(TokenKind::Keyword)
+    }
+
+    /// Call `update` until it applies a reload or a few seconds pass.
+    fn wait_for_update(registry: &mut ThemeRegistry) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if registry.update() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn reloads_changed_file_and_survives_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live.toml");
+        write_theme(&path, "#ff0000");
+
+        let bus = EventBus::new();
+        let reloads = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reloads);
+        bus.subscribe(move |event: &ThemeReloaded| sink.lock().unwrap().push(event.clone()));
+
+        let mut registry = ThemeRegistry::new();
+        registry.set_watch_interval(Duration::from_millis(5));
+        registry.set_event_bus(bus);
+        registry.watch_file(&path, "Live").unwrap();
+        assert_eq!(keyword(&registry).fg, Some(Rgba::RED));
+        assert_eq!(registry.generation(), 0);
+        assert!(!registry.update());
+
+        write_theme(&path, "bold #0000ff");
+        assert!(wait_for_update(&mut registry));
+        assert_eq!(registry.generation(), 1);
+        assert_eq!(keyword(&registry), Style::fg(Rgba::BLUE).with_bold());
+        assert_eq!(registry.get("live").unwrap().name(), "Live");
+        assert_eq!(
+            *reloads.lock().unwrap(),
+            [ThemeReloaded {
+                name: "Live".to_string()
+            }]
+        );
+
+        // A broken save keeps the last good theme and says where it broke.
+        let _lock = test_log_lock();
+        let file = path.display().to_string();
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&logs);
+        set_log_record_callback(move |record| {
+            if record.fields.first() == Some(&("file", file.as_str())) {
+                let fields: Vec<String> =
+                    record.fields.iter().map(|(_, v)| v.to_string()).collect();
+                sink.lock().unwrap().push((record.level, fields));
+            }
+        });
+        fs::write(
+            &path,
+            "name = \"Live\"\n[styles]\nkeyword = \"#zzzzzz\" # broken\n",
+        )
+        .unwrap();
+        // A poll may also catch the file half-written; wait for the full one.
+        let full_error = |logs: &[(LogLevel, Vec<String>)]| {
+            logs.iter()
+                .any(|(level, fields)| *level == LogLevel::Warn && fields[1] == "3")
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !full_error(&logs.lock().unwrap()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        crate::event::clear_log_callback();
+        assert!(full_error(&logs.lock().unwrap()));
+        assert!(!registry.update());
+        assert_eq!(registry.generation(), 1);
+        assert_eq!(keyword(&registry), Style::fg(Rgba::BLUE).with_bold());
+
+        // Fixing the file reloads again.
+        write_theme(&path, "#00ff00");
+        assert!(wait_for_update(&mut registry));
+        assert_eq!(registry.generation(), 2);
+        assert_eq!(keyword(&registry).fg, Some(Rgba::GREEN));
+    }
+
+    #[test]
+    fn unwatch_and_drop_stop_pollers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live.toml");
+        write_theme(&path, "#ff0000");
+
+        let mut registry = ThemeRegistry::new();
+        registry.set_watch_interval(Duration::from_millis(5));
+        assert!(
+            registry
+                .watch_file(dir.path().join("missing.toml"), "Gone")
+                .is_err()
+        );
+        assert!(registry.get("gone").is_none());
+
+        registry.watch_file(&path, "Live").unwrap();
+        assert!(registry.unwatch("LIVE"));
+        assert!(!registry.unwatch("live"));
+        write_theme(&path, "#0000ff");
+        thread::sleep(Duration::from_millis(50));
+        assert!(!registry.update());
+        assert_eq!(keyword(&registry).fg, Some(Rgba::RED));
+
+        // Dropping the registry joins the poller; a hung thread would hang here.
+        registry.watch_file(&path, "Live").unwrap();
+        let started = Instant::now();
+        drop(registry);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
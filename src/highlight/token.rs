@@ -29,6 +29,8 @@ pub enum TokenKind {
     Comment,
     CommentBlock,
     CommentDoc,
+    /// Attention words (`TODO`, `FIXME`, ...) from a tokenizer customization.
+    Todo,
 
     // Operators and punctuation
     Operator,
@@ -47,6 +49,12 @@ pub enum TokenKind {
     CodeInline,
     CodeBlock,
 
+    // Diffs (unified diff / patch content)
+    DiffAdd,
+    DiffRemove,
+    DiffHunk,
+    DiffFile,
+
     // Errors
     Error,
 
@@ -55,7 +63,7 @@ pub enum TokenKind {
 }
 
 impl TokenKind {
-    pub const ALL: [TokenKind; 29] = [
+    pub const ALL: [TokenKind; 34] = [
         TokenKind::Keyword,
         TokenKind::KeywordControl,
         TokenKind::KeywordType,
@@ -72,6 +80,7 @@ impl TokenKind {
         TokenKind::Comment,
         TokenKind::CommentBlock,
         TokenKind::CommentDoc,
+        TokenKind::Todo,
         TokenKind::Operator,
         TokenKind::Punctuation,
         TokenKind::Delimiter,
@@ -83,6 +92,10 @@ impl TokenKind {
         TokenKind::Emphasis,
         TokenKind::CodeInline,
         TokenKind::CodeBlock,
+        TokenKind::DiffAdd,
+        TokenKind::DiffRemove,
+        TokenKind::DiffHunk,
+        TokenKind::DiffFile,
         TokenKind::Error,
         TokenKind::Text,
     ];
@@ -93,6 +106,67 @@ impl TokenKind {
     pub const fn as_usize(self) -> usize {
         self as usize
     }
+
+    /// Stable `snake_case` name used in theme files.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::KeywordControl => "keyword_control",
+            Self::KeywordType => "keyword_type",
+            Self::KeywordModifier => "keyword_modifier",
+            Self::String => "string",
+            Self::StringEscape => "string_escape",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Identifier => "identifier",
+            Self::Type => "type",
+            Self::Constant => "constant",
+            Self::Function => "function",
+            Self::Macro => "macro",
+            Self::Comment => "comment",
+            Self::CommentBlock => "comment_block",
+            Self::CommentDoc => "comment_doc",
+            Self::Todo => "todo",
+            Self::Operator => "operator",
+            Self::Punctuation => "punctuation",
+            Self::Delimiter => "delimiter",
+            Self::Attribute => "attribute",
+            Self::Lifetime => "lifetime",
+            Self::Label => "label",
+            Self::Heading => "heading",
+            Self::Link => "link",
+            Self::Emphasis => "emphasis",
+            Self::CodeInline => "code_inline",
+            Self::CodeBlock => "code_block",
+            Self::DiffAdd => "diff_add",
+            Self::DiffRemove => "diff_remove",
+            Self::DiffHunk => "diff_hunk",
+            Self::DiffFile => "diff_file",
+            Self::Error => "error",
+            Self::Text => "text",
+        }
+    }
+
+    /// Look up a kind by its [`name`](Self::name).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Kind whose style a theme uses when it has no style for this kind.
+    ///
+    /// Lets themes written before a kind existed still color it sensibly.
+    #[must_use]
+    pub const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::DiffAdd => Some(Self::String),
+            Self::DiffRemove | Self::Todo => Some(Self::Error),
+            Self::DiffHunk => Some(Self::Label),
+            Self::DiffFile => Some(Self::Keyword),
+            _ => None,
+        }
+    }
 }
 
 /// A token produced by a tokenizer.
@@ -126,6 +200,53 @@ impl Token {
     }
 }
 
+/// A cached token placed in its buffer.
+///
+/// Returned by
+/// [`HighlightedBuffer::tokens_in_range`](super::HighlightedBuffer::tokens_in_range)
+/// and [`HighlightedBuffer::token_at`](super::HighlightedBuffer::token_at).
+/// Derefs to the [`Token`], whose `start`/`end` are bytes within the line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferToken<'a> {
+    pub token: &'a Token,
+    /// Line the token is on.
+    pub line: usize,
+    /// Char offset of the line start in the buffer.
+    pub line_start: usize,
+    /// Char offset of the token start in the buffer.
+    pub char_start: usize,
+    /// Char offset of the token end in the buffer.
+    pub char_end: usize,
+}
+
+impl BufferToken<'_> {
+    /// Byte range within the line.
+    #[must_use]
+    pub fn bytes(&self) -> Range<usize> {
+        self.token.range()
+    }
+
+    /// Char range in the buffer.
+    #[must_use]
+    pub const fn chars(&self) -> Range<usize> {
+        self.char_start..self.char_end
+    }
+
+    /// Char columns within the line.
+    #[must_use]
+    pub const fn columns(&self) -> Range<usize> {
+        self.char_start - self.line_start..self.char_end - self.line_start
+    }
+}
+
+impl std::ops::Deref for BufferToken<'_> {
+    type Target = Token;
+
+    fn deref(&self) -> &Token {
+        self.token
+    }
+}
+
 /// A token paired with its source text slice for rendering.
 #[derive(Clone, Debug)]
 pub struct TokenSpan<'a> {
@@ -161,6 +282,29 @@ mod tests {
         assert_copy::<TokenKind>();
     }
 
+    #[test]
+    fn token_kind_all_matches_discriminants() {
+        for (index, kind) in TokenKind::ALL.iter().enumerate() {
+            assert_eq!(kind.as_usize(), index);
+        }
+    }
+
+    #[test]
+    fn token_kind_names_round_trip() {
+        for kind in TokenKind::ALL {
+            assert_eq!(TokenKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(TokenKind::from_name("nope"), None);
+    }
+
+    #[test]
+    fn diff_kinds_fall_back_to_core_kinds() {
+        assert_eq!(TokenKind::DiffAdd.fallback(), Some(TokenKind::String));
+        assert_eq!(TokenKind::DiffRemove.fallback(), Some(TokenKind::Error));
+        assert_eq!(TokenKind::Todo.fallback(), Some(TokenKind::Error));
+        assert_eq!(TokenKind::Keyword.fallback(), None);
+    }
+
     #[test]
     fn token_span_holds_slice() {
         let source = "let x = 1;";
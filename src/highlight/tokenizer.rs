@@ -3,6 +3,8 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::customize::{Customization, CustomizedTokenizer};
+use super::detect;
 use super::token::Token;
 
 /// Lexical state carried across lines for incremental tokenization.
@@ -14,6 +16,16 @@ pub enum LineState {
     InComment(CommentKind),
     InRawString(u8),
     InHeredoc(HeredocKind),
+    /// Inside an indentation-delimited block scalar (YAML `|`/`>`). Holds the
+    /// indentation of the node that introduced it; content lines are indented
+    /// deeper than this.
+    InBlockScalar(u16),
+    /// Inside a unified diff hunk body, with the number of old-side and
+    /// new-side lines still expected before the hunk ends.
+    InDiffHunk {
+        old: u32,
+        new: u32,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -21,7 +33,14 @@ pub enum StringKind {
     Double,
     Single,
     Backtick,
+    /// Triple-quoted with double quotes (`"""`).
     Triple,
+    /// Triple-quoted with single quotes (`'''`).
+    TripleSingle,
+    /// Triple-quoted format string (`f"""`) whose `{}` fields are code.
+    FormatTriple,
+    /// Triple-quoted format string with single quotes (`f'''`).
+    FormatTripleSingle,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -33,10 +52,104 @@ pub enum CommentKind {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum HeredocKind {
-    Shell,
+    /// Shell heredoc body. The delimiter word is stored inline so the state
+    /// stays `Copy`; `strip_tabs` is set for `<<-`, and `literal` when the
+    /// delimiter was quoted (which suppresses expansions in the body).
+    Shell {
+        delimiter: HeredocDelimiter,
+        strip_tabs: bool,
+        literal: bool,
+    },
     Ruby,
 }
 
+/// Heredoc delimiter word, held inline in the line state.
+///
+/// Words of up to 12 bytes are kept whole and compared exactly. Longer ones
+/// keep their length, first 12 bytes and a 64-bit FNV-1a hash.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeredocDelimiter {
+    len: u32,
+    prefix: [u8; 12],
+    hash: u64,
+}
+
+impl HeredocDelimiter {
+    /// Store `word` as a delimiter.
+    #[must_use]
+    pub fn new(word: &str) -> Self {
+        let bytes = word.as_bytes();
+        let mut prefix = [0; 12];
+        let kept = bytes.len().min(prefix.len());
+        prefix[..kept].copy_from_slice(&bytes[..kept]);
+        Self {
+            len: u32::try_from(bytes.len()).unwrap_or(u32::MAX),
+            prefix,
+            hash: fnv1a(bytes),
+        }
+    }
+
+    /// Returns true if `line` is exactly the delimiter word.
+    #[must_use]
+    pub fn matches(&self, line: &str) -> bool {
+        let bytes = line.as_bytes();
+        let kept = bytes.len().min(self.prefix.len());
+        u32::try_from(bytes.len()).is_ok_and(|len| len == self.len)
+            && bytes[..kept] == self.prefix[..kept]
+            && (bytes.len() <= self.prefix.len() || fnv1a(bytes) == self.hash)
+    }
+}
+
+impl std::fmt::Debug for HeredocDelimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kept = (self.len as usize).min(self.prefix.len());
+        let word = String::from_utf8_lossy(&self.prefix[..kept]);
+        if kept < self.len as usize {
+            write!(f, "HeredocDelimiter({word:?}..)")
+        } else {
+            write!(f, "HeredocDelimiter({word:?})")
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl HeredocKind {
+    /// Create a shell heredoc state for the given (unquoted) delimiter word.
+    #[must_use]
+    pub fn shell(delimiter: &str, strip_tabs: bool, literal: bool) -> Self {
+        Self::Shell {
+            delimiter: HeredocDelimiter::new(delimiter),
+            strip_tabs,
+            literal,
+        }
+    }
+
+    /// Returns true if `line` is the terminator line for this heredoc.
+    #[must_use]
+    pub fn is_terminator(self, line: &str) -> bool {
+        match self {
+            Self::Shell {
+                delimiter,
+                strip_tabs,
+                ..
+            } => {
+                let line = if strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line
+                };
+                delimiter.matches(line)
+            }
+            Self::Ruby => false,
+        }
+    }
+}
+
 /// Core tokenizer abstraction for syntax highlighting.
 pub trait Tokenizer: Send + Sync {
     /// Human-readable name of this tokenizer.
@@ -45,10 +158,26 @@ pub trait Tokenizer: Send + Sync {
     /// File extensions this tokenizer handles (e.g., `rs`, `rust`).
     fn extensions(&self) -> &'static [&'static str];
 
+    /// Additional names this tokenizer can be looked up by (e.g., `bash`).
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Tokenize a single line given the state from the previous line.
     /// Returns: (tokens, state_at_end_of_line).
     fn tokenize_line(&self, line: &str, state: LineState) -> (Vec<Token>, LineState);
 
+    /// Counter that changes whenever the output of [`tokenize_line`] for
+    /// the same input may have changed (e.g. a swapped [`Customization`]).
+    ///
+    /// Cached tokens produced under another generation are stale.
+    ///
+    /// [`tokenize_line`]: Self::tokenize_line
+    /// [`Customization`]: super::Customization
+    fn generation(&self) -> u64 {
+        0
+    }
+
     /// Tokenize an entire text by calling `tokenize_line` for each line.
     ///
     /// Handles both LF (`\n`) and CRLF (`\r\n`) line endings correctly by
@@ -97,6 +226,8 @@ pub struct TokenizerRegistry {
     tokenizers: Vec<Arc<dyn Tokenizer>>,
     by_extension: HashMap<String, usize>,
     by_name: HashMap<String, usize>,
+    by_filename: HashMap<String, usize>,
+    customized: HashMap<usize, Arc<CustomizedTokenizer>>,
 }
 
 impl TokenizerRegistry {
@@ -111,6 +242,9 @@ impl TokenizerRegistry {
         let index = self.tokenizers.len();
         let name_key = tokenizer.name().to_ascii_lowercase();
         self.by_name.insert(name_key, index);
+        for alias in tokenizer.aliases() {
+            self.by_name.insert(alias.to_ascii_lowercase(), index);
+        }
 
         for ext in tokenizer.extensions() {
             let key = ext.trim_start_matches('.').to_ascii_lowercase();
@@ -154,10 +288,108 @@ impl TokenizerRegistry {
         self.tokenizers.get(*index).cloned()
     }
 
+    /// Look up `ext` (case-insensitive, with or without dot) as the
+    /// tokenizer registered as `language`, replacing any earlier mapping.
+    /// Returns `false` if no tokenizer is registered as `language`.
+    pub fn register_extension(&mut self, ext: &str, language: &str) -> bool {
+        let Some(&index) = self.by_name.get(&language.to_ascii_lowercase()) else {
+            return false;
+        };
+        let key = ext.trim_start_matches('.').to_ascii_lowercase();
+        self.by_extension.insert(key, index);
+        true
+    }
+
+    /// Detect files named exactly `name` (case-insensitive) as `language`,
+    /// ahead of the built-in file names. Returns `false` if no tokenizer is
+    /// registered as `language`.
+    pub fn register_filename(&mut self, name: &str, language: &str) -> bool {
+        let Some(&index) = self.by_name.get(&language.to_ascii_lowercase()) else {
+            return false;
+        };
+        self.by_filename.insert(name.to_ascii_lowercase(), index);
+        true
+    }
+
+    /// Name of the registered tokenizer for a file, from its path and its
+    /// first few lines.
+    ///
+    /// Signals are tried in order, and the first one naming a registered
+    /// language wins:
+    ///
+    /// 1. The exact file name: [`register_filename`](Self::register_filename)
+    ///    mappings, then well-known names like `Makefile` and `Cargo.lock`.
+    /// 2. The extension, as [`for_extension`](Self::for_extension) finds it.
+    ///    Unclaimed extensions shared by several languages fall back to a
+    ///    fixed preference: `.h` is C, then C++, then Objective-C. Register
+    ///    the extension to override that.
+    /// 3. A `#!` line, through `env` and version suffixes (`python3` is
+    ///    Python).
+    /// 4. Content: an XML prolog, an HTML doctype, a diff header, or JSON
+    ///    opening with a quoted key.
+    #[must_use]
+    pub fn detect(&self, path: Option<&str>, first_lines: &str) -> Option<&'static str> {
+        let file_name = path.map(detect::file_name).unwrap_or_default();
+        let file_key = file_name.to_ascii_lowercase();
+        let by_file_name = self.by_filename.get(&file_key).copied().or_else(|| {
+            detect::FILENAMES
+                .iter()
+                .filter(|(name, _)| *name == file_key)
+                .find_map(|(_, language)| self.index_of(language))
+        });
+        let by_extension = || {
+            let ext = detect::extension(file_name)?.to_ascii_lowercase();
+            self.by_extension.get(&ext).copied().or_else(|| {
+                detect::AMBIGUOUS_EXTENSIONS
+                    .iter()
+                    .filter(|(ambiguous, _)| *ambiguous == ext)
+                    .flat_map(|(_, languages)| languages.iter())
+                    .find_map(|language| self.index_of(language))
+            })
+        };
+        let index = by_file_name
+            .or_else(by_extension)
+            .or_else(|| self.index_of(detect::shebang_language(first_lines)?))
+            .or_else(|| self.index_of(detect::content_language(first_lines)?))?;
+        Some(self.tokenizers[index].name())
+    }
+
+    fn index_of(&self, language: &str) -> Option<usize> {
+        self.by_name.get(&language.to_ascii_lowercase()).copied()
+    }
+
+    /// Apply a [`Customization`] to the tokenizer registered as `language`
+    /// (name or alias, case-insensitive). Returns `false` if there is none.
+    ///
+    /// The first call wraps the tokenizer in a [`CustomizedTokenizer`], so
+    /// fetch it again to see the customization. Later calls swap the
+    /// customization in place: buffers already using the wrapped tokenizer
+    /// re-tokenize on their next update. Pass `Customization::default()` to
+    /// undo a customization.
+    pub fn customize(&mut self, language: &str, customization: Customization) -> bool {
+        let Some(&index) = self.by_name.get(&language.to_ascii_lowercase()) else {
+            return false;
+        };
+        if let Some(customized) = self.customized.get(&index) {
+            customized.set_customization(&customization);
+            return true;
+        }
+        let customized = Arc::new(CustomizedTokenizer::new(
+            self.tokenizers[index].clone(),
+            &customization,
+        ));
+        self.tokenizers[index] = customized.clone();
+        self.customized.insert(index, customized);
+        true
+    }
+
     /// Create registry with all built-in tokenizers.
     #[must_use]
     pub fn with_builtins() -> Self {
         let mut registry = Self::new();
+        registry.register(Box::new(
+            crate::highlight::languages::diff::DiffTokenizer::new(),
+        ));
         registry.register(Box::new(
             crate::highlight::languages::javascript::JavaScriptTokenizer::javascript(),
         ));
@@ -176,9 +408,15 @@ impl TokenizerRegistry {
         registry.register(Box::new(
             crate::highlight::languages::rust::RustTokenizer::new(),
         ));
+        registry.register(Box::new(
+            crate::highlight::languages::shell::ShellTokenizer::new(),
+        ));
         registry.register(Box::new(
             crate::highlight::languages::toml::TomlTokenizer::new(),
         ));
+        registry.register(Box::new(
+            crate::highlight::languages::yaml::YamlTokenizer::new(),
+        ));
         registry
     }
 }
@@ -211,7 +449,8 @@ mod tests {
         let _ = LineState::InString(StringKind::Double);
         let _ = LineState::InComment(CommentKind::Block);
         let _ = LineState::InRawString(2);
-        let _ = LineState::InHeredoc(HeredocKind::Shell);
+        let _ = LineState::InHeredoc(HeredocKind::shell("EOF", false, false));
+        let _ = LineState::InBlockScalar(4);
     }
 
     #[test]
@@ -292,6 +531,120 @@ mod tests {
         assert!(registry.by_name("missing").is_none());
     }
 
+    /// A tokenizer that only has a name and extensions, for detection tests.
+    struct Named(&'static str, &'static [&'static str]);
+
+    impl Tokenizer for Named {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn extensions(&self) -> &'static [&'static str] {
+            self.1
+        }
+
+        fn tokenize_line(&self, _line: &str, state: LineState) -> (Vec<Token>, LineState) {
+            (Vec::new(), state)
+        }
+    }
+
+    #[test]
+    fn registry_detects_language() {
+        let mut registry = TokenizerRegistry::with_builtins();
+        registry.register(Box::new(Named("Make", &["mk"])));
+        registry.register(Box::new(Named("XML", &["xml"])));
+        registry.register(Box::new(Named("C", &["c"])));
+        registry.register(Box::new(Named("CPP", &["cpp", "hpp"])));
+
+        let cases: &[(Option<&str>, &str, Option<&str>)] = &[
+            // File names and extensions
+            (Some("src/main.rs"), "", Some("Rust")),
+            (Some("C:\\proj\\LIB.PY"), "", Some("Python")),
+            (Some("Makefile"), "", Some("Make")),
+            (Some("build/GNUmakefile"), "", Some("Make")),
+            (Some("Cargo.lock"), "", Some("TOML")),
+            (Some("/home/me/.bashrc"), "", Some("Shell")),
+            (Some("Dockerfile"), "", None),
+            (Some("notes.txt"), "", None),
+            // Ambiguous `.h`: C before C++
+            (Some("vec.h"), "", Some("C")),
+            // Shebangs
+            (None, "#!/usr/bin/env python3\nprint()", Some("Python")),
+            (None, "#!/usr/bin/env -S python3.12 -u", Some("Python")),
+            (None, "#!/bin/bash -e", Some("Shell")),
+            (None, "#!/bin/sh", Some("Shell")),
+            (None, "#!/usr/bin/env node", Some("JavaScript")),
+            (None, "#!/usr/bin/env LANG=C deno run", Some("TypeScript")),
+            (None, "#!/usr/bin/perl", None),
+            // Content
+            (None, "<?xml version=\"1.0\"?>\n<root/>", Some("XML")),
+            (None, "\u{feff}  {\n  \"name\": \"x\"\n}", Some("JSON")),
+            (None, "[{\"a\": 1}]", Some("JSON")),
+            (None, "{ not json }", None),
+            (None, "diff --git a/x b/x\n", Some("Diff")),
+            (None, "--- a/x\n+++ b/x\n", Some("Diff")),
+            // Conflicting signals: name, then extension, then shebang, then content
+            (Some("Makefile"), "#!/usr/bin/env python3", Some("Make")),
+            (Some("run.sh"), "#!/usr/bin/env python3", Some("Shell")),
+            (
+                Some("run"),
+                "#!/usr/bin/env python3\n{\"a\": 1}",
+                Some("Python"),
+            ),
+            (Some("data.cfg"), "{\"a\": 1}", Some("JSON")),
+            (Some("page.json"), "<?xml version=\"1.0\"?>", Some("JSON")),
+        ];
+        for &(path, text, expected) in cases {
+            assert_eq!(registry.detect(path, text), expected, "{path:?} {text:?}");
+        }
+    }
+
+    #[test]
+    fn registry_detection_overrides() {
+        let mut registry = TokenizerRegistry::new();
+        registry.register(Box::new(Named("C", &["c"])));
+        registry.register(Box::new(Named("CPP", &["cpp"])));
+        registry.register(Box::new(Named("TOML", &["toml"])));
+        assert_eq!(registry.detect(Some("a.h"), ""), Some("C"));
+
+        assert!(registry.register_extension(".H", "cpp"));
+        assert_eq!(registry.detect(Some("a.h"), ""), Some("CPP"));
+        assert!(!registry.register_extension("h", "missing"));
+        assert_eq!(registry.detect(Some("a.h"), ""), Some("CPP"));
+
+        // A registered file name beats both built-in names and extensions.
+        assert!(registry.register_filename("Config.c", "toml"));
+        assert_eq!(registry.detect(Some("etc/config.c"), ""), Some("TOML"));
+        assert_eq!(registry.detect(Some("main.c"), ""), Some("C"));
+        assert!(!registry.register_filename("x", "missing"));
+    }
+
+    #[test]
+    fn shell_heredoc_terminator_matching() {
+        let plain = HeredocKind::shell("EOF", false, false);
+        assert!(plain.is_terminator("EOF"));
+        assert!(!plain.is_terminator("\tEOF"));
+        assert!(!plain.is_terminator("EOF "));
+        assert!(!plain.is_terminator("END"));
+
+        // These words share a 32-bit FNV-1a hash.
+        let collides = HeredocKind::shell("costarring", false, false);
+        assert!(collides.is_terminator("costarring"));
+        assert!(!collides.is_terminator("liquid"));
+
+        // Long delimiters are compared by length, prefix and hash.
+        let long = HeredocKind::shell("END_OF_THE_MANIFEST", false, false);
+        assert!(long.is_terminator("END_OF_THE_MANIFEST"));
+        assert!(!long.is_terminator("END_OF_THE_MANIFESTO"));
+        assert!(!long.is_terminator("END_OF_THE_MANIFESS"));
+        assert!(!long.is_terminator("END_OF_THE_M"));
+
+        let stripped = HeredocKind::shell("EOF", true, true);
+        assert!(stripped.is_terminator("\t\tEOF"));
+        assert_ne!(plain, stripped);
+        assert!(!HeredocKind::Ruby.is_terminator("EOF"));
+    }
+
     #[test]
     fn tokenizer_trait_is_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
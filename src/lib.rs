@@ -9,11 +9,14 @@
 //! This repository is a single-crate system. The `opentui` crate is the core
 //! engine that applications build on. You provide your own application loop
 //! and input handling; OpenTUI provides the rendering, text, and terminal
-//! primitives that make that loop fast and correct.
+//! primitives that make that loop fast and correct. For the common case,
+//! [`run()`] is a thin loop that handles raw mode, input, resizes, frame
+//! pacing, and terminal restoration for you.
 //!
 //! # Architecture At A Glance
 //!
 //! - `renderer`: Double-buffered rendering, diff detection, hit testing
+//! - `run`: Optional main loop with input, resize, pacing, and cleanup
 //! - `buffer`: Cell grids, scissor clipping, opacity stacking, compositing
 //! - `cell` / `style` / `color`: The core visual primitives (Cell, Style, Rgba)
 //! - `ansi`: ANSI escape emission with state tracking for minimal output
@@ -24,6 +27,13 @@
 //! - `highlight`: Tokenization and theming for syntax-highlighted buffers
 //! - `grapheme_pool` / `link`: Interned graphemes and OSC 8 hyperlink storage
 //! - `event` / `error`: Lightweight callbacks and error types
+//! - feature `serde`: Serialize/Deserialize for `Rgba`, `Style`, `Theme`,
+//!   and cells through `cell::CellRepr`
+//! - `ffi` (feature `ffi`): C ABI over the renderer, see `include/opentui.h`
+//! - `testing` (feature `testing`): Buffer snapshots and assertions for tests
+//! - `compat` (feature `ratatui-backend`): Adapters for other TUI libraries
+//! - feature `pool-audit`: Grapheme pool allocation sites and refcount
+//!   checks, see `grapheme_pool::GraphemePool::verify_against`
 //!
 //! # Data Flow
 //!
@@ -68,26 +78,37 @@ pub mod ansi;
 pub mod buffer;
 pub mod cell;
 pub mod color;
+#[cfg(feature = "ratatui-backend")]
+pub mod compat;
 pub mod error;
 pub mod event;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod grapheme_pool;
 pub mod highlight;
 pub mod input;
 pub mod link;
 pub mod renderer;
+pub mod replay;
+pub mod run;
 pub mod style;
 pub mod terminal;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod text;
 pub mod unicode;
 
 // Re-export core types at crate root
 pub use cell::{Cell, CellContent, GraphemeId};
 pub use color::Rgba;
-pub use error::{Error, Result};
-pub use event::{LogLevel, emit_event, emit_log, set_event_callback, set_log_callback};
+pub use error::{Error, RenderPhase, Result, TerminalOp};
+pub use event::{
+    LogLevel, LogRecord, emit_event, emit_log, emit_log_record, set_event_callback,
+    set_log_callback, set_log_record_callback,
+};
 pub use grapheme_pool::GraphemePool;
 pub use link::LinkPool;
-pub use style::{Style, TextAttributes};
+pub use style::{Style, StylePatch, TextAttributes, UnderlineStyle};
 
 // Re-export input types
 pub use input::{Event, InputParser, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
@@ -98,7 +119,8 @@ pub use ansi::ColorMode;
 // Re-export commonly used types
 pub use buffer::OptimizedBuffer;
 pub use highlight::{HighlightedBuffer, Theme, ThemeRegistry, Token, TokenKind, TokenizerRegistry};
-pub use renderer::{Rect, RenderStats, Renderer, RendererOptions};
+pub use renderer::{ExitPolicy, HookId, Rect, RenderStats, Renderer, RendererOptions};
+pub use run::{ControlFlow, Frame, RunOptions, run};
 pub use terminal::{
     Capabilities, ColorSupport, RawModeGuard, Terminal, enable_raw_mode, is_tty, terminal_size,
 };
@@ -1,11 +1,27 @@
 //! Hyperlink pool for OSC 8 link storage.
+//!
+//! Links are deduplicated: allocating a URL that is already pooled (with the
+//! same params) returns its existing ID with one more reference, so repeated
+//! links share one `id=` in the OSC 8 output.
+
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+struct LinkEntry {
+    url: String,
+    /// OSC 8 params written after `id=`, e.g. `group=docs`.
+    params: String,
+}
 
 /// Pool of hyperlinks with reference counting.
 #[derive(Clone, Debug, Default)]
 pub struct LinkPool {
-    urls: Vec<Option<String>>,
+    entries: Vec<Option<LinkEntry>>,
     ref_counts: Vec<u32>,
     free_list: Vec<u32>,
+    /// Live IDs by URL; more than one when a URL is pooled with different
+    /// params.
+    index: HashMap<String, Vec<u32>>,
 }
 
 impl LinkPool {
@@ -17,28 +33,84 @@ impl LinkPool {
 
     /// Allocate a link ID for the given URL.
     ///
-    /// Returns a non-zero link ID (0 means no link).
+    /// Returns a non-zero link ID (0 means no link). A URL that is already
+    /// pooled without params gets its existing ID and one more reference;
+    /// every call must be balanced by a [`Self::decref`].
     pub fn alloc(&mut self, url: &str) -> u32 {
-        if let Some(id) = self.free_list.pop() {
-            let idx = (id - 1) as usize;
-            self.urls[idx] = Some(url.to_string());
-            self.ref_counts[idx] = 1;
+        self.alloc_with_params(url, "")
+    }
+
+    /// Allocate a link ID for a URL with extra OSC 8 params.
+    ///
+    /// `params` is written after the `id=` param, e.g. `group=docs`; several
+    /// params are separated with `:`. Links are shared only when both the URL
+    /// and the params match.
+    pub fn alloc_with_params(&mut self, url: &str, params: &str) -> u32 {
+        if let Some(id) = self.find(url, params) {
+            self.incref(id);
             return id;
         }
 
-        self.urls.push(Some(url.to_string()));
-        self.ref_counts.push(1);
-        self.urls.len() as u32
+        let entry = LinkEntry {
+            url: url.to_string(),
+            params: params.to_string(),
+        };
+        let id = if let Some(id) = self.free_list.pop() {
+            let idx = (id - 1) as usize;
+            self.entries[idx] = Some(entry);
+            self.ref_counts[idx] = 1;
+            id
+        } else {
+            self.entries.push(Some(entry));
+            self.ref_counts.push(1);
+            self.entries.len() as u32
+        };
+        self.index.entry(url.to_string()).or_default().push(id);
+        id
     }
 
-    /// Get the URL for a link ID.
-    #[must_use]
-    pub fn get(&self, id: u32) -> Option<&str> {
+    /// Get the link ID for a URL, allocating one if it is not pooled yet.
+    ///
+    /// Same as [`Self::alloc`].
+    pub fn intern(&mut self, url: &str) -> u32 {
+        self.alloc(url)
+    }
+
+    fn find(&self, url: &str, params: &str) -> Option<u32> {
+        self.index.get(url)?.iter().copied().find(|&id| {
+            self.entries[(id - 1) as usize]
+                .as_ref()
+                .is_some_and(|entry| entry.params == params)
+        })
+    }
+
+    fn entry(&self, id: u32) -> Option<&LinkEntry> {
         if id == 0 {
             return None;
         }
         let idx = id.saturating_sub(1) as usize;
-        self.urls.get(idx).and_then(|u| u.as_deref())
+        self.entries.get(idx).and_then(Option::as_ref)
+    }
+
+    /// Get the URL for a link ID.
+    #[must_use]
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.entry(id).map(|entry| entry.url.as_str())
+    }
+
+    /// Get the extra OSC 8 params for a link ID (empty when none were given).
+    #[must_use]
+    pub fn params(&self, id: u32) -> Option<&str> {
+        self.entry(id).map(|entry| entry.params.as_str())
+    }
+
+    /// Iterate over the live links as `(id, url)`, in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.entries.iter().enumerate().filter_map(|(idx, entry)| {
+            entry
+                .as_ref()
+                .map(|entry| (idx as u32 + 1, entry.url.as_str()))
+        })
     }
 
     /// Increment the reference count for a link ID.
@@ -58,34 +130,55 @@ impl LinkPool {
             return;
         }
         let idx = id.saturating_sub(1) as usize;
-        if let Some(count) = self.ref_counts.get_mut(idx) {
-            if *count > 0 {
-                *count -= 1;
-                if *count == 0 {
-                    self.urls[idx] = None;
-                    self.free_list.push(id);
+        let Some(count) = self.ref_counts.get_mut(idx) else {
+            return;
+        };
+        if *count == 0 {
+            return;
+        }
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        if let Some(entry) = self.entries[idx].take() {
+            if let Some(ids) = self.index.get_mut(&entry.url) {
+                ids.retain(|&other| other != id);
+                if ids.is_empty() {
+                    self.index.remove(&entry.url);
                 }
             }
         }
+        self.free_list.push(id);
+    }
+
+    /// Current reference count for a link ID (0 for freed or unknown IDs).
+    #[must_use]
+    pub fn ref_count(&self, id: u32) -> u32 {
+        if id == 0 {
+            return 0;
+        }
+        let idx = id.saturating_sub(1) as usize;
+        self.ref_counts.get(idx).copied().unwrap_or(0)
     }
 
     /// Clear all links.
     pub fn clear(&mut self) {
-        self.urls.clear();
+        self.entries.clear();
         self.ref_counts.clear();
         self.free_list.clear();
+        self.index.clear();
     }
 
-    /// Number of allocated slots (including freed slots).
+    /// Number of live links (freed slots are not counted).
     #[must_use]
     pub fn len(&self) -> usize {
-        self.urls.len()
+        self.entries.len() - self.free_list.len()
     }
 
-    /// Check if pool is empty.
+    /// Check if the pool holds no live links.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.urls.is_empty()
+        self.len() == 0
     }
 }
 
@@ -216,6 +309,84 @@ mod tests {
         assert_eq!(pool.get(id), Some("https://example.com"));
     }
 
+    #[test]
+    fn test_link_pool_intern_reuses_id() {
+        let mut pool = LinkPool::new();
+        let id = pool.intern("https://example.com");
+        assert_eq!(pool.intern("https://example.com"), id);
+        assert_eq!(pool.ref_count(id), 2);
+
+        let other = pool.intern("https://other.example");
+        assert_ne!(other, id);
+
+        pool.decref(id);
+        pool.decref(id);
+        assert_eq!(pool.ref_count(id), 0);
+        assert_eq!(pool.get(id), None);
+    }
+
+    #[test]
+    fn test_link_pool_alloc_dedupes_by_url() {
+        let mut pool = LinkPool::new();
+        let id = pool.alloc("https://example.com");
+        assert_eq!(pool.alloc("https://example.com"), id);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.ref_count(id), 2);
+
+        pool.decref(id);
+        assert_eq!(pool.get(id), Some("https://example.com"));
+        pool.decref(id);
+        assert_eq!(pool.get(id), None);
+
+        // A freed URL is allocated afresh.
+        let again = pool.alloc("https://example.com");
+        assert_eq!(pool.ref_count(again), 1);
+    }
+
+    #[test]
+    fn test_link_pool_params_are_part_of_the_key() {
+        let mut pool = LinkPool::new();
+        let plain = pool.alloc("https://example.com");
+        let grouped = pool.alloc_with_params("https://example.com", "group=docs");
+        assert_ne!(plain, grouped);
+        assert_eq!(
+            pool.alloc_with_params("https://example.com", "group=docs"),
+            grouped
+        );
+
+        assert_eq!(pool.params(plain), Some(""));
+        assert_eq!(pool.params(grouped), Some("group=docs"));
+        assert_eq!(pool.get(grouped), Some("https://example.com"));
+
+        // Freeing one variant leaves the other findable.
+        pool.decref(plain);
+        assert_eq!(
+            pool.alloc_with_params("https://example.com", "group=docs"),
+            grouped
+        );
+        assert_eq!(pool.ref_count(grouped), 3);
+        assert_eq!(pool.params(plain), None);
+    }
+
+    #[test]
+    fn test_link_pool_iter_skips_freed_links() {
+        let mut pool = LinkPool::new();
+        let one = pool.alloc("https://one.example");
+        let two = pool.alloc("https://two.example");
+        let three = pool.alloc("https://three.example");
+        pool.decref(two);
+
+        let links: Vec<_> = pool.iter().collect();
+        assert_eq!(
+            links,
+            [
+                (one, "https://one.example"),
+                (three, "https://three.example")
+            ]
+        );
+        assert_eq!(pool.iter().count(), pool.len());
+    }
+
     // ============================================
     // ID Space Management Tests
     // ============================================
@@ -398,15 +569,16 @@ mod tests {
     }
 
     #[test]
-    fn test_link_pool_len_includes_freed_slots() {
+    fn test_link_pool_len_counts_live_links() {
         let mut pool = LinkPool::new();
         let id1 = pool.alloc("https://one.example");
         pool.alloc("https://two.example");
 
         pool.decref(id1);
 
-        // len() returns total slots, including freed ones
-        // The slot is freed but still counted in the vector length
+        // The freed slot is kept for reuse but no longer counted
+        assert_eq!(pool.len(), 1);
+        pool.alloc("https://three.example");
         assert_eq!(pool.len(), 2);
     }
 
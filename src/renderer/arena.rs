@@ -0,0 +1,220 @@
+//! Per-frame storage for transient strings.
+//!
+//! Labels formatted while drawing a frame only need to live until it is
+//! presented. [`FrameArena`] bump-allocates them into a few reused chunks
+//! instead of a fresh `String` each, and [`Renderer::present`] resets it, so
+//! after the first few frames drawing labels allocates nothing.
+//!
+//! [`Renderer::present`]: super::Renderer::present
+
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::fmt::{self, Write as _};
+
+/// Capacity of the first chunk.
+const INITIAL_CHUNK_SIZE: usize = 1024;
+/// Default cap on chunk growth; see [`FrameArena::set_max_chunk_size`].
+const DEFAULT_MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Default)]
+struct Bump {
+    /// Never grown past their capacity, so their contents never move.
+    chunks: Vec<String>,
+    /// Index of the chunk being filled.
+    current: usize,
+    used: usize,
+    /// Reused buffer for [`FrameArena::format`].
+    scratch: String,
+}
+
+/// Bump allocator for strings that live until the next reset.
+///
+/// Strings are borrowed from the arena, so it can't be reset while any are
+/// in use. [`Renderer::arena`](super::Renderer::arena) lends it out with the
+/// renderer mutably borrowed, which keeps one frame's strings from
+/// outliving [`present`](super::Renderer::present):
+///
+/// ```compile_fail
+/// # fn frame(renderer: &mut opentui_rust::Renderer) -> opentui_rust::Result<()> {
+/// let label = renderer.arena().format(format_args!("{} files", 3));
+/// renderer.present()?;
+/// println!("{label}"); // error: `renderer` is still borrowed by `label`
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Chunks double in size as needed, up to the
+/// [maximum chunk size](Self::set_max_chunk_size); longer strings get a
+/// chunk of their own. Chunks are kept across resets.
+#[derive(Debug)]
+pub struct FrameArena {
+    inner: RefCell<Bump>,
+    max_chunk_size: usize,
+    /// Most bytes in use between two resets.
+    peak: usize,
+}
+
+impl Default for FrameArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameArena {
+    /// Create an empty arena; nothing is allocated until first use.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            inner: RefCell::new(Bump::default()),
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            peak: 0,
+        }
+    }
+
+    /// Cap the size chunks grow to. Chunks already allocated are kept.
+    pub fn set_max_chunk_size(&mut self, bytes: usize) {
+        self.max_chunk_size = bytes.max(1);
+    }
+
+    #[must_use]
+    pub const fn max_chunk_size(&self) -> usize {
+        self.max_chunk_size
+    }
+
+    /// Copy `text` into the arena.
+    pub fn alloc_str(&self, text: &str) -> &str {
+        if text.is_empty() {
+            return "";
+        }
+        let mut inner = self.inner.borrow_mut();
+        let inner = &mut *inner;
+        while inner
+            .chunks
+            .get(inner.current)
+            .is_some_and(|chunk| chunk.capacity() - chunk.len() < text.len())
+        {
+            inner.current += 1;
+        }
+        if inner.current == inner.chunks.len() {
+            let grown = inner
+                .chunks
+                .last()
+                .map_or(INITIAL_CHUNK_SIZE, |chunk| {
+                    chunk.capacity().saturating_mul(2)
+                })
+                .min(self.max_chunk_size);
+            inner
+                .chunks
+                .push(String::with_capacity(grown.max(text.len())));
+        }
+
+        let chunk = &mut inner.chunks[inner.current];
+        let start = chunk.len();
+        chunk.push_str(text);
+        inner.used += text.len();
+        let stored = &chunk[start..];
+        // SAFETY: `push_str` stayed within the chunk's capacity, so its heap
+        // buffer was not reallocated, and chunks are only cleared or dropped
+        // through `&mut self`. The bytes stay put and unchanged for as long
+        // as `self` is borrowed.
+        unsafe { &*std::ptr::from_ref::<str>(stored) }
+    }
+
+    /// Format `args` into the arena, as [`format!`] would into a `String`.
+    pub fn format(&self, args: fmt::Arguments<'_>) -> &str {
+        if let Some(text) = args.as_str() {
+            return self.alloc_str(text);
+        }
+        let mut scratch = std::mem::take(&mut self.inner.borrow_mut().scratch);
+        scratch.clear();
+        let _ = scratch.write_fmt(args);
+        let stored = self.alloc_str(&scratch);
+        self.inner.borrow_mut().scratch = scratch;
+        stored
+    }
+
+    /// Bytes of strings allocated since the last reset.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.inner.borrow().used
+    }
+
+    /// Most bytes in use between two resets, over the arena's lifetime.
+    #[must_use]
+    pub fn peak(&self) -> usize {
+        self.peak.max(self.used())
+    }
+
+    /// Bytes of chunk memory held.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        let inner = self.inner.borrow();
+        inner.chunks.iter().map(String::capacity).sum::<usize>() + inner.scratch.capacity()
+    }
+
+    /// Forget every string, keeping the chunks for reuse.
+    pub fn reset(&mut self) {
+        self.peak = self.peak();
+        let inner = self.inner.get_mut();
+        for chunk in &mut inner.chunks {
+            chunk.clear();
+        }
+        inner.current = 0;
+        inner.used = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings_survive_growth() {
+        let mut arena = FrameArena::new();
+        arena.set_max_chunk_size(4096);
+        let labels: Vec<&str> = (0..500)
+            .map(|i| arena.format(format_args!("label {i:>4}")))
+            .collect();
+        for (i, label) in labels.iter().enumerate() {
+            assert_eq!(*label, format!("label {i:>4}"));
+        }
+        assert_eq!(arena.used(), 500 * 10);
+
+        let chunks: Vec<usize> = arena
+            .inner
+            .borrow()
+            .chunks
+            .iter()
+            .map(String::capacity)
+            .collect();
+        assert_eq!(chunks, [1024, 2048, 4096]);
+
+        let long = "x".repeat(5000);
+        assert_eq!(arena.alloc_str(&long), long);
+        assert_eq!(arena.inner.borrow().chunks[3].capacity(), 5000);
+        assert_eq!(arena.alloc_str(""), "");
+        assert_eq!(arena.alloc_str("static"), "static");
+    }
+
+    #[test]
+    fn test_reset_reuses_chunks() {
+        let mut arena = FrameArena::new();
+        for i in 0..200 {
+            arena.format(format_args!("frame one {i}"));
+        }
+        let capacity = arena.capacity();
+        let first_peak = arena.used();
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        assert_eq!(arena.peak(), first_peak);
+
+        for i in 0..200 {
+            arena.format(format_args!("frame two {i}"));
+        }
+        assert_eq!(arena.capacity(), capacity);
+        arena.reset();
+        arena.alloc_str("small");
+        assert_eq!(arena.peak(), first_peak);
+    }
+}
@@ -0,0 +1,54 @@
+//! Fallbacks the renderer applies for what the terminal lacks, reported by
+//! [`Renderer::degradations`](super::Renderer::degradations).
+
+use std::fmt;
+
+use crate::ansi::{ColorMode, LineDrawing};
+
+/// A fallback the renderer is applying.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Degradation {
+    /// Colors are quantized to a palette, or dropped, instead of written as
+    /// 24-bit RGB.
+    ColorQuantized {
+        /// The color mode output is quantized to.
+        mode: ColorMode,
+    },
+    /// Frames are written without synchronized output, so a slow terminal
+    /// may show one half-drawn.
+    SyncOutputDisabled,
+    /// Box-drawing glyphs are written from a fallback charset instead of
+    /// as Unicode.
+    LineDrawingFallback {
+        /// The charset glyphs are written with.
+        charset: LineDrawing,
+    },
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ColorQuantized { mode } => match mode {
+                ColorMode::TrueColor => f.write_str("colors are written as 24-bit RGB"),
+                ColorMode::Color256 => f.write_str("colors are quantized to the 256-color palette"),
+                ColorMode::Color16 => f.write_str("colors are quantized to the 16 ANSI colors"),
+                ColorMode::NoColor => f.write_str("colors are not written"),
+            },
+            Self::SyncOutputDisabled => {
+                f.write_str("frames are written without synchronized output and may tear")
+            }
+            Self::LineDrawingFallback { charset } => match charset {
+                LineDrawing::Unicode => f.write_str("box-drawing glyphs are written as Unicode"),
+                LineDrawing::DecGraphics => f.write_str(
+                    "box-drawing glyphs are written with the DEC special graphics charset",
+                ),
+                LineDrawing::Ascii => f.write_str("box-drawing glyphs are written as ASCII"),
+            },
+        }
+    }
+}
@@ -1,8 +1,13 @@
 //! Buffer diffing for efficient rendering.
 
+use std::ops::Range;
+
 use crate::buffer::OptimizedBuffer;
 use crate::error::Error;
 
+/// Default for [`BufferDiff::rect_merge_window`].
+const DEFAULT_RECT_MERGE_WINDOW: usize = 64;
+
 /// A region that has changed between frames.
 #[derive(Clone, Copy, Debug)]
 pub struct DirtyRegion {
@@ -42,16 +47,68 @@ impl DirtyRegion {
     }
 }
 
+/// Changed cells spanning the same columns on consecutive rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DirtyRect {
+    #[must_use]
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The columns covered.
+    #[must_use]
+    pub const fn cols(&self) -> Range<u32> {
+        self.x..self.x + self.width
+    }
+
+    /// The rows covered, top to bottom.
+    #[must_use]
+    pub const fn rows(&self) -> Range<u32> {
+        self.y..self.y + self.height
+    }
+}
+
+impl From<DirtyRegion> for DirtyRect {
+    fn from(region: DirtyRegion) -> Self {
+        Self::new(region.x, region.y, region.width, region.height)
+    }
+}
+
 /// Result of diffing two buffers.
 pub struct BufferDiff {
     /// List of changed cells (x, y).
     pub changed_cells: Vec<(u32, u32)>,
     /// Merged dirty regions.
     pub dirty_regions: Vec<DirtyRegion>,
+    /// The single-row runs of `dirty_regions`, merged down into rectangles
+    /// where consecutive rows change the same columns.
+    pub dirty_rects: Vec<DirtyRect>,
+    /// How many of the latest rectangles each run is checked against when
+    /// merging; runs with no match there start a rectangle of their own.
+    /// Zero disables merging.
+    pub rect_merge_window: usize,
     /// Total number of changed cells.
     pub change_count: usize,
 }
 
+impl Default for BufferDiff {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
 impl BufferDiff {
     /// Create a new empty diff with pre-allocated capacity.
     ///
@@ -62,6 +119,8 @@ impl BufferDiff {
         Self {
             changed_cells: Vec::with_capacity(expected_changes),
             dirty_regions: Vec::with_capacity(expected_changes / 4),
+            dirty_rects: Vec::with_capacity(expected_changes / 4),
+            rect_merge_window: DEFAULT_RECT_MERGE_WINDOW,
             change_count: 0,
         }
     }
@@ -70,6 +129,7 @@ impl BufferDiff {
     pub fn clear(&mut self) {
         self.changed_cells.clear();
         self.dirty_regions.clear();
+        self.dirty_rects.clear();
         self.change_count = 0;
     }
 }
@@ -138,11 +198,15 @@ impl BufferDiff {
         // integer comparison for colors instead of floating-point ops
         for y in 0..height {
             let row_offset = (y * width) as usize;
-            for x in 0..width {
+            // Cells past the end of a double-size row don't exist, and a row
+            // that changes size is redrawn whole.
+            let resized = old.row_size(y) != new.row_size(y);
+            let cols = if resized { width } else { new.row_width(y) };
+            for x in 0..cols {
                 let idx = row_offset + x as usize;
                 // SAFETY: We're iterating within bounds since we use width/height from old buffer
                 // and both buffers have the same dimensions (checked at start)
-                if !old_cells[idx].bits_eq(&new_cells[idx]) {
+                if resized || !old_cells[idx].bits_eq(&new_cells[idx]) {
                     self.changed_cells.push((x, y));
                 }
             }
@@ -150,6 +214,11 @@ impl BufferDiff {
 
         self.change_count = self.changed_cells.len();
         Self::merge_into_regions_reuse(&self.changed_cells, width, &mut self.dirty_regions);
+        Self::merge_into_rects(
+            &self.dirty_regions,
+            self.rect_merge_window,
+            &mut self.dirty_rects,
+        );
 
         Ok(())
     }
@@ -215,6 +284,23 @@ impl BufferDiff {
         }
     }
 
+    /// Merge single-row runs, sorted by row, into rectangles.
+    ///
+    /// A run extends the rectangle ending on the row above it with the same
+    /// columns, looked for among the latest `window` rectangles.
+    fn merge_into_rects(runs: &[DirtyRegion], window: usize, rects: &mut Vec<DirtyRect>) {
+        rects.clear();
+        for run in runs {
+            let above = rects.iter_mut().rev().take(window).find(|rect| {
+                rect.x == run.x && rect.width == run.width && rect.y + rect.height == run.y
+            });
+            match above {
+                Some(rect) => rect.height += 1,
+                None => rects.push(DirtyRect::from(*run)),
+            }
+        }
+    }
+
     /// Calculate if a full redraw is more efficient.
     #[must_use]
     pub fn should_full_redraw(&self, total_cells: usize) -> bool {
@@ -226,9 +312,10 @@ impl BufferDiff {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::buffer::RowSize;
     use crate::cell::Cell;
     use crate::color::Rgba;
-    use crate::style::Style;
+    use crate::style::{Style, UnderlineStyle};
 
     // ============================================
     // DirtyRegion Tests
@@ -419,6 +506,7 @@ mod tests {
             changed_cells: vec![(0, 0); 40], // 40% changed
             dirty_regions: vec![],
             change_count: 40,
+            ..BufferDiff::default()
         };
 
         assert!(!diff.should_full_redraw(total_cells));
@@ -431,6 +519,7 @@ mod tests {
             changed_cells: vec![(0, 0); 60], // 60% changed
             dirty_regions: vec![],
             change_count: 60,
+            ..BufferDiff::default()
         };
 
         assert!(diff.should_full_redraw(total_cells));
@@ -443,6 +532,7 @@ mod tests {
             changed_cells: vec![(0, 0); 50], // Exactly 50%
             dirty_regions: vec![],
             change_count: 50,
+            ..BufferDiff::default()
         };
 
         // At 50%, not > 50%, so should not trigger
@@ -456,6 +546,7 @@ mod tests {
             changed_cells: vec![(0, 0); 51], // 51% changed
             dirty_regions: vec![],
             change_count: 51,
+            ..BufferDiff::default()
         };
 
         assert!(diff.should_full_redraw(total_cells));
@@ -599,6 +690,23 @@ mod tests {
 
         let diff = BufferDiff::compute(&a, &b);
         assert_eq!(diff.change_count, 1);
+
+        // Flags above the packed link ID count too.
+        for attributes in [
+            TextAttributes::OVERLINE,
+            TextAttributes::SUPERSCRIPT,
+            TextAttributes::SUBSCRIPT,
+        ] {
+            let mut cell = cell_b;
+            cell.attributes |= attributes;
+            a.set(5, 5, cell_b);
+            b.set(5, 5, cell);
+            assert_eq!(
+                BufferDiff::compute(&a, &b).change_count,
+                1,
+                "{attributes:?}"
+            );
+        }
     }
 
     // ============================================
@@ -701,6 +809,28 @@ mod tests {
         assert_eq!(diff.change_count, 1);
     }
 
+    #[test]
+    fn test_buffer_diff_underline_changes() {
+        let curly = Style::NONE
+            .with_underline_style(UnderlineStyle::Curly)
+            .with_underline_color(Rgba::RED);
+        let mut a = OptimizedBuffer::new(10, 10);
+        let mut b = OptimizedBuffer::new(10, 10);
+        a.set(1, 1, Cell::new('A', curly));
+        b.set(1, 1, Cell::new('A', curly.with_underline_color(Rgba::BLUE)));
+        a.set(2, 2, Cell::new('A', curly));
+        b.set(
+            2,
+            2,
+            Cell::new('A', curly.with_underline_style(UnderlineStyle::Dashed)),
+        );
+        a.set(3, 3, Cell::new('A', curly));
+        b.set(3, 3, Cell::new('A', curly));
+
+        let diff = BufferDiff::compute(&a, &b);
+        assert_eq!(diff.change_count, 2);
+    }
+
     #[test]
     fn test_buffer_diff_alpha_change() {
         // Test that alpha channel changes are detected
@@ -904,4 +1034,96 @@ mod tests {
         let diff = BufferDiff::compute(&a, &b);
         assert_eq!(diff.change_count, 1);
     }
+
+    // ============================================
+    // BufferDiff Tests - Rectangles
+    // ============================================
+
+    fn fill(buffer: &mut OptimizedBuffer, x: u32, y: u32, width: u32, height: u32) {
+        buffer.fill_rect(x, y, width, height, Rgba::RED);
+    }
+
+    #[test]
+    fn test_dirty_rects_merge_block() {
+        let a = OptimizedBuffer::new(40, 20);
+        let mut b = OptimizedBuffer::new(40, 20);
+        fill(&mut b, 5, 3, 10, 10);
+        fill(&mut b, 30, 3, 4, 2);
+
+        let diff = BufferDiff::compute(&a, &b);
+        assert_eq!(diff.dirty_regions.len(), 12);
+        assert_eq!(
+            diff.dirty_rects,
+            [DirtyRect::new(5, 3, 10, 10), DirtyRect::new(30, 3, 4, 2)]
+        );
+        assert_eq!(diff.dirty_rects[0].rows(), 3..13);
+        assert_eq!(diff.dirty_rects[0].cols(), 5..15);
+    }
+
+    #[test]
+    fn test_dirty_rects_keep_l_shape_apart() {
+        let a = OptimizedBuffer::new(20, 10);
+        let mut b = OptimizedBuffer::new(20, 10);
+        fill(&mut b, 2, 1, 3, 5);
+        fill(&mut b, 2, 6, 8, 1);
+        fill(&mut b, 2, 7, 3, 2);
+
+        let diff = BufferDiff::compute(&a, &b);
+        assert_eq!(
+            diff.dirty_rects,
+            [
+                DirtyRect::new(2, 1, 3, 5),
+                DirtyRect::new(2, 6, 8, 1),
+                DirtyRect::new(2, 7, 3, 2),
+            ]
+        );
+        let covered: usize = diff
+            .dirty_rects
+            .iter()
+            .map(|rect| (rect.width * rect.height) as usize)
+            .sum();
+        assert_eq!(covered, diff.change_count);
+    }
+
+    #[test]
+    fn test_dirty_rects_merge_window() {
+        let a = OptimizedBuffer::new(20, 4);
+        let mut b = OptimizedBuffer::new(20, 4);
+        // Three runs a row: each run's rectangle is three back.
+        for x in [0, 5, 10] {
+            fill(&mut b, x, 0, 2, 4);
+        }
+
+        let mut diff = BufferDiff::default();
+        diff.compute_into(&a, &b);
+        assert_eq!(diff.dirty_rects.len(), 3);
+
+        diff.rect_merge_window = 2;
+        diff.compute_into(&a, &b);
+        assert_eq!(diff.dirty_rects.len(), 12);
+
+        diff.rect_merge_window = 0;
+        diff.compute_into(&a, &b);
+        let runs: Vec<DirtyRect> = diff.dirty_regions.iter().map(|&run| run.into()).collect();
+        assert_eq!(diff.dirty_rects, runs);
+
+        diff.clear();
+        assert!(diff.dirty_rects.is_empty());
+    }
+
+    #[test]
+    fn test_row_size_changes_redraw_the_whole_row() {
+        let mut a = OptimizedBuffer::new(8, 2);
+        let mut b = OptimizedBuffer::new(8, 2);
+        a.set_row_size(0, RowSize::DoubleWidth);
+        b.set_row_size(0, RowSize::DoubleWidth);
+        // Cells past a double row's half are never shown.
+        b.cells_mut()[6] = Cell::clear(Rgba::RED);
+        assert!(BufferDiff::compute(&a, &b).is_empty());
+
+        b.set_row_size(1, RowSize::DoubleHeightTop);
+        let diff = BufferDiff::compute(&a, &b);
+        assert_eq!(diff.change_count, 8);
+        assert!((0..8).all(|x| diff.changed_cells.contains(&(x, 1))));
+    }
 }
@@ -1,82 +1,117 @@
 //! Hit testing grid for mouse events.
+//!
+//! Hit areas are stored per row as sorted, non-overlapping spans of
+//! `(x_start, x_end, id)` rather than one slot per cell. A UI with a few
+//! dozen hit areas on a 400x120 terminal needs a few kilobytes instead of
+//! hundreds per grid, and the renderer keeps several grids.
+
+use std::mem::size_of;
+
+use super::Rect;
+
+/// Metadata for a hit area registered with
+/// [`Renderer::register_hit_region`](super::Renderer::register_hit_region).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HitRegion {
+    /// ID returned by [`Renderer::hit_test`](super::Renderer::hit_test).
+    pub id: u32,
+    /// Optional name, e.g. for debugging or routing.
+    pub name: Option<&'static str>,
+    /// Application payload, such as an index or a packed key.
+    pub data: u64,
+}
+
+impl HitRegion {
+    /// A region with just an ID, as registered by
+    /// [`Renderer::register_hit_area`](super::Renderer::register_hit_area).
+    #[must_use]
+    pub const fn new(id: u32) -> Self {
+        Self {
+            id,
+            name: None,
+            data: 0,
+        }
+    }
+}
+
+/// A hit region of the presented frame, as returned by
+/// [`Renderer::hit_test_region`](super::Renderer::hit_test_region).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HitRegionInfo {
+    pub id: u32,
+    pub name: Option<&'static str>,
+    pub data: u64,
+    /// The rectangle passed at registration, before hit-scissor clipping.
+    pub rect: Rect,
+}
+
+/// Cells `start..end` of a row map to `id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Span {
+    start: u32,
+    end: u32,
+    id: u32,
+}
 
 /// A hit testing grid that maps screen positions to widget IDs.
 #[derive(Clone, Debug)]
 pub struct HitGrid {
     width: u32,
     height: u32,
-    cells: Vec<Option<u32>>,
+    /// Spans of each row, sorted by `start`. Rows past the last one
+    /// registered are not allocated.
+    rows: Vec<Vec<Span>>,
 }
 
 impl HitGrid {
-    /// Upper bound on the number of cells actually backed by the `cells` buffer.
-    ///
-    /// A hit grid mirrors a terminal screen, so the realistic cell count is tiny
-    /// (e.g. 80x24 ~= 1,920 cells). Absurd dimensions — such as a bad
-    /// terminal-size query, a negative value sign-extended into a `u32`, or a
-    /// deliberately huge coordinate — must never translate into a multi-gigabyte
-    /// allocation. We allow generous headroom for very large/hi-DPI terminals
-    /// (16,384 x 16,384 = ~256M cells -> ~2 GiB worst case) while refusing to
-    /// allocate anything beyond that. Out-of-range coordinate queries still
-    /// return `None` because `cell_index` guards on `idx < cells.len()`.
-    const MAX_CELLS: usize = 16_384 * 16_384;
-
-    /// Compute the backing-buffer length for the requested dimensions.
+    /// Upper bound on the number of rows that can hold hit areas.
     ///
-    /// Uses checked/saturating arithmetic so a `width * height` product can never
-    /// overflow `usize`, and clamps the result to [`Self::MAX_CELLS`] so a garbage
-    /// or out-of-range dimension can never trigger a runaway allocation (the bug
-    /// where `HitGrid::new(u32::MAX / 2, 2)` requested 32 GiB and aborted).
-    #[inline]
-    fn backing_len(width: u32, height: u32) -> usize {
-        (width as usize)
-            .saturating_mul(height as usize)
-            .min(Self::MAX_CELLS)
-    }
+    /// A hit grid mirrors a terminal screen, so realistic heights are tiny.
+    /// Absurd dimensions — such as a bad terminal-size query, a negative
+    /// value sign-extended into a `u32`, or a deliberately huge coordinate —
+    /// must never translate into a runaway allocation. Rows beyond this
+    /// limit are never registered and hit tests there return `None`.
+    const MAX_ROWS: u32 = 16_384;
 
     /// Create a new hit grid with the given dimensions.
     ///
-    /// The reported dimensions are preserved exactly, but the backing buffer is
-    /// clamped to [`Self::MAX_CELLS`] so pathological dimensions cannot cause a
-    /// runaway allocation. Coordinate queries outside the backed region simply
-    /// return `None`.
+    /// Nothing is allocated until a hit area is registered.
     #[must_use]
-    pub fn new(width: u32, height: u32) -> Self {
+    pub const fn new(width: u32, height: u32) -> Self {
         Self {
             width,
             height,
-            cells: vec![None; Self::backing_len(width, height)],
-        }
-    }
-
-    /// Compute cell index with overflow protection.
-    #[inline]
-    fn cell_index(&self, x: u32, y: u32) -> Option<usize> {
-        if x >= self.width || y >= self.height {
-            return None;
-        }
-        let row_offset = (y as usize).checked_mul(self.width as usize)?;
-        let idx = row_offset.checked_add(x as usize)?;
-        if idx < self.cells.len() {
-            Some(idx)
-        } else {
-            None
+            rows: Vec::new(),
         }
     }
 
     /// Clear all hit areas.
+    ///
+    /// Row storage is kept for reuse by the next frame.
     pub fn clear(&mut self) {
-        self.cells.fill(None);
+        for row in &mut self.rows {
+            row.clear();
+        }
     }
 
     /// Register a hit area.
+    ///
+    /// Later registrations replace earlier ones where they overlap.
     pub fn register(&mut self, x: u32, y: u32, width: u32, height: u32, id: u32) {
-        for row in y..y.saturating_add(height).min(self.height) {
-            for col in x..x.saturating_add(width).min(self.width) {
-                if let Some(idx) = self.cell_index(col, row) {
-                    self.cells[idx] = Some(id);
-                }
-            }
+        let end = x.saturating_add(width).min(self.width);
+        let bottom = y
+            .saturating_add(height)
+            .min(self.height)
+            .min(Self::MAX_ROWS);
+        if x >= end || y >= bottom {
+            return;
+        }
+        if self.rows.len() < bottom as usize {
+            self.rows.resize_with(bottom as usize, Vec::new);
+        }
+        let span = Span { start: x, end, id };
+        for row in &mut self.rows[y as usize..bottom as usize] {
+            insert_span(row, span);
         }
     }
 
@@ -90,9 +125,17 @@ impl HitGrid {
         if self.width != overlay.width || self.height != overlay.height {
             return;
         }
-        for (dst, src) in self.cells.iter_mut().zip(&overlay.cells) {
-            if src.is_some() {
-                *dst = *src;
+        let used = overlay
+            .rows
+            .iter()
+            .rposition(|row| !row.is_empty())
+            .map_or(0, |last| last + 1);
+        if self.rows.len() < used {
+            self.rows.resize_with(used, Vec::new);
+        }
+        for (dst, src) in self.rows.iter_mut().zip(&overlay.rows[..used]) {
+            for &span in src {
+                insert_span(dst, span);
             }
         }
     }
@@ -100,16 +143,20 @@ impl HitGrid {
     /// Test which ID is at a position.
     #[must_use]
     pub fn test(&self, x: u32, y: u32) -> Option<u32> {
-        self.cell_index(x, y).and_then(|idx| self.cells[idx])
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let row = self.rows.get(y as usize)?;
+        let span = row.get(row.partition_point(|span| span.end <= x))?;
+        (span.start <= x).then_some(span.id)
     }
 
     /// Resize the grid, clearing all hit areas.
-    ///
-    /// Uses saturating multiplication to prevent overflow for extremely large dimensions.
     pub fn resize(&mut self, width: u32, height: u32) {
         self.width = width;
         self.height = height;
-        self.cells = vec![None; Self::backing_len(width, height)];
+        self.rows.truncate(height.min(Self::MAX_ROWS) as usize);
+        self.clear();
     }
 
     /// Get dimensions.
@@ -118,13 +165,39 @@ impl HitGrid {
         (self.width, self.height)
     }
 
-    /// Estimated byte size of the hit grid storage.
+    /// Bytes allocated for the hit grid storage, including capacity kept
+    /// for reuse.
     #[must_use]
     pub fn byte_size(&self) -> usize {
-        self.cells.len() * std::mem::size_of::<Option<u32>>()
+        let spans: usize = self.rows.iter().map(Vec::capacity).sum();
+        self.rows.capacity() * size_of::<Vec<Span>>() + spans * size_of::<Span>()
     }
 }
 
+/// Insert `span` into a sorted row, trimming or splitting the spans it
+/// overlaps.
+fn insert_span(row: &mut Vec<Span>, span: Span) {
+    let first = row.partition_point(|old| old.end <= span.start);
+    let last = row.partition_point(|old| old.start < span.end);
+    if first == last {
+        row.insert(first, span);
+        return;
+    }
+    let head = Some(row[first])
+        .filter(|old| old.start < span.start)
+        .map(|old| Span {
+            end: span.start,
+            ..old
+        });
+    let tail = Some(row[last - 1])
+        .filter(|old| old.end > span.end)
+        .map(|old| Span {
+            start: span.end,
+            ..old
+        });
+    row.splice(first..last, [head, Some(span), tail].into_iter().flatten());
+}
+
 impl Default for HitGrid {
     fn default() -> Self {
         Self::new(80, 24)
@@ -352,9 +425,117 @@ mod tests {
 
     #[test]
     fn test_hit_grid_byte_size() {
-        let grid = HitGrid::new(100, 50);
-        let expected = 100 * 50 * std::mem::size_of::<Option<u32>>();
-        assert_eq!(grid.byte_size(), expected);
+        let mut grid = HitGrid::new(100, 50);
+        assert_eq!(grid.byte_size(), 0);
+
+        grid.register(0, 10, 20, 2, 1);
+        assert!(grid.byte_size() >= 11 * size_of::<Vec<Span>>() + 2 * size_of::<Span>());
+    }
+
+    #[test]
+    fn test_hit_grid_large_terminal_footprint() {
+        // A 400x120 UI with a header, a sidebar of 30 items, a 4x4 grid of
+        // cards, and a status bar: 48 hit areas.
+        let (width, height) = (400, 120);
+        let mut grid = HitGrid::new(width, height);
+        grid.register(0, 0, width, 1, 1);
+        for i in 0..30 {
+            grid.register(0, 2 + i * 3, 40, 2, 10 + i);
+        }
+        for i in 0..16 {
+            grid.register(45 + (i % 4) * 88, 2 + (i / 4) * 28, 80, 24, 100 + i);
+        }
+        grid.register(0, height - 1, width, 1, 2);
+
+        let dense = (width * height) as usize * size_of::<Option<u32>>();
+        assert!(
+            grid.byte_size() * 20 < dense,
+            "sparse {} bytes vs dense {dense} bytes",
+            grid.byte_size()
+        );
+        assert_eq!(grid.test(10, 5), Some(11));
+        assert_eq!(grid.test(50, 5), Some(100));
+        assert_eq!(grid.test(399, 119), Some(2));
+    }
+
+    #[test]
+    fn test_hit_grid_register_splits_spans() {
+        let mut grid = HitGrid::new(100, 1);
+        grid.register(0, 0, 30, 1, 1);
+        grid.register(10, 0, 5, 1, 2);
+
+        assert_eq!(
+            grid.rows[0],
+            [
+                Span {
+                    start: 0,
+                    end: 10,
+                    id: 1
+                },
+                Span {
+                    start: 10,
+                    end: 15,
+                    id: 2
+                },
+                Span {
+                    start: 15,
+                    end: 30,
+                    id: 1
+                },
+            ]
+        );
+
+        // Covering several spans replaces them and trims the edges.
+        grid.register(5, 0, 20, 1, 3);
+        assert_eq!(
+            grid.rows[0],
+            [
+                Span {
+                    start: 0,
+                    end: 5,
+                    id: 1
+                },
+                Span {
+                    start: 5,
+                    end: 25,
+                    id: 3
+                },
+                Span {
+                    start: 25,
+                    end: 30,
+                    id: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hit_grid_overlay_merges_spans() {
+        let mut base = HitGrid::new(50, 5);
+        base.register(0, 0, 50, 5, 1);
+        let mut layer = HitGrid::new(50, 5);
+        layer.register(10, 1, 10, 2, 2);
+
+        base.overlay(&layer);
+        assert_eq!(base.test(5, 1), Some(1));
+        assert_eq!(base.test(15, 1), Some(2));
+        assert_eq!(base.test(15, 3), Some(1));
+        assert_eq!(base.rows[2].len(), 3);
+
+        // Mismatched sizes are ignored.
+        base.overlay(&HitGrid::new(10, 10));
+        assert_eq!(base.test(15, 1), Some(2));
+    }
+
+    #[test]
+    fn test_hit_grid_clear_keeps_row_storage() {
+        let mut grid = HitGrid::new(80, 24);
+        grid.register(0, 0, 80, 24, 1);
+        let size = grid.byte_size();
+
+        grid.clear();
+        assert_eq!(grid.test(0, 0), None);
+        assert_eq!(grid.byte_size(), size);
     }
 
     #[test]
@@ -481,18 +662,12 @@ mod tests {
 
     #[test]
     fn test_hit_grid_pathological_dimensions_do_not_over_allocate() {
-        // Regression: HitGrid::new(u32::MAX / 2, 2) used to compute a backing
-        // buffer of (2^31 - 1) * 2 = 4_294_967_294 cells. With Option<u32>
-        // (8 bytes) that is exactly 34_359_738_352 bytes (32 GiB), aborting the
-        // process via handle_alloc_error in the CI Coverage job.
-        //
-        // The backing buffer must now be clamped to MAX_CELLS regardless of the
-        // requested dimensions, while still reporting the requested size and
-        // returning None for out-of-range coordinate queries.
-        let cell_bytes = std::mem::size_of::<Option<u32>>();
-        let max_bytes = HitGrid::MAX_CELLS.saturating_mul(cell_bytes);
-
-        // Each of these would have requested >= ~32 GiB before the fix.
+        // Regression: HitGrid::new(u32::MAX / 2, 2) used to allocate a dense
+        // buffer for every cell and abort with a 32 GiB request. Storage is
+        // now sized by what is registered, and rows are capped at MAX_ROWS.
+        let max_bytes = HitGrid::MAX_ROWS as usize * size_of::<Vec<Span>>()
+            + HitGrid::MAX_ROWS as usize * 4 * size_of::<Span>();
+
         for (w, h) in [
             (u32::MAX / 2, 2),
             (2, u32::MAX / 2),
@@ -500,31 +675,31 @@ mod tests {
             (u32::MAX, 1),
             (1, u32::MAX),
         ] {
-            let grid = HitGrid::new(w, h);
+            let mut grid = HitGrid::new(w, h);
             // Reported dimensions are preserved exactly.
             assert_eq!(grid.size(), (w, h));
-            // The actual allocation is clamped to a sane bound, never the
-            // multi-gigabyte product of the requested dimensions.
+            assert_eq!(grid.byte_size(), 0);
+
+            grid.register(0, 0, w, h, 1);
             assert!(
                 grid.byte_size() <= max_bytes,
                 "byte_size {} exceeded clamp {} for dims {w}x{h}",
                 grid.byte_size(),
                 max_bytes
             );
-            // Out-of-range coordinate queries still return None gracefully.
+            assert_eq!(grid.test(0, 0), Some(1));
             assert_eq!(grid.test(u32::MAX, u32::MAX), None);
-            assert_eq!(grid.test(w.saturating_sub(1), h.saturating_sub(1)), None);
+            // The last cell is only backed when the height is within MAX_ROWS.
+            let last = (h <= HitGrid::MAX_ROWS).then_some(1);
+            assert_eq!(grid.test(w.saturating_sub(1), h.saturating_sub(1)), last);
         }
 
         // resize() must apply the same clamp.
         let mut grid = HitGrid::new(10, 10);
         grid.resize(u32::MAX / 2, 2);
         assert_eq!(grid.size(), (u32::MAX / 2, 2));
+        grid.register(0, 0, u32::MAX, u32::MAX, 1);
         assert!(grid.byte_size() <= max_bytes);
-
-        // Sanity: realistic terminal sizes are unaffected and fully backed.
-        let normal = HitGrid::new(80, 24);
-        assert_eq!(normal.byte_size(), 80 * 24 * cell_bytes);
     }
 
     #[test]
@@ -45,30 +45,55 @@
 //!
 //! The renderer includes a hit grid for mouse interaction. Register clickable
 //! areas with [`register_hit_area`](Renderer::register_hit_area) and query
-//! them with [`hit_test`](Renderer::hit_test).
+//! them with [`hit_test`](Renderer::hit_test). To carry a name and payload
+//! with an area, use [`register_hit_region`](Renderer::register_hit_region)
+//! and [`hit_test_region`](Renderer::hit_test_region).
 
+mod arena;
+mod degradation;
 mod diff;
 mod hitgrid;
+mod output;
+mod pacing;
 mod threaded;
+mod validate;
 
-pub use diff::BufferDiff;
-pub use hitgrid::HitGrid;
+pub use arena::FrameArena;
+pub use degradation::Degradation;
+pub use diff::{BufferDiff, DirtyRect};
+pub use hitgrid::{HitGrid, HitRegion, HitRegionInfo};
+pub use pacing::FrameTiming;
 pub use threaded::{ThreadedRenderStats, ThreadedRenderer};
-
-use crate::ansi::AnsiWriter;
-use crate::buffer::{BoxOptions, BoxStyle, ClipRect, OptimizedBuffer, ScissorStack, TitleAlign};
-use crate::color::Rgba;
+pub use validate::{FrameIssue, FrameIssueKind, ValidationLevel};
+
+use crate::ansi::sixel::{self, SixelOptions};
+use crate::ansi::{AnsiWriter, AnsiWriterMetrics, ColorFallbacks, ColorMode, LineDrawing};
+use crate::buffer::{
+    BoxOptions, BoxStyle, ClipRect, Opacity, OptimizedBuffer, PixelBuffer, RowSize, ScissorStack,
+    TitleAlign,
+};
+use crate::cell::Cell;
+use crate::color::{self, BlendMode, Rgba};
+use crate::error::{Error, RenderPhase, Result};
+use crate::event::{EventBus, RendererEvent};
+use crate::grapheme_pool::GraphemePool;
 use crate::link::LinkPool;
+use crate::replay::{RecordOptions, Recorder, RecordingSummary};
 use crate::terminal::{CursorStyle, Terminal};
+use output::Output;
+use pacing::FramePacer;
 use std::collections::BTreeMap;
 use std::collections::btree_map::Entry;
-use std::io::{self, Stdout, Write};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Renderer configuration options.
 ///
 /// These options control terminal setup behavior when creating a [`Renderer`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct RendererOptions {
     /// Use the alternate screen buffer.
     pub use_alt_screen: bool,
@@ -78,6 +103,12 @@ pub struct RendererOptions {
     pub enable_mouse: bool,
     /// Query terminal capabilities on startup.
     pub query_capabilities: bool,
+    /// Capabilities to assume instead of detecting and querying them, such
+    /// as [`Capabilities::minimal`](crate::terminal::Capabilities::minimal).
+    ///
+    /// They decide whether mouse tracking is enabled, whether frames use
+    /// synchronized output, and the color mode.
+    pub capabilities: Option<crate::terminal::Capabilities>,
 }
 
 impl Default for RendererOptions {
@@ -87,10 +118,32 @@ impl Default for RendererOptions {
             hide_cursor: true,
             enable_mouse: true,
             query_capabilities: true,
+            capabilities: None,
         }
     }
 }
 
+/// What [`Renderer::cleanup`] does with the last frame when rendering on
+/// the main screen.
+///
+/// Without the alternate screen, frames are drawn from the top of the
+/// visible screen and stay there after exit. Policies other than
+/// [`Unchanged`](Self::Unchanged) work from the lowest row any presented
+/// frame has drawn on (cells that are not blank); with the alternate screen
+/// they do nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExitPolicy {
+    /// Leave the cursor wherever the last frame left it.
+    #[default]
+    Unchanged,
+    /// Keep the last frame visible and move the cursor to the line below
+    /// it, so the shell prompt continues under the UI instead of over it.
+    LeaveLastFrame,
+    /// Erase the rows the UI drew on and move the cursor to the top of
+    /// them.
+    ClearOnExit,
+}
+
 /// Rendering statistics.
 #[derive(Clone, Debug, Default)]
 pub struct RenderStats {
@@ -100,7 +153,38 @@ pub struct RenderStats {
     pub fps: f32,
     pub buffer_bytes: usize,
     pub hitgrid_bytes: usize,
+    /// Memory held by [`Renderer::cached_panel`] buffers.
+    pub panel_cache_bytes: usize,
+    /// Memory held by the [frame arena](Renderer::arena).
+    pub arena_bytes: usize,
+    /// Most bytes of frame arena strings any one frame has used.
+    pub arena_peak_bytes: usize,
     pub total_bytes: usize,
+    /// Output written for the last frame, by kind. Only collected while the
+    /// debug overlay is enabled.
+    pub last_frame_output: AnsiWriterMetrics,
+}
+
+/// Identifies a present hook for [`Renderer::remove_present_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+type BeforePresentHook = Box<dyn FnMut(&mut OptimizedBuffer)>;
+type AfterPresentHook = Box<dyn FnMut(&RenderStats)>;
+
+/// Run a hook, turning a panic into an error naming `stage`.
+fn run_hook(stage: &str, hook: impl FnOnce()) -> Result<()> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(hook)).map_err(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Error::Render {
+            phase: RenderPhase::Hook,
+            source: io::Error::other(format!("{stage} hook panicked: {reason}")),
+        }
+    })
 }
 
 /// Rectangle with unsigned coordinates for dirty-region tracking.
@@ -134,6 +218,10 @@ impl Rect {
         self.x.saturating_add(self.width)
     }
 
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.max_x() && y >= self.y && y < self.max_y()
+    }
+
     fn max_y(&self) -> u32 {
         self.y.saturating_add(self.height)
     }
@@ -177,6 +265,31 @@ impl Rect {
     }
 }
 
+/// Default size of a terminal cell in pixels, used to place sixel images.
+pub const DEFAULT_CELL_PIXEL_SIZE: (u32, u32) = (10, 20);
+
+/// A sixel image placed for one frame.
+#[derive(Clone, Debug)]
+struct PlacedImage {
+    /// Cells the image covers.
+    rect: Rect,
+    pixels: PixelBuffer,
+    sixel: Arc<[u8]>,
+}
+
+impl PlacedImage {
+    fn same_as(&self, other: &Self) -> bool {
+        self.rect == other.rect && Arc::ptr_eq(&self.sixel, &other.sixel)
+    }
+}
+
+/// An offscreen panel kept across frames by [`Renderer::cached_panel`].
+struct CachedPanel {
+    /// Version the buffer was last rendered at.
+    version: u64,
+    buffer: OptimizedBuffer,
+}
+
 /// CLI renderer with double buffering.
 ///
 /// The renderer is the main entry point for terminal rendering. It manages:
@@ -202,79 +315,183 @@ pub struct Renderer {
     front_buffer: OptimizedBuffer,
     back_buffer: OptimizedBuffer,
 
-    terminal: Terminal<Stdout>,
+    terminal: Terminal<Output>,
     /// Hit areas for the last presented frame (used by `hit_test`).
     front_hit_grid: HitGrid,
     /// Hit areas being built for the next frame (populated by `register_hit_area`).
     back_hit_grid: HitGrid,
+    /// Regions of the presented frame; hit grids store indices into these.
+    front_hit_regions: Vec<HitRegionInfo>,
+    /// Regions registered for the next frame.
+    back_hit_regions: Vec<HitRegionInfo>,
     layer_hit_grids: BTreeMap<u16, HitGrid>,
     hit_scissor: ScissorStack,
     link_pool: LinkPool,
+    /// Link references held by text drawn into the back buffer.
+    back_links: Vec<u32>,
+    /// Link references held by the presented front buffer.
+    front_links: Vec<u32>,
     grapheme_pool: crate::grapheme_pool::GraphemePool,
     scratch_buffer: Vec<u8>,
+    /// Strings for the frame being drawn, reset on present.
+    arena: FrameArena,
     /// Reusable diff to avoid per-frame allocation.
     cached_diff: BufferDiff,
+    exit_policy: ExitPolicy,
+    /// Lowest row with non-blank cells in any frame presented on the main
+    /// screen, tracked while an exit policy needs it.
+    max_used_row: Option<u32>,
+    /// Mutation count of the back buffer right after the renderer cleared
+    /// it, and the color it was cleared to.
+    back_blank: Option<(u64, Rgba)>,
+    /// Color the presented front buffer was cleared to, if nothing was drawn
+    /// over it.
+    front_blank: Option<Rgba>,
     manual_dirty_regions: Vec<Rect>,
 
+    panels: BTreeMap<String, CachedPanel>,
     layers: BTreeMap<u16, OptimizedBuffer>,
+    layer_blend_modes: BTreeMap<u16, BlendMode>,
+    layer_opacities: BTreeMap<u16, Opacity>,
     active_hit_layer: u16,
     layers_dirty: bool,
 
+    /// Sixel images drawn for the next frame.
+    images: Vec<PlacedImage>,
+    /// Sixel images on screen from the last presented frame.
+    front_images: Vec<PlacedImage>,
+    cell_pixel_size: (u32, u32),
+
     background: Rgba,
+    /// The terminal's default background, when known.
+    terminal_background: Option<Rgba>,
+    color_mode: ColorMode,
+    color_fallbacks: Option<Arc<ColorFallbacks>>,
     force_redraw: bool,
+    validation: ValidationLevel,
     stats: RenderStats,
     last_present_at: Instant,
+    pacer: FramePacer,
+    recorder: Option<Recorder>,
     show_debug_overlay: bool,
     debug_overlay_position: (u32, u32),
+    event_bus: Option<EventBus>,
+    before_present_hooks: Vec<(HookId, BeforePresentHook)>,
+    after_present_hooks: Vec<(HookId, AfterPresentHook)>,
+    next_hook_id: u64,
+    /// Set while `present` runs, so hooks cannot re-enter it.
+    presenting: bool,
+    /// Cleared once the output is found closed; nothing is written after.
+    alive: bool,
 }
 
 impl Renderer {
     /// Create a new renderer with the given dimensions.
-    pub fn new(width: u32, height: u32) -> io::Result<Self> {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
         Self::new_with_options(width, height, RendererOptions::default())
     }
 
     /// Create a new renderer with custom options.
-    pub fn new_with_options(width: u32, height: u32, options: RendererOptions) -> io::Result<Self> {
-        let mut terminal = Terminal::new(io::stdout());
+    pub fn new_with_options(width: u32, height: u32, options: RendererOptions) -> Result<Self> {
+        Self::with_output(width, height, options, Box::new(io::stdout()))
+    }
+
+    /// Create a renderer that writes to `output` instead of stdout.
+    ///
+    /// Useful for tests, recording sessions, or terminals reached over
+    /// another channel.
+    pub fn with_writer(
+        width: u32,
+        height: u32,
+        options: RendererOptions,
+        output: impl Write + 'static,
+    ) -> Result<Self> {
+        Self::with_output(width, height, options, Box::new(output))
+    }
+
+    pub(crate) fn with_output(
+        width: u32,
+        height: u32,
+        options: RendererOptions,
+        output: Box<dyn Write>,
+    ) -> Result<Self> {
+        let forced = options.capabilities.is_some();
+        let mut terminal = match options.capabilities {
+            Some(capabilities) => Terminal::with_capabilities(Output::new(output), capabilities),
+            None => Terminal::new(Output::new(output)),
+        };
+        terminal.set_size(width, height);
         if options.use_alt_screen {
             terminal.enter_alt_screen()?;
         }
         if options.hide_cursor {
             terminal.hide_cursor()?;
         }
-        if options.enable_mouse {
+        if options.enable_mouse && (!forced || terminal.capabilities().mouse) {
             terminal.enable_mouse()?;
         }
-        if options.query_capabilities {
+        if options.query_capabilities && !forced {
             terminal.query_capabilities()?;
         }
+        let color_mode = if forced {
+            ColorMode::from(terminal.capabilities().color)
+        } else {
+            ColorMode::TrueColor
+        };
 
         let total_cells = (width as usize).saturating_mul(height as usize);
+        let grapheme_pool = GraphemePool::new();
         Ok(Self {
             width,
             height,
-            front_buffer: OptimizedBuffer::new(width, height),
-            back_buffer: OptimizedBuffer::new(width, height),
+            front_buffer: OptimizedBuffer::new(width, height).with_grapheme_pool(&grapheme_pool),
+            back_buffer: OptimizedBuffer::new(width, height).with_grapheme_pool(&grapheme_pool),
             terminal,
             front_hit_grid: HitGrid::new(width, height),
             back_hit_grid: HitGrid::new(width, height),
+            front_hit_regions: Vec::new(),
+            back_hit_regions: Vec::new(),
             layer_hit_grids: BTreeMap::new(),
             hit_scissor: ScissorStack::new(),
             link_pool: LinkPool::new(),
-            grapheme_pool: crate::grapheme_pool::GraphemePool::new(),
+            back_links: Vec::new(),
+            front_links: Vec::new(),
+            grapheme_pool,
             scratch_buffer: Vec::with_capacity(total_cells.saturating_mul(20)),
+            arena: FrameArena::new(),
             cached_diff: BufferDiff::with_capacity(total_cells / 8),
+            exit_policy: ExitPolicy::Unchanged,
+            max_used_row: None,
+            back_blank: None,
+            front_blank: None,
             manual_dirty_regions: Vec::new(),
+            panels: BTreeMap::new(),
             layers: BTreeMap::new(),
+            layer_blend_modes: BTreeMap::new(),
+            layer_opacities: BTreeMap::new(),
             active_hit_layer: 0,
             layers_dirty: false,
+            images: Vec::new(),
+            front_images: Vec::new(),
+            cell_pixel_size: DEFAULT_CELL_PIXEL_SIZE,
             background: Rgba::BLACK,
+            terminal_background: None,
+            color_mode,
+            color_fallbacks: None,
             force_redraw: true,
+            validation: ValidationLevel::Off,
             stats: RenderStats::default(),
             last_present_at: Instant::now(),
+            pacer: FramePacer::default(),
+            recorder: None,
             show_debug_overlay: false,
             debug_overlay_position: (0, 0),
+            event_bus: None,
+            before_present_hooks: Vec::new(),
+            after_present_hooks: Vec::new(),
+            next_hook_id: 0,
+            presenting: false,
+            alive: true,
         })
     }
 
@@ -290,6 +507,11 @@ impl Renderer {
     }
 
     /// Get the back buffer with the grapheme pool for pool-aware drawing.
+    ///
+    /// The back buffer is bound to this pool: buffers drawn with another
+    /// pool must be blitted with
+    /// [`OptimizedBuffer::draw_buffer_from_pool`], or their graphemes are
+    /// dropped to blanks.
     pub fn buffer_with_pool(
         &mut self,
     ) -> (
@@ -299,12 +521,65 @@ impl Renderer {
         (&mut self.back_buffer, &mut self.grapheme_pool)
     }
 
+    /// Get the arena for strings that only need to last until the frame is
+    /// presented, such as formatted labels.
+    ///
+    /// Strings from the arena keep the renderer borrowed; to draw them, use
+    /// [`buffer_with_arena`](Self::buffer_with_arena).
+    pub fn arena(&mut self) -> &FrameArena {
+        &self.arena
+    }
+
+    /// Get the back buffer with the frame arena, to draw arena strings.
+    ///
+    /// ```no_run
+    /// # use opentui_rust::{Renderer, Style};
+    /// # fn frame(renderer: &mut Renderer, count: usize) {
+    /// let (buffer, arena) = renderer.buffer_with_arena();
+    /// let label = arena.format(format_args!("{count} files"));
+    /// buffer.draw_text(0, 0, label, Style::NONE);
+    /// # }
+    /// ```
+    pub fn buffer_with_arena(&mut self) -> (&mut OptimizedBuffer, &FrameArena) {
+        (&mut self.back_buffer, &self.arena)
+    }
+
+    /// Cap the size the frame arena's chunks grow to.
+    pub fn set_arena_max_chunk_size(&mut self, bytes: usize) {
+        self.arena.set_max_chunk_size(bytes);
+    }
+
+    /// Set how far back the diff looks for a rectangle to extend with each
+    /// changed run; see [`BufferDiff::rect_merge_window`].
+    pub fn set_rect_merge_window(&mut self, window: usize) {
+        self.cached_diff.rect_merge_window = window;
+    }
+
     /// Get the front buffer (current display state).
     #[must_use]
     pub fn front_buffer(&self) -> &OptimizedBuffer {
         &self.front_buffer
     }
 
+    /// Publish [`RendererEvent`]s on `bus`, and have the terminal publish
+    /// its [`TerminalEvent`](crate::event::TerminalEvent)s there too.
+    pub fn set_event_bus(&mut self, bus: EventBus) {
+        self.terminal.set_event_bus(bus.clone());
+        self.event_bus = Some(bus);
+    }
+
+    /// The bus this renderer publishes to, if any.
+    #[must_use]
+    pub fn event_bus(&self) -> Option<&EventBus> {
+        self.event_bus.as_ref()
+    }
+
+    fn publish(&self, event: RendererEvent) {
+        if let Some(bus) = &self.event_bus {
+            bus.publish(&event);
+        }
+    }
+
     /// Get rendering stats.
     #[must_use]
     pub fn stats(&self) -> &RenderStats {
@@ -332,6 +607,49 @@ impl Renderer {
         &mut self.link_pool
     }
 
+    /// Draw hyperlinked text into the back buffer and make it clickable.
+    ///
+    /// The URL is interned in the link pool and applied to every drawn cell,
+    /// and a hit area covering the text's display width is registered. The
+    /// returned id is the link id, which serves as both the hit-area id and
+    /// the key for [`LinkPool::get`]; drawing the same URL twice yields the
+    /// same id. The link reference is released when the frame is cleared or
+    /// replaced by the next presented frame.
+    pub fn draw_link(
+        &mut self,
+        x: u32,
+        y: u32,
+        text: &str,
+        style: crate::style::Style,
+        url: &str,
+    ) -> u32 {
+        let id = self.link_pool.intern(url);
+        self.back_links.push(id);
+        self.back_buffer.draw_text_with_pool(
+            &mut self.grapheme_pool,
+            x,
+            y,
+            text,
+            style.with_link(id),
+        );
+        let width = crate::unicode::display_width(text) as u32;
+        self.register_hit_area(x, y, width, 1, id);
+        id
+    }
+
+    /// URL of the hyperlink shown at a cell of the presented frame, if any.
+    #[must_use]
+    pub fn link_at(&self, x: u32, y: u32) -> Option<&str> {
+        let id = self.front_buffer.get(x, y)?.attributes.link_id()?;
+        self.link_pool.get(id)
+    }
+
+    fn release_links(pool: &mut LinkPool, links: &mut Vec<u32>) {
+        for id in links.drain(..) {
+            pool.decref(id);
+        }
+    }
+
     /// Get a mutable reference to the grapheme pool.
     ///
     /// The grapheme pool stores multi-codepoint grapheme clusters (emoji, ZWJ sequences)
@@ -346,6 +664,19 @@ impl Renderer {
         &self.grapheme_pool
     }
 
+    /// Cross-check the grapheme pool's refcounts against every buffer the
+    /// renderer keeps; see [`GraphemePool::verify_against`].
+    #[cfg(feature = "pool-audit")]
+    #[must_use]
+    pub fn audit_grapheme_pool(&self) -> Vec<crate::grapheme_pool::AuditIssue> {
+        let buffers: Vec<&OptimizedBuffer> = [&self.front_buffer, &self.back_buffer]
+            .into_iter()
+            .chain(self.layers.values())
+            .chain(self.panels.values().map(|panel| &panel.buffer))
+            .collect();
+        self.grapheme_pool.verify_against(&buffers)
+    }
+
     /// Get detected terminal capabilities.
     ///
     /// Capabilities include color support level, hyperlink support,
@@ -391,6 +722,72 @@ impl Renderer {
         self.background = color;
     }
 
+    /// Set the terminal's default background color, usually from the reply
+    /// to [`Terminal::query_background_color`](crate::terminal::Terminal::query_background_color).
+    ///
+    /// While set, blank cells with exactly this background are erased with
+    /// `CSI K` or `CSI X` instead of written as spaces.
+    pub fn set_terminal_background(&mut self, color: Option<Rgba>) {
+        self.terminal_background = color;
+    }
+
+    /// The terminal's default background color, if known.
+    #[must_use]
+    pub fn terminal_background(&self) -> Option<Rgba> {
+        self.terminal_background
+    }
+
+    /// Set the color output mode. Defaults to [`ColorMode::TrueColor`].
+    ///
+    /// Use `ColorMode::from(renderer.capabilities().color)` to follow the
+    /// detected terminal support. Forces a full redraw.
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        if self.color_mode != mode {
+            self.color_mode = mode;
+            self.force_redraw = true;
+        }
+    }
+
+    /// Current color output mode.
+    #[must_use]
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// The fallbacks output currently applies for the [color
+    /// mode](Self::color_mode) and what the [terminal
+    /// supports](Self::capabilities), for diagnostics alongside
+    /// [`Capabilities::report`](crate::terminal::Capabilities::report).
+    #[must_use]
+    pub fn degradations(&self) -> Vec<Degradation> {
+        let caps = self.terminal.capabilities();
+        let mut degradations = Vec::new();
+        if self.color_mode != ColorMode::TrueColor {
+            degradations.push(Degradation::ColorQuantized {
+                mode: self.color_mode,
+            });
+        }
+        if !caps.sync_output {
+            degradations.push(Degradation::SyncOutputDisabled);
+        }
+        let charset = caps.line_drawing();
+        if charset != LineDrawing::Unicode {
+            degradations.push(Degradation::LineDrawingFallback { charset });
+        }
+        degradations
+    }
+
+    /// Set precomputed palette fallbacks, usually from
+    /// [`Theme::derive_fallbacks`](crate::highlight::Theme::derive_fallbacks).
+    ///
+    /// In 256-color and 16-color modes, colors they cover are written with
+    /// the precomputed index instead of being quantized per cell. Forces a
+    /// full redraw.
+    pub fn set_color_fallbacks(&mut self, fallbacks: Option<ColorFallbacks>) {
+        self.color_fallbacks = fallbacks.map(Arc::new);
+        self.force_redraw = true;
+    }
+
     /// Mark a rectangular region as dirty for the next present.
     ///
     /// This is useful when you know only a portion of the screen needs to be
@@ -413,12 +810,165 @@ impl Renderer {
         self.manual_dirty_regions.push(rect);
     }
 
+    /// Set the size of a terminal cell in pixels, used to work out which
+    /// cells a sixel image covers. Defaults to [`DEFAULT_CELL_PIXEL_SIZE`].
+    pub fn set_cell_pixel_size(&mut self, width: u32, height: u32) {
+        self.cell_pixel_size = (width.max(1), height.max(1));
+    }
+
+    /// Size of a terminal cell in pixels.
+    #[must_use]
+    pub fn cell_pixel_size(&self) -> (u32, u32) {
+        self.cell_pixel_size
+    }
+
+    /// Draw an image with its top-left corner at cell (`x`, `y`) as sixel
+    /// graphics.
+    ///
+    /// Like cell drawing, an image lasts one frame: draw it before every
+    /// [`present`](Self::present) that should show it. It covers as many
+    /// cells as its pixels fill (see [`Self::set_cell_pixel_size`]) and is
+    /// cropped to the screen. Covered cells are left to the image rather than
+    /// written, and an image unchanged since the last frame is not re-sent.
+    ///
+    /// Returns `false`, drawing nothing, when the terminal lacks
+    /// [`Capabilities::sixel`](crate::terminal::Capabilities::sixel); draw a
+    /// cell-based fallback such as
+    /// [`OptimizedBuffer::draw_supersample_buffer`] instead.
+    pub fn draw_image(&mut self, x: u32, y: u32, image: &PixelBuffer) -> bool {
+        if !self.terminal.capabilities().sixel {
+            return false;
+        }
+        if x >= self.width || y >= self.height {
+            return true;
+        }
+
+        let (cell_width, cell_height) = self.cell_pixel_size;
+        let pixel_width = image.width.min((self.width - x) * cell_width);
+        let pixel_height = image.height.min((self.height - y) * cell_height);
+        let rect = Rect::new(
+            x,
+            y,
+            pixel_width.div_ceil(cell_width),
+            pixel_height.div_ceil(cell_height),
+        );
+        if rect.is_empty() {
+            return true;
+        }
+        let pixels = if (pixel_width, pixel_height) == (image.width, image.height) {
+            image.clone()
+        } else {
+            crop(image, pixel_width, pixel_height)
+        };
+
+        // Reuse the encoding of an identical image from the last frame.
+        let sixel = self
+            .front_images
+            .iter()
+            .find(|placed| placed.rect == rect && same_pixels(&placed.pixels, &pixels))
+            .map_or_else(
+                || Arc::from(sixel::encode(&pixels, SixelOptions::default())),
+                |placed| placed.sixel.clone(),
+            );
+        self.images.push(PlacedImage {
+            rect,
+            pixels,
+            sixel,
+        });
+        true
+    }
+
     /// Get the currently tracked dirty regions.
     #[must_use]
     pub fn get_dirty_regions(&self) -> &[Rect] {
         &self.manual_dirty_regions
     }
 
+    /// Draw a panel that only needs redrawing when `version` changes.
+    ///
+    /// The panel is rendered by `render_fn` into an offscreen buffer the size
+    /// of `rect`, kept under `key`, and then drawn into the back buffer at
+    /// `rect`. While later calls pass the same key, version, and size, the
+    /// cached buffer is drawn again without calling `render_fn`. The buffer
+    /// starts out transparent, so cells the panel leaves empty show the back
+    /// buffer through.
+    ///
+    /// `render_fn` also gets the grapheme pool, as with
+    /// [`buffer_with_pool`](Self::buffer_with_pool).
+    pub fn cached_panel<F>(&mut self, key: &str, version: u64, rect: Rect, render_fn: F)
+    where
+        F: FnOnce(&mut OptimizedBuffer, &mut GraphemePool),
+    {
+        if rect.is_empty() {
+            return;
+        }
+        if !self.panels.contains_key(key) {
+            let buffer = OptimizedBuffer::new(rect.width, rect.height)
+                .with_grapheme_pool(&self.grapheme_pool);
+            self.panels.insert(
+                key.to_string(),
+                CachedPanel {
+                    // Never matches a caller's version before the first draw.
+                    version: version.wrapping_add(1),
+                    buffer,
+                },
+            );
+        }
+        let Some(panel) = self.panels.get_mut(key) else {
+            return;
+        };
+
+        let size = (rect.width, rect.height);
+        if panel.version != version || panel.buffer.size() != size {
+            if panel.buffer.size() != size {
+                panel
+                    .buffer
+                    .resize_with_pool(&mut self.grapheme_pool, rect.width, rect.height);
+            }
+            panel
+                .buffer
+                .clear_transparent_with_pool(&mut self.grapheme_pool);
+            render_fn(&mut panel.buffer, &mut self.grapheme_pool);
+            panel.version = version;
+        }
+
+        self.back_buffer.draw_buffer_region_with_pool(
+            &mut self.grapheme_pool,
+            rect.x as i32,
+            rect.y as i32,
+            &panel.buffer,
+            0,
+            0,
+            rect.width,
+            rect.height,
+            true,
+        );
+    }
+
+    /// Drop the cached panel under `key`, releasing its graphemes.
+    ///
+    /// Returns whether there was one.
+    pub fn evict(&mut self, key: &str) -> bool {
+        let Some(mut panel) = self.panels.remove(key) else {
+            return false;
+        };
+        Self::release_panel(&mut self.grapheme_pool, &mut panel);
+        true
+    }
+
+    /// Drop every cached panel, releasing their graphemes.
+    pub fn clear_cache(&mut self) {
+        for panel in self.panels.values_mut() {
+            Self::release_panel(&mut self.grapheme_pool, panel);
+        }
+        self.panels.clear();
+    }
+
+    fn release_panel(pool: &mut GraphemePool, panel: &mut CachedPanel) {
+        panel.buffer.drain_orphaned_graphemes(pool);
+        panel.buffer.release_graphemes(pool);
+    }
+
     /// Render into an offscreen layer buffer.
     ///
     /// Layer `0` is the base layer (the regular back buffer). Higher layer IDs are
@@ -443,7 +993,8 @@ impl Renderer {
 
         let layer = match self.layers.entry(layer_id) {
             Entry::Vacant(entry) => {
-                let mut buf = OptimizedBuffer::new(width, height);
+                let mut buf =
+                    OptimizedBuffer::new(width, height).with_grapheme_pool(&self.grapheme_pool);
                 // Layer buffers must start fully transparent (no tint/no-op cells).
                 buf.clear_transparent_with_pool(&mut self.grapheme_pool);
                 entry.insert(buf)
@@ -465,18 +1016,84 @@ impl Renderer {
         self.layers.len()
     }
 
+    /// Set how an overlay layer blends with the layers beneath it.
+    ///
+    /// Defaults to [`BlendMode::Over`]. The mode persists across frames;
+    /// it has no effect on the base layer `0`.
+    pub fn set_layer_blend_mode(&mut self, layer_id: u16, mode: BlendMode) {
+        if mode == BlendMode::Over {
+            self.layer_blend_modes.remove(&layer_id);
+        } else {
+            self.layer_blend_modes.insert(layer_id, mode);
+        }
+    }
+
+    /// Get how an overlay layer blends with the layers beneath it.
+    #[must_use]
+    pub fn layer_blend_mode(&self, layer_id: u16) -> BlendMode {
+        self.layer_blend_modes
+            .get(&layer_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set the opacity an overlay layer is composited with.
+    ///
+    /// Accepts a single factor or an [`Opacity`] pair, so a layer can fade
+    /// its text and backgrounds independently. Defaults to fully opaque;
+    /// the opacity persists across frames and has no effect on layer `0`.
+    pub fn set_layer_opacity(&mut self, layer_id: u16, opacity: impl Into<Opacity>) {
+        let opacity = opacity.into();
+        let opacity = Opacity::new(opacity.fg, opacity.bg);
+        if opacity.is_opaque() {
+            self.layer_opacities.remove(&layer_id);
+        } else {
+            self.layer_opacities.insert(layer_id, opacity);
+        }
+    }
+
+    /// Get the opacity an overlay layer is composited with.
+    #[must_use]
+    pub fn layer_opacity(&self, layer_id: u16) -> Opacity {
+        self.layer_opacities
+            .get(&layer_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Composite all active layers into the base back buffer.
     ///
-    /// Higher layer IDs are composited on top of lower ones, using proper alpha blending.
+    /// Higher layer IDs are composited on top of lower ones, using proper alpha
+    /// blending and each layer's [blend mode](Self::set_layer_blend_mode) and
+    /// [opacity](Self::set_layer_opacity).
     pub fn merge_layers(&mut self) {
         if !self.layers_dirty {
             self.active_hit_layer = 0;
             return;
         }
 
-        for layer in self.layers.values() {
-            self.back_buffer
-                .draw_buffer_with_pool(&mut self.grapheme_pool, 0, 0, layer);
+        for (layer_id, layer) in &self.layers {
+            let mode = self
+                .layer_blend_modes
+                .get(layer_id)
+                .copied()
+                .unwrap_or_default();
+            let opacity = self
+                .layer_opacities
+                .get(layer_id)
+                .copied()
+                .unwrap_or_default();
+            self.back_buffer.push_opacity_fg(opacity.fg);
+            self.back_buffer.push_opacity_bg(opacity.bg);
+            self.back_buffer.draw_buffer_with_pool_and_mode(
+                &mut self.grapheme_pool,
+                0,
+                0,
+                layer,
+                mode,
+            );
+            self.back_buffer.pop_opacity();
+            self.back_buffer.pop_opacity();
         }
 
         for grid in self.layer_hit_grids.values() {
@@ -491,165 +1108,554 @@ impl Renderer {
     pub fn clear(&mut self) {
         self.back_buffer
             .clear_with_pool(&mut self.grapheme_pool, self.background);
+        self.back_blank = Some((self.back_buffer.mutation_count(), self.background));
         self.back_hit_grid.clear();
+        self.back_hit_regions.clear();
+        Self::release_links(&mut self.link_pool, &mut self.back_links);
         self.clear_overlay_layers();
     }
 
+    /// Clear `rect` of the back buffer to the background color, leaving the
+    /// rest of the frame as drawn.
+    ///
+    /// When the background is the
+    /// [terminal's default](Self::set_terminal_background), the next present
+    /// erases the region instead of writing spaces.
+    pub fn clear_region(&mut self, rect: Rect) {
+        self.back_buffer.fill_rect_with_pool(
+            &mut self.grapheme_pool,
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            self.background,
+        );
+    }
+
+    /// Fill `rect` with `color` in the layer last selected with
+    /// [`Self::render_to_layer`], honoring that buffer's scissor and
+    /// opacity stacks.
+    pub fn fill_region(&mut self, rect: Rect, color: Rgba) {
+        self.render_to_layer(self.active_hit_layer, |buffer| {
+            buffer.fill_rect(rect.x, rect.y, rect.width, rect.height, color);
+        });
+    }
+
+    /// Run `hook` on the back buffer just before each present, after layers
+    /// are merged and the debug overlay is drawn; what it draws appears in
+    /// that frame.
+    ///
+    /// Hooks run in registration order. A panicking hook makes
+    /// [`present`](Self::present) return an error without presenting.
+    pub fn on_before_present<F>(&mut self, hook: F) -> HookId
+    where
+        F: FnMut(&mut OptimizedBuffer) + 'static,
+    {
+        let id = self.next_hook_id();
+        self.before_present_hooks.push((id, Box::new(hook)));
+        id
+    }
+
+    /// Run `hook` with the updated stats after each present.
+    ///
+    /// Hooks run in registration order. A panicking hook makes
+    /// [`present`](Self::present) return an error; the frame has already
+    /// been presented by then.
+    pub fn on_after_present<F>(&mut self, hook: F) -> HookId
+    where
+        F: FnMut(&RenderStats) + 'static,
+    {
+        let id = self.next_hook_id();
+        self.after_present_hooks.push((id, Box::new(hook)));
+        id
+    }
+
+    /// Remove a hook added by [`on_before_present`](Self::on_before_present)
+    /// or [`on_after_present`](Self::on_after_present). Returns false if it
+    /// was already removed.
+    pub fn remove_present_hook(&mut self, id: HookId) -> bool {
+        let before = self.before_present_hooks.len() + self.after_present_hooks.len();
+        self.before_present_hooks.retain(|(hook, _)| *hook != id);
+        self.after_present_hooks.retain(|(hook, _)| *hook != id);
+        self.before_present_hooks.len() + self.after_present_hooks.len() != before
+    }
+
+    fn next_hook_id(&mut self) -> HookId {
+        let id = HookId(self.next_hook_id);
+        self.next_hook_id += 1;
+        id
+    }
+
+    /// Whether the output is still open.
+    ///
+    /// Becomes false when a present finds the terminal or pipe gone (broken
+    /// pipe, I/O error on a hung-up terminal, or writes that keep blocking).
+    /// Applications should stop their render loop once this is false.
+    #[must_use]
+    pub const fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    /// Set what [`Self::cleanup`] does with the last frame when rendering on
+    /// the main screen. Defaults to [`ExitPolicy::Unchanged`].
+    pub fn set_exit_policy(&mut self, policy: ExitPolicy) {
+        self.exit_policy = policy;
+    }
+
+    /// Current exit policy.
+    #[must_use]
+    pub fn exit_policy(&self) -> ExitPolicy {
+        self.exit_policy
+    }
+
+    /// Check the cells each [`present`](Self::present) is about to write for
+    /// malformed states: continuation cells without a wide character before
+    /// them, graphemes whose pool entry was freed, and NaN colors.
+    ///
+    /// Only the changed regions are checked, or the whole frame when it is
+    /// redrawn in full. Off by default.
+    pub fn set_validation(&mut self, level: ValidationLevel) {
+        self.validation = level;
+    }
+
+    /// Current frame validation level.
+    #[must_use]
+    pub fn validation(&self) -> ValidationLevel {
+        self.validation
+    }
+
+    /// Run frame validation over the regions about to be written.
+    fn validate_frame(&self, full_redraw: bool) -> Result<()> {
+        if self.validation == ValidationLevel::Off {
+            return Ok(());
+        }
+        let whole = [diff::DirtyRegion::new(0, 0, self.width, self.height)];
+        let regions = if full_redraw {
+            &whole[..]
+        } else {
+            &self.cached_diff.dirty_regions[..]
+        };
+        let issues = validate::validate_regions(&self.back_buffer, &self.grapheme_pool, regions);
+        if issues.is_empty() {
+            return Ok(());
+        }
+        if self.validation == ValidationLevel::Strict {
+            return Err(Error::InvalidFrame { issues });
+        }
+        validate::log_issues(&issues);
+        Ok(())
+    }
+
+    /// Set how many times a write that would block is retried before the
+    /// output is treated as closed. Defaults to 3.
+    pub fn set_write_retries(&mut self, retries: u32) {
+        self.terminal.writer_mut().retries = retries;
+    }
+
     /// Present the back buffer to screen (swap buffers).
-    pub fn present(&mut self) -> io::Result<()> {
+    ///
+    /// When nothing has been drawn since the last blank frame, the buffers
+    /// are known to match and the frame is neither diffed nor written; see
+    /// [`OptimizedBuffer::mutation_count`]. The [frame arena](Self::arena)
+    /// is reset either way.
+    ///
+    /// Fails if called from a present hook, or if a hook panics. If the
+    /// output turns out to be closed, this returns the write error and every
+    /// later call returns [`Error::OutputClosed`] without writing; see
+    /// [`is_alive`](Self::is_alive).
+    pub fn present(&mut self) -> Result<()> {
+        if !self.alive {
+            return Err(Error::OutputClosed);
+        }
+        if self.presenting {
+            return Err(Error::InvalidInput(
+                "present called from a present hook".to_string(),
+            ));
+        }
+        self.presenting = true;
+        let result = self.present_frame();
+        self.presenting = false;
+        self.arena.reset();
+        if let Err(err) = &result {
+            if err.io_source().is_some_and(output::is_closed) {
+                self.alive = false;
+                self.terminal.mark_output_closed();
+            }
+        }
+        result
+    }
+
+    /// Cap [`present_paced`](Self::present_paced) at `fps` frames per
+    /// second, or stop pacing with `None`. Rates that aren't positive and
+    /// finite also stop it, as do rates so extreme that the interval between
+    /// frames rounds to zero or overflows a [`Duration`]. Restarts the
+    /// schedule.
+    pub fn set_target_fps(&mut self, fps: Option<f32>) {
+        self.pacer.set_target_fps(fps);
+    }
+
+    /// The rate set with [`set_target_fps`](Self::set_target_fps).
+    #[must_use]
+    pub const fn target_fps(&self) -> Option<f32> {
+        self.pacer.target_fps()
+    }
+
+    /// Time until the next paced frame is due, or zero.
+    ///
+    /// Use it as the input poll timeout between frames: the loop then
+    /// wakes for input or for the frame, whichever comes first, and
+    /// [`present_paced`](Self::present_paced) has little or nothing left
+    /// to sleep.
+    #[must_use]
+    pub fn time_until_next_frame(&self) -> Duration {
+        self.pacer.time_until_next_frame()
+    }
+
+    /// Sleep until the next frame is due at the [target
+    /// rate](Self::set_target_fps), then [`present`](Self::present).
+    ///
+    /// Frames are due on a fixed schedule, so a frame that sleeps too long
+    /// or takes too long to draw doesn't push back the ones after it, and
+    /// [`RenderStats::fps`] settles at the target. A frame more than a
+    /// whole interval late skips the frames it overran; see
+    /// [`FrameTiming::missed`]. Without a target rate this is `present`.
+    pub fn present_paced(&mut self) -> Result<FrameTiming> {
+        let timing = self.pacer.wait();
+        self.present()?;
+        Ok(timing)
+    }
+
+    /// Start recording every presented frame to the file at `path`, to play
+    /// back with a [`Player`](crate::replay::Player). A recording already
+    /// running is stopped first.
+    ///
+    /// Frames are copied as they are presented and written by a background
+    /// thread; frames it can't keep up with are dropped and counted in the
+    /// [`RecordingSummary`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file can't be created, or the error
+    /// finishing the previous recording.
+    pub fn start_recording(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: RecordOptions,
+    ) -> Result<()> {
+        self.stop_recording()?;
+        self.recorder = Some(Recorder::start(path.as_ref(), options)?);
+        Ok(())
+    }
+
+    /// Stop recording and wait for the recorded frames to be written.
+    /// Returns `None` if no recording was running.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if writing the recording failed.
+    pub fn stop_recording(&mut self) -> Result<Option<RecordingSummary>> {
+        match self.recorder.take() {
+            Some(recorder) => Ok(Some(recorder.finish()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether frames are being recorded.
+    #[must_use]
+    pub const fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Add terminal input to the recording, if one is running with
+    /// [`RecordOptions::include_input`].
+    pub fn record_input(&mut self, bytes: &[u8]) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_input(bytes);
+        }
+    }
+
+    fn present_frame(&mut self) -> Result<()> {
         if self.layers_dirty {
             self.merge_layers();
         }
         if self.show_debug_overlay {
             self.draw_debug_overlay();
         }
+        for (_, hook) in &mut self.before_present_hooks {
+            run_hook("before-present", || hook(&mut self.back_buffer))?;
+        }
 
-        let total_cells = (self.width as usize).saturating_mul(self.height as usize);
-        // Use cached diff to avoid per-frame allocation
-        self.cached_diff
-            .compute_into(&self.front_buffer, &self.back_buffer);
-        self.append_manual_dirty_regions();
+        // Repaint the cells under images that went away or changed.
+        let stale: Vec<Rect> = self
+            .front_images
+            .iter()
+            .filter(|front| !self.images.iter().any(|image| image.same_as(front)))
+            .map(|front| front.rect)
+            .collect();
+        let images_kept = stale.is_empty() && self.images.len() == self.front_images.len();
+        for rect in stale {
+            self.mark_region_dirty(rect);
+        }
 
-        if self.force_redraw || self.cached_diff.should_full_redraw(total_cells) {
-            self.present_force()?;
-            self.update_stats(total_cells);
-            self.force_redraw = false;
+        if images_kept && self.is_idle() {
+            // Both buffers hold the same blank frame: nothing to diff or write.
+            self.cached_diff.clear();
+            self.stats.last_frame_output = AnsiWriterMetrics::default();
+            self.update_stats(0);
         } else {
-            self.present_diff()?;
-            self.update_stats(self.cached_diff.change_count);
+            let total_cells = (self.width as usize).saturating_mul(self.height as usize);
+            // Use cached diff to avoid per-frame allocation
+            self.cached_diff
+                .compute_into(&self.front_buffer, &self.back_buffer);
+            self.append_manual_dirty_regions();
+
+            let full_redraw = self.force_redraw || self.cached_diff.should_full_redraw(total_cells);
+            self.validate_frame(full_redraw)?;
+            if full_redraw {
+                self.publish(RendererEvent::ForcedRedraw);
+                self.present_force()?;
+                self.update_stats(total_cells);
+                self.force_redraw = false;
+            } else {
+                self.present_diff()?;
+                self.update_stats(self.cached_diff.change_count);
+            }
+            self.track_used_rows();
+
+            // Swap buffers
+            let presented_blank = self
+                .back_blank
+                .filter(|&(mark, _)| mark == self.back_buffer.mutation_count())
+                .map(|(_, bg)| bg);
+            std::mem::swap(&mut self.front_buffer, &mut self.back_buffer);
+            std::mem::swap(&mut self.front_links, &mut self.back_links);
+            Self::release_links(&mut self.link_pool, &mut self.back_links);
+            self.back_buffer
+                .clear_with_pool(&mut self.grapheme_pool, self.background);
+            self.front_blank = presented_blank;
+            self.back_blank = Some((self.back_buffer.mutation_count(), self.background));
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record_frame(&self.front_buffer, &self.grapheme_pool);
         }
 
-        // Swap buffers
-        std::mem::swap(&mut self.front_buffer, &mut self.back_buffer);
         std::mem::swap(&mut self.front_hit_grid, &mut self.back_hit_grid);
-        self.back_buffer
-            .clear_with_pool(&mut self.grapheme_pool, self.background);
+        std::mem::swap(&mut self.front_hit_regions, &mut self.back_hit_regions);
+        self.front_images = std::mem::take(&mut self.images);
         self.back_hit_grid.clear();
+        self.back_hit_regions.clear();
         self.clear_overlay_layers();
         self.manual_dirty_regions.clear();
 
+        for (_, hook) in &mut self.after_present_hooks {
+            run_hook("after-present", || hook(&self.stats))?;
+        }
+        self.publish(RendererEvent::FramePresented(self.stats.clone()));
         Ok(())
     }
 
+    /// Whether the back buffer is known to match the front buffer without
+    /// diffing them: both are blank frames of the same color that nothing
+    /// has been drawn over since they were cleared.
+    fn is_idle(&self) -> bool {
+        let back_blank = self
+            .back_blank
+            .filter(|&(mark, _)| mark == self.back_buffer.mutation_count());
+        !self.force_redraw
+            && self.manual_dirty_regions.is_empty()
+            && matches!(
+                (back_blank, self.front_blank),
+                (Some((_, back)), Some(front)) if back.bits_eq(front)
+            )
+    }
+
     /// Force a full redraw.
-    pub fn present_force(&mut self) -> io::Result<()> {
+    pub fn present_force(&mut self) -> Result<()> {
         if self.terminal.capabilities().sync_output {
-            self.terminal.begin_sync()?;
+            self.terminal
+                .begin_sync()
+                .map_err(Error::render(RenderPhase::BeginSync))?;
         }
 
         self.scratch_buffer.clear();
-        let mut writer = AnsiWriter::new(&mut self.scratch_buffer);
+        let mut writer = AnsiWriter::with_color_mode(&mut self.scratch_buffer, self.color_mode);
+        writer.set_color_fallbacks(self.color_fallbacks.clone());
+        writer.set_styled_underlines(self.terminal.capabilities().styled_underlines);
+        writer.set_rep(self.terminal.capabilities().rep);
+        writer.set_line_drawing(self.terminal.capabilities().line_drawing());
+        if self.show_debug_overlay {
+            writer = writer.with_metrics();
+        }
         // Emit cursor home to synchronize terminal cursor with writer's internal tracking.
         // The writer starts tracking at (0,0), but the terminal cursor may be elsewhere
         // (e.g., pending-wrap state at end of previous frame).
         writer.write_str("\x1b[H");
 
+        let line_attributes = self.terminal.capabilities().dec_line_attributes;
         for y in 0..self.height {
-            for x in 0..self.width {
-                if let Some(cell) = self.back_buffer.get(x, y) {
-                    if !cell.is_continuation() {
-                        // Always move cursor to exact position before writing
-                        // This ensures correct positioning even when cells are skipped
-                        writer.move_cursor(y, x);
-                        let url = cell
-                            .attributes
-                            .link_id()
-                            .and_then(|id| self.link_pool.get(id));
-                        writer.write_cell_with_link_and_pool(cell, url, &self.grapheme_pool);
-                    }
-                }
-            }
+            let columns = write_row_size(
+                &mut writer,
+                &self.front_buffer,
+                &self.back_buffer,
+                y,
+                line_attributes,
+                true,
+            );
+            write_row_runs(
+                &mut writer,
+                &self.back_buffer,
+                &self.grapheme_pool,
+                &self.link_pool,
+                y,
+                0..columns,
+                &[],
+                self.terminal_background,
+            );
         }
+        write_images(&mut writer, &self.images);
 
         writer.reset();
-        writer.flush()?;
+        writer.flush().map_err(Error::render(RenderPhase::Write))?;
+        self.stats.last_frame_output = writer.metrics().unwrap_or_default();
 
-        self.terminal.flush()?;
+        self.terminal
+            .flush()
+            .map_err(Error::render(RenderPhase::Flush))?;
         // Write the accumulated content from scratch buffer to terminal
-        io::stdout().write_all(&self.scratch_buffer)?;
-        io::stdout().flush()?;
+        self.terminal
+            .writer_mut()
+            .write_all(&self.scratch_buffer)
+            .map_err(Error::render(RenderPhase::Write))?;
 
         if self.terminal.capabilities().sync_output {
-            self.terminal.end_sync()?;
+            self.terminal
+                .end_sync()
+                .map_err(Error::render(RenderPhase::EndSync))?;
         }
-        self.terminal.flush()
+        self.terminal
+            .flush()
+            .map_err(Error::render(RenderPhase::Flush))
     }
 
     /// Present using diff detection.
-    fn present_diff(&mut self) -> io::Result<()> {
+    fn present_diff(&mut self) -> Result<()> {
         if self.terminal.capabilities().sync_output {
-            self.terminal.begin_sync()?;
+            self.terminal
+                .begin_sync()
+                .map_err(Error::render(RenderPhase::BeginSync))?;
         }
 
         self.scratch_buffer.clear();
-        let mut writer = AnsiWriter::new(&mut self.scratch_buffer);
+        let mut writer = AnsiWriter::with_color_mode(&mut self.scratch_buffer, self.color_mode);
+        writer.set_color_fallbacks(self.color_fallbacks.clone());
+        writer.set_styled_underlines(self.terminal.capabilities().styled_underlines);
+        writer.set_rep(self.terminal.capabilities().rep);
+        writer.set_line_drawing(self.terminal.capabilities().line_drawing());
+        if self.show_debug_overlay {
+            writer = writer.with_metrics();
+        }
         // Emit cursor home to synchronize terminal cursor with writer's internal tracking.
         // The writer starts tracking at (0,0), but the terminal cursor may be elsewhere
         // from the previous frame. Without this, relative moves would be incorrect.
         writer.write_str("\x1b[H");
 
-        for region in &self.cached_diff.dirty_regions {
-            if region.width == 0 || region.height == 0 {
-                continue;
-            }
-            for row in 0..region.height {
-                let y = region.y + row;
-                for col in 0..region.width {
-                    let x = region.x + col;
-                    let back_cell = self.back_buffer.get(x, y);
-                    if let Some(cell) = back_cell {
-                        // Skip continuation cells - they don't produce output
-                        if cell.is_continuation() {
-                            continue;
-                        }
-                        // Always move cursor to exact position before writing
-                        // This ensures correct positioning even when continuation cells are skipped
-                        writer.move_cursor(y, x);
-                        let url = cell
-                            .attributes
-                            .link_id()
-                            .and_then(|id| self.link_pool.get(id));
-                        writer.write_cell_with_pool_and_link(cell, &self.grapheme_pool, url);
-                    }
+        // Images already on screen keep their cells; new ones are drawn over
+        // the freshly written cells.
+        let (kept, new): (Vec<&PlacedImage>, Vec<&PlacedImage>) = self
+            .images
+            .iter()
+            .partition(|image| self.front_images.iter().any(|front| front.same_as(image)));
+        let kept: Vec<Rect> = kept.iter().map(|image| image.rect).collect();
+
+        // A row written from its first column follows on by autowrap from a
+        // row written to its last, unless a scrolling region could turn the
+        // wrap into a scroll.
+        let wraps = self.terminal.scroll_region().is_none();
+        let line_attributes = self.terminal.capabilities().dec_line_attributes;
+        for rect in &self.cached_diff.dirty_rects {
+            for y in rect.rows() {
+                let columns = write_row_size(
+                    &mut writer,
+                    &self.front_buffer,
+                    &self.back_buffer,
+                    y,
+                    line_attributes,
+                    false,
+                );
+                if wraps
+                    && rect.x == 0
+                    && starts_with_text(&self.back_buffer, y, &kept, self.terminal_background)
+                {
+                    writer.wrap_to_row(y, self.width);
                 }
+                write_row_runs(
+                    &mut writer,
+                    &self.back_buffer,
+                    &self.grapheme_pool,
+                    &self.link_pool,
+                    y,
+                    rect.x..(rect.x + rect.width).min(columns),
+                    &kept,
+                    self.terminal_background,
+                );
             }
         }
+        write_images(&mut writer, new);
 
         writer.reset();
-        writer.flush()?;
+        writer.flush().map_err(Error::render(RenderPhase::Write))?;
+        self.stats.last_frame_output = writer.metrics().unwrap_or_default();
 
         if !self.scratch_buffer.is_empty() {
-            io::stdout().write_all(&self.scratch_buffer)?;
-            io::stdout().flush()?;
+            self.terminal
+                .writer_mut()
+                .write_all(&self.scratch_buffer)
+                .map_err(Error::render(RenderPhase::Write))?;
         }
 
         if self.terminal.capabilities().sync_output {
-            self.terminal.end_sync()?;
+            self.terminal
+                .end_sync()
+                .map_err(Error::render(RenderPhase::EndSync))?;
         }
-        self.terminal.flush()
+        self.terminal
+            .flush()
+            .map_err(Error::render(RenderPhase::Flush))
     }
 
     /// Resize the renderer.
-    pub fn resize(&mut self, width: u32, height: u32) -> io::Result<()> {
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
         self.width = width;
         self.height = height;
+        self.terminal.set_size(width, height);
         self.front_buffer
             .resize_with_pool(&mut self.grapheme_pool, width, height);
         self.back_buffer
             .resize_with_pool(&mut self.grapheme_pool, width, height);
         self.front_hit_grid = HitGrid::new(width, height);
         self.back_hit_grid = HitGrid::new(width, height);
+        self.front_hit_regions.clear();
+        self.back_hit_regions.clear();
         self.resize_overlay_layers(width, height);
         self.hit_scissor.clear();
         // Clear cached diff (it will grow as needed on next present)
         self.cached_diff.clear();
         self.manual_dirty_regions.clear();
+        self.front_images.clear();
+        self.back_blank = None;
+        self.front_blank = None;
         self.force_redraw = true;
-        self.terminal.clear()
+        if self.alive {
+            self.terminal
+                .clear()
+                .map_err(Error::render(RenderPhase::Resize))?;
+        }
+        self.publish(RendererEvent::Resized { width, height });
+        Ok(())
     }
 
     /// Set cursor position.
-    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> io::Result<()> {
+    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> Result<()> {
         if visible {
             self.terminal.show_cursor()?;
             self.terminal.move_cursor(x, y)?;
@@ -660,51 +1666,83 @@ impl Renderer {
     }
 
     /// Set cursor style.
-    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> io::Result<()> {
+    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> Result<()> {
         self.terminal.set_cursor_style(style, blinking)
     }
 
     /// Set window title.
-    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
         self.terminal.set_title(title)
     }
 
     /// Register a hit area for mouse testing.
     pub fn register_hit_area(&mut self, x: u32, y: u32, width: u32, height: u32, id: u32) {
-        let rect = ClipRect::new(x as i32, y as i32, width, height);
-        if let Some(intersect) = self.hit_scissor.current().intersect(&rect) {
-            if !intersect.is_empty() {
-                let hit_grid = if self.active_hit_layer == 0 {
-                    &mut self.back_hit_grid
-                } else {
-                    let width = self.width;
-                    let height = self.height;
-                    let grid = self
-                        .layer_hit_grids
-                        .entry(self.active_hit_layer)
-                        .or_insert_with(|| HitGrid::new(width, height));
-                    if grid.size() != (width, height) {
-                        grid.resize(width, height);
-                    }
-                    self.layers_dirty = true;
-                    grid
-                };
-
-                hit_grid.register(
-                    intersect.x.max(0) as u32,
-                    intersect.y.max(0) as u32,
-                    intersect.width,
-                    intersect.height,
-                    id,
-                );
+        self.register_hit_region(Rect::new(x, y, width, height), HitRegion::new(id));
+    }
+
+    /// Register a hit area with a name and payload, returned by
+    /// [`Self::hit_test_region`] once the frame is presented.
+    pub fn register_hit_region(&mut self, rect: Rect, region: HitRegion) {
+        let clip = ClipRect::new(rect.x as i32, rect.y as i32, rect.width, rect.height);
+        let mut visible = self
+            .hit_scissor
+            .rects()
+            .filter_map(|scissor| scissor.intersect(&clip))
+            .filter(|intersect| !intersect.is_empty())
+            .peekable();
+        if visible.peek().is_none() {
+            return;
+        }
+        let Ok(index) = u32::try_from(self.back_hit_regions.len()) else {
+            return;
+        };
+        self.back_hit_regions.push(HitRegionInfo {
+            id: region.id,
+            name: region.name,
+            data: region.data,
+            rect,
+        });
+
+        let hit_grid = if self.active_hit_layer == 0 {
+            &mut self.back_hit_grid
+        } else {
+            let width = self.width;
+            let height = self.height;
+            let grid = self
+                .layer_hit_grids
+                .entry(self.active_hit_layer)
+                .or_insert_with(|| HitGrid::new(width, height));
+            if grid.size() != (width, height) {
+                grid.resize(width, height);
             }
+            self.layers_dirty = true;
+            grid
+        };
+
+        // A multi-rect hit scissor registers one piece per visible sub-rect.
+        for intersect in visible {
+            hit_grid.register(
+                intersect.x.max(0) as u32,
+                intersect.y.max(0) as u32,
+                intersect.width,
+                intersect.height,
+                index,
+            );
         }
     }
 
     /// Test which hit area contains a point.
     #[must_use]
     pub fn hit_test(&self, x: u32, y: u32) -> Option<u32> {
-        self.front_hit_grid.test(x, y)
+        self.hit_test_region(x, y).map(|region| region.id)
+    }
+
+    /// Test which hit region contains a point, with its name, payload, and
+    /// registered rectangle.
+    #[must_use]
+    pub fn hit_test_region(&self, x: u32, y: u32) -> Option<&HitRegionInfo> {
+        let index = self.front_hit_grid.test(x, y)?;
+        self.front_hit_regions.get(index as usize)
     }
 
     /// Push a hit-scissor rectangle (for hit testing).
@@ -712,6 +1750,13 @@ impl Renderer {
         self.hit_scissor.push(rect);
     }
 
+    /// Push a hit scissor made of several rectangles (for hit testing).
+    ///
+    /// See [`ScissorStack::push_multi`].
+    pub fn push_hit_scissor_multi(&mut self, rects: &[ClipRect]) {
+        self.hit_scissor.push_multi(rects);
+    }
+
     /// Pop a hit-scissor rectangle.
     pub fn pop_hit_scissor(&mut self) {
         self.hit_scissor.pop();
@@ -728,12 +1773,70 @@ impl Renderer {
     }
 
     /// Cleanup and restore terminal state.
-    pub fn cleanup(&mut self) -> io::Result<()> {
-        self.terminal.cleanup()
+    ///
+    /// On the main screen this first applies the [`ExitPolicy`]. Once the
+    /// output is closed this only leaves raw mode.
+    pub fn cleanup(&mut self) -> Result<()> {
+        if self.alive {
+            self.apply_exit_policy()
+                .map_err(Error::render(RenderPhase::Cleanup))?;
+        }
+        self.terminal
+            .cleanup()
+            .map_err(Error::render(RenderPhase::Cleanup))
+    }
+
+    /// Note the lowest non-blank row of the frame being presented.
+    fn track_used_rows(&mut self) {
+        if self.exit_policy == ExitPolicy::Unchanged || self.terminal.is_alt_screen() {
+            return;
+        }
+        let (width, height) = self.back_buffer.size();
+        let first_unseen = self.max_used_row.map_or(0, |row| row + 1);
+        let cells = self.back_buffer.cells();
+        let background = self.background;
+        let last_used = (first_unseen..height).rev().find(|&y| {
+            let start = y as usize * width as usize;
+            cells[start..start + width as usize]
+                .iter()
+                .any(|cell| !is_blank(cell, background))
+        });
+        if last_used.is_some() {
+            self.max_used_row = last_used;
+        }
+    }
+
+    /// Leave the last frame or erase it, once, as the exit policy says.
+    fn apply_exit_policy(&mut self) -> io::Result<()> {
+        let Some(max_row) = self.max_used_row.take() else {
+            return Ok(());
+        };
+        if self.terminal.is_alt_screen() {
+            return Ok(());
+        }
+        let last_row = max_row.min(self.height.saturating_sub(1));
+        let mut seq = String::from(crate::ansi::sequences::RESET);
+        match self.exit_policy {
+            ExitPolicy::Unchanged => return Ok(()),
+            ExitPolicy::LeaveLastFrame => {
+                seq.push_str(&crate::ansi::cursor_position(last_row, 0));
+                seq.push_str("\r\n");
+            }
+            ExitPolicy::ClearOnExit => {
+                for row in 0..=last_row {
+                    seq.push_str(&crate::ansi::cursor_position(row, 0));
+                    seq.push_str(crate::ansi::sequences::CLEAR_LINE);
+                }
+                seq.push_str(crate::ansi::sequences::CURSOR_HOME);
+            }
+        }
+        let writer = self.terminal.writer_mut();
+        writer.write_all(seq.as_bytes())?;
+        writer.flush()
     }
 
     fn update_stats(&mut self, cells_updated: usize) {
-        let now = Instant::now();
+        let now = self.pacer.now();
         let frame_time = now.duration_since(self.last_present_at);
         self.last_present_at = now;
 
@@ -747,10 +1850,25 @@ impl Renderer {
         };
 
         let buffer_bytes = self.front_buffer.byte_size() + self.back_buffer.byte_size();
-        let hitgrid_bytes = self.front_hit_grid.byte_size() + self.back_hit_grid.byte_size();
+        let hitgrid_bytes = self.front_hit_grid.byte_size()
+            + self.back_hit_grid.byte_size()
+            + self
+                .layer_hit_grids
+                .values()
+                .map(HitGrid::byte_size)
+                .sum::<usize>();
+        let panel_cache_bytes = self
+            .panels
+            .values()
+            .map(|panel| panel.buffer.byte_size())
+            .sum::<usize>();
         self.stats.buffer_bytes = buffer_bytes;
         self.stats.hitgrid_bytes = hitgrid_bytes;
-        self.stats.total_bytes = buffer_bytes + hitgrid_bytes;
+        let arena_bytes = self.arena.capacity();
+        self.stats.panel_cache_bytes = panel_cache_bytes;
+        self.stats.arena_bytes = arena_bytes;
+        self.stats.arena_peak_bytes = self.arena.peak();
+        self.stats.total_bytes = buffer_bytes + hitgrid_bytes + panel_cache_bytes + arena_bytes;
     }
 
     fn draw_debug_overlay(&mut self) {
@@ -776,6 +1894,16 @@ impl Renderer {
                 pool_stats.active_slots, pool_stats.total_slots
             ),
             format!("Mem: {mem_total} B"),
+            format!(
+                "Out: {} B, {} text",
+                stats.last_frame_output.total_bytes, stats.last_frame_output.text_bytes
+            ),
+            format!(
+                "Seq: {} mv, {} sgr, {} osc",
+                stats.last_frame_output.cursor_moves,
+                stats.last_frame_output.sgr_sequences,
+                stats.last_frame_output.osc_sequences
+            ),
         ];
 
         let title = "Debug";
@@ -799,16 +1927,20 @@ impl Renderer {
         x = x.min(max_x);
         y = y.min(max_y);
 
-        let border_style = crate::style::Style::fg(Rgba::WHITE).with_bold();
+        // Keep the stats readable whatever the background shows through
+        let fill = Rgba::BLACK.with_alpha(0.6);
+        let text_color = color::ensure_contrast(Rgba::WHITE, fill.blend_over(self.background), 4.5);
+
+        let border_style = crate::style::Style::fg(text_color).with_bold();
         let mut options = BoxOptions::new(BoxStyle::rounded(border_style));
-        options.fill = Some(Rgba::BLACK.with_alpha(0.6));
+        options.fill = Some(fill);
         options.title = Some(title.to_string());
         options.title_align = TitleAlign::Left;
 
         self.back_buffer
             .draw_box_with_options(x, y, box_w, box_h, options);
 
-        let text_style = crate::style::Style::fg(Rgba::WHITE);
+        let text_style = crate::style::Style::fg(text_color);
         for (idx, line) in lines.iter().enumerate() {
             let row = y.saturating_add(1 + idx as u32);
             self.back_buffer
@@ -847,19 +1979,220 @@ impl Renderer {
             .dirty_regions
             .reserve(self.manual_dirty_regions.len());
         for rect in &self.manual_dirty_regions {
-            self.cached_diff.dirty_regions.push(diff::DirtyRegion::new(
-                rect.x,
-                rect.y,
-                rect.width,
-                rect.height,
-            ));
+            let region = diff::DirtyRegion::new(rect.x, rect.y, rect.width, rect.height);
+            self.cached_diff.dirty_regions.push(region);
+            self.cached_diff.dirty_rects.push(region.into());
+        }
+    }
+}
+
+/// Whether a cell shows nothing over the renderer's background.
+fn is_blank(cell: &Cell, background: Rgba) -> bool {
+    matches!(
+        cell.content,
+        crate::cell::CellContent::Empty | crate::cell::CellContent::Char(' ')
+    ) && (cell.bg.bits_eq(background) || cell.bg.a == 0.0)
+        && cell.attributes.is_empty()
+}
+
+/// Whether two cells would be written with the same SGR state and link.
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    a.fg.bits_eq(b.fg)
+        && a.bg.bits_eq(b.bg)
+        && a.attributes == b.attributes
+        && a.underline_style == b.underline_style
+        && match (a.underline_color, b.underline_color) {
+            (Some(a), Some(b)) => a.bits_eq(b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+}
+
+/// Number of cells in `row` from `x`, up to `end`, styled like the cell at
+/// `x`.
+///
+/// Runs only cover single-width cells, so they break at wide characters as
+/// well as at style and hyperlink changes.
+pub(crate) fn style_run(row: &[Cell], x: u32, end: u32) -> u32 {
+    let Some(first) = row.get(x as usize) else {
+        return 0;
+    };
+    if first.display_width() != 1 {
+        return 1;
+    }
+    let cells = row.get(x as usize + 1..end as usize).unwrap_or_default();
+    let len = cells
+        .iter()
+        .take_while(|cell| cell.display_width() == 1 && same_style(cell, first))
+        .count();
+    1 + len as u32
+}
+
+/// Whether `cell` can be written by erasing it to the terminal's default
+/// background, `background`.
+fn is_erasable(cell: &Cell, background: Rgba) -> bool {
+    matches!(
+        cell.content,
+        crate::cell::CellContent::Empty | crate::cell::CellContent::Char(' ')
+    ) && cell.bg.bits_eq(background)
+        && cell.attributes.is_empty()
+}
+
+/// Whether [`write_row_runs`] starts row `y` by printing its first cell.
+fn starts_with_text(
+    buffer: &OptimizedBuffer,
+    y: u32,
+    skip: &[Rect],
+    erase_bg: Option<Rgba>,
+) -> bool {
+    buffer.get(0, y).is_some_and(|cell| {
+        !cell.is_continuation()
+            && !skip.iter().any(|rect| rect.contains(0, y))
+            && !erase_bg.is_some_and(|background| is_erasable(cell, background))
+    })
+}
+
+/// Set the line attribute of row `y` where it changed from `front` to
+/// `back`, or on a full redraw wherever it isn't normal, and return how many
+/// columns the row shows.
+///
+/// Without `line_attributes` support, every row shows at normal size.
+pub(crate) fn write_row_size<W: Write>(
+    writer: &mut AnsiWriter<W>,
+    front: &OptimizedBuffer,
+    back: &OptimizedBuffer,
+    y: u32,
+    line_attributes: bool,
+    full_redraw: bool,
+) -> u32 {
+    if !line_attributes {
+        return back.width();
+    }
+    let size = back.row_size(y);
+    if size != front.row_size(y) || (full_redraw && size != RowSize::Normal) {
+        writer.set_row_size(y, size);
+    }
+    size.columns(back.width())
+}
+
+/// Write the cells of row `y` within `cols`, batching runs of same-styled
+/// cells and leaving cells inside `skip` untouched.
+///
+/// Each run gets one cursor move and one style change, then its glyphs back
+/// to back (see [`AnsiWriter::write_styled_run_with_pool_and_link`]). The
+/// cursor is moved to the start of each run, so skipped cells never leave it
+/// out of place. With `erase_bg`, the terminal's default background, runs of
+/// blank cells in that color are erased instead of written as spaces.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_row_runs<W: Write>(
+    writer: &mut AnsiWriter<W>,
+    buffer: &OptimizedBuffer,
+    grapheme_pool: &GraphemePool,
+    link_pool: &LinkPool,
+    y: u32,
+    cols: Range<u32>,
+    skip: &[Rect],
+    erase_bg: Option<Rgba>,
+) {
+    let (width, height) = buffer.size();
+    if y >= height {
+        return;
+    }
+    let start = y as usize * width as usize;
+    let row = &buffer.cells()[start..start + width as usize];
+    let cols = cols.start..cols.end.min(width);
+
+    let mut x = cols.start;
+    while x < cols.end {
+        if let Some(rect) = skip.iter().find(|rect| rect.contains(x, y)) {
+            x = rect.max_x();
+            continue;
+        }
+        let cell = &row[x as usize];
+        if cell.is_continuation() {
+            x += 1;
+            continue;
+        }
+        let end = skip
+            .iter()
+            .filter(|rect| rect.y <= y && y < rect.max_y() && rect.x > x)
+            .map(|rect| rect.x)
+            .fold(cols.end, u32::min);
+        if let Some(background) = erase_bg
+            && is_erasable(cell, background)
+        {
+            let blank = row[x as usize..end as usize]
+                .iter()
+                .take_while(|cell| is_erasable(cell, background))
+                .count() as u32;
+            writer.move_cursor(y, x);
+            writer.erase_cells(blank, x + blank == width);
+            x += blank;
+            continue;
         }
+        let run = style_run(row, x, end);
+        writer.move_cursor(y, x);
+        let link = cell.attributes.link_id();
+        let url = link.and_then(|id| link_pool.get(id));
+        let params = link.and_then(|id| link_pool.params(id)).unwrap_or("");
+        writer.set_link_with_params(link, url, params);
+        writer.write_styled_run_with_pool_and_link(
+            &row[x as usize..(x + run) as usize],
+            grapheme_pool,
+            url,
+        );
+        x += run;
+    }
+}
+
+/// Write sixel images at their cells.
+///
+/// Attributes and any open hyperlink are reset first so they neither tint
+/// nor wrap the image. The cursor position after a sixel image depends on
+/// the terminal, so the cursor is sent home after each one.
+fn write_images<'a, W: Write>(
+    writer: &mut AnsiWriter<W>,
+    images: impl IntoIterator<Item = &'a PlacedImage>,
+) {
+    for image in images {
+        writer.reset();
+        writer.move_cursor(image.rect.y, image.rect.x);
+        writer.write_raw(&image.sixel);
+        writer.write_str(crate::ansi::CURSOR_HOME);
+        writer.reset_state();
+    }
+}
+
+fn same_pixels(a: &PixelBuffer, b: &PixelBuffer) -> bool {
+    a.width == b.width && a.height == b.height && a.pixels == b.pixels
+}
+
+/// The top-left `width` x `height` pixels of an image.
+fn crop(image: &PixelBuffer, width: u32, height: u32) -> PixelBuffer {
+    let mut cropped = PixelBuffer::new(width, height);
+    for y in 0..height {
+        let start = (y * image.width) as usize;
+        let row = &image.pixels[start..start + width as usize];
+        let out = (y * width) as usize;
+        cropped.pixels[out..out + width as usize].copy_from_slice(row);
     }
+    cropped
 }
 
 impl Drop for Renderer {
     fn drop(&mut self) {
         let _ = self.cleanup();
+        // Hand the buffers' references back so the audited pool drops empty.
+        #[cfg(feature = "pool-audit")]
+        {
+            let buffers = [&mut self.front_buffer, &mut self.back_buffer]
+                .into_iter()
+                .chain(self.layers.values_mut())
+                .chain(self.panels.values_mut().map(|panel| &mut panel.buffer));
+            for buffer in buffers {
+                buffer.drain_orphaned_graphemes(&mut self.grapheme_pool);
+                buffer.release_graphemes(&mut self.grapheme_pool);
+            }
+        }
     }
 }
 
@@ -868,6 +2201,7 @@ mod tests {
     #![allow(clippy::float_cmp)] // Exact float comparison is intentional in tests
     use super::*;
     use crate::cell::Cell;
+    use crate::terminal::Capabilities;
 
     // ============================================
     // RendererOptions Tests
@@ -889,6 +2223,7 @@ mod tests {
             hide_cursor: false,
             enable_mouse: false,
             query_capabilities: false,
+            capabilities: None,
         };
         assert!(!opts.use_alt_screen);
         assert!(!opts.hide_cursor);
@@ -897,10 +2232,17 @@ mod tests {
     }
 
     #[test]
-    fn test_renderer_options_copy() {
-        let opts = RendererOptions::default();
-        let copy = opts;
+    fn test_renderer_options_clone() {
+        let opts = RendererOptions {
+            capabilities: Some(Capabilities::minimal()),
+            ..RendererOptions::default()
+        };
+        let copy = opts.clone();
         assert_eq!(opts.use_alt_screen, copy.use_alt_screen);
+        assert_eq!(
+            copy.capabilities.map(|caps| caps.color),
+            Some(crate::terminal::ColorSupport::None)
+        );
     }
 
     // ============================================
@@ -927,7 +2269,11 @@ mod tests {
             fps: 60.0,
             buffer_bytes: 10000,
             hitgrid_bytes: 5000,
+            panel_cache_bytes: 0,
+            arena_bytes: 0,
+            arena_peak_bytes: 0,
             total_bytes: 15000,
+            last_frame_output: AnsiWriterMetrics::default(),
         };
         let cloned = stats.clone();
         assert_eq!(cloned.frames, 100);
@@ -1088,7 +2434,7 @@ mod tests {
         let id1 = pool.alloc("https://example.com");
         let id2 = pool.alloc("https://other.com");
 
-        assert!(id1 != id2);
+        assert_ne!(id1, id2);
         assert_eq!(pool.get(id1), Some("https://example.com"));
         assert_eq!(pool.get(id2), Some("https://other.com"));
     }
@@ -1262,11 +2608,12 @@ mod tests {
 
     #[test]
     fn test_hit_grid_byte_size() {
-        let grid = HitGrid::new(80, 24);
-        let byte_size = grid.byte_size();
-        // Should be width * height * size_of(Option<u32>)
-        let expected = 80 * 24 * std::mem::size_of::<Option<u32>>();
-        assert_eq!(byte_size, expected);
+        let mut grid = HitGrid::new(80, 24);
+        // Storage grows with registered areas, not with the grid size
+        assert_eq!(grid.byte_size(), 0);
+        grid.register(0, 0, 80, 24, 1);
+        assert!(grid.byte_size() > 0);
+        assert!(grid.byte_size() < 80 * 24 * std::mem::size_of::<Option<u32>>());
     }
 
     impl HitGrid {
@@ -1294,6 +2641,7 @@ mod tests {
                 hide_cursor: false,
                 enable_mouse: false,
                 query_capabilities: false,
+                capabilities: None,
             },
         )
         .expect("test renderer creation should succeed with disabled options")
@@ -1308,7 +2656,9 @@ mod tests {
             r.merge_layers();
         }
         std::mem::swap(&mut r.front_hit_grid, &mut r.back_hit_grid);
+        std::mem::swap(&mut r.front_hit_regions, &mut r.back_hit_regions);
         r.back_hit_grid.clear();
+        r.back_hit_regions.clear();
     }
 
     // --- new() / new_with_options() ---
@@ -1323,6 +2673,7 @@ mod tests {
                 hide_cursor: false,
                 enable_mouse: false,
                 query_capabilities: false,
+                capabilities: None,
             },
         );
         assert!(result.is_ok());
@@ -1585,6 +2936,39 @@ mod tests {
         assert!(matches!(cell.content, crate::cell::CellContent::Char('╭')));
     }
 
+    #[test]
+    fn test_debug_overlay_text_is_readable() {
+        for background in [Rgba::BLACK, Rgba::WHITE, Rgba::rgb(1.0, 1.0, 0.0)] {
+            let mut r = test_renderer(40, 20);
+            r.set_background(background);
+            r.clear();
+            r.draw_debug_overlay();
+
+            let cell = r.buffer().get(1, 1).unwrap();
+            assert!(matches!(cell.content, crate::cell::CellContent::Char('F')));
+            assert!(
+                color::contrast_ratio(cell.fg, cell.bg) >= 4.5,
+                "{background}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_debug_overlay_collects_output_metrics() {
+        let mut r = test_renderer(40, 20);
+        r.present().unwrap();
+        assert_eq!(r.stats().last_frame_output, AnsiWriterMetrics::default());
+
+        r.set_debug_overlay(true);
+        r.present_force().unwrap();
+        let output = r.stats().last_frame_output;
+        assert_eq!(output.total_bytes, r.scratch_buffer.len() as u64);
+        assert!(output.cursor_moves >= 1);
+        assert!(output.sgr_sequences >= 1);
+        assert!(output.text_bytes > 0);
+        assert_eq!(output.osc_sequences, 0);
+    }
+
     // --- Capabilities access ---
 
     #[test]
@@ -1614,6 +2998,74 @@ mod tests {
         assert_eq!(r.link_pool().get(id), Some("https://example.com"));
     }
 
+    #[test]
+    fn test_renderer_draw_link_hit_test_and_lookup() {
+        let mut r = test_renderer(40, 5);
+        let style = crate::style::Style::fg(Rgba::BLUE);
+        let docs = r.draw_link(2, 1, "docs", style, "https://docs.example");
+        let repo = r.draw_link(10, 1, "repo", style, "https://repo.example");
+        assert_ne!(docs, repo);
+        assert_eq!(
+            r.draw_link(2, 3, "again", style, "https://docs.example"),
+            docs
+        );
+        assert_eq!(r.link_pool().ref_count(docs), 2);
+
+        r.present().unwrap();
+        assert_eq!(r.hit_test(2, 1), Some(docs));
+        assert_eq!(r.hit_test(5, 1), Some(docs));
+        assert_eq!(r.hit_test(6, 1), None);
+        assert_eq!(r.hit_test(9, 1), None);
+        assert_eq!(r.hit_test(13, 1), Some(repo));
+        assert_eq!(r.link_at(3, 1), Some("https://docs.example"));
+        assert_eq!(r.link_at(7, 1), None);
+        assert_eq!(r.link_at(10, 1), Some("https://repo.example"));
+
+        // Links stay alive while their frame is on screen...
+        r.clear();
+        assert_eq!(r.link_pool().ref_count(docs), 2);
+        assert_eq!(r.link_pool().ref_count(repo), 1);
+
+        // ...and are released once the next frame replaces it.
+        r.present().unwrap();
+        assert_eq!(r.link_pool().ref_count(docs), 0);
+        assert_eq!(r.link_pool().ref_count(repo), 0);
+        assert_eq!(r.link_at(3, 1), None);
+    }
+
+    #[test]
+    fn test_renderer_links_shared_across_widgets() {
+        let mut r = test_renderer(40, 4);
+        let style = crate::style::Style::NONE;
+        let url = "https://docs.example";
+        // Two widgets linking the same page share one pool entry.
+        let header = r.draw_link(0, 0, "Docs", style, url);
+        let footer = r.draw_link(0, 3, "Read the docs", style, url);
+        assert_eq!(header, footer);
+        assert_eq!(r.link_pool().len(), 1);
+        assert_eq!(r.link_pool().ref_count(header), 2);
+
+        r.present().unwrap();
+        r.draw_link(0, 0, "Docs", style, url);
+        assert_eq!(r.link_pool().ref_count(header), 3);
+        r.clear();
+        assert_eq!(r.link_pool().ref_count(header), 2);
+
+        r.present().unwrap();
+        assert_eq!(r.link_pool().ref_count(header), 0);
+        assert!(r.link_pool().is_empty());
+    }
+
+    #[test]
+    fn test_renderer_clear_releases_back_buffer_links() {
+        let mut r = test_renderer(20, 2);
+        let id = r.draw_link(0, 0, "x", crate::style::Style::NONE, "https://x.example");
+        assert_eq!(r.link_pool().ref_count(id), 1);
+        r.clear();
+        assert_eq!(r.link_pool().ref_count(id), 0);
+        assert_eq!(r.link_pool().get(id), None);
+    }
+
     #[test]
     fn test_renderer_grapheme_pool_usable() {
         let mut r = test_renderer(80, 24);
@@ -1628,6 +3080,55 @@ mod tests {
         // Pool exists and is readable without panic
     }
 
+    #[test]
+    fn test_renderer_composes_buffer_from_worker_pool() {
+        // An offscreen buffer drawn on a worker with its own pool.
+        let mut worker_pool = GraphemePool::new();
+        let mut offscreen = OptimizedBuffer::new(12, 1);
+        offscreen.draw_text_with_pool(
+            &mut worker_pool,
+            0,
+            0,
+            "hi 👨‍👩‍👧 🏳️‍🌈",
+            crate::style::Style::NONE,
+        );
+        let worker_refs = worker_pool.stats().live_refs;
+
+        let mut r = test_renderer(12, 2);
+        let (buffer, pool) = r.buffer_with_pool();
+        buffer.draw_buffer_from_pool(pool, &worker_pool, 0, 0, &offscreen);
+        // Without the source pool the foreign graphemes are dropped.
+        buffer.draw_buffer_with_pool(pool, 0, 1, &offscreen);
+
+        let mut out = Vec::new();
+        let mut writer = AnsiWriter::new(&mut out);
+        for y in 0..2 {
+            write_row_runs(
+                &mut writer,
+                &r.back_buffer,
+                &r.grapheme_pool,
+                &r.link_pool,
+                y,
+                0..12,
+                &[],
+                None,
+            );
+        }
+        writer.flush().unwrap();
+        drop(writer);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("👨‍👩‍👧").count(), 1, "{out:?}");
+        assert_eq!(out.matches("🏳️‍🌈").count(), 1, "{out:?}");
+        assert_eq!(r.grapheme_pool_ref().stats().live_refs, 2);
+
+        r.present().unwrap();
+        r.present().unwrap();
+        assert_eq!(r.grapheme_pool_ref().stats().live_refs, 0);
+        assert_eq!(worker_pool.stats().live_refs, worker_refs);
+        offscreen.release_graphemes(&mut worker_pool);
+        assert_eq!(worker_pool.stats().live_refs, 0);
+    }
+
     // --- Hit testing through Renderer ---
 
     #[test]
@@ -1665,6 +3166,22 @@ mod tests {
         assert_eq!(r.hit_test(5, 5), Some(1));
     }
 
+    #[test]
+    fn test_renderer_multi_hit_scissor_leaves_notch() {
+        let mut r = test_renderer(20, 10);
+        // Content area minus a floating button at (15..20, 0..2), registered
+        // after the button without covering it.
+        r.register_hit_area(15, 0, 5, 2, 2);
+        r.push_hit_scissor_multi(&[ClipRect::new(0, 0, 15, 10), ClipRect::new(15, 2, 5, 8)]);
+        r.register_hit_area(0, 0, 20, 10, 1);
+        r.pop_hit_scissor();
+        commit_hits_for_test(&mut r);
+
+        assert_eq!(r.hit_test(14, 0), Some(1));
+        assert_eq!(r.hit_test(17, 5), Some(1));
+        assert_eq!(r.hit_test(17, 1), Some(2));
+    }
+
     // --- Present integration ---
 
     #[test]
@@ -1741,103 +3258,735 @@ mod tests {
         );
     }
 
-    // --- Clear through Renderer ---
+    /// Output that counts the bytes written to it.
+    struct CountingOutput(std::rc::Rc<std::cell::Cell<usize>>);
 
-    #[test]
-    fn test_renderer_clear_resets_buffer() {
-        let mut r = test_renderer(10, 10);
-        r.buffer()
-            .set(0, 0, Cell::new('X', crate::style::Style::NONE));
-        r.clear();
-        let cell = r.buffer().get(0, 0).unwrap();
-        assert_eq!(cell.bg, Rgba::BLACK);
+    impl Write for CountingOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.set(self.0.get() + buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
     }
 
-    #[test]
-    fn test_renderer_clear_resets_hit_grid() {
-        let mut r = test_renderer(80, 24);
-        r.register_hit_area(0, 0, 80, 24, 1);
-        commit_hits_for_test(&mut r);
-        assert_eq!(r.hit_test(5, 5), Some(1));
+    fn counting_renderer(
+        width: u32,
+        height: u32,
+    ) -> (Renderer, std::rc::Rc<std::cell::Cell<usize>>) {
+        let written = std::rc::Rc::new(std::cell::Cell::new(0));
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let r = Renderer::with_output(
+            width,
+            height,
+            options,
+            Box::new(CountingOutput(written.clone())),
+        )
+        .unwrap();
+        (r, written)
+    }
 
-        r.clear();
-        commit_hits_for_test(&mut r);
-        assert_eq!(r.hit_test(5, 5), None);
+    /// Output that keeps everything written to it.
+    #[derive(Clone, Default)]
+    struct CapturedOutput(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for CapturedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
     }
 
-    // ============================================
-    // Renderer Hit Testing & Scissor Tests (bd-aj8c)
-    // ============================================
+    impl CapturedOutput {
+        fn since(&self, start: usize) -> String {
+            String::from_utf8_lossy(&self.0.borrow()[start..]).into_owned()
+        }
 
-    #[test]
-    fn test_renderer_multiple_non_overlapping_hit_areas() {
-        let mut r = test_renderer(80, 24);
-        r.register_hit_area(0, 0, 10, 5, 1);
-        r.register_hit_area(20, 0, 10, 5, 2);
-        r.register_hit_area(40, 0, 10, 5, 3);
-        commit_hits_for_test(&mut r);
+        fn len(&self) -> usize {
+            self.0.borrow().len()
+        }
+    }
 
-        assert_eq!(r.hit_test(5, 2), Some(1));
-        assert_eq!(r.hit_test(25, 2), Some(2));
-        assert_eq!(r.hit_test(45, 2), Some(3));
-        assert_eq!(r.hit_test(15, 2), None); // gap
+    /// Present two frames on the main screen, using rows 0 and 2, and return
+    /// what cleanup writes.
+    fn cleanup_after_frames(policy: ExitPolicy, use_alt_screen: bool) -> String {
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let mut r = Renderer::with_output(20, 6, options, Box::new(output.clone())).unwrap();
+        r.set_exit_policy(policy);
+        r.clear();
+        r.buffer()
+            .draw_text(0, 0, "title", crate::style::Style::NONE);
+        r.buffer()
+            .draw_text(0, 2, "body", crate::style::Style::NONE);
+        r.present().unwrap();
+        r.clear();
+        r.buffer()
+            .draw_text(0, 1, "later", crate::style::Style::NONE);
+        r.present().unwrap();
+
+        let start = output.len();
+        r.cleanup().unwrap();
+        drop(r);
+        output.since(start)
     }
 
     #[test]
-    fn test_renderer_overlapping_hit_areas_later_wins() {
-        let mut r = test_renderer(80, 24);
-        r.register_hit_area(0, 0, 30, 10, 100);
-        r.register_hit_area(20, 0, 30, 10, 200);
-        commit_hits_for_test(&mut r);
+    fn test_exit_policy_leave_last_frame() {
+        let out = cleanup_after_frames(ExitPolicy::LeaveLastFrame, false);
+        assert!(out.starts_with("\x1b[0m\x1b[3;1H\r\n"), "{out:?}");
+        // Dropping after cleanup doesn't move the cursor again.
+        assert_eq!(out.matches("\x1b[3;1H").count(), 1);
+    }
 
-        // Overlap region: later registration wins
-        assert_eq!(r.hit_test(5, 5), Some(100)); // Only in first
-        assert_eq!(r.hit_test(25, 5), Some(200)); // Overlap, second wins
-        assert_eq!(r.hit_test(45, 5), Some(200)); // Only in second
+    #[test]
+    fn test_exit_policy_clear_on_exit() {
+        let out = cleanup_after_frames(ExitPolicy::ClearOnExit, false);
+        assert!(
+            out.starts_with("\x1b[0m\x1b[1;1H\x1b[2K\x1b[2;1H\x1b[2K\x1b[3;1H\x1b[2K\x1b[H"),
+            "{out:?}"
+        );
+        assert!(!out.contains("\x1b[4;1H"));
     }
 
     #[test]
-    fn test_renderer_hit_test_boundary_conditions() {
-        let mut r = test_renderer(80, 24);
-        r.register_hit_area(10, 5, 20, 10, 1);
-        commit_hits_for_test(&mut r);
+    fn test_exit_policy_is_skipped_when_unchanged_or_on_alt_screen() {
+        let plain = cleanup_after_frames(ExitPolicy::Unchanged, false);
+        assert!(!plain.contains("\x1b[3;1H"), "{plain:?}");
+        assert!(!plain.contains("\x1b[2K"));
+
+        for policy in [ExitPolicy::LeaveLastFrame, ExitPolicy::ClearOnExit] {
+            let out = cleanup_after_frames(policy, true);
+            assert!(!out.contains("\x1b[3;1H"), "{out:?}");
+            assert!(!out.contains("\x1b[2K"));
+        }
+    }
 
-        // Exact boundaries
-        assert_eq!(r.hit_test(10, 5), Some(1)); // Top-left
-        assert_eq!(r.hit_test(29, 5), Some(1)); // Top-right
-        assert_eq!(r.hit_test(10, 14), Some(1)); // Bottom-left
-        assert_eq!(r.hit_test(29, 14), Some(1)); // Bottom-right
+    /// Set up a renderer with forced capabilities, present one red cell, and
+    /// return the setup output, the frame output, and the renderer's color
+    /// mode.
+    fn forced_capabilities_output(caps: Capabilities) -> (String, String, ColorMode) {
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: true,
+            query_capabilities: true,
+            capabilities: Some(caps),
+        };
+        let mut r = Renderer::with_output(10, 2, options, Box::new(output.clone())).unwrap();
+        let setup = output.since(0);
 
-        // Just outside
-        assert_eq!(r.hit_test(9, 5), None);
-        assert_eq!(r.hit_test(30, 5), None);
-        assert_eq!(r.hit_test(10, 4), None);
-        assert_eq!(r.hit_test(10, 15), None);
+        let start = output.len();
+        r.clear();
+        r.buffer()
+            .draw_text(0, 0, "x", crate::style::Style::fg(Rgba::RED));
+        r.present().unwrap();
+        let frame = output.since(start);
+        let mode = r.color_mode();
+        drop(r);
+        (setup, frame, mode)
     }
 
     #[test]
-    fn test_renderer_hit_area_outside_buffer_bounds() {
-        let mut r = test_renderer(20, 20);
-        // Register area that extends beyond buffer
-        r.register_hit_area(15, 15, 20, 20, 1);
-        commit_hits_for_test(&mut r);
-
-        // Inside buffer + area
-        assert_eq!(r.hit_test(18, 18), Some(1));
-        // Outside buffer entirely
-        assert_eq!(r.hit_test(25, 25), None);
+    fn test_forced_capabilities_ideal() {
+        let (setup, frame, mode) = forced_capabilities_output(Capabilities::ideal());
+        assert!(setup.contains(crate::ansi::MOUSE_ON), "{setup:?}");
+        assert!(
+            !setup.contains(&crate::terminal::all_queries()),
+            "{setup:?}"
+        );
+        assert!(frame.contains(crate::ansi::sync::BEGIN), "{frame:?}");
+        assert!(frame.contains("\x1b[38;2;255;0;0m"), "{frame:?}");
+        assert_eq!(mode, ColorMode::TrueColor);
     }
 
     #[test]
-    fn test_renderer_nested_hit_scissors() {
-        let mut r = test_renderer(80, 24);
-
-        // Outer scissor
-        r.push_hit_scissor(ClipRect::new(5, 5, 40, 20));
-        // Inner scissor (narrower)
-        r.push_hit_scissor(ClipRect::new(10, 8, 20, 10));
+    fn test_forced_capabilities_legacy_16color() {
+        let (setup, frame, mode) = forced_capabilities_output(Capabilities::legacy_16color());
+        assert!(setup.contains(crate::ansi::MOUSE_ON), "{setup:?}");
+        assert!(
+            !setup.contains(&crate::terminal::all_queries()),
+            "{setup:?}"
+        );
+        assert!(!frame.contains(crate::ansi::sync::BEGIN), "{frame:?}");
+        assert!(!frame.contains("38;2;"), "{frame:?}");
+        assert!(frame.contains("\x1b[91m"), "{frame:?}");
+        assert_eq!(mode, ColorMode::Color16);
+    }
 
-        // Register hit area that spans beyond both scissors
+    #[test]
+    fn test_forced_capabilities_minimal() {
+        let (setup, frame, mode) = forced_capabilities_output(Capabilities::minimal());
+        assert!(!setup.contains(crate::ansi::MOUSE_ON), "{setup:?}");
+        assert!(
+            !setup.contains(&crate::terminal::all_queries()),
+            "{setup:?}"
+        );
+        assert!(!frame.contains(crate::ansi::sync::BEGIN), "{frame:?}");
+        assert!(!frame.contains("38;"), "{frame:?}");
+        assert!(frame.contains('x'));
+        assert_eq!(mode, ColorMode::NoColor);
+    }
+
+    /// Present a frame of `x` rows, then the same frame with row 1 cleared
+    /// from column 2 to 7 and row 2 from column 10 to the end, and return
+    /// what the second present writes.
+    fn clear_region_output(terminal_background: Option<Rgba>) -> String {
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let mut r = Renderer::with_output(20, 4, options, Box::new(output.clone())).unwrap();
+        let background = Rgba::from_rgb_u8(30, 30, 46);
+        r.set_background(background);
+        r.set_terminal_background(terminal_background);
+        let draw = |r: &mut Renderer| {
+            r.clear();
+            for y in 1..3 {
+                r.buffer()
+                    .draw_text(0, y, &"x".repeat(20), crate::style::Style::NONE);
+            }
+        };
+        draw(&mut r);
+        r.present().unwrap();
+
+        let start = output.len();
+        draw(&mut r);
+        r.clear_region(Rect::new(2, 1, 5, 1));
+        r.clear_region(Rect::new(10, 2, 20, 1));
+        r.present().unwrap();
+        let frame = output.since(start);
+        drop(r);
+        frame
+    }
+
+    #[test]
+    fn test_clear_region_buffer_state() {
+        let (mut r, _) = counting_renderer(20, 4);
+        let background = Rgba::from_rgb_u8(30, 30, 46);
+        r.set_background(background);
+        r.clear();
+        r.buffer()
+            .draw_text(0, 1, &"x".repeat(20), crate::style::Style::bold());
+        r.clear_region(Rect::new(2, 1, 5, 2));
+
+        for x in 0..20 {
+            let cell = r.buffer().get(x, 1).unwrap();
+            if (2..7).contains(&x) {
+                assert_eq!(cell.content, crate::cell::CellContent::Empty);
+                assert!(cell.bg.bits_eq(background));
+                assert!(cell.attributes.is_empty());
+            } else {
+                assert_eq!(cell.content, crate::cell::CellContent::Char('x'), "{x}");
+            }
+        }
+        assert!(r.buffer().get(3, 2).unwrap().bg.bits_eq(background));
+    }
+
+    #[test]
+    fn test_clear_region_erases_default_background() {
+        let background = Rgba::from_rgb_u8(30, 30, 46);
+        let frame = clear_region_output(Some(background));
+        assert!(frame.contains("\x1b[5X"), "{frame:?}");
+        assert!(frame.contains(crate::ansi::CLEAR_LINE_RIGHT), "{frame:?}");
+        assert!(!frame.contains(' '), "{frame:?}");
+        assert!(!frame.contains("48;2;30;30;46"), "{frame:?}");
+
+        // On a terminal showing the previous frame, only the cleared cells
+        // are blanked.
+        let mut parser = vt100::Parser::new(4, 20, 0);
+        parser.process(format!("\x1b[2;1H{0}\x1b[3;1H{0}", "x".repeat(20)).as_bytes());
+        parser.process(frame.as_bytes());
+        let rows: Vec<String> = parser.screen().rows(0, 20).collect();
+        assert_eq!(rows[1], "xx     xxxxxxxxxxxxx");
+        assert_eq!(rows[2], "xxxxxxxxxx");
+    }
+
+    #[test]
+    fn test_clear_region_writes_spaces_for_other_backgrounds() {
+        for terminal_background in [None, Some(Rgba::BLACK)] {
+            let frame = clear_region_output(terminal_background);
+            assert!(!frame.contains("\x1b[5X"), "{frame:?}");
+            assert!(!frame.contains(crate::ansi::CLEAR_LINE_RIGHT), "{frame:?}");
+            assert!(frame.contains("48;2;30;30;46"), "{frame:?}");
+            assert_eq!(frame.matches(' ').count(), 15, "{frame:?}");
+        }
+    }
+
+    /// Present a frame, then one changing four full rows and part of
+    /// another; return the whole output and the second frame's.
+    fn banded_frames(scroll_region: bool) -> (Vec<u8>, Vec<u8>) {
+        use crate::style::Style;
+
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let mut r = Renderer::with_output(20, 10, options, Box::new(output.clone())).unwrap();
+        if scroll_region {
+            r.terminal.set_scroll_region(0, 9).unwrap();
+        }
+        // The first frame clears buffers to the renderer background.
+        r.present().unwrap();
+        for y in 0..10 {
+            r.buffer().draw_text(0, y, &format!("row {y}"), Style::NONE);
+        }
+        r.present().unwrap();
+
+        let start = output.len();
+        for y in 0..10 {
+            r.buffer().draw_text(0, y, &format!("row {y}"), Style::NONE);
+        }
+        r.buffer().draw_text(4, 0, "zero", Style::NONE);
+        for y in 3..7 {
+            let text: String = ('a'..='t').cycle().skip(y as usize).take(20).collect();
+            r.buffer().draw_text(0, y, &text, Style::fg(Rgba::RED));
+        }
+        r.present().unwrap();
+        assert_eq!(r.cached_diff.dirty_rects.len(), 2);
+
+        drop(r);
+        let bytes = output.0.borrow().clone();
+        let frame = bytes[start..].to_vec();
+        (bytes, frame)
+    }
+
+    #[test]
+    fn test_full_width_rect_rows_wrap_instead_of_moving() {
+        let cursor_moves = |bytes: &[u8]| {
+            let mut writer = AnsiWriter::new(Vec::new()).with_metrics();
+            writer.write_raw(bytes);
+            writer.flush().unwrap();
+            writer.metrics().unwrap().cursor_moves
+        };
+        let screen = |bytes: &[u8]| {
+            let mut parser = vt100::Parser::new(10, 20, 0);
+            parser.process(&expand_rep(bytes));
+            let rows: Vec<String> = parser.screen().rows(0, 20).collect();
+            let red = parser.screen().cell(5, 19).unwrap().fgcolor();
+            (rows, red)
+        };
+
+        let (wrapped, wrapped_frame) = banded_frames(false);
+        let (moved, moved_frame) = banded_frames(true);
+        // Rows 4 to 6 follow row 3 without a cursor move.
+        assert_eq!(cursor_moves(&wrapped_frame) + 6, cursor_moves(&moved_frame));
+
+        let (rows, red) = screen(&wrapped);
+        assert_eq!(screen(&moved), (rows.clone(), red));
+        assert_eq!(rows[0].trim_end(), "row zero");
+        assert_eq!(rows[4], "efghijklmnopqrstabcd");
+        assert_eq!(rows[7].trim_end(), "row 7");
+        assert_eq!(red, vt100::Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn test_fill_region_respects_scissor_and_layers() {
+        let (mut r, _) = counting_renderer(20, 4);
+        r.clear();
+        r.buffer().push_scissor(ClipRect::new(0, 0, 4, 4));
+        r.fill_region(Rect::new(2, 0, 4, 1), Rgba::RED);
+        r.buffer().pop_scissor();
+        assert!(r.buffer().get(3, 0).unwrap().bg.bits_eq(Rgba::RED));
+        assert!(r.buffer().get(4, 0).unwrap().bg.bits_eq(Rgba::BLACK));
+
+        r.render_to_layer(1, |_| {});
+        r.fill_region(Rect::new(0, 2, 2, 1), Rgba::BLUE);
+        assert!(r.buffer().get(0, 2).unwrap().bg.bits_eq(Rgba::BLACK));
+        r.merge_layers();
+        assert!(r.buffer().get(0, 2).unwrap().bg.bits_eq(Rgba::BLUE));
+    }
+
+    #[test]
+    fn test_idle_frame_writes_nothing() {
+        let (mut r, written) = counting_renderer(40, 10);
+        r.clear();
+        r.present().unwrap();
+        assert!(written.get() > 0);
+
+        let before = written.get();
+        r.present().unwrap();
+        assert_eq!(written.get(), before);
+        assert_eq!(r.stats().frames, 2);
+        assert_eq!(r.stats().last_frame_cells, 0);
+
+        // Clearing again without drawing is still idle.
+        r.clear();
+        r.present().unwrap();
+        assert_eq!(written.get(), before);
+    }
+
+    #[test]
+    fn test_single_set_after_idle_frame_is_diffed() {
+        let (mut r, written) = counting_renderer(40, 10);
+        r.present().unwrap();
+        r.present().unwrap();
+        let before = written.get();
+
+        r.buffer()
+            .set(3, 2, Cell::new('X', crate::style::Style::NONE));
+        r.present().unwrap();
+        assert!(written.get() > before);
+        assert_eq!(r.stats().last_frame_cells, 1);
+        assert_eq!(
+            r.front_buffer().get(3, 2).unwrap().content,
+            crate::cell::CellContent::Char('X')
+        );
+
+        // The next blank frame erases it, and only then is the screen idle.
+        let before = written.get();
+        r.present().unwrap();
+        assert!(written.get() > before);
+        let before = written.get();
+        r.present().unwrap();
+        assert_eq!(written.get(), before);
+    }
+
+    #[test]
+    fn test_idle_frame_is_not_diffed_when_forced_or_marked() {
+        let (mut r, written) = counting_renderer(40, 10);
+        r.present().unwrap();
+        r.present().unwrap();
+
+        let before = written.get();
+        r.invalidate();
+        r.present().unwrap();
+        assert!(written.get() > before);
+
+        let before = written.get();
+        r.mark_region_dirty(Rect::new(0, 0, 5, 1));
+        r.present().unwrap();
+        assert!(written.get() > before);
+    }
+
+    #[test]
+    fn test_idle_frame_still_commits_hit_areas() {
+        let (mut r, _) = counting_renderer(40, 10);
+        r.present().unwrap();
+        r.present().unwrap();
+        r.register_hit_area(2, 2, 4, 1, 7);
+        r.present().unwrap();
+        assert_eq!(r.stats().last_frame_cells, 0);
+        assert_eq!(r.hit_test(3, 2), Some(7));
+    }
+
+    #[test]
+    fn test_idle_frame_skips_diffing() {
+        let (mut r, _) = counting_renderer(40, 10);
+        r.clear();
+        r.present().unwrap();
+
+        // Present branches on this before computing any diff.
+        r.clear();
+        assert!(r.is_idle());
+        r.present().unwrap();
+        assert!(r.cached_diff.is_empty());
+
+        // Touching the buffer without changing it costs a full diff.
+        r.clear();
+        r.buffer().cells_mut();
+        assert!(!r.is_idle());
+    }
+
+    // --- Cached panels ---
+
+    fn draw_sidebar(buffer: &mut OptimizedBuffer, pool: &mut GraphemePool) {
+        let style = crate::style::Style::fg(Rgba::GREEN).with_bg(Rgba::BLUE);
+        buffer.fill_rect(0, 0, buffer.width(), buffer.height(), Rgba::BLUE);
+        buffer.draw_text_with_pool(pool, 1, 0, "Files 👍🏽", style);
+        buffer.draw_text_with_pool(pool, 1, 1, "日本語", style);
+        // Row 2 is left transparent over the frame's background.
+        buffer.fill_rect(0, 2, buffer.width(), 1, Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    fn test_cached_panel_renders_only_on_version_change() {
+        let mut r = test_renderer(40, 10);
+        let calls = std::cell::Cell::new(0);
+        let frame = |r: &mut Renderer, version, rect| {
+            r.cached_panel("sidebar", version, rect, |buffer, pool| {
+                calls.set(calls.get() + 1);
+                draw_sidebar(buffer, pool);
+            });
+            r.present().unwrap();
+        };
+
+        let rect = Rect::new(2, 1, 12, 4);
+        for _ in 0..3 {
+            frame(&mut r, 1, rect);
+        }
+        assert_eq!(calls.get(), 1);
+        frame(&mut r, 2, rect);
+        frame(&mut r, 2, rect);
+        assert_eq!(calls.get(), 2);
+
+        // A new size redraws even at the same version.
+        frame(&mut r, 2, Rect::new(2, 1, 14, 4));
+        assert_eq!(calls.get(), 3);
+
+        assert!(r.evict("sidebar"));
+        assert!(!r.evict("sidebar"));
+        frame(&mut r, 2, Rect::new(2, 1, 14, 4));
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_cached_panel_output_matches_fresh_render() {
+        let mut r = test_renderer(40, 10);
+        let rect = Rect::new(3, 2, 12, 4);
+        let frame = |r: &mut Renderer| {
+            r.clear();
+            r.buffer()
+                .draw_text(0, 4, "under the panel", crate::style::Style::NONE);
+            r.cached_panel("sidebar", 7, rect, draw_sidebar);
+            let cells = r.buffer().cells().to_vec();
+            let text = (0..10)
+                .map(|y| {
+                    (0..40)
+                        .map(|x| {
+                            let cell = r.back_buffer.get(x, y).unwrap();
+                            match cell.content {
+                                crate::cell::CellContent::Grapheme(id) => {
+                                    r.grapheme_pool.get(id).unwrap_or("?").to_string()
+                                }
+                                _ => cell.content.as_char().unwrap_or(' ').to_string(),
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            r.present().unwrap();
+            (cells, text)
+        };
+
+        let (rendered, rendered_text) = frame(&mut r);
+        let (cached, cached_text) = frame(&mut r);
+        assert_eq!(rendered, cached);
+        assert_eq!(rendered_text, cached_text);
+        assert!(rendered_text[2].contains("Files 👍🏽"));
+        assert!(rendered_text[4].starts_with("under"));
+    }
+
+    #[test]
+    fn test_panel_cache_memory_and_grapheme_release() {
+        let mut r = test_renderer(40, 10);
+        r.present().unwrap();
+        let baseline = r.grapheme_pool.active_count();
+        assert_eq!(r.stats().panel_cache_bytes, 0);
+
+        r.cached_panel("a", 1, Rect::new(0, 0, 12, 3), draw_sidebar);
+        r.cached_panel("b", 1, Rect::new(0, 5, 12, 3), draw_sidebar);
+        r.present().unwrap();
+        let stats = r.stats();
+        assert!(stats.panel_cache_bytes >= 2 * 12 * 3 * std::mem::size_of::<Cell>());
+        assert_eq!(
+            stats.total_bytes,
+            stats.buffer_bytes + stats.hitgrid_bytes + stats.panel_cache_bytes
+        );
+
+        r.evict("a");
+        r.clear_cache();
+        // Two more frames flush the graphemes out of the front and back
+        // buffers.
+        r.present().unwrap();
+        r.present().unwrap();
+        assert_eq!(r.stats().panel_cache_bytes, 0);
+        assert_eq!(r.grapheme_pool.active_count(), baseline);
+    }
+
+    // --- Clear through Renderer ---
+
+    #[test]
+    fn test_renderer_clear_resets_buffer() {
+        let mut r = test_renderer(10, 10);
+        r.buffer()
+            .set(0, 0, Cell::new('X', crate::style::Style::NONE));
+        r.clear();
+        let cell = r.buffer().get(0, 0).unwrap();
+        assert_eq!(cell.bg, Rgba::BLACK);
+    }
+
+    #[test]
+    fn test_renderer_clear_resets_hit_grid() {
+        let mut r = test_renderer(80, 24);
+        r.register_hit_area(0, 0, 80, 24, 1);
+        commit_hits_for_test(&mut r);
+        assert_eq!(r.hit_test(5, 5), Some(1));
+
+        r.clear();
+        commit_hits_for_test(&mut r);
+        assert_eq!(r.hit_test(5, 5), None);
+    }
+
+    #[test]
+    fn test_hit_regions_carry_payload_after_present() {
+        let mut r = test_renderer(40, 10);
+        let button = HitRegion {
+            id: 7,
+            name: Some("save"),
+            data: 0xdead_beef,
+        };
+        r.register_hit_region(Rect::new(2, 1, 10, 3), button);
+        r.register_hit_area(20, 1, 5, 1, 8);
+
+        // Nothing is visible until the frame is presented.
+        assert_eq!(r.hit_test_region(5, 2), None);
+        r.present().unwrap();
+
+        let info = r.hit_test_region(5, 2).unwrap();
+        assert_eq!(info.id, 7);
+        assert_eq!(info.name, Some("save"));
+        assert_eq!(info.data, 0xdead_beef);
+        assert_eq!(info.rect, Rect::new(2, 1, 10, 3));
+        assert_eq!(r.hit_test(5, 2), Some(7));
+
+        let plain = r.hit_test_region(21, 1).unwrap();
+        assert_eq!((plain.id, plain.name, plain.data), (8, None, 0));
+        assert_eq!(plain.rect, Rect::new(20, 1, 5, 1));
+
+        // The next frame registers nothing, so the regions are gone.
+        r.present().unwrap();
+        assert_eq!(r.hit_test_region(5, 2), None);
+        assert_eq!(r.hit_test(21, 1), None);
+    }
+
+    #[test]
+    fn test_hit_regions_follow_layer_compositing() {
+        let mut r = test_renderer(10, 2);
+        r.register_hit_region(
+            Rect::new(0, 0, 10, 2),
+            HitRegion {
+                data: 1,
+                ..HitRegion::new(1)
+            },
+        );
+        r.render_to_layer(1, |_| {});
+        r.register_hit_region(
+            Rect::new(0, 0, 3, 1),
+            HitRegion {
+                data: 2,
+                ..HitRegion::new(2)
+            },
+        );
+        r.present().unwrap();
+
+        assert_eq!(r.hit_test_region(1, 0).map(|info| info.data), Some(2));
+        assert_eq!(r.hit_test_region(5, 0).map(|info| info.data), Some(1));
+    }
+
+    #[test]
+    fn test_hit_region_keeps_rect_when_clipped() {
+        let mut r = test_renderer(20, 5);
+        r.push_hit_scissor(ClipRect::new(0, 0, 5, 5));
+        r.register_hit_region(Rect::new(3, 0, 10, 1), HitRegion::new(4));
+        r.pop_hit_scissor();
+        commit_hits_for_test(&mut r);
+
+        assert_eq!(r.hit_test_region(6, 0), None);
+        let info = r.hit_test_region(4, 0).unwrap();
+        assert_eq!(info.rect, Rect::new(3, 0, 10, 1));
+    }
+
+    // ============================================
+    // Renderer Hit Testing & Scissor Tests (bd-aj8c)
+    // ============================================
+
+    #[test]
+    fn test_renderer_multiple_non_overlapping_hit_areas() {
+        let mut r = test_renderer(80, 24);
+        r.register_hit_area(0, 0, 10, 5, 1);
+        r.register_hit_area(20, 0, 10, 5, 2);
+        r.register_hit_area(40, 0, 10, 5, 3);
+        commit_hits_for_test(&mut r);
+
+        assert_eq!(r.hit_test(5, 2), Some(1));
+        assert_eq!(r.hit_test(25, 2), Some(2));
+        assert_eq!(r.hit_test(45, 2), Some(3));
+        assert_eq!(r.hit_test(15, 2), None); // gap
+    }
+
+    #[test]
+    fn test_renderer_overlapping_hit_areas_later_wins() {
+        let mut r = test_renderer(80, 24);
+        r.register_hit_area(0, 0, 30, 10, 100);
+        r.register_hit_area(20, 0, 30, 10, 200);
+        commit_hits_for_test(&mut r);
+
+        // Overlap region: later registration wins
+        assert_eq!(r.hit_test(5, 5), Some(100)); // Only in first
+        assert_eq!(r.hit_test(25, 5), Some(200)); // Overlap, second wins
+        assert_eq!(r.hit_test(45, 5), Some(200)); // Only in second
+    }
+
+    #[test]
+    fn test_renderer_hit_test_boundary_conditions() {
+        let mut r = test_renderer(80, 24);
+        r.register_hit_area(10, 5, 20, 10, 1);
+        commit_hits_for_test(&mut r);
+
+        // Exact boundaries
+        assert_eq!(r.hit_test(10, 5), Some(1)); // Top-left
+        assert_eq!(r.hit_test(29, 5), Some(1)); // Top-right
+        assert_eq!(r.hit_test(10, 14), Some(1)); // Bottom-left
+        assert_eq!(r.hit_test(29, 14), Some(1)); // Bottom-right
+
+        // Just outside
+        assert_eq!(r.hit_test(9, 5), None);
+        assert_eq!(r.hit_test(30, 5), None);
+        assert_eq!(r.hit_test(10, 4), None);
+        assert_eq!(r.hit_test(10, 15), None);
+    }
+
+    #[test]
+    fn test_renderer_hit_area_outside_buffer_bounds() {
+        let mut r = test_renderer(20, 20);
+        // Register area that extends beyond buffer
+        r.register_hit_area(15, 15, 20, 20, 1);
+        commit_hits_for_test(&mut r);
+
+        // Inside buffer + area
+        assert_eq!(r.hit_test(18, 18), Some(1));
+        // Outside buffer entirely
+        assert_eq!(r.hit_test(25, 25), None);
+    }
+
+    #[test]
+    fn test_renderer_nested_hit_scissors() {
+        let mut r = test_renderer(80, 24);
+
+        // Outer scissor
+        r.push_hit_scissor(ClipRect::new(5, 5, 40, 20));
+        // Inner scissor (narrower)
+        r.push_hit_scissor(ClipRect::new(10, 8, 20, 10));
+
+        // Register hit area that spans beyond both scissors
         r.register_hit_area(0, 0, 80, 24, 1);
         commit_hits_for_test(&mut r);
 
@@ -1959,13 +4108,71 @@ mod tests {
             ),
         );
 
-        // Create an overlay layer but do not draw into it.
-        r.render_to_layer(1, |_| {});
+        // Create an overlay layer but do not draw into it.
+        r.render_to_layer(1, |_| {});
+        r.merge_layers();
+
+        let cell = r.buffer().get(0, 0).unwrap();
+        assert_eq!(cell.fg, Rgba::RED);
+        assert_eq!(cell.bg, Rgba::BLACK);
+    }
+
+    #[test]
+    fn test_merge_layers_honors_layer_blend_mode() {
+        let mut r = test_renderer(2, 1);
+        let base = crate::style::Style::builder()
+            .fg(Rgba::WHITE)
+            .bg(Rgba::rgb(1.0, 0.5, 0.0))
+            .build();
+        r.buffer().set(0, 0, Cell::new('A', base));
+        r.buffer().set(1, 0, Cell::new('B', base));
+
+        assert_eq!(r.layer_blend_mode(1), BlendMode::Over);
+        r.set_layer_blend_mode(1, BlendMode::Multiply);
+        assert_eq!(r.layer_blend_mode(1), BlendMode::Multiply);
+        r.render_to_layer(1, |layer| {
+            // Dim the first cell only; the second stays transparent
+            let mut dim = Cell::clear(Rgba::rgb(0.5, 0.5, 0.5));
+            dim.fg = Rgba::rgb(0.5, 0.5, 0.5);
+            layer.set(0, 0, dim);
+        });
+        r.merge_layers();
+
+        let dimmed = r.buffer().get(0, 0).unwrap();
+        assert!(matches!(
+            dimmed.content,
+            crate::cell::CellContent::Char('A')
+        ));
+        assert_eq!(dimmed.fg.to_rgb_u8(), (128, 128, 128));
+        assert_eq!(dimmed.bg.to_rgb_u8(), (128, 64, 0));
+        let untouched = r.buffer().get(1, 0).unwrap();
+        assert_eq!(untouched.bg, Rgba::rgb(1.0, 0.5, 0.0));
+
+        r.set_layer_blend_mode(1, BlendMode::Over);
+        assert_eq!(r.layer_blend_mode(1), BlendMode::Over);
+    }
+
+    #[test]
+    fn test_merge_layers_honors_layer_opacity_per_channel() {
+        let mut r = test_renderer(1, 1);
+        r.buffer().set(0, 0, Cell::clear(Rgba::BLACK));
+
+        assert_eq!(r.layer_opacity(1), Opacity::OPAQUE);
+        // Solid text over a fully faded-out panel background.
+        r.set_layer_opacity(1, Opacity::new(1.0, 0.0));
+        r.render_to_layer(1, |layer| {
+            let style = crate::style::Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+            layer.set(0, 0, Cell::new('X', style));
+        });
         r.merge_layers();
 
         let cell = r.buffer().get(0, 0).unwrap();
-        assert_eq!(cell.fg, Rgba::RED);
-        assert_eq!(cell.bg, Rgba::BLACK);
+        assert!(matches!(cell.content, crate::cell::CellContent::Char('X')));
+        assert_eq!(cell.fg.to_rgb_u8(), (255, 255, 255));
+        assert_eq!(cell.bg.to_rgb_u8(), (0, 0, 0));
+
+        r.set_layer_opacity(1, 1.0);
+        assert_eq!(r.layer_opacity(1), Opacity::OPAQUE);
     }
 
     #[test]
@@ -2102,6 +4309,34 @@ mod tests {
         assert!(!r.capabilities().sync_output);
     }
 
+    #[test]
+    fn test_degradations_follow_color_mode_and_capabilities() {
+        let mut r = test_renderer(80, 24);
+        *r.capabilities_mut() = crate::terminal::Capabilities::ideal();
+        r.set_color_mode(ColorMode::TrueColor);
+        assert_eq!(r.degradations(), []);
+
+        *r.capabilities_mut() = crate::terminal::Capabilities::default();
+        r.set_color_mode(ColorMode::Color256);
+        let degradations = r.degradations();
+        assert_eq!(
+            degradations,
+            [
+                Degradation::ColorQuantized {
+                    mode: ColorMode::Color256
+                },
+                Degradation::SyncOutputDisabled,
+                Degradation::LineDrawingFallback {
+                    charset: LineDrawing::DecGraphics
+                },
+            ]
+        );
+        assert_eq!(
+            degradations[0].to_string(),
+            "colors are quantized to the 256-color palette"
+        );
+    }
+
     #[test]
     fn test_capabilities_hyperlinks_override() {
         let mut r = test_renderer(80, 24);
@@ -2121,4 +4356,826 @@ mod tests {
         // Present should succeed with new background
         assert!(r.present().is_ok());
     }
+
+    // ============================================
+    // Run Batching Tests
+    // ============================================
+
+    fn fill_row(width: u32) -> OptimizedBuffer {
+        let mut buffer = OptimizedBuffer::new(width, 1);
+        let style = crate::style::Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+        for x in 0..width {
+            buffer.set(x, 0, Cell::new(' ', style));
+        }
+        buffer
+    }
+
+    fn encode_rows(buffer: &OptimizedBuffer, links: &LinkPool, rep: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut writer = AnsiWriter::new(&mut out);
+        writer.set_rep(rep);
+        for y in 0..buffer.height() {
+            write_row_runs(
+                &mut writer,
+                buffer,
+                &GraphemePool::new(),
+                links,
+                y,
+                0..buffer.width(),
+                &[],
+                None,
+            );
+        }
+        writer.flush().unwrap();
+        drop(writer);
+        out
+    }
+
+    /// Expand REP sequences, which the vt100 parser does not implement,
+    /// into copies of the preceding ASCII char.
+    fn expand_rep(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let digits = bytes[i..]
+                .strip_prefix(b"\x1b[")
+                .map(|rest| rest.iter().take_while(|b| b.is_ascii_digit()).count())
+                .filter(|&n| n > 0 && bytes.get(i + 2 + n) == Some(&b'b'));
+            if let Some(n) = digits {
+                let count: usize = std::str::from_utf8(&bytes[i + 2..i + 2 + n])
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                let last = *out.last().unwrap();
+                out.extend(std::iter::repeat_n(last, count));
+                i += 3 + n;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_full_width_fill_with_rep() {
+        let out = encode_rows(&fill_row(80), &LinkPool::new(), true);
+        assert_eq!(
+            out,
+            b"\x1b[38;2;255;255;255m\x1b[48;2;0;0;255m \x1b[79b".as_slice()
+        );
+    }
+
+    #[test]
+    fn test_full_width_fill_without_rep() {
+        let out = encode_rows(&fill_row(80), &LinkPool::new(), false);
+        let mut expected = b"\x1b[38;2;255;255;255m\x1b[48;2;0;0;255m".to_vec();
+        expected.extend([b' '; 80]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_short_runs_are_written_out() {
+        // ESC[2b is longer than two spaces.
+        let out = encode_rows(&fill_row(3), &LinkPool::new(), true);
+        assert!(out.ends_with(b"m   "));
+    }
+
+    #[test]
+    fn test_repeats_inside_a_styled_run_use_rep() {
+        let style = crate::style::Style::fg(Rgba::WHITE).with_bg(Rgba::BLUE);
+        let mut buffer = OptimizedBuffer::new(24, 1);
+        buffer.draw_text(0, 0, "ab--------------------cd", style);
+        let out = encode_rows(&buffer, &LinkPool::new(), true);
+        assert_eq!(
+            out,
+            b"\x1b[38;2;255;255;255m\x1b[48;2;0;0;255mab-\x1b[19bcd".as_slice()
+        );
+    }
+
+    #[test]
+    fn test_runs_break_at_style_wide_chars_and_links() {
+        let mut links = LinkPool::new();
+        let link = links.alloc("https://example.com");
+        let plain = crate::style::Style::fg(Rgba::WHITE);
+        let linked = plain.with_link(link);
+
+        let mut buffer = OptimizedBuffer::new(40, 2);
+        for x in 0..40 {
+            buffer.set(x, 0, Cell::new('-', plain));
+            buffer.set(x, 1, Cell::new('=', plain.with_bold()));
+        }
+        for x in 10..20 {
+            buffer.set(x, 0, Cell::new('-', linked));
+        }
+        buffer.draw_text(25, 0, "漢漢", plain);
+        for x in 30..40 {
+            buffer.set(x, 1, Cell::new('=', plain.with_bold().with_bg(Rgba::RED)));
+        }
+
+        let run = |x, y: u32| {
+            let row = &buffer.cells()[y as usize * 40..(y as usize + 1) * 40];
+            style_run(row, x, 40)
+        };
+        assert_eq!(run(0, 0), 10);
+        assert_eq!(run(10, 0), 10);
+        assert_eq!(run(20, 0), 5);
+        assert_eq!(run(25, 0), 1);
+        assert_eq!(run(0, 1), 30);
+
+        let out = encode_rows(&buffer, &links, true);
+        let mut parser = vt100::Parser::new(2, 40, 0);
+        parser.process(&expand_rep(&out));
+        let screen = parser.screen();
+        assert_eq!(
+            screen.contents_between(0, 0, 0, 40),
+            format!("{}漢漢{}", "-".repeat(25), "-".repeat(11))
+        );
+        assert_eq!(screen.contents_between(1, 0, 1, 40), "=".repeat(40));
+        for x in 0..40 {
+            let cell = screen.cell(1, x).unwrap();
+            assert!(cell.bold());
+            let bg = if x < 30 {
+                vt100::Color::Rgb(0, 0, 0)
+            } else {
+                vt100::Color::Rgb(255, 0, 0)
+            };
+            assert_eq!(cell.bgcolor(), bg, "column {x}");
+        }
+    }
+
+    #[test]
+    fn test_styled_text_is_written_as_one_run() {
+        let style = crate::style::Style::fg(Rgba::GREEN).with_bg(Rgba::BLACK);
+        let mut buffer = OptimizedBuffer::new(60, 2);
+        buffer.draw_text(
+            0,
+            0,
+            "The quick brown fox jumps over the lazy dog, twice over!!",
+            style,
+        );
+        buffer.draw_text(0, 1, "Status: all systems nominal    ", style.with_bold());
+        let pool = GraphemePool::new();
+        let links = LinkPool::new();
+
+        // Cell-at-a-time output, as the diff path wrote it before batching.
+        let mut per_cell = AnsiWriter::new(Vec::new()).with_metrics();
+        for y in 0..2 {
+            for x in 0..60 {
+                per_cell.move_cursor(y, x);
+                per_cell.write_cell_with_pool_and_link(buffer.get(x, y).unwrap(), &pool, None);
+            }
+        }
+        per_cell.flush().unwrap();
+
+        let mut batched = AnsiWriter::new(Vec::new()).with_metrics();
+        for y in 0..2 {
+            write_row_runs(&mut batched, &buffer, &pool, &links, y, 0..60, &[], None);
+        }
+        batched.flush().unwrap();
+
+        let (per_cell_metrics, batched_metrics) =
+            (per_cell.metrics().unwrap(), batched.metrics().unwrap());
+        assert!(batched_metrics.total_bytes <= per_cell_metrics.total_bytes);
+        // One move per row, and no more style changes than the cell-at-a-time
+        // encoding needs.
+        assert_eq!(batched_metrics.cursor_moves, 2);
+        assert!(batched_metrics.sgr_sequences <= per_cell_metrics.sgr_sequences);
+        assert_eq!(batched_metrics.text_bytes, 120);
+
+        let screen = |bytes: &[u8]| {
+            let mut parser = vt100::Parser::new(2, 60, 0);
+            parser.process(bytes);
+            parser.screen().clone()
+        };
+        let (expected, actual) = (screen(per_cell.buffer()), screen(batched.buffer()));
+        for y in 0..2 {
+            for x in 0..60 {
+                let (a, b) = (expected.cell(y, x).unwrap(), actual.cell(y, x).unwrap());
+                assert_eq!(a.contents(), b.contents(), "({x},{y})");
+                assert_eq!(a.fgcolor(), b.fgcolor(), "({x},{y})");
+                assert_eq!(a.bgcolor(), b.bgcolor(), "({x},{y})");
+                assert_eq!(a.bold(), b.bold(), "({x},{y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_runs_emit_link_params() {
+        let mut links = LinkPool::new();
+        let link = links.alloc_with_params("https://example.com", "group=docs");
+        let mut buffer = OptimizedBuffer::new(4, 1);
+        buffer.draw_text(0, 0, "docs", crate::style::Style::NONE.with_link(link));
+
+        let out = String::from_utf8(encode_rows(&buffer, &links, false)).unwrap();
+        assert!(
+            out.contains(&format!(
+                "\x1b]8;id={link}:group=docs;https://example.com\x1b\\"
+            )),
+            "{out:?}"
+        );
+        assert!(out.ends_with("docs"));
+    }
+
+    #[test]
+    fn test_rep_output_reconstructs_like_plain_output() {
+        let mut buffer = fill_row(80);
+        buffer.draw_text(5, 0, "status: ok", crate::style::Style::fg(Rgba::GREEN));
+        let links = LinkPool::new();
+        let with_rep = encode_rows(&buffer, &links, true);
+        let without = encode_rows(&buffer, &links, false);
+        assert!(with_rep.len() < without.len());
+
+        let screen = |bytes: &[u8]| {
+            let mut parser = vt100::Parser::new(1, 80, 0);
+            parser.process(bytes);
+            parser.screen().clone()
+        };
+        assert_eq!(
+            screen(&expand_rep(&with_rep)).contents_formatted(),
+            screen(&without).contents_formatted()
+        );
+    }
+
+    // ============================================
+    // Sixel Image Tests
+    // ============================================
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    fn test_image() -> PixelBuffer {
+        let mut image = PixelBuffer::new(20, 40);
+        image.fill(Rgba::RED);
+        image
+    }
+
+    #[test]
+    fn test_draw_image_without_sixel_support_emits_nothing() {
+        let mut r = test_renderer(20, 5);
+        r.capabilities_mut().sixel = false;
+        assert!(!r.draw_image(0, 0, &test_image()));
+        r.present().unwrap();
+        assert!(!contains(&r.scratch_buffer, b"\x1bP"));
+    }
+
+    #[test]
+    fn test_image_covers_cells_until_removed() {
+        let mut r = test_renderer(80, 24);
+        r.capabilities_mut().sixel = true;
+        r.set_cell_pixel_size(10, 20);
+        let style = crate::style::Style::fg(Rgba::WHITE);
+
+        // Frame 1: the image is sent after the cells.
+        r.clear();
+        r.buffer().draw_text(0, 1, "XXXXXXXX", style);
+        assert!(r.draw_image(3, 1, &test_image()));
+        assert_eq!(r.images[0].rect, Rect::new(3, 1, 2, 2));
+        r.present().unwrap();
+        let sixel = sixel::encode(&test_image(), SixelOptions::default());
+        assert!(contains(&r.scratch_buffer, &sixel));
+
+        // Frame 2: the unchanged image is not re-sent and the cells it
+        // covers are not written over it.
+        r.clear();
+        r.buffer().draw_text(0, 1, "YYYYYYYY", style);
+        r.draw_image(3, 1, &test_image());
+        r.present().unwrap();
+        assert!(!contains(&r.scratch_buffer, b"\x1bP"));
+        assert!(contains(&r.scratch_buffer, b"YYY"));
+        assert!(!contains(&r.scratch_buffer, b"YYYY"));
+
+        // Frame 3: without the image, its cells are repainted.
+        r.clear();
+        r.buffer().draw_text(0, 1, "YYYYYYYY", style);
+        r.present().unwrap();
+        assert!(!contains(&r.scratch_buffer, b"\x1bP"));
+        assert!(contains(&r.scratch_buffer, b"YY"));
+    }
+
+    #[test]
+    fn test_image_after_styled_cells_resets_attributes_first() {
+        let mut r = test_renderer(80, 24);
+        r.capabilities_mut().sixel = true;
+        r.set_cell_pixel_size(10, 20);
+        r.clear();
+        // The styled text ends the last row, so no later cell resets it.
+        r.buffer().draw_text(
+            76,
+            23,
+            "bold",
+            crate::style::Style::fg(Rgba::GREEN).with_bold(),
+        );
+        assert!(r.draw_image(0, 0, &test_image()));
+        r.present().unwrap();
+
+        let out = &r.scratch_buffer;
+        let sixel = sixel::encode(&test_image(), SixelOptions::default());
+        let find = |needle: &[u8]| out.windows(needle.len()).position(|w| w == needle);
+        let text = find(b"bold").expect("text is sent");
+        let image = find(&sixel).expect("image is sent");
+        assert!(contains(&out[text..image], crate::ansi::RESET.as_bytes()));
+    }
+
+    #[test]
+    fn test_image_next_to_link_closes_link_first() {
+        let mut r = test_renderer(80, 24);
+        r.capabilities_mut().sixel = true;
+        r.set_cell_pixel_size(10, 20);
+        r.clear();
+        // The link ends the last row, so no later cell closes it.
+        r.draw_link(
+            76,
+            23,
+            "docs",
+            crate::style::Style::NONE,
+            "https://docs.example",
+        );
+        assert!(r.draw_image(0, 0, &test_image()));
+        r.present().unwrap();
+
+        let out = &r.scratch_buffer;
+        let sixel = sixel::encode(&test_image(), SixelOptions::default());
+        let find = |needle: &[u8]| out.windows(needle.len()).position(|w| w == needle);
+        let link_end = find(crate::ansi::HYPERLINK_END.as_bytes()).expect("link is closed");
+        let image = find(&sixel).expect("image is sent");
+        assert!(link_end < image);
+    }
+
+    #[test]
+    fn test_image_is_cropped_to_screen() {
+        let mut r = test_renderer(4, 2);
+        r.capabilities_mut().sixel = true;
+        r.set_cell_pixel_size(10, 20);
+        assert!(r.draw_image(3, 1, &test_image()));
+        let image = &r.images[0];
+        assert_eq!(image.rect, Rect::new(3, 1, 1, 1));
+        assert_eq!((image.pixels.width, image.pixels.height), (10, 20));
+        assert!(r.draw_image(9, 9, &test_image()));
+        assert_eq!(r.images.len(), 1);
+    }
+
+    #[test]
+    fn test_event_bus_delivers_present_and_resize_in_order() {
+        use crate::event::{EventBus, TerminalEvent};
+        use std::sync::Mutex;
+
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        bus.subscribe(move |event: &RendererEvent| {
+            let entry = match event {
+                RendererEvent::Resized { width, height } => format!("a:resized {width}x{height}"),
+                RendererEvent::FramePresented(stats) => format!("a:frame {}", stats.frames),
+                RendererEvent::ForcedRedraw => "a:forced".to_string(),
+            };
+            log.lock().unwrap().push(entry);
+        });
+        let log = Arc::clone(&seen);
+        let dropped = bus.subscribe(move |_: &RendererEvent| log.lock().unwrap().push("b".into()));
+        let log = Arc::clone(&seen);
+        bus.subscribe(move |event: &TerminalEvent| {
+            if let TerminalEvent::CapabilityDetected(_) = event {
+                log.lock().unwrap().push("c:capabilities".into());
+            }
+        });
+
+        let mut r = test_renderer(20, 5);
+        r.set_event_bus(bus.clone());
+        r.present().unwrap();
+        assert!(bus.unsubscribe(dropped));
+        // The first cleared frame differs everywhere from the initial one.
+        r.present().unwrap();
+        r.buffer().draw_text(0, 0, "x", crate::style::Style::NONE);
+        r.present().unwrap();
+        r.resize(24, 6).unwrap();
+        r.present().unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "c:capabilities",
+                "a:forced",
+                "b",
+                "a:frame 1",
+                "b",
+                "a:forced",
+                "a:frame 2",
+                "a:frame 3",
+                "a:resized 24x6",
+                "a:forced",
+                "a:frame 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_before_present_hook_draws_into_presented_frame() {
+        let mut r = test_renderer(20, 5);
+        r.on_before_present(|buffer| buffer.draw_text(0, 4, "hook", crate::style::Style::NONE));
+        r.present().unwrap();
+        assert_eq!(
+            r.front_buffer.get(0, 4).unwrap().content,
+            crate::cell::CellContent::Char('h')
+        );
+        assert_eq!(
+            r.front_buffer.get(3, 4).unwrap().content,
+            crate::cell::CellContent::Char('k')
+        );
+    }
+
+    #[test]
+    fn test_present_hooks_run_in_order_and_remove_by_id() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut r = test_renderer(20, 5);
+        let log = Rc::clone(&seen);
+        r.on_after_present(move |stats| log.borrow_mut().push(format!("after {}", stats.frames)));
+        let log = Rc::clone(&seen);
+        let middle = r.on_before_present(move |_| log.borrow_mut().push("before 1".to_string()));
+        let log = Rc::clone(&seen);
+        r.on_before_present(move |_| log.borrow_mut().push("before 2".to_string()));
+
+        r.present().unwrap();
+        assert!(r.remove_present_hook(middle));
+        assert!(!r.remove_present_hook(middle));
+        r.present().unwrap();
+
+        assert_eq!(
+            *seen.borrow(),
+            ["before 1", "before 2", "after 1", "before 2", "after 2"]
+        );
+    }
+
+    #[test]
+    fn test_panicking_hook_fails_present_without_swapping() {
+        let mut r = test_renderer(20, 5);
+        r.present().unwrap();
+        let id = r.on_before_present(|_| panic!("boom"));
+        r.buffer().draw_text(0, 0, "x", crate::style::Style::NONE);
+
+        let err = r.present().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rendering failed to run a present hook: before-present hook panicked: boom"
+        );
+        assert_eq!(r.stats().frames, 1);
+        assert_ne!(
+            r.front_buffer.get(0, 0).unwrap().content,
+            crate::cell::CellContent::Char('x')
+        );
+
+        assert!(r.remove_present_hook(id));
+        r.present().unwrap();
+        assert_eq!(
+            r.front_buffer.get(0, 0).unwrap().content,
+            crate::cell::CellContent::Char('x')
+        );
+    }
+
+    #[test]
+    fn test_present_rejects_reentry() {
+        let mut r = test_renderer(20, 5);
+        r.presenting = true;
+        assert!(r.present().is_err());
+        r.presenting = false;
+        r.present().unwrap();
+    }
+
+    /// A pipe that accepts `budget` bytes, then fails every write with
+    /// `error`. Counts write calls so tests can see what was attempted.
+    struct ClosingPipe {
+        budget: usize,
+        error: io::ErrorKind,
+        /// Fail this many writes with `WouldBlock` before anything else.
+        blocks: usize,
+        writes: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Write for ClosingPipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.set(self.writes.get() + 1);
+            if self.blocks > 0 {
+                self.blocks -= 1;
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            if self.budget == 0 {
+                return Err(self.error.into());
+            }
+            let n = buf.len().min(self.budget);
+            self.budget -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn piped_renderer(pipe: ClosingPipe) -> Renderer {
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        Renderer::with_output(10, 3, options, Box::new(pipe)).unwrap()
+    }
+
+    #[test]
+    fn test_broken_pipe_marks_renderer_dead() {
+        let writes = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut r = piped_renderer(ClosingPipe {
+            budget: 2000,
+            error: io::ErrorKind::BrokenPipe,
+            blocks: 0,
+            writes: writes.clone(),
+        });
+        r.present().unwrap();
+        assert!(r.is_alive());
+
+        // The second full redraw runs out of budget.
+        r.buffer()
+            .draw_text(0, 0, "gone", crate::style::Style::NONE);
+        let mut result = r.present();
+        while result.is_ok() {
+            r.invalidate();
+            result = r.present();
+        }
+        let err = result.unwrap_err();
+        assert!(matches!(err, Error::Render { .. }), "{err:?}");
+        assert_eq!(
+            err.io_source().map(io::Error::kind),
+            Some(io::ErrorKind::BrokenPipe)
+        );
+        assert!(!r.is_alive());
+
+        let attempted = writes.get();
+        assert!(matches!(r.present(), Err(Error::OutputClosed)));
+        r.resize(12, 4).unwrap();
+        r.cleanup().unwrap();
+        drop(r);
+        assert_eq!(writes.get(), attempted, "nothing is written once dead");
+    }
+
+    #[test]
+    fn test_would_block_is_retried_before_giving_up() {
+        let pipe = |blocks| ClosingPipe {
+            budget: usize::MAX,
+            error: io::ErrorKind::BrokenPipe,
+            blocks,
+            writes: std::rc::Rc::default(),
+        };
+
+        let mut r = piped_renderer(pipe(2));
+        r.set_write_retries(2);
+        r.present().unwrap();
+        assert!(r.is_alive());
+
+        let mut r = piped_renderer(pipe(3));
+        r.set_write_retries(2);
+        let err = r.present().unwrap_err();
+        assert_eq!(
+            err.io_source().map(io::Error::kind),
+            Some(io::ErrorKind::WouldBlock)
+        );
+        assert!(!r.is_alive());
+    }
+
+    #[test]
+    fn test_arena_resets_on_present() {
+        let mut r = test_renderer(20, 2);
+        let (buffer, arena) = r.buffer_with_arena();
+        for row in 0..2 {
+            let label = arena.format(format_args!("row {row}: {}", "ok"));
+            buffer.draw_text(0, row, label, crate::style::Style::NONE);
+        }
+        assert_eq!(r.arena().used(), 18);
+        r.present().unwrap();
+        assert_eq!(r.arena().used(), 0);
+        assert_eq!(r.stats().arena_peak_bytes, 18);
+        assert!(r.stats().arena_bytes >= 18);
+        assert_eq!(
+            r.front_buffer().get(5, 1).unwrap().content.as_char(),
+            Some(':')
+        );
+
+        r.arena().alloc_str("short");
+        r.present().unwrap();
+        assert_eq!(r.stats().arena_peak_bytes, 18);
+    }
+
+    #[test]
+    fn test_paced_presents_keep_the_target_cadence() {
+        use super::pacing::tests::FakeClock;
+
+        let (mut r, _) = counting_renderer(10, 2);
+        let clock = FakeClock::new();
+        r.pacer = FramePacer::with_clock(Box::new(clock.clone()));
+        r.set_target_fps(Some(50.0));
+        assert_eq!(r.target_fps(), Some(50.0));
+        r.present_paced().unwrap();
+
+        for cost in [3, 12, 0, 19] {
+            clock.advance(Duration::from_millis(cost));
+            assert_eq!(r.time_until_next_frame(), Duration::from_millis(20 - cost));
+            let timing = r.present_paced().unwrap();
+            assert_eq!(timing.lateness(), Duration::ZERO);
+            assert!((r.stats().fps - 50.0).abs() < 0.01, "{}", r.stats().fps);
+        }
+
+        // Unpaced, presents go out as soon as they're asked for.
+        r.set_target_fps(None);
+        clock.advance(Duration::from_millis(5));
+        let timing = r.present_paced().unwrap();
+        assert_eq!(timing.actual, timing.scheduled);
+        assert!((r.stats().fps - 200.0).abs() < 0.1, "{}", r.stats().fps);
+    }
+
+    /// Draw `text` as a double-height banner on rows 0 and 1 and present
+    /// it, returning what was written.
+    fn present_banner(r: &mut Renderer, output: &CapturedOutput, text: &str) -> String {
+        use crate::style::Style;
+
+        let start = output.len();
+        let buffer = r.buffer();
+        buffer.set_row_size(0, RowSize::DoubleHeightTop);
+        buffer.set_row_size(1, RowSize::DoubleHeightBottom);
+        buffer.draw_text(0, 0, text, Style::NONE);
+        buffer.draw_text(0, 1, text, Style::NONE);
+        r.present().unwrap();
+        output.since(start)
+    }
+
+    #[test]
+    fn test_double_height_rows_emit_line_attributes() {
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let mut r = Renderer::with_output(12, 3, options, Box::new(output.clone())).unwrap();
+        r.capabilities_mut().dec_line_attributes = true;
+        r.present().unwrap();
+
+        let first = present_banner(&mut r, &output, "Title");
+        let top = first.find("\x1b#3").expect("top half marked");
+        let bottom = first.find("\x1b#4").expect("bottom half marked");
+        assert!(top < first.find("Title").unwrap());
+        assert!(bottom < first.rfind("Title").unwrap());
+
+        // Same rows, same sizes: only the changed cell is written.
+        let second = present_banner(&mut r, &output, "Tidle");
+        assert!(!second.contains("\x1b#"), "{second:?}");
+        assert_eq!(second.matches('d').count(), 2);
+
+        let start = output.len();
+        r.present().unwrap();
+        let normal = output.since(start);
+        assert_eq!(normal.matches("\x1b#5").count(), 2);
+
+        r.capabilities_mut().dec_line_attributes = false;
+        let fallback = present_banner(&mut r, &output, "Title");
+        assert!(!fallback.contains("\x1b#"), "{fallback:?}");
+    }
+
+    /// A renderer with one clean frame presented, so the next present
+    /// writes only changes.
+    fn validating_renderer(level: ValidationLevel) -> (Renderer, CapturedOutput) {
+        let output = CapturedOutput::default();
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        let mut r = Renderer::with_output(12, 3, options, Box::new(output.clone())).unwrap();
+        r.set_validation(level);
+        r.present().unwrap();
+        (r, output)
+    }
+
+    #[test]
+    fn test_strict_validation_rejects_malformed_cells() {
+        use crate::cell::CellContent;
+        use crate::style::Style;
+
+        type Malform = fn(&mut Renderer);
+        let malform: [(FrameIssueKind, Malform); 3] = [
+            (FrameIssueKind::OrphanContinuation, |r| {
+                r.buffer().cells_mut()[12 + 5] = Cell::continuation(Rgba::BLACK);
+            }),
+            (FrameIssueKind::FreedGrapheme, |r| {
+                let id = r.grapheme_pool().alloc("👍🏽");
+                r.grapheme_pool().decref(id);
+                let buffer = r.buffer();
+                let mut cell = Cell::new(' ', Style::NONE);
+                cell.content = CellContent::Grapheme(id);
+                buffer.cells_mut()[12 + 5] = cell;
+                buffer.cells_mut()[12 + 6] = Cell::continuation(Rgba::BLACK);
+            }),
+            (FrameIssueKind::NanColor, |r| {
+                let mut cell = Cell::new('x', Style::NONE);
+                cell.bg.g = f32::NAN;
+                r.buffer().cells_mut()[12 + 5] = cell;
+            }),
+        ];
+        for (kind, malform) in malform {
+            let (mut r, output) = validating_renderer(ValidationLevel::Strict);
+            r.buffer().draw_text(0, 0, "fine", Style::NONE);
+            malform(&mut r);
+            let written = output.len();
+            let Err(Error::InvalidFrame { issues }) = r.present() else {
+                panic!("{kind:?} was not rejected");
+            };
+            assert_eq!(output.len(), written, "{kind:?} wrote output");
+            let found: Vec<(u32, u32, FrameIssueKind)> = issues
+                .iter()
+                .map(|issue| (issue.x, issue.y, issue.kind))
+                .collect();
+            assert_eq!(found, [(5, 1, kind)]);
+            assert_ne!(
+                r.front_buffer().get(0, 0).unwrap().content.as_char(),
+                Some('f')
+            );
+        }
+    }
+
+    #[test]
+    fn test_warn_validation_logs_and_presents() {
+        use crate::event::capture_logs;
+        use crate::style::Style;
+
+        let (mut r, output) = validating_renderer(ValidationLevel::Warn);
+        let logs = capture_logs(|| {
+            r.buffer().draw_text(0, 0, "日本", Style::NONE);
+            r.present().unwrap();
+        });
+        assert_eq!(logs, []);
+
+        let written = output.len();
+        // Outside the changed region, a malformed cell goes unchecked.
+        r.front_buffer.cells_mut()[12 * 2] = Cell::continuation(Rgba::BLACK);
+        r.buffer().cells_mut()[12 * 2] = Cell::continuation(Rgba::BLACK);
+        r.buffer().cells_mut()[2] = Cell::continuation(Rgba::BLACK);
+        let logs = capture_logs(|| r.present().unwrap());
+        let fields: Vec<Vec<(String, String)>> =
+            logs.iter().map(|log| log.fields.clone()).collect();
+        assert_eq!(logs.len(), 1, "{logs:?}");
+        assert_eq!(
+            logs[0].message,
+            "continuation cell without a wide character before it"
+        );
+        assert_eq!(
+            fields[0][..2],
+            [("x".into(), "2".into()), ("y".into(), "0".into())]
+        );
+        assert!(output.len() > written);
+    }
+
+    #[cfg(feature = "pool-audit")]
+    #[test]
+    fn test_pool_audit_clean_over_frame_cycle() {
+        use crate::grapheme_pool::AuditAction;
+        use crate::style::Style;
+
+        let mut r = test_renderer(24, 6);
+        r.grapheme_pool().set_audit_action(AuditAction::Panic);
+        let text = ["👨‍👩‍👧 family", "🇯🇵🇫🇷 flags", "e\u{301}👍🏽 tone"];
+        for frame in 0..4u32 {
+            let (buffer, pool) = r.buffer_with_pool();
+            for (y, line) in (0..).zip(text) {
+                buffer.draw_text_with_pool(pool, frame, y, line, Style::NONE);
+            }
+            // Overwritten without the pool: released on the next pool call.
+            buffer.set(frame, 0, Cell::new('#', Style::NONE));
+            r.render_to_layer(1, |layer| {
+                layer.draw_text(0, 4, "layer", Style::NONE);
+            });
+            r.cached_panel(
+                "badge",
+                u64::from(frame / 2),
+                Rect::new(12, 5, 8, 1),
+                |panel, pool| {
+                    panel.draw_text_with_pool(pool, 0, 0, "🚀 v2", Style::NONE);
+                },
+            );
+            assert_eq!(r.audit_grapheme_pool(), [], "frame {frame} drawn");
+            r.present().unwrap();
+            assert_eq!(r.audit_grapheme_pool(), [], "frame {frame} presented");
+        }
+        r.resize(16, 4).unwrap();
+        assert_eq!(r.audit_grapheme_pool(), []);
+        assert!(r.grapheme_pool_ref().active_count() > 0);
+    }
 }
@@ -0,0 +1,62 @@
+//! Renderer output with retries for non-blocking stdout.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Default number of times a write that would block is retried.
+pub const DEFAULT_WRITE_RETRIES: u32 = 3;
+
+/// Pause between retries of a write that would block.
+const RETRY_DELAY: Duration = Duration::from_millis(1);
+
+/// The renderer's output stream.
+///
+/// A write or flush that fails with [`io::ErrorKind::WouldBlock`] is retried
+/// up to `retries` times before the error is returned. Other errors are
+/// returned immediately.
+pub struct Output {
+    inner: Box<dyn Write>,
+    pub retries: u32,
+}
+
+impl Output {
+    pub fn new(inner: Box<dyn Write>) -> Self {
+        Self {
+            inner,
+            retries: DEFAULT_WRITE_RETRIES,
+        }
+    }
+
+    fn retry<T>(&mut self, mut op: impl FnMut(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        let mut attempts = 0;
+        loop {
+            match op(&mut *self.inner) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock && attempts < self.retries => {
+                    attempts += 1;
+                    thread::sleep(RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.retry(|inner| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.retry(|inner| inner.flush())
+    }
+}
+
+/// Whether `err` means the output is gone for good: the reader closed the
+/// pipe, the terminal hung up, or writes kept blocking past the retries.
+pub fn is_closed(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::BrokenPipe | io::ErrorKind::WriteZero | io::ErrorKind::WouldBlock
+    ) || err.raw_os_error() == Some(libc::EIO)
+}
@@ -0,0 +1,270 @@
+//! Frame pacing for [`Renderer::present_paced`].
+//!
+//! Frames are due on a fixed schedule, one interval apart from the first
+//! paced frame, rather than one interval after the previous frame went
+//! out: oversleeping or a slow frame delays that frame but not the ones
+//! after it, so the average rate holds at the target.
+//!
+//! [`Renderer::present_paced`]: super::Renderer::present_paced
+
+use std::time::{Duration, Instant};
+
+/// Source of time for pacing, replaced in tests.
+pub(super) trait Clock: Send {
+    fn now(&self) -> Instant;
+    fn sleep(&self, duration: Duration);
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// When a paced frame was due and when it was presented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameTiming {
+    /// When the frame was due. Without a target rate, the same as `actual`.
+    pub scheduled: Instant,
+    /// When presenting started, after any sleep.
+    pub actual: Instant,
+    /// Frames the schedule skipped because this one was more than a whole
+    /// interval late.
+    pub missed: u32,
+}
+
+impl FrameTiming {
+    /// How late the frame was presented.
+    #[must_use]
+    pub fn lateness(&self) -> Duration {
+        self.actual.saturating_duration_since(self.scheduled)
+    }
+}
+
+/// A target frame rate and the schedule of frames due at it.
+pub(super) struct FramePacer {
+    target_fps: Option<f32>,
+    interval: Duration,
+    /// When the next frame is due, once a paced frame has been presented.
+    next: Option<Instant>,
+    clock: Box<dyn Clock>,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+}
+
+impl FramePacer {
+    pub(super) fn with_clock(clock: Box<dyn Clock>) -> Self {
+        Self {
+            target_fps: None,
+            interval: Duration::ZERO,
+            next: None,
+            clock,
+        }
+    }
+
+    pub(super) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Set the target rate, restarting the schedule; a rate that isn't
+    /// positive and finite, or whose interval rounds to zero or doesn't fit
+    /// a [`Duration`], turns pacing off.
+    pub(super) fn set_target_fps(&mut self, fps: Option<f32>) {
+        let paced = fps.and_then(|fps| {
+            let interval = Duration::try_from_secs_f64(1.0 / f64::from(fps)).ok()?;
+            (fps.is_finite() && !interval.is_zero()).then_some((fps, interval))
+        });
+        self.target_fps = paced.map(|(fps, _)| fps);
+        self.interval = paced.map_or(Duration::ZERO, |(_, interval)| interval);
+        self.next = None;
+    }
+
+    pub(super) const fn target_fps(&self) -> Option<f32> {
+        self.target_fps
+    }
+
+    /// Time left until the next frame is due, zero if it is due already or
+    /// pacing is off.
+    pub(super) fn time_until_next_frame(&self) -> Duration {
+        self.next.map_or(Duration::ZERO, |next| {
+            next.saturating_duration_since(self.now())
+        })
+    }
+
+    /// Sleep until the next frame is due and move the schedule past it.
+    pub(super) fn wait(&mut self) -> FrameTiming {
+        let now = self.now();
+        if self.target_fps.is_none() {
+            return FrameTiming {
+                scheduled: now,
+                actual: now,
+                missed: 0,
+            };
+        }
+        let scheduled = self.next.unwrap_or(now);
+        if scheduled > now {
+            self.clock.sleep(scheduled - now);
+        }
+        let actual = self.now();
+
+        // A late frame catches up on the next one, but a frame a whole
+        // interval late drops the frames it overran rather than presenting
+        // a burst of them.
+        let late = actual.saturating_duration_since(scheduled).as_nanos();
+        let interval = self.interval.as_nanos();
+        let missed = u32::try_from(late / interval).unwrap_or(u32::MAX);
+        let into_interval = Duration::from_nanos(u64::try_from(late % interval).unwrap_or(0));
+        // An interval too long to add to the clock restarts the schedule.
+        self.next = actual.checked_add(self.interval.saturating_sub(into_interval));
+        FrameTiming {
+            scheduled,
+            actual,
+            missed,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A clock that only moves when told to, or when slept on, which
+    /// oversleeps by a varying amount like a real timer.
+    #[derive(Clone)]
+    pub struct FakeClock {
+        now: Arc<Mutex<Instant>>,
+        seed: Arc<Mutex<u64>>,
+        pub max_oversleep: Duration,
+    }
+
+    impl FakeClock {
+        pub fn new() -> Self {
+            Self {
+                now: Arc::new(Mutex::new(Instant::now())),
+                seed: Arc::new(Mutex::new(0x2545_f491_4f6c_dd1d)),
+                max_oversleep: Duration::ZERO,
+            }
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+
+        /// A pseudo-random duration up to `max`.
+        pub fn jitter(&self, max: Duration) -> Duration {
+            let permille = {
+                let mut seed = self.seed.lock().unwrap();
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 7;
+                *seed ^= *seed << 17;
+                u32::try_from(*seed % 1000).unwrap()
+            };
+            max.mul_f64(f64::from(permille) / 1000.0)
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.advance(duration + self.jitter(self.max_oversleep));
+        }
+    }
+
+    fn pacer(fps: f32, clock: &FakeClock) -> FramePacer {
+        let mut pacer = FramePacer::with_clock(Box::new(clock.clone()));
+        pacer.set_target_fps(Some(fps));
+        pacer
+    }
+
+    #[test]
+    fn test_jittery_frames_average_the_target_rate() {
+        let mut clock = FakeClock::new();
+        clock.max_oversleep = Duration::from_millis(2);
+        let mut pacer = pacer(60.0, &clock);
+        let interval = Duration::from_secs(1) / 60;
+
+        let first = pacer.wait().actual;
+        let frames = 600u32;
+        for _ in 0..frames {
+            // Most frames take well under the interval, some overrun it.
+            clock.advance(clock.jitter(interval.mul_f64(1.2)));
+            let timing = pacer.wait();
+            assert_eq!(timing.missed, 0);
+            assert!(timing.lateness() < interval);
+        }
+        let elapsed = clock.now() - first;
+        let fps = f64::from(frames) / elapsed.as_secs_f64();
+        assert!((fps - 60.0).abs() < 0.6, "{fps} fps");
+    }
+
+    #[test]
+    fn test_overrun_frames_are_dropped_not_bursted() {
+        let clock = FakeClock::new();
+        let mut pacer = pacer(100.0, &clock);
+        let start = pacer.wait().actual;
+
+        clock.advance(Duration::from_millis(35));
+        let timing = pacer.wait();
+        assert_eq!(timing.scheduled, start + Duration::from_millis(10));
+        assert_eq!(timing.missed, 2);
+        assert_eq!(pacer.time_until_next_frame(), Duration::from_millis(5));
+
+        assert_eq!(pacer.wait().actual, start + Duration::from_millis(40));
+        pacer.set_target_fps(Some(f32::NAN));
+        assert_eq!(pacer.target_fps(), None);
+        assert_eq!(pacer.time_until_next_frame(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_extreme_rates_turn_pacing_off() {
+        let clock = FakeClock::new();
+        // Too slow for a `Duration`, and too fast for a nonzero one.
+        for fps in [
+            1e-20,
+            f32::MIN_POSITIVE,
+            1e10,
+            f32::MAX,
+            -60.0,
+            0.0,
+            f32::INFINITY,
+        ] {
+            let mut pacer = pacer(fps, &clock);
+            assert_eq!(pacer.target_fps(), None, "{fps}");
+            let timing = pacer.wait();
+            assert_eq!(timing.missed, 0);
+            clock.advance(Duration::from_millis(5));
+            assert_eq!(pacer.wait().lateness(), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_extreme_rates_in_range_still_pace() {
+        let clock = FakeClock::new();
+        let mut fast = pacer(1e9, &clock);
+        assert_eq!(fast.target_fps(), Some(1e9));
+        let start = fast.wait().actual;
+        clock.advance(Duration::from_micros(1));
+        assert_eq!(fast.wait().missed, 999);
+        assert_eq!(fast.time_until_next_frame(), Duration::from_nanos(1));
+        assert!(fast.wait().actual > start);
+
+        let mut slow = pacer(1e-9, &clock);
+        assert_eq!(slow.target_fps(), Some(1e-9));
+        slow.wait();
+        assert!(slow.time_until_next_frame() > Duration::from_secs(999_999_999));
+    }
+}
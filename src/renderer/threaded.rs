@@ -37,13 +37,23 @@
 //! renderer.shutdown()?;
 //! # Ok::<(), std::io::Error>(())
 //! ```
+//!
+//! # Frame Pacing
+//!
+//! [`ThreadedRenderer::present_paced`] paces on the main thread, like
+//! [`Renderer::present_paced`](super::Renderer::present_paced): it sleeps
+//! until the frame is due, then submits it. Each frame reaches the terminal
+//! after the render thread's diff and write, so frames keep the target
+//! spacing but arrive that much later.
 
 use crate::ansi::AnsiWriter;
 use crate::buffer::OptimizedBuffer;
 use crate::color::Rgba;
+use crate::error::{Error, RenderPhase, Result};
 use crate::grapheme_pool::GraphemePool;
 use crate::link::LinkPool;
-use crate::renderer::{BufferDiff, RendererOptions};
+use crate::renderer::pacing::{FramePacer, FrameTiming};
+use crate::renderer::{BufferDiff, RendererOptions, write_row_runs, write_row_size};
 use crate::terminal::{CursorStyle, Terminal};
 use std::io::{self, Stdout, Write};
 use std::panic::AssertUnwindSafe;
@@ -150,18 +160,19 @@ pub struct ThreadedRenderer {
     /// Statistics.
     stats: ThreadedRenderStats,
     last_present_at: Instant,
+    pacer: FramePacer,
 }
 
 impl ThreadedRenderer {
     /// Create a new threaded renderer with the given dimensions.
     ///
     /// This spawns a render thread that handles all terminal I/O.
-    pub fn new(width: u32, height: u32) -> io::Result<Self> {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
         Self::new_with_options(width, height, RendererOptions::default())
     }
 
     /// Create a new threaded renderer with custom options.
-    pub fn new_with_options(width: u32, height: u32, options: RendererOptions) -> io::Result<Self> {
+    pub fn new_with_options(width: u32, height: u32, options: RendererOptions) -> Result<Self> {
         let (tx, render_rx) = mpsc::channel::<RenderCommand>();
         let (render_tx, rx) = mpsc::channel::<RenderReply>();
 
@@ -184,6 +195,7 @@ impl ThreadedRenderer {
             background: Rgba::BLACK,
             stats: ThreadedRenderStats::default(),
             last_present_at: Instant::now(),
+            pacer: FramePacer::default(),
         })
     }
 
@@ -227,7 +239,7 @@ impl ThreadedRenderer {
     /// Submit the current frame for rendering.
     ///
     /// This blocks until the render thread returns the buffer.
-    pub fn present(&mut self) -> io::Result<()> {
+    pub fn present(&mut self) -> Result<()> {
         // Take ownership of current buffer and pools
         let buffer = std::mem::replace(
             &mut self.back_buffer,
@@ -258,20 +270,44 @@ impl ThreadedRenderer {
                 self.update_stats();
                 Ok(())
             }
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
+    /// Cap [`present_paced`](Self::present_paced) at `fps` frames per
+    /// second, or stop pacing with `None`; see
+    /// [`Renderer::set_target_fps`](super::Renderer::set_target_fps).
+    pub fn set_target_fps(&mut self, fps: Option<f32>) {
+        self.pacer.set_target_fps(fps);
+    }
+
+    /// The rate set with [`set_target_fps`](Self::set_target_fps).
+    #[must_use]
+    pub const fn target_fps(&self) -> Option<f32> {
+        self.pacer.target_fps()
+    }
+
+    /// Time until the next paced frame is due, or zero; a good input poll
+    /// timeout between frames.
+    #[must_use]
+    pub fn time_until_next_frame(&self) -> Duration {
+        self.pacer.time_until_next_frame()
+    }
+
+    /// Sleep until the next frame is due at the target rate, then
+    /// [`present`](Self::present) it.
+    pub fn present_paced(&mut self) -> Result<FrameTiming> {
+        let timing = self.pacer.wait();
+        self.present()?;
+        Ok(timing)
+    }
+
     /// Resize the renderer.
-    pub fn resize(&mut self, width: u32, height: u32) -> io::Result<()> {
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
         self.tx
             .send(RenderCommand::Resize { width, height })
             .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected"))?;
@@ -283,60 +319,48 @@ impl ThreadedRenderer {
                 self.back_buffer = OptimizedBuffer::new(width, height);
                 Ok(())
             }
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
     /// Set cursor position and visibility.
-    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> io::Result<()> {
+    pub fn set_cursor(&mut self, x: u32, y: u32, visible: bool) -> Result<()> {
         self.tx
             .send(RenderCommand::SetCursor { x, y, visible })
             .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected"))?;
 
         match self.rx.recv() {
             Ok(RenderReply::CursorComplete) => Ok(()),
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
     /// Set cursor style.
-    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> io::Result<()> {
+    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> Result<()> {
         self.tx
             .send(RenderCommand::SetCursorStyle { style, blinking })
             .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected"))?;
 
         match self.rx.recv() {
             Ok(RenderReply::CursorComplete) => Ok(()),
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
     /// Set window title.
-    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
         self.tx
             .send(RenderCommand::SetTitle {
                 title: title.to_string(),
@@ -345,35 +369,27 @@ impl ThreadedRenderer {
 
         match self.rx.recv() {
             Ok(RenderReply::TitleComplete) => Ok(()),
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
     /// Force a full redraw on next present.
-    pub fn invalidate(&mut self) -> io::Result<()> {
+    pub fn invalidate(&mut self) -> Result<()> {
         self.tx
             .send(RenderCommand::Invalidate)
             .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected"))?;
 
         match self.rx.recv() {
             Ok(RenderReply::InvalidateComplete) => Ok(()),
-            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg)),
-            Err(_) => Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "render thread disconnected",
-            )),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unexpected reply",
-            )),
+            Ok(RenderReply::Error(msg)) => Err(io::Error::other(msg).into()),
+            Err(_) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "render thread disconnected").into())
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply").into()),
         }
     }
 
@@ -381,11 +397,11 @@ impl ThreadedRenderer {
     ///
     /// This waits for the render thread to complete cleanup and restore
     /// terminal state.
-    pub fn shutdown(mut self) -> io::Result<()> {
+    pub fn shutdown(mut self) -> Result<()> {
         self.shutdown_internal()
     }
 
-    fn shutdown_internal(&mut self) -> io::Result<()> {
+    fn shutdown_internal(&mut self) -> Result<()> {
         // Send shutdown command
         if self.tx.send(RenderCommand::Shutdown).is_err() {
             // Thread already gone, try to join it
@@ -410,7 +426,7 @@ impl ThreadedRenderer {
     }
 
     fn update_stats(&mut self) {
-        let now = Instant::now();
+        let now = self.pacer.now();
         let frame_time = now.duration_since(self.last_present_at);
         self.last_present_at = now;
 
@@ -531,6 +547,7 @@ fn render_thread_inner(
                     render_full(
                         &mut terminal,
                         &mut scratch_buffer,
+                        &front_buffer,
                         &buffer,
                         &grapheme_pool,
                         &link_pool,
@@ -541,6 +558,7 @@ fn render_thread_inner(
                     render_diff(
                         &mut terminal,
                         &mut scratch_buffer,
+                        &front_buffer,
                         &buffer,
                         &grapheme_pool,
                         &link_pool,
@@ -627,91 +645,131 @@ fn render_thread_inner(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_full(
     terminal: &mut Terminal<Stdout>,
     scratch: &mut Vec<u8>,
+    front: &OptimizedBuffer,
     buffer: &OptimizedBuffer,
     grapheme_pool: &GraphemePool,
     link_pool: &LinkPool,
     width: u32,
     height: u32,
-) -> io::Result<()> {
+) -> Result<()> {
     if terminal.capabilities().sync_output {
-        terminal.begin_sync()?;
+        terminal
+            .begin_sync()
+            .map_err(Error::render(RenderPhase::BeginSync))?;
     }
 
     scratch.clear();
     let mut writer = AnsiWriter::new(&mut *scratch);
+    writer.set_rep(terminal.capabilities().rep);
+    writer.set_line_drawing(terminal.capabilities().line_drawing());
     // Emit cursor home to synchronize terminal cursor with writer's internal tracking.
     // The writer starts tracking at (0,0), but the terminal cursor may be elsewhere
     // (e.g., pending-wrap state at end of previous frame).
     writer.write_str("\x1b[H");
 
+    let line_attributes = terminal.capabilities().dec_line_attributes;
     for y in 0..height {
-        writer.move_cursor(y, 0);
-        for x in 0..width {
-            if let Some(cell) = buffer.get(x, y) {
-                if !cell.is_continuation() {
-                    let url = cell.attributes.link_id().and_then(|id| link_pool.get(id));
-                    writer.write_cell_with_pool_and_link(cell, grapheme_pool, url);
-                }
-            }
-        }
+        let columns = write_row_size(&mut writer, front, buffer, y, line_attributes, true);
+        write_row_runs(
+            &mut writer,
+            buffer,
+            grapheme_pool,
+            link_pool,
+            y,
+            0..columns.min(width),
+            &[],
+            None,
+        );
     }
 
     writer.reset();
-    writer.flush()?;
-
-    terminal.flush()?;
-    io::stdout().write_all(scratch)?;
-    io::stdout().flush()?;
+    writer.flush().map_err(Error::render(RenderPhase::Write))?;
+
+    terminal
+        .flush()
+        .map_err(Error::render(RenderPhase::Flush))?;
+    io::stdout()
+        .write_all(scratch)
+        .map_err(Error::render(RenderPhase::Write))?;
+    io::stdout()
+        .flush()
+        .map_err(Error::render(RenderPhase::Flush))?;
 
     if terminal.capabilities().sync_output {
-        terminal.end_sync()?;
+        terminal
+            .end_sync()
+            .map_err(Error::render(RenderPhase::EndSync))?;
     }
-    terminal.flush()
+    terminal.flush().map_err(Error::render(RenderPhase::Flush))
 }
 
 fn render_diff(
     terminal: &mut Terminal<Stdout>,
     scratch: &mut Vec<u8>,
+    front: &OptimizedBuffer,
     buffer: &OptimizedBuffer,
     grapheme_pool: &GraphemePool,
     link_pool: &LinkPool,
     diff: &BufferDiff,
-) -> io::Result<()> {
+) -> Result<()> {
     if terminal.capabilities().sync_output {
-        terminal.begin_sync()?;
+        terminal
+            .begin_sync()
+            .map_err(Error::render(RenderPhase::BeginSync))?;
     }
 
     scratch.clear();
     let mut writer = AnsiWriter::new(&mut *scratch);
+    writer.set_line_drawing(terminal.capabilities().line_drawing());
     // Emit cursor home to synchronize terminal cursor with writer's internal tracking.
     // The writer starts tracking at (0,0), but the terminal cursor may be elsewhere
     // from the previous frame. Without this, relative moves would be incorrect.
     writer.write_str("\x1b[H");
 
+    let line_attributes = terminal.capabilities().dec_line_attributes;
+    let mut row = None;
+    let mut columns = 0;
     for &(x, y) in &diff.changed_cells {
+        if row != Some(y) {
+            columns = write_row_size(&mut writer, front, buffer, y, line_attributes, false);
+            row = Some(y);
+        }
+        if x >= columns {
+            continue;
+        }
         if let Some(cell) = buffer.get(x, y) {
             if !cell.is_continuation() {
-                let url = cell.attributes.link_id().and_then(|id| link_pool.get(id));
+                let link = cell.attributes.link_id();
+                let url = link.and_then(|id| link_pool.get(id));
+                let params = link.and_then(|id| link_pool.params(id)).unwrap_or("");
+                writer.set_link_with_params(link, url, params);
                 writer.write_cell_at_with_pool_and_link(y, x, cell, grapheme_pool, url);
             }
         }
     }
 
     writer.reset();
-    writer.flush()?;
+    writer.flush().map_err(Error::render(RenderPhase::Write))?;
 
     if !scratch.is_empty() {
-        io::stdout().write_all(scratch)?;
-        io::stdout().flush()?;
+        io::stdout()
+            .write_all(scratch)
+            .map_err(Error::render(RenderPhase::Write))?;
+        io::stdout()
+            .flush()
+            .map_err(Error::render(RenderPhase::Flush))?;
     }
 
     if terminal.capabilities().sync_output {
-        terminal.end_sync()?;
+        terminal
+            .end_sync()
+            .map_err(Error::render(RenderPhase::EndSync))?;
     }
-    terminal.flush()
+    terminal.flush().map_err(Error::render(RenderPhase::Flush))
 }
 
 #[cfg(test)]
@@ -789,6 +847,7 @@ mod tests {
             hide_cursor: false,
             enable_mouse: false,
             query_capabilities: false,
+            capabilities: None,
         };
         assert!(!opts.use_alt_screen);
         assert!(!opts.hide_cursor);
@@ -0,0 +1,171 @@
+//! Checks on cells about to be written, for [`Renderer::set_validation`].
+//!
+//! [`Renderer::set_validation`]: super::Renderer::set_validation
+
+use std::fmt;
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+use crate::color::Rgba;
+use crate::event::{LogLevel, LogRecord, emit_log_record};
+use crate::grapheme_pool::GraphemePool;
+
+use super::diff::DirtyRegion;
+
+/// How [`Renderer::present`](super::Renderer::present) checks the cells it
+/// is about to write.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationLevel {
+    /// No checks.
+    #[default]
+    Off,
+    /// Log each malformed cell as a warning, then present as usual.
+    Warn,
+    /// Fail with [`Error::InvalidFrame`](crate::Error::InvalidFrame)
+    /// without writing anything.
+    Strict,
+}
+
+/// What is wrong with a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameIssueKind {
+    /// A continuation cell not covered by a wide character to its left.
+    OrphanContinuation,
+    /// A grapheme whose pool entry is free.
+    FreedGrapheme,
+    /// A color with a NaN component.
+    NanColor,
+}
+
+impl FrameIssueKind {
+    const fn describe(self) -> &'static str {
+        match self {
+            Self::OrphanContinuation => "continuation cell without a wide character before it",
+            Self::FreedGrapheme => "grapheme ID refers to a freed pool entry",
+            Self::NanColor => "color has a NaN component",
+        }
+    }
+}
+
+/// A malformed cell found by frame validation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameIssue {
+    pub x: u32,
+    pub y: u32,
+    pub kind: FrameIssueKind,
+    pub cell: Cell,
+}
+
+impl fmt::Display for FrameIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cell ({}, {}): {}: {:?}",
+            self.x,
+            self.y,
+            self.kind.describe(),
+            self.cell
+        )
+    }
+}
+
+fn has_nan(color: Rgba) -> bool {
+    [color.r, color.g, color.b, color.a]
+        .iter()
+        .any(|component| component.is_nan())
+}
+
+/// Whether the continuation at `x` of `row` is covered by the wide
+/// character it continues.
+fn is_covered(row: &[Cell], x: usize) -> bool {
+    (1..=x)
+        .map(|back| (back, &row[x - back]))
+        .find(|(_, cell)| !cell.content.is_continuation())
+        .is_some_and(|(back, cell)| cell.content.display_width() > back)
+}
+
+/// Check the cells of `buffer` inside `regions`, in row order per region.
+pub(super) fn validate_regions(
+    buffer: &OptimizedBuffer,
+    pool: &GraphemePool,
+    regions: &[DirtyRegion],
+) -> Vec<FrameIssue> {
+    let (width, height) = buffer.size();
+    let cells = buffer.cells();
+    let mut issues = Vec::new();
+    for region in regions {
+        let right = region.x.saturating_add(region.width).min(width);
+        let bottom = region.y.saturating_add(region.height).min(height);
+        for y in region.y..bottom {
+            let row = &cells[y as usize * width as usize..(y as usize + 1) * width as usize];
+            for x in region.x..right {
+                let cell = row[x as usize];
+                let kind = match cell.content {
+                    CellContent::Continuation if !is_covered(row, x as usize) => {
+                        Some(FrameIssueKind::OrphanContinuation)
+                    }
+                    CellContent::Grapheme(id) if id.pool_id() != 0 && pool.get(id).is_none() => {
+                        Some(FrameIssueKind::FreedGrapheme)
+                    }
+                    _ => None,
+                };
+                let nan = has_nan(cell.fg)
+                    || has_nan(cell.bg)
+                    || cell.underline_color.is_some_and(has_nan);
+                let kinds = kind
+                    .into_iter()
+                    .chain(nan.then_some(FrameIssueKind::NanColor));
+                issues.extend(kinds.map(|kind| FrameIssue { x, y, kind, cell }));
+            }
+        }
+    }
+    issues
+}
+
+/// Log each issue as a warning with its position and cell.
+pub(super) fn log_issues(issues: &[FrameIssue]) {
+    for issue in issues {
+        let (x, y) = (issue.x.to_string(), issue.y.to_string());
+        let cell = format!("{:?}", issue.cell);
+        emit_log_record(
+            &LogRecord::new(LogLevel::Warn, issue.kind.describe())
+                .with_target(module_path!())
+                .with_fields(&[("x", &x), ("y", &y), ("cell", &cell)]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Style;
+
+    #[test]
+    fn test_continuations_need_a_wide_cell() {
+        let mut pool = GraphemePool::new();
+        let mut buffer = OptimizedBuffer::new(8, 1);
+        buffer.draw_text(0, 0, "日", Style::NONE);
+        buffer.draw_text_with_pool(&mut pool, 3, 0, "👨‍👩‍👧", Style::NONE);
+        let whole = [DirtyRegion::new(0, 0, 8, 1)];
+        assert_eq!(validate_regions(&buffer, &pool, &whole), []);
+
+        buffer.cells_mut()[0] = Cell::new('a', Style::NONE);
+        buffer.cells_mut()[6] = Cell::continuation(Rgba::TRANSPARENT);
+        let issues = validate_regions(&buffer, &pool, &whole);
+        let found: Vec<(u32, FrameIssueKind)> =
+            issues.iter().map(|issue| (issue.x, issue.kind)).collect();
+        assert_eq!(
+            found,
+            [
+                (1, FrameIssueKind::OrphanContinuation),
+                (6, FrameIssueKind::OrphanContinuation),
+            ]
+        );
+
+        // Only the regions given are checked.
+        assert_eq!(
+            validate_regions(&buffer, &pool, &[DirtyRegion::new(2, 0, 4, 1)]),
+            []
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Recording presented frames to a file and playing them back.
+//!
+//! [`Renderer::start_recording`] appends every frame the renderer presents
+//! to a file, with when it was presented and, optionally, the terminal
+//! input the app read. A [`Player`] loads the file to step through the
+//! frames in a test or to show them again through a renderer at the
+//! recorded pace, which makes a user's capture of a rendering glitch
+//! something to replay locally.
+//!
+//! ```no_run
+//! use opentui_rust::Renderer;
+//! use opentui_rust::replay::{Player, RecordOptions};
+//!
+//! # fn main() -> opentui_rust::Result<()> {
+//! let mut renderer = Renderer::new(80, 24)?;
+//! renderer.start_recording("session.otrec", RecordOptions::default())?;
+//! // ... draw and present frames
+//! let summary = renderer.stop_recording()?;
+//!
+//! let player = Player::open("session.otrec")?;
+//! player.play(&mut renderer)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Graphemes are stored as text and hyperlinks as their IDs only; link
+//! targets are not recorded.
+
+mod format;
+mod recorder;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::buffer::OptimizedBuffer;
+use crate::error::Result;
+use crate::grapheme_pool::GraphemePool;
+use crate::renderer::Renderer;
+
+pub(crate) use recorder::Recorder;
+
+/// Options for [`Renderer::start_recording`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordOptions {
+    /// Stop recording frames after this many; `None` records every frame.
+    pub max_frames: Option<u64>,
+    /// Also record input passed to [`Renderer::record_input`].
+    pub include_input: bool,
+}
+
+/// What a recording captured, from [`Renderer::stop_recording`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordingSummary {
+    /// Frames written.
+    pub frames: u64,
+    /// Input chunks written.
+    pub inputs: u64,
+    /// Frames and input left out because the writer fell behind.
+    pub dropped: u64,
+}
+
+/// A recorded frame.
+#[derive(Clone, Debug)]
+pub struct RecordedFrame {
+    /// When the frame was presented, from the start of the recording.
+    pub time: Duration,
+    /// The frame as presented. Its graphemes live in the player's
+    /// [pool](Player::grapheme_pool).
+    pub buffer: OptimizedBuffer,
+}
+
+/// Terminal input recorded with [`RecordOptions::include_input`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedInput {
+    /// When the input was recorded, from the start of the recording.
+    pub time: Duration,
+    /// The bytes as read from the terminal, for an
+    /// [`InputParser`](crate::InputParser).
+    pub bytes: Vec<u8>,
+}
+
+/// A loaded recording.
+pub struct Player {
+    frames: Vec<RecordedFrame>,
+    inputs: Vec<RecordedInput>,
+    pool: GraphemePool,
+}
+
+impl Player {
+    /// Load the recording at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::Error::Io) if the file can't be read or
+    /// isn't a recording.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load a recording from `reader`.
+    ///
+    /// A recording cut off partway through a record, as when the app
+    /// crashed, loads up to the last whole record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::Error::Io) for read errors, and with
+    /// [`io::ErrorKind::InvalidData`] for data that isn't a recording.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self> {
+        format::read_magic(&mut reader)?;
+        let mut player = Self {
+            frames: Vec::new(),
+            inputs: Vec::new(),
+            pool: GraphemePool::new(),
+        };
+        loop {
+            match format::read_record(&mut reader, &mut player.pool) {
+                Ok(Some(format::Record::Frame(frame))) => player.frames.push(frame),
+                Ok(Some(format::Record::Input(input))) => player.inputs.push(input),
+                Ok(None) => break,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(player)
+    }
+
+    /// The recorded frames, in presentation order.
+    #[must_use]
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// The recorded input, in the order it was read.
+    #[must_use]
+    pub fn inputs(&self) -> &[RecordedInput] {
+        &self.inputs
+    }
+
+    /// The pool holding the frames' graphemes.
+    #[must_use]
+    pub const fn grapheme_pool(&self) -> &GraphemePool {
+        &self.pool
+    }
+
+    /// Draw frame `index` into `renderer`'s back buffer and present it,
+    /// resizing the renderer to the frame first if their sizes differ.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidInput`](crate::Error::InvalidInput) for an
+    /// index past the last frame, or the error resizing or presenting.
+    pub fn present_frame(&self, index: usize, renderer: &mut Renderer) -> Result<()> {
+        let frame = self
+            .frames
+            .get(index)
+            .ok_or_else(|| crate::Error::InvalidInput(format!("no recorded frame {index}")))?;
+        let (width, height) = frame.buffer.size();
+        if renderer.size() != (width, height) {
+            renderer.resize(width, height)?;
+        }
+        let (buffer, pool) = renderer.buffer_with_pool();
+        for y in 0..height {
+            buffer.set_row_size(y, frame.buffer.row_size(y));
+        }
+        buffer.draw_buffer_region_from_pool(
+            pool,
+            &self.pool,
+            0,
+            0,
+            &frame.buffer,
+            0,
+            0,
+            width,
+            height,
+            false,
+        );
+        renderer.present()
+    }
+
+    /// Present every frame through `renderer`, spaced as they were
+    /// recorded.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first error from [`present_frame`](Self::present_frame).
+    pub fn play(&self, renderer: &mut Renderer) -> Result<()> {
+        let start = Instant::now();
+        let first = self
+            .frames
+            .first()
+            .map_or(Duration::ZERO, |frame| frame.time);
+        for (index, frame) in self.frames.iter().enumerate() {
+            let due = start + frame.time.saturating_sub(first);
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            self.present_frame(index, renderer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        // Hand the frames' references back so the audited pool drops empty.
+        #[cfg(feature = "pool-audit")]
+        for frame in &mut self.frames {
+            frame.buffer.release_graphemes(&mut self.pool);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::RowSize;
+    use crate::cell::CellContent;
+    use crate::color::Rgba;
+    use crate::renderer::RendererOptions;
+    use crate::style::Style;
+
+    fn renderer(width: u32, height: u32) -> Renderer {
+        let options = RendererOptions {
+            use_alt_screen: false,
+            hide_cursor: false,
+            enable_mouse: false,
+            query_capabilities: false,
+            capabilities: None,
+        };
+        Renderer::with_output(width, height, options, Box::new(io::sink())).unwrap()
+    }
+
+    /// Each visible cell with its grapheme text looked up, so frames can be
+    /// compared across pools.
+    fn cells(buffer: &OptimizedBuffer, pool: &GraphemePool) -> Vec<String> {
+        let (_, height) = buffer.size();
+        let mut cells = Vec::new();
+        for y in 0..height {
+            for x in 0..buffer.row_width(y) {
+                let cell = buffer.get(x, y).unwrap();
+                let text = match cell.content {
+                    CellContent::Grapheme(id) => pool.get(id).unwrap_or("?").to_string(),
+                    content => format!("{content:?}"),
+                };
+                cells.push(format!(
+                    "{x},{y} {text} {:?} {:?} {:?} {:?} {:?}",
+                    cell.fg, cell.bg, cell.attributes, cell.underline_style, cell.underline_color
+                ));
+            }
+        }
+        cells
+    }
+
+    fn draw(renderer: &mut Renderer, frame: u32) {
+        let (buffer, pool) = renderer.buffer_with_pool();
+        let style = Style::fg(Rgba::rgb(0.2, 0.4, 0.6)).with_bold();
+        buffer.draw_text_with_pool(pool, frame, 0, "frame 👩‍🚀 ok", style);
+        buffer.draw_text_with_pool(
+            pool,
+            0,
+            1,
+            &format!("{frame}"),
+            Style::default().with_overline(),
+        );
+        buffer.set_row_size(2, RowSize::DoubleWidth);
+    }
+
+    #[test]
+    fn test_recorded_frames_replay_cell_for_cell() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.otrec");
+        let mut r = renderer(20, 4);
+        r.start_recording(&path, RecordOptions::default()).unwrap();
+        assert!(r.is_recording());
+
+        let mut expected = Vec::new();
+        for frame in 0..3 {
+            draw(&mut r, frame);
+            r.present().unwrap();
+            expected.push(cells(r.front_buffer(), r.grapheme_pool_ref()));
+        }
+        let summary = r.stop_recording().unwrap().unwrap();
+        assert_eq!(
+            summary,
+            RecordingSummary {
+                frames: 3,
+                inputs: 0,
+                dropped: 0
+            }
+        );
+        assert!(!r.is_recording());
+
+        let player = Player::open(&path).unwrap();
+        assert_eq!(player.frames().len(), 3);
+        for (frame, expected) in player.frames().iter().zip(&expected) {
+            assert_eq!(&cells(&frame.buffer, player.grapheme_pool()), expected);
+            assert_eq!(frame.buffer.row_size(2), RowSize::DoubleWidth);
+        }
+        assert!(
+            player
+                .frames()
+                .windows(2)
+                .all(|pair| pair[0].time <= pair[1].time)
+        );
+
+        let mut replayed = renderer(8, 2);
+        player.present_frame(2, &mut replayed).unwrap();
+        assert_eq!(replayed.size(), (20, 4));
+        assert_eq!(
+            cells(replayed.front_buffer(), replayed.grapheme_pool_ref()),
+            expected[2]
+        );
+        assert!(player.present_frame(3, &mut replayed).is_err());
+    }
+
+    #[test]
+    fn test_input_is_recorded_and_frames_stop_at_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.otrec");
+        let mut r = renderer(10, 2);
+        let options = RecordOptions {
+            max_frames: Some(2),
+            include_input: true,
+        };
+        r.start_recording(&path, options).unwrap();
+        for frame in 0..4 {
+            draw(&mut r, frame);
+            r.present().unwrap();
+        }
+        r.record_input(b"\x1b[A");
+        r.record_input(b"");
+        let summary = r.stop_recording().unwrap().unwrap();
+        assert_eq!(
+            summary,
+            RecordingSummary {
+                frames: 2,
+                inputs: 1,
+                dropped: 0
+            }
+        );
+        assert_eq!(r.stop_recording().unwrap(), None);
+
+        let player = Player::open(&path).unwrap();
+        assert_eq!(player.frames().len(), 2);
+        assert_eq!(player.inputs().len(), 1);
+        assert_eq!(player.inputs()[0].bytes, b"\x1b[A");
+    }
+
+    #[test]
+    fn test_truncated_recordings_load_their_whole_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.otrec");
+        let mut r = renderer(10, 2);
+        r.start_recording(&path, RecordOptions::default()).unwrap();
+        for frame in 0..2 {
+            draw(&mut r, frame);
+            r.present().unwrap();
+        }
+        r.stop_recording().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let player = Player::from_reader(&bytes[..bytes.len() - 5]).unwrap();
+        assert_eq!(player.frames().len(), 1);
+        assert!(Player::from_reader(&b"not a recording"[..]).is_err());
+    }
+}
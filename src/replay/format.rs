@@ -0,0 +1,299 @@
+//! The recording file format.
+//!
+//! A recording starts with [`MAGIC`] and holds a sequence of records, each
+//! a tag byte and the microseconds since recording started, then:
+//!
+//! - a frame: width and height, one size byte per row, the graphemes the
+//!   frame uses by pool ID, and every cell in row order;
+//! - input: a length and the raw bytes read from the terminal.
+//!
+//! Integers and floats are little-endian. A cell is a content tag (empty,
+//! char, grapheme, continuation) with its value, the foreground and
+//! background as four `f32`s each, the attribute bits as a `u64`, the
+//! underline shape, and an optional underline color behind a flag byte.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use super::{RecordedFrame, RecordedInput};
+use crate::buffer::{OptimizedBuffer, RowSize};
+use crate::cell::{Cell, CellContent, GraphemeId};
+use crate::color::Rgba;
+use crate::grapheme_pool::GraphemePool;
+use crate::style::{TextAttributes, UnderlineStyle};
+
+/// The first bytes of every recording.
+pub(super) const MAGIC: &[u8; 8] = b"OTUIREC1";
+
+const FRAME: u8 = 1;
+const INPUT: u8 = 2;
+
+/// Largest frame a recording may hold, against corrupt sizes.
+const MAX_CELLS: u64 = 1 << 24;
+
+/// A presented frame, copied out of the renderer for the writer thread.
+pub(super) struct FrameCapture {
+    pub(super) time: Duration,
+    width: u32,
+    height: u32,
+    row_sizes: Vec<RowSize>,
+    cells: Vec<Cell>,
+    /// The text of each grapheme the cells use, by pool ID.
+    graphemes: HashMap<u32, String>,
+}
+
+impl FrameCapture {
+    pub(super) fn new(time: Duration, buffer: &OptimizedBuffer, pool: &GraphemePool) -> Self {
+        let (width, height) = buffer.size();
+        let mut graphemes = HashMap::new();
+        for cell in buffer.cells() {
+            if let CellContent::Grapheme(id) = cell.content {
+                if let Some(text) = pool.get(id) {
+                    graphemes
+                        .entry(id.pool_id())
+                        .or_insert_with(|| text.to_string());
+                }
+            }
+        }
+        Self {
+            time,
+            width,
+            height,
+            row_sizes: (0..height).map(|y| buffer.row_size(y)).collect(),
+            cells: buffer.cells().to_vec(),
+            graphemes,
+        }
+    }
+}
+
+fn micros(time: Duration) -> u64 {
+    u64::try_from(time.as_micros()).unwrap_or(u64::MAX)
+}
+
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    w.write_all(&len.to_le_bytes())
+}
+
+fn write_color(w: &mut impl Write, color: Rgba) -> io::Result<()> {
+    for component in [color.r, color.g, color.b, color.a] {
+        w.write_all(&component.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+const fn row_size_byte(size: RowSize) -> u8 {
+    match size {
+        RowSize::Normal => 0,
+        RowSize::DoubleWidth => 1,
+        RowSize::DoubleHeightTop => 2,
+        RowSize::DoubleHeightBottom => 3,
+    }
+}
+
+pub(super) fn write_frame(w: &mut impl Write, frame: &FrameCapture) -> io::Result<()> {
+    w.write_all(&[FRAME])?;
+    w.write_all(&micros(frame.time).to_le_bytes())?;
+    w.write_all(&frame.width.to_le_bytes())?;
+    w.write_all(&frame.height.to_le_bytes())?;
+    let sizes: Vec<u8> = frame
+        .row_sizes
+        .iter()
+        .map(|&size| row_size_byte(size))
+        .collect();
+    w.write_all(&sizes)?;
+
+    write_len(w, frame.graphemes.len())?;
+    for (pool_id, text) in &frame.graphemes {
+        w.write_all(&pool_id.to_le_bytes())?;
+        write_len(w, text.len())?;
+        w.write_all(text.as_bytes())?;
+    }
+
+    for cell in &frame.cells {
+        match cell.content {
+            CellContent::Empty => w.write_all(&[0])?,
+            CellContent::Char(ch) => {
+                w.write_all(&[1])?;
+                w.write_all(&u32::from(ch).to_le_bytes())?;
+            }
+            CellContent::Grapheme(id) => {
+                w.write_all(&[2])?;
+                w.write_all(&id.raw().to_le_bytes())?;
+            }
+            CellContent::Continuation => w.write_all(&[3])?,
+        }
+        write_color(w, cell.fg)?;
+        write_color(w, cell.bg)?;
+        w.write_all(&cell.attributes.bits().to_le_bytes())?;
+        w.write_all(&[cell.underline_style.sgr_subparam()])?;
+        match cell.underline_color {
+            Some(color) => {
+                w.write_all(&[1])?;
+                write_color(w, color)?;
+            }
+            None => w.write_all(&[0])?,
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn write_input(w: &mut impl Write, time: Duration, bytes: &[u8]) -> io::Result<()> {
+    w.write_all(&[INPUT])?;
+    w.write_all(&micros(time).to_le_bytes())?;
+    write_len(w, bytes.len())?;
+    w.write_all(bytes)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad recording: {message}"),
+    )
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    read_array(r).map(u32::from_le_bytes)
+}
+
+fn read_color(r: &mut impl Read) -> io::Result<Rgba> {
+    let mut components = [0.0; 4];
+    for component in &mut components {
+        *component = f32::from_le_bytes(read_array(r)?);
+    }
+    let [r, g, b, a] = components;
+    Ok(Rgba::new(r, g, b, a))
+}
+
+fn read_bytes(r: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_u32(r)?;
+    let mut bytes = Vec::new();
+    r.take(u64::from(len)).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+/// Check that `r` starts with [`MAGIC`].
+pub(super) fn read_magic(r: &mut impl Read) -> io::Result<()> {
+    if &read_array::<8>(r)? == MAGIC {
+        Ok(())
+    } else {
+        Err(invalid("not an OpenTUI recording"))
+    }
+}
+
+/// A record read back, with frame graphemes allocated in the player's pool.
+pub(super) enum Record {
+    Frame(RecordedFrame),
+    Input(RecordedInput),
+}
+
+/// Read the next record, or `None` at the end of the recording.
+pub(super) fn read_record(
+    r: &mut impl Read,
+    pool: &mut GraphemePool,
+) -> io::Result<Option<Record>> {
+    let mut tag = [0];
+    if r.read(&mut tag)? == 0 {
+        return Ok(None);
+    }
+    let time = Duration::from_micros(u64::from_le_bytes(read_array(r)?));
+    match tag[0] {
+        FRAME => read_frame(r, pool, time).map(|frame| Some(Record::Frame(frame))),
+        INPUT => {
+            let bytes = read_bytes(r)?;
+            Ok(Some(Record::Input(RecordedInput { time, bytes })))
+        }
+        _ => Err(invalid("unknown record")),
+    }
+}
+
+fn read_frame(
+    r: &mut impl Read,
+    pool: &mut GraphemePool,
+    time: Duration,
+) -> io::Result<RecordedFrame> {
+    let (width, height) = (read_u32(r)?, read_u32(r)?);
+    if width == 0 || height == 0 || u64::from(width) * u64::from(height) > MAX_CELLS {
+        return Err(invalid("frame size"));
+    }
+    let mut buffer = OptimizedBuffer::new(width, height).with_grapheme_pool(pool);
+    for (y, byte) in (0..height).zip(read_bytes_exact(r, height as usize)?) {
+        let size = match byte {
+            0 => RowSize::Normal,
+            1 => RowSize::DoubleWidth,
+            2 => RowSize::DoubleHeightTop,
+            3 => RowSize::DoubleHeightBottom,
+            _ => return Err(invalid("row size")),
+        };
+        buffer.set_row_size(y, size);
+    }
+
+    let mut graphemes = HashMap::new();
+    for _ in 0..read_u32(r)? {
+        let pool_id = read_u32(r)?;
+        let text = String::from_utf8(read_bytes(r)?).map_err(|_| invalid("grapheme text"))?;
+        graphemes.insert(pool_id, text);
+    }
+
+    let mut cells = Vec::with_capacity(buffer.cells().len());
+    for _ in 0..buffer.cells().len() {
+        let content = match read_array::<1>(r)?[0] {
+            0 => CellContent::Empty,
+            1 => char::from_u32(read_u32(r)?)
+                .map(CellContent::Char)
+                .ok_or_else(|| invalid("char"))?,
+            2 => {
+                let id = GraphemeId::from_raw(read_u32(r)?);
+                graphemes.get(&id.pool_id()).map_or_else(
+                    || {
+                        let width = u8::try_from(id.width()).unwrap_or(GraphemeId::MAX_WIDTH);
+                        CellContent::Grapheme(GraphemeId::placeholder(width))
+                    },
+                    |text| CellContent::Grapheme(pool.alloc(text)),
+                )
+            }
+            3 => CellContent::Continuation,
+            _ => return Err(invalid("cell content")),
+        };
+        let fg = read_color(r)?;
+        let bg = read_color(r)?;
+        let attributes = TextAttributes::from_bits_retain(u64::from_le_bytes(read_array(r)?));
+        let underline_style = match read_array::<1>(r)?[0] {
+            2 => UnderlineStyle::Double,
+            3 => UnderlineStyle::Curly,
+            4 => UnderlineStyle::Dotted,
+            5 => UnderlineStyle::Dashed,
+            _ => UnderlineStyle::Single,
+        };
+        let underline_color = match read_array::<1>(r)?[0] {
+            0 => None,
+            _ => Some(read_color(r)?),
+        };
+        cells.push(Cell {
+            content,
+            fg,
+            bg,
+            attributes,
+            underline_style,
+            underline_color,
+        });
+    }
+    buffer.cells_mut().copy_from_slice(&cells);
+    Ok(RecordedFrame { time, buffer })
+}
+
+fn read_bytes_exact(r: &mut impl Read, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; len];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
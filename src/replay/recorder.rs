@@ -0,0 +1,125 @@
+//! The writer thread behind [`Renderer::start_recording`].
+//!
+//! Presenting copies the frame and hands it to a bounded queue; a thread
+//! encodes and writes it. When the queue is full the frame is dropped and
+//! counted instead of making the renderer wait on the disk.
+//!
+//! [`Renderer::start_recording`]: crate::Renderer::start_recording
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::format::{self, FrameCapture};
+use super::{RecordOptions, RecordingSummary};
+use crate::buffer::OptimizedBuffer;
+use crate::grapheme_pool::GraphemePool;
+
+/// Records queued for the writer before new ones are dropped.
+const QUEUE_LEN: usize = 16;
+
+enum Message {
+    Frame(FrameCapture),
+    Input(Duration, Vec<u8>),
+}
+
+/// A recording in progress.
+pub struct Recorder {
+    tx: Option<SyncSender<Message>>,
+    handle: Option<JoinHandle<io::Result<()>>>,
+    start: Instant,
+    options: RecordOptions,
+    summary: RecordingSummary,
+}
+
+impl Recorder {
+    /// Create the file at `path`, write the header, and start the writer.
+    pub fn start(path: &Path, options: RecordOptions) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(format::MAGIC)?;
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let handle = thread::Builder::new()
+            .name("opentui-record".to_string())
+            .spawn(move || write_records(&rx, file))?;
+        Ok(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+            start: Instant::now(),
+            options,
+            summary: RecordingSummary::default(),
+        })
+    }
+
+    /// Queue a copy of a presented frame, unless the frame limit is reached.
+    pub fn record_frame(&mut self, buffer: &OptimizedBuffer, pool: &GraphemePool) {
+        if self
+            .options
+            .max_frames
+            .is_some_and(|max| self.summary.frames >= max)
+        {
+            return;
+        }
+        let frame = FrameCapture::new(self.start.elapsed(), buffer, pool);
+        if self.send(Message::Frame(frame)) {
+            self.summary.frames += 1;
+        } else {
+            self.summary.dropped += 1;
+        }
+    }
+
+    /// Queue input bytes, if the options ask for input.
+    pub fn record_input(&mut self, bytes: &[u8]) {
+        if !self.options.include_input || bytes.is_empty() {
+            return;
+        }
+        if self.send(Message::Input(self.start.elapsed(), bytes.to_vec())) {
+            self.summary.inputs += 1;
+        } else {
+            self.summary.dropped += 1;
+        }
+    }
+
+    fn send(&self, message: Message) -> bool {
+        let Some(tx) = &self.tx else {
+            return false;
+        };
+        match tx.try_send(message) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Write out what is queued and close the file.
+    pub fn finish(mut self) -> io::Result<RecordingSummary> {
+        self.tx = None;
+        let handle = self.handle.take().expect("writer joined once");
+        handle
+            .join()
+            .map_err(|_| io::Error::other("recording thread panicked"))??;
+        Ok(self.summary)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Write queued records until the recorder hangs up. A write error ends
+/// the thread, and the recorder counts what it queues after as dropped.
+fn write_records(rx: &Receiver<Message>, mut file: BufWriter<File>) -> io::Result<()> {
+    for message in rx {
+        match message {
+            Message::Frame(frame) => format::write_frame(&mut file, &frame)?,
+            Message::Input(time, bytes) => format::write_input(&mut file, time, &bytes)?,
+        }
+    }
+    file.flush()
+}
@@ -0,0 +1,615 @@
+//! Optional main loop: [`run`] owns the renderer, input, and terminal state.
+//!
+//! Every application repeats the same loop around a [`Renderer`]: enter
+//! raw mode, read stdin without blocking, notice resizes, pace frames, and
+//! put the terminal back however the program ends. [`run`] does exactly
+//! that and nothing more; there is no widget tree. Each frame, the closure
+//! gets a [`Frame`] with the back buffer, the input parsed since the last
+//! frame, and timing, and returns whether to keep going.
+//!
+//! ```no_run
+//! use opentui_rust::{ControlFlow, KeyCode, RunOptions, Style, run};
+//!
+//! run(RunOptions::default(), |mut frame| {
+//!     let quit = frame
+//!         .events()
+//!         .iter()
+//!         .filter_map(|event| event.key())
+//!         .any(|key| key.code == KeyCode::Char('q'));
+//!     let text = format!("frame {} - press q to quit", frame.index());
+//!     frame.buffer().draw_text(0, 0, &text, Style::NONE);
+//!     if quit { ControlFlow::Quit } else { ControlFlow::Continue }
+//! })
+//! .unwrap();
+//! ```
+//!
+//! Resizes are found by checking the terminal size once per frame; a
+//! `SIGWINCH` cuts the input wait short, so they show up on the next
+//! frame. [`run_with`] takes any [`InputSource`] and a prebuilt renderer,
+//! which is how the loop is tested without a terminal.
+
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::buffer::OptimizedBuffer;
+use crate::error::{Error, Result, TerminalOp};
+use crate::event::{LogLevel, LogRecord, emit_log_record};
+use crate::grapheme_pool::GraphemePool;
+use crate::input::{Event, InputParser, KeyCode, KeyEvent, ParseError, ResizeEvent};
+use crate::renderer::{Renderer, RendererOptions};
+use crate::terminal::{RawModeGuard, enable_raw_mode, read_stdin_timeout, terminal_size};
+
+/// What the frame closure wants next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Present this frame and keep running.
+    #[default]
+    Continue,
+    /// Stop without presenting this frame.
+    Quit,
+}
+
+/// Options for [`run`].
+#[derive(Clone, Debug)]
+pub struct RunOptions {
+    /// Target frames per second; 0 is treated as 1.
+    pub fps: u32,
+    /// Terminal setup for the renderer.
+    pub renderer: RendererOptions,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            fps: 60,
+            renderer: RendererOptions::default(),
+        }
+    }
+}
+
+impl RunOptions {
+    fn frame_interval(self) -> Duration {
+        Duration::from_secs(1) / self.fps.max(1)
+    }
+}
+
+/// One frame of a [`run`] loop.
+pub struct Frame<'a> {
+    renderer: &'a mut Renderer,
+    events: &'a [Event],
+    elapsed: Duration,
+    delta: Duration,
+    index: u64,
+}
+
+impl Frame<'_> {
+    /// The back buffer to draw this frame into.
+    pub fn buffer(&mut self) -> &mut OptimizedBuffer {
+        self.renderer.buffer()
+    }
+
+    /// The back buffer with the renderer's grapheme pool.
+    pub fn buffer_with_pool(&mut self) -> (&mut OptimizedBuffer, &mut GraphemePool) {
+        self.renderer.buffer_with_pool()
+    }
+
+    /// The renderer, for hit areas, links, layers, and the like.
+    pub fn renderer(&mut self) -> &mut Renderer {
+        self.renderer
+    }
+
+    /// Input parsed since the previous frame, oldest first.
+    ///
+    /// A terminal resize shows up here as [`Event::Resize`] after the
+    /// renderer has been resized.
+    #[must_use]
+    pub fn events(&self) -> &[Event] {
+        self.events
+    }
+
+    /// Time since the loop started.
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Time since the previous frame (zero for the first).
+    #[must_use]
+    pub const fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Frame number, starting at 0.
+    #[must_use]
+    pub const fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Current buffer size.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        self.renderer.size()
+    }
+}
+
+/// Where [`run_with`] gets input bytes and the terminal size.
+pub trait InputSource {
+    /// Read available input into `buf`, waiting at most `timeout`.
+    /// Returns 0 when nothing arrived.
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize>;
+
+    /// The current terminal size, checked once per frame. `None` keeps
+    /// the renderer's size.
+    fn size(&mut self) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// Stdin in raw mode, with the size from `TIOCGWINSZ`.
+struct Stdin {
+    _raw_mode: RawModeGuard,
+}
+
+impl InputSource for Stdin {
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        read_stdin_timeout(buf, timeout)
+    }
+
+    fn size(&mut self) -> Option<(u32, u32)> {
+        terminal_size()
+            .ok()
+            .map(|(width, height)| (u32::from(width), u32::from(height)))
+    }
+}
+
+/// Run `app` once per frame on a stdout renderer until it returns
+/// [`ControlFlow::Quit`].
+///
+/// The terminal is restored when the loop ends, when it fails, and when
+/// `app` panics. A panic's report is held back while the terminal is in
+/// raw mode, then sent as an error to the log callback (see
+/// [`set_log_record_callback`]) once the terminal is back to normal, and
+/// the panic is resumed.
+///
+/// [`set_log_record_callback`]: crate::event::set_log_record_callback
+pub fn run(options: RunOptions, app: impl FnMut(Frame<'_>) -> ControlFlow) -> Result<()> {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+    let raw_mode = enable_raw_mode().map_err(Error::terminal(TerminalOp::EnterRawMode))?;
+    let renderer = Renderer::new_with_options(
+        u32::from(width),
+        u32::from(height),
+        options.renderer.clone(),
+    )?;
+    run_with(
+        renderer,
+        Stdin {
+            _raw_mode: raw_mode,
+        },
+        options,
+        app,
+    )
+}
+
+/// [`run`] with a given renderer and input source.
+///
+/// `renderer` is cleaned up and `input` dropped before this returns or a
+/// panic from `app` is resumed.
+pub fn run_with(
+    mut renderer: Renderer,
+    mut input: impl InputSource,
+    options: RunOptions,
+    mut app: impl FnMut(Frame<'_>) -> ControlFlow,
+) -> Result<()> {
+    let interval = options.frame_interval();
+    let mut reader = EventReader::default();
+    let mut events = Vec::new();
+    let start = Instant::now();
+    let mut last_frame = None;
+    let deferred = DeferredPanics::install();
+
+    let result = (|| {
+        for index in 0.. {
+            let frame_start = Instant::now();
+            if let Some((width, height)) = input.size() {
+                if (width, height) != renderer.size() && width > 0 && height > 0 {
+                    renderer.resize(width, height)?;
+                    events.push(Event::Resize(ResizeEvent::new(
+                        u16::try_from(width).unwrap_or(u16::MAX),
+                        u16::try_from(height).unwrap_or(u16::MAX),
+                    )));
+                }
+            }
+
+            let frame = Frame {
+                renderer: &mut renderer,
+                events: &events,
+                elapsed: frame_start - start,
+                delta: last_frame.map_or(Duration::ZERO, |last| frame_start - last),
+                index,
+            };
+            last_frame = Some(frame_start);
+            match panic::catch_unwind(AssertUnwindSafe(|| app(frame))) {
+                Ok(ControlFlow::Continue) => {}
+                Ok(ControlFlow::Quit) => break,
+                Err(payload) => return Ok(Some(payload)),
+            }
+            renderer.present()?;
+
+            events.clear();
+            reader.read_until(&mut input, frame_start + interval, &mut events, |bytes| {
+                renderer.record_input(bytes);
+            })?;
+        }
+        Ok(None)
+    })();
+
+    let cleanup = renderer.cleanup();
+    drop(renderer);
+    drop(input);
+    let report = deferred.finish();
+    match result {
+        Ok(Some(payload)) => {
+            if let Some(report) = report {
+                emit_log_record(
+                    &LogRecord::new(LogLevel::Error, &report).with_target(module_path!()),
+                );
+            }
+            panic::resume_unwind(payload)
+        }
+        Ok(None) => cleanup,
+        Err(err) => Err(err),
+    }
+}
+
+/// Accumulates input bytes and parses them into events.
+#[derive(Default)]
+struct EventReader {
+    parser: InputParser,
+    pending: Vec<u8>,
+}
+
+impl EventReader {
+    /// Read and parse input until `deadline`, passing what is read to
+    /// `record` as well.
+    fn read_until(
+        &mut self,
+        input: &mut impl InputSource,
+        deadline: Instant,
+        events: &mut Vec<Event>,
+        mut record: impl FnMut(&[u8]),
+    ) -> Result<()> {
+        let mut buf = [0u8; 1024];
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let read = input.read(&mut buf, timeout).map_err(Error::Io)?;
+            if read > 0 {
+                record(&buf[..read]);
+            }
+            self.pending.extend_from_slice(&buf[..read]);
+            self.parse(events);
+            if timeout.is_zero() {
+                break;
+            }
+        }
+        // Nothing followed a lone ESC for a whole frame, so it was the key.
+        if self.pending == [0x1b] {
+            self.pending.clear();
+            events.push(KeyEvent::key(KeyCode::Esc).into());
+        }
+        Ok(())
+    }
+
+    fn parse(&mut self, events: &mut Vec<Event>) {
+        let mut offset = 0;
+        while offset < self.pending.len() {
+            match self.parser.parse(&self.pending[offset..]) {
+                Ok((event, used)) => {
+                    events.push(event);
+                    offset += used;
+                }
+                Err(ParseError::Incomplete | ParseError::Empty) => break,
+                Err(_) => offset += 1,
+            }
+        }
+        self.pending.drain(..offset);
+    }
+}
+
+thread_local! {
+    /// Whether this thread's panic reports are being held back.
+    static DEFERRING: Cell<bool> = const { Cell::new(false) };
+    /// The last report held back on this thread.
+    static DEFERRED_REPORT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static DEFER_HOOK: Once = Once::new();
+
+/// Holds back panic reports from this thread while the terminal is in raw
+/// mode or the alternate screen, where they would be garbled or erased.
+///
+/// The hook is installed once and forwards every other report to the hook
+/// it replaced.
+struct DeferredPanics;
+
+impl DeferredPanics {
+    fn install() -> Self {
+        DEFER_HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if DEFERRING.try_with(Cell::get).unwrap_or(false) {
+                    let thread = thread::current();
+                    let name = thread.name().unwrap_or("<unnamed>");
+                    let report = format!("thread '{name}' {info}");
+                    DEFERRED_REPORT.with(|slot| *slot.borrow_mut() = Some(report));
+                } else {
+                    previous(info);
+                }
+            }));
+        });
+        DEFERRED_REPORT.take();
+        DEFERRING.set(true);
+        Self
+    }
+
+    /// Stop holding reports back and return the held one, if any.
+    fn finish(self) -> Option<String> {
+        drop(self);
+        DEFERRED_REPORT.take()
+    }
+}
+
+impl Drop for DeferredPanics {
+    fn drop(&mut self) {
+        DEFERRING.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    /// Renderer output shared with the test.
+    #[derive(Clone, Default)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn contains(&self, needle: &[u8]) -> bool {
+            self.0.borrow().windows(needle.len()).any(|w| w == needle)
+        }
+    }
+
+    /// Hands out one chunk per read and one size per frame.
+    #[derive(Default)]
+    struct Scripted {
+        chunks: VecDeque<&'static [u8]>,
+        sizes: VecDeque<(u32, u32)>,
+        dropped: DropFlag,
+    }
+
+    /// Set once the input source has been dropped.
+    #[derive(Clone, Default)]
+    struct DropFlag(Rc<std::cell::Cell<bool>>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            if Rc::strong_count(&self.0) > 1 {
+                self.0.set(true);
+            }
+        }
+    }
+
+    impl InputSource for Scripted {
+        fn read(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+            let Some(chunk) = self.chunks.pop_front() else {
+                thread::sleep(timeout);
+                return Ok(0);
+            };
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+
+        fn size(&mut self) -> Option<(u32, u32)> {
+            self.sizes.pop_front()
+        }
+    }
+
+    fn renderer(output: &SharedOutput) -> Renderer {
+        let options = RendererOptions {
+            query_capabilities: false,
+            ..RendererOptions::default()
+        };
+        Renderer::with_writer(10, 4, options, output.clone()).unwrap()
+    }
+
+    fn options(fps: u32) -> RunOptions {
+        RunOptions {
+            fps,
+            ..RunOptions::default()
+        }
+    }
+
+    /// Run until frame `frames` and return the events seen by each frame.
+    fn events_per_frame(input: Scripted, frames: u64) -> Vec<Vec<Event>> {
+        let output = SharedOutput::default();
+        let mut seen = Vec::new();
+        run_with(renderer(&output), input, options(200), |frame| {
+            seen.push(frame.events().to_vec());
+            if frame.index() == frames {
+                ControlFlow::Quit
+            } else {
+                ControlFlow::Continue
+            }
+        })
+        .unwrap();
+        seen
+    }
+
+    #[test]
+    fn input_reaches_the_next_frame() {
+        let input = Scripted {
+            chunks: [b"a".as_slice(), b"\x1b[A"].into(),
+            ..Scripted::default()
+        };
+        let seen = events_per_frame(input, 2);
+
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0].is_empty());
+        let codes: Vec<_> = seen[1]
+            .iter()
+            .filter_map(|e| e.key())
+            .map(|k| k.code)
+            .collect();
+        assert_eq!(codes, [KeyCode::Char('a'), KeyCode::Up]);
+        assert!(seen[2].is_empty());
+    }
+
+    #[test]
+    fn sequences_split_across_reads_are_joined() {
+        let input = Scripted {
+            chunks: [b"\x1b[".as_slice(), b"B"].into(),
+            ..Scripted::default()
+        };
+        let seen = events_per_frame(input, 1);
+        assert_eq!(seen[1].len(), 1);
+        assert_eq!(seen[1][0].key().map(|k| k.code), Some(KeyCode::Down));
+    }
+
+    #[test]
+    fn lone_escape_is_the_escape_key() {
+        let input = Scripted {
+            chunks: [b"\x1b".as_slice()].into(),
+            ..Scripted::default()
+        };
+        let seen = events_per_frame(input, 1);
+        assert_eq!(seen[1].len(), 1);
+        assert_eq!(seen[1][0].key().map(|k| k.code), Some(KeyCode::Esc));
+    }
+
+    #[test]
+    fn resize_is_applied_and_reported() {
+        let output = SharedOutput::default();
+        let input = Scripted {
+            sizes: [(10, 4), (20, 6)].into(),
+            ..Scripted::default()
+        };
+        let mut sizes = Vec::new();
+        let mut resizes = Vec::new();
+        run_with(renderer(&output), input, options(200), |frame| {
+            sizes.push(frame.size());
+            resizes.extend(frame.events().iter().filter_map(|e| match e {
+                Event::Resize(resize) => Some((resize.width, resize.height)),
+                _ => None,
+            }));
+            if frame.index() == 2 {
+                ControlFlow::Quit
+            } else {
+                ControlFlow::Continue
+            }
+        })
+        .unwrap();
+
+        assert_eq!(sizes, [(10, 4), (20, 6), (20, 6)]);
+        assert_eq!(resizes, [(20, 6)]);
+    }
+
+    #[test]
+    fn frames_are_paced_to_fps() {
+        let output = SharedOutput::default();
+        let mut timings = Vec::new();
+        run_with(
+            renderer(&output),
+            Scripted::default(),
+            options(50),
+            |frame| {
+                timings.push((frame.index(), frame.elapsed(), frame.delta()));
+                if frame.index() == 4 {
+                    ControlFlow::Quit
+                } else {
+                    ControlFlow::Continue
+                }
+            },
+        )
+        .unwrap();
+
+        let interval = Duration::from_millis(20);
+        assert_eq!(timings.len(), 5);
+        assert_eq!(timings[0].2, Duration::ZERO);
+        for (i, &(index, elapsed, delta)) in timings.iter().enumerate().skip(1) {
+            assert_eq!(index, i as u64);
+            assert!(delta >= interval, "frame {i} came after {delta:?}");
+            assert!(elapsed >= interval * u32::try_from(i).unwrap());
+        }
+    }
+
+    #[test]
+    fn quit_skips_present_and_restores_terminal() {
+        let output = SharedOutput::default();
+        let input = Scripted::default();
+        let dropped = input.dropped.clone();
+        run_with(renderer(&output), input, options(200), |mut frame| {
+            frame.buffer().draw_text(0, 0, "ZZ", crate::Style::NONE);
+            ControlFlow::Quit
+        })
+        .unwrap();
+
+        assert!(!output.contains(b"ZZ"));
+        assert!(output.contains(b"\x1b[?1049l"));
+        assert!(dropped.0.get());
+    }
+
+    #[test]
+    fn panic_propagates_after_terminal_is_restored() {
+        let output = SharedOutput::default();
+        let input = Scripted::default();
+        let dropped = input.dropped.clone();
+        let renderer = renderer(&output);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            run_with(renderer, input, options(200), |frame| {
+                assert!(frame.index() < 1, "app failed");
+                ControlFlow::Continue
+            })
+        }));
+
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"app failed"));
+        assert!(output.contains(b"\x1b[?1049l"));
+        assert!(dropped.0.get());
+    }
+
+    #[test]
+    fn panic_report_goes_to_the_log_callback() {
+        let output = SharedOutput::default();
+        let logs = crate::event::capture_logs(|| {
+            let renderer = renderer(&output);
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                run_with(renderer, Scripted::default(), options(200), |_| {
+                    panic!("app failed")
+                })
+            }));
+        });
+
+        let target = module_path!().trim_end_matches("::tests");
+        let reports: Vec<_> = logs.iter().filter(|log| log.target == target).collect();
+        assert_eq!(reports.len(), 1, "{logs:#?}");
+        assert_eq!(reports[0].level, LogLevel::Error);
+        assert!(reports[0].message.contains("app failed"));
+    }
+}
@@ -3,8 +3,10 @@
 //! This module provides types for styling text in the terminal:
 //!
 //! - [`TextAttributes`]: Bitflags for bold, italic, underline, etc.
+//! - [`UnderlineStyle`]: Straight, double, curly, dotted, or dashed underlines
 //! - [`Style`]: Complete styling including colors, attributes, and hyperlinks
 //! - [`StyleBuilder`]: Fluent builder for constructing styles
+//! - [`StylePatch`]: Partial style that overrides, inherits, or clears fields
 //!
 //! # Examples
 //!
@@ -24,9 +26,23 @@
 //!
 //! // Merge styles (overlay takes precedence)
 //! let combined = Style::bold().merge(Style::fg(Rgba::RED));
+//!
+//! // Parse a human-readable spec
+//! let warning = Style::parse("bold #ff8800 on black").unwrap();
+//! assert_eq!(warning.to_spec_string(), "bold #ff8800 on #000000");
+//!
+//! // Patch a base style: keep its colors, drop bold, add italic
+//! let quieter = combined.apply(&Style::patch().bold(false).italic(true));
+//! assert_eq!(quieter.fg, Some(Rgba::RED));
+//! assert!(!quieter.attributes.contains(TextAttributes::BOLD));
 //! ```
 
+#[cfg(feature = "serde")]
+mod repr;
+pub(crate) mod spec;
+
 use crate::color::Rgba;
+use crate::error::Result;
 use bitflags::bitflags;
 
 bitflags! {
@@ -35,12 +51,14 @@ bitflags! {
     /// Attributes are represented as bitflags and can be combined using
     /// bitwise OR. Not all terminals support all attributes.
     ///
-    /// Link IDs are packed into the upper 24 bits to match the Zig spec.
+    /// Link IDs are packed into bits 8-31 to match the Zig spec. The classic
+    /// eight flags sit below them and the newer ones above, from bit 32.
     #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
-    pub struct TextAttributes: u32 {
+    pub struct TextAttributes: u64 {
         /// Bold/increased intensity.
         const BOLD          = 0x01;
-        /// Dim/decreased intensity.
+        /// Dim/decreased intensity, which some terminals call faint.
+        /// Shares its reset with bold, so turning either off turns off both.
         const DIM           = 0x02;
         /// Italic (not widely supported).
         const ITALIC        = 0x04;
@@ -54,14 +72,21 @@ bitflags! {
         const HIDDEN        = 0x40;
         /// Strikethrough text.
         const STRIKETHROUGH = 0x80;
+        /// A line over the text (SGR 53).
+        const OVERLINE      = 1 << 32;
+        /// Raised, smaller text (SGR 73); few terminals show it, see
+        /// [`Capabilities::superscript`](crate::terminal::Capabilities::superscript).
+        const SUPERSCRIPT   = 1 << 33;
+        /// Lowered, smaller text (SGR 74); as rarely shown as superscript.
+        const SUBSCRIPT     = 1 << 34;
     }
 }
 
 impl TextAttributes {
-    /// Mask for the lower 8 bits containing style flags.
-    pub const FLAGS_MASK: u32 = 0x0000_00FF;
-    /// Mask for the upper 24 bits containing link ID.
-    pub const LINK_ID_MASK: u32 = 0xFFFF_FF00;
+    /// Mask for the bits containing style flags.
+    pub const FLAGS_MASK: u64 = 0x0000_0007_0000_00FF;
+    /// Mask for bits 8-31, containing the link ID.
+    pub const LINK_ID_MASK: u64 = 0xFFFF_FF00;
     /// Bit shift for link ID storage.
     pub const LINK_ID_SHIFT: u32 = 8;
     /// Maximum link ID that fits in 24 bits.
@@ -70,14 +95,14 @@ impl TextAttributes {
     /// Extract the link ID (if any).
     #[must_use]
     pub const fn link_id(self) -> Option<u32> {
-        let id = (self.bits() & Self::LINK_ID_MASK) >> Self::LINK_ID_SHIFT;
+        let id = ((self.bits() & Self::LINK_ID_MASK) >> Self::LINK_ID_SHIFT) as u32;
         if id == 0 { None } else { Some(id) }
     }
 
     /// Return attributes with a link ID set (masked to 24 bits).
     #[must_use]
     pub const fn with_link_id(self, link_id: u32) -> Self {
-        let id = link_id & Self::MAX_LINK_ID;
+        let id = (link_id & Self::MAX_LINK_ID) as u64;
         let bits = (self.bits() & Self::FLAGS_MASK) | (id << Self::LINK_ID_SHIFT);
         Self::from_bits_retain(bits)
     }
@@ -112,6 +137,45 @@ impl TextAttributes {
     }
 }
 
+/// Shape of an underline (SGR `4:n`).
+///
+/// Only takes effect when [`TextAttributes::UNDERLINE`] is set. Terminals
+/// without styled underline support get a plain underline instead (see
+/// [`Capabilities::styled_underlines`](crate::terminal::Capabilities::styled_underlines)).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum UnderlineStyle {
+    /// Plain single underline.
+    #[default]
+    Single,
+    /// Double underline.
+    Double,
+    /// Curly (wavy) underline, typically used for diagnostics.
+    Curly,
+    /// Dotted underline.
+    Dotted,
+    /// Dashed underline.
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// The `n` in the `4:n` SGR subparameter.
+    #[must_use]
+    pub const fn sgr_subparam(self) -> u8 {
+        match self {
+            Self::Single => 1,
+            Self::Double => 2,
+            Self::Curly => 3,
+            Self::Dotted => 4,
+            Self::Dashed => 5,
+        }
+    }
+}
+
 /// Complete text style including colors, attributes, and optional hyperlink.
 ///
 /// Styles are immutable and cheap to copy. Use the builder methods to create
@@ -135,6 +199,14 @@ pub struct Style {
     pub bg: Option<Rgba>,
     /// Text rendering attributes.
     pub attributes: TextAttributes,
+    /// Underline shape, used when [`TextAttributes::UNDERLINE`] is set.
+    pub underline_style: UnderlineStyle,
+    /// Underline color (None = same as the foreground).
+    pub underline_color: Option<Rgba>,
+    /// Keep the foreground of the cells drawn over; see [`Self::with_preserve_fg`].
+    pub preserve_fg: bool,
+    /// Keep the background of the cells drawn over; see [`Self::with_preserve_bg`].
+    pub preserve_bg: bool,
 }
 
 impl Style {
@@ -143,14 +215,35 @@ impl Style {
         fg: None,
         bg: None,
         attributes: TextAttributes::empty(),
+        underline_style: UnderlineStyle::Single,
+        underline_color: None,
+        preserve_fg: false,
+        preserve_bg: false,
     };
 
+    /// Start an empty [`StylePatch`], which inherits everything.
+    #[must_use]
+    pub fn patch() -> StylePatch {
+        StylePatch::default()
+    }
+
     /// Create a new style builder.
     #[must_use]
     pub fn builder() -> StyleBuilder {
         StyleBuilder::default()
     }
 
+    /// Create a style using the terminal's default foreground and background
+    /// ([`Rgba::DEFAULT_FG`] and [`Rgba::DEFAULT_BG`]).
+    #[must_use]
+    pub const fn default_colors() -> Self {
+        Self {
+            fg: Some(Rgba::DEFAULT_FG),
+            bg: Some(Rgba::DEFAULT_BG),
+            ..Self::NONE
+        }
+    }
+
     /// Create a style with only foreground color.
     #[must_use]
     pub const fn fg(color: Rgba) -> Self {
@@ -158,6 +251,7 @@ impl Style {
             fg: Some(color),
             bg: None,
             attributes: TextAttributes::empty(),
+            ..Self::NONE
         }
     }
 
@@ -168,6 +262,7 @@ impl Style {
             fg: None,
             bg: Some(color),
             attributes: TextAttributes::empty(),
+            ..Self::NONE
         }
     }
 
@@ -178,6 +273,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::BOLD,
+            ..Self::NONE
         }
     }
 
@@ -188,6 +284,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::ITALIC,
+            ..Self::NONE
         }
     }
 
@@ -198,6 +295,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::UNDERLINE,
+            ..Self::NONE
         }
     }
 
@@ -208,6 +306,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::DIM,
+            ..Self::NONE
         }
     }
 
@@ -218,6 +317,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::INVERSE,
+            ..Self::NONE
         }
     }
 
@@ -228,6 +328,7 @@ impl Style {
             fg: None,
             bg: None,
             attributes: TextAttributes::STRIKETHROUGH,
+            ..Self::NONE
         }
     }
 
@@ -249,6 +350,48 @@ impl Style {
         }
     }
 
+    /// Create a style that keeps the background of the cells it's drawn
+    /// over; shorthand for `Style::NONE.with_preserve_bg()`.
+    #[must_use]
+    pub const fn bg_inherit() -> Self {
+        Self::NONE.with_preserve_bg()
+    }
+
+    /// Return a new style that keeps the foreground of the cells it's drawn
+    /// over.
+    ///
+    /// Text drawing (`draw_text*`, `draw_spans`, `draw_char_with_pool`)
+    /// reads each cell before writing it and puts its foreground back
+    /// afterwards, unchanged by this style's color, opacity, or blending;
+    /// only the glyph, background, and attributes are replaced.
+    #[must_use]
+    pub const fn with_preserve_fg(self) -> Self {
+        Self {
+            preserve_fg: true,
+            ..self
+        }
+    }
+
+    /// Return a new style that keeps the background of the cells it's drawn
+    /// over, so text over a gradient or pattern leaves it intact.
+    ///
+    /// The counterpart of [`Self::with_preserve_fg`]: only the glyph,
+    /// foreground, and attributes are replaced.
+    #[must_use]
+    pub const fn with_preserve_bg(self) -> Self {
+        Self {
+            preserve_bg: true,
+            ..self
+        }
+    }
+
+    /// Whether drawing with this style keeps any color of the cells it
+    /// covers.
+    #[must_use]
+    pub const fn preserves_colors(&self) -> bool {
+        self.preserve_fg || self.preserve_bg
+    }
+
     /// Return a new style with the specified attributes added.
     #[must_use]
     pub const fn with_attributes(self, attrs: TextAttributes) -> Self {
@@ -276,6 +419,42 @@ impl Style {
         self.with_attributes(TextAttributes::UNDERLINE)
     }
 
+    /// Return a new style with an underline of the given shape.
+    #[must_use]
+    pub const fn with_underline_style(self, style: UnderlineStyle) -> Self {
+        Self {
+            underline_style: style,
+            ..self.with_underline()
+        }
+    }
+
+    /// Return a new style with the overline attribute added.
+    #[must_use]
+    pub const fn with_overline(self) -> Self {
+        self.with_attributes(TextAttributes::OVERLINE)
+    }
+
+    /// Return a new style with the superscript attribute added.
+    #[must_use]
+    pub const fn with_superscript(self) -> Self {
+        self.with_attributes(TextAttributes::SUPERSCRIPT)
+    }
+
+    /// Return a new style with the subscript attribute added.
+    #[must_use]
+    pub const fn with_subscript(self) -> Self {
+        self.with_attributes(TextAttributes::SUBSCRIPT)
+    }
+
+    /// Return a new style with the specified underline color.
+    #[must_use]
+    pub const fn with_underline_color(self, color: Rgba) -> Self {
+        Self {
+            underline_color: Some(color),
+            ..self
+        }
+    }
+
     /// Return a new style with a hyperlink ID.
     #[must_use]
     pub const fn with_link(self, link_id: u32) -> Self {
@@ -285,19 +464,132 @@ impl Style {
         }
     }
 
+    /// Parse a spec such as `"bold italic #ff8800 on black"`.
+    ///
+    /// A spec lists attribute keywords (`bold`, `dim`, `italic`,
+    /// `underline`, `blink`, `inverse`, `hidden`, `strikethrough`,
+    /// `overline`, `superscript`, `subscript`), at most
+    /// one foreground color, and an optional `on <color>` background, in
+    /// any order. Colors are anything [`Rgba::parse`] accepts: hex, `rgb()`,
+    /// or a CSS name. `none` and `default` mean the terminal default color,
+    /// and on their own an empty style. Keywords are case-insensitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StyleParse`](crate::Error::StyleParse) with the
+    /// offending token and its byte offset for unknown words, invalid or
+    /// repeated colors, a trailing `on`, or an empty spec.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentui_rust::{Rgba, Style};
+    ///
+    /// let style = Style::parse("italic rgb(255, 136, 0) on default").unwrap();
+    /// assert_eq!(style, Style::fg(Rgba::from_hex("#ff8800").unwrap()).with_italic());
+    /// assert!(Style::parse("bold on").is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self> {
+        let parsed = spec::split(input, |token| Rgba::parse(token).is_ok())?;
+        Ok(Self {
+            fg: parsed.fg.map(Rgba::parse).transpose()?,
+            bg: parsed.bg.map(Rgba::parse).transpose()?,
+            attributes: parsed.attributes,
+            ..Self::NONE
+        })
+    }
+
+    /// Write the style as a spec that [`Self::parse`] reads back.
+    ///
+    /// Colors are written as hex, and the default color sentinels as
+    /// `default-fg` and `default-bg` (a bare `default` means no color). An
+    /// empty style is `none`. Links, the underline shape and color, and the
+    /// preserve flags have no spec syntax and are left out.
+    #[must_use]
+    pub fn to_spec_string(&self) -> String {
+        let color = |color: Rgba| {
+            if color.bits_eq(Rgba::DEFAULT_FG) {
+                "default-fg".to_string()
+            } else {
+                color.to_color_string()
+            }
+        };
+        let mut parts: Vec<String> = spec::ATTRIBUTE_KEYWORDS
+            .iter()
+            .filter(|(_, flag)| self.attributes.contains(*flag))
+            .map(|(name, _)| (*name).to_string())
+            .collect();
+        if let Some(fg) = self.fg {
+            parts.push(color(fg));
+        }
+        if let Some(bg) = self.bg {
+            parts.push(format!("on {}", color(bg)));
+        }
+        if parts.is_empty() {
+            "none".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+
     /// Check if this style has any non-default properties.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.fg.is_none() && self.bg.is_none() && self.attributes.is_empty()
+        self.fg.is_none()
+            && self.bg.is_none()
+            && self.attributes.is_empty()
+            && self.underline_color.is_none()
+            && !self.preserves_colors()
     }
 
     /// Merge two styles, with `other` taking precedence for set values.
+    ///
+    /// A color is set when it is `Some`, including `Some` of a transparent
+    /// color; `None` (terminal default) inherits from `self`. Attributes are
+    /// OR-merged, so `other` can add but not clear them, and its link wins
+    /// when it has one. The underline shape comes from `other` if it is
+    /// underlined. Preserve flags are OR-merged too. Otherwise this is
+    /// [`Self::apply`] with `other` as a patch; see [`StylePatch::from`].
     #[must_use]
     pub fn merge(self, other: Self) -> Self {
         Self {
-            fg: other.fg.or(self.fg),
-            bg: other.bg.or(self.bg),
-            attributes: self.attributes.merge(other.attributes),
+            preserve_fg: self.preserve_fg || other.preserve_fg,
+            preserve_bg: self.preserve_bg || other.preserve_bg,
+            ..self.apply(&StylePatch::from(other))
+        }
+    }
+
+    /// Apply a patch over this style: fields set in `patch` win, the rest
+    /// are kept. Patches have no preserve flags, so this style's are kept.
+    #[must_use]
+    pub fn apply(&self, patch: &StylePatch) -> Self {
+        let mut attributes = self.attributes;
+        for (flag, value) in patch.flags() {
+            if let Some(on) = value {
+                attributes.set(flag, on);
+            }
+        }
+        if let Some(link_id) = patch.link {
+            attributes = attributes.with_link_id(link_id);
+        }
+        Self {
+            fg: patch.fg.or(self.fg),
+            bg: patch.bg.or(self.bg),
+            attributes,
+            underline_style: patch.underline_style.unwrap_or(self.underline_style),
+            underline_color: patch.underline_color.or(self.underline_color),
+            preserve_fg: self.preserve_fg,
+            preserve_bg: self.preserve_bg,
+        }
+    }
+
+    /// This style's underline shape if it is underlined, else `base`.
+    #[must_use]
+    pub(crate) fn underline_style_over(self, base: UnderlineStyle) -> UnderlineStyle {
+        if self.attributes.contains(TextAttributes::UNDERLINE) {
+            self.underline_style
+        } else {
+            base
         }
     }
 }
@@ -351,6 +643,21 @@ impl StyleBuilder {
         self
     }
 
+    /// Add an underline of the given shape.
+    #[must_use]
+    pub fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.style.attributes |= TextAttributes::UNDERLINE;
+        self.style.underline_style = style;
+        self
+    }
+
+    /// Set underline color.
+    #[must_use]
+    pub fn underline_color(mut self, color: Rgba) -> Self {
+        self.style.underline_color = Some(color);
+        self
+    }
+
     /// Add blink attribute.
     #[must_use]
     pub fn blink(mut self) -> Self {
@@ -379,6 +686,27 @@ impl StyleBuilder {
         self
     }
 
+    /// Add overline attribute.
+    #[must_use]
+    pub fn overline(mut self) -> Self {
+        self.style.attributes |= TextAttributes::OVERLINE;
+        self
+    }
+
+    /// Add superscript attribute.
+    #[must_use]
+    pub fn superscript(mut self) -> Self {
+        self.style.attributes |= TextAttributes::SUPERSCRIPT;
+        self
+    }
+
+    /// Add subscript attribute.
+    #[must_use]
+    pub fn subscript(mut self) -> Self {
+        self.style.attributes |= TextAttributes::SUBSCRIPT;
+        self
+    }
+
     /// Set hyperlink ID.
     #[must_use]
     pub fn link(mut self, link_id: u32) -> Self {
@@ -393,6 +721,222 @@ impl StyleBuilder {
     }
 }
 
+/// Partial style for [`Style::apply`], e.g. "the theme's base style, but
+/// bold and with another foreground".
+///
+/// `None` fields inherit from the base style and `Some` fields replace it.
+/// Attributes are tri-state, so unlike [`Style::merge`] a patch can clear
+/// an attribute the base sets.
+///
+/// ```
+/// use opentui_rust::{Rgba, Style, TextAttributes};
+///
+/// let base = Style::fg(Rgba::WHITE).with_bg(Rgba::BLACK).with_italic();
+/// let style = base.apply(&Style::patch().bold(true).italic(false).fg(Rgba::RED));
+/// assert_eq!(style, Style::fg(Rgba::RED).with_bg(Rgba::BLACK).with_bold());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StylePatch {
+    /// Foreground color override.
+    pub fg: Option<Rgba>,
+    /// Background color override.
+    pub bg: Option<Rgba>,
+    /// Set or clear [`TextAttributes::BOLD`].
+    pub bold: Option<bool>,
+    /// Set or clear [`TextAttributes::DIM`].
+    pub dim: Option<bool>,
+    /// Set or clear [`TextAttributes::ITALIC`].
+    pub italic: Option<bool>,
+    /// Set or clear [`TextAttributes::UNDERLINE`].
+    pub underline: Option<bool>,
+    /// Set or clear [`TextAttributes::BLINK`].
+    pub blink: Option<bool>,
+    /// Set or clear [`TextAttributes::INVERSE`].
+    pub inverse: Option<bool>,
+    /// Set or clear [`TextAttributes::HIDDEN`].
+    pub hidden: Option<bool>,
+    /// Set or clear [`TextAttributes::STRIKETHROUGH`].
+    pub strikethrough: Option<bool>,
+    /// Set or clear [`TextAttributes::OVERLINE`].
+    pub overline: Option<bool>,
+    /// Set or clear [`TextAttributes::SUPERSCRIPT`].
+    pub superscript: Option<bool>,
+    /// Set or clear [`TextAttributes::SUBSCRIPT`].
+    pub subscript: Option<bool>,
+    /// Underline shape override.
+    pub underline_style: Option<UnderlineStyle>,
+    /// Underline color override.
+    pub underline_color: Option<Rgba>,
+    /// Hyperlink ID override; `Some(0)` removes the base style's link.
+    pub link: Option<u32>,
+}
+
+impl StylePatch {
+    /// Set the foreground color.
+    #[must_use]
+    pub fn fg(mut self, color: Rgba) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Set the background color.
+    #[must_use]
+    pub fn bg(mut self, color: Rgba) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) bold.
+    #[must_use]
+    pub fn bold(mut self, on: bool) -> Self {
+        self.bold = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) dim.
+    #[must_use]
+    pub fn dim(mut self, on: bool) -> Self {
+        self.dim = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) italic.
+    #[must_use]
+    pub fn italic(mut self, on: bool) -> Self {
+        self.italic = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) underline.
+    #[must_use]
+    pub fn underline(mut self, on: bool) -> Self {
+        self.underline = Some(on);
+        self
+    }
+
+    /// Set underline with the given shape.
+    #[must_use]
+    pub fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.underline = Some(true);
+        self.underline_style = Some(style);
+        self
+    }
+
+    /// Set the underline color.
+    #[must_use]
+    pub fn underline_color(mut self, color: Rgba) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) blink.
+    #[must_use]
+    pub fn blink(mut self, on: bool) -> Self {
+        self.blink = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) inverse.
+    #[must_use]
+    pub fn inverse(mut self, on: bool) -> Self {
+        self.inverse = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) hidden.
+    #[must_use]
+    pub fn hidden(mut self, on: bool) -> Self {
+        self.hidden = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) strikethrough.
+    #[must_use]
+    pub fn strikethrough(mut self, on: bool) -> Self {
+        self.strikethrough = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) overline.
+    #[must_use]
+    pub fn overline(mut self, on: bool) -> Self {
+        self.overline = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) superscript.
+    #[must_use]
+    pub fn superscript(mut self, on: bool) -> Self {
+        self.superscript = Some(on);
+        self
+    }
+
+    /// Set (`true`) or clear (`false`) subscript.
+    #[must_use]
+    pub fn subscript(mut self, on: bool) -> Self {
+        self.subscript = Some(on);
+        self
+    }
+
+    /// Set the hyperlink ID; 0 removes the link.
+    #[must_use]
+    pub fn link(mut self, link_id: u32) -> Self {
+        self.link = Some(link_id);
+        self
+    }
+
+    /// Check if the patch changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Attribute flags paired with their tri-state values.
+    fn flags(&self) -> [(TextAttributes, Option<bool>); 11] {
+        [
+            (TextAttributes::BOLD, self.bold),
+            (TextAttributes::DIM, self.dim),
+            (TextAttributes::ITALIC, self.italic),
+            (TextAttributes::UNDERLINE, self.underline),
+            (TextAttributes::BLINK, self.blink),
+            (TextAttributes::INVERSE, self.inverse),
+            (TextAttributes::HIDDEN, self.hidden),
+            (TextAttributes::STRIKETHROUGH, self.strikethrough),
+            (TextAttributes::OVERLINE, self.overline),
+            (TextAttributes::SUPERSCRIPT, self.superscript),
+            (TextAttributes::SUBSCRIPT, self.subscript),
+        ]
+    }
+}
+
+impl From<Style> for StylePatch {
+    /// The patch [`Style::merge`] applies: the style's `Some` colors, its
+    /// attributes as `Some(true)` (unset ones inherit), its underline shape
+    /// if underlined, and its link if it has one.
+    fn from(style: Style) -> Self {
+        let flag = |attr: TextAttributes| style.attributes.contains(attr).then_some(true);
+        let underline = flag(TextAttributes::UNDERLINE);
+        Self {
+            fg: style.fg,
+            bg: style.bg,
+            bold: flag(TextAttributes::BOLD),
+            dim: flag(TextAttributes::DIM),
+            italic: flag(TextAttributes::ITALIC),
+            underline,
+            blink: flag(TextAttributes::BLINK),
+            inverse: flag(TextAttributes::INVERSE),
+            hidden: flag(TextAttributes::HIDDEN),
+            strikethrough: flag(TextAttributes::STRIKETHROUGH),
+            overline: flag(TextAttributes::OVERLINE),
+            superscript: flag(TextAttributes::SUPERSCRIPT),
+            subscript: flag(TextAttributes::SUBSCRIPT),
+            underline_style: underline.map(|_| style.underline_style),
+            underline_color: style.underline_color,
+            link: style.attributes.link_id(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +980,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_underline_style_and_color_merge() {
+        let curly = Style::NONE
+            .with_underline_style(UnderlineStyle::Curly)
+            .with_underline_color(Rgba::RED);
+        assert!(curly.attributes.contains(TextAttributes::UNDERLINE));
+        assert!(!Style::NONE.with_underline_color(Rgba::RED).is_empty());
+
+        // A non-underlined overlay keeps the base shape and color.
+        let merged = curly.merge(Style::bold());
+        assert_eq!(merged.underline_style, UnderlineStyle::Curly);
+        assert_eq!(merged.underline_color, Some(Rgba::RED));
+
+        // An underlined overlay replaces the shape.
+        let merged = curly.merge(Style::underline());
+        assert_eq!(merged.underline_style, UnderlineStyle::Single);
+        assert_eq!(merged.underline_color, Some(Rgba::RED));
+
+        let built = Style::builder()
+            .underline_style(UnderlineStyle::Dashed)
+            .underline_color(Rgba::BLUE)
+            .build();
+        assert_eq!(
+            built,
+            Style::NONE
+                .with_underline_style(UnderlineStyle::Dashed)
+                .with_underline_color(Rgba::BLUE)
+        );
+    }
+
     #[test]
     fn test_text_attributes_link_id_packing() {
         let attrs = TextAttributes::BOLD.with_link_id(0x12_3456);
@@ -465,4 +1039,238 @@ mod tests {
         let attrs = TextAttributes::empty().with_link_id(0x1FF_FFFF);
         assert_eq!(attrs.link_id(), Some(TextAttributes::MAX_LINK_ID));
     }
+
+    #[test]
+    fn test_overline_and_script_keep_link_packing() {
+        let style = Style::builder().overline().superscript().link(7).build();
+        assert_eq!(
+            style,
+            Style::NONE.with_overline().with_superscript().with_link(7)
+        );
+        assert_eq!(style.attributes.link_id(), Some(7));
+        assert!(
+            style
+                .attributes
+                .with_link_id(TextAttributes::MAX_LINK_ID)
+                .contains(TextAttributes::OVERLINE | TextAttributes::SUPERSCRIPT)
+        );
+        assert_eq!(
+            style.attributes.clear_link_id(),
+            TextAttributes::OVERLINE | TextAttributes::SUPERSCRIPT
+        );
+
+        let lowered = style.apply(&Style::patch().superscript(false).subscript(true));
+        assert_eq!(
+            lowered.attributes.flags_only(),
+            TextAttributes::OVERLINE | TextAttributes::SUBSCRIPT
+        );
+        assert_eq!(lowered.to_spec_string(), "overline subscript");
+        assert_eq!(
+            Style::parse("OVERLINE subscript").unwrap(),
+            lowered.with_link(0)
+        );
+    }
+
+    #[test]
+    fn test_patch_clears_attribute() {
+        let base = Style::fg(Rgba::RED).with_bold().with_italic();
+        let style = base.apply(&Style::patch().bold(false));
+        assert!(!style.attributes.contains(TextAttributes::BOLD));
+        assert!(style.attributes.contains(TextAttributes::ITALIC));
+        assert_eq!(style.fg, Some(Rgba::RED));
+
+        // Merging can't clear: unset attributes inherit.
+        assert!(
+            base.merge(Style::NONE)
+                .attributes
+                .contains(TextAttributes::BOLD)
+        );
+        assert!(Style::patch().is_empty());
+        assert_eq!(base.apply(&Style::patch()), base);
+    }
+
+    #[test]
+    fn test_patch_color_override_vs_inherit() {
+        let base = Style::fg(Rgba::WHITE).with_bg(Rgba::BLACK);
+        let style = base.apply(&Style::patch().fg(Rgba::RED));
+        assert_eq!(style.fg, Some(Rgba::RED));
+        assert_eq!(style.bg, Some(Rgba::BLACK));
+
+        // A transparent color is set, so it overrides.
+        let style = base.merge(Style::bg(Rgba::TRANSPARENT));
+        assert_eq!(style.bg, Some(Rgba::TRANSPARENT));
+        assert_eq!(style.fg, Some(Rgba::WHITE));
+    }
+
+    #[test]
+    fn test_patch_link_propagation() {
+        let linked = Style::bold().with_link(7);
+        assert_eq!(
+            linked
+                .apply(&Style::patch().italic(true))
+                .attributes
+                .link_id(),
+            Some(7)
+        );
+        assert_eq!(
+            linked.apply(&Style::patch().link(9)).attributes.link_id(),
+            Some(9)
+        );
+        let unlinked = linked.apply(&Style::patch().link(0));
+        assert_eq!(unlinked.attributes.link_id(), None);
+        assert!(unlinked.attributes.contains(TextAttributes::BOLD));
+
+        // Clearing attributes keeps the link bits.
+        let style = linked.apply(&Style::patch().bold(false));
+        assert_eq!(style.attributes.link_id(), Some(7));
+        assert!(style.attributes.flags_only().is_empty());
+
+        assert_eq!(Style::NONE.merge(linked).attributes.link_id(), Some(7));
+        assert_eq!(StylePatch::from(linked).link, Some(7));
+    }
+
+    #[test]
+    fn test_patch_underline_style() {
+        let curly = Style::NONE.with_underline_style(UnderlineStyle::Curly);
+        let style = Style::bold().apply(&Style::patch().underline_style(UnderlineStyle::Dotted));
+        assert!(style.attributes.contains(TextAttributes::UNDERLINE));
+        assert_eq!(style.underline_style, UnderlineStyle::Dotted);
+
+        let plain = curly.apply(&Style::patch().underline(false));
+        assert!(!plain.attributes.contains(TextAttributes::UNDERLINE));
+        assert_eq!(
+            curly
+                .apply(&StylePatch::from(Style::bold()))
+                .underline_style,
+            UnderlineStyle::Curly
+        );
+    }
+
+    #[test]
+    fn test_parse_every_attribute_keyword() {
+        for (name, flag) in spec::ATTRIBUTE_KEYWORDS {
+            let style = Style::parse(name).unwrap();
+            assert_eq!(style, Style::NONE.with_attributes(flag), "{name}");
+            assert_eq!(style.to_spec_string(), name);
+        }
+        let all = Style::parse(
+            "BOLD dim Italic underline blink inverse hidden strikethrough overline superscript \
+             subscript",
+        );
+        assert_eq!(all.unwrap().attributes, TextAttributes::all());
+    }
+
+    #[test]
+    fn test_parse_color_syntaxes() {
+        let orange = Rgba::from_hex("#ff8800").unwrap();
+        assert_eq!(Style::parse("#ff8800").unwrap(), Style::fg(orange));
+        assert_eq!(Style::parse("rgb(255, 136, 0)").unwrap(), Style::fg(orange));
+        assert_eq!(
+            Style::parse("bold italic #ff8800 on black").unwrap(),
+            Style::fg(orange)
+                .with_bg(Rgba::BLACK)
+                .with_bold()
+                .with_italic()
+        );
+        assert_eq!(
+            Style::parse("on rgb(0, 0, 255) underline").unwrap(),
+            Style::bg(Rgba::BLUE).with_underline()
+        );
+    }
+
+    #[test]
+    fn test_parse_defaults_and_missing_background() {
+        let style = Style::parse("bold red").unwrap();
+        assert_eq!(style.bg, None);
+        assert_eq!(style.fg, Some(Rgba::RED));
+        assert_eq!(Style::parse("none").unwrap(), Style::NONE);
+        assert_eq!(Style::parse(" default ").unwrap(), Style::NONE);
+        assert_eq!(
+            Style::parse("default on red").unwrap(),
+            Style::bg(Rgba::RED)
+        );
+        assert_eq!(Style::NONE.to_spec_string(), "none");
+    }
+
+    #[test]
+    fn test_parse_errors_pinpoint_token() {
+        let cases = [
+            ("bold bolt", "bolt", 5),
+            ("bold on", "on", 5),
+            ("red blue", "blue", 4),
+            ("on red on blue", "blue", 10),
+            ("italic #ggg", "#ggg", 7),
+            ("rgb(1, 2", "rgb(1, 2", 0),
+            ("   ", "   ", 0),
+        ];
+        for (input, bad, at) in cases {
+            let Err(crate::Error::StyleParse { token, offset, .. }) = Style::parse(input) else {
+                panic!("{input:?} should fail to parse");
+            };
+            assert_eq!((token.as_str(), offset), (bad, at), "{input:?}");
+        }
+        let err = Style::parse("bold bolt").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid style spec at byte 5 (`bolt`): unknown attribute or color"
+        );
+    }
+
+    #[test]
+    fn test_spec_string_round_trips() {
+        let styles = [
+            Style::fg(Rgba::from_hex("#ff8800").unwrap()).with_bold(),
+            Style::bg(Rgba::from_rgba_u8(1, 2, 3, 128)).with_italic(),
+            Style::builder()
+                .fg(Rgba::WHITE)
+                .bg(Rgba::BLACK)
+                .inverse()
+                .hidden()
+                .build(),
+            Style::NONE,
+        ];
+        for style in styles {
+            let spec = style.to_spec_string();
+            assert_eq!(Style::parse(&spec).unwrap(), style, "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_spec_string_round_trips_default_colors() {
+        let style = Style::default_colors().with_bold();
+        let spec = style.to_spec_string();
+        assert_eq!(spec, "bold default-fg on default-bg");
+        let back = Style::parse(&spec).unwrap();
+        assert!(back.fg.unwrap().bits_eq(Rgba::DEFAULT_FG));
+        assert!(back.bg.unwrap().bits_eq(Rgba::DEFAULT_BG));
+        assert_eq!(back.attributes, style.attributes);
+    }
+
+    #[test]
+    fn test_default_colors_style() {
+        let style = Style::default_colors();
+        assert!(style.fg.unwrap().bits_eq(Rgba::DEFAULT_FG));
+        assert!(style.bg.unwrap().bits_eq(Rgba::DEFAULT_BG));
+        assert!(style.attributes.is_empty());
+
+        let cell = crate::cell::Cell::new('a', style);
+        assert!(cell.fg.is_default() && cell.bg.is_default());
+    }
+
+    #[test]
+    fn test_preserve_flags() {
+        assert!(Style::NONE.is_empty());
+        assert!(!Style::bg_inherit().is_empty());
+        assert!(Style::bg_inherit().preserve_bg);
+        assert!(!Style::bg_inherit().preserve_fg);
+
+        let merged = Style::fg(Rgba::RED)
+            .with_preserve_fg()
+            .merge(Style::bg_inherit().with_bold());
+        assert!(merged.preserve_fg && merged.preserve_bg);
+        assert_eq!(merged.fg, Some(Rgba::RED));
+
+        let patched = Style::bg_inherit().apply(&Style::patch().bold(true));
+        assert!(patched.preserve_bg);
+    }
 }
@@ -0,0 +1,210 @@
+//! Serde support for [`TextAttributes`] and [`Style`] (`serde` feature).
+//!
+//! `TextAttributes` serializes as a list of the keywords [`Style::parse`]
+//! reads, with a packed link ID written as a trailing `"link:<id>"` entry.
+//! `Style` serializes as a table whose unset fields are omitted, with its
+//! link ID pulled out into a `link` field:
+//!
+//! ```json
+//! { "fg": "#ff8800", "attributes": ["bold", "italic"], "link": 3 }
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::spec::ATTRIBUTE_KEYWORDS;
+use super::{Style, TextAttributes, UnderlineStyle};
+use crate::color::Rgba;
+
+const LINK_PREFIX: &str = "link:";
+
+impl Serialize for TextAttributes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names = ATTRIBUTE_KEYWORDS
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(name, _)| *name);
+        let mut seq = serializer.serialize_seq(None)?;
+        for name in names {
+            seq.serialize_element(name)?;
+        }
+        if let Some(link) = self.link_id() {
+            seq.serialize_element(&format!("{LINK_PREFIX}{link}"))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TextAttributes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(AttributesVisitor)
+    }
+}
+
+struct AttributesVisitor;
+
+impl<'de> Visitor<'de> for AttributesVisitor {
+    type Value = TextAttributes;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of attribute names")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TextAttributes, A::Error> {
+        let mut attributes = TextAttributes::empty();
+        while let Some(name) = seq.next_element::<String>()? {
+            if let Some(link) = name.strip_prefix(LINK_PREFIX) {
+                let link = link
+                    .parse()
+                    .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&name), &self))?;
+                attributes.set_link_id(link);
+            } else if let Some(&(_, flag)) = ATTRIBUTE_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| name.eq_ignore_ascii_case(keyword))
+            {
+                attributes |= flag;
+            } else {
+                return Err(de::Error::custom(format!("unknown attribute `{name}`")));
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// The serialized shape of a [`Style`].
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleRepr {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fg: Option<Rgba>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bg: Option<Rgba>,
+    #[serde(skip_serializing_if = "TextAttributes::is_empty")]
+    attributes: TextAttributes,
+    #[serde(skip_serializing_if = "is_single")]
+    underline_style: UnderlineStyle,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underline_color: Option<Rgba>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    preserve_fg: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    preserve_bg: bool,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde passes a reference
+fn is_single(style: &UnderlineStyle) -> bool {
+    *style == UnderlineStyle::Single
+}
+
+impl Serialize for Style {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StyleRepr {
+            fg: self.fg,
+            bg: self.bg,
+            attributes: self.attributes.flags_only(),
+            underline_style: self.underline_style,
+            underline_color: self.underline_color,
+            link: self.attributes.link_id(),
+            preserve_fg: self.preserve_fg,
+            preserve_bg: self.preserve_bg,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Style {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = StyleRepr::deserialize(deserializer)?;
+        // A link given both ways resolves to the explicit `link` field.
+        let attributes = repr
+            .link
+            .map_or(repr.attributes, |link| repr.attributes.with_link_id(link));
+        Ok(Self {
+            fg: repr.fg,
+            bg: repr.bg,
+            attributes,
+            underline_style: repr.underline_style,
+            underline_color: repr.underline_color,
+            preserve_fg: repr.preserve_fg,
+            preserve_bg: repr.preserve_bg,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: &T) -> (String, T) {
+        let json = serde_json::to_string(value).unwrap();
+        let back = serde_json::from_str(&json).unwrap();
+        (json, back)
+    }
+
+    #[test]
+    fn attributes_as_names() {
+        let attributes = TextAttributes::BOLD | TextAttributes::STRIKETHROUGH;
+        let (json, back) = round_trip(&attributes);
+        assert_eq!(json, r#"["bold","strikethrough"]"#);
+        assert_eq!(back, attributes);
+    }
+
+    #[test]
+    fn attributes_keep_link_id() {
+        let attributes = TextAttributes::ITALIC.with_link_id(42);
+        let (json, back) = round_trip(&attributes);
+        assert_eq!(json, r#"["italic","link:42"]"#);
+        assert_eq!(back, attributes);
+    }
+
+    #[test]
+    fn unknown_attribute_is_an_error() {
+        let err = serde_json::from_str::<TextAttributes>(r#"["bold","sparkly"]"#).unwrap_err();
+        assert!(err.to_string().contains("sparkly"), "{err}");
+        assert!(serde_json::from_str::<TextAttributes>(r#"["link:x"]"#).is_err());
+    }
+
+    #[test]
+    fn style_round_trip_with_link() {
+        let style = Style::builder()
+            .fg(Rgba::from_rgb_u8(255, 136, 0))
+            .bg(Rgba::BLACK)
+            .bold()
+            .underline()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Rgba::RED)
+            .link(7)
+            .build();
+        let (json, back) = round_trip(&style);
+        assert_eq!(
+            json,
+            r##"{"fg":"#ff8800","bg":"#000000","attributes":["bold","underline"],"underline_style":"curly","underline_color":"#ff0000","link":7}"##
+        );
+        assert_eq!(back, style);
+    }
+
+    #[test]
+    fn empty_style_is_an_empty_table() {
+        let (json, back) = round_trip(&Style::NONE);
+        assert_eq!(json, "{}");
+        assert_eq!(back, Style::NONE);
+    }
+
+    #[test]
+    fn unknown_style_field_is_an_error() {
+        assert!(serde_json::from_str::<Style>(r#"{"colour":"red"}"#).is_err());
+    }
+
+    #[test]
+    fn style_round_trip_with_preserve_flags() {
+        let style = Style::bg_inherit().with_bold();
+        let (json, back) = round_trip(&style);
+        assert_eq!(json, r#"{"attributes":["bold"],"preserve_bg":true}"#);
+        assert_eq!(back, style);
+    }
+}
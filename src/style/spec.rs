@@ -0,0 +1,159 @@
+//! Human-readable style specs such as `"bold italic #ff8800 on black"`.
+//!
+//! A spec is a whitespace-separated list of attribute keywords, at most one
+//! foreground color, and an optional `on <color>` background clause, in any
+//! order. Keywords and `on` are case-insensitive. `none` and `default` stand
+//! for the terminal default color, and on their own give an empty style.
+
+use super::TextAttributes;
+use crate::error::{Error, Result};
+
+/// Attribute keywords, in the order [`Style::to_spec_string`] writes them.
+///
+/// [`Style::to_spec_string`]: super::Style::to_spec_string
+pub const ATTRIBUTE_KEYWORDS: [(&str, TextAttributes); 11] = [
+    ("bold", TextAttributes::BOLD),
+    ("dim", TextAttributes::DIM),
+    ("italic", TextAttributes::ITALIC),
+    ("underline", TextAttributes::UNDERLINE),
+    ("blink", TextAttributes::BLINK),
+    ("inverse", TextAttributes::INVERSE),
+    ("hidden", TextAttributes::HIDDEN),
+    ("strikethrough", TextAttributes::STRIKETHROUGH),
+    ("overline", TextAttributes::OVERLINE),
+    ("superscript", TextAttributes::SUPERSCRIPT),
+    ("subscript", TextAttributes::SUBSCRIPT),
+];
+
+/// A spec split into attributes and color tokens, which are left for the
+/// caller to resolve. `None` colors are the terminal default.
+#[derive(Debug, Default)]
+pub struct Spec<'a> {
+    pub attributes: TextAttributes,
+    pub fg: Option<&'a str>,
+    pub bg: Option<&'a str>,
+}
+
+/// Split `input` into a [`Spec`], using `is_color` to recognize color
+/// tokens.
+///
+/// # Errors
+///
+/// Returns [`Error::StyleParse`] for the first token that is neither a
+/// keyword nor a color, a repeated foreground or background, a trailing
+/// `on`, or an empty spec.
+pub fn split(input: &str, is_color: impl Fn(&str) -> bool) -> Result<Spec<'_>> {
+    let mut spec = Spec::default();
+    let (mut foreground_seen, mut background_seen) = (false, false);
+    let mut tokens = tokens(input);
+    let mut any = false;
+
+    while let Some((offset, token)) = tokens.next() {
+        any = true;
+        if let Some(flag) = attribute(token) {
+            spec.attributes |= flag;
+        } else if token.eq_ignore_ascii_case("on") {
+            let Some((offset, color)) = tokens.next() else {
+                return Err(error(
+                    token,
+                    offset,
+                    "expected a background color after `on`",
+                ));
+            };
+            spec.bg = color_token(color, offset, &is_color)?;
+            if std::mem::replace(&mut background_seen, true) {
+                return Err(error(color, offset, "background color given twice"));
+            }
+        } else {
+            spec.fg = color_token(token, offset, &is_color)?;
+            if std::mem::replace(&mut foreground_seen, true) {
+                return Err(error(token, offset, "foreground color given twice"));
+            }
+        }
+    }
+
+    if any {
+        Ok(spec)
+    } else {
+        Err(error(input, 0, "empty style spec; use `none` for no style"))
+    }
+}
+
+fn attribute(token: &str) -> Option<TextAttributes> {
+    ATTRIBUTE_KEYWORDS
+        .iter()
+        .find(|(name, _)| token.eq_ignore_ascii_case(name))
+        .map(|&(_, flag)| flag)
+}
+
+fn color_token(
+    token: &str,
+    offset: usize,
+    is_color: impl Fn(&str) -> bool,
+) -> Result<Option<&str>> {
+    if token.eq_ignore_ascii_case("none") || token.eq_ignore_ascii_case("default") {
+        Ok(None)
+    } else if is_color(token) {
+        Ok(Some(token))
+    } else {
+        Err(error(token, offset, "unknown attribute or color"))
+    }
+}
+
+fn error(token: &str, offset: usize, message: &str) -> Error {
+    Error::StyleParse {
+        token: token.to_string(),
+        offset,
+        message: message.to_string(),
+    }
+}
+
+/// Whitespace-separated tokens with their byte offsets. Parentheses group,
+/// so `rgb(255, 136, 0)` is one token.
+fn tokens(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = input.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while rest.next_if(|(_, ch)| ch.is_whitespace()).is_some() {}
+        let (start, _) = *rest.peek()?;
+        let mut depth = 0usize;
+        let mut end = input.len();
+        while let Some(&(idx, ch)) = rest.peek() {
+            if ch.is_whitespace() && depth == 0 {
+                end = idx;
+                break;
+            }
+            match ch {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            rest.next();
+        }
+        Some((start, &input[start..end]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_keep_functional_colors_whole() {
+        let tokens: Vec<_> = tokens("  bold rgb(255, 136, 0)\ton  black ").collect();
+        assert_eq!(
+            tokens,
+            [
+                (2, "bold"),
+                (7, "rgb(255, 136, 0)"),
+                (24, "on"),
+                (28, "black")
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_paren_runs_to_the_end() {
+        let tokens: Vec<_> = tokens("rgb(1, 2 bold").collect();
+        assert_eq!(tokens, [(0, "rgb(1, 2 bold")]);
+    }
+}
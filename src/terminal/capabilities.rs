@@ -1,5 +1,11 @@
 //! Terminal capability detection.
 
+mod report;
+
+pub use report::{CapabilityReport, Evidence, Feature, FeatureReport, FeatureStatus};
+
+use crate::ansi::LineDrawing;
+use crate::event::{LogLevel, LogRecord, emit_log_record};
 use crate::unicode::WidthMethod;
 use std::env;
 
@@ -52,8 +58,79 @@ pub struct Capabilities {
     pub sixel: bool,
     /// Terminal supports explicit cursor positioning (DECCRA).
     pub explicit_cursor_positioning: bool,
+    /// Terminal supports underline shapes (SGR `4:n`) and underline color
+    /// (SGR 58).
+    pub styled_underlines: bool,
+    /// Terminal supports repeating the preceding character (REP, `CSI n b`).
+    pub rep: bool,
+    /// Terminal supports double-width and double-height lines (DECDWL,
+    /// DECDHL).
+    pub dec_line_attributes: bool,
+    /// Terminal shows superscript and subscript text (SGR 73/74).
+    ///
+    /// Never detected: only a few emulators, such as mintty, implement
+    /// them, and the rest drop the codes and show plain text. Set it for a
+    /// terminal known to, and check it before relying on
+    /// [`TextAttributes::SUPERSCRIPT`](crate::TextAttributes::SUPERSCRIPT)
+    /// or [`TextAttributes::SUBSCRIPT`](crate::TextAttributes::SUBSCRIPT) to
+    /// carry meaning. Overline (SGR 53) needs no flag: it is widely shown
+    /// and harmless where it isn't.
+    pub superscript: bool,
     /// Terminal name if known.
     pub term_name: Option<String>,
+    /// What [`Capabilities::detect`] read from the environment, or `None`
+    /// for capabilities that weren't detected. Kept for
+    /// [`Capabilities::report`].
+    pub detected_from: Option<Box<DetectionInputs>>,
+    /// Features turned on by terminal replies, with the reply, escaped, that
+    /// turned each on. Kept for [`Capabilities::report`].
+    pub query_replies: Vec<(Feature, String)>,
+}
+
+/// The environment [`Capabilities::detect`] reads.
+///
+/// Detection from the same inputs gives the same capabilities, so a copy
+/// from a bug report, through [`Capabilities::from_inputs`], reproduces
+/// what the reporter's terminal was detected as.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetectionInputs {
+    /// `TERM`.
+    pub term: String,
+    /// `COLORTERM`.
+    pub colorterm: String,
+    /// `TERM_PROGRAM`.
+    pub term_program: String,
+    /// `KITTY_WINDOW_ID`, if set.
+    pub kitty_window_id: Option<String>,
+    /// Whether `XTERM_VERSION` is set.
+    pub xterm_version: bool,
+    /// Whether `WT_SESSION` is set.
+    pub wt_session: bool,
+    /// `LANG`.
+    pub lang: String,
+    /// `LC_ALL`.
+    pub lc_all: String,
+    /// `LC_CTYPE`.
+    pub lc_ctype: String,
+}
+
+impl DetectionInputs {
+    /// Read the inputs from the environment.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self {
+            term: env::var("TERM").unwrap_or_default(),
+            colorterm: env::var("COLORTERM").unwrap_or_default(),
+            term_program: env::var("TERM_PROGRAM").unwrap_or_default(),
+            kitty_window_id: env::var("KITTY_WINDOW_ID").ok(),
+            xterm_version: env::var_os("XTERM_VERSION").is_some(),
+            wt_session: env::var_os("WT_SESSION").is_some(),
+            lang: env::var("LANG").unwrap_or_default(),
+            lc_all: env::var("LC_ALL").unwrap_or_default(),
+            lc_ctype: env::var("LC_CTYPE").unwrap_or_default(),
+        }
+    }
 }
 
 impl Default for Capabilities {
@@ -84,12 +161,80 @@ impl Default for Capabilities {
             sixel: false,
             // Conservative: DECCRA is widely supported but not universal
             explicit_cursor_positioning: false,
+            // Conservative: older terminals misparse `4:3` as other attributes
+            styled_underlines: false,
+            // Conservative: REP is ECMA-48 but missing from many emulators
+            rep: false,
+            // Conservative: most emulators ignore DEC line attributes
+            dec_line_attributes: false,
+            // Never detected; see the field docs
+            superscript: false,
             term_name: None,
+            detected_from: None,
+            query_replies: Vec::new(),
         }
     }
 }
 
+/// A flag as a log field value.
+const fn bool_str(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
+
 impl Capabilities {
+    /// A terminal that supports everything: true color, Unicode, and every
+    /// protocol extension.
+    #[must_use]
+    pub fn ideal() -> Self {
+        Self {
+            color: ColorSupport::TrueColor,
+            unicode: true,
+            width_method: WidthMethod::default(),
+            hyperlinks: true,
+            sync_output: true,
+            mouse: true,
+            focus: true,
+            bracketed_paste: true,
+            kitty_keyboard: true,
+            kitty_graphics: true,
+            sgr_pixels: true,
+            color_scheme_updates: true,
+            explicit_width: true,
+            scaled_text: true,
+            sixel: true,
+            explicit_cursor_positioning: true,
+            styled_underlines: true,
+            rep: true,
+            dec_line_attributes: true,
+            superscript: true,
+            term_name: None,
+            detected_from: None,
+            query_replies: Vec::new(),
+        }
+    }
+
+    /// A terminal that supports nothing beyond cursor movement: no color,
+    /// no Unicode, no mouse.
+    #[must_use]
+    pub fn minimal() -> Self {
+        Self {
+            color: ColorSupport::None,
+            ..Self::default()
+        }
+    }
+
+    /// A legacy terminal with 16 colors, Unicode, and basic mouse tracking,
+    /// but none of the newer extensions.
+    #[must_use]
+    pub fn legacy_16color() -> Self {
+        Self {
+            color: ColorSupport::Basic,
+            unicode: true,
+            mouse: true,
+            ..Self::default()
+        }
+    }
+
     /// Detect terminal capabilities from environment.
     ///
     /// Probes environment variables (TERM, COLORTERM, TERM_PROGRAM, etc.)
@@ -97,35 +242,57 @@ impl Capabilities {
     /// and enables features only when detection confirms support.
     #[must_use]
     pub fn detect() -> Self {
-        let term = env::var("TERM").unwrap_or_default();
-        let colorterm = env::var("COLORTERM").unwrap_or_default();
-        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
-        let kitty_window_id = env::var("KITTY_WINDOW_ID").ok();
-
-        let color = Self::detect_color(&term, &colorterm);
-        let unicode = Self::detect_unicode();
-        let kitty_present = kitty_window_id.is_some();
-        let hyperlinks = Self::detect_hyperlinks(&term, &term_program, kitty_present);
-        let sync_output = Self::detect_sync(&term, &term_program, kitty_present);
-        let kitty_keyboard = kitty_present;
-        let kitty_graphics = kitty_present;
+        let inputs = DetectionInputs::from_env();
+        let caps = Self::from_inputs(inputs.clone());
+
+        if inputs.term.is_empty() {
+            emit_log_record(
+                &LogRecord::new(
+                    LogLevel::Warn,
+                    "TERM is not set; assuming no color, mouse, or paste support",
+                )
+                .with_target(module_path!()),
+            );
+        }
+        let color_name = format!("{:?}", caps.color);
+        emit_log_record(
+            &LogRecord::new(LogLevel::Debug, "detected terminal capabilities")
+                .with_target(module_path!())
+                .with_fields(&[
+                    ("term", &inputs.term),
+                    ("term_program", &inputs.term_program),
+                    ("color", &color_name),
+                    ("sync_output", bool_str(caps.sync_output)),
+                    ("hyperlinks", bool_str(caps.hyperlinks)),
+                    ("kitty", bool_str(inputs.kitty_window_id.is_some())),
+                ]),
+        );
+        caps
+    }
+
+    /// Detect capabilities from `inputs` instead of the environment.
+    #[must_use]
+    pub fn from_inputs(inputs: DetectionInputs) -> Self {
+        let term = inputs.term.as_str();
+        let term_program = inputs.term_program.as_str();
+        let kitty_present = inputs.kitty_window_id.is_some();
 
         // Detect basic terminal features based on TERM value
         // These features are widely supported in any xterm-compatible terminal
-        let is_xterm_compatible = Self::is_xterm_compatible(&term);
+        let is_xterm_compatible = Self::is_xterm_compatible(term);
 
         Self {
-            color,
-            unicode,
+            color: Self::detect_color(term, &inputs.colorterm),
+            unicode: Self::detect_unicode(&inputs),
             width_method: WidthMethod::default(),
-            hyperlinks,
-            sync_output,
+            hyperlinks: Self::detect_hyperlinks(term, term_program, kitty_present),
+            sync_output: Self::detect_sync(term, term_program, kitty_present),
             // Mouse/focus/bracketed-paste require xterm compatibility
             mouse: is_xterm_compatible,
             focus: is_xterm_compatible,
             bracketed_paste: is_xterm_compatible,
-            kitty_keyboard,
-            kitty_graphics,
+            kitty_keyboard: kitty_present,
+            kitty_graphics: kitty_present,
             sgr_pixels: false,
             color_scheme_updates: false,
             explicit_width: false,
@@ -133,7 +300,21 @@ impl Capabilities {
             sixel: term.contains("sixel"),
             // DECCRA (explicit cursor positioning) is widely supported in modern terminals
             explicit_cursor_positioning: is_xterm_compatible,
-            term_name: if term.is_empty() { None } else { Some(term) },
+            styled_underlines: Self::detect_styled_underlines(term, term_program, kitty_present),
+            rep: Self::detect_rep(term, term_program, kitty_present),
+            dec_line_attributes: Self::detect_dec_line_attributes(
+                term_program,
+                inputs.xterm_version,
+                inputs.wt_session,
+            ),
+            superscript: false,
+            term_name: if term.is_empty() {
+                None
+            } else {
+                Some(term.to_string())
+            },
+            detected_from: Some(Box::new(inputs)),
+            query_replies: Vec::new(),
         }
     }
 
@@ -179,23 +360,38 @@ impl Capabilities {
 
     /// Apply a best-effort capability response (from query output).
     pub fn apply_query_response(&mut self, response: &str) {
-        if response.contains("[?u") {
-            self.kitty_keyboard = true;
-        }
+        self.record_reply(response.as_bytes(), |caps| {
+            if response.contains("[?u") {
+                caps.kitty_keyboard = true;
+            }
 
-        if let Some((width, height)) = parse_pixel_resolution(response) {
-            if width > 0 && height > 0 {
-                self.explicit_width = true;
-                self.sgr_pixels = true;
+            if let Some((width, height)) = parse_pixel_resolution(response) {
+                if width > 0 && height > 0 {
+                    caps.explicit_width = true;
+                    caps.sgr_pixels = true;
+                }
             }
-        }
 
-        let lower = response.to_lowercase();
-        if lower.contains("kitty") {
-            self.kitty_graphics = true;
-            self.kitty_keyboard = true;
-        } else if lower.contains("wezterm") || lower.contains("alacritty") {
-            self.sync_output = true;
+            let lower = response.to_lowercase();
+            if lower.contains("kitty") {
+                caps.kitty_graphics = true;
+                caps.kitty_keyboard = true;
+            } else if lower.contains("wezterm") || lower.contains("alacritty") {
+                caps.sync_output = true;
+            }
+        });
+    }
+
+    /// Apply a terminal reply with `apply`, noting the features it turns on
+    /// in [`Self::query_replies`].
+    pub(super) fn record_reply(&mut self, reply: &[u8], apply: impl FnOnce(&mut Self)) {
+        let before = Feature::ALL.map(|feature| feature.enabled(self));
+        apply(self);
+        for (feature, before) in Feature::ALL.into_iter().zip(before) {
+            if feature.enabled(self) != before {
+                self.query_replies
+                    .push((feature, reply.escape_ascii().to_string()));
+            }
         }
     }
 
@@ -243,15 +439,11 @@ impl Capabilities {
         ColorSupport::None
     }
 
-    fn detect_unicode() -> bool {
+    fn detect_unicode(inputs: &DetectionInputs) -> bool {
         // Check locale for UTF-8
-        let lang = env::var("LANG").unwrap_or_default();
-        let lc_all = env::var("LC_ALL").unwrap_or_default();
-        let lc_ctype = env::var("LC_CTYPE").unwrap_or_default();
-
-        lang.to_lowercase().contains("utf")
-            || lc_all.to_lowercase().contains("utf")
-            || lc_ctype.to_lowercase().contains("utf")
+        [&inputs.lang, &inputs.lc_all, &inputs.lc_ctype]
+            .iter()
+            .any(|locale| locale.to_lowercase().contains("utf"))
     }
 
     /// Detect hyperlink support from multiple signals.
@@ -316,6 +508,81 @@ impl Capabilities {
         supported_terms.iter().any(|t| term_lower.contains(t))
     }
 
+    /// Detect underline shape and color support from multiple signals.
+    ///
+    /// Considers:
+    /// - `TERM_PROGRAM`: kitty, WezTerm, ghostty, iTerm.app
+    /// - `TERM`: kitty, ghostty, wezterm, foot
+    /// - `KITTY_WINDOW_ID` presence
+    fn detect_styled_underlines(term: &str, term_program: &str, kitty_present: bool) -> bool {
+        if kitty_present {
+            return true;
+        }
+
+        let supported_programs = ["kitty", "WezTerm", "ghostty", "iTerm.app"];
+        if supported_programs
+            .iter()
+            .any(|t| term_program.eq_ignore_ascii_case(t) || term_program.contains(t))
+        {
+            return true;
+        }
+
+        let term_lower = term.to_lowercase();
+        let supported_terms = ["kitty", "ghostty", "wezterm", "foot"];
+        supported_terms.iter().any(|t| term_lower.contains(t))
+    }
+
+    /// Detect REP (repeat preceding character) support from multiple signals.
+    ///
+    /// Considers:
+    /// - `TERM_PROGRAM`: kitty, WezTerm, ghostty, Alacritty
+    /// - `TERM`: kitty, ghostty, wezterm, alacritty, foot
+    /// - `KITTY_WINDOW_ID` presence
+    ///
+    /// A bare `xterm*` TERM is not enough: many emulators claim it without
+    /// implementing REP, and an unsupported REP silently drops cells.
+    fn detect_rep(term: &str, term_program: &str, kitty_present: bool) -> bool {
+        if kitty_present {
+            return true;
+        }
+
+        let supported_programs = ["kitty", "WezTerm", "ghostty", "Alacritty"];
+        if supported_programs
+            .iter()
+            .any(|t| term_program.eq_ignore_ascii_case(t) || term_program.contains(t))
+        {
+            return true;
+        }
+
+        let term_lower = term.to_lowercase();
+        let supported_terms = ["kitty", "ghostty", "wezterm", "alacritty", "foot"];
+        supported_terms.iter().any(|t| term_lower.contains(t))
+    }
+
+    /// Detect DEC line attribute support from multiple signals.
+    ///
+    /// Considers:
+    /// - `TERM_PROGRAM`: WezTerm, iTerm.app
+    /// - `XTERM_VERSION` presence, set by xterm itself
+    /// - `WT_SESSION` presence, set by Windows Terminal
+    ///
+    /// `TERM` is no help: most emulators claiming `xterm*` ignore the
+    /// attributes, which leaves double-size rows half empty.
+    fn detect_dec_line_attributes(
+        term_program: &str,
+        xterm_version: bool,
+        windows_terminal: bool,
+    ) -> bool {
+        if xterm_version || windows_terminal {
+            return true;
+        }
+
+        let supported_programs = ["WezTerm", "iTerm.app"];
+        supported_programs
+            .iter()
+            .any(|t| term_program.eq_ignore_ascii_case(t) || term_program.contains(t))
+    }
+
     /// Check if true color is supported.
     #[must_use]
     pub fn has_true_color(&self) -> bool {
@@ -327,6 +594,17 @@ impl Capabilities {
     pub fn has_256_colors(&self) -> bool {
         self.color >= ColorSupport::Extended
     }
+
+    /// How box-drawing glyphs should be written: as UTF-8, or from the DEC
+    /// special graphics charset when [`Self::unicode`] is false.
+    #[must_use]
+    pub fn line_drawing(&self) -> LineDrawing {
+        if self.unicode {
+            LineDrawing::Unicode
+        } else {
+            LineDrawing::DecGraphics
+        }
+    }
 }
 
 fn parse_pixel_resolution(response: &str) -> Option<(u32, u32)> {
@@ -396,6 +674,16 @@ mod tests {
             !caps.explicit_cursor_positioning,
             "Default should disable explicit cursor positioning"
         );
+        assert!(
+            !caps.styled_underlines,
+            "Default should disable styled underlines"
+        );
+        assert!(!caps.rep, "Default should disable REP");
+        assert!(
+            !caps.dec_line_attributes,
+            "Default should disable DEC line attributes"
+        );
+        assert!(!caps.superscript, "Default should disable superscript");
     }
 
     #[test]
@@ -572,6 +860,63 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_styled_underlines_detection() {
+        assert!(Capabilities::detect_styled_underlines(
+            "xterm-kitty",
+            "",
+            false
+        ));
+        assert!(Capabilities::detect_styled_underlines("foot", "", false));
+        assert!(Capabilities::detect_styled_underlines(
+            "xterm-256color",
+            "WezTerm",
+            false
+        ));
+        assert!(Capabilities::detect_styled_underlines("linux", "", true));
+        assert!(!Capabilities::detect_styled_underlines(
+            "xterm-256color",
+            "",
+            false
+        ));
+        assert!(!Capabilities::detect_styled_underlines(
+            "xterm-256color",
+            "Apple_Terminal",
+            false
+        ));
+    }
+
+    #[test]
+    fn test_rep_detection() {
+        assert!(Capabilities::detect_rep("xterm-kitty", "", false));
+        assert!(Capabilities::detect_rep("foot", "", false));
+        assert!(Capabilities::detect_rep(
+            "xterm-256color",
+            "Alacritty",
+            false
+        ));
+        assert!(Capabilities::detect_rep("linux", "", true));
+        assert!(!Capabilities::detect_rep("xterm-256color", "", false));
+        assert!(!Capabilities::detect_rep(
+            "xterm-256color",
+            "Apple_Terminal",
+            false
+        ));
+    }
+
+    #[test]
+    fn test_dec_line_attributes_detection() {
+        assert!(Capabilities::detect_dec_line_attributes("", true, false));
+        assert!(Capabilities::detect_dec_line_attributes("", false, true));
+        assert!(Capabilities::detect_dec_line_attributes(
+            "WezTerm", false, false
+        ));
+        assert!(!Capabilities::detect_dec_line_attributes("", false, false));
+        assert!(!Capabilities::detect_dec_line_attributes(
+            "ghostty", false, false
+        ));
+    }
+
     #[test]
     fn test_case_insensitive_term_matching() {
         // TERM values should match case-insensitively
@@ -580,4 +925,20 @@ mod tests {
         assert!(Capabilities::detect_sync("ALACRITTY", "", false));
         assert!(Capabilities::detect_sync("Alacritty", "", false));
     }
+
+    #[test]
+    fn test_presets() {
+        let ideal = Capabilities::ideal();
+        assert_eq!(ideal.color, ColorSupport::TrueColor);
+        assert!(ideal.unicode && ideal.sync_output && ideal.mouse && ideal.hyperlinks);
+
+        let minimal = Capabilities::minimal();
+        assert_eq!(minimal.color, ColorSupport::None);
+        assert!(!minimal.unicode && !minimal.sync_output && !minimal.mouse);
+
+        let legacy = Capabilities::legacy_16color();
+        assert_eq!(legacy.color, ColorSupport::Basic);
+        assert!(legacy.unicode && legacy.mouse);
+        assert!(!legacy.sync_output && !legacy.hyperlinks && !legacy.kitty_keyboard);
+    }
 }
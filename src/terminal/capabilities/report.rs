@@ -0,0 +1,522 @@
+//! Capability reports: which features are on, and what decided it.
+//!
+//! A report attributes each feature to the input that settled it: the flag
+//! as detection from [`DetectionInputs`] left it, then any terminal reply
+//! that turned it on, and finally any change the application made after.
+
+use std::fmt;
+
+use super::{Capabilities, ColorSupport, DetectionInputs};
+
+/// A feature covered by a [`CapabilityReport`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Feature {
+    /// 24-bit color.
+    TrueColor,
+    /// The 256-color palette.
+    Color256,
+    /// Mouse tracking.
+    Mouse,
+    /// OSC 8 hyperlinks.
+    Hyperlinks,
+    /// Synchronized output.
+    SyncOutput,
+    /// The kitty keyboard protocol.
+    KittyKeyboard,
+    /// Sixel graphics.
+    Sixel,
+    /// The kitty graphics protocol.
+    KittyGraphics,
+    /// Focus events.
+    Focus,
+    /// Bracketed paste.
+    BracketedPaste,
+    /// OSC 52 clipboard writes.
+    Osc52,
+}
+
+impl Feature {
+    /// Every feature, in report order.
+    pub const ALL: [Self; 11] = [
+        Self::TrueColor,
+        Self::Color256,
+        Self::Mouse,
+        Self::Hyperlinks,
+        Self::SyncOutput,
+        Self::KittyKeyboard,
+        Self::Sixel,
+        Self::KittyGraphics,
+        Self::Focus,
+        Self::BracketedPaste,
+        Self::Osc52,
+    ];
+
+    /// Short name, as shown in reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TrueColor => "truecolor",
+            Self::Color256 => "256color",
+            Self::Mouse => "mouse",
+            Self::Hyperlinks => "hyperlinks",
+            Self::SyncOutput => "sync output",
+            Self::KittyKeyboard => "kitty keyboard",
+            Self::Sixel => "sixel",
+            Self::KittyGraphics => "kitty graphics",
+            Self::Focus => "focus",
+            Self::BracketedPaste => "bracketed paste",
+            Self::Osc52 => "osc52",
+        }
+    }
+
+    /// Whether `caps` has the feature, or `None` for one that isn't
+    /// tracked.
+    pub(super) fn enabled(self, caps: &Capabilities) -> Option<bool> {
+        Some(match self {
+            Self::TrueColor => caps.has_true_color(),
+            Self::Color256 => caps.has_256_colors(),
+            Self::Mouse => caps.mouse,
+            Self::Hyperlinks => caps.hyperlinks,
+            Self::SyncOutput => caps.sync_output,
+            Self::KittyKeyboard => caps.kitty_keyboard,
+            Self::Sixel => caps.sixel,
+            Self::KittyGraphics => caps.kitty_graphics,
+            Self::Focus => caps.focus,
+            Self::BracketedPaste => caps.bracketed_paste,
+            Self::Osc52 => return None,
+        })
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether a feature is available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum FeatureStatus {
+    /// The feature is on.
+    Supported,
+    /// The feature is off.
+    Unsupported,
+    /// Whether the terminal supports the feature can't be told.
+    Unknown,
+}
+
+impl fmt::Display for FeatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Supported => "supported",
+            Self::Unsupported => "unsupported",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// What settled a feature's status.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Evidence {
+    /// An environment variable that declares the feature directly.
+    EnvVar {
+        /// The variable.
+        name: String,
+        /// Its value.
+        value: String,
+    },
+    /// `TERM` or `TERM_PROGRAM`, matched against known terminals.
+    TerminalName {
+        /// The variable.
+        var: String,
+        /// The terminal it names.
+        name: String,
+    },
+    /// The terminal's reply to a query, such as device attributes, with
+    /// control bytes escaped.
+    QueryReply(String),
+    /// The application changed the flag after detection.
+    Override,
+    /// Nothing indicated support, so the conservative default stands.
+    Default,
+}
+
+impl Evidence {
+    fn env(name: &str, value: &str) -> Self {
+        Self::EnvVar {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn term(var: &str, name: &str) -> Self {
+        Self::TerminalName {
+            var: var.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EnvVar { name, value }
+            | Self::TerminalName {
+                var: name,
+                name: value,
+            } => {
+                write!(f, "{name}={value}")
+            }
+            Self::QueryReply(reply) => write!(f, "reply {reply}"),
+            Self::Override => f.write_str("override"),
+            Self::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// One feature's line in a [`CapabilityReport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureReport {
+    /// The feature.
+    pub feature: Feature,
+    /// Whether it is available.
+    pub status: FeatureStatus,
+    /// What settled the status.
+    pub evidence: Evidence,
+    /// Why, for people.
+    pub reason: String,
+}
+
+/// The status of each terminal feature and why, from
+/// [`Capabilities::report`].
+///
+/// Displays as a table for diagnostics panels and bug reports; with the
+/// `serde` feature it also serializes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilityReport {
+    /// `TERM`, if known.
+    pub term_name: Option<String>,
+    /// What detection was based on, or `None` for capabilities that weren't
+    /// detected.
+    pub detected_from: Option<DetectionInputs>,
+    /// Every [`Feature`], in [`Feature::ALL`] order.
+    pub features: Vec<FeatureReport>,
+}
+
+impl CapabilityReport {
+    /// The line for `feature`.
+    #[must_use]
+    pub fn get(&self, feature: Feature) -> Option<&FeatureReport> {
+        self.features.iter().find(|line| line.feature == feature)
+    }
+
+    /// The features that are off or unknown.
+    pub fn disabled(&self) -> impl Iterator<Item = &FeatureReport> {
+        self.features
+            .iter()
+            .filter(|line| line.status != FeatureStatus::Supported)
+    }
+}
+
+impl fmt::Display for CapabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "terminal: {}",
+            self.term_name.as_deref().unwrap_or("unknown")
+        )?;
+        for line in &self.features {
+            writeln!(
+                f,
+                "{:<17}{:<13}{} ({})",
+                line.feature.name(),
+                line.status.to_string(),
+                line.reason,
+                line.evidence
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Capabilities {
+    /// The status of each [`Feature`], with the evidence that settled it
+    /// and a reason to show users.
+    #[must_use]
+    pub fn report(&self) -> CapabilityReport {
+        let detected = self
+            .detected_from
+            .clone()
+            .map_or_else(Self::default, |inputs| Self::from_inputs(*inputs));
+        CapabilityReport {
+            term_name: self.term_name.clone(),
+            detected_from: self.detected_from.as_deref().cloned(),
+            features: Feature::ALL
+                .into_iter()
+                .map(|feature| self.feature_report(feature, &detected))
+                .collect(),
+        }
+    }
+
+    fn feature_report(&self, feature: Feature, detected: &Self) -> FeatureReport {
+        let Some(enabled) = feature.enabled(self) else {
+            return FeatureReport {
+                feature,
+                status: FeatureStatus::Unknown,
+                evidence: Evidence::Default,
+                reason: "terminals don't report whether they accept clipboard writes".to_string(),
+            };
+        };
+        let reply = self
+            .query_replies
+            .iter()
+            .rev()
+            .find(|(replied, _)| *replied == feature)
+            .map(|(_, reply)| reply);
+        let expected = reply.is_some() || feature.enabled(detected) == Some(true);
+
+        let (evidence, reason) = if enabled != expected {
+            let was = if expected { "on" } else { "off" };
+            (
+                Evidence::Override,
+                format!("set by the application; detection had it {was}"),
+            )
+        } else if let Some(reply) = reply {
+            (
+                Evidence::QueryReply(reply.clone()),
+                "the terminal's reply to a capability query".to_string(),
+            )
+        } else if let Some(inputs) = &self.detected_from {
+            detection_evidence(feature, enabled, inputs)
+        } else {
+            (
+                Evidence::Default,
+                "capabilities weren't detected".to_string(),
+            )
+        };
+        FeatureReport {
+            feature,
+            status: if enabled {
+                FeatureStatus::Supported
+            } else {
+                FeatureStatus::Unsupported
+            },
+            evidence,
+            reason,
+        }
+    }
+}
+
+/// The input behind a feature's status as detection left it.
+fn detection_evidence(
+    feature: Feature,
+    enabled: bool,
+    inputs: &DetectionInputs,
+) -> (Evidence, String) {
+    let term = inputs.term.as_str();
+    let term_program = inputs.term_program.as_str();
+    let kitty = inputs.kitty_window_id.as_deref();
+    match feature {
+        Feature::TrueColor | Feature::Color256 => color_evidence(inputs),
+        Feature::Mouse | Feature::Focus | Feature::BracketedPaste => {
+            if enabled {
+                (
+                    Evidence::term("TERM", term),
+                    "an xterm-compatible terminal".to_string(),
+                )
+            } else {
+                term_evidence(term, "not known to be xterm-compatible")
+            }
+        }
+        Feature::Hyperlinks | Feature::SyncOutput => {
+            let detect = if feature == Feature::Hyperlinks {
+                Capabilities::detect_hyperlinks
+            } else {
+                Capabilities::detect_sync
+            };
+            if let (true, Some(id)) = (enabled, kitty) {
+                (
+                    Evidence::env("KITTY_WINDOW_ID", id),
+                    "running in kitty".to_string(),
+                )
+            } else if enabled && detect("", term_program, false) {
+                (
+                    Evidence::term("TERM_PROGRAM", term_program),
+                    "a terminal known to support it".to_string(),
+                )
+            } else if enabled {
+                (
+                    Evidence::term("TERM", term),
+                    "a terminal known to support it".to_string(),
+                )
+            } else {
+                term_evidence(term, "not known to support it")
+            }
+        }
+        Feature::KittyKeyboard | Feature::KittyGraphics => match (enabled, kitty) {
+            (true, Some(id)) => (
+                Evidence::env("KITTY_WINDOW_ID", id),
+                "running in kitty".to_string(),
+            ),
+            _ => (Evidence::Default, "not running in kitty".to_string()),
+        },
+        Feature::Sixel => {
+            if enabled {
+                (
+                    Evidence::term("TERM", term),
+                    "the terminal type names sixel".to_string(),
+                )
+            } else {
+                term_evidence(term, "not known to support sixel")
+            }
+        }
+        Feature::Osc52 => (Evidence::Default, String::new()),
+    }
+}
+
+fn color_evidence(inputs: &DetectionInputs) -> (Evidence, String) {
+    let support = match Capabilities::detect_color(&inputs.term, &inputs.colorterm) {
+        ColorSupport::TrueColor => "24-bit color",
+        ColorSupport::Extended => "256 colors",
+        ColorSupport::Basic => "16 colors",
+        ColorSupport::None => return (Evidence::Default, "TERM is not set".to_string()),
+    };
+    if Capabilities::detect_color("", &inputs.colorterm) == ColorSupport::TrueColor {
+        (
+            Evidence::env("COLORTERM", &inputs.colorterm),
+            format!("the terminal declares {support}"),
+        )
+    } else {
+        (
+            Evidence::term("TERM", &inputs.term),
+            format!("the terminal type supports {support}"),
+        )
+    }
+}
+
+/// Evidence for a feature that `TERM` didn't turn on.
+fn term_evidence(term: &str, reason: &str) -> (Evidence, String) {
+    if term.is_empty() {
+        (Evidence::Default, "TERM is not set".to_string())
+    } else {
+        (
+            Evidence::term("TERM", term),
+            format!("the terminal is {reason}"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+
+    fn detect(inputs: DetectionInputs) -> Capabilities {
+        Capabilities::from_inputs(inputs)
+    }
+
+    #[test]
+    fn test_report_from_environment() {
+        let caps = detect(DetectionInputs {
+            term: "xterm-kitty".to_string(),
+            colorterm: "truecolor".to_string(),
+            kitty_window_id: Some("1".to_string()),
+            lang: "en_US.UTF-8".to_string(),
+            ..DetectionInputs::default()
+        });
+        insta::assert_snapshot!(caps.report().to_string());
+
+        let caps = detect(DetectionInputs {
+            term: "screen-256color".to_string(),
+            term_program: "WezTerm".to_string(),
+            ..DetectionInputs::default()
+        });
+        insta::assert_snapshot!("report_from_term_program", caps.report().to_string());
+    }
+
+    #[test]
+    fn test_report_without_term() {
+        let report = detect(DetectionInputs::default()).report();
+        insta::assert_snapshot!(report.to_string());
+        assert_eq!(report.term_name, None);
+        assert_eq!(report.disabled().count(), Feature::ALL.len());
+    }
+
+    #[test]
+    fn test_report_credits_replies_and_overrides() {
+        let caps = detect(DetectionInputs {
+            term: "xterm-256color".to_string(),
+            ..DetectionInputs::default()
+        });
+        let mut terminal = Terminal::with_capabilities(Vec::new(), caps);
+        terminal.parse_response(b"\x1b[?62;4;22c").unwrap();
+        terminal.apply_capability_response("\x1bP>|kitty(0.31.0)\x1b\\");
+        let mut caps = terminal.capabilities().clone();
+        caps.mouse = false;
+        caps.hyperlinks = true;
+
+        let report = caps.report();
+        insta::assert_snapshot!(report.to_string());
+        assert_eq!(
+            report.get(Feature::Sixel).unwrap().evidence,
+            Evidence::QueryReply("\\x1b[?62;4;22c".to_string())
+        );
+        assert_eq!(
+            report.get(Feature::Mouse).unwrap().evidence,
+            Evidence::Override
+        );
+    }
+
+    #[test]
+    fn test_presets_report_their_flags_as_overrides() {
+        let report = Capabilities::ideal().report();
+        assert_eq!(report.detected_from, None);
+        let osc52 = report.get(Feature::Osc52).unwrap();
+        assert_eq!(osc52.status, FeatureStatus::Unknown);
+        assert!(report.features.iter().all(|line| {
+            line.feature == Feature::Osc52
+                || (line.status == FeatureStatus::Supported && line.evidence == Evidence::Override)
+        }));
+
+        let report = Capabilities::default().report();
+        let line = report.get(Feature::Mouse).unwrap();
+        assert_eq!(line.evidence, Evidence::Default);
+        assert_eq!(line.reason, "capabilities weren't detected");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_round_trips_through_json() {
+        let mut caps = detect(DetectionInputs {
+            term: "xterm-kitty".to_string(),
+            kitty_window_id: Some("1".to_string()),
+            ..DetectionInputs::default()
+        });
+        caps.apply_query_response("\x1b[4;900;1440t");
+        caps.sync_output = false;
+        let report = caps.report();
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""evidence":"override""#), "{json}");
+        assert_eq!(
+            serde_json::from_str::<CapabilityReport>(&json).unwrap(),
+            report
+        );
+    }
+}
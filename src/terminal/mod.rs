@@ -10,14 +10,20 @@ mod mouse;
 mod queries;
 mod raw;
 
-pub use capabilities::{Capabilities, ColorSupport};
+pub use capabilities::{
+    Capabilities, CapabilityReport, ColorSupport, DetectionInputs, Evidence, Feature,
+    FeatureReport, FeatureStatus,
+};
 pub use cursor::{CursorState, CursorStyle};
 pub use mouse::{MouseButton, MouseEvent, MouseEventKind};
 pub use queries::{TerminalResponse, all_queries, query_constants};
-pub use raw::{RawModeGuard, enable_raw_mode, is_tty, terminal_size};
+pub use raw::{RawModeGuard, enable_raw_mode, is_tty, read_stdin_timeout, terminal_size};
 
 use crate::ansi::sequences;
-use std::io::{self, Write};
+use crate::error::{Error, Result, TerminalOp};
+use crate::event::{EventBus, TerminalEvent};
+use std::collections::BTreeSet;
+use std::io::Write;
 
 /// Terminal state manager.
 pub struct Terminal<W: Write> {
@@ -26,19 +32,60 @@ pub struct Terminal<W: Write> {
     cursor: CursorState,
     alt_screen: bool,
     mouse_enabled: bool,
+    // Palette indices changed via OSC 4, restored on cleanup
+    modified_palette: BTreeSet<u8>,
+    /// Last known size in cells, used to validate scroll regions.
+    size: Option<(u32, u32)>,
+    /// Scroll region rows (inclusive) while one is set; reset on cleanup.
+    scroll_region: Option<(u32, u32)>,
     raw_mode_guard: Option<RawModeGuard>,
+    event_bus: Option<EventBus>,
+    /// Set once the writer is known to be gone; cleanup then skips writing.
+    output_closed: bool,
 }
 
 impl<W: Write> Terminal<W> {
     /// Create a new terminal with the given writer.
     pub fn new(writer: W) -> Self {
+        Self::with_capabilities(writer, Capabilities::detect())
+    }
+
+    /// Create a terminal that assumes `capabilities` instead of detecting
+    /// them.
+    pub fn with_capabilities(writer: W, capabilities: Capabilities) -> Self {
         Self {
             writer,
-            capabilities: Capabilities::detect(),
+            capabilities,
             cursor: CursorState::default(),
             alt_screen: false,
             mouse_enabled: false,
+            modified_palette: BTreeSet::new(),
+            size: None,
+            scroll_region: None,
             raw_mode_guard: None,
+            event_bus: None,
+            output_closed: false,
+        }
+    }
+
+    /// Publish [`TerminalEvent`]s on `bus`, starting with the capabilities
+    /// detected so far.
+    pub fn set_event_bus(&mut self, bus: EventBus) {
+        bus.publish(&TerminalEvent::CapabilityDetected(
+            self.capabilities.clone(),
+        ));
+        self.event_bus = Some(bus);
+    }
+
+    /// The bus this terminal publishes to, if any.
+    #[must_use]
+    pub fn event_bus(&self) -> Option<&EventBus> {
+        self.event_bus.as_ref()
+    }
+
+    fn publish(&self, event: TerminalEvent) {
+        if let Some(bus) = &self.event_bus {
+            bus.publish(&event);
         }
     }
 
@@ -52,9 +99,11 @@ impl<W: Write> Terminal<W> {
     ///
     /// Raw mode disables terminal line buffering, echo, and signal processing,
     /// allowing the application to receive individual key presses.
-    pub fn enter_raw_mode(&mut self) -> io::Result<()> {
+    pub fn enter_raw_mode(&mut self) -> Result<()> {
         if self.raw_mode_guard.is_none() {
-            self.raw_mode_guard = Some(enable_raw_mode()?);
+            self.raw_mode_guard =
+                Some(enable_raw_mode().map_err(Error::terminal(TerminalOp::EnterRawMode))?);
+            self.publish(TerminalEvent::RawModeChanged(true));
         }
         Ok(())
     }
@@ -62,8 +111,10 @@ impl<W: Write> Terminal<W> {
     /// Exit raw mode.
     ///
     /// Restores the terminal to its original state before raw mode was enabled.
-    pub fn exit_raw_mode(&mut self) -> io::Result<()> {
-        self.raw_mode_guard = None;
+    pub fn exit_raw_mode(&mut self) -> Result<()> {
+        if self.raw_mode_guard.take().is_some() {
+            self.publish(TerminalEvent::RawModeChanged(false));
+        }
         Ok(())
     }
 
@@ -86,9 +137,9 @@ impl<W: Write> Terminal<W> {
     /// - XTVERSION (terminal version)
     /// - Pixel resolution
     /// - Kitty keyboard protocol
-    pub fn query_capabilities(&mut self) -> io::Result<()> {
-        self.writer.write_all(all_queries().as_bytes())?;
-        self.writer.flush()
+    pub fn query_capabilities(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::QueryCapabilities, &all_queries())?;
+        self.flush_op(TerminalOp::QueryCapabilities)
     }
 
     /// Parse a terminal response and update capabilities.
@@ -96,12 +147,15 @@ impl<W: Write> Terminal<W> {
     /// Returns the parsed response if recognized.
     pub fn parse_response(&mut self, response: &[u8]) -> Option<TerminalResponse> {
         let parsed = TerminalResponse::parse(response)?;
-        self.update_capabilities_from_response(&parsed);
+        self.capabilities.record_reply(response, |caps| {
+            Self::update_capabilities_from_response(caps, &parsed);
+        });
+        self.publish(TerminalEvent::CapabilityDetected(self.capabilities.clone()));
         Some(parsed)
     }
 
     /// Update capabilities based on a parsed response.
-    fn update_capabilities_from_response(&mut self, response: &TerminalResponse) {
+    fn update_capabilities_from_response(caps: &mut Capabilities, response: &TerminalResponse) {
         match response {
             TerminalResponse::DeviceAttributes {
                 primary: true,
@@ -109,30 +163,30 @@ impl<W: Write> Terminal<W> {
             } => {
                 // DA1 param 4 indicates sixel support
                 if params.contains(&4) {
-                    self.capabilities.sixel = true;
+                    caps.sixel = true;
                 }
             }
             TerminalResponse::XtVersion { name, .. } => {
                 let name_lower = name.to_lowercase();
                 if name_lower.contains("kitty") {
-                    self.capabilities.kitty_keyboard = true;
-                    self.capabilities.kitty_graphics = true;
-                    self.capabilities.sync_output = true;
+                    caps.kitty_keyboard = true;
+                    caps.kitty_graphics = true;
+                    caps.sync_output = true;
                 } else if name_lower.contains("foot")
                     || name_lower.contains("alacritty")
                     || name_lower.contains("wezterm")
                 {
-                    self.capabilities.sync_output = true;
+                    caps.sync_output = true;
                 }
             }
             TerminalResponse::PixelSize { width, height } => {
                 if *width > 0 && *height > 0 {
-                    self.capabilities.explicit_width = true;
-                    self.capabilities.sgr_pixels = true;
+                    caps.explicit_width = true;
+                    caps.sgr_pixels = true;
                 }
             }
             TerminalResponse::KittyKeyboard { flags: _ } => {
-                self.capabilities.kitty_keyboard = true;
+                caps.kitty_keyboard = true;
             }
             _ => {}
         }
@@ -141,6 +195,7 @@ impl<W: Write> Terminal<W> {
     /// Apply a raw capability response to update detection hints.
     pub fn apply_capability_response(&mut self, response: &str) {
         self.capabilities.apply_query_response(response);
+        self.publish(TerminalEvent::CapabilityDetected(self.capabilities.clone()));
     }
 
     /// Get cursor state.
@@ -150,62 +205,67 @@ impl<W: Write> Terminal<W> {
     }
 
     /// Enter alternate screen buffer.
-    pub fn enter_alt_screen(&mut self) -> io::Result<()> {
+    pub fn enter_alt_screen(&mut self) -> Result<()> {
         if !self.alt_screen {
-            self.writer.write_all(sequences::ALT_SCREEN_ON.as_bytes())?;
+            self.write_seq(TerminalOp::AltScreen, sequences::ALT_SCREEN_ON)?;
             self.alt_screen = true;
         }
         Ok(())
     }
 
+    /// Whether the alternate screen buffer is active.
+    #[must_use]
+    pub fn is_alt_screen(&self) -> bool {
+        self.alt_screen
+    }
+
     /// Leave alternate screen buffer.
-    pub fn leave_alt_screen(&mut self) -> io::Result<()> {
+    pub fn leave_alt_screen(&mut self) -> Result<()> {
         if self.alt_screen {
-            self.writer
-                .write_all(sequences::ALT_SCREEN_OFF.as_bytes())?;
+            self.write_seq(TerminalOp::AltScreen, sequences::ALT_SCREEN_OFF)?;
             self.alt_screen = false;
         }
         Ok(())
     }
 
     /// Enable mouse tracking.
-    pub fn enable_mouse(&mut self) -> io::Result<()> {
+    pub fn enable_mouse(&mut self) -> Result<()> {
         if !self.mouse_enabled {
-            self.writer.write_all(sequences::MOUSE_ON.as_bytes())?;
+            self.write_seq(TerminalOp::Mouse, sequences::MOUSE_ON)?;
             self.mouse_enabled = true;
         }
         Ok(())
     }
 
     /// Disable mouse tracking.
-    pub fn disable_mouse(&mut self) -> io::Result<()> {
+    pub fn disable_mouse(&mut self) -> Result<()> {
         if self.mouse_enabled {
-            self.writer.write_all(sequences::MOUSE_OFF.as_bytes())?;
+            self.write_seq(TerminalOp::Mouse, sequences::MOUSE_OFF)?;
             self.mouse_enabled = false;
         }
         Ok(())
     }
 
     /// Hide cursor.
-    pub fn hide_cursor(&mut self) -> io::Result<()> {
+    pub fn hide_cursor(&mut self) -> Result<()> {
         if self.cursor.visible {
-            self.writer.write_all(sequences::CURSOR_HIDE.as_bytes())?;
+            self.write_seq(TerminalOp::Cursor, sequences::CURSOR_HIDE)?;
             self.cursor.visible = false;
         }
         Ok(())
     }
 
     /// Show cursor.
-    pub fn show_cursor(&mut self) -> io::Result<()> {
+    pub fn show_cursor(&mut self) -> Result<()> {
         if !self.cursor.visible {
-            self.writer.write_all(sequences::CURSOR_SHOW.as_bytes())?;
+            self.write_seq(TerminalOp::Cursor, sequences::CURSOR_SHOW)?;
             self.cursor.visible = true;
         }
         Ok(())
     }
 
     /// Set cursor style.
-    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> io::Result<()> {
+    pub fn set_cursor_style(&mut self, style: CursorStyle, blinking: bool) -> Result<()> {
         let seq = match (style, blinking) {
             (CursorStyle::Block, true) => sequences::cursor_style::BLOCK_BLINK,
             (CursorStyle::Block, false) => sequences::cursor_style::BLOCK_STEADY,
@@ -214,51 +274,190 @@ impl<W: Write> Terminal<W> {
             (CursorStyle::Bar, true) => sequences::cursor_style::BAR_BLINK,
             (CursorStyle::Bar, false) => sequences::cursor_style::BAR_STEADY,
         };
-        self.writer.write_all(seq.as_bytes())?;
+        self.write_seq(TerminalOp::Cursor, seq)?;
         self.cursor.style = style;
         self.cursor.blinking = blinking;
         Ok(())
     }
 
     /// Move cursor to position.
-    pub fn move_cursor(&mut self, x: u32, y: u32) -> io::Result<()> {
+    pub fn move_cursor(&mut self, x: u32, y: u32) -> Result<()> {
         let seq = crate::ansi::cursor_position(y, x);
-        self.writer.write_all(seq.as_bytes())?;
+        self.write_seq(TerminalOp::Cursor, &seq)?;
         self.cursor.x = x;
         self.cursor.y = y;
         Ok(())
     }
 
     /// Save cursor position using DEC sequence.
-    pub fn save_cursor(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::CURSOR_SAVE.as_bytes())
+    pub fn save_cursor(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Cursor, sequences::CURSOR_SAVE)
     }
 
     /// Restore cursor position using DEC sequence.
-    pub fn restore_cursor(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::CURSOR_RESTORE.as_bytes())
+    pub fn restore_cursor(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Cursor, sequences::CURSOR_RESTORE)
     }
 
     /// Set cursor color using OSC 12.
-    pub fn set_cursor_color(&mut self, color: crate::color::Rgba) -> io::Result<()> {
+    pub fn set_cursor_color(&mut self, color: crate::color::Rgba) -> Result<()> {
         let (r, g, b) = color.to_rgb_u8();
         let seq = sequences::cursor_color(r, g, b);
-        self.writer.write_all(seq.as_bytes())
+        self.write_seq(TerminalOp::Cursor, &seq)
     }
 
     /// Reset cursor color to default using OSC 112.
-    pub fn reset_cursor_color(&mut self) -> io::Result<()> {
-        self.writer
-            .write_all(sequences::CURSOR_COLOR_RESET.as_bytes())
+    pub fn reset_cursor_color(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Cursor, sequences::CURSOR_COLOR_RESET)
     }
 
-    /// Clear the screen.
-    pub fn clear(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::CLEAR_SCREEN.as_bytes())?;
-        self.writer.write_all(sequences::CURSOR_HOME.as_bytes())?;
+    /// Redefine a palette entry using OSC 4.
+    ///
+    /// The entry is restored by [`Self::cleanup`] unless reset earlier.
+    pub fn set_palette_color(&mut self, index: u8, color: crate::color::Rgba) -> Result<()> {
+        let (r, g, b) = color.to_rgb_u16();
+        let seq = sequences::palette_color(index, r, g, b);
+        self.write_seq(TerminalOp::Palette, &seq)?;
+        self.modified_palette.insert(index);
+        Ok(())
+    }
+
+    /// Reset a palette entry to its default using OSC 104.
+    pub fn reset_palette_color(&mut self, index: u8) -> Result<()> {
+        self.write_seq(TerminalOp::Palette, &sequences::palette_reset(&[index]))?;
+        self.modified_palette.remove(&index);
+        Ok(())
+    }
+
+    /// Reset the whole palette to its defaults using OSC 104.
+    pub fn reset_palette(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Palette, &sequences::palette_reset(&[]))?;
+        self.modified_palette.clear();
+        Ok(())
+    }
+
+    /// Query a palette entry using OSC 4.
+    ///
+    /// The reply parses as [`TerminalResponse::PaletteColor`].
+    pub fn query_palette_color(&mut self, index: u8) -> Result<()> {
+        self.write_seq(TerminalOp::Palette, &sequences::query::palette_color(index))?;
+        self.flush_op(TerminalOp::Palette)
+    }
+
+    /// Query the default background color using OSC 11.
+    ///
+    /// The reply parses as [`TerminalResponse::BackgroundColor`].
+    pub fn query_background_color(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Palette, sequences::query::BACKGROUND_COLOR)?;
+        self.flush_op(TerminalOp::Palette)
+    }
+
+    /// Restore every palette entry changed by [`Self::set_palette_color`].
+    fn restore_palette(&mut self) -> Result<()> {
+        if self.modified_palette.is_empty() {
+            return Ok(());
+        }
+        let indices: Vec<u8> = std::mem::take(&mut self.modified_palette)
+            .into_iter()
+            .collect();
+        self.write_seq(TerminalOp::Palette, &sequences::palette_reset(&indices))
+    }
+
+    /// Record the terminal size in cells.
+    ///
+    /// Scroll regions are checked against it; the renderer keeps it up to
+    /// date.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.size = Some((width, height));
+    }
+
+    /// Last size recorded with [`Self::set_size`].
+    #[must_use]
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.size
+    }
+
+    /// Restrict scrolling to rows `top..=bottom` (0-based) using DECSTBM.
+    ///
+    /// The terminal moves the cursor home. The region is reset by
+    /// [`Self::cleanup`] unless reset earlier.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidInput`] unless `top < bottom` and, once a
+    /// size is known, `bottom` is on screen.
+    pub fn set_scroll_region(&mut self, top: u32, bottom: u32) -> Result<()> {
+        if top >= bottom {
+            return Err(Error::InvalidInput(format!(
+                "scroll region top {top} must be above bottom {bottom}"
+            )));
+        }
+        if let Some((_, height)) = self.size {
+            if bottom >= height {
+                return Err(Error::InvalidInput(format!(
+                    "scroll region bottom {bottom} is outside {height} rows"
+                )));
+            }
+        }
+        self.write_seq(TerminalOp::Scroll, &sequences::scroll_region(top, bottom))?;
+        self.scroll_region = Some((top, bottom));
+        self.cursor.x = 0;
+        self.cursor.y = 0;
         Ok(())
     }
 
+    /// Reset the scroll region to the whole screen.
+    pub fn reset_scroll_region(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Scroll, sequences::SCROLL_REGION_RESET)?;
+        self.scroll_region = None;
+        self.cursor.x = 0;
+        self.cursor.y = 0;
+        Ok(())
+    }
+
+    /// Current scroll region rows, if one is set.
+    #[must_use]
+    pub fn scroll_region(&self) -> Option<(u32, u32)> {
+        self.scroll_region
+    }
+
+    /// Scroll the scroll region's content up `n` lines (SU), adding blank
+    /// lines at the bottom. Does nothing for `n == 0`.
+    pub fn scroll_up(&mut self, n: u32) -> Result<()> {
+        self.write_lines_op(n, sequences::scroll_up)
+    }
+
+    /// Scroll the scroll region's content down `n` lines (SD), adding blank
+    /// lines at the top. Does nothing for `n == 0`.
+    pub fn scroll_down(&mut self, n: u32) -> Result<()> {
+        self.write_lines_op(n, sequences::scroll_down)
+    }
+
+    /// Insert `n` blank lines at the cursor row (IL), pushing the lines
+    /// below it down within the scroll region. Does nothing for `n == 0`.
+    pub fn insert_lines(&mut self, n: u32) -> Result<()> {
+        self.write_lines_op(n, sequences::insert_lines)
+    }
+
+    /// Delete `n` lines at the cursor row (DL), pulling the lines below it
+    /// up within the scroll region. Does nothing for `n == 0`.
+    pub fn delete_lines(&mut self, n: u32) -> Result<()> {
+        self.write_lines_op(n, sequences::delete_lines)
+    }
+
+    fn write_lines_op(&mut self, n: u32, seq: fn(u32) -> String) -> Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        self.write_seq(TerminalOp::Scroll, &seq(n))
+    }
+
+    /// Clear the screen.
+    pub fn clear(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Clear, sequences::CLEAR_SCREEN)?;
+        self.write_seq(TerminalOp::Clear, sequences::CURSOR_HOME)
+    }
+
     /// Set window title.
     ///
     /// Control characters are filtered out to prevent escape sequence injection attacks.
@@ -268,44 +467,61 @@ impl<W: Write> Terminal<W> {
     /// - DEL (U+007F): Another control character
     /// - C1 controls (U+0080-U+009F): Contains CSI (0x9B), OSC (0x9D), and ST (0x9C)
     ///   which some terminals interpret as control sequences
-    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
-        write!(self.writer, "{}", sequences::TITLE_PREFIX)?;
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
         // Filter out control characters to prevent escape sequence injection
         // Using char::is_control() which covers C0, DEL, and C1 control characters
-        for ch in title.chars() {
-            if !ch.is_control() {
-                write!(self.writer, "{ch}")?;
-            }
-        }
-        write!(self.writer, "{}", sequences::TITLE_SUFFIX)?;
-        Ok(())
+        let title: String = title.chars().filter(|ch| !ch.is_control()).collect();
+        let seq = format!(
+            "{}{title}{}",
+            sequences::TITLE_PREFIX,
+            sequences::TITLE_SUFFIX
+        );
+        self.write_seq(TerminalOp::SetTitle, &seq)
     }
 
     /// Reset terminal state.
-    pub fn reset(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::RESET.as_bytes())?;
-        self.writer
-            .write_all(sequences::cursor_style::DEFAULT.as_bytes())?;
-        Ok(())
+    pub fn reset(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Reset, sequences::RESET)?;
+        self.write_seq(TerminalOp::Reset, sequences::cursor_style::DEFAULT)
     }
 
     /// Flush the output.
-    pub fn flush(&mut self) -> io::Result<()> {
-        self.writer.flush()
+    pub fn flush(&mut self) -> Result<()> {
+        self.flush_op(TerminalOp::Flush)
     }
 
     /// Begin synchronized update (for flicker-free rendering).
-    pub fn begin_sync(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::sync::BEGIN.as_bytes())
+    pub fn begin_sync(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Sync, sequences::sync::BEGIN)
     }
 
     /// End synchronized update.
-    pub fn end_sync(&mut self) -> io::Result<()> {
-        self.writer.write_all(sequences::sync::END.as_bytes())
+    pub fn end_sync(&mut self) -> Result<()> {
+        self.write_seq(TerminalOp::Sync, sequences::sync::END)
+    }
+
+    /// Stop writing in [`Self::cleanup`], because the writer is gone.
+    pub(crate) fn mark_output_closed(&mut self) {
+        self.output_closed = true;
+    }
+
+    pub(crate) fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
     }
 
     /// Cleanup terminal on exit.
-    pub fn cleanup(&mut self) -> io::Result<()> {
+    ///
+    /// Once the output is closed this only leaves raw mode.
+    pub fn cleanup(&mut self) -> Result<()> {
+        if self.output_closed {
+            self.modified_palette.clear();
+            self.scroll_region = None;
+            return self.exit_raw_mode();
+        }
+        if self.scroll_region.is_some() {
+            self.reset_scroll_region()?;
+        }
+        self.restore_palette()?;
         self.show_cursor()?;
         self.disable_mouse()?;
         self.leave_alt_screen()?;
@@ -313,6 +529,16 @@ impl<W: Write> Terminal<W> {
         self.reset()?;
         self.flush()
     }
+
+    fn write_seq(&mut self, op: TerminalOp, seq: &str) -> Result<()> {
+        self.writer
+            .write_all(seq.as_bytes())
+            .map_err(Error::terminal(op))
+    }
+
+    fn flush_op(&mut self, op: TerminalOp) -> Result<()> {
+        self.writer.flush().map_err(Error::terminal(op))
+    }
 }
 
 impl<W: Write> Drop for Terminal<W> {
@@ -333,6 +559,176 @@ mod tests {
         assert!(!terminal.is_raw_mode());
     }
 
+    #[test]
+    fn test_write_failures_name_the_operation() {
+        struct Broken;
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut terminal = Terminal::new(Broken);
+        let err = terminal.begin_sync().unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::Terminal {
+                op: TerminalOp::Sync,
+                source,
+            } if source.kind() == std::io::ErrorKind::BrokenPipe
+        ));
+        let err = terminal.set_title("hi").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("failed to set the window title: ")
+        );
+    }
+
+    #[test]
+    fn test_scroll_region_sequences() {
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new(&mut output);
+        terminal.set_scroll_region(2, 9).unwrap();
+        terminal.scroll_up(3).unwrap();
+        terminal.scroll_down(1).unwrap();
+        terminal.insert_lines(2).unwrap();
+        terminal.delete_lines(4).unwrap();
+        terminal.scroll_up(0).unwrap();
+        terminal.reset_scroll_region().unwrap();
+        assert_eq!(terminal.scroll_region(), None);
+        drop(terminal);
+
+        // Reset already, so cleanup doesn't reset the region again.
+        let s = String::from_utf8_lossy(&output);
+        assert!(s.starts_with("\x1b[3;10r\x1b[3S\x1b[1T\x1b[2L\x1b[4M\x1b[r"));
+        assert_eq!(s.matches("\x1b[r").count(), 1);
+    }
+
+    #[test]
+    fn test_scroll_region_is_validated() {
+        let mut output = Vec::new();
+        let mut terminal = Terminal::new(&mut output);
+        // Without a known size only the order is checked.
+        assert!(matches!(
+            terminal.set_scroll_region(5, 5),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            terminal.set_scroll_region(6, 2),
+            Err(Error::InvalidInput(_))
+        ));
+        terminal.set_scroll_region(0, 500).unwrap();
+
+        terminal.set_size(80, 24);
+        assert!(matches!(
+            terminal.set_scroll_region(0, 24),
+            Err(Error::InvalidInput(_))
+        ));
+        assert_eq!(terminal.scroll_region(), Some((0, 500)));
+        terminal.set_scroll_region(0, 23).unwrap();
+        assert_eq!(terminal.scroll_region(), Some((0, 23)));
+        drop(terminal);
+        assert!(String::from_utf8_lossy(&output).starts_with("\x1b[1;501r\x1b[1;24r"));
+    }
+
+    #[test]
+    fn test_scroll_region_is_reset_on_cleanup() {
+        let mut output = Vec::new();
+        {
+            let mut terminal = Terminal::new(&mut output);
+            terminal.set_scroll_region(1, 4).unwrap();
+        }
+        assert!(String::from_utf8_lossy(&output).starts_with("\x1b[2;5r\x1b[r"));
+
+        let mut output = Vec::new();
+        drop(Terminal::new(&mut output));
+        assert!(!String::from_utf8_lossy(&output).contains("\x1b[r"));
+    }
+
+    #[test]
+    fn test_scroll_region_scenario() {
+        let mut output = Vec::new();
+        {
+            let mut terminal = Terminal::new(&mut output);
+            terminal.set_size(10, 5);
+            for row in 0..5 {
+                terminal.move_cursor(0, row).unwrap();
+                terminal
+                    .write_seq(TerminalOp::Cursor, &format!("line{row}"))
+                    .unwrap();
+            }
+            // Scroll rows 1..=3 up by one, leaving rows 0 and 4 in place.
+            terminal.set_scroll_region(1, 3).unwrap();
+            terminal.scroll_up(1).unwrap();
+            // Open a blank line at row 2 within the region.
+            terminal.move_cursor(0, 2).unwrap();
+            terminal.insert_lines(1).unwrap();
+            terminal.reset_scroll_region().unwrap();
+            // With the region reset, scrolling moves the whole screen.
+            terminal.scroll_down(1).unwrap();
+        }
+
+        let mut parser = vt100::Parser::new(5, 10, 0);
+        parser.process(&output);
+        let rows: Vec<String> = parser.screen().rows(0, 10).collect();
+        assert_eq!(rows, ["", "line0", "line2", "", "line3"]);
+    }
+
+    #[test]
+    fn test_palette_changes_are_restored_on_cleanup() {
+        let mut output = Vec::new();
+        {
+            let mut terminal = Terminal::new(&mut output);
+            terminal
+                .set_palette_color(4, crate::color::Rgba::rgb(0.0, 0.5, 1.0))
+                .unwrap();
+            terminal
+                .set_palette_color(1, crate::color::Rgba::RED)
+                .unwrap();
+            terminal
+                .set_palette_color(9, crate::color::Rgba::RED)
+                .unwrap();
+            terminal.reset_palette_color(9).unwrap();
+            assert_eq!(
+                terminal
+                    .modified_palette
+                    .iter()
+                    .copied()
+                    .collect::<Vec<_>>(),
+                [1, 4]
+            );
+        }
+
+        let s = String::from_utf8_lossy(&output);
+        assert!(s.starts_with(
+            "\x1b]4;4;rgb:0000/8000/ffff\x1b\\\
+             \x1b]4;1;rgb:ffff/0000/0000\x1b\\\
+             \x1b]4;9;rgb:ffff/0000/0000\x1b\\\
+             \x1b]104;9\x1b\\\
+             \x1b]104;1;4\x1b\\"
+        ));
+    }
+
+    #[test]
+    fn test_reset_palette_clears_restoration_set() {
+        let mut output = Vec::new();
+        {
+            let mut terminal = Terminal::new(&mut output);
+            terminal
+                .set_palette_color(2, crate::color::Rgba::GREEN)
+                .unwrap();
+            terminal.reset_palette().unwrap();
+            assert!(terminal.modified_palette.is_empty());
+        }
+
+        let s = String::from_utf8_lossy(&output);
+        assert_eq!(s.matches("\x1b]104").count(), 1);
+        assert!(s.contains("\x1b]104\x1b\\"));
+    }
+
     #[test]
     fn test_terminal_alt_screen() {
         let mut terminal = Terminal::new(Vec::new());
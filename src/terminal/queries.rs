@@ -6,8 +6,11 @@
 //! - XTVERSION: `ESC[>0q`
 //! - Pixel resolution: `ESC[14t`
 //! - Kitty keyboard protocol: `ESC[?u`
+//! - Palette colors: `OSC 4 ; index ; ? ST`
+//! - Default background color: `OSC 11 ; ? ST`
 
 use crate::ansi::sequences;
+use crate::color::Rgba;
 
 /// Maximum length for DCS response parsing.
 ///
@@ -61,6 +64,22 @@ pub enum TerminalResponse {
         flags: u32,
     },
 
+    /// Palette color (OSC 4) response.
+    /// Response format: `ESC ] 4 ; index ; rgb:RRRR/GGGG/BBBB ST`
+    PaletteColor {
+        /// Palette index.
+        index: u8,
+        /// Red, green, and blue, scaled to 16 bits per channel.
+        rgb: (u16, u16, u16),
+    },
+
+    /// Default background color (OSC 11) response.
+    /// Response format: `ESC ] 11 ; rgb:RRRR/GGGG/BBBB ST`
+    BackgroundColor {
+        /// Red, green, and blue, scaled to 16 bits per channel.
+        rgb: (u16, u16, u16),
+    },
+
     /// Unknown or unparseable response.
     Unknown(Vec<u8>),
 }
@@ -94,6 +113,12 @@ impl TerminalResponse {
         if let Some(resp) = Self::parse_kitty_keyboard(input) {
             return Some(resp);
         }
+        if let Some(resp) = Self::parse_palette_color(input) {
+            return Some(resp);
+        }
+        if let Some(resp) = Self::parse_background_color(input) {
+            return Some(resp);
+        }
 
         Some(TerminalResponse::Unknown(input.to_vec()))
     }
@@ -243,6 +268,24 @@ impl TerminalResponse {
         Some(TerminalResponse::KittyKeyboard { flags })
     }
 
+    /// Parse palette color response: `ESC ] 4 ; index ; rgb:R/G/B ST`
+    ///
+    /// ST may be `ESC \`, `\x9c`, or BEL. Each channel has 1-4 hex digits
+    /// and is scaled to 16 bits.
+    fn parse_palette_color(input: &[u8]) -> Option<Self> {
+        let content = osc_content(input, b"\x1b]4;")?;
+        let (index, spec) = content.split_once(';')?;
+        let index: u8 = index.parse().ok()?;
+        let rgb = parse_rgb_spec(spec)?;
+        Some(TerminalResponse::PaletteColor { index, rgb })
+    }
+
+    /// Parse default background color response: `ESC ] 11 ; rgb:R/G/B ST`
+    fn parse_background_color(input: &[u8]) -> Option<Self> {
+        let rgb = parse_rgb_spec(osc_content(input, b"\x1b]11;")?)?;
+        Some(TerminalResponse::BackgroundColor { rgb })
+    }
+
     /// Check if DA1 response indicates sixel support.
     /// Sixel is indicated by parameter 4 in the DA1 response.
     #[must_use]
@@ -258,6 +301,31 @@ impl TerminalResponse {
         }
     }
 
+    /// Get the palette index and color from an OSC 4 response.
+    #[must_use]
+    pub fn palette_color(&self) -> Option<(u8, Rgba)> {
+        if let TerminalResponse::PaletteColor { index, rgb } = self {
+            let channel = |value: u16| f32::from(value) / 65535.0;
+            Some((
+                *index,
+                Rgba::rgb(channel(rgb.0), channel(rgb.1), channel(rgb.2)),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Get the color from an OSC 11 response.
+    #[must_use]
+    pub fn background_color(&self) -> Option<Rgba> {
+        if let TerminalResponse::BackgroundColor { rgb } = self {
+            let channel = |value: u16| f32::from(value) / 65535.0;
+            Some(Rgba::rgb(channel(rgb.0), channel(rgb.1), channel(rgb.2)))
+        } else {
+            None
+        }
+    }
+
     /// Get terminal name from XTVERSION response.
     #[must_use]
     pub fn terminal_name(&self) -> Option<&str> {
@@ -269,6 +337,36 @@ impl TerminalResponse {
     }
 }
 
+/// The text of an OSC response after `prefix`, up to its terminator.
+fn osc_content<'a>(input: &'a [u8], prefix: &[u8]) -> Option<&'a str> {
+    if input.len() > MAX_DCS_RESPONSE_LENGTH || !input.starts_with(prefix) {
+        return None;
+    }
+    let content = &input[prefix.len()..];
+    let end = content
+        .iter()
+        .position(|&b| b == 0x07 || b == 0x1b || b == 0x9c)?;
+    std::str::from_utf8(&content[..end]).ok()
+}
+
+/// Parse an X11 color spec, `rgb:R/G/B` with 1-4 hex digits per channel,
+/// scaling each channel to 16 bits.
+fn parse_rgb_spec(spec: &str) -> Option<(u16, u16, u16)> {
+    let mut channels = spec.strip_prefix("rgb:")?.split('/').map(|hex| {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len())) - 1;
+        u16::try_from(value * 0xffff / max).ok()
+    });
+    let rgb = (channels.next()??, channels.next()??, channels.next()??);
+    if channels.next().is_some() {
+        return None;
+    }
+    Some(rgb)
+}
+
 /// Get all capability query sequences as a single string.
 #[must_use]
 pub fn all_queries() -> String {
@@ -450,6 +548,71 @@ mod tests {
         assert!(response.has_sixel());
     }
 
+    #[test]
+    fn test_parse_palette_color_replies() {
+        // xterm answers with the query's terminator, here BEL
+        let xterm = TerminalResponse::parse(b"\x1b]4;1;rgb:cdcd/0000/0000\x07").unwrap();
+        assert_eq!(
+            xterm,
+            TerminalResponse::PaletteColor {
+                index: 1,
+                rgb: (0xcdcd, 0, 0)
+            }
+        );
+
+        // VTE, ST-terminated
+        let vte = TerminalResponse::parse(b"\x1b]4;12;rgb:7272/9f9f/cfcf\x1b\\").unwrap();
+        let (index, color) = vte.palette_color().unwrap();
+        assert_eq!(index, 12);
+        assert_eq!(color.to_rgb_u8(), (0x72, 0x9f, 0xcf));
+
+        // Two hex digits per channel are scaled up to 16 bits
+        let short = TerminalResponse::parse(b"\x1b]4;255;rgb:ee/ee/ee\x1b\\").unwrap();
+        assert_eq!(
+            short,
+            TerminalResponse::PaletteColor {
+                index: 255,
+                rgb: (0xeeee, 0xeeee, 0xeeee)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed_palette_color_is_unknown() {
+        for input in [
+            &b"\x1b]4;256;rgb:ff/ff/ff\x07"[..],
+            b"\x1b]4;1;rgb:ff/ff\x07",
+            b"\x1b]4;1;rgb:fffff/0/0\x07",
+            b"\x1b]4;1;#ff0000\x07",
+            b"\x1b]4;1;rgb:ff/ff/ff",
+        ] {
+            let response = TerminalResponse::parse(input).unwrap();
+            assert!(
+                matches!(response, TerminalResponse::Unknown(_)),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_background_color_reply() {
+        let response = TerminalResponse::parse(b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\").unwrap();
+        assert_eq!(
+            response,
+            TerminalResponse::BackgroundColor {
+                rgb: (0x1e1e, 0x1e1e, 0x2e2e)
+            }
+        );
+        assert_eq!(
+            response.background_color().unwrap().to_rgb_u8(),
+            (0x1e, 0x1e, 0x2e)
+        );
+        assert_eq!(response.palette_color(), None);
+
+        let malformed = TerminalResponse::parse(b"\x1b]11;rgb:1e/1e\x07").unwrap();
+        assert!(matches!(malformed, TerminalResponse::Unknown(_)));
+    }
+
     #[test]
     fn test_terminal_name_extraction() {
         let input = b"\x1bP>|foot 1.15.3\x1b\\";
@@ -104,6 +104,46 @@ pub fn terminal_size() -> io::Result<(u16, u16)> {
     }
 }
 
+/// Read available stdin bytes into `buf`, waiting at most `timeout`.
+///
+/// Returns `Ok(0)` when nothing arrived in time or the wait was
+/// interrupted by a signal such as `SIGWINCH`. Unlike [`io::Stdin`], this
+/// reads the file descriptor directly, so no input is left sitting in a
+/// userspace buffer between calls.
+pub fn read_stdin_timeout(buf: &mut [u8], timeout: std::time::Duration) -> io::Result<usize> {
+    let mut pollfd = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let millis = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+
+    // SAFETY: poll is given one valid pollfd.
+    let ready = unsafe { libc::poll(&mut pollfd, 1, millis) };
+    if ready == -1 {
+        let err = io::Error::last_os_error();
+        return if err.kind() == io::ErrorKind::Interrupted {
+            Ok(0)
+        } else {
+            Err(err)
+        };
+    }
+    if ready == 0 || buf.is_empty() {
+        return Ok(0);
+    }
+
+    // SAFETY: buf is valid for writes of buf.len() bytes.
+    let read = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+    if read == -1 {
+        let err = io::Error::last_os_error();
+        if err.kind() == io::ErrorKind::Interrupted || err.kind() == io::ErrorKind::WouldBlock {
+            return Ok(0);
+        }
+        return Err(err);
+    }
+    Ok(read as usize)
+}
+
 /// Get termios attributes.
 fn get_termios(fd: RawFd) -> io::Result<libc::termios> {
     let mut termios: libc::termios = unsafe { std::mem::zeroed() };
@@ -0,0 +1,332 @@
+//! Snapshot and assertion helpers for testing rendered buffers.
+//!
+//! Enabled by the `testing` feature. [`BufferSnapshot`] captures what a
+//! buffer would show, cell by cell, in a stable text format suited to
+//! golden files and `insta` snapshots:
+//!
+//! ```text
+//! size: 6x1
+//! glyphs:
+//!   |Hi 世 |
+//! styles:
+//!   |ABAAAA|
+//! legend:
+//!   A fg=#FFFFFF bg=#000000 attrs=-
+//!   B fg=#FF0000 bg=#000000 attrs=BOLD
+//! ```
+//!
+//! Each glyph row shows one line of the buffer, with wide characters taking
+//! their two columns. Each style row has one symbol per cell, explained by
+//! the legend. [`assert_buffer_matches`] compares two snapshots and panics
+//! with a per-cell report of the differences.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::buffer::OptimizedBuffer;
+use crate::cell::{Cell, CellContent};
+use crate::grapheme_pool::GraphemePool;
+use crate::style::UnderlineStyle;
+
+/// Symbols assigned to styles in order of first appearance.
+const STYLE_SYMBOLS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Shown for control characters and graphemes missing from the pool.
+const REPLACEMENT: &str = "\u{FFFD}";
+
+/// Mismatched cells listed individually before the report is cut short.
+const MAX_LISTED_MISMATCHES: usize = 20;
+
+/// A cell's colors and attributes as they appear in the snapshot text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CellStyle {
+    fg: String,
+    bg: String,
+    attrs: String,
+}
+
+impl CellStyle {
+    fn from_cell(cell: &Cell) -> Self {
+        let mut attrs: Vec<String> = cell
+            .attributes
+            .iter_names()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        if attrs.is_empty() {
+            attrs.push("-".to_string());
+        }
+        let mut attrs = attrs.join("|");
+        if cell.underline_style != UnderlineStyle::Single {
+            attrs.push_str(&format!(" underline={:?}", cell.underline_style));
+        }
+        if let Some(color) = cell.underline_color {
+            attrs.push_str(&format!(" underline_color={color}"));
+        }
+        if let Some(link) = cell.attributes.link_id() {
+            attrs.push_str(&format!(" link={link}"));
+        }
+        Self {
+            fg: cell.fg.to_string(),
+            bg: cell.bg.to_string(),
+            attrs,
+        }
+    }
+}
+
+impl fmt::Display for CellStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fg={} bg={} attrs={}", self.fg, self.bg, self.attrs)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SnapshotCell {
+    /// Displayed text; empty for the trailing half of a wide character.
+    glyph: String,
+    style: CellStyle,
+}
+
+/// What a buffer shows, captured for comparison and golden files.
+///
+/// Cells compare by their displayed glyph and by colors rounded to 8 bits
+/// per channel, as printed in the snapshot text; an empty cell and a space
+/// look the same.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BufferSnapshot {
+    width: u32,
+    height: u32,
+    cells: Vec<SnapshotCell>,
+}
+
+impl BufferSnapshot {
+    /// Capture `buffer`, resolving pooled graphemes through `pool`.
+    #[must_use]
+    pub fn from_buffer(buffer: &OptimizedBuffer, pool: &GraphemePool) -> Self {
+        let cells = buffer
+            .cells()
+            .iter()
+            .map(|cell| {
+                let glyph = match cell.content {
+                    CellContent::Char(c) if c.is_control() => REPLACEMENT.to_string(),
+                    CellContent::Char(c) => c.to_string(),
+                    CellContent::Grapheme(id) => pool.get(id).unwrap_or(REPLACEMENT).to_string(),
+                    CellContent::Empty => " ".to_string(),
+                    CellContent::Continuation => String::new(),
+                };
+                SnapshotCell {
+                    glyph,
+                    style: CellStyle::from_cell(cell),
+                }
+            })
+            .collect();
+        let (width, height) = buffer.size();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Buffer width in cells.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Buffer height in cells.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The text of each row, as in the snapshot's glyph section.
+    #[must_use]
+    pub fn rows(&self) -> Vec<String> {
+        self.row_cells()
+            .map(|row| row.iter().map(|cell| cell.glyph.as_str()).collect())
+            .collect()
+    }
+
+    fn row_cells(&self) -> impl Iterator<Item = &[SnapshotCell]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// Compare against `actual`, treating `self` as the expected snapshot.
+    ///
+    /// Returns `None` if they match, or a report listing each mismatched
+    /// cell by row and column with its expected and actual glyph and style,
+    /// followed by the actual rows with the mismatched cells marked.
+    #[must_use]
+    pub fn diff(&self, actual: &Self) -> Option<String> {
+        if self == actual {
+            return None;
+        }
+        if (self.width, self.height) != (actual.width, actual.height) {
+            return Some(format!(
+                "buffer size mismatch: expected {}x{}, actual {}x{}\n\
+                 --- expected\n{self}--- actual\n{actual}",
+                self.width, self.height, actual.width, actual.height
+            ));
+        }
+
+        let width = self.width as usize;
+        let mismatched: Vec<usize> = (0..self.cells.len())
+            .filter(|&idx| self.cells[idx] != actual.cells[idx])
+            .collect();
+        let mut out = format!(
+            "buffer mismatch: {} of {} cells differ\n",
+            mismatched.len(),
+            self.cells.len()
+        );
+        for &idx in mismatched.iter().take(MAX_LISTED_MISMATCHES) {
+            let (expected, found) = (&self.cells[idx], &actual.cells[idx]);
+            out.push_str(&format!(
+                "  row {}, col {}:\n    expected {:?} {}\n    actual   {:?} {}\n",
+                idx / width,
+                idx % width,
+                expected.glyph,
+                expected.style,
+                found.glyph,
+                found.style
+            ));
+        }
+        if mismatched.len() > MAX_LISTED_MISMATCHES {
+            out.push_str(&format!(
+                "  ... and {} more\n",
+                mismatched.len() - MAX_LISTED_MISMATCHES
+            ));
+        }
+
+        out.push_str("actual (^ marks mismatched cells):\n");
+        for (y, row) in actual.row_cells().enumerate() {
+            let glyphs: String = row.iter().map(|cell| cell.glyph.as_str()).collect();
+            let marks: String = (0..width)
+                .map(|x| {
+                    if mismatched.binary_search(&(y * width + x)).is_ok() {
+                        '^'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect();
+            out.push_str(&format!("  |{glyphs}|\n"));
+            if marks.contains('^') {
+                out.push_str(&format!("   {}\n", marks.trim_end()));
+            }
+        }
+        Some(out)
+    }
+}
+
+impl fmt::Display for BufferSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size: {}x{}", self.width, self.height)?;
+        writeln!(f, "glyphs:")?;
+        for row in self.rows() {
+            writeln!(f, "  |{row}|")?;
+        }
+
+        let mut legend: Vec<&CellStyle> = Vec::new();
+        let mut symbols: HashMap<&CellStyle, char> = HashMap::new();
+        writeln!(f, "styles:")?;
+        for row in self.row_cells() {
+            let line: String = row
+                .iter()
+                .map(|cell| {
+                    *symbols.entry(&cell.style).or_insert_with(|| {
+                        legend.push(&cell.style);
+                        style_symbol(legend.len() - 1)
+                    })
+                })
+                .collect();
+            writeln!(f, "  |{line}|")?;
+        }
+
+        writeln!(f, "legend:")?;
+        for (idx, style) in legend.iter().enumerate() {
+            writeln!(f, "  {} {style}", style_symbol(idx))?;
+        }
+        Ok(())
+    }
+}
+
+/// Legend symbol for the `idx`th distinct style, continuing past
+/// [`STYLE_SYMBOLS`] into the Latin-1 letters and beyond.
+fn style_symbol(idx: usize) -> char {
+    STYLE_SYMBOLS.chars().nth(idx).unwrap_or_else(|| {
+        let extra = (idx - STYLE_SYMBOLS.len()) as u32;
+        char::from_u32(0xC0 + extra).unwrap_or('?')
+    })
+}
+
+/// Assert that two buffer snapshots match.
+///
+/// # Panics
+///
+/// Panics with [`BufferSnapshot::diff`]'s report if they differ.
+#[track_caller]
+pub fn assert_buffer_matches(actual: &BufferSnapshot, expected: &BufferSnapshot) {
+    if let Some(report) = expected.diff(actual) {
+        panic!("{report}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgba;
+    use crate::style::Style;
+
+    fn sample(pool: &mut GraphemePool, greeting: &str) -> OptimizedBuffer {
+        let mut buffer = OptimizedBuffer::new(12, 2);
+        buffer.clear(Rgba::BLACK);
+        let plain = Style::fg(Rgba::WHITE);
+        buffer.draw_text_with_pool(pool, 0, 0, greeting, Style::fg(Rgba::RED).with_bold());
+        buffer.draw_text_with_pool(pool, 0, 1, "世界 👩‍🚀!", plain);
+        buffer.draw_text_with_pool(pool, 9, 1, "ok", plain.with_underline());
+        buffer
+    }
+
+    #[test]
+    fn test_snapshot_text_shows_wide_chars_and_pooled_graphemes() {
+        let mut pool = GraphemePool::new();
+        let buffer = sample(&mut pool, "Hello");
+        let snapshot = BufferSnapshot::from_buffer(&buffer, &pool);
+        assert_eq!(snapshot.rows()[1], "世界 👩‍🚀! ok ");
+        insta::assert_snapshot!(snapshot.to_string());
+    }
+
+    #[test]
+    fn test_matching_buffers_pass() {
+        let mut pool = GraphemePool::new();
+        let a = BufferSnapshot::from_buffer(&sample(&mut pool, "Hello"), &pool);
+        let b = BufferSnapshot::from_buffer(&sample(&mut pool, "Hello"), &pool);
+        assert_eq!(a.diff(&b), None);
+        assert_buffer_matches(&a, &b);
+    }
+
+    #[test]
+    fn test_failing_comparison_report() {
+        let mut pool = GraphemePool::new();
+        let expected = BufferSnapshot::from_buffer(&sample(&mut pool, "Hello"), &pool);
+        let mut actual = sample(&mut pool, "Help!");
+        actual.set(0, 0, Cell::new('H', Style::fg(Rgba::GREEN).with_italic()));
+        let actual = BufferSnapshot::from_buffer(&actual, &pool);
+
+        let report = expected.diff(&actual).unwrap();
+        insta::assert_snapshot!(report);
+
+        let panic = std::panic::catch_unwind(|| assert_buffer_matches(&actual, &expected))
+            .expect_err("mismatched buffers should panic");
+        assert_eq!(panic.downcast_ref::<String>(), Some(&report));
+    }
+
+    #[test]
+    fn test_size_mismatch_report() {
+        let pool = GraphemePool::new();
+        let small = BufferSnapshot::from_buffer(&OptimizedBuffer::new(2, 1), &pool);
+        let large = BufferSnapshot::from_buffer(&OptimizedBuffer::new(3, 1), &pool);
+        let report = small.diff(&large).unwrap();
+        assert!(report.starts_with("buffer size mismatch: expected 2x1, actual 3x1\n"));
+    }
+}
@@ -5,14 +5,21 @@
 //! or display-oriented text. For editing with cursor and undo, see
 //! [`EditBuffer`](super::EditBuffer).
 
+use crate::error::Result;
 use crate::highlight::SyntaxStyleRegistry;
 use crate::style::Style;
 use crate::text::rope::RopeWrapper;
 use crate::text::segment::{StyledChunk, StyledSegment};
+use crate::text::tagged::{StyleHandle, TaggedStyles};
 use crate::unicode::WidthMethod;
+use std::collections::VecDeque;
 use std::ops::Range;
 use std::sync::Arc;
 
+/// Line edits kept for [`TextBuffer::line_edits_since`]; older ones are
+/// forgotten and readers fall back to a full refresh.
+const MAX_LINE_EDITS: usize = 256;
+
 #[derive(Clone, Debug)]
 struct MemEntry {
     data: String,
@@ -83,12 +90,56 @@ impl MemRegistry {
 pub struct TextBuffer {
     rope: RopeWrapper,
     segments: Vec<StyledSegment>,
+    tagged: TaggedStyles,
     default_style: Style,
     tab_width: u8,
     mem_registry: MemRegistry,
     width_method: WidthMethod,
     syntax_styles: Option<Arc<SyntaxStyleRegistry>>,
     revision: u64,
+    max_lines: Option<usize>,
+    trimmed: Trimmed,
+    // Edits made since revision `edits_base`, oldest first
+    line_edits: VecDeque<LineEdit>,
+    edits_base: u64,
+}
+
+/// Lines replaced by one edit: `start..start + removed` before it became
+/// `start..start + inserted` after it.
+///
+/// See [`TextBuffer::line_edits_since`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineEdit {
+    /// First line touched.
+    pub start: usize,
+    /// Lines there before the edit.
+    pub removed: usize,
+    /// Lines there after the edit.
+    pub inserted: usize,
+}
+
+/// Running total of text dropped from the front of a capped buffer.
+///
+/// See [`TextBuffer::with_max_lines`]. Keep the value from one frame and
+/// hand what [`since`](Self::since) returns on the next to
+/// [`TextBufferView::rebase`](super::TextBufferView::rebase).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Trimmed {
+    /// Lines removed.
+    pub lines: usize,
+    /// Chars removed.
+    pub chars: usize,
+}
+
+impl Trimmed {
+    /// What was trimmed after `earlier` was taken.
+    #[must_use]
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            lines: self.lines - earlier.lines,
+            chars: self.chars - earlier.chars,
+        }
+    }
 }
 
 impl TextBuffer {
@@ -98,12 +149,17 @@ impl TextBuffer {
         Self {
             rope: RopeWrapper::new(),
             segments: Vec::new(),
+            tagged: TaggedStyles::default(),
             default_style: Style::NONE,
             tab_width: 4,
             mem_registry: MemRegistry::default(),
             width_method: WidthMethod::default(),
             syntax_styles: None,
             revision: 0,
+            max_lines: None,
+            trimmed: Trimmed::default(),
+            line_edits: VecDeque::new(),
+            edits_base: 0,
         }
     }
 
@@ -113,15 +169,49 @@ impl TextBuffer {
         Self {
             rope: RopeWrapper::from_str(text),
             segments: Vec::new(),
+            tagged: TaggedStyles::default(),
             default_style: Style::NONE,
             tab_width: 4,
             mem_registry: MemRegistry::default(),
             width_method: WidthMethod::default(),
             syntax_styles: None,
             revision: 0,
+            max_lines: None,
+            trimmed: Trimmed::default(),
+            line_edits: VecDeque::new(),
+            edits_base: 0,
         }
     }
 
+    /// Create an empty buffer that keeps at most `max_lines` lines.
+    ///
+    /// Appending past the cap drops the oldest lines, along with their
+    /// highlights. Meant for log panes; see [`append_line`](Self::append_line).
+    #[must_use]
+    pub fn with_max_lines(max_lines: usize) -> Self {
+        let mut buffer = Self::new();
+        buffer.set_max_lines(Some(max_lines));
+        buffer
+    }
+
+    /// Set or remove the line cap, trimming right away if over it.
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        self.max_lines = max_lines.map(|max| max.max(1));
+        self.trim_to_max_lines();
+    }
+
+    /// Get the line cap.
+    #[must_use]
+    pub fn max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+
+    /// Total text trimmed from the front since the buffer was created.
+    #[must_use]
+    pub fn trimmed(&self) -> Trimmed {
+        self.trimmed
+    }
+
     /// Set the default style for unstyled text.
     pub fn set_default_style(&mut self, style: Style) {
         self.default_style = style;
@@ -165,23 +255,76 @@ impl TextBuffer {
         self.syntax_styles = None;
     }
 
-    /// Set the text content, clearing all segments.
+    /// Set the text content, clearing all segments and tagged styles.
     pub fn set_text(&mut self, text: &str) {
         self.rope.replace(text);
         self.segments.clear();
+        self.tagged.clear();
         self.bump_revision();
+        self.trim_to_max_lines();
     }
 
     /// Append text to the buffer.
     pub fn append(&mut self, text: &str) {
+        let lines = self.rope.len_lines();
         self.rope.append(text);
-        self.bump_revision();
+        self.record_edit(LineEdit {
+            start: lines - 1,
+            removed: 1,
+            inserted: self.rope.len_lines() + 1 - lines,
+        });
+        self.trim_to_max_lines();
+    }
+
+    /// Insert text at a char offset, shifting tagged styles after it.
+    ///
+    /// Fails as [`RopeWrapper::insert`] does, changing nothing.
+    pub fn insert(&mut self, char_idx: usize, text: &str) -> Result<()> {
+        let lines = self.rope.len_lines();
+        self.rope.insert(char_idx, text)?;
+        self.tagged.apply_edit(char_idx, 0, text.chars().count());
+        self.record_edit(LineEdit {
+            start: self.rope.char_to_line(char_idx),
+            removed: 1,
+            inserted: self.rope.len_lines() + 1 - lines,
+        });
+        Ok(())
+    }
+
+    /// Remove a char range, shifting or dropping tagged styles it touches.
+    ///
+    /// Fails as [`RopeWrapper::remove`] does, changing nothing.
+    pub fn remove(&mut self, range: Range<usize>) -> Result<()> {
+        let lines = self.rope.len_lines();
+        let start = self.rope.char_to_line(range.start);
+        let removed = self.rope.char_to_line(range.end).saturating_sub(start) + 1;
+        self.rope.remove(range.clone())?;
+        self.tagged.apply_edit(range.start, range.len(), 0);
+        self.record_edit(LineEdit {
+            start,
+            removed,
+            inserted: (removed + self.rope.len_lines()).saturating_sub(lines),
+        });
+        Ok(())
+    }
+
+    /// Append `line` as a new last line.
+    ///
+    /// The first line of an empty buffer is appended without a separator.
+    pub fn append_line(&mut self, line: &str) {
+        if self.rope.is_empty() {
+            self.append(line);
+        } else {
+            self.rope.append("\n");
+            self.append(line);
+        }
     }
 
     /// Set styled text content from chunks.
     pub fn set_styled_text(&mut self, chunks: &[StyledChunk<'_>]) {
         self.rope.clear();
         self.segments.clear();
+        self.tagged.clear();
         self.bump_revision();
 
         let mut offset = 0;
@@ -201,6 +344,7 @@ impl TextBuffer {
     pub fn clear(&mut self) {
         self.rope.clear();
         self.segments.clear();
+        self.tagged.clear();
         self.bump_revision();
     }
 
@@ -262,6 +406,21 @@ impl TextBuffer {
         self.revision
     }
 
+    /// Line edits made since `revision`, oldest first, for updating
+    /// per-line caches in place.
+    ///
+    /// Returns `None` when the buffer can't say: the log no longer reaches
+    /// back that far, or a change such as [`set_text`](Self::set_text) or
+    /// [`rope_mut`](Self::rope_mut) wasn't tracked by line.
+    #[must_use]
+    pub fn line_edits_since(&self, revision: u64) -> Option<impl Iterator<Item = LineEdit> + '_> {
+        if revision < self.edits_base || revision > self.revision {
+            return None;
+        }
+        let skip = usize::try_from(revision - self.edits_base).ok()?;
+        Some(self.line_edits.iter().skip(skip).copied())
+    }
+
     /// Add a highlight (styled segment) to a range.
     pub fn add_highlight(&mut self, range: Range<usize>, style: Style, priority: u8) {
         self.segments
@@ -358,6 +517,51 @@ impl TextBuffer {
             .retain(|seg| !(seg.line == Some(line) && seg.ref_id == Some(ref_id)));
     }
 
+    /// Style a char range until the style is removed, returning a handle
+    /// for [`remove_style`](Self::remove_style).
+    ///
+    /// Unlike highlights, tagged styles shift with [`insert`](Self::insert)
+    /// and [`remove`](Self::remove), and are kept as a minimal list of
+    /// non-overlapping [`tagged_segments`](Self::tagged_segments). They
+    /// compose over highlights, syntax highlighting included, the way
+    /// highlight layers do: in ascending [tag priority](Self::set_style_tag_priority),
+    /// then in the order added. Being part of the buffer style, they sit
+    /// below the [`HighlightLayers`](crate::highlight::HighlightLayers) a
+    /// view composes over it.
+    pub fn add_style(&mut self, range: Range<usize>, style: Style, tag: u32) -> StyleHandle {
+        self.tagged.add(range, style, tag)
+    }
+
+    /// Remove a style added with [`add_style`](Self::add_style), leaving
+    /// the others as they were. Returns `false` if it is already gone.
+    pub fn remove_style(&mut self, handle: StyleHandle) -> bool {
+        self.tagged.remove(handle)
+    }
+
+    /// Remove every style added with `tag`.
+    pub fn clear_styles_with_tag(&mut self, tag: u32) {
+        self.tagged.clear_tag(tag);
+    }
+
+    /// Set the priority of styles added with `tag`; higher priorities
+    /// compose later. Tags default to priority 0.
+    pub fn set_style_tag_priority(&mut self, tag: u32, priority: u8) {
+        self.tagged.set_priority(tag, priority);
+    }
+
+    /// Priority of styles added with `tag`.
+    #[must_use]
+    pub fn style_tag_priority(&self, tag: u32) -> u8 {
+        self.tagged.priority(tag)
+    }
+
+    /// Tagged styles composed into segments, in char offsets: sorted,
+    /// non-overlapping, and with no two adjacent segments of equal style.
+    #[must_use]
+    pub fn tagged_segments(&self) -> &[(Range<usize>, Style)] {
+        self.tagged.segments()
+    }
+
     /// Register external text in the memory registry.
     pub fn register_text(&mut self, text: &str, owned: bool) -> u32 {
         self.mem_registry.register(text, owned)
@@ -382,7 +586,7 @@ impl TextBuffer {
             .filter(move |seg| seg.range.start < range.end && range.start < seg.range.end)
     }
 
-    /// Get the style at a byte position.
+    /// Get the style at a byte position, tagged styles included.
     #[must_use]
     pub fn style_at(&self, pos: usize) -> Style {
         let mut style = self.default_style;
@@ -395,6 +599,12 @@ impl TextBuffer {
             }
         }
 
+        if !self.tagged.is_empty() {
+            let offset = self.rope.byte_to_char(pos);
+            if let Some(tagged) = self.tagged.style_at(offset) {
+                style = style.merge(tagged);
+            }
+        }
         style
     }
 
@@ -404,8 +614,58 @@ impl TextBuffer {
         self.rope.to_string()
     }
 
+    /// Drop the oldest lines until the buffer is within its cap.
+    ///
+    /// Costs a rope removal plus a pass over the highlights, so appending
+    /// lines one at a time stays cheap for unstyled logs.
+    fn trim_to_max_lines(&mut self) {
+        let Some(max_lines) = self.max_lines else {
+            return;
+        };
+        let excess = self.rope.len_lines().saturating_sub(max_lines);
+        if excess == 0 {
+            return;
+        }
+        let chars = self.rope.line_to_char(excess);
+        let bytes = self.rope.char_to_byte(chars);
+        // In bounds: `chars` is the start of an existing line.
+        let _ = self.rope.remove(..chars);
+        self.tagged.apply_edit(0, chars, 0);
+        self.segments.retain_mut(|seg| {
+            if seg.range.end <= bytes || seg.line.is_some_and(|line| line < excess) {
+                return false;
+            }
+            seg.range.start = seg.range.start.saturating_sub(bytes);
+            seg.range.end -= bytes;
+            if let Some(line) = &mut seg.line {
+                *line -= excess;
+            }
+            true
+        });
+        self.trimmed.lines += excess;
+        self.trimmed.chars += chars;
+        self.record_edit(LineEdit {
+            start: 0,
+            removed: excess,
+            inserted: 0,
+        });
+    }
+
+    /// Note a content change made by line, see [`line_edits_since`](Self::line_edits_since).
+    fn record_edit(&mut self, edit: LineEdit) {
+        self.revision += 1;
+        self.line_edits.push_back(edit);
+        if self.line_edits.len() > MAX_LINE_EDITS {
+            self.line_edits.pop_front();
+            self.edits_base += 1;
+        }
+    }
+
+    /// Note a content change that wasn't tracked by line.
     fn bump_revision(&mut self) {
-        self.revision = self.revision.wrapping_add(1);
+        self.revision += 1;
+        self.line_edits.clear();
+        self.edits_base = self.revision;
     }
 }
 
@@ -482,6 +742,183 @@ mod tests {
         assert_eq!(buffer.to_string(), "External");
     }
 
+    #[test]
+    fn test_max_lines_trims_oldest() {
+        let mut buffer = TextBuffer::with_max_lines(3);
+        for line in ["one", "two", "three"] {
+            buffer.append_line(line);
+        }
+        assert_eq!(buffer.to_string(), "one\ntwo\nthree");
+        assert_eq!(buffer.trimmed(), Trimmed::default());
+
+        buffer.append_line("four");
+        buffer.append_line("five");
+        assert_eq!(buffer.to_string(), "three\nfour\nfive");
+        assert_eq!(buffer.trimmed(), Trimmed { lines: 2, chars: 8 });
+
+        let mark = buffer.trimmed();
+        buffer.append("\nsix\nseven");
+        assert_eq!(buffer.to_string(), "five\nsix\nseven");
+        assert_eq!(
+            buffer.trimmed().since(mark),
+            Trimmed {
+                lines: 2,
+                chars: 11
+            }
+        );
+
+        buffer.set_max_lines(Some(1));
+        assert_eq!(buffer.to_string(), "seven");
+        buffer.set_max_lines(None);
+        buffer.append_line("eight");
+        assert_eq!(buffer.len_lines(), 2);
+    }
+
+    #[test]
+    fn test_trim_releases_highlights() {
+        let mut buffer = TextBuffer::with_max_lines(3);
+        for line in ["aaa", "bbb", "ccc"] {
+            buffer.append_line(line);
+        }
+        buffer.add_highlight_line(0, 0, 3, Style::bold(), 0, Some(1));
+        buffer.add_highlight_line(2, 0, 3, Style::italic(), 0, Some(1));
+        // Byte ranges: one inside line 0, one from line 0 into line 1.
+        buffer.add_highlight(1..2, Style::underline(), 0);
+        buffer.add_highlight(2..6, Style::fg(Rgba::RED), 0);
+
+        buffer.append_line("ddd");
+        assert_eq!(buffer.to_string(), "bbb\nccc\nddd");
+        assert_eq!(buffer.segments_in_range(0..buffer.len_bytes()).count(), 2);
+        let bold = crate::style::TextAttributes::BOLD;
+        let italic = crate::style::TextAttributes::ITALIC;
+        assert!(buffer.style_at(0).fg.is_some_and(|fg| fg == Rgba::RED));
+        assert!(buffer.style_at(2).fg.is_none());
+        assert!(!buffer.style_at(0).attributes.contains(bold));
+        assert!(buffer.style_at(4).attributes.contains(italic));
+
+        // The line highlight follows its line and is released with it.
+        buffer.clear_line_highlights(1);
+        assert!(!buffer.style_at(4).attributes.contains(italic));
+        buffer.append_line("eee");
+        buffer.append_line("fff");
+        assert_eq!(buffer.segments_in_range(0..buffer.len_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn test_line_edits_since() {
+        let mut buffer = TextBuffer::with_text("one\ntwo\nthree");
+        let start = buffer.revision();
+        assert_eq!(buffer.line_edits_since(start).unwrap().count(), 0);
+
+        buffer.insert(4, "2a\n2b\n").unwrap();
+        buffer.remove(0..7).unwrap();
+        buffer.append("\nfour");
+        let edits: Vec<LineEdit> = buffer.line_edits_since(start).unwrap().collect();
+        let edit = |start, removed, inserted| LineEdit {
+            start,
+            removed,
+            inserted,
+        };
+        assert_eq!(edits, [edit(1, 1, 3), edit(0, 3, 1), edit(2, 1, 2)]);
+        assert_eq!(buffer.to_string(), "2b\ntwo\nthree\nfour");
+        assert!(buffer.insert(100, "x").is_err());
+        assert_eq!(buffer.line_edits_since(start).unwrap().count(), 3);
+
+        // Untracked changes, and history that fell out of the log, can't be replayed.
+        let before = buffer.revision();
+        buffer.rope_mut();
+        assert!(buffer.line_edits_since(before).is_none());
+        assert_eq!(
+            buffer.line_edits_since(buffer.revision()).unwrap().count(),
+            0
+        );
+        let before = buffer.revision();
+        for _ in 0..=MAX_LINE_EDITS {
+            buffer.append("x");
+        }
+        assert!(buffer.line_edits_since(before).is_none());
+        assert!(buffer.line_edits_since(before + 1).is_some());
+    }
+
+    #[test]
+    fn test_tagged_styles_split_and_coalesce() {
+        let mut buffer = TextBuffer::with_text("0123456789abcdef");
+        let bold = Style::bold();
+        let hit = Style::bg(Rgba::BLUE);
+        let a = buffer.add_style(2..6, bold, 1);
+        buffer.add_style(6..9, bold, 1);
+        assert_eq!(buffer.tagged_segments(), [(2..9, bold)]);
+
+        let search = buffer.add_style(4..12, hit, 2);
+        assert_eq!(
+            buffer.tagged_segments(),
+            [(2..4, bold), (4..9, bold.merge(hit)), (9..12, hit)]
+        );
+        assert_eq!(buffer.style_at(5), bold.merge(hit));
+
+        // Removing exactly the first style leaves the others untouched.
+        assert!(buffer.remove_style(a));
+        assert!(!buffer.remove_style(a));
+        assert_eq!(
+            buffer.tagged_segments(),
+            [(4..6, hit), (6..9, bold.merge(hit)), (9..12, hit)]
+        );
+
+        buffer.clear_styles_with_tag(1);
+        assert_eq!(buffer.tagged_segments(), [(4..12, hit)]);
+        assert!(buffer.remove_style(search));
+        assert!(buffer.tagged_segments().is_empty());
+        assert_eq!(buffer.style_at(5), Style::NONE);
+    }
+
+    #[test]
+    fn test_tagged_styles_priority() {
+        let mut buffer = TextBuffer::with_text("0123456789");
+        let red = Style::fg(Rgba::RED);
+        let blue = Style::fg(Rgba::BLUE);
+        buffer.add_style(0..4, red, 1);
+        buffer.add_style(2..6, blue, 2);
+        assert_eq!(buffer.style_at(3), blue);
+
+        buffer.set_style_tag_priority(1, 10);
+        assert_eq!(buffer.style_tag_priority(1), 10);
+        assert_eq!(buffer.tagged_segments(), [(0..4, red), (4..6, blue)]);
+    }
+
+    #[test]
+    fn test_tagged_styles_shift_with_edits() {
+        let mut buffer = TextBuffer::with_text("héllo wörld");
+        let bold = Style::bold();
+        let italic = Style::italic();
+        let world = buffer.add_style(6..11, bold, 1);
+        buffer.add_style(0..5, bold, 1);
+        buffer.add_style(5..6, italic, 2);
+
+        buffer.insert(0, "¡").unwrap();
+        assert_eq!(
+            buffer.tagged_segments(),
+            [(1..6, bold), (6..7, italic), (7..12, bold)]
+        );
+        let w = buffer.rope().char_to_byte(7);
+        assert_eq!(buffer.style_at(w), bold);
+
+        // Deleting the italic space brings the bold words together.
+        buffer.remove(6..7).unwrap();
+        assert_eq!(buffer.tagged_segments(), [(1..11, bold)]);
+
+        // Text typed inside a style extends it, at its edges it does not.
+        buffer.insert(3, "xx").unwrap();
+        buffer.insert(1, "<").unwrap();
+        assert_eq!(buffer.tagged_segments(), [(2..14, bold)]);
+
+        buffer.remove(7..14).unwrap();
+        assert_eq!(buffer.tagged_segments(), [(2..7, bold)]);
+        assert!(!buffer.remove_style(world));
+
+        buffer.set_text("new");
+        assert!(buffer.tagged_segments().is_empty());
+    }
+
     #[test]
     fn test_lines_iter() {
         let buffer = TextBuffer::with_text("Line 1\nLine 2");
@@ -27,7 +27,7 @@
 // if-let-else is clearer than map_or for complex logic
 #![allow(clippy::option_if_let_else)]
 
-use crate::highlight::HighlightedBuffer;
+use crate::highlight::{HighlightedBuffer, TokenKind};
 use crate::text::TextBuffer;
 
 /// Cursor position in the buffer.
@@ -63,6 +63,46 @@ pub struct CursorPosition {
     pub offset: usize,
 }
 
+/// Brackets and quotes [`EditBuffer::type_char`] completes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairConfig {
+    /// `(open, close)` pairs. A quote is its own close.
+    pub pairs: Vec<(char, char)>,
+    /// Token kinds inside which nothing is paired, when the buffer has a
+    /// tokenizer.
+    pub disabled_in: Vec<TokenKind>,
+}
+
+impl Default for PairConfig {
+    /// `()`, `[]`, `{}`, and double and single quotes, off inside strings
+    /// and comments.
+    fn default() -> Self {
+        Self {
+            pairs: vec![('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')],
+            disabled_in: vec![
+                TokenKind::String,
+                TokenKind::StringEscape,
+                TokenKind::Comment,
+                TokenKind::CommentBlock,
+                TokenKind::CommentDoc,
+            ],
+        }
+    }
+}
+
+impl PairConfig {
+    fn close_for(&self, open: char) -> Option<char> {
+        self.pairs
+            .iter()
+            .find(|&&(pair_open, _)| pair_open == open)
+            .map(|&(_, close)| close)
+    }
+
+    fn is_close(&self, ch: char) -> bool {
+        self.pairs.iter().any(|&(_, close)| close == ch)
+    }
+}
+
 /// An edit operation for undo/redo.
 #[derive(Clone, Debug)]
 enum EditOp {
@@ -187,6 +227,10 @@ pub struct EditBuffer {
     buffer: HighlightedBuffer,
     cursor: Cursor,
     history: History,
+    auto_pairs: Option<PairConfig>,
+    /// Offsets of closes inserted by auto-pairing that typing the close
+    /// steps over, innermost last.
+    pending_closes: Vec<usize>,
 }
 
 impl EditBuffer {
@@ -203,6 +247,8 @@ impl EditBuffer {
             buffer: HighlightedBuffer::new(TextBuffer::with_text(text)),
             cursor: Cursor::start(),
             history: History::new(),
+            auto_pairs: None,
+            pending_closes: Vec::new(),
         }
     }
 
@@ -216,6 +262,8 @@ impl EditBuffer {
             buffer: HighlightedBuffer::new(TextBuffer::new()),
             cursor: Cursor::start(),
             history: History::with_max_depth(max_depth),
+            auto_pairs: None,
+            pending_closes: Vec::new(),
         }
     }
 
@@ -361,7 +409,9 @@ impl EditBuffer {
     /// Insert text at cursor.
     pub fn insert(&mut self, text: &str) {
         let offset = self.cursor.offset;
-        self.buffer.rope_mut().insert(offset, text);
+        if self.buffer.insert(offset, text).is_err() {
+            return;
+        }
         let line_delta = text.chars().filter(|&ch| ch == '\n').count();
         let start_row = self.cursor.row;
         let end_row = start_row.saturating_add(line_delta + 1);
@@ -375,8 +425,155 @@ impl EditBuffer {
         self.update_cursor_position();
     }
 
+    /// Turn bracket and quote auto-pairing for
+    /// [`type_char`](Self::type_char) on, or off with `None`.
+    pub fn set_auto_pairs(&mut self, config: Option<PairConfig>) {
+        self.auto_pairs = config;
+        self.pending_closes.clear();
+    }
+
+    /// Current auto-pairing configuration, if on.
+    #[must_use]
+    pub fn auto_pairs(&self) -> Option<&PairConfig> {
+        self.auto_pairs.as_ref()
+    }
+
+    /// Insert a typed character at the cursor.
+    ///
+    /// With [auto-pairing](Self::set_auto_pairs) on, an opening character
+    /// also gets its close after the cursor, in one undo step, unless a word
+    /// follows; quotes aren't paired after a letter or digit either. Until the cursor is moved or the text edited otherwise, typing
+    /// a close just before one inserted this way steps over it, and
+    /// [`delete_backward`](Self::delete_backward) between a fresh pair
+    /// deletes both.
+    pub fn type_char(&mut self, ch: char) {
+        let mut closes = std::mem::take(&mut self.pending_closes);
+        let offset = self.cursor.offset;
+        let steps_over = closes.last() == Some(&offset)
+            && self.char_at(offset) == Some(ch)
+            && self
+                .auto_pairs
+                .as_ref()
+                .is_some_and(|config| config.is_close(ch));
+        if steps_over {
+            closes.pop();
+            self.move_right();
+            self.pending_closes = closes;
+            return;
+        }
+
+        if self.buffer.has_tokenizer() {
+            self.buffer.update_highlighting();
+        }
+        let close = self
+            .auto_pairs
+            .as_ref()
+            .and_then(|config| config.close_for(ch))
+            .filter(|&close| self.pairs_at_cursor(ch, close));
+        let mut text = String::from(ch);
+        text.extend(close);
+        self.insert(&text);
+
+        let inserted = text.chars().count();
+        for pending in &mut closes {
+            if *pending >= offset {
+                *pending += inserted;
+            }
+        }
+        if close.is_some() {
+            self.set_cursor_by_offset(offset + 1);
+            closes.push(offset + 1);
+        }
+        self.pending_closes = closes;
+    }
+
+    /// Whether typing `open` at the cursor should insert `close` too.
+    fn pairs_at_cursor(&self, open: char, close: char) -> bool {
+        let Some(config) = &self.auto_pairs else {
+            return false;
+        };
+        let offset = self.cursor.offset;
+        let before_word = self
+            .char_at(offset)
+            .is_some_and(|next| next.is_alphanumeric() || next == '_');
+        let after_word = offset
+            .checked_sub(1)
+            .and_then(|before| self.char_at(before))
+            .is_some_and(char::is_alphanumeric);
+        let quote_after_word = open == close && after_word;
+        !before_word && !quote_after_word && !self.in_token(offset, &config.disabled_in)
+    }
+
+    /// Whether `offset` is inside a token of one of `kinds`: after its
+    /// first char and before its end, or anywhere after its start for line
+    /// comments, which run to the end of the line.
+    fn in_token(&self, offset: usize, kinds: &[TokenKind]) -> bool {
+        if !self.buffer.has_tokenizer() {
+            return false;
+        }
+        let Some(before) = offset.checked_sub(1) else {
+            return false;
+        };
+        self.buffer.tokens_in_range(before..before).any(|token| {
+            kinds.contains(&token.kind)
+                && (offset < token.char_end
+                    || matches!(token.kind, TokenKind::Comment | TokenKind::CommentDoc))
+        })
+    }
+
+    fn char_at(&self, offset: usize) -> Option<char> {
+        self.buffer.rope().inner().get_char(offset)
+    }
+
+    /// Wrap chars `start..end` in `open` and `close` as one undo step,
+    /// leaving the cursor before `close`.
+    pub fn surround_range(&mut self, start: usize, end: usize, open: &str, close: &str) {
+        let len = self.buffer.len_chars();
+        let (start, end) = (start.min(end).min(len), start.max(end).min(len));
+        self.history.commit();
+        self.set_cursor_by_offset(end);
+        self.insert(close);
+        self.set_cursor_by_offset(start);
+        self.insert(open);
+        self.history.commit();
+        self.set_cursor_by_offset(end + open.chars().count());
+    }
+
     /// Delete character before cursor.
+    ///
+    /// Between a fresh pair from [`type_char`](Self::type_char), deletes the
+    /// close too.
     pub fn delete_backward(&mut self) {
+        let mut closes = std::mem::take(&mut self.pending_closes);
+        let offset = self.cursor.offset;
+        if offset == 0 {
+            self.pending_closes = closes;
+            return;
+        }
+        let fresh_pair = closes.last() == Some(&offset)
+            && self.auto_pairs.as_ref().is_some_and(|config| {
+                self.char_at(offset - 1)
+                    .zip(self.char_at(offset))
+                    .is_some_and(|pair| config.pairs.contains(&pair))
+            });
+        let removed = if fresh_pair {
+            closes.pop();
+            self.delete_range_offsets(offset - 1, offset + 1);
+            2
+        } else {
+            self.delete_char_before();
+            1
+        };
+        closes.retain(|&pending| pending != offset - 1);
+        for pending in &mut closes {
+            if *pending >= offset {
+                *pending -= removed;
+            }
+        }
+        self.pending_closes = closes;
+    }
+
+    fn delete_char_before(&mut self) {
         if self.cursor.offset == 0 {
             return;
         }
@@ -388,7 +585,9 @@ impl EditBuffer {
             .slice(start..self.cursor.offset)
             .to_string();
 
-        self.buffer.rope_mut().remove(start..self.cursor.offset);
+        if self.buffer.remove(start..self.cursor.offset).is_err() {
+            return;
+        }
         self.buffer
             .mark_dirty(self.cursor.row.saturating_sub(1), self.cursor.row + 1); // might affect prev line
         self.history.push(EditOp::Delete {
@@ -413,7 +612,9 @@ impl EditBuffer {
             .slice(self.cursor.offset..end)
             .to_string();
 
-        self.buffer.rope_mut().remove(self.cursor.offset..end);
+        if self.buffer.remove(self.cursor.offset..end).is_err() {
+            return;
+        }
         let start_row = self.cursor.row;
         let end_row = if deleted.contains('\n') {
             start_row.saturating_add(2)
@@ -449,7 +650,9 @@ impl EditBuffer {
             let deleted = rope.slice(start..end).to_string();
             (start_row, end_row, deleted)
         };
-        self.buffer.rope_mut().remove(start..end);
+        if self.buffer.remove(start..end).is_err() {
+            return;
+        }
 
         self.buffer.mark_dirty(start_row, end_row.saturating_add(1));
 
@@ -488,7 +691,9 @@ impl EditBuffer {
                 format!("\n{line_text}")
             };
 
-            self.buffer.rope_mut().insert(insert_pos, &text_to_insert);
+            if self.buffer.insert(insert_pos, &text_to_insert).is_err() {
+                return;
+            }
             self.buffer.mark_dirty(self.cursor.row, self.cursor.row + 2);
 
             self.history.push(EditOp::Insert {
@@ -532,7 +737,9 @@ impl EditBuffer {
                 format!("{current_text}\n{prev_text}")
             };
 
-            self.buffer.rope_mut().insert(prev_line_start, &new_text);
+            if self.buffer.insert(prev_line_start, &new_text).is_err() {
+                return;
+            }
             self.buffer.mark_dirty(target_row, target_row + 2);
 
             self.history.push(EditOp::Insert {
@@ -580,7 +787,9 @@ impl EditBuffer {
                 format!("{next_text}\n{current_text}")
             };
 
-            self.buffer.rope_mut().insert(current_line_start, &new_text);
+            if self.buffer.insert(current_line_start, &new_text).is_err() {
+                return;
+            }
             self.buffer.mark_dirty(self.cursor.row, self.cursor.row + 2);
 
             self.history.push(EditOp::Insert {
@@ -755,7 +964,9 @@ impl EditBuffer {
     fn apply_op(&mut self, op: &EditOp) {
         match op {
             EditOp::Insert { offset, text } => {
-                self.buffer.rope_mut().insert(*offset, text);
+                if self.buffer.insert(*offset, text).is_err() {
+                    return;
+                }
 
                 let row = self.buffer.rope().char_to_line(*offset);
                 let line_delta = text.chars().filter(|&ch| ch == '\n').count();
@@ -772,7 +983,9 @@ impl EditBuffer {
                     let end_row = rope.char_to_line(end.saturating_sub(1));
                     (start_row, end_row)
                 };
-                self.buffer.rope_mut().remove(*offset..end);
+                if self.buffer.remove(*offset..end).is_err() {
+                    return;
+                }
                 self.buffer.mark_dirty(start_row, end_row.saturating_add(1));
 
                 self.cursor.offset = *offset;
@@ -782,6 +995,7 @@ impl EditBuffer {
     }
 
     fn update_cursor_position(&mut self) {
+        self.pending_closes.clear();
         let rope = self.buffer.rope();
         self.cursor.row = rope
             .inner()
@@ -791,6 +1005,7 @@ impl EditBuffer {
     }
 
     fn update_cursor_from_row_col(&mut self) {
+        self.pending_closes.clear();
         let rope = self.buffer.rope();
         let line_start = rope.line_to_char(self.cursor.row);
 
@@ -865,6 +1080,23 @@ mod tests {
         assert_eq!(edit.text(), "Hello");
     }
 
+    #[test]
+    fn test_edits_shift_diagnostics() {
+        use crate::highlight::{Diagnostic, DiagnosticSeverity};
+
+        let mut edit = EditBuffer::with_text("let x = bad;");
+        edit.highlighted_buffer_mut()
+            .set_diagnostics(vec![Diagnostic::new(8..11, DiagnosticSeverity::Error, "e")]);
+
+        edit.move_to(0, 0);
+        edit.insert("// ");
+        edit.commit();
+        assert_eq!(edit.highlighted_buffer().diagnostics()[0].range, 11..14);
+
+        edit.undo();
+        assert_eq!(edit.highlighted_buffer().diagnostics()[0].range, 8..11);
+    }
+
     #[test]
     fn test_cursor_movement() {
         let mut edit = EditBuffer::with_text("Line 1\nLine 2");
@@ -1992,4 +2224,154 @@ mod tests {
 
         assert_eq!(edit.text(), "Line 1Line 2");
     }
+
+    // --- Auto-pairing ---
+
+    fn type_str(edit: &mut EditBuffer, text: &str) {
+        for ch in text.chars() {
+            edit.type_char(ch);
+        }
+    }
+
+    /// The text with `|` at the cursor.
+    fn with_cursor(edit: &EditBuffer) -> String {
+        let mut text = edit.text();
+        let byte = text
+            .char_indices()
+            .nth(edit.cursor().offset)
+            .map_or(text.len(), |(byte, _)| byte);
+        text.insert(byte, '|');
+        text
+    }
+
+    #[test]
+    fn test_auto_pairs_nest_and_step_over() {
+        let mut edit = paired("");
+        type_str(&mut edit, "(");
+        assert_eq!(with_cursor(&edit), "(|)");
+
+        type_str(&mut edit, "[{\"");
+        assert_eq!(with_cursor(&edit), "([{\"|\"}])");
+        type_str(&mut edit, "hi\"}");
+        assert_eq!(with_cursor(&edit), "([{\"hi\"}|])");
+        type_str(&mut edit, "])");
+        assert_eq!(with_cursor(&edit), "([{\"hi\"}])|");
+        // With nothing left to step over, a close is typed.
+        type_str(&mut edit, ")");
+        assert_eq!(with_cursor(&edit), "([{\"hi\"}]))|");
+
+        // Not before a word, and quotes not after one.
+        let mut edit = paired("word");
+        type_str(&mut edit, "(");
+        assert_eq!(with_cursor(&edit), "(|word");
+        edit.move_to_line_end();
+        type_str(&mut edit, "'s ");
+        assert_eq!(with_cursor(&edit), "(word's |");
+
+        // Off by default.
+        let mut edit = EditBuffer::new();
+        type_str(&mut edit, "(");
+        assert_eq!(with_cursor(&edit), "(|");
+    }
+
+    fn paired(text: &str) -> EditBuffer {
+        let mut edit = EditBuffer::with_text(text);
+        edit.set_auto_pairs(Some(PairConfig::default()));
+        edit
+    }
+
+    #[test]
+    fn test_step_over_expires_after_cursor_moves() {
+        let mut edit = paired("");
+        type_str(&mut edit, "f(x");
+        edit.move_left();
+        edit.move_right();
+        type_str(&mut edit, ")");
+        assert_eq!(with_cursor(&edit), "f(x)|)");
+
+        // Other edits end it too.
+        let mut edit = paired("");
+        type_str(&mut edit, "(");
+        edit.insert("a");
+        type_str(&mut edit, ")");
+        assert_eq!(with_cursor(&edit), "(a)|)");
+    }
+
+    #[test]
+    fn test_backspace_deletes_fresh_pair() {
+        let mut edit = paired("");
+        type_str(&mut edit, "((ab");
+        edit.delete_backward();
+        edit.delete_backward();
+        assert_eq!(with_cursor(&edit), "((|))");
+        edit.delete_backward();
+        assert_eq!(with_cursor(&edit), "(|)");
+        type_str(&mut edit, ")");
+        assert_eq!(with_cursor(&edit), "()|");
+
+        // Once the pair is no longer fresh, only the open goes.
+        let mut edit = paired("");
+        type_str(&mut edit, "[");
+        edit.move_right();
+        edit.move_left();
+        edit.delete_backward();
+        assert_eq!(with_cursor(&edit), "|]");
+    }
+
+    #[test]
+    fn test_pair_insertion_undoes_in_one_step() {
+        let mut edit = paired("x = ");
+        edit.move_to_line_end();
+        edit.commit();
+        type_str(&mut edit, "{");
+        assert_eq!(edit.text(), "x = {}");
+        assert!(edit.undo());
+        assert_eq!(with_cursor(&edit), "x = |");
+        assert!(edit.redo());
+        assert_eq!(edit.text(), "x = {}");
+
+        edit.set_cursor_by_offset(5);
+        edit.commit();
+        type_str(&mut edit, "(");
+        edit.commit();
+        edit.delete_backward();
+        assert_eq!(edit.text(), "x = {}");
+        assert!(edit.undo());
+        assert_eq!(edit.text(), "x = {()}");
+    }
+
+    #[test]
+    fn test_no_pairs_inside_strings_and_comments() {
+        let registry = crate::highlight::TokenizerRegistry::with_builtins();
+        let mut edit = paired("let s = \"ab\"; // note\nx");
+        edit.highlighted_buffer_mut()
+            .set_tokenizer(registry.by_name_shared("rust"));
+
+        edit.set_cursor_by_offset(10);
+        type_str(&mut edit, "(");
+        assert_eq!(with_cursor(&edit), "let s = \"a(|b\"; // note\nx");
+        edit.move_to_line_end();
+        type_str(&mut edit, " [");
+        assert_eq!(with_cursor(&edit), "let s = \"a(b\"; // note [|\nx");
+
+        // After a string and outside comments, pairing is back.
+        edit.set_cursor_by_offset(13);
+        type_str(&mut edit, "(");
+        assert_eq!(with_cursor(&edit), "let s = \"a(b\"(|); // note [\nx");
+        edit.move_to(1, 1);
+        type_str(&mut edit, " {");
+        assert_eq!(with_cursor(&edit), "let s = \"a(b\"(); // note [\nx {|}");
+    }
+
+    #[test]
+    fn test_surround_range_across_lines() {
+        let mut edit = EditBuffer::with_text("one\ntwo\nthree");
+        edit.commit();
+        edit.surround_range(8, 2, "<<", ">>");
+        assert_eq!(with_cursor(&edit), "on<<e\ntwo\n|>>three");
+        assert_eq!(edit.cursor().row, 2);
+        assert!(edit.undo());
+        assert_eq!(edit.text(), "one\ntwo\nthree");
+        assert!(!edit.can_undo());
+    }
 }
@@ -5,10 +5,14 @@
 
 use crate::buffer::OptimizedBuffer;
 use crate::color::Rgba;
+use crate::highlight::bracket::{DEFAULT_BRACKET_SCAN_LIMIT, bracket_match_within};
+use crate::highlight::layer::{BRACKET_MATCH_LAYER, SEARCH_LAYER, SELECTION_LAYER};
 use crate::highlight::theme::Theme;
 use crate::highlight::tokenizer::TokenizerRegistry;
 use crate::style::Style;
-use crate::text::view::{LocalSelection, Selection, Viewport};
+use crate::terminal::{MouseButton, MouseEvent, MouseEventKind};
+use crate::text::layout::{LayoutCache, LayoutStats};
+use crate::text::view::{LocalSelection, Selection, Viewport, VirtualLine, wrap_line};
 use crate::text::{EditBuffer, TextBufferView, WrapMode};
 
 /// Cursor style for rendering.
@@ -33,14 +37,29 @@ pub struct VisualCursor {
     pub offset: u32,
 }
 
-/// A virtual line segment for visual navigation with wrapped text.
-#[derive(Clone, Debug)]
-struct VirtualLine {
-    source_line: usize,
-    byte_start: usize,
-    byte_end: usize,
-    width: usize,
-    is_wrap: bool,
+/// Lines scrolled per mouse wheel notch by default.
+pub const DEFAULT_MOUSE_SCROLL_LINES: u32 = 3;
+
+/// What [`EditorView::handle_mouse`] did with an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorMouseAction {
+    /// The event was outside the editor or not one it handles.
+    Ignored,
+    /// A click moved the cursor and cleared the selection.
+    MovedCursor,
+    /// A shift-click, drag, or gutter click changed the selection.
+    Selected,
+    /// The wheel scrolled the view.
+    Scrolled,
+}
+
+/// Where a left-button drag started.
+#[derive(Clone, Copy, Debug)]
+enum DragAnchor {
+    /// Char offset of a click in the text.
+    Char(usize),
+    /// Line of a click in the gutter; the drag selects whole lines.
+    Line(usize),
 }
 
 /// Editor view wrapping an EditBuffer with visual rendering.
@@ -49,6 +68,9 @@ pub struct EditorView {
     cursor_style: Style,
     cursor_shape: CursorShape,
     selection_style: Style,
+    search_style: Style,
+    bracket_match_style: Option<Style>,
+    bracket_scan_limit: usize,
     wrap_mode: WrapMode,
     scroll_x: u32,
     scroll_y: u32,
@@ -59,6 +81,11 @@ pub struct EditorView {
     selection_follow_cursor: bool,
     selection: Option<Selection>,
     local_selection: Option<LocalSelection>,
+    layout: LayoutCache,
+    // Area of the last render, for mapping mouse events
+    render_area: Option<Viewport>,
+    drag: Option<DragAnchor>,
+    mouse_scroll_lines: u32,
 }
 
 impl EditorView {
@@ -70,6 +97,14 @@ impl EditorView {
             cursor_style: Style::builder().inverse().build(),
             cursor_shape: CursorShape::Block,
             selection_style: Style::builder().bg(Rgba::from_rgb_u8(60, 60, 120)).build(),
+            search_style: Style::builder().bg(Rgba::from_rgb_u8(110, 90, 20)).build(),
+            bracket_match_style: Some(
+                Style::builder()
+                    .bg(Rgba::from_rgb_u8(70, 70, 70))
+                    .bold()
+                    .build(),
+            ),
+            bracket_scan_limit: DEFAULT_BRACKET_SCAN_LIMIT,
             wrap_mode: WrapMode::None,
             scroll_x: 0,
             scroll_y: 0,
@@ -80,6 +115,10 @@ impl EditorView {
             selection_follow_cursor: false,
             selection: None,
             local_selection: None,
+            layout: LayoutCache::default(),
+            render_area: None,
+            drag: None,
+            mouse_scroll_lines: DEFAULT_MOUSE_SCROLL_LINES,
         }
     }
 
@@ -135,6 +174,11 @@ impl EditorView {
         self.selection_follow_cursor = enabled;
     }
 
+    /// Set how many rows (or columns) one mouse wheel notch scrolls.
+    pub fn set_mouse_scroll_lines(&mut self, lines: u32) {
+        self.mouse_scroll_lines = lines;
+    }
+
     /// Enable or disable line numbers.
     pub fn set_line_numbers(&mut self, enabled: bool) {
         self.line_numbers = enabled;
@@ -161,6 +205,42 @@ impl EditorView {
         }
     }
 
+    /// Enable syntax highlighting for a language, looked up by tokenizer
+    /// name or alias first and file extension second.
+    ///
+    /// Returns `false`, leaving highlighting unchanged, if the registry has
+    /// no tokenizer for `language`.
+    pub fn enable_highlighting(&mut self, registry: &TokenizerRegistry, language: &str) -> bool {
+        let tokenizer = registry
+            .by_name_shared(language)
+            .or_else(|| registry.for_extension_shared(language));
+        let Some(tokenizer) = tokenizer else {
+            return false;
+        };
+        self.edit_buffer
+            .highlighted_buffer_mut()
+            .set_tokenizer(Some(tokenizer));
+        true
+    }
+
+    /// Highlight the buffer as `language` in `theme` (builder pattern).
+    ///
+    /// Edits retokenize only the lines they touch on the next render, and
+    /// [`set_highlighting_theme`](Self::set_highlighting_theme) restyles
+    /// without retokenizing. Selection and search draw over the token
+    /// styles. An unknown language leaves the text unhighlighted.
+    #[must_use]
+    pub fn with_highlighting(
+        mut self,
+        language: &str,
+        theme: &Theme,
+        registry: &TokenizerRegistry,
+    ) -> Self {
+        self.enable_highlighting(registry, language);
+        self.set_highlighting_theme(theme.clone());
+        self
+    }
+
     /// Disable syntax highlighting.
     pub fn disable_highlighting(&mut self) {
         self.edit_buffer
@@ -174,6 +254,60 @@ impl EditorView {
         self.edit_buffer.highlighted_buffer_mut().set_theme(theme);
     }
 
+    /// Set the style for the bracket pair at the cursor, or `None` to turn
+    /// bracket matching off.
+    pub fn set_bracket_match_style(&mut self, style: Option<Style>) {
+        self.bracket_match_style = style;
+    }
+
+    /// Limit how many chars bracket matching scans for the partner bracket.
+    pub fn set_bracket_scan_limit(&mut self, limit: usize) {
+        self.bracket_scan_limit = limit;
+    }
+
+    /// Set the style used for search matches.
+    ///
+    /// Search matches only override the background and attributes, keeping
+    /// syntax colors; the selection draws over them.
+    pub fn set_search_style(&mut self, style: Style) {
+        self.search_style = style;
+        let matches = self.search_matches();
+        self.set_search_matches(matches);
+    }
+
+    /// Highlight search matches, as char offset ranges.
+    ///
+    /// Matches live in the buffer's search layer and follow later edits.
+    pub fn set_search_matches(&mut self, matches: Vec<std::ops::Range<usize>>) {
+        let style = self.search_style;
+        self.edit_buffer.highlighted_buffer_mut().set_layer_ranges(
+            SEARCH_LAYER,
+            matches.into_iter().map(|range| (range, style)).collect(),
+        );
+    }
+
+    /// Clear search match highlights.
+    pub fn clear_search_matches(&mut self) {
+        self.set_search_matches(Vec::new());
+    }
+
+    /// Current search matches, adjusted for edits made since they were set.
+    #[must_use]
+    pub fn search_matches(&self) -> Vec<std::ops::Range<usize>> {
+        self.edit_buffer
+            .highlighted_buffer()
+            .layers()
+            .get(SEARCH_LAYER)
+            .map(|layer| {
+                layer
+                    .ranges()
+                    .iter()
+                    .map(|(range, _)| range.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Set selection range by character offsets.
     pub fn set_selection(&mut self, start: usize, end: usize) {
         self.selection = Some(Selection::new(start, end, self.selection_style));
@@ -251,6 +385,54 @@ impl EditorView {
         )
     }
 
+    /// Wrap the selection in `open` and `close` as one undo step, keeping
+    /// the wrapped text selected.
+    ///
+    /// Returns `false`, changing nothing, without a non-empty selection.
+    pub fn surround_selection(&mut self, open: &str, close: &str) -> bool {
+        let Some(sel) = self.selection else {
+            return false;
+        };
+        if sel.start == sel.end {
+            return false;
+        }
+        self.edit_buffer
+            .surround_range(sel.start, sel.end, open, close);
+        let shift = open.chars().count();
+        self.selection = Some(Selection::new(
+            sel.start + shift,
+            sel.end + shift,
+            self.selection_style,
+        ));
+        true
+    }
+
+    /// Fold `lines`, hiding them from the view; a fold that overlaps or
+    /// touches another joins it.
+    ///
+    /// Folded lines take no rows, so rendering, scrolling, line numbers, and
+    /// the screen mappings all skip them. An edit to a folded line opens
+    /// its fold; edits elsewhere move folds with the text. Cursor movement
+    /// still steps through folded lines.
+    pub fn fold_lines(&mut self, lines: std::ops::Range<usize>) {
+        self.layout.fold(self.edit_buffer.buffer(), lines);
+    }
+
+    /// Open every fold that overlaps `lines`.
+    pub fn unfold_lines(&mut self, lines: std::ops::Range<usize>) {
+        self.layout.unfold(self.edit_buffer.buffer(), lines);
+    }
+
+    /// Open every fold.
+    pub fn clear_folds(&mut self) {
+        self.layout.unfold(self.edit_buffer.buffer(), 0..usize::MAX);
+    }
+
+    /// Folded line ranges, in order.
+    pub fn folds(&mut self) -> Vec<std::ops::Range<usize>> {
+        self.layout.folds(self.edit_buffer.buffer()).to_vec()
+    }
+
     /// Scroll to make cursor visible.
     pub fn scroll_to_cursor(&mut self, viewport_width: u32, viewport_height: u32) {
         let cursor = self.edit_buffer.cursor();
@@ -264,13 +446,19 @@ impl EditorView {
         let margin_rows = (viewport_height as f32 * self.scroll_margin).ceil() as u32;
         let margin_cols = (text_width as f32 * self.scroll_margin).ceil() as u32;
 
-        let (visual_row, visual_col) = if self.wrap_mode == WrapMode::None {
-            (cursor.row as u32, cursor.col as u32)
+        // Rows come from the layout so folds count; a folded cursor scrolls
+        // to where its fold would be.
+        let buffer = self.edit_buffer.buffer();
+        self.layout
+            .sync(buffer, self.wrap_mode, self.wrap_width(text_width));
+        let position = self.layout.position_of(buffer, cursor.offset);
+        let visual_row =
+            position.map_or_else(|| self.layout.first_row_of(cursor.row), |(row, _)| row);
+        let visual_row = u32::try_from(visual_row).unwrap_or(u32::MAX);
+        let visual_col = if self.wrap_mode.wraps() {
+            position.map_or(0, |(_, col)| col as u32)
         } else {
-            let view = TextBufferView::new(self.edit_buffer.buffer())
-                .viewport(0, 0, text_width, viewport_height)
-                .wrap_mode(self.wrap_mode);
-            view.visual_position_for_offset(cursor.offset)
+            cursor.col as u32
         };
 
         // Vertical scrolling
@@ -281,16 +469,21 @@ impl EditorView {
                 visual_row.saturating_sub(viewport_height.saturating_sub(margin_rows + 1));
         }
 
-        // Horizontal scrolling (if not wrapping)
-        if self.wrap_mode == WrapMode::None {
+        // Horizontal scrolling (if not wrapping), keeping the cursor off a
+        // truncation ellipsis
+        if self.wrap_mode.wraps() {
+            self.scroll_x = 0;
+        } else {
+            let text_width = match self.wrap_mode {
+                WrapMode::Truncate { ellipsis: true } => text_width.saturating_sub(1),
+                _ => text_width,
+            };
             if visual_col < self.scroll_x + margin_cols {
                 self.scroll_x = visual_col.saturating_sub(margin_cols);
             } else if visual_col >= self.scroll_x + text_width.saturating_sub(margin_cols) {
                 self.scroll_x =
                     visual_col.saturating_sub(text_width.saturating_sub(margin_cols + 1));
             }
-        } else {
-            self.scroll_x = 0;
         }
 
         if self.selection_follow_cursor {
@@ -317,7 +510,7 @@ impl EditorView {
     /// In wrapped mode, this moves up within a wrapped line segment.
     /// In non-wrapped mode, this is equivalent to move_up().
     pub fn move_up_visual(&mut self, viewport_width: u32, viewport_height: u32) {
-        if self.wrap_mode == WrapMode::None {
+        if !self.wrap_mode.wraps() {
             self.edit_buffer.move_up();
             return;
         }
@@ -354,7 +547,7 @@ impl EditorView {
     /// In wrapped mode, this moves down within a wrapped line segment.
     /// In non-wrapped mode, this is equivalent to move_down().
     pub fn move_down_visual(&mut self, viewport_width: u32, viewport_height: u32) {
-        if self.wrap_mode == WrapMode::None {
+        if !self.wrap_mode.wraps() {
             self.edit_buffer.move_down();
             return;
         }
@@ -392,7 +585,7 @@ impl EditorView {
     /// In non-wrapped mode, returns the start of the logical line.
     #[must_use]
     pub fn get_visual_sol(&self, viewport_width: u32, viewport_height: u32) -> usize {
-        if self.wrap_mode == WrapMode::None {
+        if !self.wrap_mode.wraps() {
             let cursor = self.edit_buffer.cursor();
             return self.edit_buffer.buffer().rope().line_to_char(cursor.row);
         }
@@ -426,7 +619,7 @@ impl EditorView {
     /// In non-wrapped mode, returns the end of the logical line (before newline).
     #[must_use]
     pub fn get_visual_eol(&self, viewport_width: u32, viewport_height: u32) -> usize {
-        if self.wrap_mode == WrapMode::None {
+        if !self.wrap_mode.wraps() {
             return self.edit_buffer.get_eol();
         }
 
@@ -478,10 +671,10 @@ impl EditorView {
         let view = TextBufferView::new(self.edit_buffer.buffer())
             .viewport(0, 0, text_width, viewport_height)
             .wrap_mode(self.wrap_mode);
-        let (visual_row, visual_col) = if self.wrap_mode == WrapMode::None {
-            (cursor.row as u32, cursor.col as u32)
-        } else {
+        let (visual_row, visual_col) = if self.wrap_mode.wraps() {
             view.visual_position_for_offset(cursor.offset)
+        } else {
+            (cursor.row as u32, cursor.col as u32)
         };
 
         VisualCursor {
@@ -505,136 +698,304 @@ impl EditorView {
         digits + 2 // digit count + padding
     }
 
-    /// Build virtual line information for visual navigation.
-    #[allow(clippy::too_many_lines)]
-    fn build_virtual_lines(&self, text_width: u32, _viewport_height: u32) -> Vec<VirtualLine> {
-        use unicode_segmentation::UnicodeSegmentation;
-
-        let mut lines = Vec::new();
+    /// Buffer position shown at `(x, y)`, relative to the editor's top-left
+    /// corner (the gutter's, with line numbers on), as `(line, col)` in
+    /// chars.
+    ///
+    /// Uses the area of the last [`render_to`](Self::render_to), or the
+    /// [viewport](Self::set_viewport), and accounts for the gutter, scroll,
+    /// wrapping, [folds](Self::fold_lines), tabs, and wide chars. A cell
+    /// inside a tab or wide char maps to the position before it, a cell past
+    /// the end of a row to the row's end, a cell in the gutter to the start
+    /// of the row, and a cell below the text to the end of the buffer.
+    /// Returns `None` before the first render or outside the area.
+    pub fn screen_to_buffer(&mut self, x: u32, y: u32) -> Option<(usize, usize)> {
+        let area = self.mouse_area()?;
+        if x >= area.width || y >= area.height {
+            return None;
+        }
+        let offset = self.offset_from_view(area, x, y);
         let rope = self.edit_buffer.buffer().rope();
-        let method = self.edit_buffer.buffer().width_method();
-        let tab_width = self.edit_buffer.buffer().tab_width().max(1) as usize;
-        let wrap_width = if self.wrap_mode != WrapMode::None && text_width > 0 {
-            Some(text_width as usize)
+        let line = rope.char_to_line(offset);
+        Some((line, offset - rope.line_to_char(line)))
+    }
+
+    /// Buffer position shown at `(x, y)`, as `(line, col)` in chars.
+    #[deprecated(note = "renamed to `screen_to_buffer`")]
+    pub fn position_from_view(&mut self, x: u32, y: u32) -> Option<(usize, usize)> {
+        self.screen_to_buffer(x, y)
+    }
+
+    /// Cell showing buffer position `(line, col)`, in chars, relative to the
+    /// editor's top-left corner like [`screen_to_buffer`](Self::screen_to_buffer).
+    ///
+    /// A position past the end of a line or the buffer is clamped to it; a
+    /// tab or wide char at the position maps to its first cell. Uses the
+    /// same layout as [`render_to`](Self::render_to), so the cell is
+    /// where the cursor would be drawn at that position. Returns `None`
+    /// before the first render or when the position is scrolled out of view
+    /// or folded.
+    pub fn buffer_to_screen(&mut self, line: usize, col: usize) -> Option<(u32, u32)> {
+        let area = self.mouse_area()?;
+        let buffer = self.edit_buffer.buffer();
+        let offset = if let Some(text) = buffer.line(line) {
+            let len = text.trim_end_matches(['\n', '\r']).chars().count();
+            buffer.rope().line_to_char(line) + col.min(len)
         } else {
-            None
+            buffer.len_chars()
         };
+        self.screen_cell(area, offset)
+    }
 
-        for line_idx in 0..self.edit_buffer.buffer().len_lines() {
-            let Some(line) = self.edit_buffer.buffer().line(line_idx) else {
-                continue;
-            };
-            let line = line.trim_end_matches('\n').trim_end_matches('\r');
-
-            let line_start_char = rope.line_to_char(line_idx);
-            let line_start_byte = rope.char_to_byte(line_start_char);
+    /// Cell the cursor is drawn in, relative to the editor's top-left
+    /// corner, for anchoring popups such as completion menus.
+    ///
+    /// Returns `None` before the first render or when the cursor is
+    /// scrolled out of view or folded.
+    pub fn cursor_screen_position(&mut self) -> Option<(u32, u32)> {
+        let area = self.mouse_area()?;
+        let offset = self.edit_buffer.cursor().offset;
+        self.screen_cell(area, offset)
+    }
 
-            if line.is_empty() {
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte,
-                    byte_end: line_start_byte,
-                    width: 0,
-                    is_wrap: false,
-                });
-                continue;
+    /// Apply a mouse event in screen coordinates.
+    ///
+    /// - A left click in the text moves the cursor there; with shift held it
+    ///   extends the selection (or starts one at the cursor) instead.
+    /// - Dragging with the left button selects from the click to the pointer,
+    ///   following it outside the editor.
+    /// - A click in the gutter selects the line, and dragging from there
+    ///   selects whole lines.
+    /// - The wheel scrolls by [`set_mouse_scroll_lines`](Self::set_mouse_scroll_lines)
+    ///   rows, or columns when not wrapping.
+    ///
+    /// Multi-click word selection needs click counting, which
+    /// [`MouseEvent`] doesn't carry, so a double click is two single clicks.
+    pub fn handle_mouse(&mut self, event: &MouseEvent) -> EditorMouseAction {
+        let Some(area) = self.mouse_area() else {
+            return EditorMouseAction::Ignored;
+        };
+        let inside = event.x >= area.x
+            && event.x - area.x < area.width
+            && event.y >= area.y
+            && event.y - area.y < area.height;
+        // Clamped so drags outside the editor track its nearest cell.
+        let x = event
+            .x
+            .saturating_sub(area.x)
+            .min(area.width.saturating_sub(1));
+        let y = event
+            .y
+            .saturating_sub(area.y)
+            .min(area.height.saturating_sub(1));
+
+        match event.kind {
+            MouseEventKind::Press if inside && event.button == MouseButton::Left => {
+                let offset = self.offset_from_view(area, x, y);
+                if x < self.gutter_width() {
+                    let line = self.edit_buffer.buffer().rope().char_to_line(offset);
+                    self.drag = Some(DragAnchor::Line(line));
+                    self.select_lines(line, line);
+                    EditorMouseAction::Selected
+                } else if event.shift {
+                    let anchor = self
+                        .selection
+                        .map_or_else(|| self.edit_buffer.cursor().offset, |sel| sel.start);
+                    self.drag = Some(DragAnchor::Char(anchor));
+                    self.select_offsets(anchor, offset);
+                    EditorMouseAction::Selected
+                } else {
+                    self.drag = Some(DragAnchor::Char(offset));
+                    self.selection = None;
+                    self.edit_buffer.set_cursor_by_offset(offset);
+                    EditorMouseAction::MovedCursor
+                }
             }
-
-            let Some(wrap_width) = wrap_width else {
-                let width = crate::unicode::display_width_with_method(line, method);
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte,
-                    byte_end: line_start_byte + line.len(),
-                    width,
-                    is_wrap: false,
-                });
-                continue;
-            };
-
-            let graphemes: Vec<(usize, &str)> = line.grapheme_indices(true).collect();
-            let mut start_byte = 0usize;
-            let mut current_width = 0usize;
-            let mut last_break: Option<(usize, usize, usize)> = None;
-            let mut i = 0usize;
-
-            while i < graphemes.len() {
-                let (byte_idx, grapheme) = graphemes[i];
-                if byte_idx < start_byte {
-                    i += 1;
-                    continue;
+            MouseEventKind::Move if event.button == MouseButton::Left => {
+                let Some(anchor) = self.drag else {
+                    return EditorMouseAction::Ignored;
+                };
+                let offset = self.offset_from_view(area, x, y);
+                match anchor {
+                    DragAnchor::Char(anchor) => self.select_offsets(anchor, offset),
+                    DragAnchor::Line(anchor) => {
+                        let line = self.edit_buffer.buffer().rope().char_to_line(offset);
+                        self.select_lines(anchor, line);
+                    }
                 }
-
-                let g_width = if grapheme == "\t" {
-                    let offset = current_width % tab_width;
-                    tab_width - offset
+                EditorMouseAction::Selected
+            }
+            MouseEventKind::Release => {
+                self.drag = None;
+                EditorMouseAction::Ignored
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if inside => {
+                let text_width = area.width.saturating_sub(self.gutter_width());
+                let wrap_width = self.wrap_width(text_width);
+                let buffer = self.edit_buffer.buffer();
+                self.layout.sync(buffer, self.wrap_mode, wrap_width);
+                let max_scroll = self
+                    .layout
+                    .total_rows()
+                    .saturating_sub(area.height as usize);
+                let max_scroll = u32::try_from(max_scroll).unwrap_or(u32::MAX);
+                self.scroll_y = if event.kind == MouseEventKind::ScrollUp {
+                    self.scroll_y.saturating_sub(self.mouse_scroll_lines)
                 } else {
-                    crate::unicode::display_width_with_method(grapheme, method)
+                    self.scroll_y
+                        .saturating_add(self.mouse_scroll_lines)
+                        .min(max_scroll.max(self.scroll_y))
                 };
+                EditorMouseAction::Scrolled
+            }
+            MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight
+                if inside && !self.wrap_mode.wraps() =>
+            {
+                self.scroll_x = if event.kind == MouseEventKind::ScrollLeft {
+                    self.scroll_x.saturating_sub(self.mouse_scroll_lines)
+                } else {
+                    self.scroll_x.saturating_add(self.mouse_scroll_lines)
+                };
+                EditorMouseAction::Scrolled
+            }
+            _ => EditorMouseAction::Ignored,
+        }
+    }
 
-                let is_ws = grapheme.chars().all(char::is_whitespace);
-                if self.wrap_mode == WrapMode::Word && is_ws {
-                    last_break = Some((byte_idx + grapheme.len(), current_width + g_width, i + 1));
-                }
+    fn mouse_area(&self) -> Option<Viewport> {
+        self.viewport.or(self.render_area)
+    }
 
-                if current_width + g_width > wrap_width && current_width > 0 {
-                    let (break_byte, break_width, break_index) = if self.wrap_mode == WrapMode::Word
-                    {
-                        last_break.unwrap_or((byte_idx, current_width, i))
-                    } else {
-                        (byte_idx, current_width, i)
-                    };
-
-                    lines.push(VirtualLine {
-                        source_line: line_idx,
-                        byte_start: line_start_byte + start_byte,
-                        byte_end: line_start_byte + break_byte,
-                        width: break_width,
-                        is_wrap: start_byte > 0,
-                    });
-
-                    start_byte = break_byte;
-                    current_width = 0;
-                    last_break = None;
-                    i = break_index;
-
-                    if self.wrap_mode == WrapMode::Word {
-                        while i < graphemes.len() {
-                            let (b, g) = graphemes[i];
-                            if b < start_byte {
-                                i += 1;
-                                continue;
-                            }
-                            if g.chars().all(char::is_whitespace) {
-                                start_byte = b + g.len();
-                                i += 1;
-                            } else {
-                                break;
-                            }
-                        }
-                    }
+    /// Char offset shown at `(x, y)` in `area`; see [`screen_to_buffer`](Self::screen_to_buffer).
+    fn offset_from_view(&mut self, area: Viewport, x: u32, y: u32) -> usize {
+        use unicode_segmentation::UnicodeSegmentation;
 
-                    continue;
-                }
+        let gutter_width = self.gutter_width();
+        let wrap_width = self.wrap_width(area.width.saturating_sub(gutter_width));
+        let buffer = self.edit_buffer.buffer();
+        self.layout.sync(buffer, self.wrap_mode, wrap_width);
+        let row = (self.scroll_y + y) as usize;
+        let Some(vline) = self.layout.rows(buffer, row, 1).pop() else {
+            return buffer.len_chars();
+        };
 
-                current_width += g_width;
-                i += 1;
+        let rope = buffer.rope();
+        let mut offset = rope.byte_to_char(vline.byte_start);
+        if x < gutter_width {
+            return offset;
+        }
+        let scroll_x = if self.wrap_mode.wraps() {
+            0
+        } else {
+            self.scroll_x
+        };
+        let target = (x - gutter_width + scroll_x) as usize;
+        let text = rope
+            .slice(offset..rope.byte_to_char(vline.byte_end))
+            .to_string();
+        let method = buffer.width_method();
+        let tab_width = buffer.tab_width().max(1) as usize;
+        let mut col = 0;
+        for grapheme in text.graphemes(true) {
+            col += if grapheme == "\t" {
+                tab_width - col % tab_width
+            } else {
+                crate::unicode::display_width_with_method(grapheme, method)
+            };
+            if col > target {
+                break;
             }
+            offset += grapheme.chars().count();
+        }
+        offset
+    }
+
+    /// Cell in `area` showing char offset `offset`, relative to its
+    /// top-left corner, or `None` when scrolled out of view.
+    fn screen_cell(&mut self, area: Viewport, offset: usize) -> Option<(u32, u32)> {
+        let gutter_width = self.gutter_width();
+        let text_width = area.width.saturating_sub(gutter_width);
+        let buffer = self.edit_buffer.buffer();
+        self.layout
+            .sync(buffer, self.wrap_mode, self.wrap_width(text_width));
+        self.laid_out_cell(area, offset)
+    }
+
+    /// [`screen_cell`](Self::screen_cell) with the layout already synced to
+    /// `area`.
+    fn laid_out_cell(&mut self, area: Viewport, offset: usize) -> Option<(u32, u32)> {
+        let gutter_width = self.gutter_width();
+        let text_width = area.width.saturating_sub(gutter_width);
+        let (row, col) = self.layout.position_of(self.edit_buffer.buffer(), offset)?;
+        let row = u32::try_from(row).ok()?.checked_sub(self.scroll_y)?;
+        let col = u32::try_from(col).ok()?;
+        let col = if self.wrap_mode.wraps() {
+            // The end of a full last row sits on its last cell.
+            col.min(text_width.saturating_sub(1))
+        } else {
+            col.checked_sub(self.scroll_x)?
+        };
+        (row < area.height && col < text_width).then_some((gutter_width + col, row))
+    }
+
+    /// Select from `anchor` to `offset`, moving the cursor to `offset`.
+    fn select_offsets(&mut self, anchor: usize, offset: usize) {
+        self.edit_buffer.set_cursor_by_offset(offset);
+        self.selection = Some(Selection::new(anchor, offset, self.selection_style));
+    }
 
-            if start_byte <= line.len() {
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte + start_byte,
-                    byte_end: line_start_byte + line.len(),
-                    width: current_width,
-                    is_wrap: start_byte > 0,
-                });
+    /// Select lines `anchor` through `line`, cursor at the end towards `line`.
+    fn select_lines(&mut self, anchor: usize, line: usize) {
+        let rope = self.edit_buffer.buffer().rope();
+        let line_start = |line: usize| {
+            if line < rope.len_lines() {
+                rope.line_to_char(line)
+            } else {
+                rope.len_chars()
             }
+        };
+        let (start, end) = if line < anchor {
+            (line_start(anchor + 1), line_start(line))
+        } else {
+            (line_start(anchor), line_start(line + 1))
+        };
+        self.select_offsets(start, end);
+    }
+
+    /// Build virtual line information for visual navigation.
+    fn build_virtual_lines(&self, text_width: u32, _viewport_height: u32) -> Vec<VirtualLine> {
+        let mut lines = Vec::new();
+        let rope = self.edit_buffer.buffer().rope();
+        let method = self.edit_buffer.buffer().width_method();
+        let tab_width = self.edit_buffer.buffer().tab_width().max(1) as usize;
+        let wrap_width = self.wrap_width(text_width);
+
+        for line_idx in 0..self.edit_buffer.buffer().len_lines() {
+            let Some(line) = self.edit_buffer.buffer().line(line_idx) else {
+                continue;
+            };
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            let line_start_char = rope.line_to_char(line_idx);
+            let line_start_byte = rope.char_to_byte(line_start_char);
+
+            let rows = wrap_line(line, self.wrap_mode, wrap_width, tab_width, method);
+            lines.extend(rows.into_iter().map(|(start, end, width)| VirtualLine {
+                source_line: line_idx,
+                byte_start: line_start_byte + start,
+                byte_end: line_start_byte + end,
+                width,
+                is_wrap: start > 0,
+            }));
         }
 
         lines
     }
 
+    fn wrap_width(&self, text_width: u32) -> Option<usize> {
+        (self.wrap_mode.wraps() && text_width > 0).then_some(text_width as usize)
+    }
+
     /// Find the virtual line index for a byte offset, handling cursor at newline positions.
     fn find_vline_index(vlines: &[VirtualLine], byte_offset: usize) -> usize {
         for (idx, vline) in vlines.iter().enumerate() {
@@ -736,33 +1097,46 @@ impl EditorView {
         width: u32,
         height: u32,
     ) {
-        self.edit_buffer
-            .highlighted_buffer_mut()
-            .update_highlighting();
+        let selection = self
+            .selection
+            .map(|sel| sel.normalized())
+            .map(|sel| (sel.start..sel.end, sel.style));
+        let cursor = self.edit_buffer.cursor().offset;
+        let highlighted = self.edit_buffer.highlighted_buffer_mut();
+        highlighted.update_highlighting();
+        highlighted.set_layer_ranges(SELECTION_LAYER, selection.into_iter().collect());
+        let brackets = self.bracket_match_style.and_then(|style| {
+            let (open, close) = bracket_match_within(highlighted, cursor, self.bracket_scan_limit)?;
+            Some(vec![(open..open + 1, style), (close..close + 1, style)])
+        });
+        highlighted.set_layer_ranges(BRACKET_MATCH_LAYER, brackets.unwrap_or_default());
         let (x, y, width, height) = if let Some(viewport) = self.viewport {
             (viewport.x, viewport.y, viewport.width, viewport.height)
         } else {
             (x, y, width, height)
         };
+        self.render_area = Some(Viewport::new(x, y, width, height));
 
         let gutter_width = self.gutter_width();
         let text_x = x + gutter_width;
         let text_width = width.saturating_sub(gutter_width);
 
-        // Render line numbers if enabled
+        // Lay out only what changed, then draw the visible rows
+        let buffer = self.edit_buffer.buffer();
+        self.layout
+            .sync(buffer, self.wrap_mode, self.wrap_width(text_width));
+        let rows = self
+            .layout
+            .rows(buffer, self.scroll_y as usize, height as usize);
         if self.line_numbers {
-            self.render_line_numbers(output, x, y, gutter_width, height);
+            self.render_line_numbers(output, x, y, gutter_width, &rows);
         }
-
-        // Create a view and render text
-        let mut view = TextBufferView::new(self.edit_buffer.buffer())
+        let mut view = TextBufferView::new(buffer)
             .viewport(0, 0, text_width, height)
             .wrap_mode(self.wrap_mode)
-            .scroll(self.scroll_x, self.scroll_y);
+            .scroll(self.scroll_x, self.scroll_y)
+            .highlight_layers(self.edit_buffer.highlighted_buffer().layers());
 
-        if let Some(sel) = self.selection {
-            view.set_selection(sel.start, sel.end, sel.style);
-        }
         if let Some(local) = self.local_selection {
             view.set_local_selection(
                 local.anchor_x,
@@ -773,25 +1147,45 @@ impl EditorView {
             );
         }
 
-        view.render_to(output, text_x as i32, y as i32);
+        view.render_rows(output, text_x as i32, y as i32, &rows);
 
         // Render cursor
-        self.render_cursor(output, &view, text_x, y, text_width, height);
+        let offset = self.edit_buffer.cursor().offset;
+        if let Some((cursor_x, cursor_y)) =
+            self.laid_out_cell(Viewport::new(x, y, width, height), offset)
+        {
+            if let Some(cell) = output.get_mut(x + cursor_x, y + cursor_y) {
+                cell.apply_style(self.cursor_style);
+            }
+        }
     }
 
+    /// Layout cache counters from the last [`render_to`](Self::render_to).
+    ///
+    /// On a frame where neither the text, the width, nor the scroll changed,
+    /// only the visible lines are read and none are laid out.
+    #[must_use]
+    pub fn layout_stats(&self) -> LayoutStats {
+        self.layout.stats()
+    }
+
+    /// Number the first row of each line in `rows`, leaving wrapped
+    /// continuations blank.
     fn render_line_numbers(
         &self,
         output: &mut OptimizedBuffer,
         x: u32,
         y: u32,
         width: u32,
-        height: u32,
+        rows: &[VirtualLine],
     ) {
-        let start_line = self.scroll_y as usize;
-        let end_line = (start_line + height as usize).min(self.edit_buffer.buffer().len_lines());
         let cursor_row = self.edit_buffer.cursor().row;
 
-        for (offset, line_num) in (start_line..end_line).enumerate() {
+        for (offset, row) in rows.iter().enumerate() {
+            if row.is_wrap {
+                continue;
+            }
+            let line_num = row.source_line;
             let display_num = line_num + 1;
             let s = format!("{display_num:>width$} ", width = (width - 1) as usize);
 
@@ -804,41 +1198,6 @@ impl EditorView {
             output.draw_text(x, y + offset as u32, &s, style);
         }
     }
-
-    fn render_cursor(
-        &self,
-        output: &mut OptimizedBuffer,
-        view: &TextBufferView<'_>,
-        text_x: u32,
-        text_y: u32,
-        _width: u32,
-        _height: u32,
-    ) {
-        let cursor = self.edit_buffer.cursor();
-        let (visual_row, visual_col) = if self.wrap_mode == WrapMode::None {
-            (cursor.row as u32, cursor.col as u32)
-        } else {
-            view.visual_position_for_offset(cursor.offset)
-        };
-
-        if visual_row < self.scroll_y {
-            return;
-        }
-
-        let visible_row = visual_row - self.scroll_y;
-        let visible_col = if self.wrap_mode == WrapMode::None {
-            visual_col.saturating_sub(self.scroll_x)
-        } else {
-            visual_col
-        };
-
-        let cursor_x = text_x + visible_col;
-        let cursor_y = text_y + visible_row;
-
-        if let Some(cell) = output.get_mut(cursor_x, cursor_y) {
-            cell.apply_style(self.cursor_style);
-        }
-    }
 }
 
 impl Default for EditorView {
@@ -851,6 +1210,7 @@ impl Default for EditorView {
 mod tests {
     #![allow(clippy::uninlined_format_args)]
     use super::*;
+    use crate::style::TextAttributes;
 
     #[test]
     fn test_editor_view_basic() {
@@ -869,6 +1229,20 @@ mod tests {
         assert!(view.scroll_y >= 2);
     }
 
+    #[test]
+    fn test_truncate_scroll_keeps_cursor_off_ellipsis() {
+        let mut edit = EditBuffer::with_text(&"x".repeat(30));
+        edit.move_to(0, 20);
+        let mut view = EditorView::new(edit);
+        view.set_wrap_mode(WrapMode::Truncate { ellipsis: true });
+        view.set_scroll_margin(0.0);
+
+        view.scroll_to_cursor(10, 3);
+        let (scroll_x, _) = view.scroll();
+        assert!(scroll_x > 0);
+        assert!(20 - scroll_x < 9, "cursor lands on the ellipsis column");
+    }
+
     #[test]
     fn test_gutter_width() {
         let edit = EditBuffer::with_text(&"x\n".repeat(100));
@@ -1846,6 +2220,22 @@ mod tests {
         eprintln!("[TEST] PASS: Selection can extend backward");
     }
 
+    #[test]
+    fn test_surround_selection_keeps_inner_text_selected() {
+        let mut view = EditorView::new(EditBuffer::with_text("fn a() {\n    b();\n}"));
+        assert!(!view.surround_selection("(", ")"));
+        view.set_selection(9, 5);
+        assert!(view.surround_selection("(", ")"));
+
+        assert_eq!(view.edit_buffer().text(), "fn a(() {\n)    b();\n}");
+        assert_eq!(view.selected_text().as_deref(), Some(") {\n"));
+        let sel = view.selection.unwrap();
+        assert_eq!((sel.start, sel.end), (10, 6));
+
+        assert!(view.edit_buffer_mut().undo());
+        assert_eq!(view.edit_buffer().text(), "fn a() {\n    b();\n}");
+    }
+
     #[test]
     fn test_selected_text() {
         eprintln!("[TEST] test_selected_text");
@@ -1931,4 +2321,531 @@ mod tests {
 
         eprintln!("[TEST] PASS: selection_follow_cursor mode works");
     }
+
+    #[test]
+    fn test_search_matches_render_under_selection() {
+        let mut view = EditorView::new(EditBuffer::with_text("find me, find you"));
+        let search_bg = Rgba::rgb(1.0, 1.0, 0.0);
+        let selection_bg = Rgba::BLUE;
+        view.set_search_style(Style::bg(search_bg).with_underline());
+        view.set_selection_style(Style::bg(selection_bg));
+        view.set_search_matches(vec![0..4, 9..13]);
+        view.set_selection(11, 6);
+        // Cursor off the inspected cells.
+        view.edit_buffer_mut().set_cursor_by_offset(16);
+
+        let mut output = OptimizedBuffer::new(20, 1);
+        view.render_to(&mut output, 0, 0, 20, 1);
+
+        let cell = |x| *output.get(x, 0).unwrap();
+        let underlined = |x| cell(x).attributes.contains(TextAttributes::UNDERLINE);
+        // Match outside the selection.
+        assert_eq!(cell(0).bg, search_bg);
+        assert!(underlined(0));
+        // Match under the selection: selection bg wins, match underline kept.
+        assert_eq!(cell(10).bg, selection_bg);
+        assert!(underlined(10));
+        // Match tail past the selection.
+        assert_eq!(cell(12).bg, search_bg);
+        // Selection only.
+        assert_eq!(cell(7).bg, selection_bg);
+        assert!(!underlined(7));
+    }
+
+    #[test]
+    fn test_search_matches_follow_edits() {
+        let mut view = EditorView::new(EditBuffer::with_text("needle needle"));
+        view.set_search_matches(vec![0..6, 7..13]);
+        view.edit_buffer_mut().set_cursor_by_offset(0);
+        view.edit_buffer_mut().insert(">> ");
+        assert_eq!(view.search_matches(), vec![3..9, 10..16]);
+    }
+
+    #[test]
+    fn test_bracket_pair_at_cursor_is_highlighted() {
+        let mut view = EditorView::new(EditBuffer::with_text("f(a, [b] )"));
+        let pair_bg = Rgba::rgb(0.0, 0.5, 0.0);
+        view.set_bracket_match_style(Some(Style::bg(pair_bg)));
+        // Cursor just after the closing `]`.
+        view.edit_buffer_mut().set_cursor_by_offset(8);
+
+        let mut output = OptimizedBuffer::new(10, 1);
+        view.render_to(&mut output, 0, 0, 10, 1);
+        let bg = |x| output.get(x, 0).unwrap().bg;
+        assert_eq!(bg(5), pair_bg);
+        assert_eq!(bg(7), pair_bg);
+        assert_ne!(bg(1), pair_bg);
+        assert_ne!(bg(8), pair_bg);
+
+        view.set_bracket_match_style(None);
+        let mut output = OptimizedBuffer::new(10, 1);
+        view.render_to(&mut output, 0, 0, 10, 1);
+        assert_ne!(output.get(5, 0).unwrap().bg, pair_bg);
+    }
+
+    #[test]
+    fn test_with_highlighting_restyles_edits_and_themes() {
+        use crate::highlight::TokenKind;
+
+        let registry = TokenizerRegistry::with_builtins();
+        let dark = Theme::dark();
+        let mut view = EditorView::new(EditBuffer::with_text("let a = 1;\nlet b = 2;"))
+            .with_highlighting("rust", &dark, &registry);
+        let render = |view: &mut EditorView| {
+            let mut output = OptimizedBuffer::new(12, 2);
+            view.render_to(&mut output, 0, 0, 12, 2);
+            output
+        };
+        let comment_fg = |theme: &Theme| theme.style_for(TokenKind::Comment).fg.unwrap();
+
+        let output = render(&mut view);
+        assert_ne!(output.get(6, 1).unwrap().fg, comment_fg(&dark));
+
+        // Typing `//` at a line start comments out the rest of it.
+        view.edit_buffer_mut().move_to(1, 0);
+        view.edit_buffer_mut().insert("//");
+        let output = render(&mut view);
+        for x in 0..12 {
+            assert_eq!(output.get(x, 1).unwrap().fg, comment_fg(&dark), "x {x}");
+        }
+        assert_ne!(output.get(0, 0).unwrap().fg, comment_fg(&dark));
+
+        // A new theme restyles the same tokens; the selection draws over them.
+        let light = Theme::light();
+        view.set_highlighting_theme(light.clone());
+        view.set_selection_style(Style::bg(Rgba::BLUE));
+        view.set_selection(13, 15);
+        let output = render(&mut view);
+        assert_eq!(output.get(2, 1).unwrap().fg, comment_fg(&light));
+        assert_eq!(output.get(2, 1).unwrap().bg, Rgba::BLUE);
+        assert_ne!(comment_fg(&light), comment_fg(&dark));
+    }
+
+    #[test]
+    fn test_enable_highlighting_by_name_or_extension() {
+        let registry = TokenizerRegistry::with_builtins();
+        let mut view = EditorView::empty();
+        assert!(view.enable_highlighting(&registry, "Rust"));
+        assert!(view.enable_highlighting(&registry, ".py"));
+        assert!(!view.enable_highlighting(&registry, "cobol"));
+        assert!(view.edit_buffer().highlighted_buffer().has_tokenizer());
+    }
+
+    /// Render `view` and the same rows through a fresh, uncached
+    /// `TextBufferView`, and check they agree cell for cell.
+    fn assert_matches_uncached(view: &mut EditorView, width: u32, height: u32) {
+        let mut cached = OptimizedBuffer::new(width, height);
+        view.render_to(&mut cached, 0, 0, width, height);
+
+        let (scroll_x, scroll_y) = view.scroll();
+        let plain = TextBufferView::new(view.edit_buffer().buffer())
+            .viewport(0, 0, width, height)
+            .wrap_mode(view.wrap_mode)
+            .scroll(scroll_x, scroll_y);
+        let mut uncached = OptimizedBuffer::new(width, height);
+        plain.render_to(&mut uncached, 0, 0);
+
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(cached.get(x, y), uncached.get(x, y), "cell {x},{y}");
+            }
+        }
+    }
+
+    fn long_document(lines: usize) -> String {
+        (0..lines)
+            .map(|i| format!("{i}: {}", "lorem ipsum dolor ".repeat(i % 5)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_layout_cache_idle_frame_reads_only_visible_lines() {
+        let mut view = EditorView::new(EditBuffer::with_text(&long_document(50_000)));
+        view.set_wrap_mode(WrapMode::Word);
+        view.set_scroll(0, 60_000);
+
+        assert_matches_uncached(&mut view, 40, 20);
+        let stats = view.layout_stats();
+        assert_eq!(stats.lines_laid_out, 50_000);
+        assert_eq!(stats.cached_lines, 50_000);
+        assert!(stats.total_rows > 50_000);
+
+        assert_matches_uncached(&mut view, 40, 20);
+        let stats = view.layout_stats();
+        assert_eq!(stats.lines_laid_out, 0);
+        assert!(stats.lines_read <= 20, "read {} lines", stats.lines_read);
+
+        // A new width lays everything out again.
+        assert_matches_uncached(&mut view, 30, 20);
+        assert_eq!(view.layout_stats().lines_laid_out, 50_000);
+    }
+
+    #[test]
+    fn test_layout_cache_relayouts_only_edited_lines() {
+        let mut view = EditorView::new(EditBuffer::with_text(&long_document(2_000)));
+        view.set_wrap_mode(WrapMode::Char);
+        view.set_scroll(0, 1_500);
+        assert_matches_uncached(&mut view, 24, 10);
+
+        // Edits above the viewport shift its rows without touching them.
+        view.edit_buffer_mut().goto_line(10);
+        view.edit_buffer_mut()
+            .insert("a much longer line than before ");
+        assert_matches_uncached(&mut view, 24, 10);
+        assert_eq!(view.layout_stats().lines_laid_out, 1);
+
+        view.edit_buffer_mut().insert("\nsplit\n");
+        assert_matches_uncached(&mut view, 24, 10);
+        assert_eq!(view.layout_stats().lines_laid_out, 3);
+
+        view.edit_buffer_mut().delete_backward();
+        view.edit_buffer_mut().delete_backward();
+        assert_matches_uncached(&mut view, 24, 10);
+        assert_eq!(view.layout_stats().lines_laid_out, 1);
+
+        view.edit_buffer_mut().undo();
+        assert_matches_uncached(&mut view, 24, 10);
+
+        // Untracked changes start over.
+        view.edit_buffer_mut().set_text(&long_document(1_990));
+        assert_matches_uncached(&mut view, 24, 10);
+        assert_eq!(view.layout_stats().lines_laid_out, 1_990);
+    }
+
+    #[test]
+    fn test_layout_cache_cursor_position_matches_view() {
+        let mut edit = EditBuffer::with_text("short\na line that wraps\tacross rows\n\tend");
+        edit.move_to(1, 14);
+        let mut view = EditorView::new(edit);
+        view.set_wrap_mode(WrapMode::Word);
+        let mut output = OptimizedBuffer::new(8, 10);
+        view.render_to(&mut output, 0, 0, 8, 10);
+
+        let plain = TextBufferView::new(view.edit_buffer.buffer())
+            .viewport(0, 0, 8, 10)
+            .wrap_mode(WrapMode::Word);
+        let buffer = view.edit_buffer.buffer();
+        for offset in 0..=buffer.len_chars() {
+            let (row, col) = view.layout.position_of(buffer, offset).unwrap();
+            assert_eq!(
+                (row as u32, col as u32),
+                plain.visual_position_for_offset(offset),
+                "offset {offset}"
+            );
+        }
+    }
+
+    /// Line numbers (3-column gutter), char wrapping at 11 columns, drawn
+    /// at (2, 1) and scrolled down a row. Visible rows:
+    ///
+    /// ```text
+    ///    lmnop
+    ///  2 \tx
+    ///  3 世界abc
+    ///  4 0123456789A
+    /// ```
+    fn mouse_view() -> EditorView {
+        let edit = EditBuffer::with_text("abcdefghijklmnop\n\tx\n世界abc\n0123456789ABCDEF\n");
+        let mut view = EditorView::new(edit);
+        view.set_line_numbers(true);
+        view.set_wrap_mode(WrapMode::Char);
+        view.set_scroll(0, 1);
+        let mut output = OptimizedBuffer::new(20, 8);
+        view.render_to(&mut output, 2, 1, 14, 4);
+        view
+    }
+
+    fn left(kind: MouseEventKind, x: u32, y: u32) -> MouseEvent {
+        MouseEvent::new(x, y, MouseButton::Left, kind)
+    }
+
+    #[test]
+    fn test_screen_to_buffer_maps_cells() {
+        let mut view = EditorView::new(EditBuffer::with_text("abc"));
+        assert_eq!(view.screen_to_buffer(0, 0), None, "not rendered yet");
+
+        let mut view = mouse_view();
+        assert_eq!(view.gutter_width(), 3);
+        assert_eq!(view.screen_to_buffer(3, 0), Some((0, 11)));
+        assert_eq!(view.screen_to_buffer(5, 0), Some((0, 13)));
+        assert_eq!(view.screen_to_buffer(12, 0), Some((0, 16)), "past row end");
+        // The tab spans columns 0..4.
+        assert_eq!(view.screen_to_buffer(5, 1), Some((1, 0)));
+        assert_eq!(view.screen_to_buffer(7, 1), Some((1, 1)));
+        // Wide chars take two columns each.
+        assert_eq!(view.screen_to_buffer(4, 2), Some((2, 0)));
+        assert_eq!(view.screen_to_buffer(5, 2), Some((2, 1)));
+        assert_eq!(view.screen_to_buffer(7, 2), Some((2, 2)));
+        assert_eq!(view.screen_to_buffer(1, 3), Some((3, 0)), "gutter");
+        assert_eq!(view.screen_to_buffer(14, 0), None);
+        assert_eq!(view.screen_to_buffer(0, 4), None);
+
+        // Without wrapping, horizontal scroll shifts columns.
+        view.set_wrap_mode(WrapMode::None);
+        view.set_scroll(2, 0);
+        assert_eq!(view.screen_to_buffer(3, 0), Some((0, 2)));
+        assert_eq!(view.screen_to_buffer(3, 3), Some((3, 2)));
+        view.set_scroll(0, 4);
+        assert_eq!(view.screen_to_buffer(3, 1), Some((4, 0)), "below the text");
+    }
+
+    /// Renders `view` at `(2, 1)` and returns where the cursor cell was
+    /// drawn, relative to that origin.
+    fn rendered_cursor(view: &mut EditorView, width: u32, height: u32) -> Option<(u32, u32)> {
+        let mut output = OptimizedBuffer::new(width + 4, height + 2);
+        view.render_to(&mut output, 2, 1, width, height);
+        let mut found = None;
+        for y in 0..output.height() {
+            for x in 0..output.width() {
+                if output.get(x, y).unwrap().bg == Rgba::RED {
+                    assert_eq!(found, None, "two cursor cells");
+                    found = Some((x - 2, y - 1));
+                }
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_popup_anchors_at_rendered_cursor() {
+        let edit = EditBuffer::with_text("fn main() {\n\tlet 世界 = 1;\n}\n");
+        let mut view = EditorView::new(edit);
+        view.set_cursor_style(Style::builder().bg(Rgba::RED).build());
+        view.set_bracket_match_style(None);
+        view.set_line_numbers(true);
+        view.set_wrap_mode(WrapMode::Char);
+        assert_eq!(view.cursor_screen_position(), None, "not rendered yet");
+
+        // After the tab and "let 世": gutter 3, tab 4, "let " 4, wide 2.
+        view.edit_buffer_mut().move_to(1, 6);
+        let mut anchors = Vec::new();
+        let mut check = |view: &mut EditorView, width: u32, height: u32| {
+            let drawn = rendered_cursor(view, width, height);
+            let anchor = view.cursor_screen_position();
+            assert_eq!(anchor, drawn);
+            let cursor = view.edit_buffer().cursor();
+            assert_eq!(view.buffer_to_screen(cursor.row, cursor.col), anchor);
+            if let Some((x, y)) = anchor {
+                assert_eq!(view.screen_to_buffer(x, y), Some((cursor.row, cursor.col)));
+            }
+            anchors.push(anchor);
+        };
+        check(&mut view, 20, 4);
+
+        // Typing pushes the anchor to the start of the next wrapped row.
+        view.edit_buffer_mut().insert("界界界");
+        check(&mut view, 20, 4);
+        view.edit_buffer_mut().insert("\n");
+        check(&mut view, 20, 4);
+
+        // Scrolling moves it up, then out of view.
+        view.set_scroll(0, 1);
+        check(&mut view, 20, 4);
+        view.set_scroll(0, 3);
+        check(&mut view, 20, 4);
+
+        // A narrower wrap width reflows the line above the cursor.
+        view.set_scroll(0, 0);
+        view.edit_buffer_mut().move_to(1, 8);
+        check(&mut view, 12, 6);
+        check(&mut view, 9, 6);
+
+        // Without wrapping, columns count tab and wide char widths and
+        // shift with horizontal scroll.
+        view.set_wrap_mode(WrapMode::None);
+        view.edit_buffer_mut().move_to(1, 5);
+        check(&mut view, 12, 6);
+        view.set_scroll(4, 0);
+        check(&mut view, 12, 6);
+        view.set_scroll(20, 0);
+        check(&mut view, 12, 6);
+
+        assert_eq!(
+            anchors,
+            [
+                Some((13, 1)),
+                Some((3, 2)),
+                Some((3, 2)),
+                Some((3, 1)),
+                None,
+                Some((9, 3)),
+                Some((5, 4)),
+                Some((11, 1)),
+                Some((7, 1)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_folds_hide_rows_from_render_and_mappings() {
+        let edit = EditBuffer::with_text("zero\none\ntwo\nthree\nfour is long\nfive");
+        let mut view = EditorView::new(edit);
+        view.set_cursor_style(Style::builder().bg(Rgba::RED).build());
+        view.set_bracket_match_style(None);
+        view.set_line_numbers(true);
+        view.edit_buffer_mut().move_to(4, 6);
+        let check = |view: &mut EditorView, width: u32, height: u32| {
+            let drawn = rendered_cursor(view, width, height);
+            assert_eq!(view.cursor_screen_position(), drawn);
+            let cursor = view.edit_buffer().cursor();
+            assert_eq!(view.buffer_to_screen(cursor.row, cursor.col), drawn);
+            if let Some((x, y)) = drawn {
+                assert_eq!(view.screen_to_buffer(x, y), Some((cursor.row, cursor.col)));
+            }
+            drawn
+        };
+        assert_eq!(check(&mut view, 20, 6), Some((9, 4)));
+
+        // A fold above the cursor moves it up by the rows it hides.
+        view.fold_lines(1..3);
+        assert_eq!(check(&mut view, 20, 6), Some((9, 2)));
+        assert_eq!(view.screen_to_buffer(4, 1), Some((3, 1)));
+        assert_eq!(view.buffer_to_screen(2, 0), None, "folded");
+        let mut output = OptimizedBuffer::new(20, 6);
+        view.render_to(&mut output, 0, 0, 20, 6);
+        let row = |output: &OptimizedBuffer, y: u32| -> String {
+            (0..8)
+                .map(|x| output.get(x, y).unwrap().content.as_char().unwrap_or(' '))
+                .collect()
+        };
+        assert_eq!(row(&output, 0), " 1 zero ");
+        assert_eq!(row(&output, 1), " 4 three");
+
+        // Folds join, follow edits around them, and open when edited.
+        view.fold_lines(3..4);
+        assert_eq!(view.folds(), [1..4]);
+        assert_eq!(check(&mut view, 20, 6), Some((9, 1)));
+        view.edit_buffer_mut().move_to(0, 4);
+        view.edit_buffer_mut().insert("\nnew");
+        assert_eq!(view.folds(), [2..5]);
+        view.edit_buffer_mut().move_to(5, 6);
+        assert_eq!(check(&mut view, 20, 6), Some((9, 2)));
+        view.edit_buffer_mut().move_to(3, 1);
+        view.edit_buffer_mut().insert("x");
+        assert_eq!(view.folds(), []);
+        view.edit_buffer_mut().move_to(5, 6);
+        assert_eq!(check(&mut view, 20, 6), Some((9, 5)));
+
+        // With wrapping, a folded line hides all of its rows, and scrolling
+        // to the cursor counts only the rows shown.
+        view.set_wrap_mode(WrapMode::Char);
+        view.fold_lines(0..5);
+        assert_eq!(check(&mut view, 7, 3), Some((4, 1)));
+        view.unfold_lines(4..5);
+        assert_eq!(view.folds(), []);
+        view.fold_lines(0..4);
+        view.scroll_to_cursor(7, 3);
+        assert_eq!(view.scroll(), (0, 2));
+        assert_eq!(check(&mut view, 7, 3), Some((4, 1)));
+        view.clear_folds();
+        assert_eq!(check(&mut view, 7, 3), None, "unfolding pushes it down");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_position_from_view_is_screen_to_buffer() {
+        let mut view = mouse_view();
+        for (x, y) in [(3, 0), (5, 1), (1, 3), (14, 0)] {
+            assert_eq!(view.position_from_view(x, y), view.screen_to_buffer(x, y));
+        }
+    }
+
+    #[test]
+    fn test_mouse_click_and_drag_select() {
+        let mut view = mouse_view();
+
+        // Outside the editor.
+        assert_eq!(
+            view.handle_mouse(&left(MouseEventKind::Press, 0, 0)),
+            EditorMouseAction::Ignored
+        );
+
+        // Click on `n`, then drag to `5` two rows down.
+        assert_eq!(
+            view.handle_mouse(&left(MouseEventKind::Press, 7, 1)),
+            EditorMouseAction::MovedCursor
+        );
+        assert_eq!(view.edit_buffer().cursor().offset, 13);
+        assert_eq!(view.selected_text(), None);
+        assert_eq!(
+            view.handle_mouse(&left(MouseEventKind::Move, 10, 4)),
+            EditorMouseAction::Selected
+        );
+        assert_eq!(view.selected_text().unwrap(), "nop\n\tx\n世界abc\n01234");
+        assert_eq!(view.edit_buffer().cursor().offset, 31);
+
+        // Dragging above the editor clamps to its top row.
+        view.handle_mouse(&left(MouseEventKind::Move, 5, 0));
+        assert_eq!(view.selected_text().unwrap(), "lm");
+        view.handle_mouse(&left(MouseEventKind::Release, 5, 0));
+        assert_eq!(
+            view.handle_mouse(&left(MouseEventKind::Move, 9, 3)),
+            EditorMouseAction::Ignored,
+            "moves after release don't select"
+        );
+
+        // Shift-click extends from the selection anchor.
+        let shift_click = left(MouseEventKind::Press, 9, 3).with_modifiers(true, false, false);
+        assert_eq!(view.handle_mouse(&shift_click), EditorMouseAction::Selected);
+        assert_eq!(view.selected_text().unwrap(), "nop\n\tx\n世界");
+
+        // A plain click clears it.
+        view.handle_mouse(&left(MouseEventKind::Press, 5, 1));
+        assert_eq!(view.selected_text(), None);
+    }
+
+    #[test]
+    fn test_mouse_gutter_selects_lines() {
+        let mut view = mouse_view();
+        assert_eq!(
+            view.handle_mouse(&left(MouseEventKind::Press, 3, 2)),
+            EditorMouseAction::Selected
+        );
+        assert_eq!(view.selected_text().unwrap(), "\tx\n");
+
+        // Dragging, even into the text, extends by whole lines.
+        view.handle_mouse(&left(MouseEventKind::Move, 8, 4));
+        assert_eq!(
+            view.selected_text().unwrap(),
+            "\tx\n世界abc\n0123456789ABCDEF\n"
+        );
+        view.handle_mouse(&left(MouseEventKind::Move, 8, 1));
+        assert_eq!(view.selected_text().unwrap(), "abcdefghijklmnop\n\tx\n");
+        assert_eq!(view.edit_buffer().cursor().offset, 0);
+    }
+
+    #[test]
+    fn test_mouse_wheel_scrolls() {
+        let mut view = mouse_view();
+        view.set_mouse_scroll_lines(2);
+        let wheel = |kind| MouseEvent::new(8, 2, MouseButton::None, kind);
+
+        assert_eq!(
+            view.handle_mouse(&wheel(MouseEventKind::ScrollDown)),
+            EditorMouseAction::Scrolled
+        );
+        assert_eq!(view.scroll(), (0, 3));
+        // 7 rows in a 4-row view scroll at most 3.
+        view.handle_mouse(&wheel(MouseEventKind::ScrollDown));
+        assert_eq!(view.scroll(), (0, 3));
+        view.handle_mouse(&wheel(MouseEventKind::ScrollUp));
+        view.handle_mouse(&wheel(MouseEventKind::ScrollUp));
+        assert_eq!(view.scroll(), (0, 0));
+        assert_eq!(
+            view.handle_mouse(&wheel(MouseEventKind::ScrollRight)),
+            EditorMouseAction::Ignored,
+            "wrapped text doesn't scroll sideways"
+        );
+
+        view.set_wrap_mode(WrapMode::None);
+        view.handle_mouse(&wheel(MouseEventKind::ScrollRight));
+        assert_eq!(view.scroll(), (2, 0));
+        assert_eq!(
+            view.handle_mouse(&MouseEvent::scroll_down(30, 2)),
+            EditorMouseAction::Ignored
+        );
+    }
 }
@@ -0,0 +1,318 @@
+//! Fuzzy subsequence matching for palette-style filtering.
+//!
+//! A needle matches a haystack when its graphemes appear in order in the
+//! haystack, ignoring case. Matches are scored Smith–Waterman style: every
+//! matched grapheme scores, gaps between matches cost, and matches at word
+//! boundaries, camelCase humps, and in consecutive runs earn bonuses. The
+//! alignment with the best score is reported, so typing `fb` finds
+//! `FooBar` before `fab bar`.
+//!
+//! # Example
+//!
+//! ```
+//! use opentui_rust::text::fuzzy::{match_score, rank};
+//!
+//! let found = match_score("fb", "FooBar").unwrap();
+//! assert_eq!(found.positions, [0, 3]);
+//!
+//! let ranked = rank("fb", ["fab bar", "FooBar", "baz"]);
+//! let order: Vec<usize> = ranked.iter().map(|(index, _)| *index).collect();
+//! assert_eq!(order, [1, 0]);
+//! ```
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::buffer::Span;
+use crate::style::Style;
+
+const SCORE_MATCH: i32 = 16;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+/// Match right after whitespace, punctuation, or at the start.
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+/// Uppercase after lowercase, or a digit after a letter.
+const BONUS_CAMEL: i32 = BONUS_BOUNDARY;
+/// Match right after the previous one, when it has no better bonus.
+const BONUS_CONSECUTIVE: i32 = PENALTY_GAP_START + PENALTY_GAP_EXTENSION;
+/// The first needle grapheme's bonus counts this many times.
+const BONUS_FIRST_MULTIPLIER: i32 = 2;
+
+/// Best alignment of a needle in a haystack.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better. Only comparable between matches of the same needle.
+    pub score: i32,
+    /// Byte offsets in the haystack of the matched graphemes, ascending.
+    /// Each is the start of a grapheme cluster.
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// Split `haystack` into spans, drawing the matched graphemes in
+    /// `matched` and the rest in `style`, for
+    /// [`OptimizedBuffer::draw_spans`](crate::OptimizedBuffer::draw_spans).
+    ///
+    /// `haystack` must be the string this match was made against.
+    #[must_use]
+    pub fn spans<'a>(&self, haystack: &'a str, style: Style, matched: Style) -> Vec<Span<'a>> {
+        let mut spans = Vec::new();
+        let mut positions = self.positions.iter().copied().peekable();
+        let mut start = 0;
+        let mut matching = false;
+        for (offset, _) in haystack.grapheme_indices(true) {
+            let matched_here = positions.next_if_eq(&offset).is_some();
+            if matched_here != matching && offset > start {
+                let style = if matching { matched } else { style };
+                spans.push(Span::new(&haystack[start..offset], style));
+                start = offset;
+            }
+            matching = matched_here;
+        }
+        if start < haystack.len() {
+            let style = if matching { matched } else { style };
+            spans.push(Span::new(&haystack[start..], style));
+        }
+        spans
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Whitespace,
+    Punctuation,
+    Lower,
+    Upper,
+    Digit,
+    /// Other letters and symbols, including emoji.
+    Other,
+}
+
+impl CharClass {
+    fn of(grapheme: &str) -> Self {
+        let Some(c) = grapheme.chars().next() else {
+            return Self::Other;
+        };
+        if c.is_whitespace() {
+            Self::Whitespace
+        } else if c.is_ascii_punctuation() {
+            Self::Punctuation
+        } else if c.is_lowercase() {
+            Self::Lower
+        } else if c.is_uppercase() {
+            Self::Upper
+        } else if c.is_numeric() {
+            Self::Digit
+        } else {
+            Self::Other
+        }
+    }
+
+    const fn is_word(self) -> bool {
+        !matches!(self, Self::Whitespace | Self::Punctuation)
+    }
+}
+
+/// Bonus for a match on a grapheme of class `class` following `prev`.
+fn bonus(prev: CharClass, class: CharClass) -> i32 {
+    match (prev, class) {
+        (prev, class) if class.is_word() && !prev.is_word() => BONUS_BOUNDARY,
+        (CharClass::Lower, CharClass::Upper)
+        | (CharClass::Lower | CharClass::Upper, CharClass::Digit) => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+/// Score `needle` as a case-insensitive subsequence of `haystack`.
+///
+/// Returns `None` if some needle grapheme can't be matched in order. An
+/// empty needle matches anything with score 0 and no positions.
+#[must_use]
+pub fn match_score(needle: &str, haystack: &str) -> Option<FuzzyMatch> {
+    let needle: Vec<String> = needle.graphemes(true).map(str::to_lowercase).collect();
+    if needle.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+    let hay: Vec<(usize, &str)> = haystack.grapheme_indices(true).collect();
+    let (n, m) = (needle.len(), hay.len());
+    if n > m {
+        return None;
+    }
+    let lowered: Vec<String> = hay.iter().map(|(_, g)| g.to_lowercase()).collect();
+    let bonuses: Vec<i32> = hay
+        .iter()
+        .scan(CharClass::Whitespace, |prev, (_, grapheme)| {
+            let class = CharClass::of(grapheme);
+            Some(bonus(std::mem::replace(prev, class), class))
+        })
+        .collect();
+
+    // score[i * m + j]: best score with needle[..=i] matched and needle[i]
+    // on hay[j]; from[i * m + j]: where needle[i - 1] was matched then.
+    let mut score = vec![None::<i32>; n * m];
+    let mut from = vec![0usize; n * m];
+    // Bonus of the run a match extends, kept for the rest of the run.
+    let mut run_bonus = vec![0i32; n * m];
+
+    for (j, lower) in lowered.iter().enumerate() {
+        if *lower == needle[0] {
+            score[j] = Some(SCORE_MATCH + bonuses[j] * BONUS_FIRST_MULTIPLIER);
+            run_bonus[j] = bonuses[j];
+        }
+    }
+    for i in 1..n {
+        let (prev_row, row) = score[(i - 1) * m..(i + 1) * m].split_at_mut(m);
+        // Best previous match ending two or more graphemes back, net of
+        // the gap penalty to reach `j`.
+        let mut gap_best: Option<(i32, usize)> = None;
+        for j in i..m {
+            if j >= 2 {
+                let extended = gap_best.map(|(s, k)| (s - PENALTY_GAP_EXTENSION, k));
+                let opened = prev_row[j - 2].map(|s| (s - PENALTY_GAP_START, j - 2));
+                gap_best = match (extended, opened) {
+                    (Some(a), Some(b)) => Some(if b.0 >= a.0 { b } else { a }),
+                    (a, b) => a.or(b),
+                };
+            }
+            if lowered[j] != needle[i] {
+                continue;
+            }
+            let consecutive = prev_row[j - 1].map(|s| {
+                let run = run_bonus[(i - 1) * m + j - 1]
+                    .max(bonuses[j])
+                    .max(BONUS_CONSECUTIVE);
+                (s + SCORE_MATCH + run, run)
+            });
+            let gapped = gap_best.map(|(s, _)| s + SCORE_MATCH + bonuses[j]);
+            let cell = i * m + j;
+            match (consecutive, gapped) {
+                (Some((c, run)), g) if g.is_none_or(|g| c >= g) => {
+                    row[j] = Some(c);
+                    from[cell] = j - 1;
+                    run_bonus[cell] = run;
+                }
+                (_, Some(g)) => {
+                    row[j] = Some(g);
+                    from[cell] = gap_best.map_or(0, |(_, k)| k);
+                    run_bonus[cell] = bonuses[j];
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Best end, preferring the earliest on ties.
+    let last = (n - 1) * m;
+    let (mut j, best) = (0..m)
+        .filter_map(|j| score[last + j].map(|s| (j, s)))
+        .fold(None, |best: Option<(usize, i32)>, (j, s)| match best {
+            Some((_, b)) if b >= s => best,
+            _ => Some((j, s)),
+        })?;
+    let mut positions = vec![0; n];
+    for i in (0..n).rev() {
+        positions[i] = hay[j].0;
+        j = from[i * m + j];
+    }
+    Some(FuzzyMatch {
+        score: best,
+        positions,
+    })
+}
+
+/// Match `needle` against every item, returning the indices and matches of
+/// those that match, best first. Equal scores keep item order.
+pub fn rank<I>(needle: &str, items: I) -> Vec<(usize, FuzzyMatch)>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut ranked: Vec<(usize, FuzzyMatch)> = items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, item)| match_score(needle, item.as_ref()).map(|m| (index, m)))
+        .collect();
+    ranked.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(needle: &str, items: &[&str]) -> Vec<usize> {
+        rank(needle, items).into_iter().map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_camel_case_beats_plain_letters() {
+        assert_eq!(order("fb", &["fab bar", "FooBar"]), [1, 0]);
+        assert_eq!(match_score("fb", "FooBar").unwrap().positions, [0, 3]);
+    }
+
+    #[test]
+    fn test_consecutive_run_beats_scattered() {
+        assert_eq!(order("abc", &["xaxbxcx", "xabcx"]), [1, 0]);
+        assert_eq!(match_score("abc", "xabcx").unwrap().positions, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_word_boundaries_pick_alignment() {
+        // `t` could match in "settings"; the boundary at "Toggle" wins.
+        let found = match_score("tt", "settings: Toggle Tour").unwrap();
+        assert_eq!(found.positions, [10, 17]);
+        assert_eq!(
+            order("gd", &["Go Dark", "Toggle Debug", "Go to Drawing"]),
+            [0, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_no_match_and_empty_needle() {
+        assert!(match_score("xyz", "FooBar").is_none());
+        assert!(match_score("oof", "foo").is_none());
+        assert_eq!(match_score("", "foo"), Some(FuzzyMatch::default()));
+        assert!(match_score("a", "").is_none());
+    }
+
+    #[test]
+    fn test_positions_on_grapheme_boundaries() {
+        let haystack = "👨‍👩‍👧 Family 🇯🇵 Photos";
+        let found = match_score("fp", haystack).unwrap();
+        let boundaries: Vec<usize> = haystack.grapheme_indices(true).map(|(i, _)| i).collect();
+        for position in &found.positions {
+            assert!(
+                boundaries.contains(position),
+                "{position} splits a grapheme"
+            );
+        }
+        assert_eq!(&haystack[found.positions[0]..][..1], "F");
+        assert_eq!(&haystack[found.positions[1]..][..1], "P");
+
+        // A needle grapheme matches a whole cluster, not one of its chars.
+        let flag = match_score("🇯🇵p", haystack).unwrap();
+        assert_eq!(flag.positions[0], haystack.find('🇯').unwrap());
+        assert!(match_score("👩", haystack).is_none());
+    }
+
+    #[test]
+    fn test_spans_split_matches() {
+        let haystack = "Go to Drawing";
+        let found = match_score("gtd", haystack).unwrap();
+        let spans = found.spans(haystack, Style::NONE, Style::bold());
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| (span.text.as_ref(), span.style == Style::bold()))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ("G", true),
+                ("o ", false),
+                ("t", true),
+                ("o ", false),
+                ("D", true),
+                ("rawing", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Incremental line layout for [`EditorView`](super::EditorView).
+//!
+//! Each source line is wrapped once and kept until an edit touches it; the
+//! buffer's [line edit log](TextBuffer::line_edits_since) says which lines
+//! that is. A prefix sum of visual rows maps a scroll position to a source
+//! line by binary search.
+//!
+//! Folded lines are part of the same model: they take no rows, so every
+//! row lookup skips them. Folds follow edits through the same log, and an
+//! edit to a folded line opens its fold.
+
+use std::ops::Range;
+
+use crate::text::TextBuffer;
+use crate::text::view::{VirtualLine, WrapMode, wrap_line};
+use crate::unicode::{WidthMethod, display_width_with_method};
+
+/// Layout cache counters, exposed through
+/// [`EditorView::layout_stats`](super::EditorView::layout_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LayoutStats {
+    /// Source lines wrapped during the last render.
+    pub lines_laid_out: usize,
+    /// Source lines whose layout the last render read.
+    pub lines_read: usize,
+    /// Source lines with a cached layout.
+    pub cached_lines: usize,
+    /// Visual rows in the whole buffer.
+    pub total_rows: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LayoutKey {
+    wrap_mode: WrapMode,
+    wrap_width: Option<usize>,
+    tab_width: u8,
+    width_method: WidthMethod,
+}
+
+/// Rows of one source line, as from [`wrap_line`].
+type LineRows = Vec<(usize, usize, usize)>;
+
+/// Per-line wrap layout, kept in step with a [`TextBuffer`] across renders.
+#[derive(Clone, Debug, Default)]
+pub struct LayoutCache {
+    key: Option<LayoutKey>,
+    revision: u64,
+    lines: Vec<Option<LineRows>>,
+    // First visual row of each line, then the total. Empty without wrapping
+    // or folds, where every line is one row.
+    row_starts: Vec<usize>,
+    rows_stale: bool,
+    // Hidden lines, sorted and neither overlapping nor touching.
+    folds: Vec<Range<usize>>,
+    stats: LayoutStats,
+}
+
+impl LayoutCache {
+    /// Bring the cache up to date with `buffer`, dropping the layout of
+    /// lines edited since the last sync.
+    ///
+    /// With wrapping on, every line needs a layout to count rows, so lines
+    /// that lost theirs are wrapped again here. Without it, lines are only
+    /// laid out when read.
+    pub fn sync(&mut self, buffer: &TextBuffer, wrap_mode: WrapMode, wrap_width: Option<usize>) {
+        self.stats.lines_laid_out = 0;
+        self.stats.lines_read = 0;
+
+        let key = LayoutKey {
+            wrap_mode,
+            wrap_width,
+            tab_width: buffer.tab_width(),
+            width_method: buffer.width_method(),
+        };
+        let line_count = buffer.len_lines();
+        self.catch_up(buffer);
+        if self.key != Some(key) {
+            self.reset(line_count);
+        }
+        self.key = Some(key);
+
+        if wrap_width.is_none() && self.folds.is_empty() {
+            self.row_starts.clear();
+        } else if self.rows_stale {
+            self.row_starts.clear();
+            self.row_starts.reserve(line_count + 1);
+            let mut total = 0;
+            for line in 0..line_count {
+                self.row_starts.push(total);
+                if self.is_folded(line) {
+                    continue;
+                }
+                total += if wrap_width.is_some() {
+                    self.layout(buffer, line).len()
+                } else {
+                    1
+                };
+            }
+            self.row_starts.push(total);
+        }
+        self.rows_stale = false;
+        self.stats.cached_lines = self.lines.iter().filter(|rows| rows.is_some()).count();
+        self.stats.total_rows = self.total_rows();
+    }
+
+    /// Counters from the last [`sync`](Self::sync) and the reads since.
+    #[must_use]
+    pub fn stats(&self) -> LayoutStats {
+        self.stats
+    }
+
+    /// Hide `lines`, joining any folds they overlap or touch.
+    pub fn fold(&mut self, buffer: &TextBuffer, lines: Range<usize>) {
+        self.catch_up(buffer);
+        let mut lines = lines.start..lines.end.min(buffer.len_lines());
+        if lines.is_empty() {
+            return;
+        }
+        self.folds.retain(|fold| {
+            let joins = fold.start <= lines.end && lines.start <= fold.end;
+            if joins {
+                lines = lines.start.min(fold.start)..lines.end.max(fold.end);
+            }
+            !joins
+        });
+        let at = self.folds.partition_point(|fold| fold.end < lines.start);
+        self.folds.insert(at, lines);
+        self.rows_stale = true;
+    }
+
+    /// Show `lines` again, opening every fold that overlaps them.
+    pub fn unfold(&mut self, buffer: &TextBuffer, lines: Range<usize>) {
+        self.catch_up(buffer);
+        let before = self.folds.len();
+        self.folds
+            .retain(|fold| fold.end <= lines.start || lines.end <= fold.start || lines.is_empty());
+        self.rows_stale |= self.folds.len() != before;
+    }
+
+    /// Hidden line ranges, in order.
+    pub fn folds(&mut self, buffer: &TextBuffer) -> &[Range<usize>] {
+        self.catch_up(buffer);
+        &self.folds
+    }
+
+    /// Whether `line` is hidden by a fold.
+    #[must_use]
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.fold_at(line).is_some()
+    }
+
+    /// Visual rows in the whole buffer.
+    #[must_use]
+    pub fn total_rows(&self) -> usize {
+        self.row_starts.last().copied().unwrap_or(self.lines.len())
+    }
+
+    /// The source line showing visual row `row`, and which of its rows it is.
+    #[must_use]
+    pub fn line_at_row(&self, row: usize) -> Option<(usize, usize)> {
+        if row >= self.total_rows() {
+            return None;
+        }
+        if self.row_starts.is_empty() {
+            return Some((row, 0));
+        }
+        let line = self.row_starts.partition_point(|&start| start <= row) - 1;
+        Some((line, row - self.row_starts[line]))
+    }
+
+    /// Up to `count` visual rows starting at `first_row`, laying out only
+    /// the lines they come from.
+    pub fn rows(
+        &mut self,
+        buffer: &TextBuffer,
+        first_row: usize,
+        count: usize,
+    ) -> Vec<VirtualLine> {
+        let mut out = Vec::with_capacity(count);
+        let Some((mut line, mut skip)) = self.line_at_row(first_row) else {
+            return out;
+        };
+        while out.len() < count && line < self.lines.len() {
+            if let Some(fold) = self.fold_at(line) {
+                line = fold.end;
+                continue;
+            }
+            let line_start = buffer.rope().char_to_byte(buffer.rope().line_to_char(line));
+            let rows = self.layout(buffer, line);
+            out.extend(rows.iter().skip(skip).take(count - out.len()).map(
+                |&(start, end, width)| VirtualLine {
+                    source_line: line,
+                    byte_start: line_start + start,
+                    byte_end: line_start + end,
+                    width,
+                    is_wrap: start > 0,
+                },
+            ));
+            skip = 0;
+            line += 1;
+        }
+        out
+    }
+
+    /// Visual `(row, col)` of a char offset, matching
+    /// [`TextBufferView::visual_position_for_offset`](super::TextBufferView::visual_position_for_offset)
+    /// when nothing is folded, or `None` if its line is folded.
+    pub fn position_of(
+        &mut self,
+        buffer: &TextBuffer,
+        char_offset: usize,
+    ) -> Option<(usize, usize)> {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let rope = buffer.rope();
+        let char_offset = char_offset.min(rope.len_chars());
+        let line = rope.char_to_line(char_offset);
+        if self.is_folded(line) {
+            return None;
+        }
+        let line_start = rope.char_to_byte(rope.line_to_char(line));
+        let byte = rope.char_to_byte(char_offset) - line_start;
+        let first_row = self.row_starts.get(line).copied().unwrap_or(line);
+
+        let rows = self.layout(buffer, line);
+        // A break lands on the row it starts, not the one it ends.
+        let index = rows
+            .iter()
+            .position(|&(_, end, _)| byte < end)
+            .unwrap_or(rows.len() - 1);
+        let (start, _, _) = rows[index];
+        if byte <= start {
+            return Some((first_row + index, 0));
+        }
+
+        let text = rope
+            .slice(rope.byte_to_char(line_start + start)..char_offset)
+            .to_string();
+        let tab_width = buffer.tab_width().max(1) as usize;
+        let method = buffer.width_method();
+        let mut col = 0;
+        for grapheme in text.graphemes(true) {
+            if grapheme == "\t" {
+                col += tab_width - col % tab_width;
+            } else {
+                col += display_width_with_method(grapheme, method);
+            }
+        }
+        Some((first_row + index, col))
+    }
+
+    /// The first visual row at or after the start of `line`: its own first
+    /// row, or the next shown line's if it is folded.
+    #[must_use]
+    pub fn first_row_of(&self, line: usize) -> usize {
+        if self.row_starts.is_empty() {
+            line.min(self.total_rows())
+        } else {
+            self.row_starts[line.min(self.row_starts.len() - 1)]
+        }
+    }
+
+    /// Apply the buffer's line edits since the last catch-up to the cached
+    /// layouts and the folds. A fold an edit touches is opened.
+    fn catch_up(&mut self, buffer: &TextBuffer) {
+        if self.revision == buffer.revision() {
+            return;
+        }
+        let line_count = buffer.len_lines();
+        if let Some(edits) = buffer.line_edits_since(self.revision) {
+            for edit in edits {
+                let end = (edit.start + edit.removed).min(self.lines.len());
+                let start = edit.start.min(end);
+                self.lines
+                    .splice(start..end, std::iter::repeat_n(None, edit.inserted));
+                let removed_end = edit.start + edit.removed;
+                self.folds.retain_mut(|fold| {
+                    if fold.end <= edit.start {
+                        true
+                    } else if fold.start >= removed_end {
+                        let shift = |line: usize| line - edit.removed + edit.inserted;
+                        *fold = shift(fold.start)..shift(fold.end);
+                        true
+                    } else {
+                        false
+                    }
+                });
+            }
+            self.rows_stale = true;
+        } else {
+            self.reset(line_count);
+            self.folds.clear();
+        }
+        // The log and the buffer disagree; start over rather than misdraw.
+        if self.lines.len() != line_count {
+            self.reset(line_count);
+        }
+        self.folds.retain(|fold| fold.end <= line_count);
+        self.revision = buffer.revision();
+    }
+
+    fn fold_at(&self, line: usize) -> Option<&Range<usize>> {
+        let at = self.folds.partition_point(|fold| fold.end <= line);
+        self.folds.get(at).filter(|fold| fold.contains(&line))
+    }
+
+    fn reset(&mut self, line_count: usize) {
+        self.lines.clear();
+        self.lines.resize(line_count, None);
+        self.rows_stale = true;
+    }
+
+    /// Layout of `line`, wrapping it if it has none cached.
+    fn layout(&mut self, buffer: &TextBuffer, line: usize) -> &LineRows {
+        self.stats.lines_read += 1;
+        let key = self.key.expect("layout read before sync");
+        let stats = &mut self.stats;
+        self.lines[line].get_or_insert_with(|| {
+            stats.lines_laid_out += 1;
+            let text = buffer.line(line).unwrap_or_default();
+            let text = text.trim_end_matches('\n').trim_end_matches('\r');
+            let tab_width = key.tab_width.max(1) as usize;
+            wrap_line(
+                text,
+                key.wrap_mode,
+                key.wrap_width,
+                tab_width,
+                key.width_method,
+            )
+        })
+    }
+}
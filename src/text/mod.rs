@@ -17,6 +17,7 @@
 //! - [`EditBuffer`]: Editable buffer with cursor movement and undo/redo
 //! - [`EditorView`]: Visual rendering with line numbers and selection
 //! - [`TextBufferView`]: Viewport configuration with wrapping modes
+//! - [`fuzzy`]: Subsequence matching for filtering lists as the user types
 //!
 //! # Examples
 //!
@@ -54,15 +55,20 @@
 mod buffer;
 mod edit;
 mod editor;
+pub mod fuzzy;
+mod layout;
 mod rope;
 mod segment;
+mod tagged;
 mod view;
 
-pub use buffer::TextBuffer;
-pub use edit::EditBuffer;
-pub use editor::{EditorView, VisualCursor};
+pub use buffer::{LineEdit, TextBuffer, Trimmed};
+pub use edit::{EditBuffer, PairConfig};
+pub use editor::{DEFAULT_MOUSE_SCROLL_LINES, EditorMouseAction, EditorView, VisualCursor};
+pub use layout::LayoutStats;
 pub use rope::RopeWrapper;
 pub use segment::StyledSegment;
+pub use tagged::StyleHandle;
 pub use view::{
     LineInfo, LocalSelection, Selection, TextBufferView, TextMeasure, Viewport, WrapMode,
 };
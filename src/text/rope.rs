@@ -1,7 +1,11 @@
 //! Rope wrapper using the ropey crate.
 
+use std::ops::{Bound, RangeBounds};
+
 use ropey::{Rope, RopeSlice};
 
+use crate::error::{Error, Result};
+
 /// Wrapper around ropey::Rope with convenience methods.
 #[derive(Clone, Debug, Default)]
 pub struct RopeWrapper {
@@ -74,18 +78,55 @@ impl RopeWrapper {
     }
 
     /// Insert text at a character position.
-    pub fn insert(&mut self, char_idx: usize, text: &str) {
-        if char_idx <= self.len_chars() {
-            self.rope.insert(char_idx, text);
+    ///
+    /// Returns [`Error::OutOfBounds`] if `char_idx` is past the end.
+    pub fn insert(&mut self, char_idx: usize, text: &str) -> Result<()> {
+        let len = self.len_chars();
+        if char_idx > len {
+            return Err(Error::OutOfBounds {
+                what: "char index",
+                index: char_idx,
+                len,
+            });
         }
+        self.rope.insert(char_idx, text);
+        Ok(())
     }
 
     /// Remove a range of characters.
-    pub fn remove<R>(&mut self, range: R)
+    ///
+    /// Returns [`Error::InvalidInput`] for a reversed range and
+    /// [`Error::OutOfBounds`] if it ends past the end; the rope is unchanged
+    /// either way.
+    pub fn remove<R>(&mut self, range: R) -> Result<()>
     where
-        R: std::ops::RangeBounds<usize>,
+        R: RangeBounds<usize>,
     {
-        self.rope.remove(range);
+        let len = self.len_chars();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        if start > end {
+            return Err(Error::InvalidInput(format!(
+                "char range {start}..{end} is reversed"
+            )));
+        }
+        if end > len {
+            return Err(Error::OutOfBounds {
+                what: "char range end",
+                index: end,
+                len,
+            });
+        }
+        self.rope.remove(start..end);
+        Ok(())
     }
 
     /// Replace the entire contents.
@@ -179,14 +220,35 @@ mod tests {
     #[test]
     fn test_rope_insert() {
         let mut rope = RopeWrapper::from_str("Hello!");
-        rope.insert(5, ", world");
+        rope.insert(5, ", world").unwrap();
         assert_eq!(rope.to_string(), "Hello, world!");
     }
 
     #[test]
     fn test_rope_remove() {
         let mut rope = RopeWrapper::from_str("Hello, world!");
-        rope.remove(5..12);
+        rope.remove(5..12).unwrap();
         assert_eq!(rope.to_string(), "Hello!");
     }
+
+    #[test]
+    fn test_rope_rejects_invalid_ranges() {
+        let mut rope = RopeWrapper::from_str("Hello");
+        assert!(matches!(
+            rope.insert(6, "!"),
+            Err(Error::OutOfBounds {
+                what: "char index",
+                index: 6,
+                len: 5
+            })
+        ));
+        assert!(matches!(
+            rope.remove(3..=5),
+            Err(Error::OutOfBounds { index: 6, .. })
+        ));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = rope.remove(4..2);
+        assert!(matches!(reversed, Err(Error::InvalidInput(_))));
+        assert_eq!(rope.to_string(), "Hello");
+    }
 }
@@ -0,0 +1,186 @@
+//! Tagged style ranges, composed into a minimal list of segments.
+//!
+//! Styles added with [`TextBuffer::add_style`](super::TextBuffer::add_style)
+//! are kept as added, so each can be removed exactly, and composed into
+//! sorted, non-overlapping segments for rendering. Overlapping styles split
+//! segments where they start and end; adjacent segments with equal styles
+//! are coalesced.
+//!
+//! Where styles overlap they compose like highlight layers (see
+//! [`crate::highlight::layer`]): in ascending tag priority, then in the
+//! order they were added, each [merged](Style::merge) over the ones below.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::highlight::diagnostic::shift_range;
+use crate::style::Style;
+
+/// Handle to a style added with [`TextBuffer::add_style`](super::TextBuffer::add_style).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StyleHandle(u64);
+
+#[derive(Clone, Debug)]
+struct TaggedStyle {
+    handle: StyleHandle,
+    range: Range<usize>,
+    style: Style,
+    tag: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TaggedStyles {
+    // In the order added, which is handle order
+    entries: Vec<TaggedStyle>,
+    priorities: HashMap<u32, u8>,
+    // Composed styles, in char offsets: sorted, non-empty, non-overlapping,
+    // and adjacent segments differ in style
+    segments: Vec<(Range<usize>, Style)>,
+    next_handle: u64,
+}
+
+impl TaggedStyles {
+    pub fn add(&mut self, range: Range<usize>, style: Style, tag: u32) -> StyleHandle {
+        let handle = StyleHandle(self.next_handle);
+        self.next_handle += 1;
+        if !range.is_empty() {
+            self.entries.push(TaggedStyle {
+                handle,
+                range: range.clone(),
+                style,
+                tag,
+            });
+            self.resolve(range);
+        }
+        handle
+    }
+
+    pub fn remove(&mut self, handle: StyleHandle) -> bool {
+        let Ok(index) = self
+            .entries
+            .binary_search_by_key(&handle.0, |entry| entry.handle.0)
+        else {
+            return false;
+        };
+        let entry = self.entries.remove(index);
+        self.resolve(entry.range);
+        true
+    }
+
+    pub fn clear_tag(&mut self, tag: u32) {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.tag != tag);
+        if self.entries.len() != before {
+            self.rebuild();
+        }
+    }
+
+    pub fn set_priority(&mut self, tag: u32, priority: u8) {
+        if self.priorities.insert(tag, priority).unwrap_or(0) != priority {
+            self.rebuild();
+        }
+    }
+
+    pub fn priority(&self, tag: u32) -> u8 {
+        self.priorities.get(&tag).copied().unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.segments.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn segments(&self) -> &[(Range<usize>, Style)] {
+        &self.segments
+    }
+
+    pub fn style_at(&self, offset: usize) -> Option<Style> {
+        let index = self
+            .segments
+            .partition_point(|(range, _)| range.end <= offset);
+        self.segments
+            .get(index)
+            .filter(|(range, _)| range.start <= offset)
+            .map(|(_, style)| *style)
+    }
+
+    /// Shift ranges for an edit that replaced `removed` chars at `offset`
+    /// with `inserted` chars, dropping styles the edit deleted.
+    pub fn apply_edit(&mut self, offset: usize, removed: usize, inserted: usize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        self.entries.retain_mut(|entry| {
+            shift_range(&mut entry.range, offset, removed, inserted) && !entry.range.is_empty()
+        });
+        // Shifting keeps segments sorted and apart; only the ones touching
+        // the edit may need splitting or coalescing again.
+        self.segments.retain_mut(|(range, _)| {
+            shift_range(range, offset, removed, inserted) && range.start < range.end
+        });
+        self.resolve(offset..offset + inserted);
+    }
+
+    fn rebuild(&mut self) {
+        self.segments.clear();
+        self.resolve(0..usize::MAX);
+    }
+
+    /// Recompose the segments over `span`, widened to the segments touching
+    /// it so they coalesce with the new ones.
+    fn resolve(&mut self, span: Range<usize>) {
+        let first = self
+            .segments
+            .partition_point(|(range, _)| range.end < span.start);
+        let last = self
+            .segments
+            .partition_point(|(range, _)| range.start <= span.end);
+        let (start, end) = if first < last {
+            (
+                self.segments[first].0.start.min(span.start),
+                self.segments[last - 1].0.end.max(span.end),
+            )
+        } else {
+            (span.start, span.end)
+        };
+
+        let mut covering: Vec<&TaggedStyle> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.range.start < end && start < entry.range.end)
+            .collect();
+        // Stable, so equal priorities stay in the order added.
+        covering.sort_by_key(|entry| self.priority(entry.tag));
+
+        let mut cuts: Vec<usize> = covering
+            .iter()
+            .flat_map(|entry| [entry.range.start.max(start), entry.range.end.min(end)])
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let mut pieces: Vec<(Range<usize>, Style)> = Vec::new();
+        for window in cuts.windows(2) {
+            let (piece_start, piece_end) = (window[0], window[1]);
+            let Some(style) = covering
+                .iter()
+                .filter(|entry| entry.range.start <= piece_start && piece_end <= entry.range.end)
+                .map(|entry| entry.style)
+                .reduce(Style::merge)
+            else {
+                continue;
+            };
+            match pieces.last_mut() {
+                Some((range, last)) if range.end == piece_start && *last == style => {
+                    range.end = piece_end;
+                }
+                _ => pieces.push((piece_start..piece_end, style)),
+            }
+        }
+        self.segments.splice(first..last, pieces);
+    }
+}
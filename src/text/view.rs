@@ -19,10 +19,12 @@
 use crate::buffer::OptimizedBuffer;
 use crate::cell::{Cell, CellContent, GraphemeId};
 use crate::color::Rgba;
+use crate::highlight::HighlightLayers;
 use crate::style::Style;
-use crate::text::TextBuffer;
-use crate::unicode::{display_width_char_with_method, display_width_with_method};
+use crate::text::{TextBuffer, Trimmed};
+use crate::unicode::{WidthMethod, display_width_char_with_method, display_width_with_method};
 use std::cell::RefCell;
+use std::ops::Range;
 
 /// Text wrapping mode.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -34,6 +36,19 @@ pub enum WrapMode {
     Char,
     /// Wrap at word boundaries.
     Word,
+    /// Keep each line on one row, cut off at the viewport edge.
+    Truncate {
+        /// Draw `…` in the last column when the line continues past it.
+        ellipsis: bool,
+    },
+}
+
+impl WrapMode {
+    /// Whether long lines continue on further rows.
+    #[must_use]
+    pub const fn wraps(self) -> bool {
+        matches!(self, Self::Char | Self::Word)
+    }
 }
 
 /// Viewport configuration.
@@ -143,21 +158,132 @@ pub struct TextBufferView<'a> {
     wrap_width: Option<u32>,
     scroll_x: u32,
     scroll_y: u32,
+    following: bool,
     selection: Option<Selection>,
     local_selection: Option<LocalSelection>,
+    layers: Option<&'a HighlightLayers>,
     tab_indicator: Option<char>,
     tab_indicator_color: Rgba,
     truncate: bool,
     line_cache: RefCell<Option<LineCache>>,
 }
 
-#[derive(Clone, Debug)]
-struct VirtualLine {
-    source_line: usize,
-    byte_start: usize,
-    byte_end: usize,
-    width: usize,
-    is_wrap: bool,
+/// One visual row of a source line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VirtualLine {
+    pub source_line: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub width: usize,
+    pub is_wrap: bool,
+}
+
+/// Split one line (without its line break) into rows of at most
+/// `wrap_width` columns, as `(byte_start, byte_end, width)` within the line.
+///
+/// There is always at least one row. Word wrapping breaks after whitespace.
+/// In both wrapping modes, whitespace that doesn't fit hangs off the end of
+/// its row rather than starting the next one.
+pub fn wrap_line(
+    line: &str,
+    wrap_mode: WrapMode,
+    wrap_width: Option<usize>,
+    tab_width: usize,
+    method: WidthMethod,
+) -> Vec<(usize, usize, usize)> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    if line.is_empty() {
+        return vec![(0, 0, 0)];
+    }
+    let Some(wrap_width) = wrap_width else {
+        return vec![(0, line.len(), display_width_with_method(line, method))];
+    };
+
+    let mut rows = Vec::new();
+    let graphemes: Vec<(usize, &str)> = line.grapheme_indices(true).collect();
+    let mut start_byte = 0usize;
+    let mut current_width = 0usize;
+    let mut last_break: Option<(usize, usize, usize)> = None; // (break_byte, width, index)
+    let mut i = 0usize;
+
+    while i < graphemes.len() {
+        let (byte_idx, grapheme) = graphemes[i];
+        if byte_idx < start_byte {
+            i += 1;
+            continue;
+        }
+
+        let g_width = if grapheme == "\t" {
+            let offset = current_width % tab_width;
+            tab_width - offset
+        } else {
+            display_width_with_method(grapheme, method)
+        };
+
+        let is_ws = grapheme.chars().all(|c| c.is_whitespace());
+        if wrap_mode == WrapMode::Word && is_ws {
+            last_break = Some((byte_idx + grapheme.len(), current_width + g_width, i + 1));
+        }
+
+        if current_width + g_width > wrap_width && current_width > 0 && is_ws {
+            // Whitespace that doesn't fit hangs off the row instead of
+            // starting the next one.
+            let next = graphemes[i..]
+                .iter()
+                .position(|(_, g)| !g.chars().all(|c| c.is_whitespace()))
+                .map_or(graphemes.len(), |len| i + len);
+            let end_byte = graphemes.get(next).map_or(line.len(), |&(b, _)| b);
+            rows.push((start_byte, end_byte, current_width));
+            start_byte = end_byte;
+            current_width = 0;
+            last_break = None;
+            i = next;
+            continue;
+        }
+
+        if current_width + g_width > wrap_width && current_width > 0 {
+            let (break_byte, break_width, break_index) = if wrap_mode == WrapMode::Word {
+                last_break.unwrap_or((byte_idx, current_width, i))
+            } else {
+                (byte_idx, current_width, i)
+            };
+
+            rows.push((start_byte, break_byte, break_width));
+
+            start_byte = break_byte;
+            current_width = 0;
+            last_break = None;
+            i = break_index;
+
+            if wrap_mode == WrapMode::Word {
+                while i < graphemes.len() {
+                    let (b, g) = graphemes[i];
+                    if b < start_byte {
+                        i += 1;
+                        continue;
+                    }
+                    if g.chars().all(|c| c.is_whitespace()) {
+                        start_byte = b + g.len();
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            continue;
+        }
+
+        current_width += g_width;
+        i += 1;
+    }
+
+    if start_byte < line.len() || rows.is_empty() {
+        rows.push((start_byte, line.len(), current_width));
+    }
+
+    rows
 }
 
 /// Cached line layout information for wrapped text.
@@ -273,8 +399,10 @@ impl<'a> TextBufferView<'a> {
             wrap_width: None,
             scroll_x: 0,
             scroll_y: 0,
+            following: false,
             selection: None,
             local_selection: None,
+            layers: None,
             tab_indicator: None,
             tab_indicator_color: Rgba::WHITE,
             truncate: false,
@@ -314,6 +442,18 @@ impl<'a> TextBufferView<'a> {
         self
     }
 
+    /// Start in follow mode: the view stays on the last page as the buffer
+    /// grows, until scrolled up.
+    ///
+    /// A view borrows its buffer, so a pager rebuilds it after each append
+    /// and carries [`scroll_position`](Self::scroll_position) and
+    /// [`is_following`](Self::is_following) over from the previous frame.
+    #[must_use]
+    pub fn follow(mut self, enabled: bool) -> Self {
+        self.following = enabled;
+        self
+    }
+
     /// Set tab indicator character and color.
     #[must_use]
     pub fn tab_indicator(mut self, ch: char, color: Rgba) -> Self {
@@ -329,6 +469,26 @@ impl<'a> TextBufferView<'a> {
         self
     }
 
+    /// Compose highlight layers (search, selection, ...) over buffer styles.
+    ///
+    /// Layer ranges are char offsets into the buffer. They are applied before
+    /// [`set_selection`](Self::set_selection) and local selections.
+    #[must_use]
+    pub fn highlight_layers(mut self, layers: &'a HighlightLayers) -> Self {
+        self.layers = Some(layers);
+        self
+    }
+
+    /// Buffer style at a char offset with highlight layers composed over it.
+    fn layered_style_at(&self, char_offset: usize) -> Style {
+        let byte_offset = self.buffer.rope().char_to_byte(char_offset);
+        let style = self.buffer.style_at(byte_offset);
+        match self.layers {
+            Some(layers) => layers.style_at(char_offset, style),
+            None => style,
+        }
+    }
+
     /// Set selection.
     pub fn set_selection(&mut self, start: usize, end: usize, style: Style) {
         self.selection = Some(Selection::new(start, end, style));
@@ -380,7 +540,7 @@ impl<'a> TextBufferView<'a> {
     }
 
     fn effective_wrap_width(&self) -> Option<usize> {
-        if self.wrap_mode == WrapMode::None || self.viewport.width == 0 {
+        if !self.wrap_mode.wraps() || self.viewport.width == 0 {
             return None;
         }
         let width = self.wrap_width.unwrap_or(self.viewport.width).max(1);
@@ -388,7 +548,7 @@ impl<'a> TextBufferView<'a> {
     }
 
     fn effective_wrap_width_for(&self, width: Option<u32>) -> Option<usize> {
-        if self.wrap_mode == WrapMode::None {
+        if !self.wrap_mode.wraps() {
             return None;
         }
         let base_width = width.unwrap_or(self.viewport.width);
@@ -447,8 +607,6 @@ impl<'a> TextBufferView<'a> {
     }
 
     fn build_virtual_lines_for(&self, wrap_width: Option<usize>) -> Vec<VirtualLine> {
-        use unicode_segmentation::UnicodeSegmentation;
-
         let mut lines = Vec::new();
         let method = self.buffer.width_method();
         let tab_width = self.buffer.tab_width().max(1) as usize;
@@ -462,107 +620,14 @@ impl<'a> TextBufferView<'a> {
             let line_start_char = self.buffer.rope().line_to_char(line_idx);
             let line_start_byte = self.buffer.rope().char_to_byte(line_start_char);
 
-            if line.is_empty() {
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte,
-                    byte_end: line_start_byte,
-                    width: 0,
-                    is_wrap: false,
-                });
-                continue;
-            }
-
-            let Some(wrap_width) = wrap_width else {
-                let width = display_width_with_method(line, method);
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte,
-                    byte_end: line_start_byte + line.len(),
-                    width,
-                    is_wrap: false,
-                });
-                continue;
-            };
-
-            let graphemes: Vec<(usize, &str)> = line.grapheme_indices(true).collect();
-            let mut start_byte = 0usize;
-            let mut current_width = 0usize;
-            let mut last_break: Option<(usize, usize, usize)> = None; // (break_byte, width, index)
-            let mut i = 0usize;
-
-            while i < graphemes.len() {
-                let (byte_idx, grapheme) = graphemes[i];
-                if byte_idx < start_byte {
-                    i += 1;
-                    continue;
-                }
-
-                let g_width = if grapheme == "\t" {
-                    let offset = current_width % tab_width;
-                    tab_width - offset
-                } else {
-                    display_width_with_method(grapheme, method)
-                };
-
-                let is_ws = grapheme.chars().all(|c| c.is_whitespace());
-                if self.wrap_mode == WrapMode::Word && is_ws {
-                    last_break = Some((byte_idx + grapheme.len(), current_width + g_width, i + 1));
-                }
-
-                if current_width + g_width > wrap_width && current_width > 0 {
-                    let (break_byte, break_width, break_index) = if self.wrap_mode == WrapMode::Word
-                    {
-                        last_break.unwrap_or((byte_idx, current_width, i))
-                    } else {
-                        (byte_idx, current_width, i)
-                    };
-
-                    lines.push(VirtualLine {
-                        source_line: line_idx,
-                        byte_start: line_start_byte + start_byte,
-                        byte_end: line_start_byte + break_byte,
-                        width: break_width,
-                        is_wrap: start_byte > 0,
-                    });
-
-                    start_byte = break_byte;
-                    current_width = 0;
-                    last_break = None;
-                    i = break_index;
-
-                    if self.wrap_mode == WrapMode::Word {
-                        while i < graphemes.len() {
-                            let (b, g) = graphemes[i];
-                            if b < start_byte {
-                                i += 1;
-                                continue;
-                            }
-                            if g.chars().all(|c| c.is_whitespace()) {
-                                start_byte = b + g.len();
-                                i += 1;
-                            } else {
-                                break;
-                            }
-                        }
-                    }
-
-                    continue;
-                }
-
-                current_width += g_width;
-                i += 1;
-            }
-
-            if start_byte <= line.len() {
-                lines.push(VirtualLine {
-                    source_line: line_idx,
-                    byte_start: line_start_byte + start_byte,
-                    byte_end: line_start_byte + line.len(),
-                    width: current_width,
-                    is_wrap: start_byte > 0,
-                });
-            }
+            let rows = wrap_line(line, self.wrap_mode, wrap_width, tab_width, method);
+            lines.extend(rows.into_iter().map(|(start, end, width)| VirtualLine {
+                source_line: line_idx,
+                byte_start: line_start_byte + start,
+                byte_end: line_start_byte + end,
+                width,
+                is_wrap: start > 0,
+            }));
         }
 
         lines
@@ -620,6 +685,127 @@ impl<'a> TextBufferView<'a> {
         (0, 0)
     }
 
+    /// Current scroll position as `(x, y)`, with `y` in virtual lines.
+    ///
+    /// In follow mode this is the last page, wherever `scroll` left it.
+    #[must_use]
+    pub fn scroll_position(&self) -> (u32, u32) {
+        (self.scroll_x, self.top_line() as u32)
+    }
+
+    /// Whether the view is following the end of the buffer.
+    #[must_use]
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+
+    /// Scroll by `delta` virtual lines (negative scrolls up), clamped to
+    /// the content.
+    ///
+    /// Scrolling up leaves follow mode. Scrolling down onto the last page
+    /// enters it, so the view sticks to the end while it is at the end.
+    pub fn scroll_lines(&mut self, delta: i32) {
+        let max = self.max_scroll_y();
+        let top = self
+            .top_line()
+            .saturating_add_signed(delta as isize)
+            .min(max);
+        self.scroll_y = top as u32;
+        if delta < 0 || (delta > 0 && top == max) {
+            self.following = delta > 0;
+        }
+    }
+
+    /// Scroll by `delta` viewport heights.
+    pub fn scroll_pages(&mut self, delta: i32) {
+        let page = self.viewport.height.max(1) as i32;
+        self.scroll_lines(delta.saturating_mul(page));
+    }
+
+    /// Scroll so source line `line` is at the top of the viewport, as far
+    /// as the content allows. Leaves follow mode.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        let cache = self.line_cache();
+        let top = cache
+            .info
+            .source_to_virtual(line)
+            .unwrap_or(cache.virtual_lines.len());
+        drop(cache);
+        self.scroll_y = top.min(self.max_scroll_y()) as u32;
+        self.following = false;
+    }
+
+    /// Scroll to the last page and enter follow mode.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_y = self.max_scroll_y() as u32;
+        self.following = true;
+    }
+
+    /// Move the scroll position and selection back over text trimmed from
+    /// the front of a capped buffer, so the content on screen stays put.
+    ///
+    /// The scroll position moves one row per trimmed line; with wrapping on,
+    /// trimmed lines that wrapped leave a view that isn't following a few
+    /// rows further down. A selection that was entirely trimmed is cleared.
+    pub fn rebase(&mut self, trimmed: Trimmed) {
+        if !self.following {
+            self.scroll_y = self.scroll_y.saturating_sub(trimmed.lines as u32);
+        }
+        if let Some(selection) = &mut self.selection {
+            selection.start = selection.start.saturating_sub(trimmed.chars);
+            selection.end = selection.end.saturating_sub(trimmed.chars);
+            if selection.is_empty() {
+                self.selection = None;
+            }
+        }
+    }
+
+    /// Whether the last virtual line is within the viewport.
+    #[must_use]
+    pub fn is_at_bottom(&self) -> bool {
+        self.top_line() >= self.max_scroll_y()
+    }
+
+    /// Source lines with at least one row in the viewport.
+    #[must_use]
+    pub fn visible_line_range(&self) -> Range<usize> {
+        let cache = self.line_cache();
+        let top = self.top_line();
+        let bottom = (top + self.viewport.height as usize).min(cache.virtual_lines.len());
+        if top >= bottom {
+            return 0..0;
+        }
+        cache.virtual_lines[top].source_line..cache.virtual_lines[bottom - 1].source_line + 1
+    }
+
+    /// Source line shown on viewport row `y`, or `None` past the content.
+    #[must_use]
+    pub fn line_at_view_row(&self, y: u32) -> Option<usize> {
+        if y >= self.viewport.height {
+            return None;
+        }
+        let row = self.top_line() + y as usize;
+        self.line_cache()
+            .virtual_lines
+            .get(row)
+            .map(|vline| vline.source_line)
+    }
+
+    /// Largest scroll offset that still fills the viewport.
+    fn max_scroll_y(&self) -> usize {
+        self.virtual_line_count()
+            .saturating_sub(self.viewport.height as usize)
+    }
+
+    /// First virtual line in the viewport.
+    fn top_line(&self) -> usize {
+        if self.following {
+            self.max_scroll_y()
+        } else {
+            self.scroll_y as usize
+        }
+    }
+
     /// Calculate the number of virtual lines (accounting for wrapping).
     #[must_use]
     pub fn virtual_line_count(&self) -> usize {
@@ -704,15 +890,42 @@ impl<'a> TextBufferView<'a> {
         output: &mut OptimizedBuffer,
         dest_x: i32,
         dest_y: i32,
-        mut pool: Option<&mut crate::grapheme_pool::GraphemePool>,
+        pool: Option<&mut crate::grapheme_pool::GraphemePool>,
     ) {
         let cache = self.line_cache();
         let virtual_lines = &cache.virtual_lines;
-        let start_line = self.scroll_y as usize;
+        let start_line = self.top_line();
         let end_line = (start_line + self.viewport.height as usize).min(virtual_lines.len());
+        self.render_rows_impl(
+            output,
+            dest_x,
+            dest_y,
+            &virtual_lines[start_line..end_line],
+            pool,
+        );
+    }
 
-        for (row_offset, vline_idx) in (start_line..end_line).enumerate() {
-            let vline = &virtual_lines[vline_idx];
+    /// Render already laid-out rows from the top of the viewport, for
+    /// callers that cache layout across frames.
+    pub(crate) fn render_rows(
+        &self,
+        output: &mut OptimizedBuffer,
+        dest_x: i32,
+        dest_y: i32,
+        rows: &[VirtualLine],
+    ) {
+        self.render_rows_impl(output, dest_x, dest_y, rows, None);
+    }
+
+    fn render_rows_impl(
+        &self,
+        output: &mut OptimizedBuffer,
+        dest_x: i32,
+        dest_y: i32,
+        rows: &[VirtualLine],
+        mut pool: Option<&mut crate::grapheme_pool::GraphemePool>,
+    ) {
+        for (row_offset, vline) in rows.iter().enumerate() {
             let dest_row = dest_y + row_offset as i32;
             if dest_row < 0 {
                 continue;
@@ -757,9 +970,14 @@ impl<'a> TextBufferView<'a> {
         let local_sel = self.local_selection;
 
         let max_col = self.scroll_x + self.viewport.width;
+        // First char at or past the column an ellipsis would cover
+        let mut hidden_from = None;
 
         let mut global_char_offset = char_start;
         for grapheme in line.graphemes(true) {
+            if hidden_from.is_none() && col + 1 >= max_col {
+                hidden_from = Some(global_char_offset);
+            }
             // Optimization: Stop if we've gone past the viewport
             if col >= max_col {
                 break;
@@ -769,8 +987,7 @@ impl<'a> TextBufferView<'a> {
                 let tab_width = self.buffer.tab_width().max(1) as u32;
                 let spaces_to_next = tab_width - (col % tab_width);
                 // Get the actual style at this position (preserves syntax highlighting)
-                let byte_offset = rope.char_to_byte(global_char_offset);
-                let base_style = self.buffer.style_at(byte_offset);
+                let base_style = self.layered_style_at(global_char_offset);
 
                 for space_idx in 0..spaces_to_next {
                     // Optimization: Skip if before scroll position
@@ -824,8 +1041,7 @@ impl<'a> TextBufferView<'a> {
                 continue;
             }
 
-            let byte_offset = rope.char_to_byte(global_char_offset);
-            let style = self.buffer.style_at(byte_offset);
+            let style = self.layered_style_at(global_char_offset);
             let (content, width) = if grapheme.chars().count() == 1 {
                 let ch = grapheme.chars().next().unwrap();
                 let w = display_width_char_with_method(ch, method);
@@ -844,6 +1060,8 @@ impl<'a> TextBufferView<'a> {
                 fg: style.fg.unwrap_or(Rgba::WHITE),
                 bg: style.bg.unwrap_or(Rgba::TRANSPARENT),
                 attributes: style.attributes,
+                underline_style: style.underline_style,
+                underline_color: style.underline_color,
             };
 
             // Optimization: Skip if completely before scroll position
@@ -900,17 +1118,22 @@ impl<'a> TextBufferView<'a> {
             global_char_offset += grapheme.chars().count();
         }
 
-        if self.truncate && self.wrap_mode == WrapMode::None {
-            let max_cols = self.viewport.width as i32;
-            if vline.width as i32 > max_cols && max_cols > 0 {
-                let ellipsis_col = dest_x + (max_cols - 1);
-                if ellipsis_col >= 0 {
-                    output.set(
-                        ellipsis_col as u32,
-                        dest_y,
-                        Cell::new('…', self.buffer.default_style()),
-                    );
+        let ellipsis = match self.wrap_mode {
+            WrapMode::None => self.truncate,
+            WrapMode::Truncate { ellipsis } => ellipsis,
+            WrapMode::Char | WrapMode::Word => false,
+        };
+        if ellipsis && vline.width as u32 > max_col && self.viewport.width > 0 {
+            let ellipsis_col = dest_x + (self.viewport.width as i32 - 1);
+            if ellipsis_col >= 0 {
+                let mut cell = Cell::new('…', self.buffer.default_style());
+                // Show that the selection reaches into the hidden part.
+                if let (Some(sel), Some(hidden_from)) = (selection, hidden_from) {
+                    if sel.start < char_end && sel.end > hidden_from {
+                        cell.apply_style(sel.style);
+                    }
                 }
+                output.set(ellipsis_col as u32, dest_y, cell);
             }
         }
     }
@@ -1025,6 +1248,246 @@ mod tests {
         eprintln!("[TEST] PASS: Wrapped line byte ranges are correct");
     }
 
+    /// A pager frame: a fresh view carrying the previous frame's scroll state.
+    fn pager_view(
+        buffer: &TextBuffer,
+        wrap: Option<u32>,
+        (scroll_y, following): (u32, bool),
+    ) -> TextBufferView<'_> {
+        let view = TextBufferView::new(buffer)
+            .viewport(0, 0, wrap.unwrap_or(80), 5)
+            .scroll(0, scroll_y)
+            .follow(following);
+        match wrap {
+            Some(_) => view.wrap_mode(WrapMode::Char),
+            None => view,
+        }
+    }
+
+    fn scroll_state(view: &TextBufferView<'_>) -> (u32, bool) {
+        (view.scroll_position().1, view.is_following())
+    }
+
+    #[test]
+    fn test_follow_tracks_appends() {
+        for wrap in [None, Some(12), Some(5)] {
+            let mut buffer = TextBuffer::with_text("line 0");
+            let mut state = (0, true);
+            for i in 1..40 {
+                buffer.append(&format!("\nline {i}{}", "+".repeat(i % 7)));
+                let view = pager_view(&buffer, wrap, state);
+                assert!(view.is_at_bottom(), "wrap {wrap:?}, line {i}");
+                assert_eq!(view.visible_line_range().end, i + 1, "wrap {wrap:?}");
+                let last_row = view.virtual_line_count().min(5) as u32 - 1;
+                assert_eq!(view.line_at_view_row(last_row), Some(i), "wrap {wrap:?}");
+                assert_eq!(view.line_at_view_row(last_row + 1), None);
+                state = scroll_state(&view);
+            }
+        }
+    }
+
+    #[test]
+    fn test_follow_disengages_and_reengages() {
+        let text: Vec<String> = (0..20).map(|i| format!("line {i}")).collect();
+        let mut buffer = TextBuffer::with_text(&text.join("\n"));
+
+        let mut view = pager_view(&buffer, None, (0, false));
+        assert!(!view.is_at_bottom());
+        view.scroll_to_bottom();
+        assert!(view.is_following());
+        assert_eq!(view.visible_line_range(), 15..20);
+
+        // Scrolling up leaves follow mode; appends no longer move the view.
+        view.scroll_lines(-2);
+        assert!(!view.is_following());
+        assert_eq!(view.visible_line_range(), 13..18);
+        let state = scroll_state(&view);
+        buffer.append("\nline 20\nline 21");
+        let mut view = pager_view(&buffer, None, state);
+        assert_eq!(view.visible_line_range(), 13..18);
+        assert!(!view.is_at_bottom());
+
+        view.scroll_pages(-1);
+        assert_eq!(view.visible_line_range(), 8..13);
+        view.scroll_pages(-10);
+        assert_eq!(view.scroll_position(), (0, 0));
+        view.scroll_to_line(3);
+        assert_eq!(view.line_at_view_row(0), Some(3));
+
+        // Back to the end re-engages, and appends are tracked again.
+        view.scroll_to_bottom();
+        assert_eq!(view.visible_line_range(), 17..22);
+        let state = scroll_state(&view);
+        buffer.append("\nline 22");
+        let mut view = pager_view(&buffer, None, state);
+        assert_eq!(view.visible_line_range(), 18..23);
+
+        // Scrolling down onto the last page re-engages too.
+        view.scroll_lines(-1);
+        assert!(!view.is_following());
+        view.scroll_lines(100);
+        assert!(view.is_following());
+        assert_eq!(view.scroll_position(), (0, 18));
+    }
+
+    #[test]
+    fn test_rebase_keeps_content_in_place() {
+        let mut buffer = TextBuffer::with_max_lines(10);
+        for i in 0..10 {
+            buffer.append_line(&format!("line {i}"));
+        }
+        let mut view = pager_view(&buffer, None, (0, false));
+        view.scroll_to_line(4);
+        let start = buffer.rope().line_to_char(5);
+        view.set_selection(start, start + 6, Style::NONE);
+        let (state, mark) = (scroll_state(&view), buffer.trimmed());
+
+        buffer.append_line("line 10");
+        buffer.append_line("line 11");
+        let mut view = pager_view(&buffer, None, state);
+        view.set_selection(start, start + 6, Style::NONE);
+        view.rebase(buffer.trimmed().since(mark));
+        assert_eq!(view.line_at_view_row(0), Some(2));
+        assert_eq!(buffer.line(2).as_deref(), Some("line 4\n"));
+        assert_eq!(view.selected_text().as_deref(), Some("line 5"));
+
+        // A selection that scrolled off the front is dropped.
+        let mark = buffer.trimmed();
+        for i in 12..20 {
+            buffer.append_line(&format!("line {i}"));
+        }
+        view = pager_view(&buffer, None, (0, false));
+        view.set_selection(0, 6, Style::NONE);
+        view.rebase(buffer.trimmed().since(mark));
+        assert_eq!(view.selected_text(), None);
+        assert_eq!(view.scroll_position(), (0, 0));
+    }
+
+    #[test]
+    fn test_capped_log_stress() {
+        const LINES: usize = 1_000_000;
+        const CAP: usize = 10_000;
+
+        let mut buffer = TextBuffer::with_max_lines(CAP);
+        for i in 0..LINES {
+            buffer.append_line(&format!("entry {i}"));
+            assert!(buffer.len_lines() <= CAP);
+        }
+        let longest = format!("entry {}\n", LINES - 1).len();
+        assert!(buffer.len_bytes() <= CAP * longest);
+        assert_eq!(buffer.trimmed().lines, LINES - CAP);
+        assert_eq!(buffer.line(0).as_deref(), Some("entry 990000\n"));
+
+        let view = TextBufferView::new(&buffer)
+            .viewport(0, 0, 20, 3)
+            .follow(true);
+        let mut output = OptimizedBuffer::new(20, 3);
+        view.render_to(&mut output, 0, 0);
+        let rows: Vec<String> = (0..3)
+            .map(|y| {
+                (0..12)
+                    .filter_map(|x| output.get(x, y).and_then(|cell| cell.content.as_char()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, ["entry 999997", "entry 999998", "entry 999999"]);
+    }
+
+    #[test]
+    fn test_view_rows_account_for_wrapping() {
+        let buffer = TextBuffer::with_text("aaaaaaaaaa\nb\ncccccc");
+        let mut view = TextBufferView::new(&buffer)
+            .viewport(0, 0, 4, 3)
+            .wrap_mode(WrapMode::Char);
+        // Rows: aaaa aaaa aa | b | cccc cc
+        assert_eq!(view.visible_line_range(), 0..1);
+        view.scroll_lines(2);
+        assert_eq!(view.visible_line_range(), 0..3);
+        assert_eq!(
+            (0..4).map(|y| view.line_at_view_row(y)).collect::<Vec<_>>(),
+            [Some(0), Some(1), Some(2), None]
+        );
+
+        // Line 2 can't reach the top; the view stops at the last page.
+        view.scroll_to_line(2);
+        assert_eq!(view.scroll_position(), (0, 3));
+        assert_eq!(view.visible_line_range(), 1..3);
+        assert!(view.is_at_bottom());
+        assert!(!view.is_following());
+    }
+
+    fn render_rows(view: &TextBufferView<'_>, width: u32, height: u32) -> Vec<String> {
+        let mut output = OptimizedBuffer::new(width, height);
+        view.render_to(&mut output, 0, 0);
+        (0..height)
+            .map(|y| {
+                let row: String = (0..width)
+                    .map(|x| match output.get(x, y).map(|cell| &cell.content) {
+                        Some(CellContent::Char(c)) => *c,
+                        _ => ' ',
+                    })
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap_modes_side_by_side() {
+        let buffer = TextBuffer::with_text("lorem ipsum dolor sit amet\nshort");
+        let render = |wrap| {
+            let view = TextBufferView::new(&buffer)
+                .viewport(0, 0, 12, 4)
+                .wrap_mode(wrap);
+            render_rows(&view, 12, 4)
+        };
+        assert_eq!(
+            render(WrapMode::Char),
+            ["lorem ipsum", "dolor sit am", "et", "short"]
+        );
+        assert_eq!(
+            render(WrapMode::Word),
+            ["lorem ipsum", "dolor sit", "amet", "short"]
+        );
+        assert_eq!(
+            render(WrapMode::Truncate { ellipsis: true }),
+            ["lorem ipsum…", "short", "", ""]
+        );
+        assert_eq!(
+            render(WrapMode::Truncate { ellipsis: false }),
+            ["lorem ipsum", "short", "", ""]
+        );
+    }
+
+    #[test]
+    fn test_wrap_trailing_space_never_opens_a_row() {
+        for wrap in [WrapMode::Char, WrapMode::Word] {
+            let rows = wrap_line("abcde fghij ", wrap, Some(5), 4, WidthMethod::WcWidth);
+            assert_eq!(rows, [(0, 6, 5), (6, 12, 5)], "{wrap:?}");
+            let rows = wrap_line("abcde   fg", wrap, Some(5), 4, WidthMethod::WcWidth);
+            assert_eq!(rows, [(0, 8, 5), (8, 10, 2)], "{wrap:?}");
+        }
+    }
+
+    #[test]
+    fn test_truncate_ellipsis_shows_hidden_selection() {
+        let buffer = TextBuffer::with_text("abcdefghijkl");
+        let selected = Style::bg(Rgba::BLUE);
+        let ellipsis_bg = |range: Range<usize>| {
+            let mut view = TextBufferView::new(&buffer)
+                .viewport(0, 0, 5, 1)
+                .wrap_mode(WrapMode::Truncate { ellipsis: true });
+            view.set_selection(range.start, range.end, selected);
+            let mut output = OptimizedBuffer::new(5, 1);
+            view.render_to(&mut output, 0, 0);
+            let cell = output.get(4, 0).unwrap();
+            assert!(matches!(cell.content, CellContent::Char('…')));
+            cell.bg
+        };
+        assert_eq!(ellipsis_bg(8..10), Rgba::BLUE);
+        assert_ne!(ellipsis_bg(0..2), Rgba::BLUE);
+    }
+
     #[test]
     fn test_measure_for_dimensions() {
         let buffer = TextBuffer::with_text("abc\ndefgh");
@@ -1525,6 +1988,53 @@ mod tests {
         eprintln!("[TEST] SUCCESS: All tab columns correctly show selection style");
     }
 
+    #[test]
+    fn test_highlight_layers_compose_over_buffer_style() {
+        use crate::highlight::{HighlightLayers, LayerMerge, SEARCH_LAYER, SELECTION_LAYER};
+        use crate::style::TextAttributes;
+
+        let mut buffer = TextBuffer::with_text("abcdefgh");
+        let syntax = Style::fg(Rgba::GREEN);
+        buffer.add_highlight(0..8, syntax, 0);
+
+        let yellow = Rgba::rgb(1.0, 1.0, 0.0);
+        let mut layers = HighlightLayers::default();
+        layers.add("current-line", 10, LayerMerge::BG);
+        layers.set_ranges("current-line", vec![(0..8, Style::bg(Rgba::BLACK))]);
+        layers.set_ranges(SEARCH_LAYER, vec![(1..4, Style::bg(yellow).with_bold())]);
+        layers.set_ranges(SELECTION_LAYER, vec![(3..6, Style::bg(Rgba::BLUE))]);
+
+        let view = TextBufferView::new(&buffer)
+            .viewport(0, 0, 10, 1)
+            .highlight_layers(&layers);
+        let mut output = OptimizedBuffer::new(10, 1);
+        view.render_to(&mut output, 0, 0);
+
+        let cells: Vec<_> = (0..7)
+            .map(|x| {
+                let cell = output.get(x, 0).unwrap();
+                (
+                    cell.fg,
+                    cell.bg,
+                    cell.attributes.contains(TextAttributes::BOLD),
+                )
+            })
+            .collect();
+        let green = Rgba::GREEN;
+        assert_eq!(
+            cells,
+            [
+                (green, Rgba::BLACK, false),
+                (green, yellow, true),
+                (green, yellow, true),
+                (green, Rgba::BLUE, true),
+                (green, Rgba::BLUE, false),
+                (green, Rgba::BLUE, false),
+                (green, Rgba::BLACK, false),
+            ]
+        );
+    }
+
     // ================== LineInfo Comprehensive Tests ==================
 
     #[test]
@@ -0,0 +1,135 @@
+//! SGR delta encoding in `AnsiWriter`.
+//!
+//! Renders a realistic 80x24 frame with about ten style runs per row and
+//! checks that:
+//!
+//! 1. Delta encoding is much smaller than re-emitting every cell's full style.
+//! 2. A VT parser fed the delta-encoded output reconstructs the same screen.
+
+use opentui::OptimizedBuffer;
+use opentui::ansi::AnsiWriter;
+use opentui::cell::Cell;
+use opentui::color::Rgba;
+use opentui::style::{Style, TextAttributes};
+use opentui_rust as opentui;
+
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+const RUN_LEN: u32 = 8;
+
+/// Styles cycled through by the runs, mixing color and attribute changes.
+fn palette() -> Vec<Style> {
+    let fg = |r, g, b| Style::fg(Rgba::from_rgb_u8(r, g, b));
+    let bg = Rgba::from_rgb_u8(30, 30, 40);
+    vec![
+        fg(220, 220, 220).with_bg(bg),
+        fg(255, 121, 198).with_bg(bg).with_bold(),
+        fg(139, 233, 253).with_bg(bg),
+        fg(80, 250, 123).with_bg(bg).with_italic(),
+        fg(220, 220, 220).with_bg(bg).with_underline(),
+        fg(241, 250, 140).with_bg(Rgba::from_rgb_u8(68, 71, 90)),
+        fg(98, 114, 164).with_bg(bg).with_italic().with_bold(),
+        fg(255, 85, 85)
+            .with_bg(bg)
+            .with_attributes(TextAttributes::INVERSE),
+        fg(189, 147, 249).with_bg(bg).with_underline().with_bold(),
+        fg(220, 220, 220).with_bg(bg),
+    ]
+}
+
+/// A frame of `RUN_LEN`-cell style runs, offset per row so runs don't line up.
+fn frame() -> OptimizedBuffer {
+    let styles = palette();
+    let text: Vec<char> = "fn render(frame: &mut Frame) -> Result<()> { let x = 42; }"
+        .chars()
+        .collect();
+    let mut buffer = OptimizedBuffer::new(WIDTH.into(), HEIGHT.into());
+    for y in 0..u32::from(HEIGHT) {
+        for x in 0..u32::from(WIDTH) {
+            let run = ((x / RUN_LEN) + y) as usize;
+            let ch = text[((x + y * 7) as usize) % text.len()];
+            buffer.set(x, y, Cell::new(ch, styles[run % styles.len()]));
+        }
+    }
+    buffer
+}
+
+/// Encode the frame, optionally resetting before every cell (the naive
+/// encoding that re-emits each cell's full style).
+fn encode(buffer: &OptimizedBuffer, reset_every_cell: bool) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut writer = AnsiWriter::new(&mut output);
+    writer.write_str("\x1b[H");
+    for y in 0..u32::from(HEIGHT) {
+        writer.move_cursor(y, 0);
+        for x in 0..u32::from(WIDTH) {
+            if reset_every_cell {
+                writer.reset();
+            }
+            writer.write_cell(buffer.get(x, y).unwrap());
+        }
+    }
+    writer.reset();
+    writer.flush().unwrap();
+    drop(writer);
+    output
+}
+
+#[test]
+fn delta_encoding_shrinks_realistic_frame() {
+    let buffer = frame();
+    let delta = encode(&buffer, false);
+    let naive = encode(&buffer, true);
+
+    // Roughly ten runs per row: only run boundaries carry style bytes.
+    assert!(
+        delta.len() * 4 < naive.len(),
+        "delta {} bytes vs naive {} bytes",
+        delta.len(),
+        naive.len()
+    );
+}
+
+#[test]
+fn vt_parser_reconstructs_delta_encoded_frame() {
+    let buffer = frame();
+    let mut parser = vt100::Parser::new(HEIGHT, WIDTH, 0);
+    parser.process(&encode(&buffer, false));
+    let screen = parser.screen();
+
+    let rgb = |color: Rgba| {
+        let (r, g, b) = color.to_rgb_u8();
+        vt100::Color::Rgb(r, g, b)
+    };
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let expected = buffer.get(x.into(), y.into()).unwrap();
+            let actual = screen.cell(y, x).unwrap();
+            let at = format!("cell ({x},{y})");
+            assert_eq!(
+                actual.contents(),
+                expected.content.as_char().unwrap().to_string(),
+                "{at}"
+            );
+            assert_eq!(actual.fgcolor(), rgb(expected.fg), "{at}");
+            assert_eq!(actual.bgcolor(), rgb(expected.bg), "{at}");
+            let attrs = expected.attributes;
+            assert_eq!(actual.bold(), attrs.contains(TextAttributes::BOLD), "{at}");
+            assert_eq!(
+                actual.italic(),
+                attrs.contains(TextAttributes::ITALIC),
+                "{at}"
+            );
+            assert_eq!(
+                actual.underline(),
+                attrs.contains(TextAttributes::UNDERLINE),
+                "{at}"
+            );
+            assert_eq!(
+                actual.inverse(),
+                attrs.contains(TextAttributes::INVERSE),
+                "{at}"
+            );
+        }
+    }
+}
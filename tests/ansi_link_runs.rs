@@ -0,0 +1,130 @@
+//! OSC 8 hyperlink runs in `AnsiWriter`.
+//!
+//! Writes a row with a 40-cell linked run between plain text and checks
+//! that:
+//!
+//! 1. The run opens and closes the link once instead of once per cell.
+//! 2. Fed through a VT parser, the link region covers exactly the linked
+//!    cells and is terminated by the end of the frame.
+
+use opentui::ansi::{AnsiWriter, HYPERLINK_END};
+use opentui::cell::Cell;
+use opentui::color::Rgba;
+use opentui::style::Style;
+use opentui_rust as opentui;
+
+const WIDTH: u16 = 60;
+const LINK_START: u16 = 10;
+const LINK_LEN: u16 = 40;
+const URL: &str = "https://example.com/docs";
+const OPEN: &str = "\x1b]8;id=1;https://example.com/docs\x1b\\";
+
+/// A row of cells where `LINK_START..LINK_START + LINK_LEN` carry link 1.
+///
+/// Linked cells alternate colors so the writer can't merge them into one
+/// style run; only link tracking keeps the link open.
+fn row() -> Vec<Cell> {
+    let colors = [
+        Rgba::from_rgb_u8(139, 233, 253),
+        Rgba::from_rgb_u8(80, 250, 123),
+    ];
+    (0..WIDTH)
+        .map(|x| {
+            let ch = char::from(b'a' + (x % 26) as u8);
+            if (LINK_START..LINK_START + LINK_LEN).contains(&x) {
+                let style = Style::fg(colors[usize::from(x % 2)])
+                    .with_underline()
+                    .with_link(1);
+                Cell::new(ch, style)
+            } else {
+                Cell::new(ch, Style::NONE)
+            }
+        })
+        .collect()
+}
+
+/// Encode the row, optionally closing the link after every cell (the
+/// encoding without link tracking).
+fn encode(close_every_cell: bool) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut writer = AnsiWriter::new(&mut output);
+    writer.write_str("\x1b[H");
+    for cell in row() {
+        let url = cell.attributes.link_id().map(|_| URL);
+        writer.write_cell_with_link(&cell, url);
+        if close_every_cell {
+            writer.set_link(None, None);
+        }
+    }
+    writer.reset();
+    writer.flush().unwrap();
+    drop(writer);
+    output
+}
+
+fn count(haystack: &[u8], needle: &str) -> usize {
+    haystack
+        .windows(needle.len())
+        .filter(|window| *window == needle.as_bytes())
+        .count()
+}
+
+#[test]
+fn linked_run_opens_and_closes_once() {
+    let coalesced = encode(false);
+    let per_cell = encode(true);
+
+    assert_eq!(count(&coalesced, OPEN), 1);
+    assert_eq!(count(&coalesced, HYPERLINK_END), 1);
+    assert_eq!(count(&per_cell, OPEN), usize::from(LINK_LEN));
+
+    let saved = (usize::from(LINK_LEN) - 1) * (OPEN.len() + HYPERLINK_END.len());
+    assert_eq!(per_cell.len() - coalesced.len(), saved);
+}
+
+#[test]
+fn vt_parser_sees_one_contiguous_terminated_link() {
+    let output = encode(false);
+    let mut parser = vt100::Parser::new(1, WIDTH, 0);
+
+    // Feed the output a piece at a time, noting the cursor at every OSC 8
+    // sequence: opens start the link region and closes end it.
+    let mut regions = Vec::new();
+    let mut open_at = None;
+    let mut rest = output.as_slice();
+    while let Some(start) = rest.windows(4).position(|w| w == b"\x1b]8;") {
+        parser.process(&rest[..start]);
+        let len = rest[start..]
+            .windows(2)
+            .position(|w| w == b"\x1b\\")
+            .unwrap()
+            + 2;
+        let sequence = &rest[start..start + len];
+        let column = parser.screen().cursor_position().1;
+        if sequence == HYPERLINK_END.as_bytes() {
+            regions.push((open_at.take().expect("close without open"), column));
+        } else {
+            assert_eq!(sequence, OPEN.as_bytes());
+            assert!(open_at.is_none(), "link reopened at column {column}");
+            open_at = Some(column);
+        }
+        parser.process(sequence);
+        rest = &rest[start + len..];
+    }
+    parser.process(rest);
+
+    assert_eq!(open_at, None, "link left open at end of frame");
+    assert_eq!(regions, [(LINK_START, LINK_START + LINK_LEN)]);
+
+    let screen = parser.screen();
+    for (x, cell) in (0..WIDTH).zip(row()) {
+        let actual = screen.cell(0, x).unwrap();
+        assert_eq!(
+            actual.contents(),
+            cell.content.as_char().unwrap().to_string(),
+            "cell {x}"
+        );
+        let linked = (LINK_START..LINK_START + LINK_LEN).contains(&x);
+        assert_eq!(actual.underline(), linked, "cell {x}");
+    }
+}
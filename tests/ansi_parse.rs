@@ -0,0 +1,116 @@
+//! Interpreting captured tool output with `ansi::parse_to_buffer`.
+//!
+//! The fixtures in `tests/fixtures/ansi` are real output captured with
+//! colors forced on:
+//!
+//! - `git_diff.ansi`: `git -c color.ui=always diff` (16-color SGR)
+//! - `cargo_build.ansi`: `cargo build --color always` with a warning
+//!   (bold + bright colors, lines wider than the region)
+//! - `ls.ansi`: `ls --color=always -1` with an `LS_COLORS` using 256-color
+//!   and truecolor SGR forms
+//!
+//! Each test snapshots the resulting text and the style runs of every row.
+
+use std::fmt::Write as _;
+
+use opentui::ansi::{ParseOptions, parse_to_buffer};
+use opentui::cell::Cell;
+use opentui::{GraphemePool, OptimizedBuffer, Style};
+use opentui_rust as opentui;
+
+const GIT_DIFF: &[u8] = include_bytes!("fixtures/ansi/git_diff.ansi");
+const CARGO_BUILD: &[u8] = include_bytes!("fixtures/ansi/cargo_build.ansi");
+const LS: &[u8] = include_bytes!("fixtures/ansi/ls.ansi");
+
+fn style_of(cell: &Cell) -> String {
+    let mut out = format!("fg={} bg={}", cell.fg, cell.bg);
+    let attrs = cell.attributes.flags_only();
+    if !attrs.is_empty() {
+        write!(out, " {attrs:?}").unwrap();
+    }
+    out
+}
+
+/// Render `input` into a `width`-column region at (1, 1) and dump the rows
+/// it consumed: their text, then each run of identically styled cells.
+fn render(input: &[u8], width: u32) -> String {
+    let mut buf = OptimizedBuffer::new(width + 2, 40);
+    let mut pool = GraphemePool::new();
+    let opts = ParseOptions {
+        width: Some(width),
+        ..ParseOptions::default()
+    };
+    let rows = parse_to_buffer(input, &mut buf, &mut pool, (1, 1), opts);
+
+    let mut out = format!("rows: {rows}\n");
+    for y in 1..=rows {
+        let line: String = (1..=width)
+            .filter_map(|x| {
+                let cell = buf.get(x, y).unwrap();
+                match cell.content.as_char() {
+                    Some(ch) => Some(ch),
+                    None if cell.is_continuation() => None,
+                    None => Some(' '),
+                }
+            })
+            .collect();
+        writeln!(out, "|{}", line.trim_end()).unwrap();
+    }
+    out.push('\n');
+    for y in 1..=rows {
+        let mut start = 1;
+        while start <= width {
+            let style = style_of(buf.get(start, y).unwrap());
+            let mut end = start + 1;
+            while end <= width && style_of(buf.get(end, y).unwrap()) == style {
+                end += 1;
+            }
+            writeln!(out, "{}:{}..{} {style}", y - 1, start - 1, end - 1).unwrap();
+            start = end;
+        }
+    }
+    out
+}
+
+#[test]
+fn git_diff_output() {
+    insta::assert_snapshot!(render(GIT_DIFF, 40));
+}
+
+#[test]
+fn cargo_build_output_wraps() {
+    insta::assert_snapshot!(render(CARGO_BUILD, 60));
+}
+
+#[test]
+fn ls_output_with_256_and_truecolor() {
+    insta::assert_snapshot!(render(LS, 16));
+}
+
+#[test]
+fn rows_consumed_match_captured_lines() {
+    let mut buf = OptimizedBuffer::new(200, 4);
+    let mut pool = GraphemePool::new();
+    let lines = |input: &[u8]| u32::try_from(input.split(|&b| b == b'\n').count() - 1).unwrap();
+
+    // The region is shorter than the output, but every line is counted.
+    for input in [GIT_DIFF, CARGO_BUILD, LS] {
+        let rows = parse_to_buffer(input, &mut buf, &mut pool, (0, 0), ParseOptions::default());
+        assert_eq!(rows, lines(input));
+    }
+}
+
+#[test]
+fn base_style_fills_in_default_colors() {
+    let mut buf = OptimizedBuffer::new(80, 12);
+    let mut pool = GraphemePool::new();
+    let base = Style::fg(opentui::Rgba::from_rgb_u8(200, 200, 200));
+    let opts = ParseOptions {
+        base_style: base,
+        ..ParseOptions::default()
+    };
+    parse_to_buffer(GIT_DIFF, &mut buf, &mut pool, (0, 0), opts);
+
+    // " fn main() {" is printed after a full reset.
+    assert_eq!(buf.get(1, 5).unwrap().fg, base.fg.unwrap());
+}
@@ -450,6 +450,7 @@ fn test_cell_write_content_with_pool_lookup() {
         fg: Rgba::WHITE,
         bg: Rgba::BLACK,
         attributes: TextAttributes::empty(),
+        ..Default::default()
     };
 
     let mut buf = Vec::new();
@@ -620,6 +620,9 @@ impl E2EHarness {
             fg: Some(cell.fg),
             bg: Some(cell.bg),
             attributes: cell.attributes,
+            underline_style: cell.underline_style,
+            underline_color: cell.underline_color,
+            ..Style::NONE
         };
         let passed = predicate(&style);
 
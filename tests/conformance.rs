@@ -301,8 +301,12 @@ fn run_box_test(case: &FixtureCase, logger: &ArtifactLogger) -> bool {
         style: BoxStyle::single(Style::NONE),
         sides: opentui::buffer::BoxSides::default(),
         fill: None,
+        shadow: None,
         title,
         title_align: opentui::buffer::TitleAlign::default(),
+        title_spans: Vec::new(),
+        secondary_title: None,
+        bottom_title: None,
     };
     buffer.draw_box_with_options(0, 0, width, height, options);
 
@@ -248,3 +248,136 @@ fn e2e_malformed_input_handling() {
         assert_tokens_well_formed(tokens, input.len());
     }
 }
+
+/// Describe each non-blank cell of a row as `glyph fg [attrs]`.
+fn describe_styled_row(output: &OptimizedBuffer, row: u32, width: u32) -> String {
+    use opentui::TextAttributes;
+
+    let mut described = String::new();
+    for x in 0..width {
+        let cell = output.get(x, row).expect("cell in bounds");
+        let Some(ch) = cell.content.as_char().filter(|ch| *ch != ' ') else {
+            continue;
+        };
+        let (r, g, b) = cell.fg.to_rgb_u8();
+        let _ = write!(described, "{ch} #{r:02x}{g:02x}{b:02x}");
+        for (flag, name) in [
+            (TextAttributes::BOLD, " bold"),
+            (TextAttributes::ITALIC, " italic"),
+            (TextAttributes::UNDERLINE, " underline"),
+        ] {
+            if cell.attributes.contains(flag) {
+                described.push_str(name);
+            }
+        }
+        described.push('\n');
+    }
+    described
+}
+
+#[test]
+fn e2e_rainbow_bracket_depth_coloring() {
+    init_logging();
+    info!("Testing rainbow bracket colors across nesting and lines");
+
+    let source = "f(a[(b + c)], {\n  \"(]\", g(d) })\n";
+    let theme = Theme::dark();
+    let mut highlighted = HighlightedBuffer::new(TextBuffer::with_text(source))
+        .with_tokenizer(Box::new(RustTokenizer::new()))
+        .with_theme(theme.clone());
+    highlighted.set_rainbow_brackets(true);
+    highlighted.update_highlighting();
+
+    let view = TextBufferView::new(highlighted.buffer()).viewport(0, 0, 20, 2);
+    let mut output = OptimizedBuffer::new(20, 2);
+    output.clear(theme.background());
+    view.render_to(&mut output, 0, 0);
+
+    let rows = format!(
+        "{}---\n{}",
+        describe_styled_row(&output, 0, 20),
+        describe_styled_row(&output, 1, 20)
+    );
+    insta::assert_snapshot!(rows);
+}
+
+#[cfg(feature = "themes")]
+#[test]
+fn e2e_theme_file_renders_styled_cells() {
+    use opentui::ThemeRegistry;
+
+    init_logging();
+    info!("Testing theme loaded from a TOML file");
+
+    let mut registry = ThemeRegistry::new();
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/e2e/fixtures/themes");
+    let loaded = registry.load_dir(dir).expect("load theme directory");
+    assert_eq!(loaded, 1);
+    let theme = registry
+        .get("ocean")
+        .expect("ocean theme registered")
+        .clone();
+
+    let source = "if ok { go!(1, \"s\") }\n";
+    let mut highlighted = HighlightedBuffer::new(TextBuffer::with_text(source))
+        .with_tokenizer(Box::new(RustTokenizer::new()))
+        .with_theme(theme.clone());
+    highlighted.update_highlighting();
+
+    let view = TextBufferView::new(highlighted.buffer()).viewport(0, 0, 40, 1);
+    let mut output = OptimizedBuffer::new(40, 1);
+    output.clear(theme.background());
+    view.render_to(&mut output, 0, 0);
+
+    insta::assert_snapshot!(describe_styled_row(&output, 0, 40));
+}
+
+#[cfg(feature = "themes")]
+#[test]
+fn e2e_malformed_theme_files_report_errors() {
+    use opentui::{Error, ThemeRegistry};
+
+    init_logging();
+    let cases = [
+        (
+            "bad_hex.toml",
+            "name = \"Bad\"\n\n[styles.keyword]\nfg = \"#12zz45\"\n",
+            Some(4),
+            "invalid hex color `#12zz45`",
+        ),
+        (
+            "no_name.json",
+            "{\n  \"palette\": {}\n}\n",
+            Some(3),
+            "missing field `name`",
+        ),
+        (
+            "syntax.toml",
+            "name = \"Broken\"\n[styles.keyword\nbold = true\n",
+            Some(2),
+            "",
+        ),
+        (
+            "palette.toml",
+            "name = \"P\"\n[styles.string]\nfg = \"seafoam\"\n",
+            Some(3),
+            "unknown palette color `seafoam`",
+        ),
+    ];
+
+    for (file_name, contents, expected_line, expected_message) in cases {
+        let dir = tempfile::tempdir().expect("temp dir");
+        std::fs::write(dir.path().join(file_name), contents).expect("write theme");
+
+        let err = ThemeRegistry::new()
+            .load_dir(dir.path())
+            .expect_err("malformed theme must fail");
+        info!(file_name, %err, "Theme error");
+        let Error::ThemeParse { line, message } = err else {
+            panic!("{file_name}: expected ThemeParse, got {err:?}");
+        };
+        assert_eq!(line, expected_line, "{file_name}: {message}");
+        assert!(message.contains(file_name), "{message}");
+        assert!(message.contains(expected_message), "{message}");
+    }
+}
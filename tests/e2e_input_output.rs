@@ -787,11 +787,13 @@ fn test_e2e_threaded_renderer_lifecycle_components() {
         changed_cells: vec![(0, 0); total_cells / 10],
         dirty_regions: vec![],
         change_count: total_cells / 10,
+        ..BufferDiff::default()
     };
     let diff_80_percent = BufferDiff {
         changed_cells: vec![(0, 0); total_cells * 8 / 10],
         dirty_regions: vec![],
         change_count: total_cells * 8 / 10,
+        ..BufferDiff::default()
     };
 
     // 10% changes should use diff
@@ -856,6 +858,7 @@ fn test_e2e_threaded_renderer_smoke_render() {
         fg: Rgba::WHITE,
         bg: Rgba::TRANSPARENT,
         attributes: opentui::style::TextAttributes::empty(),
+        ..Default::default()
     };
     buffer.set(20, 0, emoji_cell);
     buffer.set(21, 0, opentui::cell::Cell::continuation(Rgba::TRANSPARENT));
@@ -134,6 +134,7 @@ fn test_e2e_layered_gradient_hit_test() {
             hide_cursor: false,
             enable_mouse: false,
             query_capabilities: false,
+            capabilities: None,
         },
     )
     .expect("Renderer creation should succeed with options disabled");
@@ -436,6 +437,7 @@ fn test_e2e_force_redraw_full_output() {
             .collect(),
         dirty_regions: vec![],
         change_count: total_cells,
+        ..BufferDiff::default()
     };
 
     assert_eq!(
@@ -797,6 +799,7 @@ fn test_e2e_diff_threshold_decision() {
         changed_cells: vec![(0, 0); total_cells / 10],
         dirty_regions: vec![],
         change_count: total_cells / 10,
+        ..BufferDiff::default()
     };
 
     assert!(
@@ -811,6 +814,7 @@ fn test_e2e_diff_threshold_decision() {
         changed_cells: vec![(0, 0); total_cells * 6 / 10],
         dirty_regions: vec![],
         change_count: total_cells * 6 / 10,
+        ..BufferDiff::default()
     };
 
     assert!(
@@ -825,6 +829,7 @@ fn test_e2e_diff_threshold_decision() {
         changed_cells: vec![(0, 0); total_cells / 2],
         dirty_regions: vec![],
         change_count: total_cells / 2,
+        ..BufferDiff::default()
     };
 
     harness.log().info(
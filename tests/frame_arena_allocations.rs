@@ -0,0 +1,118 @@
+//! Heap allocations per frame for a label-heavy scene, with labels built by
+//! `format!` versus the renderer's frame arena.
+//!
+//! A counting global allocator tallies allocations made on the test thread.
+
+#![allow(unsafe_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use opentui::renderer::{Renderer, RendererOptions};
+use opentui::{OptimizedBuffer, Style};
+use opentui_rust as opentui;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+// SAFETY: forwards to the system allocator, only counting calls.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        // SAFETY: the caller upholds `GlobalAlloc::alloc`'s contract.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the caller upholds `GlobalAlloc::dealloc`'s contract.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        // SAFETY: the caller upholds `GlobalAlloc::realloc`'s contract.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+const WIDTH: u32 = 80;
+const HEIGHT: u32 = 24;
+
+fn renderer() -> Renderer {
+    let options = RendererOptions {
+        use_alt_screen: false,
+        hide_cursor: false,
+        enable_mouse: false,
+        query_capabilities: false,
+        capabilities: None,
+    };
+    Renderer::with_writer(WIDTH, HEIGHT, options, std::io::sink()).unwrap()
+}
+
+fn draw_label(buffer: &mut OptimizedBuffer, index: u32, label: &str) {
+    buffer.draw_text((index % 4) * 20, index / 4, label, Style::NONE);
+}
+
+/// Allocations made while drawing `frame`'s labels.
+fn draw_scene(renderer: &mut Renderer, frame: u32, use_arena: bool) -> usize {
+    let before = allocations();
+    let (buffer, arena) = renderer.buffer_with_arena();
+    for index in 0..WIDTH / 20 * HEIGHT {
+        if use_arena {
+            let label = arena.format(format_args!("item {index}: {frame}%"));
+            draw_label(buffer, index, label);
+        } else {
+            let label = format!("item {index}: {frame}%");
+            draw_label(buffer, index, &label);
+        }
+    }
+    allocations() - before
+}
+
+#[test]
+fn arena_labels_stop_allocating_once_warm() {
+    let labels = (WIDTH / 20 * HEIGHT) as usize;
+    let mut with_format = renderer();
+    let mut with_arena = renderer();
+    // The first frames size the arena's chunks.
+    for frame in 0..3 {
+        draw_scene(&mut with_arena, frame, true);
+        with_arena.present().unwrap();
+        draw_scene(&mut with_format, frame, false);
+        with_format.present().unwrap();
+    }
+
+    for frame in 3..6 {
+        let formatted = draw_scene(&mut with_format, frame, false);
+        with_format.present().unwrap();
+        let arena = draw_scene(&mut with_arena, frame, true);
+        with_arena.present().unwrap();
+
+        assert!(
+            formatted >= labels,
+            "frame {frame}: {formatted} allocations"
+        );
+        assert_eq!(arena, 0, "frame {frame}");
+    }
+    assert_eq!(
+        with_arena.stats().arena_peak_bytes,
+        peak_label_bytes(labels)
+    );
+}
+
+/// Bytes of the labels of a frame with a single-digit frame number.
+fn peak_label_bytes(labels: usize) -> usize {
+    (0..labels)
+        .map(|index| format!("item {index}: 5%").len())
+        .sum()
+}
@@ -0,0 +1,135 @@
+//! Property-based tests for the sparse `HitGrid`.
+//!
+//! Every sequence of registrations, overlays, clears, and resizes must give
+//! the same hit test results as a plain dense grid with one slot per cell.
+
+use opentui::renderer::HitGrid;
+use opentui_rust as opentui;
+use proptest::prelude::*;
+
+// ============================================================================
+// Dense reference model
+// ============================================================================
+
+/// One `Option<u32>` per cell, the layout the sparse grid replaced.
+#[derive(Clone)]
+struct DenseGrid {
+    width: u32,
+    height: u32,
+    cells: Vec<Option<u32>>,
+}
+
+impl DenseGrid {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![None; (width * height) as usize],
+        }
+    }
+
+    fn register(&mut self, x: u32, y: u32, width: u32, height: u32, id: u32) {
+        for row in y..y.saturating_add(height).min(self.height) {
+            for col in x..x.saturating_add(width).min(self.width) {
+                self.cells[(row * self.width + col) as usize] = Some(id);
+            }
+        }
+    }
+
+    fn overlay(&mut self, other: &Self) {
+        for (dst, src) in self.cells.iter_mut().zip(&other.cells) {
+            if src.is_some() {
+                *dst = *src;
+            }
+        }
+    }
+
+    fn test(&self, x: u32, y: u32) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells[(y * self.width + x) as usize]
+    }
+}
+
+// ============================================================================
+// Strategies
+// ============================================================================
+
+#[derive(Clone, Debug)]
+enum Op {
+    Register(u32, u32, u32, u32, u32),
+    /// Register these areas on a fresh layer grid, then overlay it.
+    Overlay(Vec<(u32, u32, u32, u32, u32)>),
+    Clear,
+    Resize(u32, u32),
+}
+
+/// Areas may start inside the grid or just past its edge, and may extend
+/// beyond it.
+fn area_strategy() -> impl Strategy<Value = (u32, u32, u32, u32, u32)> {
+    (0u32..24, 0u32..14, 0u32..16, 0u32..8, 0u32..6)
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        8 => area_strategy().prop_map(|(x, y, w, h, id)| Op::Register(x, y, w, h, id)),
+        2 => prop::collection::vec(area_strategy(), 0..6).prop_map(Op::Overlay),
+        1 => Just(Op::Clear),
+        1 => (1u32..=20, 1u32..=12).prop_map(|(w, h)| Op::Resize(w, h)),
+    ]
+}
+
+fn assert_same(sparse: &HitGrid, dense: &DenseGrid) -> Result<(), TestCaseError> {
+    prop_assert_eq!(sparse.size(), (dense.width, dense.height));
+    for y in 0..dense.height + 2 {
+        for x in 0..dense.width + 2 {
+            prop_assert_eq!(sparse.test(x, y), dense.test(x, y), "cell ({}, {})", x, y);
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Properties
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn sparse_grid_matches_dense_grid(
+        (width, height) in (1u32..=20, 1u32..=12),
+        ops in prop::collection::vec(op_strategy(), 0..40),
+    ) {
+        let mut sparse = HitGrid::new(width, height);
+        let mut dense = DenseGrid::new(width, height);
+
+        for op in ops {
+            match op {
+                Op::Register(x, y, w, h, id) => {
+                    sparse.register(x, y, w, h, id);
+                    dense.register(x, y, w, h, id);
+                }
+                Op::Overlay(areas) => {
+                    let (w, h) = sparse.size();
+                    let mut sparse_layer = HitGrid::new(w, h);
+                    let mut dense_layer = DenseGrid::new(w, h);
+                    for (x, y, aw, ah, id) in areas {
+                        sparse_layer.register(x, y, aw, ah, id);
+                        dense_layer.register(x, y, aw, ah, id);
+                    }
+                    sparse.overlay(&sparse_layer);
+                    dense.overlay(&dense_layer);
+                }
+                Op::Clear => {
+                    sparse.clear();
+                    dense = DenseGrid::new(dense.width, dense.height);
+                }
+                Op::Resize(w, h) => {
+                    sparse.resize(w, h);
+                    dense = DenseGrid::new(w, h);
+                }
+            }
+            assert_same(&sparse, &dense)?;
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Property-based tests for tagged styles on `TextBuffer`.
+//!
+//! Every sequence of added and removed styles and text edits must leave a
+//! minimal, non-overlapping segment list that renders the same as a plain
+//! per-char map of the styles covering each char.
+
+use opentui::text::StyleHandle;
+use opentui::{Rgba, Style, TextBuffer};
+use opentui_rust as opentui;
+use proptest::prelude::*;
+
+const TAG_PRIORITIES: [u8; 3] = [0, 5, 5];
+
+const PALETTE: [Style; 5] = [
+    Style::bold(),
+    Style::fg(Rgba::RED),
+    Style::bg(Rgba::BLUE).with_underline(),
+    Style::fg(Rgba::GREEN).with_italic(),
+    Style::bold(),
+];
+
+// ============================================================================
+// Per-char reference model
+// ============================================================================
+
+/// The styles covering each char, as indices into `added`.
+struct CharMap {
+    added: Vec<(StyleHandle, Style, u32)>,
+    chars: Vec<Vec<usize>>,
+}
+
+impl CharMap {
+    fn style_at(&self, offset: usize) -> Option<Style> {
+        let mut covering = self.chars[offset].clone();
+        covering.sort_by_key(|&id| (TAG_PRIORITIES[self.added[id].2 as usize], id));
+        covering
+            .into_iter()
+            .map(|id| self.added[id].1)
+            .reduce(Style::merge)
+    }
+
+    /// Inserted chars are covered by the styles covering both neighbors.
+    fn insert(&mut self, offset: usize, len: usize) {
+        let covering: Vec<usize> = match (offset.checked_sub(1), self.chars.get(offset)) {
+            (Some(before), Some(after)) => self.chars[before]
+                .iter()
+                .copied()
+                .filter(|id| after.contains(id))
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.chars
+            .splice(offset..offset, std::iter::repeat_n(covering, len));
+    }
+}
+
+// ============================================================================
+// Strategies
+// ============================================================================
+
+#[derive(Clone, Debug)]
+enum Op {
+    Add(usize, usize, usize, u32),
+    /// Remove the nth style added so far, if any.
+    Remove(usize),
+    ClearTag(u32),
+    Insert(usize, usize),
+    Delete(usize, usize),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        8 => (0usize..40, 0usize..12, 0usize..5, 0u32..3)
+            .prop_map(|(start, len, style, tag)| Op::Add(start, len, style, tag)),
+        4 => (0usize..64).prop_map(Op::Remove),
+        1 => (0u32..3).prop_map(Op::ClearTag),
+        2 => (0usize..40, 1usize..4).prop_map(|(offset, len)| Op::Insert(offset, len)),
+        2 => (0usize..40, 1usize..6).prop_map(|(offset, len)| Op::Delete(offset, len)),
+    ]
+}
+
+fn assert_matches(buffer: &TextBuffer, model: &CharMap) -> Result<(), TestCaseError> {
+    let segments = buffer.tagged_segments();
+    prop_assert_eq!(buffer.len_chars(), model.chars.len());
+    for (range, _) in segments {
+        prop_assert!(range.start < range.end, "empty segment {:?}", range);
+    }
+    for pair in segments.windows(2) {
+        let ((a, a_style), (b, b_style)) = (&pair[0], &pair[1]);
+        prop_assert!(a.end <= b.start, "{:?} overlaps {:?}", a, b);
+        let adjacent = a.end == b.start;
+        prop_assert!(
+            !adjacent || a_style != b_style,
+            "{:?} and {:?} should be one segment",
+            a,
+            b
+        );
+    }
+
+    for offset in 0..model.chars.len() {
+        let expected = model.style_at(offset);
+        let segment = segments
+            .iter()
+            .find(|(range, _)| range.contains(&offset))
+            .map(|(_, style)| *style);
+        prop_assert_eq!(segment, expected, "segment style at char {}", offset);
+
+        let byte = buffer.rope().char_to_byte(offset);
+        let rendered = expected.map_or(Style::NONE, |style| Style::NONE.merge(style));
+        prop_assert_eq!(buffer.style_at(byte), rendered, "style at char {}", offset);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Properties
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn tagged_segments_match_char_map(
+        ops in prop::collection::vec(op_strategy(), 0..48),
+    ) {
+        // Multi-byte chars keep char and byte offsets apart.
+        let text: String = "aé".repeat(16);
+        let mut buffer = TextBuffer::with_text(&text);
+        for (tag, priority) in (0u32..).zip(TAG_PRIORITIES) {
+            buffer.set_style_tag_priority(tag, priority);
+        }
+        let mut model = CharMap {
+            added: Vec::new(),
+            chars: vec![Vec::new(); buffer.len_chars()],
+        };
+
+        for op in ops {
+            let len_chars = model.chars.len();
+            match op {
+                Op::Add(start, len, style, tag) => {
+                    let start = start.min(len_chars);
+                    let end = (start + len).min(len_chars);
+                    let style = PALETTE[style];
+                    let handle = buffer.add_style(start..end, style, tag);
+                    let id = model.added.len();
+                    model.added.push((handle, style, tag));
+                    for covering in &mut model.chars[start..end] {
+                        covering.push(id);
+                    }
+                }
+                Op::Remove(nth) => {
+                    let Some(&(handle, _, _)) = model.added.get(nth) else {
+                        continue;
+                    };
+                    let covered = model.chars.iter().any(|ids| ids.contains(&nth));
+                    prop_assert_eq!(buffer.remove_style(handle), covered);
+                    for covering in &mut model.chars {
+                        covering.retain(|&id| id != nth);
+                    }
+                }
+                Op::ClearTag(tag) => {
+                    buffer.clear_styles_with_tag(tag);
+                    let added = &model.added;
+                    for covering in &mut model.chars {
+                        covering.retain(|&id| added[id].2 != tag);
+                    }
+                }
+                Op::Insert(offset, len) => {
+                    let offset = offset.min(len_chars);
+                    buffer.insert(offset, &"ü".repeat(len)).unwrap();
+                    model.insert(offset, len);
+                }
+                Op::Delete(offset, len) => {
+                    let offset = offset.min(len_chars);
+                    let end = (offset + len).min(len_chars);
+                    buffer.remove(offset..end).unwrap();
+                    model.chars.drain(offset..end);
+                }
+            }
+            assert_matches(&buffer, &model)?;
+        }
+    }
+}
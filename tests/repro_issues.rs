@@ -55,6 +55,7 @@ mod tests {
             fg: opentui::color::Rgba::WHITE,
             bg: opentui::color::Rgba::TRANSPARENT,
             attributes: opentui::style::TextAttributes::empty(),
+            ..Default::default()
         };
 
         buffer.set_with_pool(&mut pool, 0, 0, cell);
@@ -116,6 +117,7 @@ mod tests {
                 fg: Rgba::WHITE,
                 bg: Rgba::BLACK,
                 attributes: TextAttributes::empty(),
+                ..Default::default()
             },
         );
         old_buf.set(1, 0, Cell::continuation(Rgba::BLACK));
@@ -142,6 +144,7 @@ mod tests {
                 fg: Rgba::WHITE,
                 bg: Rgba::BLACK,
                 attributes: TextAttributes::empty(),
+                ..Default::default()
             },
         );
         new_buf.set(1, 0, Cell::continuation(Rgba::BLACK));
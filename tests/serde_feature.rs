@@ -0,0 +1,37 @@
+//! The `serde` feature must stay additive: off by default, pulling in no
+//! dependency unless enabled, and only adding trait impls when on.
+
+const MANIFEST: &str = include_str!("../Cargo.toml");
+
+fn manifest_line(prefix: &str) -> &'static str {
+    MANIFEST
+        .lines()
+        .find(|line| line.starts_with(prefix))
+        .unwrap_or_else(|| panic!("no `{prefix}` line in Cargo.toml"))
+}
+
+#[test]
+fn serde_is_not_a_default_feature() {
+    let default = manifest_line("default = ");
+    assert!(!default.contains("serde"), "{default}");
+}
+
+#[test]
+fn serde_dependency_is_optional() {
+    let dependency = manifest_line("serde = {");
+    assert!(dependency.contains("optional = true"), "{dependency}");
+    assert_eq!(manifest_line("serde = ["), r#"serde = ["dep:serde"]"#);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn core_types_implement_serde() {
+    fn assert_serde<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+
+    assert_serde::<opentui_rust::Rgba>();
+    assert_serde::<opentui_rust::TextAttributes>();
+    assert_serde::<opentui_rust::UnderlineStyle>();
+    assert_serde::<opentui_rust::Style>();
+    assert_serde::<opentui_rust::cell::CellRepr>();
+    assert_serde::<opentui_rust::Theme>();
+}